@@ -0,0 +1,50 @@
+//! Backfills: data migrations that would hold locks for too long if they
+//! ran inside a migration. A migration schedules one with the SQL for a
+//! single batch, and the server runs that batch repeatedly in the
+//! background until it changes no rows. Each batch must therefore make
+//! progress and touch a bounded number of rows, for example
+//!
+//! ```sql
+//! UPDATE reports SET status = 'done'
+//! WHERE id IN (SELECT id FROM reports WHERE status IS NULL LIMIT 1000)
+//! ```
+//!
+//! Code deployed alongside the migration has to cope with rows the
+//! backfill hasn't reached yet.
+
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+/// Schedules the backfill `name`. Scheduling one that exists does nothing,
+/// so a migration that is rolled back and reapplied doesn't start over.
+pub async fn schedule(
+    manager: &SchemaManager<'_>,
+    name: &str,
+    batch_sql: &str,
+) -> Result<(), DbErr> {
+    manager
+        .get_connection()
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"INSERT INTO data_backfills (name, batch_sql, created_at)
+               VALUES ($1, $2, NOW())
+               ON CONFLICT (name) DO NOTHING"#,
+            [name.into(), batch_sql.into()],
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Forgets the backfill `name`, for the `down` of the migration that
+/// scheduled it. Rows it already changed stay changed.
+pub async fn unschedule(manager: &SchemaManager<'_>, name: &str) -> Result<(), DbErr> {
+    manager
+        .get_connection()
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "DELETE FROM data_backfills WHERE name = $1",
+            [name.into()],
+        ))
+        .await?;
+    Ok(())
+}
@@ -1,6 +1,53 @@
 pub use sea_orm_migration::prelude::*;
 
+pub mod backfill;
+
 mod m20241221_000001_create_driftwatch_tables;
+mod m20261016_000001_add_report_commit_metadata;
+mod m20261016_000002_add_report_merge_base;
+mod m20261016_000003_create_stale_alerts;
+mod m20261016_000004_create_jobs;
+mod m20261016_000005_add_series_indexes;
+mod m20261016_000006_create_metric_summaries;
+mod m20261016_000007_add_report_finalized;
+mod m20261016_000008_add_report_status;
+mod m20261016_000009_create_benchmark_owners;
+mod m20261016_000010_add_alert_triage;
+mod m20261016_000011_add_alert_github_issues;
+mod m20261016_000012_add_alert_confirmation;
+mod m20261016_000013_create_benchmark_noise;
+mod m20261016_000014_add_report_excluded;
+mod m20261016_000015_create_annotations;
+mod m20261016_000016_add_report_version;
+mod m20261016_000017_create_project_groups;
+mod m20261016_000018_add_benchmark_name_trigram_index;
+mod m20261016_000019_create_report_outputs;
+mod m20261016_000020_add_metric_harness_stats;
+mod m20261016_000021_add_benchmark_parameters;
+mod m20261016_000022_create_pull_request_checks;
+mod m20261016_000023_create_report_context;
+mod m20261016_000024_create_experiments;
+mod m20261016_000025_add_report_evaluated_commit;
+mod m20261016_000026_create_threshold_evaluations;
+mod m20261016_000027_add_secret_redaction;
+mod m20261016_000028_create_uploads;
+mod m20261016_000029_create_blobs;
+mod m20261016_000030_add_flamegraph_upload_sha256;
+mod m20261016_000031_create_notification_channels_and_digests;
+mod m20261016_000032_add_alert_feed_token;
+mod m20261016_000033_create_remote_write;
+mod m20261016_000034_create_report_exports;
+mod m20261016_000035_add_channel_events_and_templates;
+mod m20261016_000036_create_idempotency_keys;
+mod m20261016_000037_create_data_backfills;
+mod m20261016_000038_add_anonymous_projects;
+mod m20261016_000039_add_default_branch_and_aliases;
+mod m20261016_000040_create_report_tags_and_filters;
+mod m20261016_000041_add_report_kind;
+mod m20261016_000042_add_storage_encoding;
+mod m20261016_000043_create_expected_benchmarks;
+mod m20261016_000044_add_report_exit_code;
+mod m20261016_000045_add_testbed_aliases;
 
 pub struct Migrator;
 
@@ -11,6 +58,85 @@ impl MigratorTrait for Migrator {
         migrations.push(Box::new(
             m20241221_000001_create_driftwatch_tables::Migration,
         ));
+        migrations.push(Box::new(
+            m20261016_000001_add_report_commit_metadata::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000002_add_report_merge_base::Migration));
+        migrations.push(Box::new(m20261016_000003_create_stale_alerts::Migration));
+        migrations.push(Box::new(m20261016_000004_create_jobs::Migration));
+        migrations.push(Box::new(m20261016_000005_add_series_indexes::Migration));
+        migrations.push(Box::new(
+            m20261016_000006_create_metric_summaries::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000007_add_report_finalized::Migration));
+        migrations.push(Box::new(m20261016_000008_add_report_status::Migration));
+        migrations.push(Box::new(
+            m20261016_000009_create_benchmark_owners::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000010_add_alert_triage::Migration));
+        migrations.push(Box::new(
+            m20261016_000011_add_alert_github_issues::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000012_add_alert_confirmation::Migration));
+        migrations.push(Box::new(m20261016_000013_create_benchmark_noise::Migration));
+        migrations.push(Box::new(m20261016_000014_add_report_excluded::Migration));
+        migrations.push(Box::new(m20261016_000015_create_annotations::Migration));
+        migrations.push(Box::new(m20261016_000016_add_report_version::Migration));
+        migrations.push(Box::new(m20261016_000017_create_project_groups::Migration));
+        migrations.push(Box::new(
+            m20261016_000018_add_benchmark_name_trigram_index::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000019_create_report_outputs::Migration));
+        migrations.push(Box::new(
+            m20261016_000020_add_metric_harness_stats::Migration,
+        ));
+        migrations.push(Box::new(
+            m20261016_000021_add_benchmark_parameters::Migration,
+        ));
+        migrations.push(Box::new(
+            m20261016_000022_create_pull_request_checks::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000023_create_report_context::Migration));
+        migrations.push(Box::new(m20261016_000024_create_experiments::Migration));
+        migrations.push(Box::new(
+            m20261016_000025_add_report_evaluated_commit::Migration,
+        ));
+        migrations.push(Box::new(
+            m20261016_000026_create_threshold_evaluations::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000027_add_secret_redaction::Migration));
+        migrations.push(Box::new(m20261016_000028_create_uploads::Migration));
+        migrations.push(Box::new(m20261016_000029_create_blobs::Migration));
+        migrations.push(Box::new(
+            m20261016_000030_add_flamegraph_upload_sha256::Migration,
+        ));
+        migrations.push(Box::new(
+            m20261016_000031_create_notification_channels_and_digests::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000032_add_alert_feed_token::Migration));
+        migrations.push(Box::new(m20261016_000033_create_remote_write::Migration));
+        migrations.push(Box::new(m20261016_000034_create_report_exports::Migration));
+        migrations.push(Box::new(
+            m20261016_000035_add_channel_events_and_templates::Migration,
+        ));
+        migrations.push(Box::new(
+            m20261016_000036_create_idempotency_keys::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000037_create_data_backfills::Migration));
+        migrations.push(Box::new(m20261016_000038_add_anonymous_projects::Migration));
+        migrations.push(Box::new(
+            m20261016_000039_add_default_branch_and_aliases::Migration,
+        ));
+        migrations.push(Box::new(
+            m20261016_000040_create_report_tags_and_filters::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000041_add_report_kind::Migration));
+        migrations.push(Box::new(m20261016_000042_add_storage_encoding::Migration));
+        migrations.push(Box::new(
+            m20261016_000043_create_expected_benchmarks::Migration,
+        ));
+        migrations.push(Box::new(m20261016_000044_add_report_exit_code::Migration));
+        migrations.push(Box::new(m20261016_000045_add_testbed_aliases::Migration));
         migrations
     }
 }
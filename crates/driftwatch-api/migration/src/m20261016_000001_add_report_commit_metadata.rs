@@ -0,0 +1,41 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .add_column_if_not_exists(text_null(Reports::CommitMessage))
+                    .add_column_if_not_exists(string_null(Reports::CommitAuthor))
+                    .add_column_if_not_exists(timestamp_with_time_zone_null(Reports::CommittedAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .drop_column(Reports::CommitMessage)
+                    .drop_column(Reports::CommitAuthor)
+                    .drop_column(Reports::CommittedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    CommitMessage,
+    CommitAuthor,
+    CommittedAt,
+}
@@ -0,0 +1,129 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .add_column_if_not_exists(integer_null(Projects::ExpectedCadenceHours))
+                    .add_column_if_not_exists(text_null(Projects::StaleBranches))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(StaleAlerts::Table)
+                    .if_not_exists()
+                    .col(uuid(StaleAlerts::Id).primary_key())
+                    .col(uuid(StaleAlerts::ProjectId).not_null())
+                    .col(uuid(StaleAlerts::BranchId).not_null())
+                    .col(uuid(StaleAlerts::TestbedId).not_null())
+                    .col(timestamp_with_time_zone(StaleAlerts::LastReportAt).not_null())
+                    .col(integer(StaleAlerts::ExpectedCadenceHours).not_null())
+                    .col(string(StaleAlerts::Status).not_null().default("active"))
+                    .col(timestamp_with_time_zone_null(StaleAlerts::ResolvedAt))
+                    .col(timestamp_with_time_zone(StaleAlerts::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(StaleAlerts::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(StaleAlerts::Table, StaleAlerts::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(StaleAlerts::Table, StaleAlerts::BranchId)
+                            .to(Branches::Table, Branches::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(StaleAlerts::Table, StaleAlerts::TestbedId)
+                            .to(Testbeds::Table, Testbeds::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_stale_alerts_project_status")
+                    .table(StaleAlerts::Table)
+                    .col(StaleAlerts::ProjectId)
+                    .col(StaleAlerts::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        // At most one active alert per branch/testbed, even when two checks
+        // overlap
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_stale_alerts_active \
+             ON stale_alerts (project_id, branch_id, testbed_id) WHERE status = 'active'",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StaleAlerts::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .drop_column(Projects::ExpectedCadenceHours)
+                    .drop_column(Projects::StaleBranches)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+    ExpectedCadenceHours,
+    StaleBranches,
+}
+
+#[derive(DeriveIden)]
+enum Branches {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Testbeds {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum StaleAlerts {
+    Table,
+    Id,
+    ProjectId,
+    BranchId,
+    TestbedId,
+    LastReportAt,
+    ExpectedCadenceHours,
+    Status,
+    ResolvedAt,
+    CreatedAt,
+    UpdatedAt,
+}
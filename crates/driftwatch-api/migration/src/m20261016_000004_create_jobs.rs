@@ -0,0 +1,86 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Jobs::Table)
+                    .if_not_exists()
+                    .col(uuid(Jobs::Id).primary_key())
+                    .col(string(Jobs::Kind).not_null())
+                    .col(json_binary(Jobs::Payload).not_null())
+                    .col(string(Jobs::Status).not_null().default("pending"))
+                    .col(integer(Jobs::Attempts).not_null().default(0))
+                    .col(integer(Jobs::MaxAttempts).not_null())
+                    .col(timestamp_with_time_zone(Jobs::RunAt).not_null())
+                    .col(timestamp_with_time_zone_null(Jobs::LockedAt))
+                    .col(text_null(Jobs::LastError))
+                    .col(timestamp_with_time_zone(Jobs::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(Jobs::UpdatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_jobs_status_run_at")
+                    .table(Jobs::Table)
+                    .col(Jobs::Status)
+                    .col(Jobs::RunAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // When each scheduled job kind is next due, shared by all instances
+        manager
+            .create_table(
+                Table::create()
+                    .table(JobSchedules::Table)
+                    .if_not_exists()
+                    .col(string(JobSchedules::Kind).primary_key())
+                    .col(timestamp_with_time_zone(JobSchedules::NextRunAt).not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JobSchedules::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Jobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Jobs {
+    Table,
+    Id,
+    Kind,
+    Payload,
+    Status,
+    Attempts,
+    MaxAttempts,
+    RunAt,
+    LockedAt,
+    LastError,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum JobSchedules {
+    Table,
+    Kind,
+    NextRunAt,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Series and baseline lookups filter metrics by benchmark + measure
+        // and walk them newest first
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_metrics_benchmark_measure_created_at")
+                    .table(Metrics::Table)
+                    .col(Metrics::BenchmarkId)
+                    .col(Metrics::MeasureId)
+                    .col(Metrics::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // The join back to reports narrows by branch + testbed and date
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_reports_branch_testbed_created_at")
+                    .table(Reports::Table)
+                    .col(Reports::BranchId)
+                    .col(Reports::TestbedId)
+                    .col(Reports::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Merge-base comparisons look reports up by commit
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_reports_branch_testbed_git_hash")
+                    .table(Reports::Table)
+                    .col(Reports::BranchId)
+                    .col(Reports::TestbedId)
+                    .col(Reports::GitHash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for name in [
+            "idx_metrics_benchmark_measure_created_at",
+            "idx_reports_branch_testbed_created_at",
+            "idx_reports_branch_testbed_git_hash",
+        ] {
+            manager
+                .drop_index(Index::drop().if_exists().name(name).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Metrics {
+    Table,
+    BenchmarkId,
+    MeasureId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    BranchId,
+    TestbedId,
+    GitHash,
+    CreatedAt,
+}
@@ -0,0 +1,140 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MetricSummaries::Table)
+                    .if_not_exists()
+                    .col(uuid(MetricSummaries::Id).primary_key())
+                    .col(uuid(MetricSummaries::ProjectId).not_null())
+                    .col(uuid(MetricSummaries::BenchmarkId).not_null())
+                    .col(uuid(MetricSummaries::MeasureId).not_null())
+                    .col(uuid(MetricSummaries::BranchId).not_null())
+                    .col(uuid(MetricSummaries::TestbedId).not_null())
+                    .col(uuid(MetricSummaries::LatestMetricId).not_null())
+                    .col(uuid(MetricSummaries::LatestReportId).not_null())
+                    .col(double(MetricSummaries::LatestValue).not_null())
+                    .col(timestamp_with_time_zone(MetricSummaries::LatestAt).not_null())
+                    .col(double_null(MetricSummaries::Delta7d))
+                    .col(double_null(MetricSummaries::Delta30d))
+                    .col(timestamp_with_time_zone(MetricSummaries::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricSummaries::Table, MetricSummaries::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricSummaries::Table, MetricSummaries::BranchId)
+                            .to(Branches::Table, Branches::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricSummaries::Table, MetricSummaries::TestbedId)
+                            .to(Testbeds::Table, Testbeds::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_metric_summaries_key")
+                    .table(MetricSummaries::Table)
+                    .col(MetricSummaries::BenchmarkId)
+                    .col(MetricSummaries::MeasureId)
+                    .col(MetricSummaries::BranchId)
+                    .col(MetricSummaries::TestbedId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_metric_summaries_project_id")
+                    .table(MetricSummaries::Table)
+                    .col(MetricSummaries::ProjectId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Seed latest values from existing history; deltas fill in as new
+        // reports arrive
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO metric_summaries (
+                    id, project_id, benchmark_id, measure_id, branch_id, testbed_id,
+                    latest_metric_id, latest_report_id, latest_value, latest_at, updated_at
+                )
+                SELECT DISTINCT ON (m.benchmark_id, m.measure_id, r.branch_id, r.testbed_id)
+                    gen_random_uuid(), r.project_id, m.benchmark_id, m.measure_id,
+                    r.branch_id, r.testbed_id, m.id, r.id, m.value, r.created_at, NOW()
+                FROM metrics m
+                JOIN reports r ON r.id = m.report_id
+                ORDER BY m.benchmark_id, m.measure_id, r.branch_id, r.testbed_id, r.created_at DESC
+                ON CONFLICT DO NOTHING"#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MetricSummaries::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Branches {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Testbeds {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum MetricSummaries {
+    Table,
+    Id,
+    ProjectId,
+    BenchmarkId,
+    MeasureId,
+    BranchId,
+    TestbedId,
+    LatestMetricId,
+    LatestReportId,
+    LatestValue,
+    LatestAt,
+    #[sea_orm(iden = "delta_7d")]
+    Delta7d,
+    #[sea_orm(iden = "delta_30d")]
+    Delta30d,
+    UpdatedAt,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BenchmarkOwners::Table)
+                    .if_not_exists()
+                    .col(uuid(BenchmarkOwners::Id).primary_key())
+                    .col(uuid(BenchmarkOwners::ProjectId).not_null())
+                    .col(integer(BenchmarkOwners::Position).not_null())
+                    .col(string(BenchmarkOwners::Pattern).not_null())
+                    .col(text(BenchmarkOwners::Owners).not_null())
+                    .col(timestamp_with_time_zone(BenchmarkOwners::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(BenchmarkOwners::Table, BenchmarkOwners::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_benchmark_owners_project_position")
+                    .table(BenchmarkOwners::Table)
+                    .col(BenchmarkOwners::ProjectId)
+                    .col(BenchmarkOwners::Position)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BenchmarkOwners::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum BenchmarkOwners {
+    Table,
+    Id,
+    ProjectId,
+    Position,
+    Pattern,
+    Owners,
+    CreatedAt,
+}
@@ -0,0 +1,122 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BenchmarkNoise::Table)
+                    .if_not_exists()
+                    .col(uuid(BenchmarkNoise::Id).primary_key())
+                    .col(uuid(BenchmarkNoise::ProjectId).not_null())
+                    .col(uuid(BenchmarkNoise::BenchmarkId).not_null())
+                    .col(uuid(BenchmarkNoise::MeasureId).not_null())
+                    .col(uuid(BenchmarkNoise::TestbedId).not_null())
+                    .col(integer(BenchmarkNoise::SampleSize).not_null())
+                    .col(double(BenchmarkNoise::Mean).not_null())
+                    .col(double(BenchmarkNoise::StdDev).not_null())
+                    .col(double(BenchmarkNoise::CoefficientOfVariation).not_null())
+                    .col(timestamp_with_time_zone(BenchmarkNoise::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(BenchmarkNoise::Table, BenchmarkNoise::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(BenchmarkNoise::Table, BenchmarkNoise::TestbedId)
+                            .to(Testbeds::Table, Testbeds::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_benchmark_noise_key")
+                    .table(BenchmarkNoise::Table)
+                    .col(BenchmarkNoise::BenchmarkId)
+                    .col(BenchmarkNoise::MeasureId)
+                    .col(BenchmarkNoise::TestbedId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_benchmark_noise_project_id")
+                    .table(BenchmarkNoise::Table)
+                    .col(BenchmarkNoise::ProjectId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .add_column_if_not_exists(double_null(Projects::NoiseCvLimit))
+                    .add_column_if_not_exists(
+                        string(Projects::NoiseAction).not_null().default("flag"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .drop_column(Projects::NoiseCvLimit)
+                    .drop_column(Projects::NoiseAction)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(BenchmarkNoise::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+    NoiseCvLimit,
+    NoiseAction,
+}
+
+#[derive(DeriveIden)]
+enum Testbeds {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum BenchmarkNoise {
+    Table,
+    Id,
+    ProjectId,
+    BenchmarkId,
+    MeasureId,
+    TestbedId,
+    SampleSize,
+    Mean,
+    StdDev,
+    CoefficientOfVariation,
+    UpdatedAt,
+}
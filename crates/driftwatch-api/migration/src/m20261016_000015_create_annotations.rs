@@ -0,0 +1,90 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Annotations::Table)
+                    .if_not_exists()
+                    .col(uuid(Annotations::Id).primary_key())
+                    .col(uuid(Annotations::ProjectId).not_null())
+                    .col(uuid_null(Annotations::ReportId))
+                    .col(string(Annotations::Kind).not_null().default("note"))
+                    .col(text(Annotations::Note).not_null())
+                    .col(timestamp_with_time_zone(Annotations::OccurredAt).not_null())
+                    .col(timestamp_with_time_zone(Annotations::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Annotations::Table, Annotations::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Annotations::Table, Annotations::ReportId)
+                            .to(Reports::Table, Reports::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_annotations_project_occurred_at")
+                    .table(Annotations::Table)
+                    .col(Annotations::ProjectId)
+                    .col(Annotations::OccurredAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_annotations_report")
+                    .table(Annotations::Table)
+                    .col(Annotations::ReportId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Annotations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Annotations {
+    Table,
+    Id,
+    ProjectId,
+    ReportId,
+    Kind,
+    Note,
+    OccurredAt,
+    CreatedAt,
+}
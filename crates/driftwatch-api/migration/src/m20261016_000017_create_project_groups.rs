@@ -0,0 +1,114 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectGroups::Table)
+                    .if_not_exists()
+                    .col(uuid(ProjectGroups::Id).primary_key())
+                    .col(uuid(ProjectGroups::UserId).not_null())
+                    .col(string(ProjectGroups::Slug).not_null())
+                    .col(string(ProjectGroups::Name).not_null())
+                    .col(text_null(ProjectGroups::Description))
+                    .col(timestamp_with_time_zone(ProjectGroups::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(ProjectGroups::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProjectGroups::Table, ProjectGroups::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectGroupMembers::Table)
+                    .if_not_exists()
+                    .col(uuid(ProjectGroupMembers::GroupId).not_null())
+                    .col(uuid(ProjectGroupMembers::ProjectId).not_null())
+                    .col(timestamp_with_time_zone(ProjectGroupMembers::CreatedAt).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ProjectGroupMembers::GroupId)
+                            .col(ProjectGroupMembers::ProjectId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProjectGroupMembers::Table, ProjectGroupMembers::GroupId)
+                            .to(ProjectGroups::Table, ProjectGroups::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ProjectGroupMembers::Table, ProjectGroupMembers::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_project_groups_user_slug")
+                    .table(ProjectGroups::Table)
+                    .col(ProjectGroups::UserId)
+                    .col(ProjectGroups::Slug)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProjectGroupMembers::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ProjectGroups::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ProjectGroups {
+    Table,
+    Id,
+    UserId,
+    Slug,
+    Name,
+    Description,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProjectGroupMembers {
+    Table,
+    GroupId,
+    ProjectId,
+    CreatedAt,
+}
@@ -0,0 +1,29 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // pg_trgm is a trusted extension, so the app role can create it. The
+        // GIN index serves ILIKE '%pattern%' lookups from benchmark search.
+        let db = manager.get_connection();
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm")
+            .await?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_benchmarks_name_trgm \
+             ON benchmarks USING gin (name gin_trgm_ops)",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DROP INDEX IF EXISTS idx_benchmarks_name_trgm")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportOutputs::Table)
+                    .if_not_exists()
+                    .col(uuid(ReportOutputs::ReportId).primary_key())
+                    .col(blob(ReportOutputs::Content).not_null())
+                    .col(timestamp_with_time_zone(ReportOutputs::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ReportOutputs::Table, ReportOutputs::ReportId)
+                            .to(Reports::Table, Reports::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReportOutputs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ReportOutputs {
+    Table,
+    ReportId,
+    Content,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Metrics::Table)
+                    .add_column_if_not_exists(double_null(Metrics::ReportedChange))
+                    .add_column_if_not_exists(integer_null(Metrics::Outliers))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Metrics::Table)
+                    .drop_column(Metrics::ReportedChange)
+                    .drop_column(Metrics::Outliers)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Metrics {
+    Table,
+    ReportedChange,
+    Outliers,
+}
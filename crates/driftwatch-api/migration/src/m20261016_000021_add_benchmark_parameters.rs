@@ -0,0 +1,74 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Benchmarks::Table)
+                    .add_column_if_not_exists(string_null(Benchmarks::BaseName))
+                    .add_column_if_not_exists(double_null(Benchmarks::Parameter))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_benchmarks_project_base_name")
+                    .table(Benchmarks::Table)
+                    .col(Benchmarks::ProjectId)
+                    .col(Benchmarks::BaseName)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Same rule as `scaling::split_parameter`: a trailing numeric path
+        // segment, as in `sort/1000`
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE benchmarks
+                SET base_name = regexp_replace(name, '/[0-9]+(\.[0-9]+)?$', ''),
+                    parameter = substring(name from '/([0-9]+(?:\.[0-9]+)?)$')::double precision
+                WHERE name ~ '^.+/[0-9]+(\.[0-9]+)?$'"#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_benchmarks_project_base_name")
+                    .table(Benchmarks::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Benchmarks::Table)
+                    .drop_column(Benchmarks::BaseName)
+                    .drop_column(Benchmarks::Parameter)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Benchmarks {
+    Table,
+    ProjectId,
+    BaseName,
+    Parameter,
+}
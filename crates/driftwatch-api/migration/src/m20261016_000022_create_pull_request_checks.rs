@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .add_column_if_not_exists(text_null(Projects::BenchmarkRequiredPaths))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PullRequestChecks::Table)
+                    .if_not_exists()
+                    .col(uuid(PullRequestChecks::ProjectId).not_null())
+                    .col(integer(PullRequestChecks::PrNumber).not_null())
+                    .col(string(PullRequestChecks::HeadSha).not_null())
+                    .col(string(PullRequestChecks::State).not_null())
+                    .col(text(PullRequestChecks::Description).not_null())
+                    .col(timestamp_with_time_zone(PullRequestChecks::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(PullRequestChecks::UpdatedAt).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(PullRequestChecks::ProjectId)
+                            .col(PullRequestChecks::PrNumber),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PullRequestChecks::Table, PullRequestChecks::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PullRequestChecks::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .drop_column(Projects::BenchmarkRequiredPaths)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+    BenchmarkRequiredPaths,
+}
+
+#[derive(DeriveIden)]
+enum PullRequestChecks {
+    Table,
+    ProjectId,
+    PrNumber,
+    HeadSha,
+    State,
+    Description,
+    CreatedAt,
+    UpdatedAt,
+}
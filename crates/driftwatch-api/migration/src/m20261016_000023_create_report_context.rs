@@ -0,0 +1,64 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportContext::Table)
+                    .if_not_exists()
+                    .col(uuid(ReportContext::ReportId).not_null())
+                    .col(string(ReportContext::Key).not_null())
+                    .col(string(ReportContext::Value).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ReportContext::ReportId)
+                            .col(ReportContext::Key),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ReportContext::Table, ReportContext::ReportId)
+                            .to(Reports::Table, Reports::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_report_context_key_value")
+                    .table(ReportContext::Table)
+                    .col(ReportContext::Key)
+                    .col(ReportContext::Value)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReportContext::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ReportContext {
+    Table,
+    ReportId,
+    Key,
+    Value,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+}
@@ -0,0 +1,126 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Experiments::Table)
+                    .if_not_exists()
+                    .col(uuid(Experiments::Id).primary_key())
+                    .col(uuid(Experiments::ProjectId).not_null())
+                    .col(string_null(Experiments::Name))
+                    .col(text(Experiments::BaselineCommand).not_null())
+                    .col(text(Experiments::CandidateCommand).not_null())
+                    .col(string_null(Experiments::GitHash))
+                    .col(string_null(Experiments::Testbed))
+                    .col(integer(Experiments::Rounds).not_null())
+                    .col(double(Experiments::Alpha).not_null())
+                    .col(timestamp_with_time_zone(Experiments::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Experiments::Table, Experiments::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_experiments_project_created")
+                    .table(Experiments::Table)
+                    .col(Experiments::ProjectId)
+                    .col(Experiments::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExperimentResults::Table)
+                    .if_not_exists()
+                    .col(uuid(ExperimentResults::Id).primary_key())
+                    .col(uuid(ExperimentResults::ExperimentId).not_null())
+                    .col(string(ExperimentResults::Benchmark).not_null())
+                    .col(string(ExperimentResults::Measure).not_null())
+                    .col(double(ExperimentResults::BaselineMean).not_null())
+                    .col(double(ExperimentResults::CandidateMean).not_null())
+                    .col(double_null(ExperimentResults::PercentChange))
+                    .col(double_null(ExperimentResults::PValue))
+                    .col(string(ExperimentResults::Verdict).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ExperimentResults::Table, ExperimentResults::ExperimentId)
+                            .to(Experiments::Table, Experiments::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_experiment_results_experiment")
+                    .table(ExperimentResults::Table)
+                    .col(ExperimentResults::ExperimentId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ExperimentResults::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Experiments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Experiments {
+    Table,
+    Id,
+    ProjectId,
+    Name,
+    BaselineCommand,
+    CandidateCommand,
+    GitHash,
+    Testbed,
+    Rounds,
+    Alpha,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ExperimentResults {
+    Table,
+    Id,
+    ExperimentId,
+    Benchmark,
+    Measure,
+    BaselineMean,
+    CandidateMean,
+    PercentChange,
+    PValue,
+    Verdict,
+}
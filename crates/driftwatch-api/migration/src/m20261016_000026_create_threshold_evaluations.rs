@@ -0,0 +1,119 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ThresholdEvaluations::Table)
+                    .if_not_exists()
+                    .col(uuid(ThresholdEvaluations::Id).primary_key())
+                    .col(uuid(ThresholdEvaluations::ReportId).not_null())
+                    .col(uuid(ThresholdEvaluations::MetricId).not_null())
+                    .col(uuid(ThresholdEvaluations::ThresholdId).not_null())
+                    .col(string(ThresholdEvaluations::Verdict).not_null())
+                    .col(double_null(ThresholdEvaluations::Baseline))
+                    .col(integer(ThresholdEvaluations::BaselineSize).not_null())
+                    .col(boolean(ThresholdEvaluations::Pinned).not_null())
+                    .col(double_null(ThresholdEvaluations::PercentChange))
+                    .col(double_null(ThresholdEvaluations::UpperBoundary))
+                    .col(double_null(ThresholdEvaluations::LowerBoundary))
+                    .col(integer(ThresholdEvaluations::MinSampleSize).not_null())
+                    .col(boolean(ThresholdEvaluations::NoiseAdjusted).not_null())
+                    .col(timestamp_with_time_zone(ThresholdEvaluations::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ThresholdEvaluations::Table, ThresholdEvaluations::ReportId)
+                            .to(Reports::Table, Reports::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ThresholdEvaluations::Table, ThresholdEvaluations::MetricId)
+                            .to(Metrics::Table, Metrics::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                ThresholdEvaluations::Table,
+                                ThresholdEvaluations::ThresholdId,
+                            )
+                            .to(Thresholds::Table, Thresholds::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_threshold_evaluations_report")
+                    .table(ThresholdEvaluations::Table)
+                    .col(ThresholdEvaluations::ReportId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_threshold_evaluations_metric_threshold")
+                    .table(ThresholdEvaluations::Table)
+                    .col(ThresholdEvaluations::MetricId)
+                    .col(ThresholdEvaluations::ThresholdId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ThresholdEvaluations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ThresholdEvaluations {
+    Table,
+    Id,
+    ReportId,
+    MetricId,
+    ThresholdId,
+    Verdict,
+    Baseline,
+    BaselineSize,
+    Pinned,
+    PercentChange,
+    UpperBoundary,
+    LowerBoundary,
+    MinSampleSize,
+    NoiseAdjusted,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Metrics {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Thresholds {
+    Table,
+    Id,
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Uploads::Table)
+                    .if_not_exists()
+                    .col(uuid(Uploads::Id).primary_key())
+                    .col(uuid(Uploads::ProjectId).not_null())
+                    .col(string(Uploads::Token).not_null().unique_key())
+                    .col(string(Uploads::FileName).not_null())
+                    .col(blob(Uploads::Content).not_null())
+                    .col(big_integer_null(Uploads::TotalSize))
+                    .col(timestamp_with_time_zone(Uploads::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone_null(Uploads::CompletedAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Uploads::Table, Uploads::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Flamegraphs::Table)
+                    .add_column_if_not_exists(string_null(Flamegraphs::Sha256))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Flamegraphs::Table)
+                    .drop_column(Flamegraphs::Sha256)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Uploads::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Uploads {
+    Table,
+    Id,
+    ProjectId,
+    Token,
+    FileName,
+    Content,
+    TotalSize,
+    CreatedAt,
+    CompletedAt,
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Flamegraphs {
+    Table,
+    Sha256,
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Blobs::Table)
+                    .if_not_exists()
+                    .col(string(Blobs::Sha256).primary_key())
+                    .col(blob(Blobs::Content).not_null())
+                    .col(big_integer(Blobs::Size).not_null())
+                    .col(integer(Blobs::RefCount).not_null().default(0))
+                    .col(timestamp_with_time_zone(Blobs::CreatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // Move confirmed uploads into blobs, one per distinct file
+        db.execute_unprepared(
+            r#"INSERT INTO blobs (sha256, content, size, ref_count, created_at)
+               SELECT DISTINCT ON (f.sha256)
+                   f.sha256, u.content, octet_length(u.content), 0, u.created_at
+               FROM flamegraphs f
+               JOIN uploads u ON f.storage_path = 'uploads/' || u.id::text
+               WHERE f.sha256 IS NOT NULL
+               ORDER BY f.sha256, u.created_at
+               ON CONFLICT DO NOTHING"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"UPDATE blobs b
+               SET ref_count = (SELECT count(*) FROM flamegraphs f WHERE f.sha256 = b.sha256)"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"UPDATE flamegraphs SET storage_path = 'blobs/' || sha256
+               WHERE sha256 IS NOT NULL"#,
+        )
+        .await?;
+        db.execute_unprepared(r#"UPDATE uploads SET content = '' WHERE completed_at IS NOT NULL"#)
+            .await?;
+
+        // Keep reference counts right however flamegraphs go away, including
+        // cascades from deleted reports and projects
+        db.execute_unprepared(
+            r#"CREATE OR REPLACE FUNCTION count_blob_refs() RETURNS trigger AS $$
+               BEGIN
+                   IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.sha256 IS NOT NULL THEN
+                       UPDATE blobs SET ref_count = ref_count + 1 WHERE sha256 = NEW.sha256;
+                   END IF;
+                   IF TG_OP IN ('DELETE', 'UPDATE') AND OLD.sha256 IS NOT NULL THEN
+                       UPDATE blobs SET ref_count = ref_count - 1 WHERE sha256 = OLD.sha256;
+                   END IF;
+                   RETURN NULL;
+               END
+               $$ LANGUAGE plpgsql"#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"CREATE OR REPLACE TRIGGER flamegraphs_count_blob_refs
+               AFTER INSERT OR DELETE OR UPDATE OF sha256 ON flamegraphs
+               FOR EACH ROW EXECUTE FUNCTION count_blob_refs()"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared("DROP TRIGGER IF EXISTS flamegraphs_count_blob_refs ON flamegraphs")
+            .await?;
+        db.execute_unprepared("DROP FUNCTION IF EXISTS count_blob_refs()")
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Blobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Blobs {
+    Table,
+    Sha256,
+    Content,
+    Size,
+    RefCount,
+    CreatedAt,
+}
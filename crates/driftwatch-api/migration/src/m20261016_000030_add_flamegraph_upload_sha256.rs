@@ -0,0 +1,44 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Flamegraphs::Table)
+                    .add_column_if_not_exists(string_null(Flamegraphs::UploadSha256))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Files stored so far were kept exactly as uploaded
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE flamegraphs SET upload_sha256 = sha256 WHERE upload_sha256 IS NULL",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Flamegraphs::Table)
+                    .drop_column(Flamegraphs::UploadSha256)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Flamegraphs {
+    Table,
+    UploadSha256,
+}
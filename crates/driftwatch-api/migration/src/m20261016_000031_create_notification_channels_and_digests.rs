@@ -0,0 +1,100 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationChannels::Table)
+                    .if_not_exists()
+                    .col(uuid(NotificationChannels::Id).primary_key())
+                    .col(uuid(NotificationChannels::ProjectId).not_null())
+                    .col(string(NotificationChannels::Kind).not_null())
+                    .col(text(NotificationChannels::Url).not_null())
+                    .col(timestamp_with_time_zone(NotificationChannels::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(NotificationChannels::Table, NotificationChannels::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(Digests::Table)
+                    .if_not_exists()
+                    .col(uuid(Digests::Id).primary_key())
+                    .col(uuid(Digests::ProjectId).not_null())
+                    .col(timestamp_with_time_zone(Digests::PeriodStart).not_null())
+                    .col(timestamp_with_time_zone(Digests::PeriodEnd).not_null())
+                    .col(text(Digests::Markdown).not_null())
+                    .col(text(Digests::Html).not_null())
+                    .col(timestamp_with_time_zone(Digests::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Digests::Table, Digests::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // One digest per project and week, however many servers generate them
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_digests_project_period")
+                    .table(Digests::Table)
+                    .col(Digests::ProjectId)
+                    .col(Digests::PeriodStart)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Digests::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(NotificationChannels::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NotificationChannels {
+    Table,
+    Id,
+    ProjectId,
+    Kind,
+    Url,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Digests {
+    Table,
+    Id,
+    ProjectId,
+    PeriodStart,
+    PeriodEnd,
+    Markdown,
+    Html,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
@@ -0,0 +1,121 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RemoteWriteRules::Table)
+                    .if_not_exists()
+                    .col(uuid(RemoteWriteRules::Id).primary_key())
+                    .col(uuid(RemoteWriteRules::ProjectId).not_null())
+                    .col(string(RemoteWriteRules::Metric).not_null())
+                    .col(json_binary(RemoteWriteRules::Matchers).not_null())
+                    .col(text(RemoteWriteRules::Benchmark).not_null())
+                    .col(string(RemoteWriteRules::Measure).not_null())
+                    .col(string(RemoteWriteRules::Branch).not_null())
+                    .col(text(RemoteWriteRules::Testbed).not_null())
+                    .col(timestamp_with_time_zone(RemoteWriteRules::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone_null(
+                        RemoteWriteRules::FlushedUntil,
+                    ))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RemoteWriteRules::Table, RemoteWriteRules::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // Samples are summed per series and hour here until the hour closes
+        // and becomes a report, so Prometheus' steady stream of writes
+        // doesn't turn into a report per write
+        manager
+            .create_table(
+                Table::create()
+                    .table(RemoteWriteBuckets::Table)
+                    .if_not_exists()
+                    .col(uuid(RemoteWriteBuckets::RuleId).not_null())
+                    .col(text(RemoteWriteBuckets::Benchmark).not_null())
+                    .col(text(RemoteWriteBuckets::Testbed).not_null())
+                    .col(timestamp_with_time_zone(RemoteWriteBuckets::BucketStart).not_null())
+                    .col(double(RemoteWriteBuckets::Total).not_null())
+                    .col(big_integer(RemoteWriteBuckets::Samples).not_null())
+                    .col(double(RemoteWriteBuckets::MinValue).not_null())
+                    .col(double(RemoteWriteBuckets::MaxValue).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(RemoteWriteBuckets::RuleId)
+                            .col(RemoteWriteBuckets::Benchmark)
+                            .col(RemoteWriteBuckets::Testbed)
+                            .col(RemoteWriteBuckets::BucketStart),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RemoteWriteBuckets::Table, RemoteWriteBuckets::RuleId)
+                            .to(RemoteWriteRules::Table, RemoteWriteRules::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_remote_write_buckets_bucket_start")
+                    .table(RemoteWriteBuckets::Table)
+                    .col(RemoteWriteBuckets::BucketStart)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RemoteWriteBuckets::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(RemoteWriteRules::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RemoteWriteRules {
+    Table,
+    Id,
+    ProjectId,
+    Metric,
+    Matchers,
+    Benchmark,
+    Measure,
+    Branch,
+    Testbed,
+    CreatedAt,
+    FlushedUntil,
+}
+
+#[derive(DeriveIden)]
+enum RemoteWriteBuckets {
+    Table,
+    RuleId,
+    Benchmark,
+    Testbed,
+    BucketStart,
+    Total,
+    Samples,
+    MinValue,
+    MaxValue,
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
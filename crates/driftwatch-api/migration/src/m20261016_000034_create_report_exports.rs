@@ -0,0 +1,56 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Evaluated reports waiting to be sent to the export sink
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportExports::Table)
+                    .if_not_exists()
+                    .col(uuid(ReportExports::ReportId).primary_key())
+                    .col(timestamp_with_time_zone(ReportExports::QueuedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ReportExports::Table, ReportExports::ReportId)
+                            .to(Reports::Table, Reports::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_report_exports_queued_at")
+                    .table(ReportExports::Table)
+                    .col(ReportExports::QueuedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReportExports::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ReportExports {
+    Table,
+    ReportId,
+    QueuedAt,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Channels created so far only ever received digests
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationChannels::Table)
+                    .add_column_if_not_exists(
+                        json_binary(NotificationChannels::Events)
+                            .default(Expr::cust("'[\"digest\"]'::jsonb")),
+                    )
+                    .add_column_if_not_exists(text_null(NotificationChannels::Template))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NotificationChannels::Table)
+                    .drop_column(NotificationChannels::Events)
+                    .drop_column(NotificationChannels::Template)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NotificationChannels {
+    Table,
+    Events,
+    Template,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Data migrations too large for one transaction, run in batches by
+        // the job queue after the schema change is deployed
+        manager
+            .create_table(
+                Table::create()
+                    .table(DataBackfills::Table)
+                    .if_not_exists()
+                    .col(text(DataBackfills::Name).primary_key())
+                    .col(text(DataBackfills::BatchSql))
+                    .col(big_integer(DataBackfills::RowsDone).default(0))
+                    .col(integer(DataBackfills::Batches).default(0))
+                    .col(text_null(DataBackfills::LastError))
+                    .col(timestamp_with_time_zone(DataBackfills::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone_null(DataBackfills::CompletedAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DataBackfills::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DataBackfills {
+    Table,
+    Name,
+    BatchSql,
+    RowsDone,
+    Batches,
+    LastError,
+    CreatedAt,
+    CompletedAt,
+}
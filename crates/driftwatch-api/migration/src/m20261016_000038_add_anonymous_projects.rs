@@ -0,0 +1,94 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Anonymous shares are projects without an owner that expire
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE projects ALTER COLUMN user_id DROP NOT NULL")
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .add_column_if_not_exists(timestamp_with_time_zone_null(Projects::ExpiresAt))
+                    .add_column_if_not_exists(text_null(Projects::ShareTokenHash))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_projects_share_token_hash")
+                    .table(Projects::Table)
+                    .col(Projects::ShareTokenHash)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE INDEX IF NOT EXISTS idx_projects_expires_at \
+                 ON projects (expires_at) WHERE expires_at IS NOT NULL",
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM reports WHERE project_id IN \
+                 (SELECT id FROM projects WHERE user_id IS NULL)",
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM projects WHERE user_id IS NULL")
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_projects_expires_at")
+                    .table(Projects::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_projects_share_token_hash")
+                    .table(Projects::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .drop_column(Projects::ShareTokenHash)
+                    .drop_column(Projects::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE projects ALTER COLUMN user_id SET NOT NULL")
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    ExpiresAt,
+    ShareTokenHash,
+}
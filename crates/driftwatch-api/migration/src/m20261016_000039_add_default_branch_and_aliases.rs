@@ -0,0 +1,94 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .add_column_if_not_exists(string(Projects::DefaultBranch).default("main"))
+                    .to_owned(),
+            )
+            .await?;
+        // Other names a branch is submitted under, e.g. `main` after a
+        // repository renamed `master`, so its history stays in one series
+        manager
+            .create_table(
+                Table::create()
+                    .table(BranchAliases::Table)
+                    .if_not_exists()
+                    .col(uuid(BranchAliases::Id).primary_key())
+                    .col(uuid(BranchAliases::ProjectId).not_null())
+                    .col(string(BranchAliases::Alias).not_null())
+                    .col(uuid(BranchAliases::BranchId).not_null())
+                    .col(timestamp_with_time_zone(BranchAliases::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(BranchAliases::Table, BranchAliases::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(BranchAliases::Table, BranchAliases::BranchId)
+                            .to(Branches::Table, Branches::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_branch_aliases_project_alias")
+                    .table(BranchAliases::Table)
+                    .col(BranchAliases::ProjectId)
+                    .col(BranchAliases::Alias)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BranchAliases::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Projects::Table)
+                    .drop_column(Projects::DefaultBranch)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+    DefaultBranch,
+}
+
+#[derive(DeriveIden)]
+enum Branches {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum BranchAliases {
+    Table,
+    Id,
+    ProjectId,
+    Alias,
+    BranchId,
+    CreatedAt,
+}
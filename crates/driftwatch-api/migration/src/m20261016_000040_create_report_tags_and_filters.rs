@@ -0,0 +1,117 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportTags::Table)
+                    .if_not_exists()
+                    .col(uuid(ReportTags::ReportId).not_null())
+                    .col(string(ReportTags::Tag).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ReportTags::ReportId)
+                            .col(ReportTags::Tag),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ReportTags::Table, ReportTags::ReportId)
+                            .to(Reports::Table, Reports::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_report_tags_tag")
+                    .table(ReportTags::Table)
+                    .col(ReportTags::Tag)
+                    .col(ReportTags::ReportId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Named combinations of tags and context, e.g. "nightlies"
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReportFilters::Table)
+                    .if_not_exists()
+                    .col(uuid(ReportFilters::Id).primary_key())
+                    .col(uuid(ReportFilters::ProjectId).not_null())
+                    .col(string(ReportFilters::Name).not_null())
+                    .col(text(ReportFilters::Tags).not_null())
+                    .col(json_binary(ReportFilters::Context).not_null())
+                    .col(timestamp_with_time_zone(ReportFilters::CreatedAt).not_null())
+                    .col(timestamp_with_time_zone(ReportFilters::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ReportFilters::Table, ReportFilters::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_report_filters_project_name")
+                    .table(ReportFilters::Table)
+                    .col(ReportFilters::ProjectId)
+                    .col(ReportFilters::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReportFilters::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ReportTags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ReportTags {
+    Table,
+    ReportId,
+    Tag,
+}
+
+#[derive(DeriveIden)]
+enum ReportFilters {
+    Table,
+    Id,
+    ProjectId,
+    Name,
+    Tags,
+    Context,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
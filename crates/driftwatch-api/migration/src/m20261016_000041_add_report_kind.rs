@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::backfill;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // What triggered the run: `pr`, `push`, `nightly`, `release` or `manual`
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .add_column_if_not_exists(string(Reports::Kind).default("manual"))
+                    .to_owned(),
+            )
+            .await?;
+        // Reports from before kinds existed: a PR number or release tag says
+        // what most of them were
+        backfill::schedule(
+            manager,
+            "reports_kind",
+            "UPDATE reports \
+             SET kind = CASE WHEN pr_number IS NOT NULL THEN 'pr' ELSE 'release' END \
+             WHERE id IN (SELECT id FROM reports WHERE kind = 'manual' \
+             AND (pr_number IS NOT NULL OR version IS NOT NULL) LIMIT 1000)",
+        )
+        .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        backfill::unschedule(manager, "reports_kind").await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .drop_column(Reports::Kind)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Kind,
+}
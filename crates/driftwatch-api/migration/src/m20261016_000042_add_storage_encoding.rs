@@ -0,0 +1,79 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // How `content` is compressed: `identity`, `gzip` or `zstd`. Existing
+        // rows keep their encoding until the storage cleanup recompresses them
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Blobs::Table)
+                    .add_column_if_not_exists(string(Blobs::Encoding).default("identity"))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ReportOutputs::Table)
+                    .add_column_if_not_exists(string(ReportOutputs::Encoding).default("gzip"))
+                    .add_column_if_not_exists(big_integer_null(ReportOutputs::Size))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Recompressed rows can't be read by the previous version
+        let db = manager.get_connection();
+        for table in ["blobs", "report_outputs"] {
+            let recompressed = db
+                .query_one(sea_orm::Statement::from_string(
+                    manager.get_database_backend(),
+                    format!("SELECT 1 FROM {} WHERE encoding = 'zstd' LIMIT 1", table),
+                ))
+                .await?;
+            if recompressed.is_some() {
+                return Err(DbErr::Migration(format!(
+                    "{} holds zstd-compressed content; restore it before rolling back",
+                    table
+                )));
+            }
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ReportOutputs::Table)
+                    .drop_column(ReportOutputs::Encoding)
+                    .drop_column(ReportOutputs::Size)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Blobs::Table)
+                    .drop_column(Blobs::Encoding)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Blobs {
+    Table,
+    Encoding,
+}
+
+#[derive(DeriveIden)]
+enum ReportOutputs {
+    Table,
+    Encoding,
+    Size,
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The suite manifest a report was submitted with
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExpectedBenchmarks::Table)
+                    .if_not_exists()
+                    .col(uuid(ExpectedBenchmarks::ReportId).not_null())
+                    .col(string(ExpectedBenchmarks::Name).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(ExpectedBenchmarks::ReportId)
+                            .col(ExpectedBenchmarks::Name),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ExpectedBenchmarks::Table, ExpectedBenchmarks::ReportId)
+                            .to(Reports::Table, Reports::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ExpectedBenchmarks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ExpectedBenchmarks {
+    Table,
+    ReportId,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum Reports {
+    Table,
+    Id,
+}
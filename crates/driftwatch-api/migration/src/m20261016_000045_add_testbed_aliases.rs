@@ -0,0 +1,78 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Other names a testbed is submitted under, e.g. `linux-x86_64` once
+        // the CLI's default testbed includes the architecture, so the
+        // machine's history stays in one series
+        manager
+            .create_table(
+                Table::create()
+                    .table(TestbedAliases::Table)
+                    .if_not_exists()
+                    .col(uuid(TestbedAliases::Id).primary_key())
+                    .col(uuid(TestbedAliases::ProjectId).not_null())
+                    .col(string(TestbedAliases::Alias).not_null())
+                    .col(uuid(TestbedAliases::TestbedId).not_null())
+                    .col(timestamp_with_time_zone(TestbedAliases::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TestbedAliases::Table, TestbedAliases::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(TestbedAliases::Table, TestbedAliases::TestbedId)
+                            .to(Testbeds::Table, Testbeds::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_testbed_aliases_project_alias")
+                    .table(TestbedAliases::Table)
+                    .col(TestbedAliases::ProjectId)
+                    .col(TestbedAliases::Alias)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TestbedAliases::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Projects {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Testbeds {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum TestbedAliases {
+    Table,
+    Id,
+    ProjectId,
+    Alias,
+    TestbedId,
+    CreatedAt,
+}
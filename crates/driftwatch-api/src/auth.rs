@@ -11,6 +11,7 @@ pub struct AuthUser {
     pub session: Option<Session>,
     pub api_key: Option<ApiKey>,
     pub token: String,
+    pub is_admin: bool,
 }
 
 impl AuthUser {
@@ -21,11 +22,27 @@ impl AuthUser {
     pub fn is_session_auth(&self) -> bool {
         self.session.is_some()
     }
+
+    pub fn require_admin(&self) -> Result<(), &'static str> {
+        if self.is_admin {
+            Ok(())
+        } else {
+            Err("Admin access required")
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct AuthError(pub String);
 
+/// The token from an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
 pub async fn validate_token(token: &str, auth: &Arc<TsaAuth>) -> Result<AuthUser, AuthError> {
     if let Ok((user, session)) = auth.validate_session(token).await {
         return Ok(AuthUser {
@@ -33,6 +50,7 @@ pub async fn validate_token(token: &str, auth: &Arc<TsaAuth>) -> Result<AuthUser
             session: Some(session),
             api_key: None,
             token: token.to_string(),
+            is_admin: false,
         });
     }
 
@@ -42,6 +60,7 @@ pub async fn validate_token(token: &str, auth: &Arc<TsaAuth>) -> Result<AuthUser
             session: None,
             api_key: Some(api_key),
             token: token.to_string(),
+            is_admin: false,
         });
     }
 
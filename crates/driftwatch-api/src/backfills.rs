@@ -0,0 +1,176 @@
+//! Runs the backfills migrations schedule with `migration::backfill`.
+//!
+//! One job at a time takes the oldest unfinished backfill and runs a single
+//! batch of it in its own transaction, then queues the next job. A batch
+//! that changes no rows marks the backfill complete. A failing batch is
+//! recorded on the backfill and retried by the job queue, and once that
+//! gives up, by the next job the scheduler queues.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, FromQueryResult, Statement,
+    TransactionTrait,
+};
+
+use crate::error_reports;
+use crate::jobs::{self, JobRegistry};
+
+/// Job kind that runs one batch of the oldest unfinished backfill.
+pub const BACKFILL_JOB: &str = "run_backfill";
+
+/// How often unfinished backfills are checked for a queued job
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Job kind that queues a backfill job when one is unfinished
+pub const SCHEDULE_BACKFILLS_JOB: &str = "schedule_backfills";
+
+/// A backfill and how far it has got
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct Backfill {
+    pub name: String,
+    pub rows_done: i64,
+    pub batches: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub completed_at: Option<DateTimeWithTimeZone>,
+}
+
+/// Backfills that haven't finished, oldest first. Empty until the
+/// migration that creates the table has run.
+pub async fn unfinished<C: ConnectionTrait>(db: &C) -> Result<Vec<Backfill>, DbErr> {
+    let exists = db
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT to_regclass('data_backfills') IS NOT NULL AS found",
+        ))
+        .await?
+        .map(|row| row.try_get::<bool>("", "found"))
+        .transpose()?
+        .unwrap_or(false);
+    if !exists {
+        return Ok(Vec::new());
+    }
+    Backfill::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        r#"SELECT name, rows_done, batches, last_error, created_at, completed_at
+           FROM data_backfills
+           WHERE completed_at IS NULL
+           ORDER BY created_at, name"#,
+    ))
+    .all(db)
+    .await
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Claimed {
+    name: String,
+    batch_sql: String,
+}
+
+/// Runs one batch of the oldest unfinished backfill. Returns its name and
+/// the rows the batch changed, or `None` when nothing is left to run.
+pub async fn run_batch(db: &DatabaseConnection) -> Result<Option<(String, u64)>> {
+    let txn = db.begin().await?;
+    let Some(claimed) = Claimed::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        r#"SELECT name, batch_sql FROM data_backfills
+           WHERE completed_at IS NULL
+           ORDER BY created_at, name
+           LIMIT 1
+           FOR UPDATE SKIP LOCKED"#,
+    ))
+    .one(&txn)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let rows = match txn
+        .execute(Statement::from_string(
+            DbBackend::Postgres,
+            claimed.batch_sql.clone(),
+        ))
+        .await
+    {
+        Ok(result) => result.rows_affected(),
+        Err(e) => {
+            txn.rollback().await?;
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "UPDATE data_backfills SET last_error = $2 WHERE name = $1",
+                [claimed.name.clone().into(), e.to_string().into()],
+            ))
+            .await?;
+            return Err(anyhow::anyhow!("Backfill {} failed: {}", claimed.name, e));
+        }
+    };
+
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"UPDATE data_backfills
+           SET rows_done = rows_done + $2,
+               batches = batches + 1,
+               last_error = NULL,
+               completed_at = CASE WHEN $2 = 0 THEN NOW() END
+           WHERE name = $1"#,
+        [claimed.name.clone().into(), (rows as i64).into()],
+    ))
+    .await?;
+    txn.commit().await?;
+    Ok(Some((claimed.name, rows)))
+}
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(BACKFILL_JOB, |db, _payload| async move {
+        let Some((name, rows)) = run_batch(&db).await? else {
+            return Ok(());
+        };
+        if rows == 0 {
+            tracing::info!("Backfill {} complete", name);
+        }
+        // Keep going while this or another backfill has work left
+        jobs::enqueue(&db, BACKFILL_JOB, serde_json::json!({})).await?;
+        Ok(())
+    });
+
+    registry.schedule(SCHEDULE_BACKFILLS_JOB, SCHEDULE_INTERVAL, |db| async move {
+        if let Err(e) = schedule(&db).await {
+            tracing::error!("Backfill scheduling failed: {}", e);
+            error_reports::capture(&e.into(), "backfills", &[]);
+        }
+        Ok(())
+    });
+}
+
+#[derive(Debug, FromQueryResult)]
+struct QueueState {
+    unfinished: bool,
+    scheduled: bool,
+}
+
+/// Queues a backfill job when a backfill is unfinished and none is
+/// scheduled. Returns whether it queued one.
+pub async fn schedule<C: ConnectionTrait>(db: &C) -> Result<bool, DbErr> {
+    let state = QueueState::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT EXISTS (SELECT 1 FROM data_backfills WHERE completed_at IS NULL) AS unfinished,
+                  EXISTS (SELECT 1 FROM jobs
+                          WHERE kind = $1 AND status IN ('pending', 'running')) AS scheduled"#,
+        [BACKFILL_JOB.into()],
+    ))
+    .one(db)
+    .await?;
+    match state {
+        Some(QueueState {
+            unfinished: true,
+            scheduled: false,
+        }) => {
+            jobs::enqueue(db, BACKFILL_JOB, serde_json::json!({})).await?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
@@ -0,0 +1,218 @@
+//! Load shedding for report ingestion.
+//!
+//! When every database connection is busy, or the job queue has fallen too
+//! far behind, ingestion mutations are refused up front with an
+//! `OVERLOADED` error before they touch anything, and the HTTP response
+//! becomes `429 Too Many Requests` with `Retry-After`. Clients can retry the
+//! same request safely, rather than hanging until their own timeouts while
+//! the server works through a backlog.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_graphql::{Context, ErrorExtensions, Response, Value};
+use axum::http::{header, HeaderValue, StatusCode};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter};
+
+use crate::entities::{self, job};
+
+/// GraphQL error code for refused requests
+pub const OVERLOADED: &str = "OVERLOADED";
+
+/// How often the job backlog is counted
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Suggested wait when the connection pool is exhausted; connections free
+/// up quickly
+const POOL_RETRY_AFTER_SECS: u64 = 5;
+
+/// Suggested wait when the job queue is behind; it drains more slowly
+const QUEUE_RETRY_AFTER_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overload {
+    pub reason: &'static str,
+    pub retry_after_secs: u64,
+}
+
+impl fmt::Display for Overload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}; retry in {} seconds",
+            self.reason, self.retry_after_secs
+        )
+    }
+}
+
+/// Shared saturation state, handed to resolvers as GraphQL data
+#[derive(Clone)]
+pub struct Backpressure {
+    db: Option<DatabaseConnection>,
+    /// Due jobs above which ingestion is refused; 0 disables the check
+    max_pending_jobs: u64,
+    pending_jobs: Arc<AtomicU64>,
+}
+
+impl Backpressure {
+    pub fn new(db: DatabaseConnection, max_pending_jobs: u64) -> Self {
+        Self {
+            db: Some(db),
+            max_pending_jobs,
+            pending_jobs: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Never refuses anything
+    pub fn disabled() -> Self {
+        Self {
+            db: None,
+            max_pending_jobs: 0,
+            pending_jobs: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn pool_exhausted(&self) -> bool {
+        let Some(db) = &self.db else {
+            return false;
+        };
+        let pool = db.get_postgres_connection_pool();
+        pool.num_idle() == 0 && pool.size() >= pool.options().get_max_connections()
+    }
+
+    /// Why new ingestion should wait, or `None` when the server has room
+    pub fn check(&self) -> Option<Overload> {
+        if self.pool_exhausted() {
+            return Some(Overload {
+                reason: "All database connections are busy",
+                retry_after_secs: POOL_RETRY_AFTER_SECS,
+            });
+        }
+        if self.max_pending_jobs > 0
+            && self.pending_jobs.load(Ordering::Relaxed) > self.max_pending_jobs
+        {
+            return Some(Overload {
+                reason: "The job queue is backlogged",
+                retry_after_secs: QUEUE_RETRY_AFTER_SECS,
+            });
+        }
+        None
+    }
+
+    /// Counts due jobs in the background, so checks don't query the database
+    pub fn spawn(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let db = self.db.clone().filter(|_| self.max_pending_jobs > 0)?;
+        let pending_jobs = self.pending_jobs.clone();
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                let now = chrono::Utc::now().fixed_offset();
+                match entities::Job::find()
+                    .filter(job::Column::Status.eq(job::JobStatus::Pending))
+                    .filter(job::Column::RunAt.lte(now))
+                    .count(&db)
+                    .await
+                {
+                    Ok(count) => pending_jobs.store(count, Ordering::Relaxed),
+                    Err(e) => tracing::warn!("Failed to count pending jobs: {}", e),
+                }
+            }
+        }))
+    }
+}
+
+/// Refuses an ingestion mutation while the server is saturated. Call it
+/// before doing any work, so a refused request can simply be retried.
+pub fn admit(ctx: &Context<'_>) -> async_graphql::Result<()> {
+    let Some(overload) = ctx.data_opt::<Backpressure>().and_then(|b| b.check()) else {
+        return Ok(());
+    };
+    tracing::warn!("Refusing ingestion: {}", overload.reason);
+    Err(
+        async_graphql::Error::new(overload.to_string()).extend_with(|_, e| {
+            e.set("code", OVERLOADED);
+            e.set("retryAfter", overload.retry_after_secs);
+        }),
+    )
+}
+
+/// Seconds to put in `Retry-After` when the response holds an `OVERLOADED`
+/// error, or `None`
+pub fn retry_after(response: &Response) -> Option<u64> {
+    response
+        .errors
+        .iter()
+        .filter_map(|e| e.extensions.as_ref())
+        .filter(|ext| ext.get("code") == Some(&Value::from(OVERLOADED)))
+        .filter_map(|ext| match ext.get("retryAfter") {
+            Some(Value::Number(n)) => n.as_u64(),
+            _ => None,
+        })
+        .max()
+}
+
+/// Turns a response carrying an `OVERLOADED` error into a 429
+pub fn throttle(http: &mut axum::response::Response, retry_after_secs: Option<u64>) {
+    if let Some(secs) = retry_after_secs {
+        *http.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+        http.headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn ingest(&self, ctx: &Context<'_>) -> async_graphql::Result<bool> {
+            admit(ctx)?;
+            Ok(true)
+        }
+    }
+
+    fn backlogged(pending: u64, max: u64) -> Backpressure {
+        let backpressure = Backpressure {
+            max_pending_jobs: max,
+            ..Backpressure::disabled()
+        };
+        backpressure.pending_jobs.store(pending, Ordering::Relaxed);
+        backpressure
+    }
+
+    #[test]
+    fn test_check_job_backlog() {
+        assert_eq!(backlogged(100, 100).check(), None);
+        assert_eq!(
+            backlogged(101, 100).check().map(|o| o.retry_after_secs),
+            Some(QUEUE_RETRY_AFTER_SECS)
+        );
+        // 0 turns the check off
+        assert_eq!(backlogged(1_000_000, 0).check(), None);
+    }
+
+    #[tokio::test]
+    async fn test_refused_requests_carry_retry_after() {
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription).finish();
+
+        let response = schema
+            .execute(async_graphql::Request::new("{ ingest }").data(backlogged(0, 10)))
+            .await;
+        assert!(response.errors.is_empty());
+        assert_eq!(retry_after(&response), None);
+
+        let response = schema
+            .execute(async_graphql::Request::new("{ ingest }").data(backlogged(11, 10)))
+            .await;
+        assert_eq!(retry_after(&response), Some(QUEUE_RETRY_AFTER_SECS));
+        let extensions = response.errors[0].extensions.as_ref().unwrap();
+        assert_eq!(extensions.get("code"), Some(&Value::from(OVERLOADED)));
+    }
+}
@@ -1,12 +1,24 @@
 use moka::future::Cache;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
 use std::time::Duration;
 use uuid::Uuid;
 
+use crate::entities::{self, project, report};
+
+/// Number of most recent reports kept per project; larger requests go to the database.
+pub const LATEST_REPORTS_CACHED: usize = 100;
+
 #[derive(Clone)]
 pub struct AppCache {
     pub projects: Cache<String, String>,
     pub project: Cache<String, String>,
     pub tokens: Cache<String, String>,
+    /// Project rows keyed by owner and slug, resolved on every write
+    pub project_models: Cache<String, project::Model>,
+    /// Most recent reports per project, newest first
+    pub latest_reports: Cache<Uuid, Vec<report::Model>>,
 }
 
 impl AppCache {
@@ -24,6 +36,14 @@ impl AppCache {
                 .time_to_live(Duration::from_secs(300))
                 .max_capacity(1000)
                 .build(),
+            project_models: Cache::builder()
+                .time_to_live(Duration::from_secs(300))
+                .max_capacity(5000)
+                .build(),
+            latest_reports: Cache::builder()
+                .time_to_live(Duration::from_secs(60))
+                .max_capacity(1000)
+                .build(),
         }
     }
 
@@ -37,9 +57,78 @@ impl AppCache {
         self.project
             .invalidate(&format!("user:{}:project:{}", user_id, slug))
             .await;
+        self.project_models
+            .invalidate(&format!("user:{}:project:{}", user_id, slug))
+            .await;
         self.invalidate_user_projects(user_id).await;
     }
 
+    pub async fn invalidate_latest_reports(&self, project_id: Uuid) {
+        self.latest_reports.invalidate(&project_id).await;
+    }
+
+    /// Looks up a user's project by slug, going to the database on a miss.
+    pub async fn resolve_project<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        user_id: Uuid,
+        slug: &str,
+    ) -> Result<Option<project::Model>, DbErr> {
+        let key = format!("user:{}:project:{}", user_id, slug);
+        if let Some(project) = self.project_models.get(&key).await {
+            return Ok(Some(project));
+        }
+
+        let project = entities::Project::find()
+            .filter(project::Column::UserId.eq(user_id))
+            .filter(project::Column::Slug.eq(slug))
+            .one(db)
+            .await?;
+
+        if let Some(ref project) = project {
+            self.project_models.insert(key, project.clone()).await;
+        }
+        Ok(project)
+    }
+
+    /// Returns up to `limit` of the project's newest reports. Requests beyond
+    /// [`LATEST_REPORTS_CACHED`] bypass the cache.
+    pub async fn latest_reports<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        project_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<report::Model>, DbErr> {
+        if limit > LATEST_REPORTS_CACHED {
+            return entities::Report::find()
+                .filter(report::Column::ProjectId.eq(project_id))
+                .filter(report::Column::Finalized.eq(true))
+                .order_by_desc(report::Column::CreatedAt)
+                .limit(limit as u64)
+                .all(db)
+                .await;
+        }
+
+        let reports = match self.latest_reports.get(&project_id).await {
+            Some(reports) => reports,
+            None => {
+                let reports = entities::Report::find()
+                    .filter(report::Column::ProjectId.eq(project_id))
+                    .filter(report::Column::Finalized.eq(true))
+                    .order_by_desc(report::Column::CreatedAt)
+                    .limit(LATEST_REPORTS_CACHED as u64)
+                    .all(db)
+                    .await?;
+                self.latest_reports
+                    .insert(project_id, reports.clone())
+                    .await;
+                reports
+            }
+        };
+
+        Ok(reports.into_iter().take(limit).collect())
+    }
+
     pub async fn invalidate_user_tokens(&self, user_id: Uuid) {
         self.tokens
             .invalidate(&format!("user:{}:tokens", user_id))
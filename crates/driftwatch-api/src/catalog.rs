@@ -0,0 +1,191 @@
+//! The catalog of public projects, for anyone to browse without signing in.
+//! Stats come from `metric_summaries`, so listing stays cheap however much
+//! history a project has.
+
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement, Value};
+use uuid::Uuid;
+
+use crate::search::{like_pattern, BenchmarkMatch};
+
+/// A public project with a summary of what it tracks.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct PublicProject {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub github_repo: Option<String>,
+    pub benchmarks: i64,
+    pub series: i64,
+    pub reports: i64,
+    pub last_report_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// The order projects are listed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogSort {
+    /// Most recently reported first; projects without reports last
+    Recent,
+    /// Most benchmarks first
+    Benchmarks,
+    Name,
+}
+
+impl CatalogSort {
+    fn order_by(self) -> &'static str {
+        match self {
+            CatalogSort::Recent => "last_report_at DESC NULLS LAST, p.name",
+            CatalogSort::Benchmarks => "benchmarks DESC, p.name",
+            CatalogSort::Name => "p.name, p.slug",
+        }
+    }
+}
+
+const SELECT_PUBLIC_PROJECTS: &str = r#"
+    SELECT p.id, p.slug, p.name, p.description, p.github_repo,
+           COALESCE(s.benchmarks, 0) AS benchmarks,
+           COALESCE(s.series, 0) AS series,
+           (SELECT COUNT(*) FROM reports r WHERE r.project_id = p.id AND r.finalized)
+               AS reports,
+           s.last_report_at, p.created_at
+    FROM projects p
+    LEFT JOIN LATERAL (
+        SELECT COUNT(DISTINCT benchmark_id) AS benchmarks, COUNT(*) AS series,
+               MAX(latest_at) AS last_report_at
+        FROM metric_summaries
+        WHERE project_id = p.id
+    ) s ON true
+    WHERE p.public"#;
+
+/// `AND ...` narrowing the catalog to projects whose name, slug or
+/// description contains `search`, bound as parameter `$n`
+fn search_filter(search: Option<&str>, n: usize, values: &mut Vec<Value>) -> String {
+    match search.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(search) => {
+            values.push(like_pattern(search).into());
+            format!(
+                " AND (p.name ILIKE ${n} OR p.slug ILIKE ${n} OR p.description ILIKE ${n})",
+                n = n
+            )
+        }
+        None => String::new(),
+    }
+}
+
+/// A page of the catalog, optionally narrowed by `search`.
+pub async fn public_projects<C: ConnectionTrait>(
+    db: &C,
+    search: Option<&str>,
+    sort: CatalogSort,
+    limit: u64,
+    offset: u64,
+) -> Result<Vec<PublicProject>, DbErr> {
+    let mut values = Vec::new();
+    let filter = search_filter(search, 1, &mut values);
+    let sql = format!(
+        "{}{} ORDER BY {} LIMIT ${} OFFSET ${}",
+        SELECT_PUBLIC_PROJECTS,
+        filter,
+        sort.order_by(),
+        values.len() + 1,
+        values.len() + 2
+    );
+    values.push((limit as i64).into());
+    values.push((offset as i64).into());
+    PublicProject::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        values,
+    ))
+    .all(db)
+    .await
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+/// How many public projects match `search`, for paging.
+pub async fn count_public_projects<C: ConnectionTrait>(
+    db: &C,
+    search: Option<&str>,
+) -> Result<i64, DbErr> {
+    let mut values = Vec::new();
+    let filter = search_filter(search, 1, &mut values);
+    let count = Count::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            "SELECT COUNT(*) AS count FROM projects p WHERE p.public{}",
+            filter
+        ),
+        values,
+    ))
+    .one(db)
+    .await?;
+    Ok(count.map_or(0, |c| c.count))
+}
+
+/// One public project, or `None` when it doesn't exist or isn't public.
+pub async fn public_project<C: ConnectionTrait>(
+    db: &C,
+    id: Uuid,
+) -> Result<Option<PublicProject>, DbErr> {
+    PublicProject::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!("{} AND p.id = $1", SELECT_PUBLIC_PROJECTS),
+        [id.into()],
+    ))
+    .one(db)
+    .await
+}
+
+/// The most recently updated series of a public project with their latest
+/// values.
+pub async fn latest_results<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    limit: u64,
+) -> Result<Vec<BenchmarkMatch>, DbErr> {
+    BenchmarkMatch::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT p.slug AS project_slug, p.name AS project_name,
+                  b.name AS benchmark, ms.name AS measure,
+                  br.name AS branch, t.name AS testbed,
+                  s.latest_value, s.latest_at
+           FROM metric_summaries s
+           JOIN projects p ON p.id = s.project_id
+           JOIN benchmarks b ON b.id = s.benchmark_id
+           JOIN measures ms ON ms.id = s.measure_id
+           JOIN branches br ON br.id = s.branch_id
+           JOIN testbeds t ON t.id = s.testbed_id
+           WHERE s.project_id = $1 AND p.public
+           ORDER BY s.latest_at DESC, b.name
+           LIMIT $2"#,
+        [project_id.into(), (limit as i64).into()],
+    ))
+    .all(db)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_filter_binds_a_literal_pattern() {
+        let mut values = Vec::new();
+        assert_eq!(search_filter(None, 1, &mut values), "");
+        assert_eq!(search_filter(Some("  "), 1, &mut values), "");
+        assert!(values.is_empty());
+
+        let filter = search_filter(Some(" 100%"), 2, &mut values);
+        assert_eq!(
+            filter,
+            " AND (p.name ILIKE $2 OR p.slug ILIKE $2 OR p.description ILIKE $2)"
+        );
+        assert_eq!(values, vec![Value::from("%100\\%%".to_string())]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Gzip for responses. GraphQL results, metric series and exports are
+//! verbose JSON or text that shrinks several times over, which matters
+//! most to CLIs on slow links. Only bodies already held in memory are
+//! compressed, so streamed responses keep streaming.
+//!
+//! Turn it off with `HTTP_COMPRESSION=false` when a proxy in front of the
+//! API compresses responses itself.
+
+use axum::body::{to_bytes, Body, HttpBody};
+use axum::extract::Request;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+pub use driftwatch_protocol::compression::{gunzip, gzip};
+
+/// Smaller bodies fit in a packet or two either way
+pub const MIN_COMPRESSED_BYTES: u64 = 1024;
+
+/// Whether the client listed gzip in `Accept-Encoding` without refusing it
+/// with `q=0`
+pub fn accepts_gzip(headers: &HeaderMap) -> bool {
+    accepts_encoding(headers, "gzip")
+}
+
+/// Whether the client listed `coding` in `Accept-Encoding`, or `*`, without
+/// refusing it with `q=0`
+pub fn accepts_encoding(headers: &HeaderMap, coding: &str) -> bool {
+    let codings: Vec<(String, bool)> = headers
+        .get_all(header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            let name = params.next().unwrap_or_default().to_ascii_lowercase();
+            let refused = params.any(|p| {
+                p.strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .is_some_and(|q| q == 0.0)
+            });
+            (name, !refused)
+        })
+        .collect();
+    // An explicit entry for the coding wins over `*`
+    codings
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(coding))
+        .or_else(|| codings.iter().find(|(name, _)| name == "*"))
+        .is_some_and(|&(_, accepted)| accepted)
+}
+
+/// Text formats worth compressing; images and archives already are
+fn is_compressible(content_type: &str) -> bool {
+    let content_type = content_type.to_ascii_lowercase();
+    content_type.starts_with("text/")
+        || ["json", "xml", "javascript", "openmetrics-text"]
+            .iter()
+            .any(|kind| content_type.contains(kind))
+}
+
+/// Compresses text responses of a known size for clients that accept gzip
+pub async fn middleware(request: Request, next: Next) -> Response {
+    let wants_gzip = accepts_gzip(request.headers());
+    let response = next.run(request).await;
+
+    let (mut parts, body) = response.into_parts();
+    let compressible = !parts.headers.contains_key(header::CONTENT_ENCODING)
+        && parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(is_compressible)
+        && body
+            .size_hint()
+            .exact()
+            .is_some_and(|size| size >= MIN_COMPRESSED_BYTES);
+    if !compressible {
+        return Response::from_parts(parts, body);
+    }
+    parts.headers.append(
+        header::VARY,
+        HeaderValue::from_static(header::ACCEPT_ENCODING.as_str()),
+    );
+    if !wants_gzip {
+        return Response::from_parts(parts, body);
+    }
+
+    let compressed = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => tokio::task::spawn_blocking(move || gzip(&bytes)).await,
+        Err(e) => {
+            tracing::error!("Failed to read response body for compression: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    match compressed {
+        Ok(Ok(compressed)) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts
+                .headers
+                .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            Response::from_parts(parts, Body::from(compressed))
+        }
+        Ok(Err(e)) => {
+            tracing::error!("Failed to compress response: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+        Err(e) => {
+            tracing::error!("Response compression task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT_ENCODING,
+            HeaderValue::from_str(value).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        assert!(accepts_gzip(&accept("gzip")));
+        assert!(accepts_gzip(&accept("br, gzip;q=0.8")));
+        assert!(accepts_gzip(&accept("*")));
+        assert!(!accepts_gzip(&accept("gzip;q=0")));
+        assert!(!accepts_gzip(&accept("*, gzip;q=0")));
+        assert!(!accepts_gzip(&accept("br, identity")));
+        assert!(!accepts_gzip(&HeaderMap::new()));
+        assert!(accepts_encoding(&accept("gzip, br, zstd"), "zstd"));
+        assert!(!accepts_encoding(&accept("gzip"), "zstd"));
+    }
+
+    #[tokio::test]
+    async fn test_compresses_large_text_responses() {
+        let app = Router::new()
+            .route(
+                "/large",
+                get(|| async { axum::Json(vec!["driftwatch"; 500]) }),
+            )
+            .route("/small", get(|| async { axum::Json("ok") }))
+            .layer(axum::middleware::from_fn(middleware));
+        let request = |path: &str, encoding: &str| {
+            Request::get(path)
+                .header(header::ACCEPT_ENCODING, encoding)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("/large", "gzip"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: Vec<String> = serde_json::from_slice(&gunzip(&body).unwrap()).unwrap();
+        assert_eq!(json.len(), 500);
+
+        let response = app
+            .clone()
+            .oneshot(request("/large", "identity"))
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+
+        let response = app.oneshot(request("/small", "gzip")).await.unwrap();
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+}
@@ -1,17 +1,89 @@
 use std::env;
 
+use crate::events::EventBus;
+use crate::export::ExportSink;
+use crate::http::HttpSecurity;
+use crate::shares::ShareSettings;
+
 #[derive(Clone)]
 pub struct Config {
     pub database_url: String,
+    /// Optional replica that serves heavy read-only queries
+    pub database_read_url: Option<String>,
+    pub db_max_connections: u32,
+    pub db_min_connections: u32,
+    pub db_connect_timeout_secs: u64,
+    pub db_acquire_timeout_secs: u64,
+    pub db_idle_timeout_secs: u64,
     pub port: u16,
     pub grpc_port: u16,
     pub rust_log: String,
+    /// Store metrics in a TimescaleDB hypertable; see `migrations::convert_metrics_to_hypertable`
+    pub metrics_hypertable: bool,
+    /// Apply pending migrations when `serve` starts; with
+    /// `AUTO_MIGRATE=false` they're left to `driftwatch migrate up` and
+    /// `serve` refuses to start until they're applied
+    pub auto_migrate: bool,
+    /// Number of background job workers started by `serve`
+    pub job_workers: usize,
+    /// Due jobs above which ingestion is refused with 429; 0 disables
+    pub max_pending_jobs: u64,
+    /// Users allowed to run admin queries, from the comma-separated `ADMIN_EMAILS`
+    pub admin_emails: Vec<String>,
+    /// Base64 master key that encrypts stored secrets, from
+    /// `DRIFTWATCH_SECRET_KEY` or the file named by `DRIFTWATCH_SECRET_KEY_FILE`
+    pub secret_key: Option<String>,
+    /// CORS origins and security headers for the HTTP server
+    pub http_security: HttpSecurity,
+    /// Gzip responses for clients that ask; turn off with
+    /// `HTTP_COMPRESSION=false` when a proxy already compresses them
+    pub http_compression: bool,
+    /// Where to report panics and background failures; off when unset
+    pub sentry_dsn: Option<String>,
+    pub sentry_environment: Option<String>,
+    /// Address of the web UI, linked from GitHub issues and commit statuses
+    pub web_url: Option<String>,
+    /// Answer introspection queries and serve GraphiQL; turn off in
+    /// production with `GRAPHQL_INTROSPECTION=false`
+    pub graphql_introspection: bool,
+    /// Analytical store that evaluated reports are copied to; off when
+    /// `EXPORT_SINK` is unset
+    pub export_sink: Option<ExportSink>,
+    /// Message bus that report and alert events are published to; off
+    /// when `EVENT_BUS` is unset
+    pub event_bus: Option<EventBus>,
+    /// Accept reports without an account and serve them at a secret URL
+    /// for a while; off unless `ANONYMOUS_SHARES=true`
+    pub anonymous_shares: Option<ShareSettings>,
 }
 
 impl Config {
     pub fn from_env() -> Self {
         Self {
             database_url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+            database_read_url: env::var("DATABASE_READ_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            db_max_connections: env::var("DB_MAX_CONNECTIONS")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .expect("DB_MAX_CONNECTIONS must be a valid number"),
+            db_min_connections: env::var("DB_MIN_CONNECTIONS")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .expect("DB_MIN_CONNECTIONS must be a valid number"),
+            db_connect_timeout_secs: env::var("DB_CONNECT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .expect("DB_CONNECT_TIMEOUT_SECS must be a valid number"),
+            db_acquire_timeout_secs: env::var("DB_ACQUIRE_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .expect("DB_ACQUIRE_TIMEOUT_SECS must be a valid number"),
+            db_idle_timeout_secs: env::var("DB_IDLE_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .expect("DB_IDLE_TIMEOUT_SECS must be a valid number"),
             port: env::var("PORT")
                 .unwrap_or_else(|_| "4000".to_string())
                 .parse()
@@ -21,6 +93,51 @@ impl Config {
                 .parse()
                 .expect("GRPC_PORT must be a valid number"),
             rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+            metrics_hypertable: env::var("METRICS_HYPERTABLE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            auto_migrate: env::var("AUTO_MIGRATE")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            job_workers: env::var("JOB_WORKERS")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .expect("JOB_WORKERS must be a valid number"),
+            max_pending_jobs: env::var("MAX_PENDING_JOBS")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .expect("MAX_PENDING_JOBS must be a valid number"),
+            admin_emails: env::var("ADMIN_EMAILS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|e| e.trim().to_lowercase())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            secret_key: env::var("DRIFTWATCH_SECRET_KEY")
+                .ok()
+                .or_else(|| {
+                    env::var("DRIFTWATCH_SECRET_KEY_FILE").ok().map(|path| {
+                        std::fs::read_to_string(&path)
+                            .unwrap_or_else(|e| panic!("Failed to read {}: {}", path, e))
+                    })
+                })
+                .map(|key| key.trim().to_string())
+                .filter(|key| !key.is_empty()),
+            http_security: HttpSecurity::from_env(),
+            http_compression: env::var("HTTP_COMPRESSION")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            sentry_dsn: env::var("SENTRY_DSN").ok().filter(|dsn| !dsn.is_empty()),
+            sentry_environment: env::var("SENTRY_ENVIRONMENT")
+                .ok()
+                .filter(|env| !env.is_empty()),
+            web_url: env::var("WEB_URL").ok().filter(|url| !url.is_empty()),
+            graphql_introspection: env::var("GRAPHQL_INTROSPECTION")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            export_sink: ExportSink::from_env(),
+            event_bus: EventBus::from_env(),
+            anonymous_shares: ShareSettings::from_env(),
         }
     }
 }
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement};
+use uuid::Uuid;
+
+/// Most context entries accepted on one report.
+pub const MAX_CONTEXT_ENTRIES: usize = 32;
+
+const MAX_KEY_LEN: usize = 64;
+const MAX_VALUE_LEN: usize = 256;
+
+/// Checks and trims report context. Keys are limited to letters, digits and
+/// `_ . -` so they stay usable as filter and split keys.
+pub fn validate_context(entries: Vec<(String, String)>) -> Result<Vec<(String, String)>, String> {
+    if entries.len() > MAX_CONTEXT_ENTRIES {
+        return Err(format!(
+            "Reports can carry at most {} context entries",
+            MAX_CONTEXT_ENTRIES
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    let mut validated = Vec::with_capacity(entries.len());
+
+    for (key, value) in entries {
+        let key = key.trim().to_string();
+        let value = value.trim().to_string();
+
+        if key.is_empty() || key.len() > MAX_KEY_LEN {
+            return Err(format!(
+                "Context keys must be 1-{} characters long",
+                MAX_KEY_LEN
+            ));
+        }
+        if !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        {
+            return Err(format!(
+                "Context key '{}' may only contain letters, digits, '_', '.' and '-'",
+                key
+            ));
+        }
+        if value.is_empty() || value.len() > MAX_VALUE_LEN {
+            return Err(format!(
+                "Context value for '{}' must be 1-{} characters long",
+                key, MAX_VALUE_LEN
+            ));
+        }
+        if !seen.insert(key.clone()) {
+            return Err(format!("Context key '{}' given more than once", key));
+        }
+
+        validated.push((key, value));
+    }
+
+    Ok(validated)
+}
+
+/// One report's value of a benchmark, labelled with its value of the
+/// context key the series is split by.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct ContextPoint {
+    /// None for reports that didn't record the key
+    pub context_value: Option<String>,
+    pub report_id: Uuid,
+    pub git_hash: Option<String>,
+    pub value: f64,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// The latest `limit` results of a benchmark, split by the value each report
+/// recorded for `key`. Ordered by context value, then oldest first.
+/// Excluded and unfinalized reports are left out.
+#[allow(clippy::too_many_arguments)]
+pub async fn context_series<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    benchmark: &str,
+    measure: &str,
+    key: &str,
+    branch: Option<&str>,
+    testbed: Option<&str>,
+    limit: u64,
+) -> Result<Vec<ContextPoint>, DbErr> {
+    ContextPoint::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT * FROM (
+               SELECT c.value AS context_value, r.id AS report_id, r.git_hash,
+                      m.value, r.created_at
+               FROM metrics m
+               JOIN reports r ON r.id = m.report_id
+               JOIN benchmarks b ON b.id = m.benchmark_id
+               JOIN measures ms ON ms.id = m.measure_id
+               JOIN branches br ON br.id = r.branch_id
+               JOIN testbeds t ON t.id = r.testbed_id
+               LEFT JOIN report_context c ON c.report_id = r.id AND c.key = $4
+               WHERE r.project_id = $1
+                 AND r.finalized AND NOT r.excluded
+                 AND b.name = $2
+                 AND ms.name = $3
+                 AND ($5::text IS NULL OR br.name = $5)
+                 AND ($6::text IS NULL OR t.name = $6)
+               ORDER BY r.created_at DESC
+               LIMIT $7
+           ) latest
+           ORDER BY context_value NULLS LAST, created_at"#,
+        [
+            project_id.into(),
+            benchmark.into(),
+            measure.into(),
+            key.into(),
+            branch.map(str::to_string).into(),
+            testbed.map(str::to_string).into(),
+            (limit as i64).into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_validate_context_trims() {
+        let validated = validate_context(vec![
+            entry(" rustc ", "1.80 "),
+            entry("allocator", "jemalloc"),
+        ])
+        .unwrap();
+        assert_eq!(
+            validated,
+            vec![entry("rustc", "1.80"), entry("allocator", "jemalloc")]
+        );
+    }
+
+    #[test]
+    fn test_validate_context_rejects_bad_entries() {
+        assert!(validate_context(vec![entry("", "x")]).is_err());
+        assert!(validate_context(vec![entry("has space", "x")]).is_err());
+        assert!(validate_context(vec![entry("rustc", " ")]).is_err());
+        assert!(validate_context(vec![entry("rustc", "1.79"), entry("rustc", "1.80")]).is_err());
+
+        let too_many = (0..=MAX_CONTEXT_ENTRIES)
+            .map(|i| entry(&format!("k{}", i), "v"))
+            .collect();
+        assert!(validate_context(too_many).is_err());
+    }
+}
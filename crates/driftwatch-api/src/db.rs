@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use async_graphql::Context;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
+
+use crate::config::Config;
+
+/// Connection to the read replica, handed to resolvers alongside the primary
+/// `DatabaseConnection`. Falls back to the primary when no replica is set.
+#[derive(Clone)]
+pub struct ReadDb(pub DatabaseConnection);
+
+pub async fn connect(url: &str, config: &Config) -> Result<DatabaseConnection, DbErr> {
+    let mut options = ConnectOptions::new(url);
+    options
+        .max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .connect_timeout(Duration::from_secs(config.db_connect_timeout_secs))
+        .acquire_timeout(Duration::from_secs(config.db_acquire_timeout_secs))
+        .idle_timeout(Duration::from_secs(config.db_idle_timeout_secs));
+
+    Database::connect(options).await
+}
+
+/// Connection for heavy analytical reads (history, series, exports). These
+/// may lag the primary slightly, so don't use it to read back fresh writes.
+pub fn read_connection<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a DatabaseConnection> {
+    match ctx.data_opt::<ReadDb>() {
+        Some(ReadDb(db)) => Ok(db),
+        None => ctx.data::<DatabaseConnection>(),
+    }
+}
@@ -0,0 +1,528 @@
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, project, report};
+use crate::ingest::{self, Dimensions, NewMetric, NewReport};
+use crate::{evaluation, summary, templates};
+
+/// Days of history generated when none are asked for
+pub const DEFAULT_DAYS: u32 = 90;
+
+pub const MAX_DAYS: u32 = 365;
+
+const MAIN_BRANCH: &str = "main";
+
+/// Testbeds every demo project reports from, with how much slower each is
+/// than the first
+const TESTBEDS: &[(&str, f64)] = &[("ubuntu-22.04", 1.0), ("macos-14", 1.35)];
+
+const COMMIT_MESSAGES: &[&str] = &[
+    "Refactor request parsing",
+    "Bump dependencies",
+    "Add tracing spans",
+    "Fix off-by-one in buffer growth",
+    "Inline hot path",
+    "Tidy error handling",
+    "Cache compiled patterns",
+    "Update CI matrix",
+];
+
+struct DemoBenchmark {
+    name: &'static str,
+    measure: &'static str,
+    value: f64,
+    /// Coefficient of variation of the generated samples; zero for
+    /// deterministic measures like binary size
+    noise: f64,
+}
+
+/// A change injected into a benchmark's history, from `start` until `end`
+/// (fractions of the history); open-ended changes are real regressions,
+/// bounded ones get fixed again
+struct InjectedChange {
+    benchmark: &'static str,
+    measure: &'static str,
+    start: f64,
+    end: Option<f64>,
+    /// Percent, positive for an increase
+    change: f64,
+}
+
+struct DemoProject {
+    slug: &'static str,
+    name: &'static str,
+    description: &'static str,
+    benchmarks: &'static [DemoBenchmark],
+    changes: &'static [InjectedChange],
+    /// Feature branch reporting against main for the last few days, with
+    /// its own change to one benchmark
+    feature: Option<InjectedChange>,
+}
+
+const PROJECTS: &[DemoProject] = &[
+    DemoProject {
+        slug: "demo-http-server",
+        name: "Demo HTTP server",
+        description: "Generated demo data: a web server with latency, throughput, allocation and binary size benchmarks",
+        benchmarks: &[
+            DemoBenchmark {
+                name: "router/match",
+                measure: "latency",
+                value: 850.0,
+                noise: 0.02,
+            },
+            DemoBenchmark {
+                name: "json/serialize/1024",
+                measure: "latency",
+                value: 12_000.0,
+                noise: 0.03,
+            },
+            DemoBenchmark {
+                name: "json/serialize/4096",
+                measure: "latency",
+                value: 47_000.0,
+                noise: 0.03,
+            },
+            DemoBenchmark {
+                name: "handler/echo",
+                measure: "throughput",
+                value: 52_000.0,
+                noise: 0.04,
+            },
+            DemoBenchmark {
+                name: "handler/echo",
+                measure: "allocs",
+                value: 38.0,
+                noise: 0.0,
+            },
+            DemoBenchmark {
+                name: "server",
+                measure: "binary_size",
+                value: 4_200_000.0,
+                noise: 0.0,
+            },
+        ],
+        changes: &[
+            InjectedChange {
+                benchmark: "handler/echo",
+                measure: "allocs",
+                start: 0.35,
+                end: None,
+                change: 10.5,
+            },
+            InjectedChange {
+                benchmark: "router/match",
+                measure: "latency",
+                start: 0.6,
+                end: None,
+                change: 18.0,
+            },
+            InjectedChange {
+                benchmark: "handler/echo",
+                measure: "throughput",
+                start: 0.8,
+                end: Some(0.9),
+                change: -15.0,
+            },
+        ],
+        feature: Some(InjectedChange {
+            benchmark: "router/match",
+            measure: "latency",
+            start: 0.0,
+            end: None,
+            change: -12.0,
+        }),
+    },
+    DemoProject {
+        slug: "demo-parser",
+        name: "Demo parser",
+        description: "Generated demo data: a parser library with a noisy benchmark",
+        benchmarks: &[
+            DemoBenchmark {
+                name: "parse/small/100",
+                measure: "latency",
+                value: 3_100.0,
+                noise: 0.02,
+            },
+            DemoBenchmark {
+                name: "parse/large/100000",
+                measure: "latency",
+                value: 2_900_000.0,
+                noise: 0.025,
+            },
+            DemoBenchmark {
+                name: "tokenize",
+                measure: "latency",
+                value: 640.0,
+                noise: 0.09,
+            },
+            DemoBenchmark {
+                name: "parser",
+                measure: "binary_size",
+                value: 1_150_000.0,
+                noise: 0.0,
+            },
+        ],
+        changes: &[
+            InjectedChange {
+                benchmark: "parser",
+                measure: "binary_size",
+                start: 0.5,
+                end: None,
+                change: 3.0,
+            },
+            InjectedChange {
+                benchmark: "parse/large/100000",
+                measure: "latency",
+                start: 0.7,
+                end: None,
+                change: 25.0,
+            },
+        ],
+        feature: None,
+    },
+];
+
+/// Slugs `seed` creates, so callers can check they're free first
+pub fn project_slugs() -> impl Iterator<Item = &'static str> {
+    PROJECTS.iter().map(|p| p.slug)
+}
+
+/// A generated report, before it's stored
+#[derive(Debug, Clone)]
+pub struct PlannedReport {
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: String,
+    pub commit_message: String,
+    pub created_at: DateTime<FixedOffset>,
+    /// (benchmark, measure, value)
+    pub metrics: Vec<(String, String, f64)>,
+}
+
+/// Standard normal sample, by Box-Muller
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn active_change(change: &InjectedChange, benchmark: &DemoBenchmark, progress: f64) -> f64 {
+    let applies = change.benchmark == benchmark.name
+        && change.measure == benchmark.measure
+        && progress >= change.start
+        && change.end.is_none_or(|end| progress < end);
+    if applies {
+        change.change
+    } else {
+        0.0
+    }
+}
+
+fn sample(
+    rng: &mut StdRng,
+    benchmark: &DemoBenchmark,
+    slowdown: f64,
+    changes: &[&InjectedChange],
+    progress: f64,
+) -> f64 {
+    let change: f64 = changes
+        .iter()
+        .map(|c| active_change(c, benchmark, progress))
+        .sum();
+    let value = benchmark.value * (1.0 + change / 100.0);
+    if benchmark.noise == 0.0 {
+        // Counts and sizes don't vary between runs or machines
+        return value.round();
+    }
+    // Throughput drops on slower machines, everything else grows
+    let value = if benchmark.measure == "throughput" {
+        value / slowdown
+    } else {
+        value * slowdown
+    };
+    (value * (1.0 + benchmark.noise * gaussian(rng))).max(1.0)
+}
+
+fn planned_report(
+    rng: &mut StdRng,
+    project: &DemoProject,
+    branch: &str,
+    testbed: (&str, f64),
+    changes: &[&InjectedChange],
+    progress: f64,
+    created_at: DateTime<FixedOffset>,
+) -> PlannedReport {
+    let git_hash: String = (0..40)
+        .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap())
+        .collect();
+    let commit_message = COMMIT_MESSAGES[rng.gen_range(0..COMMIT_MESSAGES.len())].to_string();
+    let metrics = project
+        .benchmarks
+        .iter()
+        .map(|b| {
+            (
+                b.name.to_string(),
+                b.measure.to_string(),
+                sample(rng, b, testbed.1, changes, progress),
+            )
+        })
+        .collect();
+
+    PlannedReport {
+        branch: branch.to_string(),
+        testbed: testbed.0.to_string(),
+        git_hash,
+        commit_message,
+        created_at,
+        metrics,
+    }
+}
+
+/// Daily reports on main from every testbed over `days`, ending at `now`,
+/// plus the feature branch's reports over the last few days. The same seed
+/// always gives the same history.
+fn plan(project: &DemoProject, days: u32, seed: u64, now: DateTime<Utc>) -> Vec<PlannedReport> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let main_changes: Vec<&InjectedChange> = project.changes.iter().collect();
+    let mut reports = Vec::new();
+
+    for day in 0..days {
+        let progress = day as f64 / days as f64;
+        let created_at = (now - Duration::days((days - day) as i64)).fixed_offset();
+        for &testbed in TESTBEDS {
+            reports.push(planned_report(
+                &mut rng,
+                project,
+                MAIN_BRANCH,
+                testbed,
+                &main_changes,
+                progress,
+                created_at,
+            ));
+        }
+    }
+
+    if let Some(feature) = &project.feature {
+        let branch = format!("feature/{}", feature.benchmark.replace('/', "-"));
+        let mut changes = main_changes.clone();
+        changes.push(feature);
+        for day in 0..days.min(3) {
+            let created_at = (now - Duration::days(day as i64) - Duration::hours(2)).fixed_offset();
+            reports.push(planned_report(
+                &mut rng,
+                project,
+                &branch,
+                TESTBEDS[0],
+                &changes,
+                1.0,
+                created_at,
+            ));
+        }
+    }
+
+    reports.sort_by_key(|r| r.created_at);
+    reports
+}
+
+pub struct DemoSummary {
+    pub projects: Vec<project::Model>,
+    pub reports: usize,
+    pub alerts: usize,
+}
+
+/// Creates the demo projects for `user_id` with `days` of noisy history and
+/// their injected regressions, evaluating each report as it would have been
+/// when submitted. Projects use the `standard` template's thresholds.
+pub async fn seed(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    days: u32,
+    seed: u64,
+) -> Result<DemoSummary, DbErr> {
+    let template = templates::find("standard").expect("standard template exists");
+    let now = Utc::now();
+    let mut summary = DemoSummary {
+        projects: Vec::new(),
+        reports: 0,
+        alerts: 0,
+    };
+
+    for (i, demo) in PROJECTS.iter().enumerate() {
+        let txn = db.begin().await?;
+        let created_at = (now - Duration::days(days as i64 + 1)).fixed_offset();
+        let project = project::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(Some(user_id)),
+            slug: Set(demo.slug.to_string()),
+            name: Set(demo.name.to_string()),
+            description: Set(Some(demo.description.to_string())),
+            public: Set(false),
+            default_branch: Set(MAIN_BRANCH.to_string()),
+            github_repo: Set(None),
+            github_token: Set(None),
+            github_pr_comments: Set(false),
+            github_status_checks: Set(false),
+            expected_cadence_hours: Set(None),
+            stale_branches: Set(None),
+            github_issue_after_reports: Set(None),
+            alert_after_reports: Set(None),
+            noise_cv_limit: Set(Some(5.0)),
+            noise_action: Set(project::NoiseAction::WidenThresholds),
+            benchmark_required_paths: Set(None),
+            redact_secrets: Set(true),
+            alert_feed_token_hash: Set(None),
+            expires_at: Set(None),
+            share_token_hash: Set(None),
+            created_at: Set(created_at),
+            updated_at: Set(created_at),
+        }
+        .insert(&txn)
+        .await?;
+        templates::apply(&txn, project.id, template).await?;
+
+        let mut dimensions = Dimensions::default();
+        for planned in plan(demo, days, seed.wrapping_add(i as u64), now) {
+            let base_branch = (planned.branch != MAIN_BRANCH).then(|| MAIN_BRANCH.to_string());
+            let kind = match base_branch {
+                Some(_) => report::ReportKind::Pr,
+                None => report::ReportKind::Push,
+            };
+            let report = ingest::open_report(
+                &txn,
+                &mut dimensions,
+                NewReport {
+                    id: None,
+                    project_id: project.id,
+                    branch: planned.branch,
+                    testbed: planned.testbed,
+                    git_hash: Some(planned.git_hash),
+                    pr_number: None,
+                    commit_message: Some(planned.commit_message),
+                    commit_author: Some("Demo Author".to_string()),
+                    committed_at: Some(planned.created_at),
+                    base_branch,
+                    merge_base_hash: None,
+                    evaluated_commit: None,
+                    version: None,
+                    kind,
+                    exit_code: Some(0),
+                    partial: false,
+                    created_at: planned.created_at,
+                    context: Vec::new(),
+                    tags: Vec::new(),
+                    expected_benchmarks: Vec::new(),
+                },
+                true,
+            )
+            .await?;
+            let metrics = planned
+                .metrics
+                .into_iter()
+                .map(|(benchmark, measure, value)| NewMetric {
+                    benchmark,
+                    measure,
+                    value,
+                    lower_value: None,
+                    upper_value: None,
+                    reported_change: None,
+                    outliers: None,
+                })
+                .collect();
+            let metrics = ingest::append_metrics(&txn, &mut dimensions, &report, metrics).await?;
+
+            let alerts = evaluation::evaluate_report(&txn, &report, &metrics).await?;
+            summary::update_summaries(&txn, &report, &metrics).await?;
+            let mut active: report::ActiveModel = report.into();
+            active.status = Set(report::ReportStatus::Evaluated);
+            active.update(&txn).await?;
+
+            summary.reports += 1;
+            summary.alerts += alerts.len();
+        }
+
+        txn.commit().await?;
+        summary.projects.push(project);
+    }
+
+    Ok(summary)
+}
+
+/// Demo projects whose slugs are already taken
+pub async fn existing_slugs(db: &DatabaseConnection) -> Result<Vec<String>, DbErr> {
+    let projects = entities::Project::find()
+        .filter(project::Column::Slug.is_in(project_slugs()))
+        .all(db)
+        .await?;
+    Ok(projects.into_iter().map(|p| p.slug).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(reports: &[PlannedReport], branch: &str, testbed: &str, benchmark: &str) -> Vec<f64> {
+        reports
+            .iter()
+            .filter(|r| r.branch == branch && r.testbed == testbed)
+            .flat_map(|r| r.metrics.iter())
+            .filter(|(b, m, _)| b == benchmark && m == "latency")
+            .map(|(_, _, v)| *v)
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_is_deterministic() {
+        let now = Utc::now();
+        let a = plan(&PROJECTS[0], 30, 7, now);
+        let b = plan(&PROJECTS[0], 30, 7, now);
+        assert_eq!(a.len(), 30 * TESTBEDS.len() + 3);
+        for (a, b) in a.iter().zip(&b) {
+            assert_eq!(a.git_hash, b.git_hash);
+            assert_eq!(a.metrics, b.metrics);
+        }
+        assert!(a.windows(2).all(|w| w[0].created_at <= w[1].created_at));
+        assert!(a.iter().all(|r| r.created_at <= now.fixed_offset()));
+    }
+
+    #[test]
+    fn test_plan_injects_regressions() {
+        let reports = plan(&PROJECTS[0], 100, 1, Utc::now());
+        let router = values(&reports, MAIN_BRANCH, "ubuntu-22.04", "router/match");
+        assert_eq!(router.len(), 100);
+
+        let before = evaluation::mean(&router[..60]).unwrap();
+        let after = evaluation::mean(&router[60..]).unwrap();
+        assert!((before / 850.0 - 1.0).abs() < 0.02);
+        assert!((after / before - 1.18).abs() < 0.03);
+
+        let feature = values(
+            &reports,
+            "feature/router-match",
+            "ubuntu-22.04",
+            "router/match",
+        );
+        assert_eq!(feature.len(), 3);
+        assert!(evaluation::mean(&feature).unwrap() < after * 0.95);
+    }
+
+    #[test]
+    fn test_deterministic_measures_have_no_noise() {
+        let reports = plan(&PROJECTS[1], 20, 3, Utc::now());
+        let sizes: Vec<f64> = reports
+            .iter()
+            .flat_map(|r| r.metrics.iter())
+            .filter(|(_, m, _)| m == "binary_size")
+            .map(|(_, _, v)| *v)
+            .collect();
+        assert_eq!(sizes.first(), Some(&1_150_000.0));
+        assert_eq!(sizes.last(), Some(&1_184_500.0));
+        assert!(sizes.iter().all(|s| *s == 1_150_000.0 || *s == 1_184_500.0));
+    }
+}
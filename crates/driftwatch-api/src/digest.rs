@@ -0,0 +1,565 @@
+//! Weekly digests: a per-project summary of the biggest regressions and
+//! improvements, alert churn and branches that stopped reporting, rendered
+//! to markdown and HTML once each week ends and sent to the project's
+//! notification channels.
+//!
+//! Weeks run Monday to Monday in UTC. Movers compare each series' last
+//! value in the week against its last value before it, so a benchmark
+//! that went up and came back down again doesn't show up.
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveTime, Utc};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, PaginatorTrait, QueryFilter, QuerySelect, RelationTrait, Set, Statement,
+    TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, alert, digest, project, threshold};
+use crate::evaluation::CLOSED_STATUSES;
+use crate::jobs::JobRegistry;
+use crate::overview::{self, TREND_CHANGE_PERCENT};
+use crate::{error_reports, links, notifications};
+
+/// How often the generator looks for weeks that ended without a digest.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Job kind that generates digests for weeks that ended without one.
+pub const GENERATE_DIGESTS_JOB: &str = "generate_digests";
+
+/// Regressions and improvements listed in each digest.
+pub const TOP_MOVERS: usize = 5;
+
+/// Branch/testbed pairs that reported within this many days before the
+/// week but not during it count as missing data.
+pub const MISSING_LOOKBACK_DAYS: i64 = 28;
+
+/// One benchmark series that moved over the week.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct Mover {
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    pub before: f64,
+    pub after: f64,
+    pub percent_change: f64,
+}
+
+/// A branch/testbed pair that went quiet during the week.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct MissingSeries {
+    pub branch: String,
+    pub testbed: String,
+    pub last_report_at: DateTimeWithTimeZone,
+}
+
+/// Everything a digest reports, before rendering.
+#[derive(Debug, Clone)]
+pub struct Contents {
+    pub project_name: String,
+    pub project_slug: String,
+    pub period_start: DateTime<FixedOffset>,
+    pub period_end: DateTime<FixedOffset>,
+    pub reports: u64,
+    /// Biggest increases first; lower values are better
+    pub regressions: Vec<Mover>,
+    /// Biggest decreases first
+    pub improvements: Vec<Mover>,
+    pub alerts_raised: u64,
+    pub alerts_resolved: u64,
+    /// Alerts open when the digest was compiled
+    pub alerts_open: u64,
+    pub missing: Vec<MissingSeries>,
+}
+
+/// Registers the periodic digest generation.
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.schedule(GENERATE_DIGESTS_JOB, CHECK_INTERVAL, |db| async move {
+        let now = Utc::now().fixed_offset();
+        match generate_due(&db, now).await {
+            Ok(generated) if generated > 0 => {
+                tracing::info!("Generated {} weekly digests", generated);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!("Digest generation failed: {}", e);
+                error_reports::capture(&e.into(), "digest", &[]);
+            }
+        }
+        Ok(())
+    });
+}
+
+/// The Monday-to-Monday UTC week that `at` falls in.
+pub fn week_containing(
+    at: DateTime<FixedOffset>,
+) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+    let date = at.with_timezone(&Utc).date_naive();
+    let monday = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+    let start = monday.and_time(NaiveTime::MIN).and_utc().fixed_offset();
+    (start, start + chrono::Duration::days(7))
+}
+
+/// Compiles, stores and queues delivery of the digest for the last
+/// completed week, for every project that reported in or shortly before
+/// it. Projects that already have that week's digest are skipped.
+pub async fn generate_due(
+    db: &DatabaseConnection,
+    now: DateTime<FixedOffset>,
+) -> Result<u64, DbErr> {
+    let (this_week, _) = week_containing(now);
+    let (start, end) = (this_week - chrono::Duration::days(7), this_week);
+
+    let projects = entities::Project::find()
+        .filter(
+            project::Column::Id.in_subquery(
+                sea_orm::sea_query::Query::select()
+                    .column(entities::report::Column::ProjectId)
+                    .from(entities::Report)
+                    .and_where(
+                        entities::report::Column::CreatedAt
+                            .gte(start - chrono::Duration::days(MISSING_LOOKBACK_DAYS)),
+                    )
+                    .and_where(entities::report::Column::CreatedAt.lt(end))
+                    .to_owned(),
+            ),
+        )
+        .filter(
+            project::Column::Id.not_in_subquery(
+                sea_orm::sea_query::Query::select()
+                    .column(digest::Column::ProjectId)
+                    .from(entities::Digest)
+                    .and_where(digest::Column::PeriodStart.eq(start))
+                    .to_owned(),
+            ),
+        )
+        .all(db)
+        .await?;
+
+    let mut generated = 0;
+    for project in projects {
+        let contents = compile(db, &project, start, end).await?;
+        if store(db, &project, &contents, now).await?.is_some() {
+            generated += 1;
+        }
+    }
+    Ok(generated)
+}
+
+/// Saves a digest and queues its deliveries in one transaction. Returns
+/// `None` when another server already stored this week's digest.
+pub async fn store(
+    db: &DatabaseConnection,
+    project: &project::Model,
+    contents: &Contents,
+    now: DateTime<FixedOffset>,
+) -> Result<Option<digest::Model>, DbErr> {
+    let model = digest::Model {
+        id: Uuid::new_v4(),
+        project_id: project.id,
+        period_start: contents.period_start,
+        period_end: contents.period_end,
+        markdown: render_markdown(contents),
+        html: render_html(contents),
+        created_at: now,
+    };
+
+    let txn = db.begin().await?;
+    let inserted = entities::Digest::insert(digest::ActiveModel {
+        id: Set(model.id),
+        project_id: Set(model.project_id),
+        period_start: Set(model.period_start),
+        period_end: Set(model.period_end),
+        markdown: Set(model.markdown.clone()),
+        html: Set(model.html.clone()),
+        created_at: Set(model.created_at),
+    })
+    .on_conflict(
+        OnConflict::columns([digest::Column::ProjectId, digest::Column::PeriodStart])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(&txn)
+    .await?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+    notifications::queue_digest(&txn, &model).await?;
+    txn.commit().await?;
+    Ok(Some(model))
+}
+
+/// Gathers a project's digest for the period from `start` to `end`.
+pub async fn compile<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> Result<Contents, DbErr> {
+    let reports = entities::Report::find()
+        .filter(entities::report::Column::ProjectId.eq(project.id))
+        .filter(entities::report::Column::CreatedAt.gte(start))
+        .filter(entities::report::Column::CreatedAt.lt(end))
+        .count(db)
+        .await?;
+
+    let movers = movers(db, project.id, start, end).await?;
+    let regressions = movers
+        .iter()
+        .filter(|m| m.percent_change > TREND_CHANGE_PERCENT)
+        .take(TOP_MOVERS)
+        .cloned()
+        .collect();
+    let improvements = movers
+        .iter()
+        .rev()
+        .filter(|m| m.percent_change < -TREND_CHANGE_PERCENT)
+        .take(TOP_MOVERS)
+        .cloned()
+        .collect();
+
+    let project_alerts = || {
+        entities::Alert::find()
+            .join(
+                sea_orm::JoinType::InnerJoin,
+                alert::Relation::Threshold.def(),
+            )
+            .filter(threshold::Column::ProjectId.eq(project.id))
+    };
+    let alerts_raised = project_alerts()
+        .filter(alert::Column::Status.ne(alert::AlertStatus::Unconfirmed))
+        .filter(alert::Column::CreatedAt.gte(start))
+        .filter(alert::Column::CreatedAt.lt(end))
+        .count(db)
+        .await?;
+    let alerts_resolved = project_alerts()
+        .filter(alert::Column::Status.is_in(CLOSED_STATUSES))
+        .filter(alert::Column::UpdatedAt.gte(start))
+        .filter(alert::Column::UpdatedAt.lt(end))
+        .count(db)
+        .await?;
+    let alerts_open = overview::open_alerts(db, project.id).await?;
+
+    Ok(Contents {
+        project_name: project.name.clone(),
+        project_slug: project.slug.clone(),
+        period_start: start,
+        period_end: end,
+        reports,
+        regressions,
+        improvements,
+        alerts_raised,
+        alerts_resolved,
+        alerts_open,
+        missing: missing_series(db, project.id, start, end).await?,
+    })
+}
+
+/// Every series with a value both in the week and in the lookback before
+/// it, ordered from the biggest increase to the biggest decrease.
+async fn movers<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> Result<Vec<Mover>, DbErr> {
+    Mover::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"WITH series AS (
+               SELECT DISTINCT ON (m.benchmark_id, m.measure_id, r.branch_id, r.testbed_id, r.created_at >= $2)
+                      m.benchmark_id, m.measure_id, r.branch_id, r.testbed_id,
+                      r.created_at >= $2 AS in_week, m.value
+               FROM metrics m
+               JOIN reports r ON r.id = m.report_id
+               WHERE r.project_id = $1 AND r.finalized AND NOT r.excluded
+                 AND r.created_at >= $4 AND r.created_at < $3
+               ORDER BY m.benchmark_id, m.measure_id, r.branch_id, r.testbed_id,
+                        r.created_at >= $2, r.created_at DESC
+           )
+           SELECT b.name AS benchmark, ms.name AS measure, br.name AS branch,
+                  t.name AS testbed, prev.value AS before, cur.value AS after,
+                  (cur.value - prev.value) / prev.value * 100 AS percent_change
+           FROM series cur
+           JOIN series prev
+             ON prev.benchmark_id = cur.benchmark_id AND prev.measure_id = cur.measure_id
+            AND prev.branch_id = cur.branch_id AND prev.testbed_id = cur.testbed_id
+            AND NOT prev.in_week
+           JOIN benchmarks b ON b.id = cur.benchmark_id
+           JOIN measures ms ON ms.id = cur.measure_id
+           JOIN branches br ON br.id = cur.branch_id
+           JOIN testbeds t ON t.id = cur.testbed_id
+           WHERE cur.in_week AND prev.value <> 0
+           ORDER BY percent_change DESC"#,
+        [
+            project_id.into(),
+            start.into(),
+            end.into(),
+            (start - chrono::Duration::days(MISSING_LOOKBACK_DAYS)).into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
+
+/// Branch/testbed pairs that reported in the lookback but not in the week.
+async fn missing_series<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+) -> Result<Vec<MissingSeries>, DbErr> {
+    MissingSeries::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT br.name AS branch, t.name AS testbed, MAX(r.created_at) AS last_report_at
+           FROM reports r
+           JOIN branches br ON br.id = r.branch_id
+           JOIN testbeds t ON t.id = r.testbed_id
+           WHERE r.project_id = $1 AND r.created_at >= $4 AND r.created_at < $3
+           GROUP BY br.name, t.name
+           HAVING MAX(r.created_at) < $2
+           ORDER BY last_report_at DESC"#,
+        [
+            project_id.into(),
+            start.into(),
+            end.into(),
+            (start - chrono::Duration::days(MISSING_LOOKBACK_DAYS)).into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
+
+fn format_value(value: f64) -> String {
+    let formatted = format!("{:.3}", value);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+fn title(contents: &Contents) -> String {
+    format!(
+        "{}: week of {}",
+        contents.project_name,
+        contents.period_start.format("%Y-%m-%d")
+    )
+}
+
+fn alert_line(contents: &Contents) -> String {
+    format!(
+        "{} raised, {} resolved or dismissed, {} open now",
+        contents.alerts_raised, contents.alerts_resolved, contents.alerts_open
+    )
+}
+
+fn period_line(contents: &Contents) -> String {
+    format!(
+        "{} reports from {} to {}.",
+        contents.reports,
+        contents.period_start.format("%Y-%m-%d"),
+        (contents.period_end - chrono::Duration::days(1)).format("%Y-%m-%d")
+    )
+}
+
+pub fn render_markdown(contents: &Contents) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", title(contents));
+    let _ = writeln!(out, "{}", period_line(contents));
+    if let Some(url) = links::web_url().map(|web| links::project_url(web, &contents.project_slug)) {
+        let _ = writeln!(out, "\n[Open in Driftwatch]({})", url);
+    }
+
+    for (heading, movers) in [
+        ("Biggest regressions", &contents.regressions),
+        ("Biggest improvements", &contents.improvements),
+    ] {
+        let _ = writeln!(out, "\n## {}\n", heading);
+        if movers.is_empty() {
+            let _ = writeln!(out, "None beyond {}%.", TREND_CHANGE_PERCENT);
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "| Benchmark | Measure | Branch / testbed | Before | After | Change |"
+        );
+        let _ = writeln!(out, "|---|---|---|---|---|---|");
+        for m in movers.iter() {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} / {} | {} | {} | {:+.1}% |",
+                m.benchmark,
+                m.measure,
+                m.branch,
+                m.testbed,
+                format_value(m.before),
+                format_value(m.after),
+                m.percent_change
+            );
+        }
+    }
+
+    let _ = writeln!(out, "\n## Alerts\n");
+    let _ = writeln!(out, "{}.", alert_line(contents));
+
+    let _ = writeln!(out, "\n## Missing data\n");
+    if contents.missing.is_empty() {
+        let _ = writeln!(out, "Every branch and testbed reported.");
+    }
+    for series in &contents.missing {
+        let _ = writeln!(
+            out,
+            "- {} / {}: last report {}",
+            series.branch,
+            series.testbed,
+            series.last_report_at.format("%Y-%m-%d")
+        );
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render_html(contents: &Contents) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<h1>{}</h1>", escape_html(&title(contents)));
+    let _ = writeln!(out, "<p>{}</p>", escape_html(&period_line(contents)));
+    if let Some(url) = links::web_url().map(|web| links::project_url(web, &contents.project_slug)) {
+        let _ = writeln!(
+            out,
+            "<p><a href=\"{}\">Open in Driftwatch</a></p>",
+            escape_html(&url)
+        );
+    }
+
+    for (heading, movers) in [
+        ("Biggest regressions", &contents.regressions),
+        ("Biggest improvements", &contents.improvements),
+    ] {
+        let _ = writeln!(out, "<h2>{}</h2>", heading);
+        if movers.is_empty() {
+            let _ = writeln!(out, "<p>None beyond {}%.</p>", TREND_CHANGE_PERCENT);
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "<table>\n<tr><th>Benchmark</th><th>Measure</th><th>Branch / testbed</th>\
+             <th>Before</th><th>After</th><th>Change</th></tr>"
+        );
+        for m in movers.iter() {
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td>{}</td><td>{} / {}</td><td>{}</td><td>{}</td><td>{:+.1}%</td></tr>",
+                escape_html(&m.benchmark),
+                escape_html(&m.measure),
+                escape_html(&m.branch),
+                escape_html(&m.testbed),
+                format_value(m.before),
+                format_value(m.after),
+                m.percent_change
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+
+    let _ = writeln!(out, "<h2>Alerts</h2>");
+    let _ = writeln!(out, "<p>{}.</p>", alert_line(contents));
+
+    let _ = writeln!(out, "<h2>Missing data</h2>");
+    if contents.missing.is_empty() {
+        let _ = writeln!(out, "<p>Every branch and testbed reported.</p>");
+    } else {
+        let _ = writeln!(out, "<ul>");
+        for series in &contents.missing {
+            let _ = writeln!(
+                out,
+                "<li>{} / {}: last report {}</li>",
+                escape_html(&series.branch),
+                escape_html(&series.testbed),
+                series.last_report_at.format("%Y-%m-%d")
+            );
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    fn contents() -> Contents {
+        let (start, end) = week_containing(at("2024-01-10T12:00:00Z"));
+        Contents {
+            project_name: "Core <lib>".to_string(),
+            project_slug: "core".to_string(),
+            period_start: start,
+            period_end: end,
+            reports: 12,
+            regressions: vec![Mover {
+                benchmark: "parse/json".to_string(),
+                measure: "latency".to_string(),
+                branch: "main".to_string(),
+                testbed: "ci".to_string(),
+                before: 100.0,
+                after: 125.5,
+                percent_change: 25.5,
+            }],
+            improvements: vec![],
+            alerts_raised: 3,
+            alerts_resolved: 1,
+            alerts_open: 4,
+            missing: vec![MissingSeries {
+                branch: "release".to_string(),
+                testbed: "arm64".to_string(),
+                last_report_at: at("2024-01-03T09:00:00Z"),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_week_containing() {
+        let (start, end) = week_containing(at("2024-01-10T12:00:00Z"));
+        assert_eq!(start, at("2024-01-08T00:00:00Z"));
+        assert_eq!(end, at("2024-01-15T00:00:00Z"));
+
+        // Mondays start their own week; offsets are converted to UTC first
+        assert_eq!(week_containing(at("2024-01-08T00:00:00Z")).0, start);
+        assert_eq!(week_containing(at("2024-01-15T01:00:00+02:00")).0, start);
+        assert_eq!(week_containing(at("2024-01-14T23:59:59Z")).0, start);
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = render_markdown(&contents());
+        assert!(markdown.starts_with("# Core <lib>: week of 2024-01-08\n"));
+        assert!(markdown.contains("12 reports from 2024-01-08 to 2024-01-14."));
+        assert!(markdown.contains("| `parse/json` | latency | main / ci | 100 | 125.5 | +25.5% |"));
+        assert!(markdown.contains("## Biggest improvements\n\nNone beyond 5%."));
+        assert!(markdown.contains("3 raised, 1 resolved or dismissed, 4 open now."));
+        assert!(markdown.contains("- release / arm64: last report 2024-01-03"));
+    }
+
+    #[test]
+    fn test_render_html_escapes() {
+        let html = render_html(&contents());
+        assert!(html.starts_with("<h1>Core &lt;lib&gt;: week of 2024-01-08</h1>"));
+        assert!(html.contains("<td><code>parse/json</code></td>"));
+        assert!(html.contains("<li>release / arm64: last report 2024-01-03</li>"));
+        assert!(!html.contains("<lib>"));
+    }
+}
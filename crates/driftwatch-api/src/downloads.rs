@@ -0,0 +1,574 @@
+//! A project's metrics as CSV or Parquet, one row per metric, for analysis
+//! in a notebook or spreadsheet without an export sink.
+//!
+//! `GET /downloads/{project_slug}/metrics.csv` and `metrics.parquet` take an
+//! API key as bearer token, like the OpenMetrics endpoint, and the optional
+//! `branch`, `testbed` and `since` query parameters. The rows come from a
+//! single query streamed off the database, so the download is one
+//! consistent snapshot however long it takes. Encoded chunks go through a
+//! small bounded channel to the response body: a slow client stalls the
+//! query instead of the server buffering the table.
+//!
+//! `GET /flamegraphs/{id}` returns a flamegraph of one of the caller's
+//! reports. Flamegraphs are stored zstd-compressed and sent that way, with
+//! `Content-Encoding: zstd`, to clients that accept it; others get the SVG
+//! decompressed. Either way it comes with a Content-Security-Policy that
+//! blocks scripts and sandboxes the document.
+
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BooleanBuilder, Float64Builder, Int32Builder, StringBuilder, TimestampMicrosecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use async_graphql::ID;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use chrono::{DateTime, FixedOffset};
+use futures_util::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use sea_orm::{DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, Statement};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::auth::{bearer_token, validate_token, AuthUser, TsaAuth};
+use crate::cache::AppCache;
+use crate::entities::blob::ContentEncoding;
+use crate::entities::{self, blob, flamegraph};
+use crate::export::ExportRow;
+use crate::graphql::authz;
+use crate::{compression, error_reports, storage};
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+pub const PARQUET_CONTENT_TYPE: &str = "application/vnd.apache.parquet";
+
+/// CSV bytes collected before a chunk is sent
+const CSV_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Rows per Arrow batch handed to the Parquet writer
+const PARQUET_BATCH_ROWS: usize = 8192;
+
+/// Rows per Parquet row group; a row group is buffered until it is full
+const PARQUET_ROW_GROUP_ROWS: usize = 64 * 1024;
+
+/// Policy for flamegraphs opened in a browser tab: their own inline styles
+/// only, no scripts, plugins or requests, and a sandboxed origin should the
+/// sanitizer ever miss something
+const FLAMEGRAPH_CONTENT_SECURITY_POLICY: &str =
+    "default-src 'none'; style-src 'unsafe-inline'; sandbox";
+
+/// Encoded chunks waiting for the client. Once they are all unsent, reading
+/// from the database pauses.
+const CHANNEL_CHUNKS: usize = 4;
+
+const CSV_HEADER: [&str; 15] = [
+    "report_id",
+    "project",
+    "branch",
+    "testbed",
+    "git_hash",
+    "pr_number",
+    "version",
+    "excluded",
+    "benchmark",
+    "measure",
+    "units",
+    "value",
+    "lower_value",
+    "upper_value",
+    "reported_at",
+];
+
+/// Format of a download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Parquet,
+}
+
+impl Format {
+    /// The format named by a file name such as `metrics.csv`
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        match file_name {
+            "metrics.csv" => Some(Self::Csv),
+            "metrics.parquet" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => CSV_CONTENT_TYPE,
+            Self::Parquet => PARQUET_CONTENT_TYPE,
+        }
+    }
+}
+
+/// Rows to include; all of the project's finalized reports by default
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct DownloadFilter {
+    pub branch: Option<String>,
+    pub testbed: Option<String>,
+    /// Only reports submitted at or after this RFC 3339 time
+    pub since: Option<DateTime<FixedOffset>>,
+}
+
+/// The metrics of a project's finalized reports, oldest report first
+fn metrics_query(project_id: Uuid, filter: &DownloadFilter) -> Statement {
+    Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT r.id AS report_id, p.slug AS project, br.name AS branch, tb.name AS testbed,
+                  r.git_hash, r.pr_number, r.version, r.excluded, b.name AS benchmark,
+                  ms.name AS measure, ms.units, m.value, m.lower_value, m.upper_value,
+                  r.created_at AS reported_at
+           FROM reports r
+           JOIN projects p ON p.id = r.project_id
+           JOIN branches br ON br.id = r.branch_id
+           JOIN testbeds tb ON tb.id = r.testbed_id
+           JOIN metrics m ON m.report_id = r.id
+           JOIN benchmarks b ON b.id = m.benchmark_id
+           JOIN measures ms ON ms.id = m.measure_id
+           WHERE r.project_id = $1 AND r.finalized
+             AND ($2::text IS NULL OR br.name = $2)
+             AND ($3::text IS NULL OR tb.name = $3)
+             AND ($4::timestamptz IS NULL OR r.created_at >= $4)
+           ORDER BY r.created_at, r.id"#,
+        [
+            project_id.into(),
+            filter.branch.clone().into(),
+            filter.testbed.clone().into(),
+            filter.since.into(),
+        ],
+    )
+}
+
+type Chunk = Result<Bytes, std::io::Error>;
+
+/// Streams the query's rows into `tx` as encoded chunks. Returns early,
+/// without error, when the client went away.
+async fn produce(
+    db: DatabaseConnection,
+    query: Statement,
+    format: Format,
+    tx: mpsc::Sender<Chunk>,
+) -> anyhow::Result<()> {
+    let mut rows = ExportRow::find_by_statement(query).stream(&db).await?;
+    match format {
+        Format::Csv => {
+            let mut csv = String::new();
+            push_csv_record(&mut csv, CSV_HEADER.iter().map(|h| h.to_string()));
+            while let Some(row) = rows.try_next().await? {
+                push_csv_record(&mut csv, csv_fields(&row));
+                if csv.len() >= CSV_CHUNK_BYTES
+                    && tx.send(Ok(std::mem::take(&mut csv).into())).await.is_err()
+                {
+                    return Ok(());
+                }
+            }
+            if !csv.is_empty() {
+                let _ = tx.send(Ok(csv.into())).await;
+            }
+        }
+        Format::Parquet => {
+            let props = WriterProperties::builder()
+                .set_max_row_group_size(PARQUET_ROW_GROUP_ROWS)
+                .build();
+            let mut writer = ArrowWriter::try_new(Vec::new(), parquet_schema(), Some(props))?;
+            let mut batch = Vec::with_capacity(PARQUET_BATCH_ROWS);
+            loop {
+                let row = rows.try_next().await?;
+                let done = row.is_none();
+                batch.extend(row);
+                if batch.len() < PARQUET_BATCH_ROWS && !done {
+                    continue;
+                }
+                if !batch.is_empty() {
+                    writer.write(&record_batch(&batch)?)?;
+                    batch.clear();
+                }
+                if done {
+                    writer.finish()?;
+                }
+                // Completed row groups have been written out; send them on
+                let written = std::mem::take(writer.inner_mut());
+                if !written.is_empty() && tx.send(Ok(written.into())).await.is_err() {
+                    return Ok(());
+                }
+                if done {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Appends one CSV record, quoting fields that need it
+fn push_csv_record(out: &mut String, fields: impl Iterator<Item = String>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(&field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// A row's fields in [`CSV_HEADER`] order; missing values are empty
+fn csv_fields(row: &ExportRow) -> impl Iterator<Item = String> {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    [
+        row.report_id.to_string(),
+        row.project.clone(),
+        row.branch.clone(),
+        row.testbed.clone(),
+        optional(row.git_hash.clone()),
+        optional(row.pr_number.map(|n| n.to_string())),
+        optional(row.version.clone()),
+        row.excluded.to_string(),
+        row.benchmark.clone(),
+        row.measure.clone(),
+        optional(row.units.clone()),
+        row.value.to_string(),
+        optional(row.lower_value.map(|v| v.to_string())),
+        optional(row.upper_value.map(|v| v.to_string())),
+        row.reported_at.to_rfc3339(),
+    ]
+    .into_iter()
+}
+
+/// The Parquet columns, named like the CSV header
+pub fn parquet_schema() -> SchemaRef {
+    let text = |name, nullable| Field::new(name, DataType::Utf8, nullable);
+    let number = |name, nullable| Field::new(name, DataType::Float64, nullable);
+    Arc::new(Schema::new(vec![
+        text("report_id", false),
+        text("project", false),
+        text("branch", false),
+        text("testbed", false),
+        text("git_hash", true),
+        Field::new("pr_number", DataType::Int32, true),
+        text("version", true),
+        Field::new("excluded", DataType::Boolean, false),
+        text("benchmark", false),
+        text("measure", false),
+        text("units", true),
+        number("value", false),
+        number("lower_value", true),
+        number("upper_value", true),
+        Field::new(
+            "reported_at",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+    ]))
+}
+
+fn record_batch(rows: &[ExportRow]) -> Result<RecordBatch, arrow_schema::ArrowError> {
+    let text = |value: fn(&ExportRow) -> Option<&str>| -> ArrayRef {
+        let mut builder = StringBuilder::new();
+        for row in rows {
+            builder.append_option(value(row));
+        }
+        Arc::new(builder.finish())
+    };
+    let number = |value: fn(&ExportRow) -> Option<f64>| -> ArrayRef {
+        let mut builder = Float64Builder::with_capacity(rows.len());
+        for row in rows {
+            builder.append_option(value(row));
+        }
+        Arc::new(builder.finish())
+    };
+
+    let report_ids: Vec<String> = rows.iter().map(|r| r.report_id.to_string()).collect();
+    let mut pr_numbers = Int32Builder::with_capacity(rows.len());
+    let mut excluded = BooleanBuilder::with_capacity(rows.len());
+    let mut reported_at =
+        TimestampMicrosecondBuilder::with_capacity(rows.len()).with_timezone("UTC");
+    for row in rows {
+        pr_numbers.append_option(row.pr_number);
+        excluded.append_value(row.excluded);
+        reported_at.append_value(row.reported_at.timestamp_micros());
+    }
+
+    RecordBatch::try_new(
+        parquet_schema(),
+        vec![
+            Arc::new(arrow_array::StringArray::from(report_ids)),
+            text(|r| Some(&r.project)),
+            text(|r| Some(&r.branch)),
+            text(|r| Some(&r.testbed)),
+            text(|r| r.git_hash.as_deref()),
+            Arc::new(pr_numbers.finish()),
+            text(|r| r.version.as_deref()),
+            Arc::new(excluded.finish()),
+            text(|r| Some(&r.benchmark)),
+            text(|r| Some(&r.measure)),
+            text(|r| r.units.as_deref()),
+            number(|r| Some(r.value)),
+            number(|r| r.lower_value),
+            number(|r| r.upper_value),
+            Arc::new(reported_at.finish()),
+        ],
+    )
+}
+
+#[derive(Clone)]
+struct DownloadState {
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+}
+
+/// The caller, from the API key in the `Authorization` header
+async fn authorize(state: &DownloadState, headers: &HeaderMap) -> Result<AuthUser, Response> {
+    let Some(token) = bearer_token(headers) else {
+        return Err((StatusCode::UNAUTHORIZED, "Missing bearer token").into_response());
+    };
+    validate_token(token, &state.auth)
+        .await
+        .map_err(|e| (StatusCode::UNAUTHORIZED, e.0).into_response())
+}
+
+async fn download(
+    State(state): State<DownloadState>,
+    Path((project_slug, file_name)): Path<(String, String)>,
+    Query(filter): Query<DownloadFilter>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(format) = Format::from_file_name(&file_name) else {
+        return (StatusCode::NOT_FOUND, "Unknown download").into_response();
+    };
+    let user = match authorize(&state, &headers).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let project = match state
+        .cache
+        .resolve_project(&state.db, user.user_id(), &project_slug)
+        .await
+    {
+        Ok(Some(project)) => project,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Project not found").into_response(),
+        Err(e) => {
+            tracing::error!("Download failed: {}", e);
+            error_reports::capture(&e.into(), "downloads", &[]);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let (tx, mut rx) = mpsc::channel(CHANNEL_CHUNKS);
+    let query = metrics_query(project.id, &filter);
+    tokio::spawn(async move {
+        if let Err(e) = produce(state.db, query, format, tx.clone()).await {
+            tracing::error!("Download of {} failed: {}", project.slug, e);
+            // The status is already sent; failing the body tells the client
+            // the file is incomplete
+            let _ = tx.send(Err(std::io::Error::other(e.to_string()))).await;
+            error_reports::capture(&e, "downloads", &[]);
+        }
+    });
+    let body = Body::from_stream(futures_util::stream::poll_fn(move |cx| rx.poll_recv(cx)));
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(r#"attachment; filename="{}-{}""#, project_slug, file_name),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// A flamegraph and the blob holding its file, if it belongs to one of the
+/// user's reports
+async fn find_flamegraph(
+    db: &DatabaseConnection,
+    user: &AuthUser,
+    id: Uuid,
+) -> anyhow::Result<Option<(flamegraph::Model, blob::Model)>> {
+    let Some(flamegraph) = entities::Flamegraph::find_by_id(id).one(db).await? else {
+        return Ok(None);
+    };
+    let report_id = ID(flamegraph.report_id.to_string());
+    if authz::find_report(db, user, &report_id)
+        .await
+        .map_err(|e| anyhow::anyhow!(e.message))?
+        .is_none()
+    {
+        return Ok(None);
+    }
+    let Some(sha256) = &flamegraph.sha256 else {
+        return Ok(None);
+    };
+    let blob = entities::Blob::find_by_id(sha256.as_str()).one(db).await?;
+    Ok(blob.map(|blob| (flamegraph, blob)))
+}
+
+async fn download_flamegraph(
+    State(state): State<DownloadState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Response {
+    let user = match authorize(&state, &headers).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let (flamegraph, blob) = match find_flamegraph(&state.db, &user, id).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return (StatusCode::NOT_FOUND, "Flamegraph not found").into_response(),
+        Err(e) => {
+            tracing::error!("Flamegraph download failed: {}", e);
+            error_reports::capture(&e, "downloads", &[]);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let mut response = if blob.encoding == ContentEncoding::Zstd
+        && compression::accepts_encoding(&headers, "zstd")
+    {
+        ([(header::CONTENT_ENCODING, "zstd")], blob.content).into_response()
+    } else {
+        match storage::decompress(&blob.content, blob.encoding, blob.size as usize) {
+            Ok(content) => content.into_response(),
+            Err(e) => {
+                tracing::error!("Blob {} can't be decompressed: {}", blob.sha256, e);
+                error_reports::capture(&e.into(), "downloads", &[]);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    };
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(storage::ACCEPTED_CONTENT_TYPES[0]),
+    );
+    headers.insert(
+        header::VARY,
+        HeaderValue::from_static(header::ACCEPT_ENCODING.as_str()),
+    );
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static(FLAMEGRAPH_CONTENT_SECURITY_POLICY),
+    );
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    if let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", blob.sha256)) {
+        headers.insert(header::ETAG, etag);
+    }
+    let file_name = flamegraph.file_name.replace(['"', '\\'], "");
+    if let Ok(disposition) = HeaderValue::from_str(&format!(r#"inline; filename="{}""#, file_name))
+    {
+        headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+    response
+}
+
+/// Routes for `GET /downloads/{project_slug}/metrics.csv`, `metrics.parquet`
+/// and `GET /flamegraphs/{id}`. `db` should be the read replica when there
+/// is one.
+pub fn router<S: Clone + Send + Sync + 'static>(
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+) -> Router<S> {
+    Router::new()
+        .route("/downloads/{project_slug}/{file_name}", get(download))
+        .route("/flamegraphs/{id}", get(download_flamegraph))
+        .with_state(DownloadState { db, auth, cache })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn row(benchmark: &str, git_hash: Option<&str>) -> ExportRow {
+        ExportRow {
+            report_id: Uuid::nil(),
+            project: "core".to_string(),
+            branch: "main".to_string(),
+            testbed: "ci".to_string(),
+            git_hash: git_hash.map(str::to_string),
+            pr_number: Some(42),
+            version: None,
+            excluded: false,
+            benchmark: benchmark.to_string(),
+            measure: "latency".to_string(),
+            units: Some("ns".to_string()),
+            value: 1.5,
+            lower_value: None,
+            upper_value: Some(2.0),
+            reported_at: DateTime::parse_from_rfc3339("2024-01-08T10:00:00Z").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_csv_records() {
+        let mut csv = String::new();
+        push_csv_record(
+            &mut csv,
+            csv_fields(&row("parse, \"fast\"", Some("abc123"))),
+        );
+        assert_eq!(
+            csv,
+            "00000000-0000-0000-0000-000000000000,core,main,ci,abc123,42,,false,\
+             \"parse, \"\"fast\"\"\",latency,ns,1.5,,2,2024-01-08T10:00:00+00:00\r\n"
+        );
+        assert_eq!(csv_fields(&row("parse", None)).count(), CSV_HEADER.len());
+    }
+
+    #[test]
+    fn test_parquet_batches() {
+        let rows = [row("parse", Some("abc123")), row("render", None)];
+        let mut writer = ArrowWriter::try_new(Vec::new(), parquet_schema(), None).unwrap();
+        writer.write(&record_batch(&rows).unwrap()).unwrap();
+        let file = Bytes::from(writer.into_inner().unwrap());
+
+        let batches: Vec<RecordBatch> = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].schema(), parquet_schema());
+        let hashes = batches[0]
+            .column_by_name("git_hash")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<arrow_array::StringArray>()
+            .unwrap();
+        assert_eq!(hashes.value(0), "abc123");
+        assert!(hashes.is_null(1));
+    }
+
+    #[test]
+    fn test_format_from_file_name() {
+        assert_eq!(Format::from_file_name("metrics.csv"), Some(Format::Csv));
+        assert_eq!(
+            Format::from_file_name("metrics.parquet"),
+            Some(Format::Parquet)
+        );
+        assert_eq!(Format::from_file_name("metrics.json"), None);
+    }
+}
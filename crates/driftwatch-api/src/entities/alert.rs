@@ -4,12 +4,19 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "alert_status")]
 pub enum AlertStatus {
+    /// A regression waiting to reproduce on later reports before it is raised
+    #[sea_orm(string_value = "unconfirmed")]
+    Unconfirmed,
     #[sea_orm(string_value = "active")]
     Active,
     #[sea_orm(string_value = "acknowledged")]
     Acknowledged,
+    #[sea_orm(string_value = "investigating")]
+    Investigating,
     #[sea_orm(string_value = "resolved")]
     Resolved,
+    #[sea_orm(string_value = "wont_fix")]
+    WontFix,
 }
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
@@ -28,6 +35,15 @@ pub struct Model {
     pub baseline_value: f64,
     #[sea_orm(column_name = "current_value")]
     pub current_value: f64,
+    /// Who is triaging the regression
+    #[sea_orm(nullable)]
+    pub assignee: Option<String>,
+    #[sea_orm(column_name = "resolution_note", column_type = "Text", nullable)]
+    pub resolution_note: Option<String>,
+    #[sea_orm(column_name = "github_issue_number", nullable)]
+    pub github_issue_number: Option<i32>,
+    #[sea_orm(column_name = "github_issue_url", nullable)]
+    pub github_issue_url: Option<String>,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
     #[sea_orm(column_name = "updated_at")]
@@ -0,0 +1,68 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What kind of event an annotation marks, so common causes of shifts in
+/// benchmark history can be told apart from free-form notes.
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum AnnotationKind {
+    #[sea_orm(string_value = "note")]
+    Note,
+    #[sea_orm(string_value = "runner_upgrade")]
+    RunnerUpgrade,
+    #[sea_orm(string_value = "dependency_bump")]
+    DependencyBump,
+    #[sea_orm(string_value = "config_change")]
+    ConfigChange,
+}
+
+/// A note attached to a report, or to a point in a project's history when
+/// `report_id` is unset.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "annotations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    #[sea_orm(column_name = "report_id", nullable)]
+    pub report_id: Option<Uuid>,
+    pub kind: AnnotationKind,
+    #[sea_orm(column_type = "Text")]
+    pub note: String,
+    /// The report's creation time for report annotations
+    #[sea_orm(column_name = "occurred_at")]
+    pub occurred_at: DateTimeWithTimeZone,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::report::Entity",
+        from = "Column::ReportId",
+        to = "super::report::Column::Id"
+    )]
+    Report,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Report.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -9,6 +9,12 @@ pub struct Model {
     #[sea_orm(column_name = "project_id")]
     pub project_id: Uuid,
     pub name: String,
+    /// `name` without its trailing numeric segment, e.g. `sort` for
+    /// `sort/1000`; unset for unparameterized benchmarks
+    #[sea_orm(column_name = "base_name", nullable)]
+    pub base_name: Option<String>,
+    /// The trailing numeric segment, e.g. input size
+    pub parameter: Option<f64>,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
     #[sea_orm(column_name = "updated_at")]
@@ -0,0 +1,59 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Spread of the recent values of a benchmark/measure on one testbed,
+/// refreshed periodically from raw metrics.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "benchmark_noise")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    #[sea_orm(column_name = "benchmark_id")]
+    pub benchmark_id: Uuid,
+    #[sea_orm(column_name = "measure_id")]
+    pub measure_id: Uuid,
+    #[sea_orm(column_name = "testbed_id")]
+    pub testbed_id: Uuid,
+    #[sea_orm(column_name = "sample_size")]
+    pub sample_size: i32,
+    pub mean: f64,
+    #[sea_orm(column_name = "std_dev")]
+    pub std_dev: f64,
+    /// Standard deviation as a percentage of the mean
+    #[sea_orm(column_name = "coefficient_of_variation")]
+    pub coefficient_of_variation: f64,
+    #[sea_orm(column_name = "updated_at")]
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::testbed::Entity",
+        from = "Column::TestbedId",
+        to = "super::testbed::Column::Id"
+    )]
+    Testbed,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::testbed::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Testbed.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One line of a project's owners file: benchmarks matching `pattern` belong
+/// to `owners`. Later rules (higher `position`) take precedence.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "benchmark_owners")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    pub position: i32,
+    pub pattern: String,
+    /// Whitespace-separated handles, e.g. `@alice @org/team`
+    #[sea_orm(column_type = "Text")]
+    pub owners: String,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl Model {
+    pub fn owner_list(&self) -> Vec<String> {
+        self.owners.split_whitespace().map(String::from).collect()
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,38 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How stored bytes are compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum ContentEncoding {
+    #[sea_orm(string_value = "identity")]
+    Identity,
+    #[sea_orm(string_value = "gzip")]
+    Gzip,
+    #[sea_orm(string_value = "zstd")]
+    Zstd,
+}
+
+/// A stored file, kept once however many artifacts share its contents.
+/// `ref_count` is maintained by a trigger on `flamegraphs`; blobs nothing
+/// refers to any more are deleted by the storage cleanup.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "blobs")]
+pub struct Model {
+    /// Hex SHA-256 of the file, before compression
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub sha256: String,
+    pub content: Vec<u8>,
+    /// Size of the file, before compression
+    pub size: i64,
+    pub encoding: ContentEncoding,
+    #[sea_orm(column_name = "ref_count")]
+    pub ref_count: i32,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
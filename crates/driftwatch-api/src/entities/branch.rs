@@ -23,6 +23,8 @@ pub enum Relation {
         to = "super::project::Column::Id"
     )]
     Project,
+    #[sea_orm(has_many = "super::branch_alias::Entity")]
+    Aliases,
     #[sea_orm(has_many = "super::report::Entity")]
     Reports,
     #[sea_orm(has_many = "super::threshold::Entity")]
@@ -35,6 +37,12 @@ impl Related<super::project::Entity> for Entity {
     }
 }
 
+impl Related<super::branch_alias::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Aliases.def()
+    }
+}
+
 impl Related<super::report::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Reports.def()
@@ -0,0 +1,55 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// An A/B run of two benchmark commands recorded by `driftwatch ab`, kept
+/// apart from branch history so it never feeds baselines.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "experiments")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    #[sea_orm(nullable)]
+    pub name: Option<String>,
+    #[sea_orm(column_type = "Text")]
+    pub baseline_command: String,
+    #[sea_orm(column_type = "Text")]
+    pub candidate_command: String,
+    #[sea_orm(nullable)]
+    pub git_hash: Option<String>,
+    #[sea_orm(nullable)]
+    pub testbed: Option<String>,
+    /// Times each command was run
+    pub rounds: i32,
+    /// Significance level the verdicts were decided at
+    pub alpha: f64,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(has_many = "super::experiment_result::Entity")]
+    Results,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::experiment_result::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Results.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
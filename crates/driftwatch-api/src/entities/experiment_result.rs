@@ -0,0 +1,56 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How the candidate compared to the baseline; higher values count as
+/// regressions, as they do for alerts.
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum Verdict {
+    #[sea_orm(string_value = "improved")]
+    Improved,
+    #[sea_orm(string_value = "regressed")]
+    Regressed,
+    /// No significant difference
+    #[sea_orm(string_value = "unchanged")]
+    Unchanged,
+    /// Too few samples to test
+    #[sea_orm(string_value = "inconclusive")]
+    Inconclusive,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "experiment_results")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "experiment_id")]
+    pub experiment_id: Uuid,
+    pub benchmark: String,
+    pub measure: String,
+    pub baseline_mean: f64,
+    pub candidate_mean: f64,
+    #[sea_orm(nullable)]
+    pub percent_change: Option<f64>,
+    /// Two-sided p-value of Welch's t-test
+    #[sea_orm(nullable)]
+    pub p_value: Option<f64>,
+    pub verdict: Verdict,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::experiment::Entity",
+        from = "Column::ExperimentId",
+        to = "super::experiment::Column::Id"
+    )]
+    Experiment,
+}
+
+impl Related<super::experiment::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Experiment.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
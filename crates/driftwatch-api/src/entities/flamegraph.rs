@@ -10,9 +10,18 @@ pub struct Model {
     pub report_id: Uuid,
     #[sea_orm(column_name = "benchmark_id", nullable)]
     pub benchmark_id: Option<Uuid>,
-    pub filename: String,
     #[sea_orm(column_name = "storage_path")]
     pub storage_path: String,
+    #[sea_orm(column_name = "file_name")]
+    pub file_name: String,
+    #[sea_orm(column_name = "file_size")]
+    pub file_size: i32,
+    /// Hex SHA-256 of the stored file, the blob holding it
+    #[sea_orm(nullable)]
+    pub sha256: Option<String>,
+    /// Hex SHA-256 of the file as uploaded, before sanitizing
+    #[sea_orm(column_name = "upload_sha256", nullable)]
+    pub upload_sha256: Option<String>,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
 }
@@ -13,8 +13,16 @@ pub struct Model {
     #[sea_orm(column_name = "measure_id")]
     pub measure_id: Uuid,
     pub value: f64,
+    #[sea_orm(column_name = "lower_value")]
     pub lower: Option<f64>,
+    #[sea_orm(column_name = "upper_value")]
     pub upper: Option<f64>,
+    /// Change against its previous run the benchmark harness itself
+    /// reported, in percent (Criterion's `change:` estimate)
+    #[sea_orm(column_name = "reported_change")]
+    pub reported_change: Option<f64>,
+    /// Outlying samples the harness flagged in the measurement
+    pub outliers: Option<i32>,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
 }
@@ -0,0 +1,78 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Latest value and recent deltas per benchmark/measure/branch/testbed,
+/// maintained on report insert so overview pages don't aggregate raw metrics.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "metric_summaries")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    #[sea_orm(column_name = "benchmark_id")]
+    pub benchmark_id: Uuid,
+    #[sea_orm(column_name = "measure_id")]
+    pub measure_id: Uuid,
+    #[sea_orm(column_name = "branch_id")]
+    pub branch_id: Uuid,
+    #[sea_orm(column_name = "testbed_id")]
+    pub testbed_id: Uuid,
+    #[sea_orm(column_name = "latest_metric_id")]
+    pub latest_metric_id: Uuid,
+    #[sea_orm(column_name = "latest_report_id")]
+    pub latest_report_id: Uuid,
+    #[sea_orm(column_name = "latest_value")]
+    pub latest_value: f64,
+    #[sea_orm(column_name = "latest_at")]
+    pub latest_at: DateTimeWithTimeZone,
+    /// Percent change against the last value at least 7 days older
+    #[sea_orm(column_name = "delta_7d", nullable)]
+    pub delta_7d: Option<f64>,
+    #[sea_orm(column_name = "delta_30d", nullable)]
+    pub delta_30d: Option<f64>,
+    #[sea_orm(column_name = "updated_at")]
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::branch::Entity",
+        from = "Column::BranchId",
+        to = "super::branch::Column::Id"
+    )]
+    Branch,
+    #[sea_orm(
+        belongs_to = "super::testbed::Entity",
+        from = "Column::TestbedId",
+        to = "super::testbed::Column::Id"
+    )]
+    Testbed,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::branch::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Branch.def()
+    }
+}
+
+impl Related<super::testbed::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Testbed.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
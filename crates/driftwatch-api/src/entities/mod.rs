@@ -1,22 +1,69 @@
 pub mod alert;
+pub mod annotation;
 pub mod benchmark;
+pub mod benchmark_noise;
+pub mod benchmark_owner;
+pub mod blob;
 pub mod branch;
+pub mod branch_alias;
+pub mod digest;
+pub mod expected_benchmark;
+pub mod experiment;
+pub mod experiment_result;
 pub mod flamegraph;
+pub mod job;
 pub mod measure;
 pub mod metric;
+pub mod metric_summary;
+pub mod notification_channel;
 pub mod project;
+pub mod project_group;
+pub mod project_group_member;
+pub mod pull_request_check;
+pub mod remote_write_rule;
 pub mod report;
+pub mod report_context;
+pub mod report_filter;
+pub mod report_output;
+pub mod report_tag;
+pub mod stale_alert;
 pub mod testbed;
+pub mod testbed_alias;
 pub mod threshold;
+pub mod threshold_evaluation;
+pub mod upload;
 
 pub use alert::Entity as Alert;
+pub use annotation::Entity as Annotation;
 pub use benchmark::Entity as Benchmark;
+pub use benchmark_noise::Entity as BenchmarkNoise;
+pub use benchmark_owner::Entity as BenchmarkOwner;
+pub use blob::Entity as Blob;
 pub use branch::Entity as Branch;
-#[allow(unused)]
+pub use branch_alias::Entity as BranchAlias;
+pub use digest::Entity as Digest;
+pub use expected_benchmark::Entity as ExpectedBenchmark;
+pub use experiment::Entity as Experiment;
+pub use experiment_result::Entity as ExperimentResult;
 pub use flamegraph::Entity as Flamegraph;
+pub use job::Entity as Job;
 pub use measure::Entity as Measure;
 pub use metric::Entity as Metric;
+pub use metric_summary::Entity as MetricSummary;
+pub use notification_channel::Entity as NotificationChannel;
 pub use project::Entity as Project;
+pub use project_group::Entity as ProjectGroup;
+pub use project_group_member::Entity as ProjectGroupMember;
+pub use pull_request_check::Entity as PullRequestCheck;
+pub use remote_write_rule::Entity as RemoteWriteRule;
 pub use report::Entity as Report;
+pub use report_context::Entity as ReportContext;
+pub use report_filter::Entity as ReportFilter;
+pub use report_output::Entity as ReportOutput;
+pub use report_tag::Entity as ReportTag;
+pub use stale_alert::Entity as StaleAlert;
 pub use testbed::Entity as Testbed;
+pub use testbed_alias::Entity as TestbedAlias;
 pub use threshold::Entity as Threshold;
+pub use threshold_evaluation::Entity as ThresholdEvaluation;
+pub use upload::Entity as Upload;
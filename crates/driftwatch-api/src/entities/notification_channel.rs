@@ -0,0 +1,57 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Where a channel's messages are posted and in what shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum ChannelKind {
+    /// JSON POSTed to any URL
+    #[sea_orm(string_value = "webhook")]
+    Webhook,
+    /// A Slack incoming webhook
+    #[sea_orm(string_value = "slack")]
+    Slack,
+}
+
+/// A destination for a project's notifications, such as weekly digests
+/// and alerts.
+/// The URL usually embeds a credential, so it is stored sealed.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "notification_channels")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    pub kind: ChannelKind,
+    #[sea_orm(column_type = "Text")]
+    pub url: String,
+    /// Names of the events posted to the channel, e.g. `digest` or
+    /// `alert.opened`
+    #[sea_orm(column_type = "JsonBinary")]
+    pub events: Json,
+    /// Request body with `{{path}}` placeholders, replacing the channel's
+    /// default payload
+    #[sea_orm(column_type = "Text", nullable)]
+    pub template: Option<String>,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
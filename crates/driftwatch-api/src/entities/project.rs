@@ -1,28 +1,115 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// What evaluation does for benchmarks whose noise exceeds the project's limit
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum NoiseAction {
+    /// Only report them as noisy
+    #[sea_orm(string_value = "flag")]
+    Flag,
+    #[sea_orm(string_value = "widen_thresholds")]
+    WidenThresholds,
+    #[sea_orm(string_value = "require_more_samples")]
+    RequireMoreSamples,
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "projects")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
-    pub user_id: Uuid,
+    /// Unset for anonymous shares, which nobody owns
+    #[sea_orm(nullable)]
+    pub user_id: Option<Uuid>,
     #[sea_orm(unique)]
     pub slug: String,
     pub name: String,
     #[sea_orm(column_type = "Text", nullable)]
     pub description: Option<String>,
     pub public: bool,
+    /// Branch reports submitted for `HEAD` go to
+    pub default_branch: String,
     #[sea_orm(nullable)]
     pub github_repo: Option<String>,
     #[sea_orm(column_type = "Text", nullable)]
     pub github_token: Option<String>,
     pub github_pr_comments: bool,
     pub github_status_checks: bool,
+    /// How often each branch/testbed is expected to report; unset disables
+    /// stale-data alerts
+    #[sea_orm(nullable)]
+    pub expected_cadence_hours: Option<i32>,
+    /// Newline-separated branches checked for stale data; unset checks the
+    /// default branch only
+    #[sea_orm(column_type = "Text", nullable)]
+    pub stale_branches: Option<String>,
+    /// Open a GitHub issue once an alert has fired on this many consecutive
+    /// reports; unset disables issue creation
+    #[sea_orm(nullable)]
+    pub github_issue_after_reports: Option<i32>,
+    /// Only raise an alert once a regression has reproduced on this many
+    /// consecutive reports; unset alerts on the first one
+    #[sea_orm(nullable)]
+    pub alert_after_reports: Option<i32>,
+    /// Coefficient of variation, in percent, above which a benchmark counts
+    /// as noisy; unset disables noise handling
+    #[sea_orm(nullable)]
+    pub noise_cv_limit: Option<f64>,
+    pub noise_action: NoiseAction,
+    /// Newline-separated path globs; a pull request touching any of them
+    /// needs a benchmark report before its status check passes
+    #[sea_orm(column_type = "Text", nullable)]
+    pub benchmark_required_paths: Option<String>,
+    /// Scrub credentials from command output and report metadata before
+    /// storing them
+    pub redact_secrets: bool,
+    /// SHA-256 of the token in the project's alert feed URL; unset when no
+    /// feed has been created
+    #[sea_orm(column_type = "Text", nullable)]
+    pub alert_feed_token_hash: Option<String>,
+    /// When an anonymous share is deleted; unset for regular projects
+    #[sea_orm(nullable)]
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    /// SHA-256 of the token in an anonymous share's URL
+    #[sea_orm(column_type = "Text", nullable)]
+    pub share_token_hash: Option<String>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
 
+impl Model {
+    pub fn required_path_list(&self) -> Vec<String> {
+        self.benchmark_required_paths
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    /// Branches checked for stale data: the configured ones, or else the
+    /// default branch, since feature branches stop reporting once merged
+    pub fn stale_branch_list(&self) -> Vec<String> {
+        let branches: Vec<String> = self
+            .stale_branches
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|b| !b.is_empty())
+            .map(String::from)
+            .collect();
+        if branches.is_empty() {
+            vec![self.default_branch.clone()]
+        } else {
+            branches
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::branch::Entity")]
@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maps Prometheus series sent to a project's remote_write endpoint onto
+/// benchmarks and a measure. `benchmark` and `testbed` are templates in
+/// which `{label}` is replaced by the series' value for that label.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "remote_write_rules")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    /// The series' `__name__`
+    pub metric: String,
+    /// Label values a series must have, as a JSON object
+    #[sea_orm(column_type = "JsonBinary")]
+    pub matchers: Json,
+    #[sea_orm(column_type = "Text")]
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    #[sea_orm(column_type = "Text")]
+    pub testbed: String,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+    /// Windows starting at or before this were reported, and take no more
+    /// samples
+    #[sea_orm(column_name = "flushed_until")]
+    pub flushed_until: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
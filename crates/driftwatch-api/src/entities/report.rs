@@ -1,6 +1,33 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum ReportStatus {
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[sea_orm(string_value = "evaluated")]
+    Evaluated,
+}
+
+/// What triggered the run a report came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum ReportKind {
+    /// A pull request or merge queue run; only part of the baselines of the
+    /// same PR's later runs
+    #[sea_orm(string_value = "pr")]
+    Pr,
+    #[sea_orm(string_value = "push")]
+    Push,
+    #[sea_orm(string_value = "nightly")]
+    Nightly,
+    #[sea_orm(string_value = "release")]
+    Release,
+    #[sea_orm(string_value = "manual")]
+    Manual,
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "reports")]
 pub struct Model {
@@ -16,6 +43,37 @@ pub struct Model {
     pub git_hash: Option<String>,
     #[sea_orm(column_name = "pr_number", nullable)]
     pub pr_number: Option<i32>,
+    #[sea_orm(column_name = "commit_message", column_type = "Text", nullable)]
+    pub commit_message: Option<String>,
+    #[sea_orm(column_name = "commit_author", nullable)]
+    pub commit_author: Option<String>,
+    #[sea_orm(column_name = "committed_at", nullable)]
+    pub committed_at: Option<DateTimeWithTimeZone>,
+    /// Branch the report's pull request targets, compared at the merge-base
+    #[sea_orm(column_name = "base_branch_id", nullable)]
+    pub base_branch_id: Option<Uuid>,
+    #[sea_orm(column_name = "merge_base_hash", nullable)]
+    pub merge_base_hash: Option<String>,
+    /// Commit the benchmarks actually ran on when it isn't `git_hash`, e.g.
+    /// the temporary merge commit of a merge queue entry
+    #[sea_orm(nullable)]
+    pub evaluated_commit: Option<String>,
+    /// Release the report was taken for, e.g. `1.4.0`
+    #[sea_orm(nullable)]
+    pub version: Option<String>,
+    pub kind: ReportKind,
+    /// Exit code of the benchmark command, when the submitter recorded it
+    #[sea_orm(nullable)]
+    pub exit_code: Option<i32>,
+    /// Results of a benchmark command that failed, so some benchmarks may
+    /// have stopped short
+    pub partial: bool,
+    /// False while a chunked upload is still appending metrics
+    pub finalized: bool,
+    /// Left out of baselines, e.g. after a broken runner polluted history
+    pub excluded: bool,
+    /// Pending until the background job has evaluated alerts
+    pub status: ReportStatus,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
 }
@@ -0,0 +1,61 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A named set of report tags and context entries, so a view such as
+/// "nightlies only" can be picked by name.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "report_filters")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    pub name: String,
+    /// Whitespace-separated; reports need every one
+    #[sea_orm(column_type = "Text")]
+    pub tags: String,
+    /// Key/value object; reports need every entry
+    #[sea_orm(column_type = "JsonBinary")]
+    pub context: Json,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(column_name = "updated_at")]
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+impl Model {
+    pub fn tag_list(&self) -> Vec<String> {
+        self.tags.split_whitespace().map(String::from).collect()
+    }
+
+    /// Entries sorted by key
+    pub fn context_entries(&self) -> Vec<(String, String)> {
+        let mut entries: Vec<_> = self
+            .context
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+            .collect();
+        entries.sort();
+        entries
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
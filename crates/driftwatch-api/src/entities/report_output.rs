@@ -0,0 +1,40 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Raw stdout/stderr of the command that produced a report, stored with
+/// secrets redacted, unless the project turned redaction off. The CLI sends
+/// it gzip-compressed; it is kept as zstd.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "report_outputs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false, column_name = "report_id")]
+    pub report_id: Uuid,
+    pub content: Vec<u8>,
+    pub encoding: super::blob::ContentEncoding,
+    /// Size of the output before compression; unknown for outputs stored
+    /// gzip-compressed
+    #[sea_orm(nullable)]
+    pub size: Option<i64>,
+    /// Secrets scrubbed from the output before it was stored
+    pub redactions: i32,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::report::Entity",
+        from = "Column::ReportId",
+        to = "super::report::Column::Id"
+    )]
+    Report,
+}
+
+impl Related<super::report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Report.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
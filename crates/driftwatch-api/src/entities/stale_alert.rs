@@ -0,0 +1,71 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum StaleAlertStatus {
+    #[sea_orm(string_value = "active")]
+    Active,
+    #[sea_orm(string_value = "resolved")]
+    Resolved,
+    /// Silenced by a user; no new alert opens until the branch/testbed
+    /// reports again
+    #[sea_orm(string_value = "dismissed")]
+    Dismissed,
+}
+
+/// Raised when a branch/testbed goes longer than the project's expected
+/// cadence without a report.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "stale_alerts")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    #[sea_orm(column_name = "branch_id")]
+    pub branch_id: Uuid,
+    #[sea_orm(column_name = "testbed_id")]
+    pub testbed_id: Uuid,
+    #[sea_orm(column_name = "last_report_at")]
+    pub last_report_at: DateTimeWithTimeZone,
+    #[sea_orm(column_name = "expected_cadence_hours")]
+    pub expected_cadence_hours: i32,
+    pub status: StaleAlertStatus,
+    #[sea_orm(column_name = "resolved_at", nullable)]
+    pub resolved_at: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(column_name = "updated_at")]
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::branch::Entity",
+        from = "Column::BranchId",
+        to = "super::branch::Column::Id"
+    )]
+    Branch,
+    #[sea_orm(
+        belongs_to = "super::testbed::Entity",
+        from = "Column::TestbedId",
+        to = "super::testbed::Column::Id"
+    )]
+    Testbed,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
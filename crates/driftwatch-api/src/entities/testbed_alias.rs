@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Another name reports may use for a testbed, e.g. `linux-x86_64` for the
+/// `linux` testbed older CLIs reported to by default.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "testbed_aliases")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    pub alias: String,
+    #[sea_orm(column_name = "testbed_id")]
+    pub testbed_id: Uuid,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::testbed::Entity",
+        from = "Column::TestbedId",
+        to = "super::testbed::Column::Id"
+    )]
+    Testbed,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::testbed::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Testbed.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
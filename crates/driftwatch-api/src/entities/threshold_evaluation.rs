@@ -0,0 +1,89 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum Verdict {
+    /// Outside a boundary; an alert was raised
+    #[sea_orm(string_value = "violated")]
+    Violated,
+    #[sea_orm(string_value = "passed")]
+    Passed,
+    /// Fewer baseline values than the threshold's minimum sample size
+    #[sea_orm(string_value = "insufficient_history")]
+    InsufficientHistory,
+}
+
+/// One threshold checked against one metric of a report, kept so an alert,
+/// or its absence, can be explained later. Re-evaluating a report adds new
+/// rows rather than replacing them.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "threshold_evaluations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub report_id: Uuid,
+    pub metric_id: Uuid,
+    pub threshold_id: Uuid,
+    pub verdict: Verdict,
+    /// Mean of the baseline values; none without any history
+    #[sea_orm(nullable)]
+    pub baseline: Option<f64>,
+    /// How many values the baseline was computed from
+    pub baseline_size: i32,
+    /// Whether the baseline is the base branch at the merge-base
+    pub pinned: bool,
+    #[sea_orm(nullable)]
+    pub percent_change: Option<f64>,
+    /// Boundaries as applied, after any noise adjustment
+    #[sea_orm(nullable)]
+    pub upper_boundary: Option<f64>,
+    #[sea_orm(nullable)]
+    pub lower_boundary: Option<f64>,
+    pub min_sample_size: i32,
+    /// The benchmark was above the project's noise limit
+    pub noise_adjusted: bool,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::report::Entity",
+        from = "Column::ReportId",
+        to = "super::report::Column::Id"
+    )]
+    Report,
+    #[sea_orm(
+        belongs_to = "super::metric::Entity",
+        from = "Column::MetricId",
+        to = "super::metric::Column::Id"
+    )]
+    Metric,
+    #[sea_orm(
+        belongs_to = "super::threshold::Entity",
+        from = "Column::ThresholdId",
+        to = "super::threshold::Column::Id"
+    )]
+    Threshold,
+}
+
+impl Related<super::report::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Report.def()
+    }
+}
+
+impl Related<super::metric::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Metric.def()
+    }
+}
+
+impl Related<super::threshold::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Threshold.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A file uploaded in chunks through `/uploads/{token}`. `content` holds the
+/// bytes received so far; the upload is complete once the file it belongs to
+/// (e.g. a flamegraph) has been confirmed against it.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "uploads")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    /// Secret part of the upload URL; whoever holds it may write the file
+    pub token: String,
+    #[sea_orm(column_name = "file_name")]
+    pub file_name: String,
+    pub content: Vec<u8>,
+    /// Declared by the first chunk's `Content-Range`
+    #[sea_orm(column_name = "total_size", nullable)]
+    pub total_size: Option<i64>,
+    #[sea_orm(column_name = "created_at")]
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(column_name = "completed_at", nullable)]
+    pub completed_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,99 @@
+//! Optional error reporting to Sentry, or anything that accepts its
+//! protocol such as GlitchTip or a self-hosted Sentry.
+//!
+//! Set `SENTRY_DSN` to enable it. Panics, including ones in resolvers, are
+//! reported with the request ID of the request that hit them; background
+//! failures are reported through [`capture`] with their source and context.
+//! Without a DSN every call here is a no-op.
+
+use anyhow::{Context, Result};
+use sentry::integrations::anyhow::capture_anyhow;
+use sentry::types::Dsn;
+
+use crate::config::Config;
+use crate::request_log::RequestAttribution;
+
+/// Starts the client. Keep the guard alive for as long as the server runs;
+/// dropping it flushes pending reports.
+pub fn init(config: &Config) -> Result<Option<sentry::ClientInitGuard>> {
+    let Some(dsn) = config.sentry_dsn.as_deref() else {
+        return Ok(None);
+    };
+    let dsn: Dsn = dsn.parse().context("Invalid SENTRY_DSN")?;
+    let guard = sentry::init(sentry::ClientOptions {
+        dsn: Some(dsn),
+        release: sentry::release_name!(),
+        environment: config.sentry_environment.clone().map(Into::into),
+        // Reports carry user ids, never tokens or IP addresses
+        send_default_pii: false,
+        ..Default::default()
+    });
+    tracing::info!("Reporting errors to Sentry");
+    Ok(Some(guard))
+}
+
+/// Adds the GraphQL operation, project and user to reports from the current
+/// request. The request ID is added by `request_log::middleware`.
+pub fn tag_request(attribution: &RequestAttribution) {
+    sentry::configure_scope(|scope| {
+        if let Some(operation) = &attribution.operation {
+            scope.set_tag("operation", operation);
+        }
+        if let Some(project) = &attribution.project {
+            scope.set_tag("project", project);
+        }
+        scope.set_user(attribution.user_id.map(|id| sentry::User {
+            id: Some(id.to_string()),
+            ..Default::default()
+        }));
+    });
+}
+
+/// Reports a failure from outside a request, e.g. a job or a GitHub
+/// delivery. `source` groups reports by where they come from, and each
+/// `context` pair is attached as a tag.
+pub fn capture(error: &anyhow::Error, source: &str, context: &[(&str, String)]) {
+    sentry::with_scope(
+        |scope| {
+            scope.set_tag("source", source);
+            for (key, value) in context {
+                scope.set_tag(key, value);
+            }
+        },
+        || capture_anyhow(error),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sentry::test::with_captured_events;
+
+    #[test]
+    fn test_capture_attaches_source_and_context() {
+        let events = with_captured_events(|| {
+            let error = anyhow::anyhow!("connection reset").context("Failed to set status");
+            capture(
+                &error,
+                "github",
+                &[
+                    ("repo", "acme/widgets".to_string()),
+                    ("pr", "7".to_string()),
+                ],
+            );
+        });
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.tags["source"], "github");
+        assert_eq!(event.tags["repo"], "acme/widgets");
+        assert_eq!(event.tags["pr"], "7");
+        let values: Vec<_> = event
+            .exception
+            .values
+            .iter()
+            .filter_map(|e| e.value.as_deref())
+            .collect();
+        assert!(values.contains(&"connection reset"), "{:?}", values);
+    }
+}
@@ -0,0 +1,890 @@
+use std::collections::{BTreeSet, HashMap};
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Set, Statement, Value,
+};
+use uuid::Uuid;
+
+use crate::entities::threshold_evaluation::{self, Verdict};
+use crate::entities::{self, alert, metric, project, report, threshold};
+use crate::{events, noise};
+
+/// Number of most recent historical metrics used to compute a baseline.
+pub const BASELINE_WINDOW: u64 = 30;
+
+/// Changes within this many percent of the baseline count as unchanged when
+/// summarizing a report.
+pub const DEFAULT_CHANGE_TOLERANCE: f64 = 5.0;
+
+/// Statuses that end a streak of consecutive alerts: a dismissed or fixed
+/// regression neither confirms a new one nor opens an issue.
+pub const CLOSED_STATUSES: [alert::AlertStatus; 2] =
+    [alert::AlertStatus::Resolved, alert::AlertStatus::WontFix];
+
+/// How a report's benchmarks moved relative to their baselines.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ComparisonSummary {
+    pub total: usize,
+    pub improved: usize,
+    pub regressed: usize,
+    pub unchanged: usize,
+    /// Metrics with no history to compare against
+    pub without_baseline: usize,
+    /// Largest increase over baseline, in percent
+    pub max_regression: Option<f64>,
+}
+
+/// Outcome of comparing one metric against one threshold.
+#[derive(Debug, Clone)]
+pub struct ThresholdCheck {
+    /// The threshold as evaluated, after any noise adjustment
+    pub threshold: threshold::Model,
+    /// Index into the metrics that were checked
+    pub metric_index: usize,
+    pub baseline: f64,
+    pub percent_change: f64,
+    pub violated: bool,
+    /// Values the baseline was computed from
+    pub sample_size: usize,
+    /// Whether the baseline is the base branch at the merge-base
+    pub pinned: bool,
+    pub noise_adjusted: bool,
+}
+
+/// A threshold left unchecked because the metric had too little history.
+#[derive(Debug, Clone)]
+pub struct SkippedCheck {
+    pub threshold: threshold::Model,
+    pub metric_index: usize,
+    pub sample_size: usize,
+    pub noise_adjusted: bool,
+}
+
+/// Checks every metric of a freshly inserted report against the project's
+/// thresholds and persists an alert for each boundary violation. When the
+/// project requires confirmation, a violation stays unconfirmed until it has
+/// reproduced on enough consecutive reports.
+pub async fn evaluate_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<Vec<alert::Model>, DbErr> {
+    let Some(project) = entities::Project::find_by_id(report.project_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(Vec::new());
+    };
+    let confirm_after = project
+        .alert_after_reports
+        .filter(|n| *n > 1)
+        .map(|n| n as u64);
+
+    let (checks, skipped) = evaluate_thresholds(db, &project, report, metrics).await?;
+    record_evaluations(db, report, metrics, &checks, &skipped).await?;
+
+    let mut alerts = Vec::new();
+    for check in checks.iter().filter(|c| c.violated) {
+        alerts.push(raise_alert(db, check, &metrics[check.metric_index], confirm_after).await?);
+    }
+
+    Ok(alerts)
+}
+
+/// Stores an alert for a violated check, unconfirmed until it has reproduced
+/// on `confirm_after` consecutive reports when the project requires that.
+async fn raise_alert<C: ConnectionTrait>(
+    db: &C,
+    check: &ThresholdCheck,
+    metric: &metric::Model,
+    confirm_after: Option<u64>,
+) -> Result<alert::Model, DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    let alert = alert::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        threshold_id: Set(check.threshold.id),
+        metric_id: Set(metric.id),
+        status: Set(if confirm_after.is_some() {
+            alert::AlertStatus::Unconfirmed
+        } else {
+            alert::AlertStatus::Active
+        }),
+        percent_change: Set(check.percent_change),
+        baseline_value: Set(check.baseline),
+        current_value: Set(metric.value),
+        assignee: Set(None),
+        resolution_note: Set(None),
+        github_issue_number: Set(None),
+        github_issue_url: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await?;
+
+    let alert = match confirm_after {
+        Some(n) if alert_streak(db, &alert, n).await?.is_some() => {
+            let mut active: alert::ActiveModel = alert.into();
+            active.status = Set(alert::AlertStatus::Active);
+            active.update(db).await?
+        }
+        _ => alert,
+    };
+    if alert.status == alert::AlertStatus::Active {
+        events::alert_opened(db, alert.id).await?;
+    }
+    Ok(alert)
+}
+
+/// What re-running a report's evaluation changed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Reevaluation {
+    /// Violations without an alert yet
+    pub raised: u64,
+    /// Open alerts that still violate, with their baseline refreshed
+    pub updated: u64,
+    /// Open alerts that no longer violate
+    pub resolved: u64,
+}
+
+/// Checks an already evaluated report against the project's current
+/// thresholds and baselines. Open alerts are refreshed or resolved and new
+/// violations raise alerts. Closed alerts are left alone, so a triaged
+/// regression isn't raised again.
+pub async fn reevaluate_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+) -> Result<Reevaluation, DbErr> {
+    let Some(project) = entities::Project::find_by_id(report.project_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(Reevaluation::default());
+    };
+    let confirm_after = project
+        .alert_after_reports
+        .filter(|n| *n > 1)
+        .map(|n| n as u64);
+
+    let metrics = entities::Metric::find()
+        .filter(metric::Column::ReportId.eq(report.id))
+        .all(db)
+        .await?;
+    let existing: Vec<alert::Model> = entities::Alert::find()
+        .inner_join(entities::Metric)
+        .filter(metric::Column::ReportId.eq(report.id))
+        .all(db)
+        .await?;
+    let (checks, skipped) = evaluate_thresholds(db, &project, report, &metrics).await?;
+    record_evaluations(db, report, &metrics, &checks, &skipped).await?;
+    let mut outcome = Reevaluation::default();
+
+    for alert in existing
+        .iter()
+        .filter(|a| !CLOSED_STATUSES.contains(&a.status))
+    {
+        let check = checks.iter().find(|c| {
+            c.threshold.id == alert.threshold_id && metrics[c.metric_index].id == alert.metric_id
+        });
+
+        let mut active: alert::ActiveModel = alert.clone().into();
+        match check {
+            Some(check) if check.violated => {
+                active.baseline_value = Set(check.baseline);
+                active.percent_change = Set(check.percent_change);
+                outcome.updated += 1;
+            }
+            _ => {
+                active.status = Set(alert::AlertStatus::Resolved);
+                active.resolution_note = Set(Some(
+                    "No longer a regression when re-evaluated against current thresholds"
+                        .to_string(),
+                ));
+                outcome.resolved += 1;
+            }
+        }
+        active.updated_at = Set(chrono::Utc::now().fixed_offset());
+        let alert = active.update(db).await?;
+        if alert.status == alert::AlertStatus::Resolved {
+            events::alert_resolved(db, alert.id).await?;
+        }
+    }
+
+    for check in checks.iter().filter(|c| c.violated) {
+        let metric = &metrics[check.metric_index];
+        let alerted = existing
+            .iter()
+            .any(|a| a.threshold_id == check.threshold.id && a.metric_id == metric.id);
+        if !alerted {
+            raise_alert(db, check, metric, confirm_after).await?;
+            outcome.raised += 1;
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Compares each metric against every threshold that applies to the report's
+/// branch, testbed and the metric's measure, without writing anything. The
+/// report doesn't have to be stored, which lets hypothetical results be
+/// tested. Benchmarks above the project's noise limit are checked against
+/// thresholds adjusted for their noise. Pairs without enough history for a
+/// baseline are left out.
+pub async fn check_thresholds<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<Vec<ThresholdCheck>, DbErr> {
+    Ok(evaluate_thresholds(db, project, report, metrics).await?.0)
+}
+
+/// Like [`check_thresholds`], also returning the pairs that were left out for
+/// lack of history.
+pub async fn evaluate_thresholds<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<(Vec<ThresholdCheck>, Vec<SkippedCheck>), DbErr> {
+    let thresholds = entities::Threshold::find()
+        .filter(threshold::Column::ProjectId.eq(report.project_id))
+        .filter(
+            Condition::any()
+                .add(threshold::Column::BranchId.is_null())
+                .add(threshold::Column::BranchId.eq(report.branch_id)),
+        )
+        .filter(
+            Condition::any()
+                .add(threshold::Column::TestbedId.is_null())
+                .add(threshold::Column::TestbedId.eq(report.testbed_id)),
+        )
+        .all(db)
+        .await?;
+
+    if thresholds.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let noisy = noise::noisy_series(db, project, report.testbed_id).await?;
+    let baselines = baselines(db, report, metrics).await?;
+    let mut checks = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (metric_index, metric) in metrics.iter().enumerate() {
+        for threshold in thresholds
+            .iter()
+            .filter(|t| t.measure_id == metric.measure_id)
+        {
+            let noise = noisy.get(&(metric.benchmark_id, metric.measure_id));
+            let threshold = match noise {
+                Some(cv) => noise::adjust_for_noise(threshold, *cv, &project.noise_action),
+                None => threshold.clone(),
+            };
+            let baseline = baselines.get(&(metric.benchmark_id, metric.measure_id));
+            let sample_size = baseline.map_or(0, |b| b.sample_size);
+
+            // A merge-base baseline is a deliberate single point of comparison,
+            // so the rolling-window sample size requirement does not apply
+            let too_few = baseline.is_some_and(|b| !b.pinned)
+                && (sample_size as i32) < threshold.min_sample_size;
+            let Some(baseline) = baseline.filter(|_| !too_few) else {
+                skipped.push(SkippedCheck {
+                    threshold,
+                    metric_index,
+                    sample_size,
+                    noise_adjusted: noise.is_some(),
+                });
+                continue;
+            };
+            let Some(percent_change) = percent_change(baseline.mean, metric.value) else {
+                continue;
+            };
+
+            checks.push(ThresholdCheck {
+                violated: violates(&threshold, percent_change),
+                threshold,
+                metric_index,
+                baseline: baseline.mean,
+                percent_change,
+                sample_size,
+                pinned: baseline.pinned,
+                noise_adjusted: noise.is_some(),
+            });
+        }
+    }
+
+    Ok((checks, skipped))
+}
+
+/// Stores how every threshold was checked against the report, see
+/// [`threshold_evaluation::Model`].
+pub async fn record_evaluations<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    metrics: &[metric::Model],
+    checks: &[ThresholdCheck],
+    skipped: &[SkippedCheck],
+) -> Result<(), DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    let checked = checks
+        .iter()
+        .map(|check| threshold_evaluation::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            report_id: Set(report.id),
+            metric_id: Set(metrics[check.metric_index].id),
+            threshold_id: Set(check.threshold.id),
+            verdict: Set(if check.violated {
+                Verdict::Violated
+            } else {
+                Verdict::Passed
+            }),
+            baseline: Set(Some(check.baseline)),
+            baseline_size: Set(check.sample_size as i32),
+            pinned: Set(check.pinned),
+            percent_change: Set(Some(check.percent_change)),
+            upper_boundary: Set(check.threshold.upper_boundary),
+            lower_boundary: Set(check.threshold.lower_boundary),
+            min_sample_size: Set(check.threshold.min_sample_size),
+            noise_adjusted: Set(check.noise_adjusted),
+            created_at: Set(now),
+        });
+    let skipped = skipped
+        .iter()
+        .map(|skip| threshold_evaluation::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            report_id: Set(report.id),
+            metric_id: Set(metrics[skip.metric_index].id),
+            threshold_id: Set(skip.threshold.id),
+            verdict: Set(Verdict::InsufficientHistory),
+            baseline: Set(None),
+            baseline_size: Set(skip.sample_size as i32),
+            pinned: Set(false),
+            percent_change: Set(None),
+            upper_boundary: Set(skip.threshold.upper_boundary),
+            lower_boundary: Set(skip.threshold.lower_boundary),
+            min_sample_size: Set(skip.threshold.min_sample_size),
+            noise_adjusted: Set(skip.noise_adjusted),
+            created_at: Set(now),
+        });
+
+    let rows: Vec<_> = checked.chain(skipped).collect();
+    if !rows.is_empty() {
+        entities::ThresholdEvaluation::insert_many(rows)
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// The open alerts for the same threshold and series over the last `n`
+/// reports on the alert's branch and testbed, if every one of those reports
+/// raised one.
+pub async fn alert_streak<C: ConnectionTrait>(
+    db: &C,
+    alert: &alert::Model,
+    n: u64,
+) -> Result<Option<Vec<alert::Model>>, DbErr> {
+    let Some((metric, Some(report))) = entities::Metric::find_by_id(alert.metric_id)
+        .find_also_related(entities::Report)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let report_ids: Vec<Uuid> = entities::Report::find()
+        .select_only()
+        .column(report::Column::Id)
+        .filter(report::Column::BranchId.eq(report.branch_id))
+        .filter(report::Column::TestbedId.eq(report.testbed_id))
+        .filter(report::Column::Finalized.eq(true))
+        .filter(report::Column::CreatedAt.lte(report.created_at))
+        .order_by_desc(report::Column::CreatedAt)
+        .limit(n)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    if (report_ids.len() as u64) < n {
+        return Ok(None);
+    }
+
+    let streak: Vec<(alert::Model, Option<metric::Model>)> = entities::Alert::find()
+        .find_also_related(entities::Metric)
+        .filter(alert::Column::ThresholdId.eq(alert.threshold_id))
+        .filter(alert::Column::Status.is_not_in(CLOSED_STATUSES))
+        .filter(metric::Column::ReportId.is_in(report_ids.clone()))
+        .filter(metric::Column::BenchmarkId.eq(metric.benchmark_id))
+        .filter(metric::Column::MeasureId.eq(metric.measure_id))
+        .order_by_asc(alert::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let alerting_reports = report_ids
+        .iter()
+        .filter(|id| {
+            streak
+                .iter()
+                .any(|(_, m)| m.as_ref().map(|m| m.report_id) == Some(**id))
+        })
+        .count();
+    if alerting_reports < report_ids.len() {
+        return Ok(None);
+    }
+
+    Ok(Some(streak.into_iter().map(|(a, _)| a).collect()))
+}
+
+/// Re-checks the open alerts of reports whose baselines could include
+/// `changed`, after it was excluded from or restored to baselines. Alerts
+/// that still violate their threshold get the new baseline and change;
+/// the others are resolved. Returns the number of alerts updated.
+pub async fn reevaluate_later_reports<C: ConnectionTrait>(
+    db: &C,
+    changed: &report::Model,
+) -> Result<u64, DbErr> {
+    let Some(project) = entities::Project::find_by_id(changed.project_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(0);
+    };
+
+    let reports = entities::Report::find()
+        .filter(report::Column::ProjectId.eq(changed.project_id))
+        .filter(report::Column::TestbedId.eq(changed.testbed_id))
+        .filter(
+            Condition::any()
+                .add(report::Column::BranchId.eq(changed.branch_id))
+                .add(report::Column::BaseBranchId.eq(changed.branch_id)),
+        )
+        .filter(report::Column::Id.ne(changed.id))
+        .filter(report::Column::Finalized.eq(true))
+        .filter(report::Column::CreatedAt.gt(changed.created_at))
+        .order_by_asc(report::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let mut updated = 0;
+    for report in reports {
+        let open: Vec<alert::Model> = entities::Alert::find()
+            .inner_join(entities::Metric)
+            .filter(metric::Column::ReportId.eq(report.id))
+            .filter(alert::Column::Status.is_not_in(CLOSED_STATUSES))
+            .all(db)
+            .await?;
+        if open.is_empty() {
+            continue;
+        }
+
+        let metrics = entities::Metric::find()
+            .filter(metric::Column::ReportId.eq(report.id))
+            .all(db)
+            .await?;
+        let checks = check_thresholds(db, &project, &report, &metrics).await?;
+
+        for alert in open {
+            let check = checks.iter().find(|c| {
+                c.threshold.id == alert.threshold_id
+                    && metrics[c.metric_index].id == alert.metric_id
+            });
+
+            let mut active: alert::ActiveModel = alert.into();
+            match check {
+                Some(check) if check.violated => {
+                    active.baseline_value = Set(check.baseline);
+                    active.percent_change = Set(check.percent_change);
+                }
+                _ => {
+                    active.status = Set(alert::AlertStatus::Resolved);
+                    active.resolution_note = Set(Some(format!(
+                        "No longer a regression after baselines changed for report {}",
+                        changed.id
+                    )));
+                }
+            }
+            active.updated_at = Set(chrono::Utc::now().fixed_offset());
+            let alert = active.update(db).await?;
+            if alert.status == alert::AlertStatus::Resolved {
+                events::alert_resolved(db, alert.id).await?;
+            }
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Percent change of every metric in the report against its baseline, `None`
+/// where no baseline exists.
+pub async fn compare_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<Vec<Option<f64>>, DbErr> {
+    let baselines = baselines(db, report, metrics).await?;
+    Ok(metrics
+        .iter()
+        .map(|metric| {
+            baselines
+                .get(&(metric.benchmark_id, metric.measure_id))
+                .and_then(|baseline| percent_change(baseline.mean, metric.value))
+        })
+        .collect())
+}
+
+/// Classifies percent changes; higher values are treated as regressions, the
+/// same direction as threshold upper boundaries.
+pub fn summarize_changes(changes: &[Option<f64>], tolerance: f64) -> ComparisonSummary {
+    let mut summary = ComparisonSummary {
+        total: changes.len(),
+        ..Default::default()
+    };
+
+    for change in changes {
+        match *change {
+            None => summary.without_baseline += 1,
+            Some(c) if c > tolerance => {
+                summary.regressed += 1;
+                summary.max_regression = Some(summary.max_regression.map_or(c, |m| m.max(c)));
+            }
+            Some(c) if c < -tolerance => summary.improved += 1,
+            Some(_) => summary.unchanged += 1,
+        }
+    }
+
+    summary
+}
+
+/// Mean of the historical values a metric is compared against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Baseline {
+    pub mean: f64,
+    pub sample_size: usize,
+    /// Whether the values are the base branch at the merge-base
+    pub pinned: bool,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct BaselineRow {
+    benchmark_id: Uuid,
+    measure_id: Uuid,
+    sample_size: i64,
+    mean: f64,
+}
+
+/// Benchmark/measure pairs per baseline query, two bind parameters each.
+const BASELINE_CHUNK_SIZE: usize = 10_000;
+
+/// Baselines of every benchmark/measure in `metrics`, keyed by benchmark and
+/// measure. Pairs without history are left out.
+///
+/// Reports that target a base branch are compared against the base branch's
+/// reports at the merge-base commit, so commits landing on the base branch
+/// after the PR was opened don't show up as regressions. When no report
+/// exists for the merge-base, the base branch's recent history is used.
+/// Other reports use the recent history of their own branch.
+pub async fn baselines<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<HashMap<(Uuid, Uuid), Baseline>, DbErr> {
+    let pairs: Vec<(Uuid, Uuid)> = metrics
+        .iter()
+        .map(|m| (m.benchmark_id, m.measure_id))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let Some(base_branch_id) = report.base_branch_id else {
+        return branch_baselines(db, report, report.branch_id, &pairs, None).await;
+    };
+
+    let mut baselines = HashMap::new();
+    if let Some(merge_base) = &report.merge_base_hash {
+        baselines = branch_baselines(db, report, base_branch_id, &pairs, Some(merge_base)).await?;
+    }
+    let rest: Vec<(Uuid, Uuid)> = pairs
+        .into_iter()
+        .filter(|pair| !baselines.contains_key(pair))
+        .collect();
+    if !rest.is_empty() {
+        baselines.extend(branch_baselines(db, report, base_branch_id, &rest, None).await?);
+    }
+    Ok(baselines)
+}
+
+/// Aggregates the last [`BASELINE_WINDOW`] values of each pair on
+/// `branch_id` and the report's testbed, leaving out reports excluded from
+/// baselines. With a `merge_base` only that commit's reports count and the
+/// baselines are pinned. Otherwise the history before the report is used,
+/// leaving out the report itself; PR reports only count toward later runs of
+/// the same PR, so a PR run submitted to `main` doesn't move main's baseline.
+async fn branch_baselines<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    branch_id: Uuid,
+    pairs: &[(Uuid, Uuid)],
+    merge_base: Option<&str>,
+) -> Result<HashMap<(Uuid, Uuid), Baseline>, DbErr> {
+    let mut baselines = HashMap::new();
+
+    for chunk in pairs.chunks(BASELINE_CHUNK_SIZE) {
+        let mut values: Vec<Value> = vec![
+            branch_id.into(),
+            report.testbed_id.into(),
+            (BASELINE_WINDOW as i64).into(),
+        ];
+        let scope = match merge_base {
+            Some(hash) => {
+                values.push(hash.into());
+                "r.git_hash = $4 AND r.kind <> 'pr'"
+            }
+            None => {
+                let own_pr = report
+                    .pr_number
+                    .filter(|_| report.kind == report::ReportKind::Pr);
+                values.extend([report.id.into(), report.created_at.into(), own_pr.into()]);
+                // Metrics share their report's timestamp; filtering on the
+                // metric column lets partitioned tables skip old chunks
+                "r.id <> $4 AND m.created_at < $5 AND (r.kind <> 'pr' OR r.pr_number = $6)"
+            }
+        };
+        let mut placeholders = Vec::with_capacity(chunk.len());
+        for (benchmark_id, measure_id) in chunk {
+            values.push((*benchmark_id).into());
+            values.push((*measure_id).into());
+            placeholders.push(format!("(${}, ${})", values.len() - 1, values.len()));
+        }
+
+        let sql = format!(
+            r#"SELECT benchmark_id, measure_id, COUNT(*) AS sample_size,
+                      AVG(value) AS mean
+               FROM (
+                   SELECT m.benchmark_id, m.measure_id, m.value,
+                          ROW_NUMBER() OVER (
+                              PARTITION BY m.benchmark_id, m.measure_id
+                              ORDER BY m.created_at DESC
+                          ) AS recency
+                   FROM metrics m
+                   JOIN reports r ON r.id = m.report_id
+                   WHERE r.branch_id = $1 AND r.testbed_id = $2
+                     AND r.finalized AND NOT r.excluded AND {}
+                     AND (m.benchmark_id, m.measure_id) IN ({})
+               ) recent
+               WHERE recency <= $3
+               GROUP BY benchmark_id, measure_id"#,
+            scope,
+            placeholders.join(", ")
+        );
+        let rows = BaselineRow::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            values,
+        ))
+        .all(db)
+        .await?;
+
+        baselines.extend(rows.into_iter().map(|row| {
+            (
+                (row.benchmark_id, row.measure_id),
+                Baseline {
+                    mean: row.mean,
+                    sample_size: row.sample_size as usize,
+                    pinned: merge_base.is_some(),
+                },
+            )
+        }));
+    }
+
+    Ok(baselines)
+}
+
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+pub fn percent_change(baseline: f64, current: f64) -> Option<f64> {
+    if baseline == 0.0 {
+        return None;
+    }
+    Some((current - baseline) / baseline * 100.0)
+}
+
+/// Boundaries are expressed as percentages: an upper boundary of 10.0 fires
+/// when the value rises more than 10% above baseline, a lower boundary of
+/// 10.0 when it drops more than 10% below.
+pub fn violates(threshold: &threshold::Model, percent_change: f64) -> bool {
+    let above = threshold
+        .upper_boundary
+        .is_some_and(|upper| percent_change > upper);
+    let below = threshold
+        .lower_boundary
+        .is_some_and(|lower| percent_change < -lower);
+    above || below
+}
+
+fn describe_boundaries(upper: Option<f64>, lower: Option<f64>) -> String {
+    match (upper, lower) {
+        (Some(upper), Some(lower)) => format!("+{:.2}% / -{:.2}%", upper, lower),
+        (Some(upper), None) => format!("+{:.2}%", upper),
+        (None, Some(lower)) => format!("-{:.2}%", lower),
+        (None, None) => "no boundaries".to_string(),
+    }
+}
+
+/// One-line account of a stored threshold check, e.g. `+12.50% against a
+/// baseline of 100.00 (mean of 30 earlier values): outside +10.00%`.
+pub fn explain(evaluation: &threshold_evaluation::Model) -> String {
+    let mut explanation = match (evaluation.baseline, evaluation.percent_change) {
+        (Some(baseline), Some(change)) if evaluation.verdict != Verdict::InsufficientHistory => {
+            let source = if evaluation.pinned {
+                "the base branch at the merge-base".to_string()
+            } else if evaluation.baseline_size == 1 {
+                "1 earlier value".to_string()
+            } else {
+                format!("mean of {} earlier values", evaluation.baseline_size)
+            };
+            let side = match evaluation.verdict {
+                Verdict::Violated => "outside",
+                _ => "within",
+            };
+            format!(
+                "{:+.2}% against a baseline of {:.2} ({}): {} {}",
+                change,
+                baseline,
+                source,
+                side,
+                describe_boundaries(evaluation.upper_boundary, evaluation.lower_boundary)
+            )
+        }
+        _ => format!(
+            "Not checked: {} earlier value(s), the threshold needs {}",
+            evaluation.baseline_size, evaluation.min_sample_size
+        ),
+    };
+    if evaluation.noise_adjusted {
+        explanation.push_str(" (adjusted for a noisy benchmark)");
+    }
+    explanation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threshold(upper: Option<f64>, lower: Option<f64>) -> threshold::Model {
+        let now = chrono::Utc::now().fixed_offset();
+        threshold::Model {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            measure_id: Uuid::new_v4(),
+            branch_id: None,
+            testbed_id: None,
+            upper_boundary: upper,
+            lower_boundary: lower,
+            min_sample_size: 2,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_percent_change() {
+        assert_eq!(percent_change(100.0, 110.0), Some(10.0));
+        assert_eq!(percent_change(100.0, 90.0), Some(-10.0));
+        assert_eq!(percent_change(0.0, 10.0), None);
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(mean(&[]), None);
+        assert_eq!(mean(&[1.0, 2.0, 3.0]), Some(2.0));
+    }
+
+    #[test]
+    fn test_summarize_changes() {
+        let changes = [
+            Some(12.0),
+            Some(30.0),
+            Some(-8.0),
+            Some(4.0),
+            Some(-5.0),
+            None,
+        ];
+        let summary = summarize_changes(&changes, 5.0);
+        assert_eq!(
+            summary,
+            ComparisonSummary {
+                total: 6,
+                improved: 1,
+                regressed: 2,
+                unchanged: 2,
+                without_baseline: 1,
+                max_regression: Some(30.0),
+            }
+        );
+        assert_eq!(summarize_changes(&[], 5.0), ComparisonSummary::default());
+    }
+
+    #[test]
+    fn test_violates_boundaries() {
+        let t = threshold(Some(10.0), Some(5.0));
+        assert!(violates(&t, 10.5));
+        assert!(!violates(&t, 10.0));
+        assert!(violates(&t, -5.5));
+        assert!(!violates(&t, -4.0));
+
+        let upper_only = threshold(Some(10.0), None);
+        assert!(!violates(&upper_only, -50.0));
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut evaluation = threshold_evaluation::Model {
+            id: Uuid::new_v4(),
+            report_id: Uuid::new_v4(),
+            metric_id: Uuid::new_v4(),
+            threshold_id: Uuid::new_v4(),
+            verdict: Verdict::Violated,
+            baseline: Some(100.0),
+            baseline_size: 30,
+            pinned: false,
+            percent_change: Some(12.5),
+            upper_boundary: Some(10.0),
+            lower_boundary: None,
+            min_sample_size: 2,
+            noise_adjusted: false,
+            created_at: chrono::Utc::now().fixed_offset(),
+        };
+        assert_eq!(
+            explain(&evaluation),
+            "+12.50% against a baseline of 100.00 (mean of 30 earlier values): outside +10.00%"
+        );
+
+        evaluation.verdict = Verdict::Passed;
+        evaluation.pinned = true;
+        evaluation.percent_change = Some(-3.0);
+        evaluation.lower_boundary = Some(5.0);
+        evaluation.noise_adjusted = true;
+        assert_eq!(
+            explain(&evaluation),
+            "-3.00% against a baseline of 100.00 (the base branch at the merge-base): \
+             within +10.00% / -5.00% (adjusted for a noisy benchmark)"
+        );
+
+        evaluation.verdict = Verdict::InsufficientHistory;
+        evaluation.baseline = None;
+        evaluation.percent_change = None;
+        evaluation.baseline_size = 1;
+        evaluation.noise_adjusted = false;
+        assert_eq!(
+            explain(&evaluation),
+            "Not checked: 1 earlier value(s), the threshold needs 2"
+        );
+    }
+}
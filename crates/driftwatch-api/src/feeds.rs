@@ -0,0 +1,290 @@
+//! Atom feeds of a project's alerts, for feed readers, chat bots and other
+//! tools that can poll a URL but can't receive webhooks.
+//!
+//! Feed readers can't send an `Authorization` header, so each feed URL
+//! carries its own token, `/feeds/{token}/alerts.atom`, like a signed URL.
+//! Only the token's SHA-256 is stored; the URL is shown once when the feed
+//! is created, and creating it again replaces the old one.
+
+use std::fmt::Write as _;
+
+use axum::extract::{Path, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use chrono::SecondsFormat;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, QueryFilter, Statement,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, project};
+use crate::{error_reports, links, storage};
+
+/// Alerts listed in a feed, most recently changed first.
+pub const FEED_ENTRIES: u64 = 50;
+
+/// A new feed token; hand it out once and store only [`token_hash`].
+pub fn new_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+pub fn token_hash(token: &str) -> String {
+    storage::digest(token.as_bytes())
+}
+
+/// Where the feed for this token is served, relative to the API's URL.
+pub fn alerts_feed_path(token: &str) -> String {
+    format!("/feeds/{}/alerts.atom", token)
+}
+
+/// One alert as it appears in a feed.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct FeedAlert {
+    pub id: Uuid,
+    pub status: String,
+    pub percent_change: f64,
+    pub baseline_value: f64,
+    pub current_value: f64,
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// The project's raised alerts, most recently changed first.
+pub async fn feed_alerts<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+) -> Result<Vec<FeedAlert>, DbErr> {
+    FeedAlert::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT a.id, a.status, a.percent_change, a.baseline_value, a.current_value,
+                  b.name AS benchmark, ms.name AS measure, br.name AS branch,
+                  tb.name AS testbed, a.created_at, a.updated_at
+           FROM alerts a
+           JOIN thresholds t ON t.id = a.threshold_id
+           JOIN metrics m ON m.id = a.metric_id
+           JOIN reports r ON r.id = m.report_id
+           JOIN benchmarks b ON b.id = m.benchmark_id
+           JOIN measures ms ON ms.id = m.measure_id
+           JOIN branches br ON br.id = r.branch_id
+           JOIN testbeds tb ON tb.id = r.testbed_id
+           WHERE t.project_id = $1 AND a.status <> 'unconfirmed'
+           ORDER BY a.updated_at DESC
+           LIMIT $2"#,
+        [project_id.into(), FEED_ENTRIES.into()],
+    ))
+    .all(db)
+    .await
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn timestamp(at: &DateTimeWithTimeZone) -> String {
+    at.with_timezone(&chrono::Utc)
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Renders the project's alerts as an Atom feed.
+pub fn render_atom(
+    project: &project::Model,
+    alerts: &[FeedAlert],
+    web_url: Option<&str>,
+) -> String {
+    let updated = alerts
+        .iter()
+        .map(|a| a.updated_at)
+        .max()
+        .unwrap_or(project.updated_at);
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    let _ = writeln!(out, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    let _ = writeln!(out, "  <id>urn:uuid:{}</id>", project.id);
+    let _ = writeln!(out, "  <title>{} alerts</title>", escape_xml(&project.name));
+    let _ = writeln!(out, "  <updated>{}</updated>", timestamp(&updated));
+    let _ = writeln!(out, "  <author><name>Driftwatch</name></author>");
+    if let Some(web_url) = web_url {
+        let _ = writeln!(
+            out,
+            r#"  <link rel="alternate" href="{}"/>"#,
+            escape_xml(&links::project_url(web_url, &project.slug))
+        );
+    }
+
+    for alert in alerts {
+        let _ = writeln!(out, "  <entry>");
+        let _ = writeln!(out, "    <id>urn:uuid:{}</id>", alert.id);
+        let _ = writeln!(
+            out,
+            "    <title>{} {} {:+.1}% on {}/{} ({})</title>",
+            escape_xml(&alert.benchmark),
+            escape_xml(&alert.measure),
+            alert.percent_change,
+            escape_xml(&alert.branch),
+            escape_xml(&alert.testbed),
+            escape_xml(&alert.status)
+        );
+        let _ = writeln!(
+            out,
+            "    <published>{}</published>",
+            timestamp(&alert.created_at)
+        );
+        let _ = writeln!(
+            out,
+            "    <updated>{}</updated>",
+            timestamp(&alert.updated_at)
+        );
+        if let Some(web_url) = web_url {
+            let _ = writeln!(
+                out,
+                r#"    <link rel="alternate" href="{}"/>"#,
+                escape_xml(&links::alert_url(web_url, &project.slug, alert.id))
+            );
+        }
+        let _ = writeln!(
+            out,
+            "    <summary>{} went from {} to {} ({:+.1}%) on branch {}, testbed {}. Status: {}.</summary>",
+            escape_xml(&alert.measure),
+            alert.baseline_value,
+            alert.current_value,
+            alert.percent_change,
+            escape_xml(&alert.branch),
+            escape_xml(&alert.testbed),
+            escape_xml(&alert.status)
+        );
+        let _ = writeln!(out, "  </entry>");
+    }
+    let _ = writeln!(out, "</feed>");
+    out
+}
+
+async fn alerts_feed(State(db): State<DatabaseConnection>, Path(token): Path<String>) -> Response {
+    let feed = async {
+        let Some(project) = entities::Project::find()
+            .filter(project::Column::AlertFeedTokenHash.eq(token_hash(&token)))
+            .one(&db)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let alerts = feed_alerts(&db, project.id).await?;
+        Ok::<_, DbErr>(Some(render_atom(&project, &alerts, links::web_url())))
+    };
+
+    match feed.await {
+        Ok(Some(feed)) => (
+            [
+                (header::CONTENT_TYPE, "application/atom+xml; charset=utf-8"),
+                (header::CACHE_CONTROL, "private, max-age=60"),
+            ],
+            feed,
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!("Alert feed failed: {}", e);
+            error_reports::capture(&e.into(), "feeds", &[]);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Routes for `/feeds/{token}/...`. The token in the path is the only
+/// credential.
+pub fn router<S: Clone + Send + Sync + 'static>(db: DatabaseConnection) -> Router<S> {
+    Router::new()
+        .route("/feeds/{token}/alerts.atom", get(alerts_feed))
+        .with_state(db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTimeWithTimeZone {
+        chrono::DateTime::parse_from_rfc3339(rfc3339).unwrap()
+    }
+
+    #[test]
+    fn test_render_atom() {
+        let project = project::Model {
+            id: Uuid::nil(),
+            user_id: Some(Uuid::nil()),
+            slug: "core".to_string(),
+            name: "Core & Friends".to_string(),
+            description: None,
+            public: false,
+            default_branch: "main".to_string(),
+            github_repo: None,
+            github_token: None,
+            github_pr_comments: false,
+            github_status_checks: false,
+            expected_cadence_hours: None,
+            stale_branches: None,
+            github_issue_after_reports: None,
+            alert_after_reports: None,
+            noise_cv_limit: None,
+            noise_action: project::NoiseAction::Flag,
+            benchmark_required_paths: None,
+            redact_secrets: true,
+            alert_feed_token_hash: None,
+            expires_at: None,
+            share_token_hash: None,
+            created_at: at("2024-01-01T00:00:00Z"),
+            updated_at: at("2024-01-01T00:00:00Z"),
+        };
+        let alert = FeedAlert {
+            id: Uuid::nil(),
+            status: "active".to_string(),
+            percent_change: 12.5,
+            baseline_value: 100.0,
+            current_value: 112.5,
+            benchmark: "parse<json>".to_string(),
+            measure: "latency".to_string(),
+            branch: "main".to_string(),
+            testbed: "ci".to_string(),
+            created_at: at("2024-01-08T10:00:00+02:00"),
+            updated_at: at("2024-01-09T10:00:00Z"),
+        };
+
+        let empty = render_atom(&project, &[], None);
+        assert!(empty.contains("<title>Core &amp; Friends alerts</title>"));
+        assert!(empty.contains("<updated>2024-01-01T00:00:00Z</updated>"));
+        assert!(!empty.contains("<entry>"));
+
+        let feed = render_atom(&project, &[alert], Some("https://driftwatch.dev"));
+        assert!(feed.contains("<updated>2024-01-09T10:00:00Z</updated>\n  <author>"));
+        assert!(
+            feed.contains("<title>parse&lt;json&gt; latency +12.5% on main/ci (active)</title>")
+        );
+        assert!(feed.contains("<published>2024-01-08T08:00:00Z</published>"));
+        assert!(feed.contains(&format!(
+            r#"<link rel="alternate" href="https://driftwatch.dev/projects/core/alerts/{}"/>"#,
+            Uuid::nil()
+        )));
+        assert!(feed.contains("latency went from 100 to 112.5 (+12.5%)"));
+        assert!(feed.ends_with("</feed>\n"));
+    }
+
+    #[test]
+    fn test_tokens() {
+        let token = new_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_token());
+        assert_eq!(token_hash(&token).len(), 64);
+        assert_ne!(token_hash(&token), token);
+        assert_eq!(alerts_feed_path("abc"), "/feeds/abc/alerts.atom");
+    }
+}
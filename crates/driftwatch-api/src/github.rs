@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::entities::project;
+use crate::secrets;
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// How long GitHub gets to answer. Merge-base lookups run while a report
+/// is being created, so a hung request must not hold the submission up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct GitHubClient {
+    http: reqwest::Client,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct Comparison {
+    merge_base_commit: CommitRef,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitRef {
+    pub sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Issue {
+    pub number: i32,
+    pub html_url: String,
+    /// `open` or `closed`
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PullRequest {
+    pub number: i32,
+    pub head: CommitRef,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Deserialize)]
+struct PullRequestFile {
+    filename: String,
+}
+
+/// The project's GitHub token, decrypted. Tokens that can't be decrypted are
+/// logged and treated as missing.
+pub fn project_token(project: &project::Model) -> Option<String> {
+    match secrets::github_token(project) {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::warn!(
+                "Can't use the GitHub token of project {}: {:#}",
+                project.slug,
+                e
+            );
+            None
+        }
+    }
+}
+
+impl std::fmt::Debug for GitHubClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubClient")
+            .field("token", &"[redacted]")
+            .finish()
+    }
+}
+
+impl GitHubClient {
+    pub fn new(token: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            token: token.to_string(),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.http
+            .request(method, url)
+            .timeout(REQUEST_TIMEOUT)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "driftwatch")
+    }
+
+    /// Resolves the merge-base of `base` and `head` in `repo` (`owner/name`)
+    pub async fn merge_base(&self, repo: &str, base: &str, head: &str) -> Result<String> {
+        let response = self
+            .request(reqwest::Method::GET, &compare_url(repo, base, head))
+            .send()
+            .await
+            .context("Failed to reach GitHub")?;
+
+        if !response.status().is_success() {
+            bail!("GitHub compare failed: {}", response.status());
+        }
+
+        let comparison: Comparison = response.json().await?;
+        Ok(comparison.merge_base_commit.sha)
+    }
+
+    pub async fn create_issue(&self, repo: &str, title: &str, body: &str) -> Result<Issue> {
+        let url = format!("{}/repos/{}/issues", GITHUB_API_URL, repo);
+
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&serde_json::json!({ "title": title, "body": body }))
+            .send()
+            .await
+            .context("Failed to reach GitHub")?;
+
+        if !response.status().is_success() {
+            bail!("GitHub issue creation failed: {}", response.status());
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn issue(&self, repo: &str, number: i32) -> Result<Issue> {
+        let url = format!("{}/repos/{}/issues/{}", GITHUB_API_URL, repo, number);
+
+        let response = self
+            .request(reqwest::Method::GET, &url)
+            .send()
+            .await
+            .context("Failed to reach GitHub")?;
+
+        if !response.status().is_success() {
+            bail!("GitHub issue lookup failed: {}", response.status());
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn open_pull_requests(&self, repo: &str) -> Result<Vec<PullRequest>> {
+        let url = format!(
+            "{}/repos/{}/pulls?state=open&per_page=100",
+            GITHUB_API_URL, repo
+        );
+
+        let response = self
+            .request(reqwest::Method::GET, &url)
+            .send()
+            .await
+            .context("Failed to reach GitHub")?;
+
+        if !response.status().is_success() {
+            bail!("GitHub pull request listing failed: {}", response.status());
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Paths changed by a pull request. GitHub stops listing after 3000
+    /// files, which is plenty to tell whether a benchmarked path was hit.
+    pub async fn pull_request_files(&self, repo: &str, number: i32) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+
+        for page in 1..=30 {
+            let url = format!(
+                "{}/repos/{}/pulls/{}/files?per_page=100&page={}",
+                GITHUB_API_URL, repo, number, page
+            );
+
+            let response = self
+                .request(reqwest::Method::GET, &url)
+                .send()
+                .await
+                .context("Failed to reach GitHub")?;
+
+            if !response.status().is_success() {
+                bail!("GitHub pull request files failed: {}", response.status());
+            }
+
+            let batch: Vec<PullRequestFile> = response.json().await?;
+            let done = batch.len() < 100;
+            files.extend(batch.into_iter().map(|f| f.filename));
+            if done {
+                break;
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Sets a commit status; `state` is `pending`, `success` or `failure`,
+    /// and `target_url` is where the status's Details link goes
+    pub async fn create_status(
+        &self,
+        repo: &str,
+        sha: &str,
+        state: &str,
+        description: &str,
+        context: &str,
+        target_url: Option<&str>,
+    ) -> Result<()> {
+        let url = format!("{}/repos/{}/statuses/{}", GITHUB_API_URL, repo, sha);
+
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&serde_json::json!({
+                "state": state,
+                "description": description,
+                "context": context,
+                "target_url": target_url,
+            }))
+            .send()
+            .await
+            .context("Failed to reach GitHub")?;
+
+        if !response.status().is_success() {
+            bail!("GitHub status update failed: {}", response.status());
+        }
+
+        Ok(())
+    }
+}
+
+/// The compare endpoint for two refs, which may contain characters such as
+/// `#` or `?` that would otherwise end the path
+fn compare_url(repo: &str, base: &str, head: &str) -> String {
+    format!(
+        "{}/repos/{}/compare/{}...{}",
+        GITHUB_API_URL,
+        repo,
+        urlencoding::encode(base),
+        urlencoding::encode(head)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_url_encodes_refs() {
+        assert_eq!(
+            compare_url("acme/engine", "main", "abc123"),
+            "https://api.github.com/repos/acme/engine/compare/main...abc123"
+        );
+        assert_eq!(
+            compare_url("acme/engine", "release/1.0", "fix#12?x"),
+            "https://api.github.com/repos/acme/engine/compare/release%2F1.0...fix%2312%3Fx"
+        );
+    }
+}
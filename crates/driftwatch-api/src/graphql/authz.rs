@@ -0,0 +1,372 @@
+//! Tenant isolation for GraphQL resolvers.
+//!
+//! Resolvers never filter by `user_id` themselves: every root field that
+//! takes a project slug or an id loads the row through one of the policy
+//! functions below, which treat other users' rows exactly like missing ones
+//! so their existence doesn't leak. Fields nested under a loaded row (a
+//! project's reports, an alert's metric) inherit its check.
+//!
+//! [`QUERY_ACCESS`] and [`MUTATION_ACCESS`] list who may call each root
+//! field; a test fails when a field is added without an entry, and the
+//! cross-tenant e2e test exercises every `Owner` field.
+
+use async_graphql::{Result, ID};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Select};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::cache::AppCache;
+use crate::entities::{
+    self, alert, annotation, branch, measure, notification_channel, project, project_group,
+    remote_write_rule, report, stale_alert, testbed, threshold,
+};
+
+/// Who may call a root field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Anyone, signed in or not
+    Public,
+    /// Any signed-in user, acting only on their own account
+    Authenticated,
+    /// Signed-in users, on projects, groups and rows they own
+    Owner,
+    /// Server admins only
+    Admin,
+}
+
+pub const QUERY_ACCESS: &[(&str, Access)] = &[
+    ("projects", Access::Owner),
+    ("project", Access::Owner),
+    ("projectGroups", Access::Owner),
+    ("projectGroup", Access::Owner),
+    ("searchBenchmarks", Access::Authenticated),
+    ("publicProjects", Access::Public),
+    ("publicProject", Access::Public),
+    ("report", Access::Owner),
+    ("alert", Access::Owner),
+    ("thresholdTest", Access::Owner),
+    ("simulateThresholds", Access::Owner),
+    ("projectTemplates", Access::Public),
+    ("me", Access::Authenticated),
+    ("apiKeys", Access::Authenticated),
+    ("apiVersion", Access::Public),
+    ("deprecatedFieldUsage", Access::Admin),
+    ("jobs", Access::Admin),
+];
+
+pub const MUTATION_ACCESS: &[(&str, Access)] = &[
+    ("createProject", Access::Authenticated),
+    ("updateProject", Access::Owner),
+    ("deleteProject", Access::Owner),
+    ("updateGithubSettings", Access::Owner),
+    ("createAlertFeedUrl", Access::Owner),
+    ("deleteAlertFeed", Access::Owner),
+    ("createThreshold", Access::Owner),
+    ("deleteThreshold", Access::Owner),
+    ("updateAlert", Access::Owner),
+    ("linkAlertIssue", Access::Owner),
+    ("dismissStaleAlert", Access::Owner),
+    ("setBenchmarkOwners", Access::Owner),
+    ("setBranchAlias", Access::Owner),
+    ("removeBranchAlias", Access::Owner),
+    ("setTestbedAlias", Access::Owner),
+    ("removeTestbedAlias", Access::Owner),
+    ("saveReportFilter", Access::Owner),
+    ("deleteReportFilter", Access::Owner),
+    ("createReport", Access::Owner),
+    ("shareReport", Access::Public),
+    ("openReport", Access::Owner),
+    ("appendReportMetrics", Access::Owner),
+    ("finalizeReport", Access::Owner),
+    ("setReportExcluded", Access::Owner),
+    ("reevaluateReport", Access::Owner),
+    ("attachReportOutput", Access::Owner),
+    ("createFlamegraphUploadUrl", Access::Owner),
+    ("confirmFlamegraphUpload", Access::Owner),
+    ("createProjectGroup", Access::Owner),
+    ("deleteProjectGroup", Access::Owner),
+    ("addProjectToGroup", Access::Owner),
+    ("removeProjectFromGroup", Access::Owner),
+    ("createAnnotation", Access::Owner),
+    ("recordExperiment", Access::Owner),
+    ("deleteAnnotation", Access::Owner),
+    ("addNotificationChannel", Access::Owner),
+    ("updateNotificationChannel", Access::Owner),
+    ("testNotificationChannel", Access::Owner),
+    ("removeNotificationChannel", Access::Owner),
+    ("addRemoteWriteRule", Access::Owner),
+    ("removeRemoteWriteRule", Access::Owner),
+    ("retryJob", Access::Admin),
+    ("seedDemoData", Access::Admin),
+    ("signup", Access::Public),
+    ("signin", Access::Public),
+    ("signout", Access::Authenticated),
+    ("createApiKey", Access::Authenticated),
+    ("revokeApiKey", Access::Authenticated),
+];
+
+fn owns(user: &AuthUser, project: Option<&project::Model>) -> bool {
+    project.is_some_and(|p| p.user_id == Some(user.user_id()))
+}
+
+/// The caller's project with this slug
+pub async fn project<C: ConnectionTrait>(
+    db: &C,
+    cache: &AppCache,
+    user: &AuthUser,
+    slug: &str,
+) -> Result<project::Model> {
+    Ok(cache
+        .resolve_project(db, user.user_id(), slug)
+        .await?
+        .ok_or("Workspace not found")?)
+}
+
+/// The caller's projects, to filter and order further
+pub fn projects(user: &AuthUser) -> Select<entities::Project> {
+    entities::Project::find().filter(project::Column::UserId.eq(user.user_id()))
+}
+
+/// The caller's project with this slug, or `None`, bypassing the cache
+pub async fn find_project<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    slug: &str,
+) -> Result<Option<project::Model>> {
+    Ok(projects(user)
+        .filter(project::Column::Slug.eq(slug))
+        .one(db)
+        .await?)
+}
+
+/// A report in one of the caller's projects, or `None`
+pub async fn find_report<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<Option<(report::Model, project::Model)>> {
+    let report_id = Uuid::parse_str(&id.0)?;
+    Ok(entities::Report::find_by_id(report_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+        .filter(|(_, project)| owns(user, project.as_ref()))
+        .and_then(|(report, project)| Some((report, project?))))
+}
+
+pub async fn report<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<(report::Model, project::Model)> {
+    Ok(find_report(db, user, id).await?.ok_or("Report not found")?)
+}
+
+/// An alert on one of the caller's projects, or `None`
+pub async fn find_alert<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<Option<(alert::Model, project::Model)>> {
+    let alert_id = Uuid::parse_str(&id.0)?;
+    let Some((alert, Some(threshold))) = entities::Alert::find_by_id(alert_id)
+        .find_also_related(entities::Threshold)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let project = entities::Project::find_by_id(threshold.project_id)
+        .one(db)
+        .await?
+        .filter(|p| owns(user, Some(p)));
+    Ok(project.map(|project| (alert, project)))
+}
+
+pub async fn alert<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<(alert::Model, project::Model)> {
+    Ok(find_alert(db, user, id).await?.ok_or("Alert not found")?)
+}
+
+pub async fn stale_alert<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<(stale_alert::Model, project::Model)> {
+    let alert_id = Uuid::parse_str(&id.0)?;
+    Ok(entities::StaleAlert::find_by_id(alert_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+        .filter(|(_, project)| owns(user, project.as_ref()))
+        .and_then(|(alert, project)| Some((alert, project?)))
+        .ok_or("Stale alert not found")?)
+}
+
+pub async fn threshold<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<(threshold::Model, project::Model)> {
+    let threshold_id = Uuid::parse_str(&id.0)?;
+    Ok(entities::Threshold::find_by_id(threshold_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+        .filter(|(_, project)| owns(user, project.as_ref()))
+        .and_then(|(threshold, project)| Some((threshold, project?)))
+        .ok_or("Threshold not found")?)
+}
+
+pub async fn annotation<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<(annotation::Model, project::Model)> {
+    let annotation_id = Uuid::parse_str(&id.0)?;
+    Ok(entities::Annotation::find_by_id(annotation_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+        .filter(|(_, project)| owns(user, project.as_ref()))
+        .and_then(|(annotation, project)| Some((annotation, project?)))
+        .ok_or("Annotation not found")?)
+}
+
+pub async fn notification_channel<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<(notification_channel::Model, project::Model)> {
+    let channel_id = Uuid::parse_str(&id.0)?;
+    Ok(entities::NotificationChannel::find_by_id(channel_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+        .filter(|(_, project)| owns(user, project.as_ref()))
+        .and_then(|(channel, project)| Some((channel, project?)))
+        .ok_or("Notification channel not found")?)
+}
+
+pub async fn remote_write_rule<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    id: &ID,
+) -> Result<(remote_write_rule::Model, project::Model)> {
+    let rule_id = Uuid::parse_str(&id.0)?;
+    Ok(entities::RemoteWriteRule::find_by_id(rule_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+        .filter(|(_, project)| owns(user, project.as_ref()))
+        .and_then(|(rule, project)| Some((rule, project?)))
+        .ok_or("Remote write rule not found")?)
+}
+
+/// The caller's project groups, to filter and order further
+pub fn groups(user: &AuthUser) -> Select<entities::ProjectGroup> {
+    entities::ProjectGroup::find().filter(project_group::Column::UserId.eq(user.user_id()))
+}
+
+/// The caller's project group with this slug, or `None`
+pub async fn find_group<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    slug: &str,
+) -> Result<Option<project_group::Model>> {
+    Ok(groups(user)
+        .filter(project_group::Column::Slug.eq(slug))
+        .one(db)
+        .await?)
+}
+
+pub async fn group<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    slug: &str,
+) -> Result<project_group::Model> {
+    Ok(find_group(db, user, slug)
+        .await?
+        .ok_or("Project group not found")?)
+}
+
+/// Checks that ids passed alongside a project, e.g. a threshold's measure,
+/// branch and testbed, belong to that project rather than someone else's
+pub async fn project_scoped_ids<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    measure_id: Uuid,
+    branch_id: Option<Uuid>,
+    testbed_id: Option<Uuid>,
+) -> Result<()> {
+    let measure = entities::Measure::find_by_id(measure_id)
+        .filter(measure::Column::ProjectId.eq(project.id))
+        .one(db)
+        .await?;
+    if measure.is_none() {
+        return Err("Measure not found".into());
+    }
+    if let Some(branch_id) = branch_id {
+        let branch = entities::Branch::find_by_id(branch_id)
+            .filter(branch::Column::ProjectId.eq(project.id))
+            .one(db)
+            .await?;
+        if branch.is_none() {
+            return Err("Branch not found".into());
+        }
+    }
+    if let Some(testbed_id) = testbed_id {
+        let testbed = entities::Testbed::find_by_id(testbed_id)
+            .filter(testbed::Column::ProjectId.eq(project.id))
+            .one(db)
+            .await?;
+        if testbed.is_none() {
+            return Err("Testbed not found".into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphql::build_schema;
+
+    async fn root_fields(kind: &str) -> Vec<String> {
+        let query = format!("{{ __schema {{ {} {{ fields {{ name }} }} }} }}", kind);
+        let response = build_schema().execute(query).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let json = response.data.into_json().unwrap();
+        json["__schema"][kind]["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn listed(policies: &[(&str, Access)]) -> Vec<String> {
+        policies.iter().map(|(name, _)| name.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_every_root_field_has_an_access_policy() {
+        for (kind, policies) in [
+            ("queryType", QUERY_ACCESS),
+            ("mutationType", MUTATION_ACCESS),
+        ] {
+            let mut fields = root_fields(kind).await;
+            let mut listed = listed(policies);
+            fields.sort();
+            listed.sort();
+            assert_eq!(
+                fields, listed,
+                "every {} field needs exactly one entry in authz",
+                kind
+            );
+        }
+    }
+}
@@ -1,9 +1,11 @@
+pub mod authz;
 pub mod mutation;
 pub mod query;
 pub mod schema;
 pub mod types;
+pub mod versioning;
 
 pub use mutation::MutationRoot;
 pub use query::QueryRoot;
-pub use schema::build_schema;
 pub use schema::AppSchema;
+pub use schema::{build_schema, build_schema_with, sdl_router};
@@ -1,18 +1,57 @@
 use std::sync::Arc;
 
 use async_graphql::{Context, Object, Result, ID};
+use base64::Engine;
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, Set, SqlErr, TransactionTrait,
+};
 use uuid::Uuid;
 
 use super::types::{
-    AuthPayload, CreateApiKeyInput, CreateApiKeyPayload, CreateProjectInput, CreateThresholdInput,
-    GitHubSettingsInput, Project, SigninInput, SignupInput, Threshold, UpdateProjectInput,
+    Alert, Annotation, AuthPayload, BenchmarkOwner, BenchmarkOwnerInput, BranchAlias,
+    CreateAnnotationInput, CreateApiKeyInput, CreateApiKeyPayload, CreateProjectGroupInput,
+    CreateProjectInput, CreateReportInput, CreateThresholdInput, DemoData, Experiment, Flamegraph,
+    FlamegraphUploadUrl, GitHubSettingsInput, Job, MetricInput, NotificationChannel,
+    NotificationChannelKindInput, NotificationChannelTest, OpenReportInput, Project, ProjectGroup,
+    RecordExperimentInput, RemoteWriteRule, RemoteWriteRuleInput, Report, ReportFilter,
+    ReportFilterInput, ReportOutput, ReportReevaluation, SeedDemoInput, ShareReportInput,
+    SharedReport, SigninInput, SignupInput, StaleAlert, TestbedAlias, Threshold, UpdateAlertInput,
+    UpdateProjectInput,
 };
 use crate::auth::AuthUser;
+use crate::backpressure;
 use crate::cache::AppCache;
-use crate::entities::{self, measure, project, threshold};
+use crate::context;
+use crate::demo;
+use crate::entities::blob::ContentEncoding;
+use crate::entities::{
+    self, alert, annotation, benchmark, benchmark_owner, branch_alias, experiment,
+    experiment_result, flamegraph, metric, notification_channel, project, project_group,
+    project_group_member, remote_write_rule, report, report_filter, report_output, testbed,
+    testbed_alias, threshold,
+};
+use crate::evaluation::{self, percent_change};
+use crate::events;
+use crate::feeds;
+use crate::github::{self, GitHubClient};
+use crate::graphql::authz;
 use crate::grpc::AuthServiceImpl;
+use crate::ingest::{self, Dimensions, NewReport};
+use crate::jobs;
+use crate::manifests;
+use crate::notifications;
+use crate::redaction;
+use crate::remote_write;
+use crate::report_filters;
+use crate::secrets;
+use crate::shares::{self, ShareClient, ShareSettings};
+use crate::staleness;
+use crate::storage::{self, UploadError};
+use crate::svg;
+use crate::templates;
 
 pub struct MutationRoot;
 
@@ -28,43 +67,49 @@ impl MutationRoot {
         let cache = ctx.data::<AppCache>()?;
         let user_id = user.user_id();
 
-        let existing = entities::Project::find()
-            .filter(project::Column::UserId.eq(user_id))
-            .filter(project::Column::Slug.eq(&input.slug))
-            .one(db)
-            .await?;
-
-        if existing.is_some() {
+        if authz::find_project(db, user, &input.slug).await?.is_some() {
             return Err("A workspace with this slug already exists".into());
         }
 
+        let template_name = input
+            .template
+            .as_deref()
+            .unwrap_or(templates::DEFAULT_TEMPLATE);
+        let template = templates::find(template_name)
+            .ok_or_else(|| format!("Unknown project template '{}'", template_name))?;
+
         let now = Utc::now().fixed_offset();
         let project = project::ActiveModel {
             id: Set(Uuid::new_v4()),
-            user_id: Set(user_id),
+            user_id: Set(Some(user_id)),
             slug: Set(input.slug),
             name: Set(input.name),
             description: Set(input.description),
-            public: Set(false),
+            public: Set(input.public.unwrap_or(false)),
+            default_branch: Set(ingest::DEFAULT_BRANCH.to_string()),
             github_repo: Set(None),
             github_token: Set(None),
             github_pr_comments: Set(false),
             github_status_checks: Set(false),
+            expected_cadence_hours: Set(None),
+            stale_branches: Set(None),
+            github_issue_after_reports: Set(None),
+            alert_after_reports: Set(None),
+            noise_cv_limit: Set(None),
+            noise_action: Set(project::NoiseAction::Flag),
+            benchmark_required_paths: Set(None),
+            redact_secrets: Set(true),
+            alert_feed_token_hash: Set(None),
+            expires_at: Set(None),
+            share_token_hash: Set(None),
             created_at: Set(now),
             updated_at: Set(now),
         };
 
-        let project = project.insert(db).await?;
-
-        let measure = measure::ActiveModel {
-            id: Set(Uuid::new_v4()),
-            project_id: Set(project.id),
-            name: Set("latency".to_string()),
-            units: Set(Some("ns".to_string())),
-            created_at: Set(now),
-            updated_at: Set(now),
-        };
-        measure.insert(db).await?;
+        let txn = db.begin().await?;
+        let project = project.insert(&txn).await?;
+        templates::apply(&txn, project.id, template).await?;
+        txn.commit().await?;
 
         cache.invalidate_user_projects(user_id).await;
 
@@ -82,10 +127,7 @@ impl MutationRoot {
         let cache = ctx.data::<AppCache>()?;
         let user_id = user.user_id();
 
-        let project = entities::Project::find()
-            .filter(project::Column::UserId.eq(user_id))
-            .filter(project::Column::Slug.eq(&slug))
-            .one(db)
+        let project = authz::find_project(db, user, &slug)
             .await?
             .ok_or("Workspace not found")?;
 
@@ -100,6 +142,36 @@ impl MutationRoot {
         if let Some(public) = input.public {
             active.public = Set(public);
         }
+        if let Some(branch) = input.default_branch {
+            active.default_branch = Set(ingest::check_branch_name(&branch)?.to_string());
+        }
+        if let Some(hours) = input.expected_cadence_hours {
+            active.expected_cadence_hours = Set(if hours > 0 { Some(hours) } else { None });
+        }
+        if let Some(branches) = input.stale_branches {
+            let branches = branches
+                .iter()
+                .map(|b| b.trim())
+                .filter(|b| !b.is_empty())
+                .map(ingest::check_branch_name)
+                .collect::<Result<Vec<_>, _>>()?;
+            active.stale_branches = Set((!branches.is_empty()).then(|| branches.join("\n")));
+        }
+        if let Some(reports) = input.alert_after_reports {
+            active.alert_after_reports = Set(if reports > 1 { Some(reports) } else { None });
+        }
+        if let Some(limit) = input.noise_cv_limit {
+            if limit < 0.0 {
+                return Err("Noise limit must not be negative".into());
+            }
+            active.noise_cv_limit = Set(if limit > 0.0 { Some(limit) } else { None });
+        }
+        if let Some(action) = input.noise_action {
+            active.noise_action = Set(action.to_db_value());
+        }
+        if let Some(redact) = input.redact_secrets {
+            active.redact_secrets = Set(redact);
+        }
         active.updated_at = Set(Utc::now().fixed_offset());
 
         let updated = active.update(db).await?;
@@ -115,10 +187,7 @@ impl MutationRoot {
         let cache = ctx.data::<AppCache>()?;
         let user_id = user.user_id();
 
-        let project = entities::Project::find()
-            .filter(project::Column::UserId.eq(user_id))
-            .filter(project::Column::Slug.eq(&slug))
-            .one(db)
+        let project = authz::find_project(db, user, &slug)
             .await?
             .ok_or("Workspace not found")?;
 
@@ -140,12 +209,10 @@ impl MutationRoot {
         let cache = ctx.data::<AppCache>()?;
         let user_id = user.user_id();
 
-        let project = entities::Project::find()
-            .filter(project::Column::UserId.eq(user_id))
-            .filter(project::Column::Slug.eq(&slug))
-            .one(db)
+        let project = authz::find_project(db, user, &slug)
             .await?
             .ok_or("Workspace not found")?;
+        let project_id = project.id;
 
         let mut active: project::ActiveModel = project.into();
 
@@ -154,7 +221,7 @@ impl MutationRoot {
         }
         if let Some(token) = input.github_token {
             if !token.is_empty() {
-                active.github_token = Set(Some(token));
+                active.github_token = Set(Some(secrets::seal_github_token(project_id, &token)?));
             }
         }
         if let Some(pr_comments) = input.github_pr_comments {
@@ -163,6 +230,20 @@ impl MutationRoot {
         if let Some(status_checks) = input.github_status_checks {
             active.github_status_checks = Set(status_checks);
         }
+        if let Some(after) = input.github_issue_after_reports {
+            if after < 0 {
+                return Err("githubIssueAfterReports must not be negative".into());
+            }
+            active.github_issue_after_reports = Set((after > 0).then_some(after));
+        }
+        if let Some(paths) = input.benchmark_required_paths {
+            let paths: Vec<&str> = paths
+                .iter()
+                .map(|p| p.trim())
+                .filter(|p| !p.is_empty())
+                .collect();
+            active.benchmark_required_paths = Set((!paths.is_empty()).then(|| paths.join("\n")));
+        }
         active.updated_at = Set(Utc::now().fixed_offset());
 
         let updated = active.update(db).await?;
@@ -172,6 +253,56 @@ impl MutationRoot {
         Ok(updated.into())
     }
 
+    /// Creates an Atom feed of the project's alerts and returns its URL,
+    /// relative to the API's. The URL is the feed's only credential and is
+    /// shown just this once; calling this again replaces it.
+    async fn create_alert_feed_url(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+    ) -> Result<String> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
+
+        let token = feeds::new_token();
+        let mut active: project::ActiveModel = project.into();
+        active.alert_feed_token_hash = Set(Some(feeds::token_hash(&token)));
+        active.updated_at = Set(Utc::now().fixed_offset());
+        active.update(db).await?;
+
+        cache
+            .invalidate_project(user.user_id(), &project_slug)
+            .await;
+
+        Ok(feeds::alerts_feed_path(&token))
+    }
+
+    /// Turns off the project's alert feed; its URL stops working
+    async fn delete_alert_feed(&self, ctx: &Context<'_>, project_slug: String) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        if project.alert_feed_token_hash.is_none() {
+            return Ok(false);
+        }
+
+        let mut active: project::ActiveModel = project.into();
+        active.alert_feed_token_hash = Set(None);
+        active.updated_at = Set(Utc::now().fixed_offset());
+        active.update(db).await?;
+
+        cache
+            .invalidate_project(user.user_id(), &project_slug)
+            .await;
+
+        Ok(true)
+    }
+
     async fn create_threshold(
         &self,
         ctx: &Context<'_>,
@@ -182,10 +313,7 @@ impl MutationRoot {
         let cache = ctx.data::<AppCache>()?;
         let user_id = user.user_id();
 
-        let project = entities::Project::find()
-            .filter(project::Column::UserId.eq(user_id))
-            .filter(project::Column::Slug.eq(&input.project_slug))
-            .one(db)
+        let project = authz::find_project(db, user, &input.project_slug)
             .await?
             .ok_or("Workspace not found")?;
 
@@ -199,6 +327,7 @@ impl MutationRoot {
             .testbed_id
             .map(|id| Uuid::parse_str(&id.0))
             .transpose()?;
+        authz::project_scoped_ids(db, &project, measure_id, branch_id, testbed_id).await?;
 
         let now = Utc::now().fixed_offset();
         let threshold = threshold::ActiveModel {
@@ -227,108 +356,1570 @@ impl MutationRoot {
         let cache = ctx.data::<AppCache>()?;
         let user_id = user.user_id();
 
-        let threshold_id = Uuid::parse_str(&id.0)?;
+        let (threshold, project) = authz::threshold(db, user, &id).await?;
 
-        let threshold = entities::Threshold::find_by_id(threshold_id)
-            .one(db)
-            .await?
-            .ok_or("Threshold not found")?;
+        let project_slug = project.slug.clone();
+        entities::Threshold::delete_by_id(threshold.id)
+            .exec(db)
+            .await?;
 
-        let project = entities::Project::find_by_id(threshold.project_id)
-            .one(db)
+        cache.invalidate_project(user_id, &project_slug).await;
+
+        Ok(true)
+    }
+
+    /// Moves an alert through triage: status, assignee and resolution note.
+    async fn update_alert(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        input: UpdateAlertInput,
+    ) -> Result<Alert> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (alert, _) = authz::alert(db, user, &id).await?;
+
+        let previous = alert.status.clone();
+        let mut active: alert::ActiveModel = alert.into();
+        if let Some(status) = input.status {
+            active.status = Set(status.to_db_value());
+        }
+        if let Some(assignee) = input.assignee {
+            let assignee = assignee.trim().to_string();
+            active.assignee = Set((!assignee.is_empty()).then_some(assignee));
+        }
+        if let Some(note) = input.resolution_note {
+            let note = note.trim().to_string();
+            active.resolution_note = Set((!note.is_empty()).then_some(note));
+        }
+        active.updated_at = Set(Utc::now().fixed_offset());
+
+        let alert = active.update(db).await?;
+        if alert.status != previous {
+            match alert.status {
+                alert::AlertStatus::Active => events::alert_opened(db, alert.id).await?,
+                alert::AlertStatus::Resolved => events::alert_resolved(db, alert.id).await?,
+                _ => {}
+            }
+        }
+        Ok(alert.into())
+    }
+
+    /// Links an alert to an existing issue in the project's GitHub repository,
+    /// or unlinks it when `issue_number` is omitted.
+    async fn link_alert_issue(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        issue_number: Option<i32>,
+    ) -> Result<Alert> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (alert, project) = authz::alert(db, user, &id).await?;
+
+        let mut active: alert::ActiveModel = alert.into();
+        match issue_number {
+            Some(number) => {
+                let repo = project
+                    .github_repo
+                    .ok_or("Workspace has no GitHub repository configured")?;
+                active.github_issue_number = Set(Some(number));
+                active.github_issue_url = Set(Some(format!(
+                    "https://github.com/{}/issues/{}",
+                    repo, number
+                )));
+            }
+            None => {
+                active.github_issue_number = Set(None);
+                active.github_issue_url = Set(None);
+            }
+        }
+        active.updated_at = Set(Utc::now().fixed_offset());
+
+        let alert = active.update(db).await?;
+        Ok(alert.into())
+    }
+
+    /// Silences an active stale-data alert until its branch/testbed reports
+    /// again.
+    async fn dismiss_stale_alert(&self, ctx: &Context<'_>, id: ID) -> Result<StaleAlert> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (alert, _) = authz::stale_alert(db, user, &id).await?;
+        let alert = staleness::dismiss(db, alert)
             .await?
-            .ok_or("Project not found")?;
+            .ok_or("Only active stale alerts can be dismissed")?;
+        Ok(alert.into())
+    }
+
+    /// Replaces the project's benchmark owner rules, typically synced from an
+    /// owners file in the repository. Rules keep their order; the last
+    /// matching rule wins.
+    async fn set_benchmark_owners(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        rules: Vec<BenchmarkOwnerInput>,
+    ) -> Result<Vec<BenchmarkOwner>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
 
-        if project.user_id != user_id {
-            return Err("Unauthorized".into());
+        for rule in &rules {
+            if rule.pattern.trim().is_empty() {
+                return Err("Owner patterns must not be empty".into());
+            }
+            if rule.owners.is_empty()
+                || rule
+                    .owners
+                    .iter()
+                    .any(|o| o.is_empty() || o.contains(char::is_whitespace))
+            {
+                return Err(format!("Invalid owners for pattern '{}'", rule.pattern).into());
+            }
         }
 
-        let project_slug = project.slug.clone();
-        entities::Threshold::delete_by_id(threshold_id)
-            .exec(db)
+        let now = Utc::now().fixed_offset();
+        let txn = db.begin().await?;
+        entities::BenchmarkOwner::delete_many()
+            .filter(benchmark_owner::Column::ProjectId.eq(project.id))
+            .exec(&txn)
             .await?;
 
-        cache.invalidate_project(user_id, &project_slug).await;
+        let mut stored = Vec::with_capacity(rules.len());
+        for (position, rule) in rules.into_iter().enumerate() {
+            let owner = benchmark_owner::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                project_id: Set(project.id),
+                position: Set(position as i32),
+                pattern: Set(rule.pattern.trim().to_string()),
+                owners: Set(rule.owners.join(" ")),
+                created_at: Set(now),
+            }
+            .insert(&txn)
+            .await?;
+            stored.push(owner.into());
+        }
+        txn.commit().await?;
 
-        Ok(true)
+        Ok(stored)
     }
 
-    async fn signup(&self, ctx: &Context<'_>, input: SignupInput) -> Result<AuthPayload> {
-        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+    /// Makes reports submitted for `alias` count as reports of `branch`,
+    /// e.g. `main` for `master` after the repository renamed its primary
+    /// branch. Moves the alias when it already points elsewhere.
+    async fn set_branch_alias(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        alias: String,
+        branch: String,
+    ) -> Result<BranchAlias> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
 
-        let (user, session_token) = auth_service
-            .signup_direct(&input.email, &input.password, input.name)
-            .await
-            .map_err(async_graphql::Error::new)?;
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        let alias = ingest::check_branch_name(&alias)?;
+        let name = ingest::check_branch_name(&branch)?;
+        // Resolving the target keeps aliases one step deep
+        let branch = ingest::find_branch(db, project.id, name)
+            .await?
+            .ok_or_else(|| format!("Branch '{}' not found", name))?;
+        if branch.name == alias {
+            return Err(format!("'{}' can't be an alias of itself", alias).into());
+        }
 
-        Ok(AuthPayload {
-            user: user.into(),
-            session_token,
-        })
+        let existing = entities::BranchAlias::find()
+            .filter(branch_alias::Column::ProjectId.eq(project.id))
+            .filter(branch_alias::Column::Alias.eq(alias))
+            .one(db)
+            .await?;
+        let stored = match existing {
+            Some(existing) => {
+                let mut active: branch_alias::ActiveModel = existing.into();
+                active.branch_id = Set(branch.id);
+                active.update(db).await?
+            }
+            None => {
+                branch_alias::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    project_id: Set(project.id),
+                    alias: Set(alias.to_string()),
+                    branch_id: Set(branch.id),
+                    created_at: Set(Utc::now().fixed_offset()),
+                }
+                .insert(db)
+                .await?
+            }
+        };
+
+        Ok(BranchAlias::new(stored, branch))
     }
 
-    async fn signin(&self, ctx: &Context<'_>, input: SigninInput) -> Result<AuthPayload> {
-        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+    /// Returns whether the alias existed
+    async fn remove_branch_alias(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        alias: String,
+    ) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
 
-        let (user, session_token) = auth_service
-            .signin_direct(&input.email, &input.password)
-            .await
-            .map_err(async_graphql::Error::new)?;
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        let result = entities::BranchAlias::delete_many()
+            .filter(branch_alias::Column::ProjectId.eq(project.id))
+            .filter(branch_alias::Column::Alias.eq(alias.trim()))
+            .exec(db)
+            .await?;
 
-        Ok(AuthPayload {
-            user: user.into(),
-            session_token,
-        })
+        Ok(result.rows_affected > 0)
     }
 
-    async fn signout(&self, ctx: &Context<'_>) -> Result<bool> {
-        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+    /// Makes reports submitted for `alias` count as reports of `testbed`,
+    /// e.g. `linux-x86_64` for the `linux` testbed the CLI defaulted to
+    /// before it added the architecture. Moves the alias when it already
+    /// points elsewhere.
+    async fn set_testbed_alias(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        alias: String,
+        testbed: String,
+    ) -> Result<TestbedAlias> {
+        let db = ctx.data::<DatabaseConnection>()?;
         let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
 
-        if !user.is_session_auth() {
-            return Err("Signout requires session authentication, not API key".into());
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        let alias = ingest::check_testbed_name(&alias)?;
+        let name = ingest::check_testbed_name(&testbed)?;
+        // Resolving the target keeps aliases one step deep
+        let testbed = ingest::find_testbed(db, project.id, name)
+            .await?
+            .ok_or_else(|| format!("Testbed '{}' not found", name))?;
+        // An alias shadowing a testbed would hide that testbed's own series
+        let shadowed = entities::Testbed::find()
+            .filter(testbed::Column::ProjectId.eq(project.id))
+            .filter(testbed::Column::Name.eq(alias))
+            .one(db)
+            .await?;
+        if shadowed.is_some() {
+            return Err(format!(
+                "'{}' is already a testbed; an alias needs a new name",
+                alias
+            )
+            .into());
         }
 
-        auth_service
-            .signout_direct(&user.token)
-            .await
-            .map_err(async_graphql::Error::new)
+        let existing = entities::TestbedAlias::find()
+            .filter(testbed_alias::Column::ProjectId.eq(project.id))
+            .filter(testbed_alias::Column::Alias.eq(alias))
+            .one(db)
+            .await?;
+        let stored = match existing {
+            Some(existing) => {
+                let mut active: testbed_alias::ActiveModel = existing.into();
+                active.testbed_id = Set(testbed.id);
+                active.update(db).await?
+            }
+            None => {
+                testbed_alias::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    project_id: Set(project.id),
+                    alias: Set(alias.to_string()),
+                    testbed_id: Set(testbed.id),
+                    created_at: Set(Utc::now().fixed_offset()),
+                }
+                .insert(db)
+                .await?
+            }
+        };
+
+        Ok(TestbedAlias::new(stored, testbed))
     }
 
-    async fn create_api_key(
+    /// Returns whether the alias existed
+    async fn remove_testbed_alias(
         &self,
         ctx: &Context<'_>,
-        input: CreateApiKeyInput,
-    ) -> Result<CreateApiKeyPayload> {
-        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+        project_slug: String,
+        alias: String,
+    ) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
         let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
 
-        if !user.is_session_auth() {
-            return Err("Creating API keys requires session authentication, not API key".into());
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        let result = entities::TestbedAlias::delete_many()
+            .filter(testbed_alias::Column::ProjectId.eq(project.id))
+            .filter(testbed_alias::Column::Alias.eq(alias.trim()))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Saves a named set of tags and context for `Project.reports(filter:)`,
+    /// replacing the filter of the same name
+    async fn save_report_filter(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        input: ReportFilterInput,
+    ) -> Result<ReportFilter> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        let name = report_filters::validate_filter_name(&input.name)?;
+        let criteria = report_filters::validate_criteria(input.criteria())?;
+        let context = serde_json::Value::Object(
+            criteria
+                .context
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect(),
+        );
+
+        let now = Utc::now().fixed_offset();
+        let stored = match report_filters::find(db, project.id, name).await? {
+            Some(existing) => {
+                let mut active: report_filter::ActiveModel = existing.into();
+                active.tags = Set(criteria.tags.join(" "));
+                active.context = Set(context);
+                active.updated_at = Set(now);
+                active.update(db).await?
+            }
+            None => {
+                report_filter::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    project_id: Set(project.id),
+                    name: Set(name.to_string()),
+                    tags: Set(criteria.tags.join(" ")),
+                    context: Set(context),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(db)
+                .await?
+            }
+        };
+
+        Ok(stored.into())
+    }
+
+    /// Returns whether the filter existed
+    async fn delete_report_filter(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        name: String,
+    ) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        let result = entities::ReportFilter::delete_many()
+            .filter(report_filter::Column::ProjectId.eq(project.id))
+            .filter(report_filter::Column::Name.eq(name.trim()))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    async fn create_report(&self, ctx: &Context<'_>, input: CreateReportInput) -> Result<Report> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &input.project_slug).await?;
+        Ok(submit_report(db, cache, &project, input).await?.into())
+    }
+
+    /// Stores a one-off report without an account and returns a secret
+    /// path it can be read at until it expires. Only available when the
+    /// server runs with `ANONYMOUS_SHARES=true`.
+    async fn share_report(
+        &self,
+        ctx: &Context<'_>,
+        input: ShareReportInput,
+    ) -> Result<SharedReport> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let Some(settings) = ctx.data_opt::<ShareSettings>() else {
+            return Err("Anonymous sharing is disabled on this server".into());
+        };
+
+        if input.metrics.is_empty() {
+            return Err("Report must contain at least one metric".into());
         }
+        if input.metrics.len() > settings.max_metrics {
+            return Err(format!(
+                "Shared reports may hold at most {} metrics",
+                settings.max_metrics
+            )
+            .into());
+        }
+        settings.admit(ctx.data_opt::<ShareClient>().copied())?;
 
-        let (api_key, secret) = auth_service
-            .create_api_key_direct(&user.token, &input.name, input.scopes)
-            .await
-            .map_err(async_graphql::Error::new)?;
+        let share = shares::NewShare {
+            name: input.name,
+            branch: input.branch,
+            testbed: input.testbed,
+            git_hash: input.git_hash,
+            metrics: input.metrics.into_iter().map(Into::into).collect(),
+        };
+        let created = shares::create(db, settings, share).await?;
 
-        Ok(CreateApiKeyPayload {
-            api_key: api_key.into(),
-            secret,
+        Ok(SharedReport {
+            path: shares::share_path(&created.token),
+            expires_at: created
+                .project
+                .expires_at
+                .unwrap_or(created.project.created_at)
+                .into(),
+            report_id: ID(created.report.id.to_string()),
         })
     }
 
-    async fn revoke_api_key(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
-        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+    async fn open_report(&self, ctx: &Context<'_>, input: OpenReportInput) -> Result<Report> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
         let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
 
-        if !user.is_session_auth() {
-            return Err("Revoking API keys requires session authentication, not API key".into());
+        let project = authz::project(db, cache, user, &input.project_slug).await?;
+        if let Some(report) = resubmitted_report(db, &project, input.id.as_ref()).await? {
+            return Ok(report.into());
         }
 
-        auth_service
-            .revoke_api_key_direct(&user.token, &id.0)
-            .await
-            .map_err(async_graphql::Error::new)
+        let id = input.id.clone();
+        let new_report = new_report(&project, input).await?;
+        let report =
+            match ingest::open_report(db, &mut Dimensions::default(), new_report, false).await {
+                Ok(report) => report,
+                Err(e) => raced_resubmission(db, &project, id.as_ref(), e).await?,
+            };
+
+        Ok(report.into())
+    }
+
+    /// Adds a batch of metrics to an open report and returns how many were stored.
+    async fn append_report_metrics(
+        &self,
+        ctx: &Context<'_>,
+        report_id: ID,
+        metrics: Vec<MetricInput>,
+    ) -> Result<i32> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        if metrics.len() > MAX_METRIC_BATCH {
+            return Err(format!("At most {} metrics per batch", MAX_METRIC_BATCH).into());
+        }
+
+        let report = find_open_report(db, user, &report_id).await?;
+        let metrics = metrics.into_iter().map(Into::into).collect();
+
+        let txn = db.begin().await?;
+        let stored =
+            ingest::append_metrics(&txn, &mut Dimensions::default(), &report, metrics).await?;
+        txn.commit().await?;
+
+        Ok(stored.len() as i32)
     }
+
+    /// Closes a chunked upload and queues alert evaluation over all appended metrics.
+    async fn finalize_report(&self, ctx: &Context<'_>, report_id: ID) -> Result<Report> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let report = find_open_report(db, user, &report_id).await?;
+        let metric_count = entities::Metric::find()
+            .filter(metric::Column::ReportId.eq(report.id))
+            .count(db)
+            .await?;
+
+        if metric_count == 0 {
+            return Err("Report must contain at least one metric".into());
+        }
+
+        let txn = db.begin().await?;
+        ingest::enqueue_evaluation(&txn, report.id).await?;
+        let mut active: report::ActiveModel = report.into();
+        active.finalized = Set(true);
+        let report = active.update(&txn).await?;
+        txn.commit().await?;
+
+        cache.invalidate_latest_reports(report.project_id).await;
+
+        Ok(report.into())
+    }
+
+    /// Excludes a report from the baselines of later reports, e.g. after a
+    /// broken runner produced bad numbers, or restores it. Open alerts that
+    /// depended on it are re-evaluated in the background.
+    async fn set_report_excluded(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        excluded: bool,
+    ) -> Result<Report> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let (report, _) = authz::report(db, user, &id).await?;
+        if report.excluded == excluded {
+            return Ok(report.into());
+        }
+
+        let txn = db.begin().await?;
+        let mut active: report::ActiveModel = report.into();
+        active.excluded = Set(excluded);
+        let report = active.update(&txn).await?;
+        ingest::enqueue_baseline_reevaluation(&txn, report.id).await?;
+        txn.commit().await?;
+
+        cache.invalidate_latest_reports(report.project_id).await;
+
+        Ok(report.into())
+    }
+
+    /// Re-runs alert evaluation for a report against the project's current
+    /// thresholds, e.g. after tightening or loosening them, without
+    /// resubmitting it.
+    async fn reevaluate_report(&self, ctx: &Context<'_>, id: ID) -> Result<ReportReevaluation> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let (report, _) = authz::report(db, user, &id).await?;
+        if !report.finalized {
+            return Err("Report is still being uploaded".into());
+        }
+        if report.status == report::ReportStatus::Pending {
+            return Err("Report hasn't been evaluated yet".into());
+        }
+
+        let txn = db.begin().await?;
+        let outcome = evaluation::reevaluate_report(&txn, &report).await?;
+        txn.commit().await?;
+
+        cache.invalidate_latest_reports(report.project_id).await;
+
+        Ok(ReportReevaluation {
+            report: report.into(),
+            raised: outcome.raised as i32,
+            updated: outcome.updated as i32,
+            resolved: outcome.resolved as i32,
+        })
+    }
+
+    /// Stores the benchmark command's output with a report, replacing any
+    /// attached before. `content` is gzip-compressed and base64-encoded.
+    /// Secrets in it are redacted first unless the project opted out.
+    async fn attach_report_output(
+        &self,
+        ctx: &Context<'_>,
+        report_id: ID,
+        content: String,
+    ) -> Result<ReportOutput> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (report, project) = authz::report(db, user, &report_id).await?;
+
+        let content = base64::engine::general_purpose::STANDARD
+            .decode(content.trim())
+            .map_err(|_| "Output must be base64-encoded")?;
+        if !content.starts_with(&GZIP_MAGIC) {
+            return Err("Output must be gzip-compressed".into());
+        }
+        if content.len() > MAX_REPORT_OUTPUT_BYTES {
+            return Err(format!(
+                "Compressed output is larger than {} bytes",
+                MAX_REPORT_OUTPUT_BYTES
+            )
+            .into());
+        }
+        let (content, redactions) = if project.redact_secrets {
+            redaction::redact_gzip(content)?
+        } else {
+            (content, 0)
+        };
+
+        let (content, size) =
+            storage::store_output(&content).map_err(|e| format!("Invalid output: {}", e))?;
+
+        let output = report_output::ActiveModel {
+            report_id: Set(report.id),
+            content: Set(content),
+            encoding: Set(ContentEncoding::Zstd),
+            size: Set(Some(size)),
+            redactions: Set(redactions as i32),
+            created_at: Set(Utc::now().into()),
+        };
+        entities::ReportOutput::insert(output)
+            .on_conflict(
+                OnConflict::column(report_output::Column::ReportId)
+                    .update_columns([
+                        report_output::Column::Content,
+                        report_output::Column::Encoding,
+                        report_output::Column::Size,
+                        report_output::Column::Redactions,
+                        report_output::Column::CreatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await?;
+
+        let output = entities::ReportOutput::find_by_id(report.id)
+            .one(db)
+            .await?
+            .ok_or("Report output not found")?;
+        Ok(ReportOutput::try_from(output)?)
+    }
+
+    /// Opens a resumable upload for a flamegraph of one of the project's
+    /// reports. Upload the file to `signedUrl`, then confirm it. Pass the
+    /// file's `sha256` to skip the upload when the project already has it.
+    async fn create_flamegraph_upload_url(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        file_name: String,
+        sha256: Option<String>,
+    ) -> Result<FlamegraphUploadUrl> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
+        let file_name = file_name.trim();
+        if file_name.is_empty() || file_name.len() > MAX_FLAMEGRAPH_NAME_LEN {
+            return Err(format!(
+                "File name must be 1 to {} characters",
+                MAX_FLAMEGRAPH_NAME_LEN
+            )
+            .into());
+        }
+
+        if let Some(sha256) = sha256.as_deref() {
+            if let Some(stored) = storage::find_stored(db, project.id, sha256).await? {
+                return Ok(FlamegraphUploadUrl {
+                    stored: true,
+                    signed_url: None,
+                    token: None,
+                    storage_path: storage::blob_path(&stored.sha256),
+                });
+            }
+        }
+
+        let upload = storage::create(db, project.id, file_name).await?;
+        Ok(FlamegraphUploadUrl {
+            stored: false,
+            signed_url: Some(storage::upload_url(&upload)),
+            storage_path: storage::storage_path(&upload),
+            token: Some(upload.token),
+        })
+    }
+
+    /// Links a finished upload, or a file the project already stores, to a
+    /// report. Fails unless all `fileSize` bytes of an SVG arrived and, when
+    /// given, `sha256` matches them. Scripts and other active content are
+    /// stripped before the file is stored. An upload that doesn't match its
+    /// checksum or isn't a valid SVG is discarded.
+    #[allow(clippy::too_many_arguments)]
+    async fn confirm_flamegraph_upload(
+        &self,
+        ctx: &Context<'_>,
+        report_id: ID,
+        storage_path: String,
+        file_name: String,
+        file_size: i32,
+        benchmark_name: Option<String>,
+        sha256: Option<String>,
+    ) -> Result<Flamegraph> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (report, project) = authz::report(db, user, &report_id).await?;
+        if file_size <= 0 {
+            return Err("fileSize must be positive".into());
+        }
+        if file_size as i64 > storage::MAX_UPLOAD_BYTES {
+            return Err(UploadError::TooLarge.into());
+        }
+
+        let benchmark_id = match benchmark_name.as_deref() {
+            Some(name) => Some(
+                entities::Benchmark::find()
+                    .filter(benchmark::Column::ProjectId.eq(project.id))
+                    .filter(benchmark::Column::Name.eq(name))
+                    .one(db)
+                    .await?
+                    .ok_or("Benchmark not found")?
+                    .id,
+            ),
+            None => None,
+        };
+
+        let txn = db.begin().await?;
+        let (upload_digest, stored) = match storage_path.strip_prefix("blobs/") {
+            // Already stored, from `createFlamegraphUploadUrl`
+            Some(blob) => {
+                let upload_digest = sha256
+                    .as_deref()
+                    .ok_or("sha256 is required to link a stored file")?
+                    .trim()
+                    .to_lowercase();
+                let stored = storage::find_stored(&txn, project.id, &upload_digest)
+                    .await?
+                    .filter(|stored| stored.sha256.eq_ignore_ascii_case(blob))
+                    .ok_or(UploadError::NotFound)?;
+                (upload_digest, stored)
+            }
+            None => {
+                let upload = storage::find(&txn, project.id, &storage_path)
+                    .await?
+                    .ok_or(UploadError::NotFound)?;
+                let checked = storage::verify(&upload, file_size as i64, sha256.as_deref())
+                    .and_then(|digest| {
+                        let sanitized =
+                            svg::sanitize(&upload.content).map_err(UploadError::Invalid)?;
+                        Ok((digest, sanitized))
+                    });
+                let (upload_digest, sanitized) = match checked {
+                    Ok(checked) => checked,
+                    // Nothing more can make these uploads usable
+                    Err(e @ (UploadError::ChecksumMismatch | UploadError::Invalid(_))) => {
+                        txn.rollback().await?;
+                        entities::Upload::delete_by_id(upload.id).exec(db).await?;
+                        return Err(e.into());
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                if sanitized.removed > 0 {
+                    tracing::debug!(
+                        "Stripped {} active elements and attributes from flamegraph {}",
+                        sanitized.removed,
+                        file_name
+                    );
+                }
+                let stored = storage::StoredFile {
+                    sha256: storage::digest(&sanitized.content),
+                    size: sanitized.content.len() as i64,
+                };
+                storage::complete(&txn, &upload, &sanitized.content, &stored.sha256).await?;
+                (upload_digest, stored)
+            }
+        };
+
+        let flamegraph = flamegraph::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            report_id: Set(report.id),
+            benchmark_id: Set(benchmark_id),
+            storage_path: Set(storage::blob_path(&stored.sha256)),
+            file_name: Set(file_name),
+            file_size: Set(stored.size as i32),
+            sha256: Set(Some(stored.sha256)),
+            upload_sha256: Set(Some(upload_digest)),
+            created_at: Set(Utc::now().into()),
+        }
+        .insert(&txn)
+        .await?;
+        txn.commit().await?;
+
+        Ok(flamegraph.into())
+    }
+
+    async fn create_project_group(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateProjectGroupInput,
+    ) -> Result<ProjectGroup> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let user_id = user.user_id();
+
+        if authz::find_group(db, user, &input.slug).await?.is_some() {
+            return Err("A project group with this slug already exists".into());
+        }
+
+        let mut projects = Vec::with_capacity(input.project_slugs.len());
+        for slug in &input.project_slugs {
+            let project = authz::find_project(db, user, slug)
+                .await?
+                .ok_or_else(|| format!("Workspace not found: {}", slug))?;
+            projects.push(project);
+        }
+
+        let now = Utc::now().fixed_offset();
+        let txn = db.begin().await?;
+        let group = project_group::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            slug: Set(input.slug),
+            name: Set(input.name),
+            description: Set(input.description),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+        for project in projects {
+            add_group_member(&txn, group.id, project.id).await?;
+        }
+        txn.commit().await?;
+
+        Ok(group.into())
+    }
+
+    async fn delete_project_group(&self, ctx: &Context<'_>, slug: String) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let group = authz::group(db, user, &slug).await?;
+        entities::ProjectGroup::delete_by_id(group.id)
+            .exec(db)
+            .await?;
+        Ok(true)
+    }
+
+    async fn add_project_to_group(
+        &self,
+        ctx: &Context<'_>,
+        group_slug: String,
+        project_slug: String,
+    ) -> Result<ProjectGroup> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let group = authz::group(db, user, &group_slug).await?;
+        let project = authz::project(db, cache, user, &project_slug).await?;
+
+        add_group_member(db, group.id, project.id).await?;
+        Ok(group.into())
+    }
+
+    async fn remove_project_from_group(
+        &self,
+        ctx: &Context<'_>,
+        group_slug: String,
+        project_slug: String,
+    ) -> Result<ProjectGroup> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let group = authz::group(db, user, &group_slug).await?;
+        let project = authz::project(db, cache, user, &project_slug).await?;
+
+        entities::ProjectGroupMember::delete_many()
+            .filter(project_group_member::Column::GroupId.eq(group.id))
+            .filter(project_group_member::Column::ProjectId.eq(project.id))
+            .exec(db)
+            .await?;
+        Ok(group.into())
+    }
+
+    /// Records context for a change in benchmark history, such as a runner
+    /// upgrade, on a report or at a point in time.
+    async fn create_annotation(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateAnnotationInput,
+    ) -> Result<Annotation> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &input.project_slug).await?;
+
+        let note = input.note.trim().to_string();
+        if note.is_empty() {
+            return Err("Annotation note must not be empty".into());
+        }
+
+        let now = Utc::now().fixed_offset();
+        let (report_id, occurred_at) = match input.report_id {
+            Some(id) => {
+                let report_id = Uuid::parse_str(&id.0)?;
+                let report = entities::Report::find_by_id(report_id)
+                    .one(db)
+                    .await?
+                    .filter(|r| r.project_id == project.id)
+                    .ok_or("Report not found")?;
+                (Some(report.id), report.created_at)
+            }
+            None => (None, input.occurred_at.map_or(now, |t| t.fixed_offset())),
+        };
+
+        let annotation = annotation::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project.id),
+            report_id: Set(report_id),
+            kind: Set(input
+                .kind
+                .map_or(annotation::AnnotationKind::Note, |k| k.to_db_value())),
+            note: Set(note),
+            occurred_at: Set(occurred_at),
+            created_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+
+        Ok(annotation.into())
+    }
+
+    /// Stores the outcome of a `driftwatch ab` run. Experiments are kept out
+    /// of branch history, so they never affect baselines or alerts.
+    async fn record_experiment(
+        &self,
+        ctx: &Context<'_>,
+        input: RecordExperimentInput,
+    ) -> Result<Experiment> {
+        backpressure::admit(ctx)?;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &input.project_slug).await?;
+
+        if input.results.is_empty() {
+            return Err("Experiment must contain at least one result".into());
+        }
+        if input.results.len() > MAX_METRIC_BATCH {
+            return Err(
+                format!("Experiments can hold at most {} results", MAX_METRIC_BATCH).into(),
+            );
+        }
+        if input.rounds < 1 {
+            return Err("rounds must be at least 1".into());
+        }
+        if !(input.alpha > 0.0 && input.alpha < 1.0) {
+            return Err("alpha must be between 0 and 1".into());
+        }
+        let scrub = |command: String| {
+            if project.redact_secrets {
+                redaction::redact(&command).0.into_owned()
+            } else {
+                command
+            }
+        };
+
+        let txn = db.begin().await?;
+        let experiment = experiment::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project.id),
+            name: Set(input
+                .name
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())),
+            baseline_command: Set(scrub(input.baseline_command)),
+            candidate_command: Set(scrub(input.candidate_command)),
+            git_hash: Set(input.git_hash),
+            testbed: Set(input.testbed),
+            rounds: Set(input.rounds),
+            alpha: Set(input.alpha),
+            created_at: Set(Utc::now().fixed_offset()),
+        }
+        .insert(&txn)
+        .await?;
+
+        let results: Vec<experiment_result::ActiveModel> = input
+            .results
+            .into_iter()
+            .map(|r| experiment_result::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                experiment_id: Set(experiment.id),
+                percent_change: Set(percent_change(r.baseline_mean, r.candidate_mean)),
+                benchmark: Set(r.benchmark),
+                measure: Set(r.measure),
+                baseline_mean: Set(r.baseline_mean),
+                candidate_mean: Set(r.candidate_mean),
+                p_value: Set(r.p_value),
+                verdict: Set(r.verdict.to_db_value()),
+            })
+            .collect();
+        for chunk in results.chunks(1000) {
+            entities::ExperimentResult::insert_many(chunk.to_vec())
+                .exec(&txn)
+                .await?;
+        }
+        txn.commit().await?;
+
+        Ok(experiment.into())
+    }
+
+    async fn delete_annotation(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (annotation, _) = authz::annotation(db, user, &id).await?;
+
+        entities::Annotation::delete_by_id(annotation.id)
+            .exec(db)
+            .await?;
+        Ok(true)
+    }
+
+    /// Sends the project's weekly digests, or the `events` named, to a
+    /// webhook or Slack incoming webhook, optionally with a `template`
+    /// replacing the default payload. The URL is stored encrypted and only
+    /// its host is shown.
+    async fn add_notification_channel(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        kind: NotificationChannelKindInput,
+        url: String,
+        events: Option<Vec<String>>,
+        template: Option<String>,
+    ) -> Result<NotificationChannel> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
+
+        let kind = kind.to_db_value();
+        let url = url.trim();
+        notifications::validate_url(kind, url)?;
+        let events = notifications::validate_events(
+            &events.unwrap_or_else(|| vec![notifications::DIGEST_EVENT.to_string()]),
+        )?;
+        let template = template.filter(|t| !t.trim().is_empty());
+        if let Some(template) = &template {
+            notifications::validate_template(template, &events)?;
+        }
+
+        let id = Uuid::new_v4();
+        let channel = notification_channel::ActiveModel {
+            id: Set(id),
+            project_id: Set(project.id),
+            kind: Set(kind),
+            url: Set(notifications::seal_url(id, url)?),
+            events: Set(serde_json::json!(events)),
+            template: Set(template),
+            created_at: Set(Utc::now().fixed_offset()),
+        }
+        .insert(db)
+        .await?;
+        Ok(channel.into())
+    }
+
+    /// Changes the events a channel receives or its template; an empty
+    /// `template` restores the default payload.
+    async fn update_notification_channel(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        events: Option<Vec<String>>,
+        template: Option<String>,
+    ) -> Result<NotificationChannel> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (channel, _) = authz::notification_channel(db, user, &id).await?;
+
+        let events = match events {
+            Some(events) => notifications::validate_events(&events)?,
+            None => notifications::channel_events(&channel),
+        };
+        let template = match template {
+            Some(template) => (!template.trim().is_empty()).then_some(template),
+            None => channel.template.clone(),
+        };
+        if let Some(template) = &template {
+            notifications::validate_template(template, &events)?;
+        }
+
+        let mut active: notification_channel::ActiveModel = channel.into();
+        active.events = Set(serde_json::json!(events));
+        active.template = Set(template);
+        Ok(active.update(db).await?.into())
+    }
+
+    /// Posts a sample of `event`, by default the first the channel
+    /// receives, to the channel right away and reports whether it was
+    /// accepted.
+    async fn test_notification_channel(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        event: Option<String>,
+    ) -> Result<NotificationChannelTest> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (channel, project) = authz::notification_channel(db, user, &id).await?;
+
+        let event = match event {
+            Some(event) => notifications::validate_events(&[event])?.remove(0),
+            None => notifications::channel_events(&channel)
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| notifications::DIGEST_EVENT.to_string()),
+        };
+        Ok(
+            match notifications::send_test(&channel, &project, &event).await {
+                Ok(()) => NotificationChannelTest {
+                    delivered: true,
+                    error: None,
+                },
+                Err(e) => NotificationChannelTest {
+                    delivered: false,
+                    error: Some(format!("{:#}", e)),
+                },
+            },
+        )
+    }
+
+    async fn remove_notification_channel(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (channel, _) = authz::notification_channel(db, user, &id).await?;
+
+        entities::NotificationChannel::delete_by_id(channel.id)
+            .exec(db)
+            .await?;
+        Ok(true)
+    }
+
+    /// Maps Prometheus series sent to the project's remote_write endpoint
+    /// onto a benchmark and measure. Matching samples are averaged per hour
+    /// into one report per branch and testbed.
+    async fn add_remote_write_rule(
+        &self,
+        ctx: &Context<'_>,
+        project_slug: String,
+        input: RemoteWriteRuleInput,
+    ) -> Result<RemoteWriteRule> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &project_slug).await?;
+
+        let metric = input.metric.trim();
+        let measure = input.measure.trim();
+        let branch = input.branch.trim();
+        if metric.is_empty() || measure.is_empty() || branch.is_empty() {
+            return Err("Metric, measure and branch can't be empty".into());
+        }
+        remote_write::validate_template(&input.benchmark)?;
+        remote_write::validate_template(&input.testbed)?;
+        let mut matchers = serde_json::Map::new();
+        for matcher in input.matchers.unwrap_or_default() {
+            let name = matcher.name.trim();
+            if name.is_empty() || name == "__name__" {
+                return Err("Match the metric name with `metric`, not a label matcher".into());
+            }
+            if matchers
+                .insert(name.to_string(), matcher.value.into())
+                .is_some()
+            {
+                return Err(format!("Label {} is matched twice", name).into());
+            }
+        }
+
+        let rule = remote_write_rule::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project.id),
+            metric: Set(metric.to_string()),
+            matchers: Set(matchers.into()),
+            benchmark: Set(input.benchmark.trim().to_string()),
+            measure: Set(measure.to_string()),
+            branch: Set(branch.to_string()),
+            testbed: Set(input.testbed.trim().to_string()),
+            created_at: Set(Utc::now().fixed_offset()),
+            flushed_until: Set(None),
+        }
+        .insert(db)
+        .await?;
+        Ok(rule.into())
+    }
+
+    /// Stops mapping a rule's series. Samples it already collected for the
+    /// current hour are discarded.
+    async fn remove_remote_write_rule(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let (rule, _) = authz::remote_write_rule(db, user, &id).await?;
+
+        entities::RemoteWriteRule::delete_by_id(rule.id)
+            .exec(db)
+            .await?;
+        Ok(true)
+    }
+
+    /// Re-queues a dead job. Admin only.
+    async fn retry_job(&self, ctx: &Context<'_>, id: ID) -> Result<Job> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        user.require_admin()?;
+
+        let job_id = Uuid::parse_str(&id.0)?;
+        let job = jobs::retry(db, job_id).await?;
+
+        Ok(job.into())
+    }
+
+    /// Creates demo projects owned by the caller, with months of noisy
+    /// history and injected regressions already evaluated. Admin only.
+    async fn seed_demo_data(&self, ctx: &Context<'_>, input: SeedDemoInput) -> Result<DemoData> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+        user.require_admin()?;
+
+        let days = input.days.unwrap_or(demo::DEFAULT_DAYS as i32);
+        if !(1..=demo::MAX_DAYS as i32).contains(&days) {
+            return Err(format!("days must be between 1 and {}", demo::MAX_DAYS).into());
+        }
+        let existing = demo::existing_slugs(db).await?;
+        if !existing.is_empty() {
+            return Err(format!(
+                "Demo projects already exist: {}; delete them first",
+                existing.join(", ")
+            )
+            .into());
+        }
+
+        let seed = input.seed.unwrap_or(0) as u64;
+        let summary = demo::seed(db, user.user_id(), days as u32, seed).await?;
+        cache.invalidate_user_projects(user.user_id()).await;
+
+        Ok(DemoData {
+            projects: summary.projects.into_iter().map(Into::into).collect(),
+            reports: summary.reports as i32,
+            alerts: summary.alerts as i32,
+        })
+    }
+
+    async fn signup(&self, ctx: &Context<'_>, input: SignupInput) -> Result<AuthPayload> {
+        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+
+        let (user, session_token) = auth_service
+            .signup_direct(&input.email, &input.password, input.name)
+            .await
+            .map_err(async_graphql::Error::new)?;
+
+        Ok(AuthPayload {
+            user: user.into(),
+            session_token,
+        })
+    }
+
+    async fn signin(&self, ctx: &Context<'_>, input: SigninInput) -> Result<AuthPayload> {
+        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+
+        let (user, session_token) = auth_service
+            .signin_direct(&input.email, &input.password)
+            .await
+            .map_err(async_graphql::Error::new)?;
+
+        Ok(AuthPayload {
+            user: user.into(),
+            session_token,
+        })
+    }
+
+    async fn signout(&self, ctx: &Context<'_>) -> Result<bool> {
+        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        if !user.is_session_auth() {
+            return Err("Signout requires session authentication, not API key".into());
+        }
+
+        auth_service
+            .signout_direct(&user.token)
+            .await
+            .map_err(async_graphql::Error::new)
+    }
+
+    async fn create_api_key(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateApiKeyInput,
+    ) -> Result<CreateApiKeyPayload> {
+        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        if !user.is_session_auth() {
+            return Err("Creating API keys requires session authentication, not API key".into());
+        }
+
+        let (api_key, secret) = auth_service
+            .create_api_key_direct(&user.token, &input.name, input.scopes)
+            .await
+            .map_err(async_graphql::Error::new)?;
+
+        Ok(CreateApiKeyPayload {
+            api_key: api_key.into(),
+            secret,
+        })
+    }
+
+    async fn revoke_api_key(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
+        let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        if !user.is_session_auth() {
+            return Err("Revoking API keys requires session authentication, not API key".into());
+        }
+
+        auth_service
+            .revoke_api_key_direct(&user.token, &id.0)
+            .await
+            .map_err(async_graphql::Error::new)
+    }
+}
+
+/// Asks GitHub for the merge-base of a PR head and its base branch. Failures
+/// are logged and fall back to comparing against the base branch history.
+async fn resolve_merge_base(
+    project: &project::Model,
+    base_branch: &str,
+    git_hash: Option<&str>,
+) -> Option<String> {
+    let (Some(repo), Some(token), Some(head)) = (
+        &project.github_repo,
+        github::project_token(project),
+        git_hash,
+    ) else {
+        return None;
+    };
+
+    match GitHubClient::new(&token)
+        .merge_base(repo, base_branch, head)
+        .await
+    {
+        Ok(sha) => Some(sha),
+        Err(e) => {
+            tracing::warn!("Failed to resolve merge-base for {}: {:#}", head, e);
+            None
+        }
+    }
+}
+
+/// Largest batch accepted by `appendReportMetrics`.
+const MAX_METRIC_BATCH: usize = 10_000;
+
+/// Largest compressed command output accepted by `attachReportOutput`.
+const MAX_REPORT_OUTPUT_BYTES: usize = 1024 * 1024;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Longest file name accepted by `createFlamegraphUploadUrl`.
+const MAX_FLAMEGRAPH_NAME_LEN: usize = 255;
+
+async fn new_report(project: &project::Model, input: OpenReportInput) -> Result<NewReport> {
+    let context = input
+        .context
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| (e.key, e.value))
+        .collect();
+    let mut context = context::validate_context(context)?;
+    let tags = report_filters::validate_tags(input.tags.unwrap_or_default())?;
+    let expected_benchmarks =
+        manifests::validate_expected_benchmarks(input.expected_benchmarks.unwrap_or_default())?;
+    let mut commit_message = input.commit_message;
+    if project.redact_secrets {
+        for (_, value) in &mut context {
+            *value = redaction::redact(value).0.into_owned();
+        }
+        commit_message = commit_message.map(|m| redaction::redact(&m).0.into_owned());
+    }
+
+    let merge_base_hash = match (&input.merge_base_hash, &input.base_branch) {
+        (Some(hash), _) => Some(hash.clone()),
+        (None, Some(base)) => {
+            // A merge queue commit sits on top of the base branch, so its
+            // merge-base is the base commit the queue entry was built on
+            let head = input
+                .evaluated_commit
+                .as_deref()
+                .or(input.git_hash.as_deref());
+            resolve_merge_base(project, base, head).await
+        }
+        (None, None) => None,
+    };
+
+    let id = input
+        .id
+        .map(|id| Uuid::parse_str(&id))
+        .transpose()
+        .map_err(|_| "Invalid report id")?;
+    let version = input
+        .version
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let kind = match input.kind {
+        Some(kind) => kind.to_db_value(),
+        None if input.pr_number.is_some() => report::ReportKind::Pr,
+        None if version.is_some() => report::ReportKind::Release,
+        None => report::ReportKind::Manual,
+    };
+
+    Ok(NewReport {
+        id,
+        project_id: project.id,
+        branch: input.branch,
+        testbed: input.testbed,
+        git_hash: input.git_hash,
+        pr_number: input.pr_number,
+        commit_message,
+        commit_author: input.commit_author,
+        committed_at: input.committed_at.map(|t| t.fixed_offset()),
+        base_branch: input.base_branch,
+        merge_base_hash,
+        evaluated_commit: input.evaluated_commit,
+        version,
+        kind,
+        exit_code: input.exit_code,
+        partial: input
+            .partial
+            .unwrap_or(input.exit_code.is_some_and(|code| code != 0)),
+        created_at: input.created_at.unwrap_or_else(Utc::now).fixed_offset(),
+        context,
+        tags,
+        expected_benchmarks,
+    })
+}
+
+/// Stores a complete report and queues its evaluation. `createReport` and
+/// the REST API both submit through here, so they validate alike.
+pub async fn submit_report(
+    db: &DatabaseConnection,
+    cache: &AppCache,
+    project: &project::Model,
+    input: CreateReportInput,
+) -> Result<report::Model> {
+    let (input, metrics) = input.split();
+    if metrics.is_empty() {
+        return Err("Report must contain at least one metric".into());
+    }
+    if let Some(report) = resubmitted_report(db, project, input.id.as_ref()).await? {
+        return Ok(report);
+    }
+
+    let id = input.id.clone();
+    let new_report = new_report(project, input).await?;
+    let metrics = metrics.into_iter().map(Into::into).collect();
+
+    let txn = db.begin().await?;
+    let report =
+        match ingest::insert_report(&txn, &mut Dimensions::default(), new_report, metrics).await {
+            Ok((report, _)) => report,
+            Err(e) => {
+                txn.rollback().await?;
+                return raced_resubmission(db, project, id.as_ref(), e).await;
+            }
+        };
+    ingest::enqueue_evaluation(&txn, report.id).await?;
+    txn.commit().await?;
+
+    cache.invalidate_latest_reports(project.id).await;
+
+    Ok(report)
+}
+
+/// Adds a project to a group; adding it twice is a no-op.
+async fn add_group_member<C: ConnectionTrait>(
+    db: &C,
+    group_id: Uuid,
+    project_id: Uuid,
+) -> Result<(), DbErr> {
+    entities::ProjectGroupMember::insert(project_group_member::ActiveModel {
+        group_id: Set(group_id),
+        project_id: Set(project_id),
+        created_at: Set(Utc::now().fixed_offset()),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            project_group_member::Column::GroupId,
+            project_group_member::Column::ProjectId,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await?;
+    Ok(())
+}
+
+/// The report an earlier submission with the same submitter-chosen id
+/// created, so a retried submission doesn't create it twice
+async fn resubmitted_report(
+    db: &DatabaseConnection,
+    project: &project::Model,
+    id: Option<&ID>,
+) -> Result<Option<report::Model>> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+    let id = Uuid::parse_str(id).map_err(|_| "Invalid report id")?;
+    match entities::Report::find_by_id(id).one(db).await? {
+        Some(report) if report.project_id == project.id => Ok(Some(report)),
+        Some(_) => Err("Report id is already in use".into()),
+        None => Ok(None),
+    }
+}
+
+/// The report stored by a concurrent submission with the same id, when
+/// `err` is this one losing the race to insert it; `err` otherwise.
+async fn raced_resubmission(
+    db: &DatabaseConnection,
+    project: &project::Model,
+    id: Option<&ID>,
+    err: DbErr,
+) -> Result<report::Model> {
+    if let Some(SqlErr::UniqueConstraintViolation(_)) = err.sql_err() {
+        if let Some(report) = resubmitted_report(db, project, id).await? {
+            return Ok(report);
+        }
+    }
+    Err(err.into())
+}
+
+/// Loads one of the caller's reports that is still being uploaded.
+async fn find_open_report(
+    db: &DatabaseConnection,
+    user: &AuthUser,
+    report_id: &ID,
+) -> Result<report::Model> {
+    let (report, _) = authz::report(db, user, report_id).await?;
+    if report.finalized {
+        return Err("Report is already finalized".into());
+    }
+
+    Ok(report)
 }
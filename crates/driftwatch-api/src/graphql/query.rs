@@ -1,14 +1,42 @@
 use std::sync::Arc;
 
-use async_graphql::{Context, Object, Result};
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::collections::HashMap;
+
+use async_graphql::{Context, Object, Result, ID};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
 use tracing::{info_span, instrument, Instrument};
+use uuid::Uuid;
 
-use super::types::{ApiKey, Project, User};
+use super::types::{
+    Alert, ApiKey, ApiVersion, BenchmarkMatch, DeprecatedFieldUsage, Job, JobStatusInput,
+    MetricInput, Project, ProjectGroup, ProjectTemplate, PublicProject, PublicProjectPage,
+    PublicProjectSort, Report, SimulateThresholdsInput, SimulatedAlert, ThresholdSimulation,
+    ThresholdTestInput, ThresholdTestResult, User,
+};
 use crate::auth::AuthUser;
 use crate::cache::AppCache;
-use crate::entities::{self, project};
+use crate::catalog;
+use crate::db::read_connection;
+use crate::entities::{
+    self, benchmark, branch, job, measure, metric, project, project_group, report, testbed,
+};
+use crate::evaluation;
+use crate::graphql::authz;
+use crate::graphql::versioning::DeprecationLog;
 use crate::grpc::AuthServiceImpl;
+use crate::ingest;
+use crate::search;
+use crate::simulation::{self, SimulationConfig, SimulationScope};
+use crate::templates;
+
+/// Matches returned by `searchBenchmarks` when no limit is given
+const DEFAULT_SEARCH_RESULTS: u64 = 50;
+
+/// Projects per page of `publicProjects` when no limit is given
+const DEFAULT_CATALOG_PAGE: u64 = 20;
 
 pub struct QueryRoot;
 
@@ -43,8 +71,7 @@ impl QueryRoot {
         tracing::info!(cache = "miss");
 
         let db_result = async {
-            entities::Project::find()
-                .filter(project::Column::UserId.eq(user_id))
+            authz::projects(user)
                 .order_by_desc(project::Column::CreatedAt)
                 .all(db)
                 .await
@@ -95,15 +122,9 @@ impl QueryRoot {
 
         tracing::info!(cache = "miss");
 
-        let project = async {
-            entities::Project::find()
-                .filter(project::Column::UserId.eq(user_id))
-                .filter(project::Column::Slug.eq(&slug))
-                .one(db)
-                .await
-        }
-        .instrument(info_span!("db_query", table = "project"))
-        .await?;
+        let project = authz::find_project(db, user, &slug)
+            .instrument(info_span!("db_query", table = "project"))
+            .await?;
 
         let result = match project {
             Some(p) => {
@@ -127,6 +148,284 @@ impl QueryRoot {
         Ok(result)
     }
 
+    async fn project_groups(&self, ctx: &Context<'_>) -> Result<Vec<ProjectGroup>> {
+        let db = read_connection(ctx)?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let groups = authz::groups(user)
+            .order_by_asc(project_group::Column::Name)
+            .all(db)
+            .await?;
+        Ok(groups.into_iter().map(Into::into).collect())
+    }
+
+    async fn project_group(&self, ctx: &Context<'_>, slug: String) -> Result<Option<ProjectGroup>> {
+        let db = read_connection(ctx)?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let group = authz::find_group(db, user, &slug).await?;
+        Ok(group.map(Into::into))
+    }
+
+    /// Benchmarks whose name contains `pattern`, across the caller's own
+    /// projects and public ones, with the latest value of each series
+    async fn search_benchmarks(
+        &self,
+        ctx: &Context<'_>,
+        pattern: String,
+        limit: Option<i32>,
+    ) -> Result<Vec<BenchmarkMatch>> {
+        let db = read_connection(ctx)?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err("Search pattern cannot be empty".into());
+        }
+
+        let limit = limit.map_or(DEFAULT_SEARCH_RESULTS, |l| l.max(0) as u64);
+        let hits = search::search_benchmarks(db, user.user_id(), pattern, limit).await?;
+        Ok(hits.into_iter().map(Into::into).collect())
+    }
+
+    /// Projects their owners made public, for anyone to browse without
+    /// signing in. `search` matches the name, slug or description.
+    async fn public_projects(
+        &self,
+        ctx: &Context<'_>,
+        search: Option<String>,
+        #[graphql(default_with = "PublicProjectSort::Recent")] sort: PublicProjectSort,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<PublicProjectPage> {
+        let db = read_connection(ctx)?;
+
+        let limit = limit.map_or(DEFAULT_CATALOG_PAGE, |l| l.clamp(1, 100) as u64);
+        let offset = offset.unwrap_or(0).max(0) as u64;
+        let search = search.as_deref();
+        let projects = catalog::public_projects(db, search, sort.into(), limit, offset).await?;
+        let total_count = catalog::count_public_projects(db, search).await?;
+
+        Ok(PublicProjectPage {
+            total_count: total_count as i32,
+            projects: projects.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    async fn public_project(&self, ctx: &Context<'_>, id: ID) -> Result<Option<PublicProject>> {
+        let db = read_connection(ctx)?;
+        let Ok(id) = Uuid::parse_str(&id) else {
+            return Ok(None);
+        };
+        Ok(catalog::public_project(db, id).await?.map(Into::into))
+    }
+
+    async fn report(&self, ctx: &Context<'_>, id: ID) -> Result<Option<Report>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let report = authz::find_report(db, user, &id).await?;
+        Ok(report.map(|(report, _)| report.into()))
+    }
+
+    async fn alert(&self, ctx: &Context<'_>, id: ID) -> Result<Option<Alert>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let alert = authz::find_alert(db, user, &id).await?;
+        Ok(alert.map(|(alert, _)| alert.into()))
+    }
+
+    /// Which thresholds would fire for an existing report or hypothetical
+    /// metrics, without creating alerts. Comparisons without enough history
+    /// for a baseline are left out.
+    async fn threshold_test(
+        &self,
+        ctx: &Context<'_>,
+        input: ThresholdTestInput,
+    ) -> Result<Vec<ThresholdTestResult>> {
+        let db = read_connection(ctx)?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &input.project_slug).await?;
+
+        let (report, metrics) = match (input.report_id, input.metrics) {
+            (Some(id), None) => {
+                let report = entities::Report::find_by_id(Uuid::parse_str(&id.0)?)
+                    .filter(report::Column::ProjectId.eq(project.id))
+                    .one(db)
+                    .await?
+                    .ok_or("Report not found")?;
+                let metrics = entities::Metric::find()
+                    .filter(metric::Column::ReportId.eq(report.id))
+                    .all(db)
+                    .await?;
+                (report, metrics)
+            }
+            (None, Some(metrics)) => {
+                let branch = input.branch.ok_or("branch is required with metrics")?;
+                let testbed = input.testbed.ok_or("testbed is required with metrics")?;
+                hypothetical_report(db, &project, &branch, &testbed, metrics).await?
+            }
+            _ => return Err("Provide either reportId or metrics".into()),
+        };
+
+        let checks = evaluation::check_thresholds(db, &project, &report, &metrics).await?;
+
+        let benchmark_names: HashMap<Uuid, String> = entities::Benchmark::find()
+            .filter(benchmark::Column::Id.is_in(metrics.iter().map(|m| m.benchmark_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|b| (b.id, b.name))
+            .collect();
+        let measure_names: HashMap<Uuid, String> = entities::Measure::find()
+            .filter(measure::Column::ProjectId.eq(project.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m.name))
+            .collect();
+
+        Ok(checks
+            .into_iter()
+            .map(|check| {
+                let metric = &metrics[check.metric_index];
+                ThresholdTestResult {
+                    benchmark: benchmark_names
+                        .get(&metric.benchmark_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    measure: measure_names
+                        .get(&metric.measure_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    value: metric.value,
+                    baseline_value: check.baseline,
+                    percent_change: check.percent_change,
+                    would_alert: check.violated,
+                    threshold: check.threshold.into(),
+                }
+            })
+            .collect())
+    }
+
+    /// Replays the project's history through a proposed threshold
+    /// configuration and reports the alerts it would have raised, without
+    /// creating any
+    async fn simulate_thresholds(
+        &self,
+        ctx: &Context<'_>,
+        input: SimulateThresholdsInput,
+    ) -> Result<ThresholdSimulation> {
+        let db = read_connection(ctx)?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+
+        let project = authz::project(db, cache, user, &input.project_slug).await?;
+
+        let window = input.window.unwrap_or(evaluation::BASELINE_WINDOW as i32);
+        if window < 1 {
+            return Err("window must be at least 1".into());
+        }
+        let model = input.model.to_model();
+        let (upper_boundary, lower_boundary) = match (input.upper_boundary, input.lower_boundary) {
+            (None, None) => (Some(model.default_upper_boundary()), None),
+            boundaries => boundaries,
+        };
+        let config = SimulationConfig {
+            model,
+            window: window as usize,
+            upper_boundary,
+            lower_boundary,
+            min_sample_size: input.min_sample_size.unwrap_or(2).max(1) as usize,
+        };
+
+        let branches: HashMap<Uuid, String> = entities::Branch::find()
+            .filter(branch::Column::ProjectId.eq(project.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|b| (b.id, b.name))
+            .collect();
+        let testbeds: HashMap<Uuid, String> = entities::Testbed::find()
+            .filter(testbed::Column::ProjectId.eq(project.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|t| (t.id, t.name))
+            .collect();
+        let measures: HashMap<Uuid, String> = entities::Measure::find()
+            .filter(measure::Column::ProjectId.eq(project.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m.name))
+            .collect();
+        let find_id = |names: &HashMap<Uuid, String>, name: Option<&str>, kind: &str| {
+            name.map(|name| {
+                names
+                    .iter()
+                    .find(|(_, n)| n.as_str() == name)
+                    .map(|(id, _)| *id)
+                    .ok_or_else(|| {
+                        async_graphql::Error::new(format!("{} not found: {}", kind, name))
+                    })
+            })
+            .transpose()
+        };
+
+        let scope = SimulationScope {
+            project_id: project.id,
+            branch_id: find_id(&branches, input.branch.as_deref(), "Branch")?,
+            testbed_id: find_id(&testbeds, input.testbed.as_deref(), "Testbed")?,
+            measure_id: find_id(&measures, input.measure.as_deref(), "Measure")?,
+            since: input.days.map(|days| {
+                (chrono::Utc::now() - chrono::Duration::days(days.into())).fixed_offset()
+            }),
+        };
+
+        let outcome = simulation::simulate(db, &scope, &config).await?;
+        let actual_alerts = simulation::actual_alert_count(db, &scope).await?;
+
+        let benchmark_names: HashMap<Uuid, String> = entities::Benchmark::find()
+            .filter(benchmark::Column::Id.is_in(outcome.alerts.iter().map(|a| a.benchmark_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|b| (b.id, b.name))
+            .collect();
+        let name =
+            |names: &HashMap<Uuid, String>, id: Uuid| names.get(&id).cloned().unwrap_or_default();
+
+        Ok(ThresholdSimulation {
+            series: outcome.series as i32,
+            comparisons: outcome.comparisons as i32,
+            alerts: outcome
+                .alerts
+                .into_iter()
+                .map(|a| SimulatedAlert {
+                    report_id: ID(a.report_id.to_string()),
+                    benchmark: name(&benchmark_names, a.benchmark_id),
+                    measure: name(&measures, a.measure_id),
+                    branch: name(&branches, a.branch_id),
+                    testbed: name(&testbeds, a.testbed_id),
+                    created_at: a.created_at.into(),
+                    value: a.value,
+                    baseline_value: a.baseline,
+                    score: a.score,
+                })
+                .collect(),
+            actual_alerts: actual_alerts as i32,
+        })
+    }
+
+    /// Templates available when creating a project
+    async fn project_templates(&self) -> Vec<ProjectTemplate> {
+        templates::TEMPLATES.iter().map(Into::into).collect()
+    }
+
     async fn me(&self, ctx: &Context<'_>) -> Result<User> {
         let user = ctx.data::<AuthUser>()?;
         Ok(user.user.clone().into())
@@ -147,4 +446,126 @@ impl QueryRoot {
 
         Ok(api_keys.into_iter().map(Into::into).collect())
     }
+
+    /// Versions of the API and server. Needs no authentication, so clients
+    /// can check compatibility first.
+    async fn api_version(&self) -> ApiVersion {
+        ApiVersion::current()
+    }
+
+    /// Callers still using deprecated fields since the server started, most
+    /// recent first. Admin only.
+    async fn deprecated_field_usage(&self, ctx: &Context<'_>) -> Result<Vec<DeprecatedFieldUsage>> {
+        let user = ctx.data::<AuthUser>()?;
+        user.require_admin()?;
+        let log = ctx.data::<DeprecationLog>()?;
+        Ok(log.usage().into_iter().map(Into::into).collect())
+    }
+
+    /// Background jobs, most recent first. Admin only.
+    async fn jobs(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<JobStatusInput>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Job>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        user.require_admin()?;
+
+        let mut query = entities::Job::find();
+        if let Some(status) = status {
+            query = query.filter(job::Column::Status.eq(status.to_db_value()));
+        }
+
+        let jobs = query
+            .order_by_desc(job::Column::CreatedAt)
+            .limit(limit.unwrap_or(100).clamp(1, 1000) as u64)
+            .all(db)
+            .await?;
+
+        Ok(jobs.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Builds an unsaved report for hypothetical metrics. Names that don't exist
+/// in the project get fresh ids, so they simply have no history to compare
+/// against.
+async fn hypothetical_report<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    branch: &str,
+    testbed: &str,
+    input: Vec<MetricInput>,
+) -> Result<(report::Model, Vec<metric::Model>)> {
+    let branch_id = ingest::find_branch(db, project.id, branch)
+        .await?
+        .map_or_else(Uuid::new_v4, |b| b.id);
+    let testbed_id = ingest::find_testbed(db, project.id, testbed)
+        .await?
+        .map_or_else(Uuid::new_v4, |t| t.id);
+
+    let benchmarks: HashMap<String, Uuid> = entities::Benchmark::find()
+        .filter(benchmark::Column::ProjectId.eq(project.id))
+        .filter(benchmark::Column::Name.is_in(input.iter().map(|m| m.benchmark.clone())))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| (b.name, b.id))
+        .collect();
+    let measures: HashMap<String, Uuid> = entities::Measure::find()
+        .filter(measure::Column::ProjectId.eq(project.id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.name, m.id))
+        .collect();
+
+    let now = chrono::Utc::now().fixed_offset();
+    let report = report::Model {
+        id: Uuid::new_v4(),
+        project_id: project.id,
+        branch_id,
+        testbed_id,
+        git_hash: None,
+        pr_number: None,
+        commit_message: None,
+        commit_author: None,
+        committed_at: None,
+        base_branch_id: None,
+        merge_base_hash: None,
+        evaluated_commit: None,
+        version: None,
+        kind: report::ReportKind::Manual,
+        exit_code: None,
+        partial: false,
+        finalized: true,
+        excluded: false,
+        status: report::ReportStatus::Pending,
+        created_at: now,
+    };
+
+    let metrics = input
+        .into_iter()
+        .map(|m| metric::Model {
+            id: Uuid::new_v4(),
+            report_id: report.id,
+            benchmark_id: benchmarks
+                .get(&m.benchmark)
+                .copied()
+                .unwrap_or_else(Uuid::new_v4),
+            measure_id: measures
+                .get(&m.measure)
+                .copied()
+                .unwrap_or_else(Uuid::new_v4),
+            value: m.value,
+            lower: m.lower_value,
+            upper: m.upper_value,
+            reported_change: m.reported_change,
+            outliers: m.outliers,
+            created_at: now,
+        })
+        .collect();
+
+    Ok((report, metrics))
 }
@@ -1,10 +1,94 @@
+use std::sync::Arc;
+
 use async_graphql::{EmptySubscription, Schema};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
 
 use super::mutation::MutationRoot;
 use super::query::QueryRoot;
+use super::versioning::{ApiVersioning, DeprecationLog};
+use crate::auth::{bearer_token, validate_token, TsaAuth};
 
 pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
 
 pub fn build_schema() -> AppSchema {
-    Schema::build(QueryRoot, MutationRoot, EmptySubscription).finish()
+    build_schema_with(true)
+}
+
+/// Builds the schema, without `__schema` and `__type` when `introspection`
+/// is off so production servers don't describe themselves to anyone who
+/// asks. The SDL endpoint still serves the schema to signed-in users.
+pub fn build_schema_with(introspection: bool) -> AppSchema {
+    let deprecations = DeprecationLog::default();
+    let builder = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .extension(ApiVersioning::new(deprecations.clone()))
+        .data(deprecations);
+    if introspection {
+        builder.finish()
+    } else {
+        builder.disable_introspection().finish()
+    }
+}
+
+#[derive(Clone)]
+struct SdlState {
+    sdl: Arc<String>,
+    auth: Arc<TsaAuth>,
+}
+
+async fn schema_sdl(State(state): State<SdlState>, headers: HeaderMap) -> Response {
+    let Some(token) = bearer_token(&headers) else {
+        return (StatusCode::UNAUTHORIZED, "Sign in to download the schema").into_response();
+    };
+    if let Err(e) = validate_token(token, &state.auth).await {
+        return (StatusCode::UNAUTHORIZED, e.0).into_response();
+    }
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        state.sdl.as_str().to_string(),
+    )
+        .into_response()
+}
+
+/// Routes for `GET /graphql/schema.sdl`: the schema in SDL, for client code
+/// generation, to any signed-in user whether or not introspection is on.
+pub fn sdl_router<S: Clone + Send + Sync + 'static>(
+    schema: &AppSchema,
+    auth: Arc<TsaAuth>,
+) -> Router<S> {
+    Router::new()
+        .route("/graphql/schema.sdl", get(schema_sdl))
+        .with_state(SdlState {
+            sdl: Arc::new(schema.sdl()),
+            auth,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_introspection_can_be_disabled() {
+        let query = "{ __schema { queryType { name } } }";
+        let open = build_schema_with(true).execute(query).await;
+        assert!(open.errors.is_empty(), "{:?}", open.errors);
+
+        // Disabled introspection fields resolve to nothing
+        let closed = build_schema_with(false);
+        for query in [query, r#"{ __type(name: "Project") { name } }"#] {
+            let response = closed.execute(query).await;
+            let data = response.data.into_json().unwrap();
+            assert!(
+                data.as_object().unwrap().values().all(|v| v.is_null()),
+                "{}",
+                data
+            );
+        }
+        // The SDL is still there to serve
+        assert!(closed.sdl().contains("type Project"));
+    }
 }
@@ -1,27 +1,45 @@
 use async_graphql::dataloader::DataLoader;
-use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject, ID};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Result, SimpleObject, ID};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 use uuid::Uuid;
 
 use crate::entities::alert::AlertStatus as DbAlertStatus;
-use crate::loaders::{MetricLoader, ThresholdLoader};
+use crate::entities::{self, threshold_evaluation};
+use crate::loaders::{MetricLoader, ProjectLoader, ThresholdLoader};
+use crate::owners;
 
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
 pub enum AlertStatusInput {
+    Unconfirmed,
     Active,
     Acknowledged,
+    Investigating,
     Resolved,
+    WontFix,
 }
 
 impl AlertStatusInput {
     pub fn to_db_value(&self) -> DbAlertStatus {
         match self {
+            AlertStatusInput::Unconfirmed => DbAlertStatus::Unconfirmed,
             AlertStatusInput::Active => DbAlertStatus::Active,
             AlertStatusInput::Acknowledged => DbAlertStatus::Acknowledged,
+            AlertStatusInput::Investigating => DbAlertStatus::Investigating,
             AlertStatusInput::Resolved => DbAlertStatus::Resolved,
+            AlertStatusInput::WontFix => DbAlertStatus::WontFix,
         }
     }
 }
 
+/// Triage changes; omitted fields are left as they are and an empty string
+/// clears `assignee` or `resolution_note`.
+#[derive(InputObject)]
+pub struct UpdateAlertInput {
+    pub status: Option<AlertStatusInput>,
+    pub assignee: Option<String>,
+    pub resolution_note: Option<String>,
+}
+
 #[derive(SimpleObject)]
 #[graphql(complex, cache_control(max_age = 60))]
 pub struct Alert {
@@ -30,7 +48,12 @@ pub struct Alert {
     pub percent_change: f64,
     pub baseline_value: f64,
     pub current_value: f64,
+    pub assignee: Option<String>,
+    pub resolution_note: Option<String>,
+    pub github_issue_number: Option<i32>,
+    pub github_issue_url: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
     #[graphql(skip)]
     pub metric_id: Uuid,
     #[graphql(skip)]
@@ -40,9 +63,12 @@ pub struct Alert {
 impl From<crate::entities::alert::Model> for Alert {
     fn from(model: crate::entities::alert::Model) -> Self {
         let status = match model.status {
+            DbAlertStatus::Unconfirmed => "unconfirmed",
             DbAlertStatus::Active => "active",
             DbAlertStatus::Acknowledged => "acknowledged",
+            DbAlertStatus::Investigating => "investigating",
             DbAlertStatus::Resolved => "resolved",
+            DbAlertStatus::WontFix => "wont_fix",
         };
 
         Self {
@@ -51,7 +77,12 @@ impl From<crate::entities::alert::Model> for Alert {
             percent_change: model.percent_change,
             baseline_value: model.baseline_value,
             current_value: model.current_value,
+            assignee: model.assignee,
+            resolution_note: model.resolution_note,
+            github_issue_number: model.github_issue_number,
+            github_issue_url: model.github_issue_url,
             created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
             metric_id: model.metric_id,
             threshold_id: model.threshold_id,
         }
@@ -68,6 +99,40 @@ impl Alert {
             .ok_or_else(|| "Metric not found".into())
     }
 
+    /// Who to ping, from the project's benchmark owner rules
+    async fn owners(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let loader = ctx.data::<DataLoader<MetricLoader>>()?;
+        let metric = loader
+            .load_one(self.metric_id)
+            .await?
+            .ok_or("Metric not found")?;
+        let Some(benchmark) = entities::Benchmark::find_by_id(metric.benchmark_id)
+            .one(db)
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+
+        let rules = owners::project_rules(db, benchmark.project_id).await?;
+        Ok(owners::owners_for(&rules, &benchmark.name))
+    }
+
+    /// Checks of this alert's threshold against its metric, oldest first:
+    /// the evaluation that raised it and any re-evaluations since
+    async fn evaluations(&self, ctx: &Context<'_>) -> Result<Vec<super::ThresholdEvaluation>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let evaluations = entities::ThresholdEvaluation::find()
+            .filter(threshold_evaluation::Column::MetricId.eq(self.metric_id))
+            .filter(threshold_evaluation::Column::ThresholdId.eq(self.threshold_id))
+            .order_by_asc(threshold_evaluation::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(evaluations.into_iter().map(Into::into).collect())
+    }
+
     async fn threshold(&self, ctx: &Context<'_>) -> Result<super::Threshold> {
         let loader = ctx.data::<DataLoader<ThresholdLoader>>()?;
         loader
@@ -75,4 +140,13 @@ impl Alert {
             .await?
             .ok_or_else(|| "Threshold not found".into())
     }
+
+    async fn project(&self, ctx: &Context<'_>) -> Result<super::Project> {
+        let threshold = self.threshold(ctx).await?;
+        let loader = ctx.data::<DataLoader<ProjectLoader>>()?;
+        loader
+            .load_one(threshold.project_id)
+            .await?
+            .ok_or_else(|| "Workspace not found".into())
+    }
 }
@@ -0,0 +1,66 @@
+use async_graphql::{Enum, InputObject, SimpleObject, ID};
+
+use crate::entities::annotation::{self, AnnotationKind as DbAnnotationKind};
+
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 60))]
+pub struct Annotation {
+    pub id: ID,
+    /// `note`, `runner_upgrade`, `dependency_bump` or `config_change`
+    pub kind: String,
+    pub note: String,
+    /// Set when the annotation is attached to a report
+    pub report_id: Option<ID>,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<annotation::Model> for Annotation {
+    fn from(model: annotation::Model) -> Self {
+        let kind = match model.kind {
+            DbAnnotationKind::Note => "note",
+            DbAnnotationKind::RunnerUpgrade => "runner_upgrade",
+            DbAnnotationKind::DependencyBump => "dependency_bump",
+            DbAnnotationKind::ConfigChange => "config_change",
+        };
+
+        Self {
+            id: ID(model.id.to_string()),
+            kind: kind.to_string(),
+            note: model.note,
+            report_id: model.report_id.map(|id| ID(id.to_string())),
+            occurred_at: model.occurred_at.into(),
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum AnnotationKindInput {
+    Note,
+    RunnerUpgrade,
+    DependencyBump,
+    ConfigChange,
+}
+
+impl AnnotationKindInput {
+    pub fn to_db_value(&self) -> DbAnnotationKind {
+        match self {
+            AnnotationKindInput::Note => DbAnnotationKind::Note,
+            AnnotationKindInput::RunnerUpgrade => DbAnnotationKind::RunnerUpgrade,
+            AnnotationKindInput::DependencyBump => DbAnnotationKind::DependencyBump,
+            AnnotationKindInput::ConfigChange => DbAnnotationKind::ConfigChange,
+        }
+    }
+}
+
+/// Attaches a note to `report_id` when given, otherwise to the project's
+/// history at `occurred_at` (now by default).
+#[derive(InputObject)]
+pub struct CreateAnnotationInput {
+    pub project_slug: String,
+    pub report_id: Option<ID>,
+    pub kind: Option<AnnotationKindInput>,
+    pub note: String,
+    pub occurred_at: Option<chrono::DateTime<chrono::Utc>>,
+}
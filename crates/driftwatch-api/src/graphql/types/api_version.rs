@@ -0,0 +1,46 @@
+use async_graphql::{SimpleObject, ID};
+
+use crate::graphql::versioning::{self, DeprecatedUsage};
+
+#[derive(SimpleObject)]
+pub struct ApiVersion {
+    /// Bumped when a field or argument is removed or changes meaning
+    pub api_version: i32,
+    pub server_version: String,
+}
+
+impl ApiVersion {
+    pub fn current() -> Self {
+        Self {
+            api_version: versioning::API_VERSION,
+            server_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// A caller that used a deprecated field since the server started
+#[derive(SimpleObject)]
+pub struct DeprecatedFieldUsage {
+    /// `Type.field`
+    pub field: String,
+    pub user_id: Option<ID>,
+    /// API key name and prefix, `session` or `anonymous`
+    pub token: String,
+    /// The `x-driftwatch-client` header of the latest use
+    pub client: Option<String>,
+    pub count: i64,
+    pub last_used_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<DeprecatedUsage> for DeprecatedFieldUsage {
+    fn from(usage: DeprecatedUsage) -> Self {
+        Self {
+            field: usage.field,
+            user_id: usage.user_id.map(|id| ID(id.to_string())),
+            token: usage.token,
+            client: usage.client,
+            count: usage.count as i64,
+            last_used_at: usage.last_used_at,
+        }
+    }
+}
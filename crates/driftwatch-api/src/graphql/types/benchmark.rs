@@ -7,6 +7,10 @@ use crate::entities::benchmark;
 pub struct Benchmark {
     pub id: ID,
     pub name: String,
+    /// Name without the numeric parameter, for parameterized benchmarks
+    pub base_name: Option<String>,
+    /// Numeric parameter taken from the end of the name, e.g. input size
+    pub parameter: Option<f64>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -15,6 +19,8 @@ impl From<benchmark::Model> for Benchmark {
         Self {
             id: ID(model.id.to_string()),
             name: model.name,
+            base_name: model.base_name,
+            parameter: model.parameter,
             created_at: model.created_at.into(),
         }
     }
@@ -0,0 +1,30 @@
+use async_graphql::SimpleObject;
+
+use crate::search;
+
+#[derive(SimpleObject)]
+pub struct BenchmarkMatch {
+    pub project_slug: String,
+    pub project_name: String,
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    pub latest_value: f64,
+    pub latest_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<search::BenchmarkMatch> for BenchmarkMatch {
+    fn from(hit: search::BenchmarkMatch) -> Self {
+        Self {
+            project_slug: hit.project_slug,
+            project_name: hit.project_name,
+            benchmark: hit.benchmark,
+            measure: hit.measure,
+            branch: hit.branch,
+            testbed: hit.testbed,
+            latest_value: hit.latest_value,
+            latest_at: hit.latest_at.into(),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use uuid::Uuid;
+
+use crate::loaders::{BenchmarkLoader, MeasureLoader, TestbedLoader};
+
+#[derive(SimpleObject)]
+#[graphql(complex, cache_control(max_age = 300))]
+pub struct BenchmarkNoise {
+    pub id: ID,
+    pub sample_size: i32,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Standard deviation as a percentage of the mean
+    pub coefficient_of_variation: f64,
+    /// Whether the score exceeds the project's noise limit
+    pub noisy: bool,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    #[graphql(skip)]
+    pub benchmark_id: Uuid,
+    #[graphql(skip)]
+    pub measure_id: Uuid,
+    #[graphql(skip)]
+    pub testbed_id: Uuid,
+}
+
+impl BenchmarkNoise {
+    pub fn new(model: crate::entities::benchmark_noise::Model, limit: Option<f64>) -> Self {
+        Self {
+            id: ID(model.id.to_string()),
+            sample_size: model.sample_size,
+            mean: model.mean,
+            std_dev: model.std_dev,
+            coefficient_of_variation: model.coefficient_of_variation,
+            noisy: limit.is_some_and(|l| model.coefficient_of_variation > l),
+            updated_at: model.updated_at.into(),
+            benchmark_id: model.benchmark_id,
+            measure_id: model.measure_id,
+            testbed_id: model.testbed_id,
+        }
+    }
+}
+
+#[ComplexObject]
+impl BenchmarkNoise {
+    async fn benchmark(&self, ctx: &Context<'_>) -> Result<super::Benchmark> {
+        let loader = ctx.data::<DataLoader<BenchmarkLoader>>()?;
+        loader
+            .load_one(self.benchmark_id)
+            .await?
+            .ok_or_else(|| "Benchmark not found".into())
+    }
+
+    async fn measure(&self, ctx: &Context<'_>) -> Result<super::Measure> {
+        let loader = ctx.data::<DataLoader<MeasureLoader>>()?;
+        loader
+            .load_one(self.measure_id)
+            .await?
+            .ok_or_else(|| "Measure not found".into())
+    }
+
+    async fn testbed(&self, ctx: &Context<'_>) -> Result<super::Testbed> {
+        let loader = ctx.data::<DataLoader<TestbedLoader>>()?;
+        loader
+            .load_one(self.testbed_id)
+            .await?
+            .ok_or_else(|| "Testbed not found".into())
+    }
+}
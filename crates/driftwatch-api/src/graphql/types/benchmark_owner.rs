@@ -0,0 +1,29 @@
+use async_graphql::{InputObject, SimpleObject, ID};
+
+use crate::entities::benchmark_owner;
+
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 60))]
+pub struct BenchmarkOwner {
+    pub id: ID,
+    pub pattern: String,
+    pub owners: Vec<String>,
+}
+
+impl From<benchmark_owner::Model> for BenchmarkOwner {
+    fn from(model: benchmark_owner::Model) -> Self {
+        Self {
+            id: ID(model.id.to_string()),
+            owners: model.owner_list(),
+            pattern: model.pattern,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct BenchmarkOwnerInput {
+    /// Benchmark name glob; `*` matches any run of characters, `?` one
+    pub pattern: String,
+    /// GitHub handles or teams, e.g. `@alice` or `@org/team`
+    pub owners: Vec<String>,
+}
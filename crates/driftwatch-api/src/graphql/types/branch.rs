@@ -1,6 +1,6 @@
 use async_graphql::{SimpleObject, ID};
 
-use crate::entities::branch;
+use crate::entities::{branch, branch_alias};
 
 #[derive(SimpleObject, Clone)]
 #[graphql(cache_control(max_age = 300))]
@@ -19,3 +19,21 @@ impl From<branch::Model> for Branch {
         }
     }
 }
+
+/// Another name reports may use for `branch`
+#[derive(SimpleObject, Clone)]
+pub struct BranchAlias {
+    pub alias: String,
+    pub branch: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl BranchAlias {
+    pub fn new(alias: branch_alias::Model, branch: branch::Model) -> Self {
+        Self {
+            alias: alias.alias,
+            branch: branch.name,
+            created_at: alias.created_at.into(),
+        }
+    }
+}
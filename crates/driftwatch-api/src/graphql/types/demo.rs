@@ -0,0 +1,20 @@
+use async_graphql::{InputObject, SimpleObject};
+
+use super::Project;
+
+#[derive(InputObject)]
+pub struct SeedDemoInput {
+    /// Days of history to generate, up to a year (default 90)
+    pub days: Option<i32>,
+    /// Seed for the generated noise; the same seed gives the same history
+    pub seed: Option<i64>,
+}
+
+/// What `seedDemoData` created
+#[derive(SimpleObject)]
+pub struct DemoData {
+    pub projects: Vec<Project>,
+    pub reports: i32,
+    /// Alerts raised while evaluating the generated history
+    pub alerts: i32,
+}
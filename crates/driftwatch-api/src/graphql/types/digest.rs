@@ -0,0 +1,26 @@
+use async_graphql::SimpleObject;
+
+use crate::entities::digest;
+
+/// A project's weekly summary, in markdown and HTML
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 300))]
+pub struct Digest {
+    pub period_start: chrono::DateTime<chrono::Utc>,
+    pub period_end: chrono::DateTime<chrono::Utc>,
+    pub markdown: String,
+    pub html: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<digest::Model> for Digest {
+    fn from(model: digest::Model) -> Self {
+        Self {
+            period_start: model.period_start.into(),
+            period_end: model.period_end.into(),
+            markdown: model.markdown,
+            html: model.html,
+            created_at: model.created_at.into(),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Result, SimpleObject, ID};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use uuid::Uuid;
+
+use crate::db::read_connection;
+use crate::entities::experiment_result::{self, Verdict as DbVerdict};
+use crate::entities::{self, experiment};
+
+#[derive(SimpleObject)]
+#[graphql(complex, cache_control(max_age = 3600))]
+pub struct Experiment {
+    pub id: ID,
+    pub name: Option<String>,
+    pub baseline_command: String,
+    pub candidate_command: String,
+    pub git_hash: Option<String>,
+    pub testbed: Option<String>,
+    /// Times each command was run
+    pub rounds: i32,
+    /// Significance level the verdicts were decided at
+    pub alpha: f64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<experiment::Model> for Experiment {
+    fn from(model: experiment::Model) -> Self {
+        Self {
+            id: ID(model.id.to_string()),
+            name: model.name,
+            baseline_command: model.baseline_command,
+            candidate_command: model.candidate_command,
+            git_hash: model.git_hash,
+            testbed: model.testbed,
+            rounds: model.rounds,
+            alpha: model.alpha,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Experiment {
+    /// Paired benchmarks, ordered by name and measure
+    async fn results(&self, ctx: &Context<'_>) -> Result<Vec<ExperimentResult>> {
+        let db = read_connection(ctx)?;
+        let experiment_id = Uuid::parse_str(&self.id.0)?;
+
+        let results = entities::ExperimentResult::find()
+            .filter(experiment_result::Column::ExperimentId.eq(experiment_id))
+            .order_by_asc(experiment_result::Column::Benchmark)
+            .order_by_asc(experiment_result::Column::Measure)
+            .all(db)
+            .await?;
+
+        Ok(results.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ExperimentResult {
+    pub benchmark: String,
+    pub measure: String,
+    pub baseline_mean: f64,
+    pub candidate_mean: f64,
+    pub percent_change: Option<f64>,
+    /// Two-sided p-value of Welch's t-test
+    pub p_value: Option<f64>,
+    /// `improved`, `regressed`, `unchanged` or `inconclusive`
+    pub verdict: String,
+}
+
+impl From<experiment_result::Model> for ExperimentResult {
+    fn from(model: experiment_result::Model) -> Self {
+        let verdict = match model.verdict {
+            DbVerdict::Improved => "improved",
+            DbVerdict::Regressed => "regressed",
+            DbVerdict::Unchanged => "unchanged",
+            DbVerdict::Inconclusive => "inconclusive",
+        };
+
+        Self {
+            benchmark: model.benchmark,
+            measure: model.measure,
+            baseline_mean: model.baseline_mean,
+            candidate_mean: model.candidate_mean,
+            percent_change: model.percent_change,
+            p_value: model.p_value,
+            verdict: verdict.to_string(),
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum VerdictInput {
+    Improved,
+    Regressed,
+    Unchanged,
+    Inconclusive,
+}
+
+impl VerdictInput {
+    pub fn to_db_value(&self) -> DbVerdict {
+        match self {
+            VerdictInput::Improved => DbVerdict::Improved,
+            VerdictInput::Regressed => DbVerdict::Regressed,
+            VerdictInput::Unchanged => DbVerdict::Unchanged,
+            VerdictInput::Inconclusive => DbVerdict::Inconclusive,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct ExperimentResultInput {
+    pub benchmark: String,
+    pub measure: String,
+    pub baseline_mean: f64,
+    pub candidate_mean: f64,
+    pub p_value: Option<f64>,
+    pub verdict: VerdictInput,
+}
+
+/// Results of `driftwatch ab`, which runs both commands, tests each paired
+/// benchmark and sends the verdicts here
+#[derive(InputObject)]
+pub struct RecordExperimentInput {
+    pub project_slug: String,
+    pub name: Option<String>,
+    pub baseline_command: String,
+    pub candidate_command: String,
+    pub git_hash: Option<String>,
+    pub testbed: Option<String>,
+    pub rounds: i32,
+    pub alpha: f64,
+    pub results: Vec<ExperimentResultInput>,
+}
@@ -0,0 +1,45 @@
+use async_graphql::{SimpleObject, ID};
+
+use crate::entities::flamegraph;
+
+/// Where to upload a file, from `createFlamegraphUploadUrl`
+#[derive(SimpleObject)]
+pub struct FlamegraphUploadUrl {
+    /// The project already has a file with the given SHA-256; skip the
+    /// upload and confirm `storagePath` straight away
+    pub stored: bool,
+    /// Path under the API's URL to PUT the file to in chunks, each with a
+    /// `Content-Range`; responses carry `Upload-Offset`, the bytes stored
+    /// so far, and `HEAD` returns it to resume an interrupted upload. Null
+    /// when `stored`.
+    pub signed_url: Option<String>,
+    /// Secret in `signedUrl`; whoever holds it may write the file
+    pub token: Option<String>,
+    /// Pass to `confirmFlamegraphUpload` once every byte is uploaded
+    pub storage_path: String,
+}
+
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 3600))]
+pub struct Flamegraph {
+    pub id: ID,
+    pub storage_path: String,
+    pub file_name: String,
+    pub file_size: i32,
+    /// Hex SHA-256 of the stored file
+    pub sha256: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<flamegraph::Model> for Flamegraph {
+    fn from(model: flamegraph::Model) -> Self {
+        Self {
+            id: ID(model.id.to_string()),
+            storage_path: model.storage_path,
+            file_name: model.file_name,
+            file_size: model.file_size,
+            sha256: model.sha256,
+            created_at: model.created_at.into(),
+        }
+    }
+}
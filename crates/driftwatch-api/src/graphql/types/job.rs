@@ -0,0 +1,60 @@
+use async_graphql::{Enum, SimpleObject, ID};
+
+use crate::entities::job::JobStatus as DbJobStatus;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum JobStatusInput {
+    Pending,
+    Running,
+    Completed,
+    Dead,
+}
+
+impl JobStatusInput {
+    pub fn to_db_value(&self) -> DbJobStatus {
+        match self {
+            JobStatusInput::Pending => DbJobStatus::Pending,
+            JobStatusInput::Running => DbJobStatus::Running,
+            JobStatusInput::Completed => DbJobStatus::Completed,
+            JobStatusInput::Dead => DbJobStatus::Dead,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Job {
+    pub id: ID,
+    pub kind: String,
+    pub payload: async_graphql::Json<serde_json::Value>,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: chrono::DateTime<chrono::Utc>,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<crate::entities::job::Model> for Job {
+    fn from(model: crate::entities::job::Model) -> Self {
+        let status = match model.status {
+            DbJobStatus::Pending => "pending",
+            DbJobStatus::Running => "running",
+            DbJobStatus::Completed => "completed",
+            DbJobStatus::Dead => "dead",
+        };
+
+        Self {
+            id: ID(model.id.to_string()),
+            kind: model.kind,
+            payload: async_graphql::Json(model.payload),
+            status: status.to_string(),
+            attempts: model.attempts,
+            max_attempts: model.max_attempts,
+            run_at: model.run_at.into(),
+            last_error: model.last_error,
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use async_graphql::dataloader::DataLoader;
-use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use async_graphql::{ComplexObject, Context, InputObject, Result, SimpleObject, ID};
 use uuid::Uuid;
 
 use crate::loaders::{BenchmarkLoader, MeasureLoader};
@@ -11,6 +11,11 @@ pub struct Metric {
     pub value: f64,
     pub lower: Option<f64>,
     pub upper: Option<f64>,
+    /// Percent change the benchmark harness reported against its own
+    /// previous run, for cross-checking alerts
+    pub reported_change: Option<f64>,
+    /// Outlying samples the harness flagged
+    pub outliers: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     #[graphql(skip)]
     pub benchmark_id: Uuid,
@@ -25,6 +30,8 @@ impl From<crate::entities::metric::Model> for Metric {
             value: model.value,
             lower: model.lower,
             upper: model.upper,
+            reported_change: model.reported_change,
+            outliers: model.outliers,
             created_at: model.created_at.into(),
             benchmark_id: model.benchmark_id,
             measure_id: model.measure_id,
@@ -50,3 +57,29 @@ impl Metric {
             .ok_or_else(|| "Measure not found".into())
     }
 }
+
+#[derive(InputObject)]
+pub struct MetricInput {
+    pub benchmark: String,
+    pub measure: String,
+    pub value: f64,
+    pub lower_value: Option<f64>,
+    pub upper_value: Option<f64>,
+    /// Percent change the harness reported against its previous run
+    pub reported_change: Option<f64>,
+    pub outliers: Option<i32>,
+}
+
+impl From<MetricInput> for crate::ingest::NewMetric {
+    fn from(input: MetricInput) -> Self {
+        Self {
+            benchmark: input.benchmark,
+            measure: input.measure,
+            value: input.value,
+            lower_value: input.lower_value,
+            upper_value: input.upper_value,
+            reported_change: input.reported_change,
+            outliers: input.outliers,
+        }
+    }
+}
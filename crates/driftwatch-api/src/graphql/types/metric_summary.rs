@@ -0,0 +1,76 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use uuid::Uuid;
+
+use crate::loaders::{BenchmarkLoader, BranchLoader, MeasureLoader, TestbedLoader};
+
+#[derive(SimpleObject)]
+#[graphql(complex, cache_control(max_age = 60))]
+pub struct MetricSummary {
+    pub id: ID,
+    pub latest_value: f64,
+    pub latest_at: chrono::DateTime<chrono::Utc>,
+    pub latest_report_id: ID,
+    pub delta_7d: Option<f64>,
+    pub delta_30d: Option<f64>,
+    #[graphql(skip)]
+    pub benchmark_id: Uuid,
+    #[graphql(skip)]
+    pub measure_id: Uuid,
+    #[graphql(skip)]
+    pub branch_id: Uuid,
+    #[graphql(skip)]
+    pub testbed_id: Uuid,
+}
+
+impl From<crate::entities::metric_summary::Model> for MetricSummary {
+    fn from(model: crate::entities::metric_summary::Model) -> Self {
+        Self {
+            id: ID(model.id.to_string()),
+            latest_value: model.latest_value,
+            latest_at: model.latest_at.into(),
+            latest_report_id: ID(model.latest_report_id.to_string()),
+            delta_7d: model.delta_7d,
+            delta_30d: model.delta_30d,
+            benchmark_id: model.benchmark_id,
+            measure_id: model.measure_id,
+            branch_id: model.branch_id,
+            testbed_id: model.testbed_id,
+        }
+    }
+}
+
+#[ComplexObject]
+impl MetricSummary {
+    async fn benchmark(&self, ctx: &Context<'_>) -> Result<super::Benchmark> {
+        let loader = ctx.data::<DataLoader<BenchmarkLoader>>()?;
+        loader
+            .load_one(self.benchmark_id)
+            .await?
+            .ok_or_else(|| "Benchmark not found".into())
+    }
+
+    async fn measure(&self, ctx: &Context<'_>) -> Result<super::Measure> {
+        let loader = ctx.data::<DataLoader<MeasureLoader>>()?;
+        loader
+            .load_one(self.measure_id)
+            .await?
+            .ok_or_else(|| "Measure not found".into())
+    }
+
+    async fn branch(&self, ctx: &Context<'_>) -> Result<super::Branch> {
+        let loader = ctx.data::<DataLoader<BranchLoader>>()?;
+        loader
+            .load_one(self.branch_id)
+            .await?
+            .ok_or_else(|| "Branch not found".into())
+    }
+
+    async fn testbed(&self, ctx: &Context<'_>) -> Result<super::Testbed> {
+        let loader = ctx.data::<DataLoader<TestbedLoader>>()?;
+        loader
+            .load_one(self.testbed_id)
+            .await?
+            .ok_or_else(|| "Testbed not found".into())
+    }
+}
@@ -1,21 +1,73 @@
 mod alert;
+mod annotation;
+mod api_version;
 mod auth;
 mod benchmark;
+mod benchmark_match;
+mod benchmark_noise;
+mod benchmark_owner;
 mod branch;
+mod demo;
+mod digest;
+mod experiment;
+mod flamegraph;
+mod job;
 mod measure;
 mod metric;
+mod metric_summary;
+mod notification_channel;
 mod project;
+mod project_group;
+mod project_overview;
+mod project_template;
+mod public_project;
+mod pull_request_check;
+mod release_point;
+mod remote_write_rule;
 mod report;
+mod report_context;
+mod report_filter;
+mod report_output;
+mod scaling_curve;
+mod share;
+mod stale_alert;
 mod testbed;
 mod threshold;
+mod threshold_evaluation;
 
 pub use alert::*;
+pub use annotation::*;
+pub use api_version::*;
 pub use auth::*;
 pub use benchmark::*;
+pub use benchmark_match::*;
+pub use benchmark_noise::*;
+pub use benchmark_owner::*;
 pub use branch::*;
+pub use demo::*;
+pub use digest::*;
+pub use experiment::*;
+pub use flamegraph::*;
+pub use job::*;
 pub use measure::*;
 pub use metric::*;
+pub use metric_summary::*;
+pub use notification_channel::*;
 pub use project::*;
+pub use project_group::*;
+pub use project_overview::*;
+pub use project_template::*;
+pub use public_project::*;
+pub use pull_request_check::*;
+pub use release_point::*;
+pub use remote_write_rule::*;
 pub use report::*;
+pub use report_context::*;
+pub use report_filter::*;
+pub use report_output::*;
+pub use scaling_curve::*;
+pub use share::*;
+pub use stale_alert::*;
 pub use testbed::*;
 pub use threshold::*;
+pub use threshold_evaluation::*;
@@ -0,0 +1,63 @@
+use async_graphql::{Enum, SimpleObject, ID};
+
+use crate::entities::notification_channel::{self, ChannelKind};
+use crate::notifications;
+
+#[derive(SimpleObject)]
+pub struct NotificationChannel {
+    pub id: ID,
+    /// `webhook` or `slack`
+    pub kind: String,
+    /// Host the channel posts to; the full URL is never returned since it
+    /// usually embeds a credential
+    pub target: String,
+    /// Events posted to the channel: `digest`, `report.created`,
+    /// `alert.opened` or `alert.resolved`
+    pub events: Vec<String>,
+    /// Request body template replacing the default payload
+    pub template: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<notification_channel::Model> for NotificationChannel {
+    fn from(model: notification_channel::Model) -> Self {
+        let kind = match model.kind {
+            ChannelKind::Webhook => "webhook",
+            ChannelKind::Slack => "slack",
+        };
+
+        Self {
+            id: ID(model.id.to_string()),
+            kind: kind.to_string(),
+            target: notifications::channel_target(&model),
+            events: notifications::channel_events(&model),
+            template: model.template,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// The outcome of a test delivery
+#[derive(SimpleObject)]
+pub struct NotificationChannelTest {
+    pub delivered: bool,
+    /// Why the channel didn't accept the message
+    pub error: Option<String>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum NotificationChannelKindInput {
+    /// JSON POSTed to any URL
+    Webhook,
+    /// A Slack incoming webhook
+    Slack,
+}
+
+impl NotificationChannelKindInput {
+    pub fn to_db_value(&self) -> ChannelKind {
+        match self {
+            NotificationChannelKindInput::Webhook => ChannelKind::Webhook,
+            NotificationChannelKindInput::Slack => ChannelKind::Slack,
+        }
+    }
+}
@@ -1,13 +1,20 @@
-use async_graphql::{ComplexObject, Context, InputObject, Result, SimpleObject, ID};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Result, SimpleObject, ID};
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::cache::AppCache;
+use crate::db::read_connection;
+use crate::entities::pull_request_check::CheckState;
 use crate::entities::{
-    self, alert, benchmark, branch, measure, project, report, testbed, threshold,
+    self, alert, annotation, benchmark, benchmark_noise, branch, branch_alias, digest, experiment,
+    measure, metric_summary, notification_channel, project, pull_request_check, remote_write_rule,
+    report, report_filter, stale_alert, testbed, testbed_alias, threshold,
 };
+use crate::report_filters::{self, ReportCriteria};
+use crate::{context, digest as digests, ingest, overview, owners, releases, scaling};
 
-#[derive(SimpleObject, Serialize, Deserialize)]
+#[derive(SimpleObject, Clone, Serialize, Deserialize)]
 #[graphql(complex, cache_control(max_age = 300))]
 pub struct Project {
     pub id: ID,
@@ -15,26 +22,62 @@ pub struct Project {
     pub name: String,
     pub description: Option<String>,
     pub public: bool,
+    /// Branch reports submitted for `HEAD` go to
+    pub default_branch: String,
     pub github_repo: Option<String>,
     pub github_pr_comments: bool,
     pub github_status_checks: bool,
     pub has_github_token: bool,
+    pub expected_cadence_hours: Option<i32>,
+    /// Branches checked for stale data; the default branch unless set
+    pub stale_branches: Vec<String>,
+    pub github_issue_after_reports: Option<i32>,
+    pub alert_after_reports: Option<i32>,
+    pub noise_cv_limit: Option<f64>,
+    pub noise_action: String,
+    /// Path globs whose changes require a benchmark report on the pull request
+    pub benchmark_required_paths: Vec<String>,
+    /// Whether secrets are scrubbed from command output and report metadata
+    /// before they're stored
+    pub redact_secrets: bool,
+    /// Whether an Atom feed of alerts was created; its URL is only shown
+    /// when it's created
+    pub has_alert_feed: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
 impl From<project::Model> for Project {
     fn from(model: project::Model) -> Self {
+        let noise_action = match model.noise_action {
+            project::NoiseAction::Flag => "flag",
+            project::NoiseAction::WidenThresholds => "widen_thresholds",
+            project::NoiseAction::RequireMoreSamples => "require_more_samples",
+        };
+
+        let benchmark_required_paths = model.required_path_list();
+        let stale_branches = model.stale_branch_list();
+
         Self {
             id: ID(model.id.to_string()),
             slug: model.slug,
             name: model.name,
             description: model.description,
             public: model.public,
+            default_branch: model.default_branch,
             github_repo: model.github_repo,
             github_pr_comments: model.github_pr_comments,
             github_status_checks: model.github_status_checks,
             has_github_token: model.github_token.is_some(),
+            expected_cadence_hours: model.expected_cadence_hours,
+            stale_branches,
+            github_issue_after_reports: model.github_issue_after_reports,
+            alert_after_reports: model.alert_after_reports,
+            noise_cv_limit: model.noise_cv_limit,
+            noise_action: noise_action.to_string(),
+            benchmark_required_paths,
+            redact_secrets: model.redact_secrets,
+            has_alert_feed: model.alert_feed_token_hash.is_some(),
             created_at: model.created_at.into(),
             updated_at: model.updated_at.into(),
         }
@@ -56,6 +99,24 @@ impl Project {
         Ok(branches.into_iter().map(Into::into).collect())
     }
 
+    /// Other names reports may use for the project's branches, by alias
+    async fn branch_aliases(&self, ctx: &Context<'_>) -> Result<Vec<super::BranchAlias>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let aliases = entities::BranchAlias::find()
+            .find_also_related(entities::Branch)
+            .filter(branch_alias::Column::ProjectId.eq(project_id))
+            .order_by_asc(branch_alias::Column::Alias)
+            .all(db)
+            .await?;
+
+        Ok(aliases
+            .into_iter()
+            .filter_map(|(alias, branch)| Some(super::BranchAlias::new(alias, branch?)))
+            .collect())
+    }
+
     async fn testbeds(&self, ctx: &Context<'_>) -> Result<Vec<super::Testbed>> {
         let db = ctx.data::<DatabaseConnection>()?;
         let project_id = Uuid::parse_str(&self.id.0)?;
@@ -69,6 +130,24 @@ impl Project {
         Ok(testbeds.into_iter().map(Into::into).collect())
     }
 
+    /// Other names reports may use for the project's testbeds, by alias
+    async fn testbed_aliases(&self, ctx: &Context<'_>) -> Result<Vec<super::TestbedAlias>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let aliases = entities::TestbedAlias::find()
+            .find_also_related(entities::Testbed)
+            .filter(testbed_alias::Column::ProjectId.eq(project_id))
+            .order_by_asc(testbed_alias::Column::Alias)
+            .all(db)
+            .await?;
+
+        Ok(aliases
+            .into_iter()
+            .filter_map(|(alias, testbed)| Some(super::TestbedAlias::new(alias, testbed?)))
+            .collect())
+    }
+
     async fn measures(&self, ctx: &Context<'_>) -> Result<Vec<super::Measure>> {
         let db = ctx.data::<DatabaseConnection>()?;
         let project_id = Uuid::parse_str(&self.id.0)?;
@@ -95,22 +174,91 @@ impl Project {
         Ok(benchmarks.into_iter().map(Into::into).collect())
     }
 
-    async fn reports(&self, ctx: &Context<'_>, limit: Option<i32>) -> Result<Vec<super::Report>> {
+    /// Newest first. `context` keeps reports that recorded every given
+    /// key/value and `tags` those carrying every tag; `filter` names a
+    /// saved filter whose tags and context are added to them. `kind` keeps
+    /// reports of one kind, e.g. only nightlies.
+    async fn reports(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+        context: Option<Vec<super::ContextEntryInput>>,
+        tags: Option<Vec<String>>,
+        filter: Option<String>,
+        kind: Option<super::ReportKindInput>,
+    ) -> Result<Vec<super::Report>> {
         let db = ctx.data::<DatabaseConnection>()?;
         let project_id = Uuid::parse_str(&self.id.0)?;
 
-        let mut query = entities::Report::find()
-            .filter(report::Column::ProjectId.eq(project_id))
-            .order_by_desc(report::Column::CreatedAt);
+        let mut criteria = ReportCriteria {
+            tags: tags.unwrap_or_default(),
+            context: context
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| (e.key, e.value))
+                .collect(),
+        };
+        if let Some(name) = filter {
+            let saved = report_filters::find(db, project_id, &name)
+                .await?
+                .ok_or_else(|| format!("No report filter named '{}'", name.trim()))?;
+            criteria.merge(&saved);
+        }
+
+        if !criteria.is_empty() || kind.is_some() {
+            let mut query = criteria.apply(
+                entities::Report::find()
+                    .filter(report::Column::ProjectId.eq(project_id))
+                    .filter(report::Column::Finalized.eq(true)),
+            );
+            if let Some(kind) = kind {
+                query = query.filter(report::Column::Kind.eq(kind.to_db_value()));
+            }
+            if let Some(limit) = limit {
+                query = query.limit(limit.max(0) as u64);
+            }
 
-        if let Some(limit) = limit {
-            query = query.limit(limit as u64);
+            let reports = query
+                .order_by_desc(report::Column::CreatedAt)
+                .all(read_connection(ctx)?)
+                .await?;
+            return Ok(reports.into_iter().map(Into::into).collect());
         }
 
-        let reports = query.all(db).await?;
+        let reports = match limit {
+            Some(limit) => {
+                let cache = ctx.data::<AppCache>()?;
+                cache
+                    .latest_reports(db, project_id, limit.max(0) as usize)
+                    .await?
+            }
+            None => {
+                entities::Report::find()
+                    .filter(report::Column::ProjectId.eq(project_id))
+                    .filter(report::Column::Finalized.eq(true))
+                    .order_by_desc(report::Column::CreatedAt)
+                    .all(read_connection(ctx)?)
+                    .await?
+            }
+        };
+
         Ok(reports.into_iter().map(Into::into).collect())
     }
 
+    /// Saved report filters, by name
+    async fn report_filters(&self, ctx: &Context<'_>) -> Result<Vec<super::ReportFilter>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let filters = entities::ReportFilter::find()
+            .filter(report_filter::Column::ProjectId.eq(project_id))
+            .order_by_asc(report_filter::Column::Name)
+            .all(db)
+            .await?;
+
+        Ok(filters.into_iter().map(Into::into).collect())
+    }
+
     async fn thresholds(&self, ctx: &Context<'_>) -> Result<Vec<super::Threshold>> {
         let db = ctx.data::<DatabaseConnection>()?;
         let project_id = Uuid::parse_str(&self.id.0)?;
@@ -128,7 +276,7 @@ impl Project {
         ctx: &Context<'_>,
         status: Option<super::AlertStatusInput>,
     ) -> Result<Vec<super::Alert>> {
-        let db = ctx.data::<DatabaseConnection>()?;
+        let db = read_connection(ctx)?;
         let project_id = Uuid::parse_str(&self.id.0)?;
 
         // Get all thresholds for this project
@@ -143,9 +291,11 @@ impl Project {
         let mut query =
             entities::Alert::find().filter(alert::Column::ThresholdId.is_in(threshold_ids));
 
-        if let Some(status) = status {
-            query = query.filter(alert::Column::Status.eq(status.to_db_value()));
-        }
+        // Unconfirmed regressions are only listed when asked for explicitly
+        query = match status {
+            Some(status) => query.filter(alert::Column::Status.eq(status.to_db_value())),
+            None => query.filter(alert::Column::Status.ne(alert::AlertStatus::Unconfirmed)),
+        };
 
         let alerts = query
             .order_by_desc(alert::Column::CreatedAt)
@@ -153,6 +303,342 @@ impl Project {
             .await?;
         Ok(alerts.into_iter().map(Into::into).collect())
     }
+
+    /// Notes on the project's history, newest first, optionally limited to
+    /// a time range. Includes the annotations attached to reports.
+    async fn annotations(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+        to: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<super::Annotation>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let mut query =
+            entities::Annotation::find().filter(annotation::Column::ProjectId.eq(project_id));
+        if let Some(from) = from {
+            query = query.filter(annotation::Column::OccurredAt.gte(from));
+        }
+        if let Some(to) = to {
+            query = query.filter(annotation::Column::OccurredAt.lte(to));
+        }
+
+        let annotations = query
+            .order_by_desc(annotation::Column::OccurredAt)
+            .all(db)
+            .await?;
+        Ok(annotations.into_iter().map(Into::into).collect())
+    }
+
+    /// Recorded `driftwatch ab` experiments, newest first
+    async fn experiments(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 20)] limit: i32,
+    ) -> Result<Vec<super::Experiment>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let experiments = entities::Experiment::find()
+            .filter(experiment::Column::ProjectId.eq(project_id))
+            .order_by_desc(experiment::Column::CreatedAt)
+            .limit(limit.max(0) as u64)
+            .all(db)
+            .await?;
+        Ok(experiments.into_iter().map(Into::into).collect())
+    }
+
+    /// Noise scores for every benchmark/measure/testbed, noisiest first,
+    /// optionally narrowed to a testbed by name or to benchmarks above the
+    /// project's noise limit
+    async fn noise_scores(
+        &self,
+        ctx: &Context<'_>,
+        testbed: Option<String>,
+        #[graphql(default = false)] noisy_only: bool,
+    ) -> Result<Vec<super::BenchmarkNoise>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let mut query = entities::BenchmarkNoise::find()
+            .filter(benchmark_noise::Column::ProjectId.eq(project_id));
+
+        if let Some(testbed) = testbed {
+            query = query
+                .inner_join(entities::Testbed)
+                .filter(testbed::Column::Name.eq(testbed));
+        }
+        if noisy_only {
+            let Some(limit) = self.noise_cv_limit else {
+                return Ok(Vec::new());
+            };
+            query = query.filter(benchmark_noise::Column::CoefficientOfVariation.gt(limit));
+        }
+
+        let scores = query
+            .order_by_desc(benchmark_noise::Column::CoefficientOfVariation)
+            .all(db)
+            .await?;
+        Ok(scores
+            .into_iter()
+            .map(|s| super::BenchmarkNoise::new(s, self.noise_cv_limit))
+            .collect())
+    }
+
+    /// Values of `measure` per tagged release rather than per commit, one
+    /// series per benchmark, optionally narrowed to a benchmark and testbed
+    async fn release_series(
+        &self,
+        ctx: &Context<'_>,
+        measure: String,
+        benchmark: Option<String>,
+        testbed: Option<String>,
+    ) -> Result<Vec<super::ReleasePoint>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let points = releases::release_series(
+            db,
+            project_id,
+            &measure,
+            benchmark.as_deref(),
+            testbed.as_deref(),
+        )
+        .await?;
+        Ok(points.into_iter().map(Into::into).collect())
+    }
+
+    /// How a parameterized benchmark such as `sort/1000`, `sort/100000`
+    /// scales with its parameter, one curve per recent report on `branch`,
+    /// oldest first. `benchmark` is the name without the parameter.
+    async fn scaling_curves(
+        &self,
+        ctx: &Context<'_>,
+        benchmark: String,
+        measure: String,
+        branch: String,
+        testbed: Option<String>,
+        #[graphql(default = 10)] limit: i32,
+    ) -> Result<Vec<super::ScalingCurve>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let curves = scaling::scaling_curves(
+            db,
+            project_id,
+            &benchmark,
+            &measure,
+            &branch,
+            testbed.as_deref(),
+            limit.max(0) as u64,
+        )
+        .await?;
+        Ok(curves.into_iter().map(Into::into).collect())
+    }
+
+    /// The latest `limit` results of a benchmark split by each report's value
+    /// of the context key `key`, e.g. `allocator`, to compare configurations
+    /// over time. Points are grouped by context value, oldest first.
+    #[allow(clippy::too_many_arguments)]
+    async fn context_series(
+        &self,
+        ctx: &Context<'_>,
+        benchmark: String,
+        measure: String,
+        key: String,
+        branch: Option<String>,
+        testbed: Option<String>,
+        #[graphql(default = 100)] limit: i32,
+    ) -> Result<Vec<super::ContextPoint>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let points = context::context_series(
+            db,
+            project_id,
+            &benchmark,
+            &measure,
+            &key,
+            branch.as_deref(),
+            testbed.as_deref(),
+            limit.max(0) as u64,
+        )
+        .await?;
+        Ok(points.into_iter().map(Into::into).collect())
+    }
+
+    /// Latest value and 7/30-day deltas for every benchmark/measure,
+    /// optionally narrowed to a branch and testbed by name
+    async fn summaries(
+        &self,
+        ctx: &Context<'_>,
+        branch: Option<String>,
+        testbed: Option<String>,
+    ) -> Result<Vec<super::MetricSummary>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let mut query = entities::MetricSummary::find()
+            .filter(metric_summary::Column::ProjectId.eq(project_id));
+
+        if let Some(branch) = branch {
+            let Some(branch) = ingest::find_branch(db, project_id, &branch).await? else {
+                return Ok(Vec::new());
+            };
+            query = query.filter(metric_summary::Column::BranchId.eq(branch.id));
+        }
+        if let Some(testbed) = testbed {
+            let Some(testbed) = ingest::find_testbed(db, project_id, &testbed).await? else {
+                return Ok(Vec::new());
+            };
+            query = query.filter(metric_summary::Column::TestbedId.eq(testbed.id));
+        }
+
+        let summaries = query
+            .order_by_asc(metric_summary::Column::BenchmarkId)
+            .all(db)
+            .await?;
+        Ok(summaries.into_iter().map(Into::into).collect())
+    }
+
+    /// Per-measure trends, open alerts and the latest report of each
+    /// branch/testbed in one query. Trends can be narrowed to a branch and
+    /// testbed by name.
+    async fn overview(
+        &self,
+        ctx: &Context<'_>,
+        branch: Option<String>,
+        testbed: Option<String>,
+    ) -> Result<super::ProjectOverview> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let measures =
+            overview::measure_trends(db, project_id, branch.as_deref(), testbed.as_deref()).await?;
+        let open_alerts = overview::open_alerts(db, project_id).await?;
+        let latest_reports = overview::latest_reports(db, project_id).await?;
+        Ok(super::ProjectOverview {
+            measures: measures.into_iter().map(Into::into).collect(),
+            open_alerts: open_alerts as i32,
+            latest_reports: latest_reports.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Weekly digests, newest first
+    async fn digests(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] limit: u64,
+    ) -> Result<Vec<super::Digest>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let digests = entities::Digest::find()
+            .filter(digest::Column::ProjectId.eq(project_id))
+            .order_by_desc(digest::Column::PeriodStart)
+            .limit(limit)
+            .all(db)
+            .await?;
+        Ok(digests.into_iter().map(Into::into).collect())
+    }
+
+    /// The digest of the week so far, compiled now and not stored or sent
+    async fn digest_preview(&self, ctx: &Context<'_>) -> Result<super::Digest> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+        let project = entities::Project::find_by_id(project_id)
+            .one(db)
+            .await?
+            .ok_or("Project not found")?;
+
+        let now = chrono::Utc::now().fixed_offset();
+        let (start, end) = digests::week_containing(now);
+        let contents = digests::compile(db, &project, start, end).await?;
+        Ok(super::Digest {
+            period_start: start.into(),
+            period_end: end.into(),
+            markdown: digests::render_markdown(&contents),
+            html: digests::render_html(&contents),
+            created_at: now.into(),
+        })
+    }
+
+    /// Where digests are sent
+    async fn notification_channels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<super::NotificationChannel>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let channels = entities::NotificationChannel::find()
+            .filter(notification_channel::Column::ProjectId.eq(project_id))
+            .order_by_asc(notification_channel::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(channels.into_iter().map(Into::into).collect())
+    }
+
+    /// Rules mapping Prometheus remote_write series onto benchmarks
+    async fn remote_write_rules(&self, ctx: &Context<'_>) -> Result<Vec<super::RemoteWriteRule>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let rules = entities::RemoteWriteRule::find()
+            .filter(remote_write_rule::Column::ProjectId.eq(project_id))
+            .order_by_asc(remote_write_rule::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(rules.into_iter().map(Into::into).collect())
+    }
+
+    /// Branch/testbed pairs that stopped reporting within the expected cadence
+    async fn stale_alerts(
+        &self,
+        ctx: &Context<'_>,
+        include_resolved: Option<bool>,
+    ) -> Result<Vec<super::StaleAlert>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let mut query =
+            entities::StaleAlert::find().filter(stale_alert::Column::ProjectId.eq(project_id));
+
+        if !include_resolved.unwrap_or(false) {
+            query =
+                query.filter(stale_alert::Column::Status.eq(stale_alert::StaleAlertStatus::Active));
+        }
+
+        let alerts = query
+            .order_by_desc(stale_alert::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(alerts.into_iter().map(Into::into).collect())
+    }
+
+    /// Benchmark status of open pull requests, failing ones first
+    async fn pull_request_checks(&self, ctx: &Context<'_>) -> Result<Vec<super::PullRequestCheck>> {
+        let db = read_connection(ctx)?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let mut checks = entities::PullRequestCheck::find()
+            .filter(pull_request_check::Column::ProjectId.eq(project_id))
+            .order_by_desc(pull_request_check::Column::PrNumber)
+            .all(db)
+            .await?;
+        checks.sort_by_key(|c| c.state == CheckState::Success);
+        Ok(checks.into_iter().map(Into::into).collect())
+    }
+
+    /// Owner rules in precedence order; the last matching rule wins
+    async fn benchmark_owners(&self, ctx: &Context<'_>) -> Result<Vec<super::BenchmarkOwner>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+
+        let rules = owners::project_rules(db, project_id).await?;
+        Ok(rules.into_iter().map(Into::into).collect())
+    }
 }
 
 #[derive(InputObject)]
@@ -160,6 +646,10 @@ pub struct CreateProjectInput {
     pub slug: String,
     pub name: String,
     pub description: Option<String>,
+    pub public: Option<bool>,
+    /// Template whose measures and default thresholds seed the project;
+    /// see `projectTemplates`
+    pub template: Option<String>,
 }
 
 #[derive(InputObject)]
@@ -167,12 +657,56 @@ pub struct UpdateProjectInput {
     pub name: Option<String>,
     pub description: Option<String>,
     pub public: Option<bool>,
+    /// Branch reports submitted for `HEAD` go to
+    pub default_branch: Option<String>,
+    /// Hours a branch/testbed may go without a report before a stale alert
+    /// is raised; 0 disables the check
+    pub expected_cadence_hours: Option<i32>,
+    /// Branches checked for stale data; empty checks the default branch
+    pub stale_branches: Option<Vec<String>>,
+    /// Consecutive regressing reports required before an alert is raised;
+    /// 0 or 1 alerts on the first
+    pub alert_after_reports: Option<i32>,
+    /// Coefficient of variation, in percent, above which a benchmark counts
+    /// as noisy; 0 disables noise handling
+    pub noise_cv_limit: Option<f64>,
+    pub noise_action: Option<NoiseActionInput>,
+    /// Scrub tokens and keys from command output and report metadata before
+    /// storing them; on by default
+    pub redact_secrets: Option<bool>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum NoiseActionInput {
+    /// Only mark noisy benchmarks
+    Flag,
+    /// Widen their boundaries to cover normal run-to-run variation
+    WidenThresholds,
+    /// Require a larger baseline sample before alerting on them
+    RequireMoreSamples,
+}
+
+impl NoiseActionInput {
+    pub fn to_db_value(&self) -> project::NoiseAction {
+        match self {
+            NoiseActionInput::Flag => project::NoiseAction::Flag,
+            NoiseActionInput::WidenThresholds => project::NoiseAction::WidenThresholds,
+            NoiseActionInput::RequireMoreSamples => project::NoiseAction::RequireMoreSamples,
+        }
+    }
 }
 
 #[derive(InputObject)]
 pub struct GitHubSettingsInput {
     pub github_repo: Option<String>,
+    /// Encrypted before it's stored and never returned; kept out of logs
+    #[graphql(secret)]
     pub github_token: Option<String>,
     pub github_pr_comments: Option<bool>,
     pub github_status_checks: Option<bool>,
+    /// Consecutive alerting reports before an issue is opened; 0 disables
+    pub github_issue_after_reports: Option<i32>,
+    /// Path globs whose changes require a benchmark report before the
+    /// `driftwatch/benchmarks` status passes; empty disables the check
+    pub benchmark_required_paths: Option<Vec<String>>,
 }
@@ -0,0 +1,131 @@
+use async_graphql::{ComplexObject, Context, InputObject, Result, SimpleObject, ID};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use uuid::Uuid;
+
+use crate::db::read_connection;
+use crate::entities::{
+    self, alert, project, project_group, project_group_member, report, threshold,
+};
+use crate::report_filters::ReportCriteria;
+
+/// Reports returned by `ProjectGroup.reports` when no limit is given
+const DEFAULT_GROUP_REPORTS: u64 = 50;
+
+#[derive(SimpleObject)]
+#[graphql(complex, cache_control(max_age = 60))]
+pub struct ProjectGroup {
+    pub id: ID,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<project_group::Model> for ProjectGroup {
+    fn from(model: project_group::Model) -> Self {
+        Self {
+            id: ID(model.id.to_string()),
+            slug: model.slug,
+            name: model.name,
+            description: model.description,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+impl ProjectGroup {
+    async fn project_ids<C: ConnectionTrait>(&self, db: &C) -> Result<Vec<Uuid>> {
+        let group_id = Uuid::parse_str(&self.id.0)?;
+        Ok(entities::ProjectGroupMember::find()
+            .select_only()
+            .column(project_group_member::Column::ProjectId)
+            .filter(project_group_member::Column::GroupId.eq(group_id))
+            .into_tuple()
+            .all(db)
+            .await?)
+    }
+}
+
+#[ComplexObject]
+impl ProjectGroup {
+    async fn projects(&self, ctx: &Context<'_>) -> Result<Vec<super::Project>> {
+        let db = read_connection(ctx)?;
+        let project_ids = self.project_ids(db).await?;
+
+        let projects = entities::Project::find()
+            .filter(project::Column::Id.is_in(project_ids))
+            .order_by_asc(project::Column::Name)
+            .all(db)
+            .await?;
+        Ok(projects.into_iter().map(Into::into).collect())
+    }
+
+    /// Alerts of every project in the group, newest first. Each alert's
+    /// `project` tells them apart.
+    async fn alerts(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<super::AlertStatusInput>,
+        limit: Option<i32>,
+    ) -> Result<Vec<super::Alert>> {
+        let db = read_connection(ctx)?;
+        let project_ids = self.project_ids(db).await?;
+
+        let mut query = entities::Alert::find()
+            .inner_join(entities::Threshold)
+            .filter(threshold::Column::ProjectId.is_in(project_ids));
+
+        // Unconfirmed regressions are only listed when asked for explicitly
+        query = match status {
+            Some(status) => query.filter(alert::Column::Status.eq(status.to_db_value())),
+            None => query.filter(alert::Column::Status.ne(alert::AlertStatus::Unconfirmed)),
+        };
+        if let Some(limit) = limit {
+            query = query.limit(limit.max(0) as u64);
+        }
+
+        let alerts = query
+            .order_by_desc(alert::Column::CreatedAt)
+            .all(db)
+            .await?;
+        Ok(alerts.into_iter().map(Into::into).collect())
+    }
+
+    /// Combined report history of the group's projects, newest first,
+    /// optionally only reports carrying every tag in `tags`
+    async fn reports(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+        tags: Option<Vec<String>>,
+    ) -> Result<Vec<super::Report>> {
+        let db = read_connection(ctx)?;
+        let project_ids = self.project_ids(db).await?;
+
+        let criteria = ReportCriteria {
+            tags: tags.unwrap_or_default(),
+            context: Vec::new(),
+        };
+        let reports = criteria
+            .apply(
+                entities::Report::find()
+                    .filter(report::Column::ProjectId.is_in(project_ids))
+                    .filter(report::Column::Finalized.eq(true)),
+            )
+            .order_by_desc(report::Column::CreatedAt)
+            .limit(limit.map_or(DEFAULT_GROUP_REPORTS, |l| l.max(0) as u64))
+            .all(db)
+            .await?;
+        Ok(reports.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateProjectGroupInput {
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Projects to add right away
+    #[graphql(default)]
+    pub project_slugs: Vec<String>,
+}
@@ -0,0 +1,63 @@
+use async_graphql::{SimpleObject, ID};
+
+use crate::overview;
+
+/// A project at a glance, for `driftwatch project status`
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 60))]
+pub struct ProjectOverview {
+    pub measures: Vec<MeasureTrend>,
+    /// Alerts raised and not yet resolved or dismissed
+    pub open_alerts: i32,
+    pub latest_reports: Vec<LatestReport>,
+}
+
+/// How one measure moved across the project's benchmarks
+#[derive(SimpleObject)]
+pub struct MeasureTrend {
+    pub measure: String,
+    pub benchmarks: i32,
+    /// Mean percent change over 7 and 30 days, across the benchmarks with
+    /// results that old
+    pub mean_delta_7d: Option<f64>,
+    pub mean_delta_30d: Option<f64>,
+    /// Benchmarks more than 5% worse over 30 days
+    pub regressed_30d: i32,
+    /// Benchmarks more than 5% better over 30 days
+    pub improved_30d: i32,
+}
+
+impl From<overview::MeasureTrend> for MeasureTrend {
+    fn from(trend: overview::MeasureTrend) -> Self {
+        Self {
+            measure: trend.measure,
+            benchmarks: trend.benchmarks as i32,
+            mean_delta_7d: trend.mean_delta_7d,
+            mean_delta_30d: trend.mean_delta_30d,
+            regressed_30d: trend.regressed_30d as i32,
+            improved_30d: trend.improved_30d as i32,
+        }
+    }
+}
+
+/// The newest report of one branch and testbed
+#[derive(SimpleObject)]
+pub struct LatestReport {
+    pub branch: String,
+    pub testbed: String,
+    pub report_id: ID,
+    pub git_hash: Option<String>,
+    pub reported_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<overview::LatestReport> for LatestReport {
+    fn from(report: overview::LatestReport) -> Self {
+        Self {
+            branch: report.branch,
+            testbed: report.testbed,
+            report_id: ID(report.report_id.to_string()),
+            git_hash: report.git_hash,
+            reported_at: report.reported_at.into(),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use async_graphql::SimpleObject;
+
+use crate::templates;
+
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 3600))]
+pub struct ProjectTemplate {
+    pub name: String,
+    pub description: String,
+    pub measures: Vec<MeasureTemplate>,
+}
+
+#[derive(SimpleObject)]
+pub struct MeasureTemplate {
+    pub name: String,
+    pub units: String,
+    pub upper_boundary: Option<f64>,
+    pub lower_boundary: Option<f64>,
+    pub min_sample_size: i32,
+}
+
+impl From<&templates::ProjectTemplate> for ProjectTemplate {
+    fn from(template: &templates::ProjectTemplate) -> Self {
+        Self {
+            name: template.name.to_string(),
+            description: template.description.to_string(),
+            measures: template
+                .measures
+                .iter()
+                .map(|m| MeasureTemplate {
+                    name: m.name.to_string(),
+                    units: m.units.to_string(),
+                    upper_boundary: m.upper_boundary,
+                    lower_boundary: m.lower_boundary,
+                    min_sample_size: m.min_sample_size,
+                })
+                .collect(),
+        }
+    }
+}
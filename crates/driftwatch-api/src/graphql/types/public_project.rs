@@ -0,0 +1,86 @@
+use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject, ID};
+use uuid::Uuid;
+
+use super::BenchmarkMatch;
+use crate::catalog::{self, CatalogSort};
+use crate::db::read_connection;
+
+/// Results listed under a public project when no limit is given
+const DEFAULT_LATEST_RESULTS: u64 = 20;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum PublicProjectSort {
+    /// Most recently reported first
+    Recent,
+    /// Most benchmarks first
+    Benchmarks,
+    Name,
+}
+
+impl From<PublicProjectSort> for CatalogSort {
+    fn from(sort: PublicProjectSort) -> Self {
+        match sort {
+            PublicProjectSort::Recent => CatalogSort::Recent,
+            PublicProjectSort::Benchmarks => CatalogSort::Benchmarks,
+            PublicProjectSort::Name => CatalogSort::Name,
+        }
+    }
+}
+
+/// A project its owner made public, as listed in the catalog
+#[derive(SimpleObject)]
+#[graphql(complex, cache_control(max_age = 60))]
+pub struct PublicProject {
+    pub id: ID,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub github_repo: Option<String>,
+    /// Distinct benchmarks with results
+    pub benchmarks: i32,
+    /// Benchmark, measure, branch and testbed combinations with results
+    pub series: i32,
+    pub reports: i32,
+    pub last_report_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[ComplexObject]
+impl PublicProject {
+    /// The most recently updated series with their latest values
+    async fn latest_results(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i32>,
+    ) -> Result<Vec<BenchmarkMatch>> {
+        let db = read_connection(ctx)?;
+        let limit = limit.map_or(DEFAULT_LATEST_RESULTS, |l| l.clamp(0, 1000) as u64);
+        let results = catalog::latest_results(db, Uuid::parse_str(&self.id)?, limit).await?;
+        Ok(results.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<catalog::PublicProject> for PublicProject {
+    fn from(project: catalog::PublicProject) -> Self {
+        Self {
+            id: ID(project.id.to_string()),
+            slug: project.slug,
+            name: project.name,
+            description: project.description,
+            github_repo: project.github_repo,
+            benchmarks: project.benchmarks as i32,
+            series: project.series as i32,
+            reports: project.reports as i32,
+            last_report_at: project.last_report_at.map(Into::into),
+            created_at: project.created_at.into(),
+        }
+    }
+}
+
+/// A page of public projects
+#[derive(SimpleObject)]
+pub struct PublicProjectPage {
+    /// Public projects matching the search across all pages
+    pub total_count: i32,
+    pub projects: Vec<PublicProject>,
+}
@@ -0,0 +1,33 @@
+use async_graphql::SimpleObject;
+
+use crate::entities::pull_request_check::{self, CheckState};
+
+/// Latest `driftwatch/benchmarks` status posted for an open pull request
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 60))]
+pub struct PullRequestCheck {
+    pub pr_number: i32,
+    pub head_sha: String,
+    /// `pending`, `success` or `failure`
+    pub state: String,
+    pub description: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<pull_request_check::Model> for PullRequestCheck {
+    fn from(model: pull_request_check::Model) -> Self {
+        let state = match model.state {
+            CheckState::Pending => "pending",
+            CheckState::Success => "success",
+            CheckState::Failure => "failure",
+        };
+
+        Self {
+            pr_number: model.pr_number,
+            head_sha: model.head_sha,
+            state: state.to_string(),
+            description: model.description,
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
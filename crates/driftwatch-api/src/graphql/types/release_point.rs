@@ -0,0 +1,30 @@
+use async_graphql::SimpleObject;
+
+use crate::releases;
+
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 60))]
+pub struct ReleasePoint {
+    pub version: String,
+    pub benchmark: String,
+    /// Mean over every tagged report of the release
+    pub value: f64,
+    pub min_value: f64,
+    pub max_value: f64,
+    pub sample_size: i32,
+    pub first_reported_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<releases::ReleasePoint> for ReleasePoint {
+    fn from(point: releases::ReleasePoint) -> Self {
+        Self {
+            version: point.version,
+            benchmark: point.benchmark,
+            value: point.mean,
+            min_value: point.min,
+            max_value: point.max,
+            sample_size: point.sample_size as i32,
+            first_reported_at: point.first_reported_at.into(),
+        }
+    }
+}
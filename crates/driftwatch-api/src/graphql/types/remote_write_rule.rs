@@ -0,0 +1,65 @@
+use async_graphql::{InputObject, SimpleObject, ID};
+
+use crate::entities::remote_write_rule;
+use crate::remote_write;
+
+/// Maps Prometheus series sent with remote_write onto a benchmark and
+/// measure. `{label}` in the benchmark and testbed is replaced with the
+/// series' value for that label.
+#[derive(SimpleObject)]
+pub struct RemoteWriteRule {
+    pub id: ID,
+    /// The series' metric name
+    pub metric: String,
+    /// Label values a series must have to match
+    pub matchers: Vec<LabelMatcher>,
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<remote_write_rule::Model> for RemoteWriteRule {
+    fn from(model: remote_write_rule::Model) -> Self {
+        Self {
+            id: ID(model.id.to_string()),
+            matchers: remote_write::matchers(&model)
+                .into_iter()
+                .map(|(name, value)| LabelMatcher { name, value })
+                .collect(),
+            metric: model.metric,
+            benchmark: model.benchmark,
+            measure: model.measure,
+            branch: model.branch,
+            testbed: model.testbed,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+/// A label a series must have, with this exact value
+#[derive(SimpleObject)]
+pub struct LabelMatcher {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(InputObject, Clone)]
+pub struct LabelMatcherInput {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(InputObject)]
+pub struct RemoteWriteRuleInput {
+    /// The series' metric name, e.g. `http_request_duration_seconds`
+    pub metric: String,
+    pub matchers: Option<Vec<LabelMatcherInput>>,
+    /// Benchmark name template, e.g. `{handler}`
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    /// Testbed name template, e.g. `production-{region}`
+    pub testbed: String,
+}
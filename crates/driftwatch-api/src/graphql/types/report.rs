@@ -1,8 +1,18 @@
 use async_graphql::dataloader::DataLoader;
-use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Result, SimpleObject, ID};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::loaders::{BranchLoader, TestbedLoader};
+use super::{ContextEntryInput, MetricInput};
+use crate::db::read_connection;
+use crate::entities::report::{ReportKind as DbReportKind, ReportStatus as DbReportStatus};
+use crate::entities::{
+    self, alert, annotation, metric, report_context, report_tag, threshold_evaluation,
+};
+use crate::evaluation::{self, ComparisonSummary};
+use crate::loaders::{BranchLoader, ProjectLoader, TestbedLoader};
+use crate::manifests;
 
 #[derive(SimpleObject)]
 #[graphql(complex, cache_control(max_age = 3600))]
@@ -10,28 +20,86 @@ pub struct Report {
     pub id: ID,
     pub git_hash: Option<String>,
     pub pr_number: Option<i32>,
+    pub commit_message: Option<String>,
+    pub commit_author: Option<String>,
+    pub committed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub merge_base_hash: Option<String>,
+    /// Merge queue commit the benchmarks ran on, when it differs from `gitHash`
+    pub evaluated_commit: Option<String>,
+    /// Release the report was taken for
+    pub version: Option<String>,
+    /// What triggered the run: `pr`, `push`, `nightly`, `release` or `manual`
+    pub kind: String,
+    /// Exit code of the benchmark command, when the submitter recorded it
+    pub exit_code: Option<i32>,
+    /// Submitted from a benchmark command that failed, so the results may
+    /// stop short of the full suite
+    pub partial: bool,
+    pub finalized: bool,
+    /// Left out of the baselines later reports are compared against
+    pub excluded: bool,
+    /// `pending` until alerts have been evaluated, then `evaluated`
+    pub status: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     #[graphql(skip)]
+    pub project_id: Uuid,
+    #[graphql(skip)]
     pub branch_id: Uuid,
     #[graphql(skip)]
     pub testbed_id: Uuid,
+    #[graphql(skip)]
+    pub base_branch_id: Option<Uuid>,
 }
 
 impl From<crate::entities::report::Model> for Report {
     fn from(model: crate::entities::report::Model) -> Self {
+        let kind = match model.kind {
+            DbReportKind::Pr => "pr",
+            DbReportKind::Push => "push",
+            DbReportKind::Nightly => "nightly",
+            DbReportKind::Release => "release",
+            DbReportKind::Manual => "manual",
+        };
+        let status = match model.status {
+            DbReportStatus::Pending => "pending",
+            DbReportStatus::Evaluated => "evaluated",
+        };
+
         Self {
             id: ID(model.id.to_string()),
             git_hash: model.git_hash,
             pr_number: model.pr_number,
+            commit_message: model.commit_message,
+            commit_author: model.commit_author,
+            committed_at: model.committed_at.map(Into::into),
+            merge_base_hash: model.merge_base_hash,
+            evaluated_commit: model.evaluated_commit,
+            version: model.version,
+            finalized: model.finalized,
+            excluded: model.excluded,
+            kind: kind.to_string(),
+            exit_code: model.exit_code,
+            partial: model.partial,
+            status: status.to_string(),
             created_at: model.created_at.into(),
+            project_id: model.project_id,
             branch_id: model.branch_id,
             testbed_id: model.testbed_id,
+            base_branch_id: model.base_branch_id,
         }
     }
 }
 
 #[ComplexObject]
 impl Report {
+    async fn project(&self, ctx: &Context<'_>) -> Result<super::Project> {
+        let loader = ctx.data::<DataLoader<ProjectLoader>>()?;
+        loader
+            .load_one(self.project_id)
+            .await?
+            .ok_or_else(|| "Workspace not found".into())
+    }
+
     async fn branch(&self, ctx: &Context<'_>) -> Result<super::Branch> {
         let loader = ctx.data::<DataLoader<BranchLoader>>()?;
         loader
@@ -40,6 +108,14 @@ impl Report {
             .ok_or_else(|| "Branch not found".into())
     }
 
+    async fn base_branch(&self, ctx: &Context<'_>) -> Result<Option<super::Branch>> {
+        let Some(base_branch_id) = self.base_branch_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<BranchLoader>>()?;
+        loader.load_one(base_branch_id).await
+    }
+
     async fn testbed(&self, ctx: &Context<'_>) -> Result<super::Testbed> {
         let loader = ctx.data::<DataLoader<TestbedLoader>>()?;
         loader
@@ -47,4 +123,317 @@ impl Report {
             .await?
             .ok_or_else(|| "Testbed not found".into())
     }
+
+    async fn metrics(&self, ctx: &Context<'_>) -> Result<Vec<super::Metric>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let metrics = entities::Metric::find()
+            .filter(metric::Column::ReportId.eq(report_id))
+            .all(db)
+            .await?;
+
+        Ok(metrics.into_iter().map(Into::into).collect())
+    }
+
+    /// Improved/regressed/unchanged counts against each metric's baseline.
+    /// `tolerance` is the percent change still considered unchanged.
+    async fn comparison(
+        &self,
+        ctx: &Context<'_>,
+        tolerance: Option<f64>,
+    ) -> Result<ReportComparison> {
+        let db = read_connection(ctx)?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let report = entities::Report::find_by_id(report_id)
+            .one(db)
+            .await?
+            .ok_or("Report not found")?;
+        let metrics = entities::Metric::find()
+            .filter(metric::Column::ReportId.eq(report_id))
+            .all(db)
+            .await?;
+
+        let changes = evaluation::compare_report(db, &report, &metrics).await?;
+        let tolerance = tolerance.unwrap_or(evaluation::DEFAULT_CHANGE_TOLERANCE);
+        Ok(evaluation::summarize_changes(&changes, tolerance).into())
+    }
+
+    async fn alerts(&self, ctx: &Context<'_>) -> Result<Vec<super::Alert>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let alerts = entities::Alert::find()
+            .inner_join(entities::Metric)
+            .filter(metric::Column::ReportId.eq(report_id))
+            .filter(alert::Column::Status.ne(alert::AlertStatus::Unconfirmed))
+            .order_by_desc(alert::Column::PercentChange)
+            .all(db)
+            .await?;
+
+        Ok(alerts.into_iter().map(Into::into).collect())
+    }
+
+    /// Every threshold check made when the report was evaluated, including
+    /// those that passed or lacked history. Re-evaluations add to it; newest
+    /// first.
+    async fn threshold_evaluations(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<super::ThresholdEvaluation>> {
+        let db = read_connection(ctx)?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let evaluations = entities::ThresholdEvaluation::find()
+            .filter(threshold_evaluation::Column::ReportId.eq(report_id))
+            .order_by_desc(threshold_evaluation::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(evaluations.into_iter().map(Into::into).collect())
+    }
+
+    /// Regressions that will only be raised if they reproduce on the
+    /// project's next reports. CI can rerun the benchmarks to confirm them.
+    async fn unconfirmed_alerts(&self, ctx: &Context<'_>) -> Result<Vec<super::Alert>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let alerts = entities::Alert::find()
+            .inner_join(entities::Metric)
+            .filter(metric::Column::ReportId.eq(report_id))
+            .filter(alert::Column::Status.eq(alert::AlertStatus::Unconfirmed))
+            .order_by_desc(alert::Column::PercentChange)
+            .all(db)
+            .await?;
+
+        Ok(alerts.into_iter().map(Into::into).collect())
+    }
+
+    /// Notes attached to this report, oldest first
+    async fn annotations(&self, ctx: &Context<'_>) -> Result<Vec<super::Annotation>> {
+        let db = read_connection(ctx)?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let annotations = entities::Annotation::find()
+            .filter(annotation::Column::ReportId.eq(report_id))
+            .order_by_asc(annotation::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(annotations.into_iter().map(Into::into).collect())
+    }
+
+    /// Key/values recorded with the report, sorted by key
+    async fn context(&self, ctx: &Context<'_>) -> Result<Vec<super::ContextEntry>> {
+        let db = read_connection(ctx)?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let entries = entities::ReportContext::find()
+            .filter(report_context::Column::ReportId.eq(report_id))
+            .order_by_asc(report_context::Column::Key)
+            .all(db)
+            .await?;
+
+        Ok(entries.into_iter().map(Into::into).collect())
+    }
+
+    /// Sorted alphabetically
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let db = read_connection(ctx)?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let tags = entities::ReportTag::find()
+            .filter(report_tag::Column::ReportId.eq(report_id))
+            .order_by_asc(report_tag::Column::Tag)
+            .all(db)
+            .await?;
+
+        Ok(tags.into_iter().map(|t| t.tag).collect())
+    }
+
+    /// Benchmarks in the suite manifest the report was submitted with that
+    /// it has no metrics for, e.g. because they crashed or were filtered
+    /// out. Null when it came without a manifest.
+    async fn missing_benchmarks(&self, ctx: &Context<'_>) -> Result<Option<Vec<String>>> {
+        let db = read_connection(ctx)?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+        Ok(manifests::missing_benchmarks(db, report_id).await?)
+    }
+
+    /// Output of the benchmark command, when the CLI attached it
+    async fn output(&self, ctx: &Context<'_>) -> Result<Option<super::ReportOutput>> {
+        let db = read_connection(ctx)?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let output = entities::ReportOutput::find_by_id(report_id)
+            .one(db)
+            .await?;
+        Ok(output.map(super::ReportOutput::try_from).transpose()?)
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ReportComparison {
+    pub total_benchmarks: i32,
+    pub improved: i32,
+    pub regressed: i32,
+    pub unchanged: i32,
+    pub without_baseline: i32,
+    /// Largest increase over baseline among regressed benchmarks, in percent
+    pub max_regression: Option<f64>,
+}
+
+impl From<ComparisonSummary> for ReportComparison {
+    fn from(summary: ComparisonSummary) -> Self {
+        Self {
+            total_benchmarks: summary.total as i32,
+            improved: summary.improved as i32,
+            regressed: summary.regressed as i32,
+            unchanged: summary.unchanged as i32,
+            without_baseline: summary.without_baseline as i32,
+            max_regression: summary.max_regression,
+        }
+    }
+}
+
+/// What `reevaluateReport` changed
+#[derive(SimpleObject)]
+pub struct ReportReevaluation {
+    pub report: Report,
+    /// Alerts raised for violations that had none
+    pub raised: i32,
+    /// Open alerts that still violate, with refreshed baselines
+    pub updated: i32,
+    /// Open alerts resolved because they no longer violate
+    pub resolved: i32,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKindInput {
+    Pr,
+    Push,
+    Nightly,
+    Release,
+    Manual,
+}
+
+impl ReportKindInput {
+    pub fn to_db_value(&self) -> DbReportKind {
+        match self {
+            ReportKindInput::Pr => DbReportKind::Pr,
+            ReportKindInput::Push => DbReportKind::Push,
+            ReportKindInput::Nightly => DbReportKind::Nightly,
+            ReportKindInput::Release => DbReportKind::Release,
+            ReportKindInput::Manual => DbReportKind::Manual,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateReportInput {
+    pub project_slug: String,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub pr_number: Option<i32>,
+    pub commit_message: Option<String>,
+    pub commit_author: Option<String>,
+    pub committed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Branch a pull request targets; enables merge-base comparison
+    pub base_branch: Option<String>,
+    /// Merge-base of the pull request and `base_branch`. Resolved through the
+    /// project's GitHub integration when omitted.
+    pub merge_base_hash: Option<String>,
+    /// Commit the benchmarks ran on when it isn't `git_hash`, e.g. a merge
+    /// queue's temporary merge commit. Gets the report's commit status.
+    pub evaluated_commit: Option<String>,
+    /// Release tag, e.g. `1.4.0`; tagged reports make up the project's
+    /// release series
+    pub version: Option<String>,
+    /// What triggered the run. PR reports only count toward the baselines
+    /// of the same PR's later runs, so a PR run submitted to `main` doesn't
+    /// move main's baseline. Defaults to `PR` with a `pr_number`, `RELEASE`
+    /// with a `version`, otherwise `MANUAL`.
+    pub kind: Option<ReportKindInput>,
+    /// Exit code of the benchmark command
+    pub exit_code: Option<i32>,
+    /// The results are what a failed benchmark command produced before it
+    /// stopped. Defaults to whether `exit_code` is non-zero.
+    pub partial: Option<bool>,
+    /// Overrides the report timestamp, e.g. the commit time when backfilling history
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Key/values describing the run, e.g. `rustc` = `1.80` or `allocator` =
+    /// `jemalloc`, for filtering reports and splitting series later
+    pub context: Option<Vec<ContextEntryInput>>,
+    /// Labels such as `nightly` or `release-candidate` to list reports by
+    pub tags: Option<Vec<String>>,
+    /// Suite manifest: every benchmark the run was meant to produce, e.g. as
+    /// listed by the benchmark harness. Those the report ends up without
+    /// metrics for are its `missingBenchmarks`.
+    pub expected_benchmarks: Option<Vec<String>>,
+    /// Id for the new report, chosen by the submitter, e.g. an ingestion
+    /// relay that answers before forwarding. Submitting the same id again
+    /// returns the report it created.
+    pub id: Option<ID>,
+    pub metrics: Vec<MetricInput>,
+}
+
+impl CreateReportInput {
+    pub fn split(self) -> (OpenReportInput, Vec<MetricInput>) {
+        (
+            OpenReportInput {
+                project_slug: self.project_slug,
+                branch: self.branch,
+                testbed: self.testbed,
+                git_hash: self.git_hash,
+                pr_number: self.pr_number,
+                commit_message: self.commit_message,
+                commit_author: self.commit_author,
+                committed_at: self.committed_at,
+                base_branch: self.base_branch,
+                merge_base_hash: self.merge_base_hash,
+                evaluated_commit: self.evaluated_commit,
+                version: self.version,
+                kind: self.kind,
+                exit_code: self.exit_code,
+                partial: self.partial,
+                created_at: self.created_at,
+                context: self.context,
+                tags: self.tags,
+                expected_benchmarks: self.expected_benchmarks,
+                id: self.id,
+            },
+            self.metrics,
+        )
+    }
+}
+
+/// Starts a chunked upload for reports too large for a single `createReport`.
+/// Metrics are added with `appendReportMetrics` and alerts are evaluated on
+/// `finalizeReport`.
+#[derive(InputObject)]
+pub struct OpenReportInput {
+    pub project_slug: String,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub pr_number: Option<i32>,
+    pub commit_message: Option<String>,
+    pub commit_author: Option<String>,
+    pub committed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub base_branch: Option<String>,
+    pub merge_base_hash: Option<String>,
+    pub evaluated_commit: Option<String>,
+    pub version: Option<String>,
+    pub kind: Option<ReportKindInput>,
+    pub exit_code: Option<i32>,
+    pub partial: Option<bool>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub context: Option<Vec<ContextEntryInput>>,
+    pub tags: Option<Vec<String>>,
+    pub expected_benchmarks: Option<Vec<String>>,
+    pub id: Option<ID>,
 }
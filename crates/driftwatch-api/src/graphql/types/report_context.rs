@@ -0,0 +1,51 @@
+use async_graphql::{InputObject, SimpleObject, ID};
+
+use crate::context;
+use crate::entities::report_context;
+
+/// Key/value recorded with a report, e.g. `rustc` = `1.80`
+#[derive(SimpleObject)]
+pub struct ContextEntry {
+    pub key: String,
+    pub value: String,
+}
+
+impl From<report_context::Model> for ContextEntry {
+    fn from(model: report_context::Model) -> Self {
+        Self {
+            key: model.key,
+            value: model.value,
+        }
+    }
+}
+
+#[derive(InputObject, Clone)]
+pub struct ContextEntryInput {
+    pub key: String,
+    pub value: String,
+}
+
+/// A benchmark's value in one report, labelled with the report's value of
+/// the context key the series is split by
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 60))]
+pub struct ContextPoint {
+    /// Null for reports that didn't record the key
+    pub context_value: Option<String>,
+    pub report_id: ID,
+    pub git_hash: Option<String>,
+    pub value: f64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<context::ContextPoint> for ContextPoint {
+    fn from(point: context::ContextPoint) -> Self {
+        Self {
+            context_value: point.context_value,
+            report_id: ID(point.report_id.to_string()),
+            git_hash: point.git_hash,
+            value: point.value,
+            created_at: point.created_at.into(),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use async_graphql::{InputObject, SimpleObject};
+
+use super::{ContextEntry, ContextEntryInput};
+use crate::entities::report_filter;
+use crate::report_filters::ReportCriteria;
+
+/// A saved combination of tags and context to list reports by
+#[derive(SimpleObject)]
+pub struct ReportFilter {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub context: Vec<ContextEntry>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<report_filter::Model> for ReportFilter {
+    fn from(model: report_filter::Model) -> Self {
+        Self {
+            tags: model.tag_list(),
+            context: model
+                .context_entries()
+                .into_iter()
+                .map(|(key, value)| ContextEntry { key, value })
+                .collect(),
+            name: model.name,
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct ReportFilterInput {
+    pub name: String,
+    /// Reports need every tag
+    #[graphql(default)]
+    pub tags: Vec<String>,
+    /// Reports need every key/value
+    #[graphql(default)]
+    pub context: Vec<ContextEntryInput>,
+}
+
+impl ReportFilterInput {
+    pub fn criteria(&self) -> ReportCriteria {
+        ReportCriteria {
+            tags: self.tags.clone(),
+            context: self
+                .context
+                .iter()
+                .map(|e| (e.key.clone(), e.value.clone()))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use async_graphql::SimpleObject;
+use base64::Engine;
+
+use crate::entities::report_output;
+use crate::storage;
+
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 3600))]
+pub struct ReportOutput {
+    /// The command's stdout and stderr, gzip-compressed and base64-encoded
+    pub content: String,
+    pub compressed_size: i32,
+    /// Size of the output before compression
+    pub size: Option<i32>,
+    /// Secrets replaced with `[REDACTED:<kind>]` before the output was stored
+    pub redactions: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<report_output::Model> for ReportOutput {
+    type Error = std::io::Error;
+
+    /// Outputs are stored zstd-compressed; they're handed out as gzip, which
+    /// any client can read
+    fn try_from(model: report_output::Model) -> Result<Self, Self::Error> {
+        let content = storage::output_gzip(&model)?;
+        Ok(Self {
+            content: base64::engine::general_purpose::STANDARD.encode(&content),
+            compressed_size: content.len() as i32,
+            size: model.size.map(|size| size as i32),
+            redactions: model.redactions,
+            created_at: model.created_at.into(),
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use async_graphql::{SimpleObject, ID};
+
+use crate::scaling;
+
+#[derive(SimpleObject)]
+pub struct ScalingPoint {
+    pub benchmark: String,
+    pub parameter: f64,
+    pub value: f64,
+}
+
+/// A parameterized benchmark's values across its parameters in one report
+#[derive(SimpleObject)]
+#[graphql(cache_control(max_age = 60))]
+pub struct ScalingCurve {
+    pub report_id: ID,
+    pub git_hash: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub points: Vec<ScalingPoint>,
+    /// Slope of the curve on a log-log scale: about 1 for linear growth,
+    /// 2 for quadratic
+    pub growth_exponent: Option<f64>,
+}
+
+impl From<scaling::ScalingCurve> for ScalingCurve {
+    fn from(curve: scaling::ScalingCurve) -> Self {
+        Self {
+            report_id: ID(curve.report_id.to_string()),
+            git_hash: curve.git_hash,
+            created_at: curve.created_at.into(),
+            growth_exponent: scaling::growth_exponent(&curve.points),
+            points: curve
+                .points
+                .into_iter()
+                .map(|p| ScalingPoint {
+                    benchmark: p.benchmark,
+                    parameter: p.parameter,
+                    value: p.value,
+                })
+                .collect(),
+        }
+    }
+}
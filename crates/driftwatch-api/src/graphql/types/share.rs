@@ -0,0 +1,25 @@
+use async_graphql::{InputObject, SimpleObject, ID};
+
+use super::MetricInput;
+
+/// A one-off report to share without an account
+#[derive(InputObject)]
+pub struct ShareReportInput {
+    /// Title of the shared page
+    pub name: Option<String>,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub metrics: Vec<MetricInput>,
+}
+
+/// A stored anonymous share, from `shareReport`
+#[derive(SimpleObject)]
+pub struct SharedReport {
+    /// Path under the API's URL where the report can be read. The token in
+    /// it is the only credential and isn't shown again.
+    pub path: String,
+    /// When the share and its report are deleted
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub report_id: ID,
+}
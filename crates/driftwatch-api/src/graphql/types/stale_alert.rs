@@ -0,0 +1,61 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use uuid::Uuid;
+
+use crate::entities::stale_alert::StaleAlertStatus as DbStaleAlertStatus;
+use crate::loaders::{BranchLoader, TestbedLoader};
+
+#[derive(SimpleObject)]
+#[graphql(complex, cache_control(max_age = 60))]
+pub struct StaleAlert {
+    pub id: ID,
+    pub status: String,
+    pub last_report_at: chrono::DateTime<chrono::Utc>,
+    pub expected_cadence_hours: i32,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[graphql(skip)]
+    pub branch_id: Uuid,
+    #[graphql(skip)]
+    pub testbed_id: Uuid,
+}
+
+impl From<crate::entities::stale_alert::Model> for StaleAlert {
+    fn from(model: crate::entities::stale_alert::Model) -> Self {
+        let status = match model.status {
+            DbStaleAlertStatus::Active => "active",
+            DbStaleAlertStatus::Resolved => "resolved",
+            DbStaleAlertStatus::Dismissed => "dismissed",
+        };
+
+        Self {
+            id: ID(model.id.to_string()),
+            status: status.to_string(),
+            last_report_at: model.last_report_at.into(),
+            expected_cadence_hours: model.expected_cadence_hours,
+            resolved_at: model.resolved_at.map(Into::into),
+            created_at: model.created_at.into(),
+            branch_id: model.branch_id,
+            testbed_id: model.testbed_id,
+        }
+    }
+}
+
+#[ComplexObject]
+impl StaleAlert {
+    async fn branch(&self, ctx: &Context<'_>) -> Result<super::Branch> {
+        let loader = ctx.data::<DataLoader<BranchLoader>>()?;
+        loader
+            .load_one(self.branch_id)
+            .await?
+            .ok_or_else(|| "Branch not found".into())
+    }
+
+    async fn testbed(&self, ctx: &Context<'_>) -> Result<super::Testbed> {
+        let loader = ctx.data::<DataLoader<TestbedLoader>>()?;
+        loader
+            .load_one(self.testbed_id)
+            .await?
+            .ok_or_else(|| "Testbed not found".into())
+    }
+}
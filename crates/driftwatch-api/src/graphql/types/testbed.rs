@@ -1,6 +1,6 @@
 use async_graphql::{SimpleObject, ID};
 
-use crate::entities::testbed;
+use crate::entities::{testbed, testbed_alias};
 
 #[derive(SimpleObject, Clone)]
 #[graphql(cache_control(max_age = 300))]
@@ -19,3 +19,21 @@ impl From<testbed::Model> for Testbed {
         }
     }
 }
+
+/// Another name reports may use for `testbed`
+#[derive(SimpleObject, Clone)]
+pub struct TestbedAlias {
+    pub alias: String,
+    pub testbed: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TestbedAlias {
+    pub fn new(alias: testbed_alias::Model, testbed: testbed::Model) -> Self {
+        Self {
+            alias: alias.alias,
+            testbed: testbed.name,
+            created_at: alias.created_at.into(),
+        }
+    }
+}
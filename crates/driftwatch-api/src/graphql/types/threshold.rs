@@ -1,6 +1,9 @@
-use async_graphql::{InputObject, SimpleObject, ID};
+use async_graphql::{Enum, InputObject, SimpleObject, ID};
+
+use uuid::Uuid;
 
 use crate::entities::threshold;
+use crate::simulation::Model;
 
 #[derive(SimpleObject, Clone)]
 #[graphql(cache_control(max_age = 300))]
@@ -13,6 +16,8 @@ pub struct Threshold {
     pub lower_boundary: Option<f64>,
     pub min_sample_size: i32,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    #[graphql(skip)]
+    pub project_id: Uuid,
 }
 
 impl From<threshold::Model> for Threshold {
@@ -26,6 +31,7 @@ impl From<threshold::Model> for Threshold {
             lower_boundary: model.lower_boundary,
             min_sample_size: model.min_sample_size,
             created_at: model.created_at.into(),
+            project_id: model.project_id,
         }
     }
 }
@@ -40,3 +46,94 @@ pub struct CreateThresholdInput {
     pub lower_boundary: Option<f64>,
     pub min_sample_size: Option<i32>,
 }
+
+/// Either an existing report or hypothetical metrics to check against the
+/// project's current thresholds.
+#[derive(InputObject)]
+pub struct ThresholdTestInput {
+    pub project_slug: String,
+    pub report_id: Option<ID>,
+    /// Hypothetical results, compared as if reported now on `branch` and
+    /// `testbed`
+    pub metrics: Option<Vec<super::MetricInput>>,
+    pub branch: Option<String>,
+    pub testbed: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ThresholdTestResult {
+    /// The threshold as it would be evaluated, including any noise adjustment
+    pub threshold: Threshold,
+    pub benchmark: String,
+    pub measure: String,
+    pub value: f64,
+    pub baseline_value: f64,
+    pub percent_change: f64,
+    pub would_alert: bool,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum ThresholdModelInput {
+    /// Percent change from the mean of the window, like stored thresholds
+    Percent,
+    /// Standard deviations from the mean of the window
+    Zscore,
+}
+
+impl ThresholdModelInput {
+    pub fn to_model(&self) -> Model {
+        match self {
+            ThresholdModelInput::Percent => Model::Percent,
+            ThresholdModelInput::Zscore => Model::ZScore,
+        }
+    }
+}
+
+/// A proposed threshold configuration to replay a project's history through.
+/// Boundaries are percentages for `PERCENT` and standard deviations for
+/// `ZSCORE`; without either, only an upper boundary of 10% or 3 standard
+/// deviations is checked.
+#[derive(InputObject)]
+pub struct SimulateThresholdsInput {
+    pub project_slug: String,
+    pub model: ThresholdModelInput,
+    /// Earlier reports the baseline is computed from (default 30)
+    pub window: Option<i32>,
+    pub upper_boundary: Option<f64>,
+    pub lower_boundary: Option<f64>,
+    pub min_sample_size: Option<i32>,
+    /// Measure name; all measures when unset
+    pub measure: Option<String>,
+    pub branch: Option<String>,
+    pub testbed: Option<String>,
+    /// Only count alerts from the last this many days; older reports still
+    /// fill the window
+    pub days: Option<i32>,
+}
+
+#[derive(SimpleObject)]
+pub struct SimulatedAlert {
+    pub report_id: ID,
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub value: f64,
+    pub baseline_value: f64,
+    /// Percent change or z-score, depending on the model
+    pub score: f64,
+}
+
+#[derive(SimpleObject)]
+pub struct ThresholdSimulation {
+    /// Branch, testbed, benchmark and measure combinations replayed
+    pub series: i32,
+    /// Values with enough history to be checked
+    pub comparisons: i32,
+    /// Alerts the configuration would have raised, oldest first
+    pub alerts: Vec<SimulatedAlert>,
+    /// Alerts the project's current thresholds actually raised in the same
+    /// scope
+    pub actual_alerts: i32,
+}
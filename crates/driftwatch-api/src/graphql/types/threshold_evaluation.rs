@@ -0,0 +1,83 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use uuid::Uuid;
+
+use crate::entities::threshold_evaluation::{self, Verdict};
+use crate::evaluation;
+use crate::loaders::{MetricLoader, ThresholdLoader};
+
+/// How one threshold was checked against one metric when a report was
+/// evaluated
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ThresholdEvaluation {
+    pub id: ID,
+    pub report_id: ID,
+    /// `violated`, `passed` or `insufficient_history`
+    pub verdict: String,
+    pub baseline: Option<f64>,
+    /// Earlier values the baseline was computed from
+    pub baseline_size: i32,
+    /// Compared against the base branch at the merge-base
+    pub pinned: bool,
+    pub percent_change: Option<f64>,
+    /// Boundaries as applied, after any noise adjustment
+    pub upper_boundary: Option<f64>,
+    pub lower_boundary: Option<f64>,
+    pub min_sample_size: i32,
+    pub noise_adjusted: bool,
+    /// The check in one sentence
+    pub explanation: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[graphql(skip)]
+    pub metric_id: Uuid,
+    #[graphql(skip)]
+    pub threshold_id: Uuid,
+}
+
+impl From<threshold_evaluation::Model> for ThresholdEvaluation {
+    fn from(model: threshold_evaluation::Model) -> Self {
+        let verdict = match model.verdict {
+            Verdict::Violated => "violated",
+            Verdict::Passed => "passed",
+            Verdict::InsufficientHistory => "insufficient_history",
+        };
+
+        Self {
+            id: ID(model.id.to_string()),
+            report_id: ID(model.report_id.to_string()),
+            verdict: verdict.to_string(),
+            baseline: model.baseline,
+            baseline_size: model.baseline_size,
+            pinned: model.pinned,
+            percent_change: model.percent_change,
+            upper_boundary: model.upper_boundary,
+            lower_boundary: model.lower_boundary,
+            min_sample_size: model.min_sample_size,
+            noise_adjusted: model.noise_adjusted,
+            explanation: evaluation::explain(&model),
+            created_at: model.created_at.into(),
+            metric_id: model.metric_id,
+            threshold_id: model.threshold_id,
+        }
+    }
+}
+
+#[ComplexObject]
+impl ThresholdEvaluation {
+    async fn metric(&self, ctx: &Context<'_>) -> Result<super::Metric> {
+        let loader = ctx.data::<DataLoader<MetricLoader>>()?;
+        loader
+            .load_one(self.metric_id)
+            .await?
+            .ok_or_else(|| "Metric not found".into())
+    }
+
+    async fn threshold(&self, ctx: &Context<'_>) -> Result<super::Threshold> {
+        let loader = ctx.data::<DataLoader<ThresholdLoader>>()?;
+        loader
+            .load_one(self.threshold_id)
+            .await?
+            .ok_or_else(|| "Threshold not found".into())
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextRequest, NextResolve, ResolveInfo,
+};
+use async_graphql::registry::Deprecation;
+use async_graphql::{Response, ServerResult, Value};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+
+pub use driftwatch_protocol::{API_VERSION, API_VERSION_EXTENSION, CLIENT_HEADER};
+
+/// The caller's `x-driftwatch-client` header
+#[derive(Debug, Clone)]
+pub struct ClientVersion(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsageKey {
+    /// `Type.field`
+    field: String,
+    user_id: Option<Uuid>,
+    token: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DeprecatedUsage {
+    pub field: String,
+    pub user_id: Option<Uuid>,
+    /// API key name and prefix, `session` or `anonymous`; never the token
+    pub token: String,
+    pub client: Option<String>,
+    pub count: u64,
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// Which callers still use deprecated fields, since the server started.
+#[derive(Debug, Clone, Default)]
+pub struct DeprecationLog {
+    usage: Arc<Mutex<HashMap<UsageKey, DeprecatedUsage>>>,
+}
+
+/// API key name and prefix, `session` or `anonymous`, for logs
+pub fn token_label(user: Option<&AuthUser>) -> String {
+    match user {
+        Some(AuthUser {
+            api_key: Some(key), ..
+        }) => format!("api key {} ({})", key.name, key.prefix),
+        Some(_) => "session".to_string(),
+        None => "anonymous".to_string(),
+    }
+}
+
+impl DeprecationLog {
+    /// Counts a use of `field`, returning true the first time this caller
+    /// uses it
+    pub fn record(&self, field: &str, user: Option<&AuthUser>, client: Option<&str>) -> bool {
+        let key = UsageKey {
+            field: field.to_string(),
+            user_id: user.map(|u| u.user_id()),
+            token: token_label(user),
+        };
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Utc::now();
+
+        match usage.get_mut(&key) {
+            Some(entry) => {
+                entry.count += 1;
+                entry.last_used_at = now;
+                if let Some(client) = client {
+                    entry.client = Some(client.to_string());
+                }
+                false
+            }
+            None => {
+                usage.insert(
+                    key.clone(),
+                    DeprecatedUsage {
+                        field: key.field,
+                        user_id: key.user_id,
+                        token: key.token,
+                        client: client.map(str::to_string),
+                        count: 1,
+                        last_used_at: now,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Every use so far, most recent first
+    pub fn usage(&self) -> Vec<DeprecatedUsage> {
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries: Vec<DeprecatedUsage> = usage.values().cloned().collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_used_at));
+        entries
+    }
+}
+
+/// Records resolved deprecated fields in a `DeprecationLog` and stamps
+/// every response with the API version.
+pub struct ApiVersioning {
+    log: DeprecationLog,
+}
+
+impl ApiVersioning {
+    pub fn new(log: DeprecationLog) -> Self {
+        Self { log }
+    }
+}
+
+impl ExtensionFactory for ApiVersioning {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ApiVersioningExtension {
+            log: self.log.clone(),
+        })
+    }
+}
+
+struct ApiVersioningExtension {
+    log: DeprecationLog,
+}
+
+#[async_trait::async_trait]
+impl Extension for ApiVersioningExtension {
+    async fn request(&self, ctx: &ExtensionContext<'_>, next: NextRequest<'_>) -> Response {
+        next.run(ctx)
+            .await
+            .extension(API_VERSION_EXTENSION, Value::from(API_VERSION))
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        if !info.is_for_introspection {
+            let deprecation = ctx
+                .schema_env
+                .registry
+                .types
+                .get(info.parent_type)
+                .and_then(|ty| ty.field_by_name(info.name))
+                .map(|field| &field.deprecation);
+
+            if let Some(Deprecation::Deprecated { reason }) = deprecation {
+                let field = format!("{}.{}", info.parent_type, info.name);
+                let user = ctx.data_opt::<AuthUser>();
+                let client = ctx.data_opt::<ClientVersion>().map(|c| c.0.as_str());
+                if self.log.record(&field, user, client) {
+                    tracing::warn!(
+                        field = %field,
+                        user_id = ?user.map(|u| u.user_id()),
+                        token = %token_label(user),
+                        client = client.unwrap_or("unknown"),
+                        reason = reason.as_deref().unwrap_or(""),
+                        "Deprecated GraphQL field used"
+                    );
+                }
+            }
+        }
+        next.run(ctx, info).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn current(&self) -> i32 {
+            2
+        }
+
+        #[graphql(deprecation = "Use `current`")]
+        async fn legacy(&self) -> i32 {
+            1
+        }
+    }
+
+    fn schema(log: &DeprecationLog) -> Schema<Query, EmptyMutation, EmptySubscription> {
+        Schema::build(Query, EmptyMutation, EmptySubscription)
+            .extension(ApiVersioning::new(log.clone()))
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_records_deprecated_fields() {
+        let log = DeprecationLog::default();
+        let schema = schema(&log);
+
+        let response = schema.execute("{ current }").await;
+        assert_eq!(
+            response.extensions.get(API_VERSION_EXTENSION),
+            Some(&Value::from(API_VERSION))
+        );
+        assert!(log.usage().is_empty());
+
+        schema.execute("{ legacy current }").await;
+        schema
+            .execute(
+                async_graphql::Request::new("{ legacy }")
+                    .data(ClientVersion("driftwatch-cli/0.1.0".to_string())),
+            )
+            .await;
+
+        let usage = log.usage();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].field, "Query.legacy");
+        assert_eq!(usage[0].token, "anonymous");
+        assert_eq!(usage[0].count, 2);
+        assert_eq!(usage[0].client.as_deref(), Some("driftwatch-cli/0.1.0"));
+    }
+
+    #[tokio::test]
+    async fn test_ignores_introspection() {
+        let log = DeprecationLog::default();
+        schema(&log)
+            .execute("{ __type(name: \"Query\") { fields(includeDeprecated: true) { name isDeprecated } } }")
+            .await;
+        assert!(log.usage().is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+//! CORS and security headers for the HTTP server.
+//!
+//! Out of the box any origin may call the API, which suits the CLI and
+//! quick trials. Self-hosted instances that serve a browser frontend should
+//! list its origins in `CORS_ALLOWED_ORIGINS` so other sites can't script
+//! the API with a visitor's credentials.
+
+use anyhow::{Context, Result};
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use axum::Router;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// Allows the bundled GraphiQL page, which loads its scripts and styles
+/// from unpkg, and nothing else from elsewhere
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    script-src 'self' 'unsafe-inline' https://unpkg.com; \
+    style-src 'self' 'unsafe-inline' https://unpkg.com; \
+    img-src 'self' data: https://graphql.org; \
+    connect-src 'self'; \
+    frame-ancestors 'none'; \
+    base-uri 'none'; \
+    form-action 'self'";
+
+#[derive(Clone, Debug)]
+pub struct HttpSecurity {
+    /// Origins browsers may call the API from; empty allows any
+    pub cors_origins: Vec<String>,
+    /// `None` leaves the `Content-Security-Policy` header off
+    pub content_security_policy: Option<String>,
+    /// Sends `Strict-Transport-Security` when set; only enable behind HTTPS
+    pub hsts_max_age_secs: Option<u64>,
+}
+
+impl Default for HttpSecurity {
+    fn default() -> Self {
+        Self {
+            cors_origins: Vec::new(),
+            content_security_policy: Some(DEFAULT_CONTENT_SECURITY_POLICY.to_string()),
+            hsts_max_age_secs: None,
+        }
+    }
+}
+
+impl HttpSecurity {
+    /// Reads `CORS_ALLOWED_ORIGINS` (comma-separated, `*` for any),
+    /// `CONTENT_SECURITY_POLICY` (empty to disable) and `HSTS_MAX_AGE`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            cors_origins: std::env::var("CORS_ALLOWED_ORIGINS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|o| o.trim().trim_end_matches('/').to_string())
+                .filter(|o| !o.is_empty() && o != "*")
+                .collect(),
+            content_security_policy: match std::env::var("CONTENT_SECURITY_POLICY") {
+                Ok(policy) => Some(policy.trim().to_string()).filter(|p| !p.is_empty()),
+                Err(_) => defaults.content_security_policy,
+            },
+            hsts_max_age_secs: std::env::var("HSTS_MAX_AGE")
+                .ok()
+                .map(|v| v.parse().expect("HSTS_MAX_AGE must be a valid number"))
+                .filter(|&secs| secs > 0),
+        }
+    }
+
+    pub fn cors(&self) -> Result<CorsLayer> {
+        let cors = CorsLayer::new()
+            .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+            .allow_headers(Any);
+        if self.cors_origins.is_empty() {
+            return Ok(cors.allow_origin(Any));
+        }
+        let origins = self
+            .cors_origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin)
+                    .with_context(|| format!("Invalid CORS origin {:?}", origin))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(cors
+            .allow_origin(AllowOrigin::list(origins))
+            .vary([header::ORIGIN]))
+    }
+
+    /// Response headers added to every response that doesn't set them itself
+    pub fn headers(&self) -> Result<Vec<(HeaderName, HeaderValue)>> {
+        let mut headers = vec![
+            (
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ),
+            (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+            (
+                header::REFERRER_POLICY,
+                HeaderValue::from_static("no-referrer"),
+            ),
+            (
+                HeaderName::from_static("cross-origin-opener-policy"),
+                HeaderValue::from_static("same-origin"),
+            ),
+        ];
+        if let Some(policy) = &self.content_security_policy {
+            headers.push((
+                header::CONTENT_SECURITY_POLICY,
+                HeaderValue::from_str(policy).context("Invalid CONTENT_SECURITY_POLICY")?,
+            ));
+        }
+        if let Some(secs) = self.hsts_max_age_secs {
+            headers.push((
+                header::STRICT_TRANSPORT_SECURITY,
+                HeaderValue::from_str(&format!("max-age={}; includeSubDomains", secs))?,
+            ));
+        }
+        Ok(headers)
+    }
+
+    /// Wraps every route of `router` in the CORS and header layers
+    pub fn apply<S: Clone + Send + Sync + 'static>(&self, router: Router<S>) -> Result<Router<S>> {
+        let mut router = router;
+        for (name, value) in self.headers()? {
+            router = router.layer(SetResponseHeaderLayer::if_not_present(name, value));
+        }
+        Ok(router.layer(self.cors()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    async fn response(security: &HttpSecurity, origin: &str) -> axum::http::Response<Body> {
+        let app = security
+            .apply(Router::new().route("/health", get(|| async { "OK" })))
+            .unwrap();
+        app.oneshot(
+            Request::get("/health")
+                .header(header::ORIGIN, origin)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_default_allows_any_origin_with_security_headers() {
+        let response = response(&HttpSecurity::default(), "https://example.com").await;
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            DEFAULT_CONTENT_SECURITY_POLICY
+        );
+        assert!(headers.get(header::STRICT_TRANSPORT_SECURITY).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_configured_origins_and_headers() {
+        let security = HttpSecurity {
+            cors_origins: vec!["https://bench.example.com".to_string()],
+            content_security_policy: None,
+            hsts_max_age_secs: Some(31536000),
+        };
+
+        let allowed = response(&security, "https://bench.example.com").await;
+        let headers = allowed.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://bench.example.com"
+        );
+        assert!(headers.get(header::CONTENT_SECURITY_POLICY).is_none());
+        assert_eq!(
+            headers[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000; includeSubDomains"
+        );
+
+        let denied = response(&security, "https://evil.example.com").await;
+        assert!(denied
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let security = HttpSecurity {
+            content_security_policy: Some("default-src\n'self'".to_string()),
+            ..HttpSecurity::default()
+        };
+        assert!(security.headers().is_err());
+    }
+}
@@ -0,0 +1,756 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, FixedOffset};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::cache::AppCache;
+use crate::entities::{
+    self, benchmark, branch, branch_alias, expected_benchmark, measure, metric, report,
+    report_context, report_tag, testbed, testbed_alias,
+};
+use crate::jobs::{self, JobRegistry};
+use crate::{evaluation, events, export, issues, pr_checks, scaling, staleness, summary};
+
+/// Job kind that evaluates a submitted report in the background.
+pub const EVALUATE_REPORT_JOB: &str = "evaluate_report";
+
+/// Job kind that re-checks later alerts after a report was excluded from or
+/// restored to baselines.
+pub const REEVALUATE_BASELINES_JOB: &str = "reevaluate_baselines";
+
+pub struct NewMetric {
+    pub benchmark: String,
+    pub measure: String,
+    pub value: f64,
+    pub lower_value: Option<f64>,
+    pub upper_value: Option<f64>,
+    pub reported_change: Option<f64>,
+    pub outliers: Option<i32>,
+}
+
+pub struct NewReport {
+    /// Chosen by the submitter; generated when `None`
+    pub id: Option<Uuid>,
+    pub project_id: Uuid,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub pr_number: Option<i32>,
+    pub commit_message: Option<String>,
+    pub commit_author: Option<String>,
+    pub committed_at: Option<DateTime<FixedOffset>>,
+    pub base_branch: Option<String>,
+    pub merge_base_hash: Option<String>,
+    pub evaluated_commit: Option<String>,
+    pub version: Option<String>,
+    pub kind: report::ReportKind,
+    pub exit_code: Option<i32>,
+    pub partial: bool,
+    pub created_at: DateTime<FixedOffset>,
+    /// Validated key/values, see `context::validate_context`
+    pub context: Vec<(String, String)>,
+    /// Validated, see `report_filters::validate_tags`
+    pub tags: Vec<String>,
+    /// The suite manifest, validated, see
+    /// `manifests::validate_expected_benchmarks`; empty without one
+    pub expected_benchmarks: Vec<String>,
+}
+
+/// Rows per INSERT, well below Postgres' bind parameter limit.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Names per lookup, so even a large suite's benchmarks are found with one
+/// query while staying below Postgres' bind parameter limit.
+const LOOKUP_CHUNK_SIZE: usize = 30_000;
+
+/// The branches, testbeds, benchmarks and measures one request has resolved,
+/// so each name is looked up once however many metrics or reports the
+/// request stores. Scoped to a request: ids of rows created in a
+/// transaction that rolled back would be stale.
+#[derive(Default)]
+pub struct Dimensions {
+    branches: HashMap<(Uuid, String), branch::Model>,
+    testbeds: HashMap<(Uuid, String), testbed::Model>,
+    benchmarks: HashMap<(Uuid, String), Uuid>,
+    measures: HashMap<(Uuid, String), Uuid>,
+}
+
+impl Dimensions {
+    /// [`get_or_create_branch`], remembering the branch the name resolved to
+    pub async fn branch<C: ConnectionTrait>(
+        &mut self,
+        db: &C,
+        project_id: Uuid,
+        name: &str,
+    ) -> Result<branch::Model, DbErr> {
+        let key = (project_id, name.to_string());
+        if let Some(branch) = self.branches.get(&key) {
+            return Ok(branch.clone());
+        }
+        let branch = get_or_create_branch(db, project_id, name).await?;
+        self.branches.insert(key, branch.clone());
+        Ok(branch)
+    }
+
+    /// [`get_or_create_testbed`], remembering the testbed the name resolved to
+    pub async fn testbed<C: ConnectionTrait>(
+        &mut self,
+        db: &C,
+        project_id: Uuid,
+        name: &str,
+    ) -> Result<testbed::Model, DbErr> {
+        let key = (project_id, name.to_string());
+        if let Some(testbed) = self.testbeds.get(&key) {
+            return Ok(testbed.clone());
+        }
+        let testbed = get_or_create_testbed(db, project_id, name).await?;
+        self.testbeds.insert(key, testbed.clone());
+        Ok(testbed)
+    }
+
+    /// [`get_or_create_benchmarks`] for the names not resolved before
+    pub async fn benchmarks<C: ConnectionTrait>(
+        &mut self,
+        db: &C,
+        project_id: Uuid,
+        names: &BTreeSet<&str>,
+    ) -> Result<HashMap<String, Uuid>, DbErr> {
+        let unknown = unresolved(&self.benchmarks, project_id, names);
+        if !unknown.is_empty() {
+            let found = get_or_create_benchmarks(db, project_id, &unknown).await?;
+            self.benchmarks
+                .extend(found.into_iter().map(|(name, id)| ((project_id, name), id)));
+        }
+        Ok(resolved(&self.benchmarks, project_id, names))
+    }
+
+    /// [`get_or_create_measures`] for the names not resolved before
+    pub async fn measures<C: ConnectionTrait>(
+        &mut self,
+        db: &C,
+        project_id: Uuid,
+        names: &BTreeSet<&str>,
+    ) -> Result<HashMap<String, Uuid>, DbErr> {
+        let unknown = unresolved(&self.measures, project_id, names);
+        if !unknown.is_empty() {
+            let found = get_or_create_measures(db, project_id, &unknown).await?;
+            self.measures
+                .extend(found.into_iter().map(|(name, id)| ((project_id, name), id)));
+        }
+        Ok(resolved(&self.measures, project_id, names))
+    }
+}
+
+fn unresolved<'a>(
+    ids: &HashMap<(Uuid, String), Uuid>,
+    project_id: Uuid,
+    names: &BTreeSet<&'a str>,
+) -> BTreeSet<&'a str> {
+    names
+        .iter()
+        .copied()
+        .filter(|name| !ids.contains_key(&(project_id, name.to_string())))
+        .collect()
+}
+
+fn resolved(
+    ids: &HashMap<(Uuid, String), Uuid>,
+    project_id: Uuid,
+    names: &BTreeSet<&str>,
+) -> HashMap<String, Uuid> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let id = ids.get(&(project_id, name.to_string()))?;
+            Some((name.to_string(), *id))
+        })
+        .collect()
+}
+
+pub async fn insert_report<C: ConnectionTrait>(
+    db: &C,
+    dimensions: &mut Dimensions,
+    input: NewReport,
+    metrics: Vec<NewMetric>,
+) -> Result<(report::Model, Vec<metric::Model>), DbErr> {
+    let report = open_report(db, dimensions, input, true).await?;
+    let metrics = append_metrics(db, dimensions, &report, metrics).await?;
+    Ok((report, metrics))
+}
+
+/// Inserts the report row. Chunked uploads open it unfinalized and append
+/// metrics in batches before finalizing.
+pub async fn open_report<C: ConnectionTrait>(
+    db: &C,
+    dimensions: &mut Dimensions,
+    input: NewReport,
+    finalized: bool,
+) -> Result<report::Model, DbErr> {
+    let branch = dimensions
+        .branch(db, input.project_id, &input.branch)
+        .await?;
+    let testbed = dimensions
+        .testbed(db, input.project_id, &input.testbed)
+        .await?;
+    // Compared after resolving, so `HEAD` or an alias of the report's own
+    // branch doesn't count as a base branch
+    let base_branch = match input.base_branch.as_deref() {
+        Some(name) if name != input.branch => {
+            Some(dimensions.branch(db, input.project_id, name).await?)
+                .filter(|base| base.id != branch.id)
+        }
+        _ => None,
+    };
+
+    let report = report::ActiveModel {
+        id: Set(input.id.unwrap_or_else(Uuid::new_v4)),
+        project_id: Set(input.project_id),
+        branch_id: Set(branch.id),
+        testbed_id: Set(testbed.id),
+        git_hash: Set(input.git_hash),
+        pr_number: Set(input.pr_number),
+        commit_message: Set(input.commit_message),
+        commit_author: Set(input.commit_author),
+        committed_at: Set(input.committed_at),
+        base_branch_id: Set(base_branch.map(|b| b.id)),
+        merge_base_hash: Set(input.merge_base_hash),
+        evaluated_commit: Set(input.evaluated_commit),
+        version: Set(input.version),
+        kind: Set(input.kind),
+        exit_code: Set(input.exit_code),
+        partial: Set(input.partial),
+        finalized: Set(finalized),
+        excluded: Set(false),
+        status: Set(report::ReportStatus::Pending),
+        created_at: Set(input.created_at),
+    }
+    .insert(db)
+    .await?;
+
+    if !input.context.is_empty() {
+        entities::ReportContext::insert_many(input.context.into_iter().map(|(key, value)| {
+            report_context::ActiveModel {
+                report_id: Set(report.id),
+                key: Set(key),
+                value: Set(value),
+            }
+        }))
+        .exec(db)
+        .await?;
+    }
+    if !input.tags.is_empty() {
+        entities::ReportTag::insert_many(input.tags.into_iter().map(|tag| {
+            report_tag::ActiveModel {
+                report_id: Set(report.id),
+                tag: Set(tag),
+            }
+        }))
+        .exec(db)
+        .await?;
+    }
+    for names in input.expected_benchmarks.chunks(INSERT_CHUNK_SIZE) {
+        entities::ExpectedBenchmark::insert_many(names.iter().map(|name| {
+            expected_benchmark::ActiveModel {
+                report_id: Set(report.id),
+                name: Set(name.clone()),
+            }
+        }))
+        .exec(db)
+        .await?;
+    }
+
+    Ok(report)
+}
+
+pub async fn append_metrics<C: ConnectionTrait>(
+    db: &C,
+    dimensions: &mut Dimensions,
+    report: &report::Model,
+    input: Vec<NewMetric>,
+) -> Result<Vec<metric::Model>, DbErr> {
+    let benchmarks: BTreeSet<&str> = input.iter().map(|m| m.benchmark.as_str()).collect();
+    let measures: BTreeSet<&str> = input.iter().map(|m| m.measure.as_str()).collect();
+    let benchmark_ids = dimensions
+        .benchmarks(db, report.project_id, &benchmarks)
+        .await?;
+    let measure_ids = dimensions
+        .measures(db, report.project_id, &measures)
+        .await?;
+    let mut metrics = Vec::with_capacity(input.len());
+
+    for m in &input {
+        metrics.push(metric::Model {
+            id: Uuid::new_v4(),
+            report_id: report.id,
+            benchmark_id: benchmark_ids[&m.benchmark],
+            measure_id: measure_ids[&m.measure],
+            value: m.value,
+            lower: m.lower_value,
+            upper: m.upper_value,
+            reported_change: m.reported_change,
+            outliers: m.outliers,
+            created_at: report.created_at,
+        });
+    }
+
+    for chunk in metrics.chunks(INSERT_CHUNK_SIZE) {
+        entities::Metric::insert_many(chunk.iter().map(|m| metric::ActiveModel {
+            id: Set(m.id),
+            report_id: Set(m.report_id),
+            benchmark_id: Set(m.benchmark_id),
+            measure_id: Set(m.measure_id),
+            value: Set(m.value),
+            lower: Set(m.lower),
+            upper: Set(m.upper),
+            reported_change: Set(m.reported_change),
+            outliers: Set(m.outliers),
+            created_at: Set(m.created_at),
+        }))
+        .exec(db)
+        .await?;
+    }
+
+    Ok(metrics)
+}
+
+/// Runs everything that depends on a complete report: alert evaluation,
+/// issue escalation, stale-data resolution and summary maintenance.
+pub async fn process_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<(), DbErr> {
+    let alerts = evaluation::evaluate_report(db, report, metrics).await?;
+    issues::queue_for_persistent_alerts(db, report, &alerts).await?;
+    pr_checks::queue_for_report(db, report).await?;
+    pr_checks::queue_for_evaluated_commit(db, report, alerts.len()).await?;
+    staleness::resolve_for_report(db, report).await?;
+    summary::update_summaries(db, report, metrics).await?;
+    Ok(())
+}
+
+/// Queues alert evaluation for a report. Call inside the transaction that
+/// stores the report so the job can never see it half-written.
+pub async fn enqueue_evaluation<C: ConnectionTrait>(db: &C, report_id: Uuid) -> Result<(), DbErr> {
+    jobs::enqueue(
+        db,
+        EVALUATE_REPORT_JOB,
+        serde_json::json!({ "report_id": report_id }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Queues re-evaluation of the alerts whose baselines depend on a report.
+pub async fn enqueue_baseline_reevaluation<C: ConnectionTrait>(
+    db: &C,
+    report_id: Uuid,
+) -> Result<(), DbErr> {
+    jobs::enqueue(
+        db,
+        REEVALUATE_BASELINES_JOB,
+        serde_json::json!({ "report_id": report_id }),
+    )
+    .await?;
+    Ok(())
+}
+
+pub fn register_jobs(registry: &mut JobRegistry, cache: AppCache) {
+    registry.register(EVALUATE_REPORT_JOB, move |db, payload| {
+        let cache = cache.clone();
+        async move {
+            let report_id: Uuid = serde_json::from_value(payload["report_id"].clone())?;
+            if let Some(report) = evaluate_pending_report(&db, report_id).await? {
+                cache.invalidate_latest_reports(report.project_id).await;
+            }
+            Ok(())
+        }
+    });
+
+    registry.register(REEVALUATE_BASELINES_JOB, |db, payload| async move {
+        let report_id: Uuid = serde_json::from_value(payload["report_id"].clone())?;
+        let Some(report) = entities::Report::find_by_id(report_id).one(&db).await? else {
+            return Ok(());
+        };
+        let txn = db.begin().await?;
+        let updated = evaluation::reevaluate_later_reports(&txn, &report).await?;
+        txn.commit().await?;
+        tracing::info!(
+            "Re-evaluated {} alerts after baseline change for report {}",
+            updated,
+            report_id
+        );
+        Ok(())
+    });
+}
+
+/// Runs [`process_report`] for a pending report and marks it evaluated.
+/// Returns `None` when the report is gone or was already evaluated.
+pub async fn evaluate_pending_report(
+    db: &DatabaseConnection,
+    report_id: Uuid,
+) -> Result<Option<report::Model>, DbErr> {
+    let txn = db.begin().await?;
+    let Some(report) = entities::Report::find_by_id(report_id).one(&txn).await? else {
+        return Ok(None);
+    };
+    if report.status == report::ReportStatus::Evaluated {
+        return Ok(None);
+    }
+
+    let metrics = entities::Metric::find()
+        .filter(metric::Column::ReportId.eq(report.id))
+        .all(&txn)
+        .await?;
+    process_report(&txn, &report, &metrics).await?;
+
+    let mut active: report::ActiveModel = report.into();
+    active.status = Set(report::ReportStatus::Evaluated);
+    let report = active.update(&txn).await?;
+    if export::sink().is_some() {
+        export::queue_report(&txn, report.id).await?;
+    }
+    events::report_created(&txn, report.id).await?;
+    txn.commit().await?;
+
+    Ok(Some(report))
+}
+
+/// Branch name that stands for the project's default branch
+pub const HEAD_BRANCH: &str = "HEAD";
+
+/// Default branch of new projects
+pub const DEFAULT_BRANCH: &str = "main";
+
+/// Checks a name given as a project's default branch or an alias target,
+/// returning it trimmed.
+pub fn check_branch_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Branch name must not be empty".to_string());
+    }
+    if name == HEAD_BRANCH {
+        return Err(format!("{} always means the default branch", HEAD_BRANCH));
+    }
+    Ok(name)
+}
+
+/// The branch a name submitted for a project refers to: `HEAD` means the
+/// project's default branch, and an alias the branch it was added for.
+/// `None` when no such branch exists yet.
+pub async fn find_branch<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<Option<branch::Model>, DbErr> {
+    let name = branch_name(db, project_id, name).await?;
+    if let Some(aliased) = entities::Branch::find()
+        .inner_join(entities::BranchAlias)
+        .filter(branch_alias::Column::ProjectId.eq(project_id))
+        .filter(branch_alias::Column::Alias.eq(name.as_str()))
+        .one(db)
+        .await?
+    {
+        return Ok(Some(aliased));
+    }
+
+    entities::Branch::find()
+        .filter(branch::Column::ProjectId.eq(project_id))
+        .filter(branch::Column::Name.eq(name.as_str()))
+        .one(db)
+        .await
+}
+
+/// `name`, or the project's default branch for `HEAD`
+async fn branch_name<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<String, DbErr> {
+    if name != HEAD_BRANCH {
+        return Ok(name.to_string());
+    }
+    let project = entities::Project::find_by_id(project_id)
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("Project {}", project_id)))?;
+    Ok(project.default_branch)
+}
+
+/// Resolves `name` like `find_branch`, creating the branch when it doesn't
+/// exist. A concurrent submission creating the same branch isn't an error:
+/// the insert waits for it and the branch it created is returned.
+pub async fn get_or_create_branch<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<branch::Model, DbErr> {
+    if let Some(existing) = find_branch(db, project_id, name).await? {
+        return Ok(existing);
+    }
+
+    let name = branch_name(db, project_id, name).await?;
+    let now = chrono::Utc::now().fixed_offset();
+    entities::Branch::insert(branch::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        project_id: Set(project_id),
+        name: Set(name.clone()),
+        created_at: Set(now),
+        updated_at: Set(now),
+    })
+    .on_conflict(
+        OnConflict::columns([branch::Column::ProjectId, branch::Column::Name])
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await?;
+
+    entities::Branch::find()
+        .filter(branch::Column::ProjectId.eq(project_id))
+        .filter(branch::Column::Name.eq(name.as_str()))
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("Branch {}", name)))
+}
+
+/// Checks a name given as a testbed alias or its target, returning it
+/// trimmed.
+pub fn check_testbed_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Testbed name must not be empty".to_string());
+    }
+    Ok(name)
+}
+
+/// The testbed a name submitted for a project refers to: an alias means the
+/// testbed it was added for. `None` when no such testbed exists yet.
+pub async fn find_testbed<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<Option<testbed::Model>, DbErr> {
+    if let Some(aliased) = entities::Testbed::find()
+        .inner_join(entities::TestbedAlias)
+        .filter(testbed_alias::Column::ProjectId.eq(project_id))
+        .filter(testbed_alias::Column::Alias.eq(name))
+        .one(db)
+        .await?
+    {
+        return Ok(Some(aliased));
+    }
+
+    entities::Testbed::find()
+        .filter(testbed::Column::ProjectId.eq(project_id))
+        .filter(testbed::Column::Name.eq(name))
+        .one(db)
+        .await
+}
+
+/// Like [`get_or_create_branch`], for testbeds.
+pub async fn get_or_create_testbed<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<testbed::Model, DbErr> {
+    if let Some(existing) = find_testbed(db, project_id, name).await? {
+        return Ok(existing);
+    }
+
+    let now = chrono::Utc::now().fixed_offset();
+    entities::Testbed::insert(testbed::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        project_id: Set(project_id),
+        name: Set(name.to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+    })
+    .on_conflict(
+        OnConflict::columns([testbed::Column::ProjectId, testbed::Column::Name])
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await?;
+
+    entities::Testbed::find()
+        .filter(testbed::Column::ProjectId.eq(project_id))
+        .filter(testbed::Column::Name.eq(name))
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("Testbed {}", name)))
+}
+
+/// Like [`get_or_create_branch`], for measures.
+pub async fn get_or_create_measure<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<measure::Model, DbErr> {
+    let names = BTreeSet::from([name]);
+    let id = get_or_create_measures(db, project_id, &names).await?[name];
+    entities::Measure::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| DbErr::RecordNotFound(format!("Measure {}", name)))
+}
+
+/// Ids of the project's benchmarks called `names`, one for every name,
+/// creating the missing ones. New benchmarks are inserted in name order, so
+/// concurrent submissions of the same new benchmarks take their locks in the
+/// same order rather than deadlocking, and one that loses the race uses the
+/// benchmarks the other created.
+pub async fn get_or_create_benchmarks<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    names: &BTreeSet<&str>,
+) -> Result<HashMap<String, Uuid>, DbErr> {
+    let find = |names: Vec<&str>| {
+        entities::Benchmark::find()
+            .filter(benchmark::Column::ProjectId.eq(project_id))
+            .filter(benchmark::Column::Name.is_in(names))
+    };
+    let mut ids = HashMap::with_capacity(names.len());
+    let all: Vec<&str> = names.iter().copied().collect();
+    for chunk in all.chunks(LOOKUP_CHUNK_SIZE) {
+        ids.extend(
+            find(chunk.to_vec())
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|b| (b.name, b.id)),
+        );
+    }
+
+    let missing: Vec<&str> = all
+        .into_iter()
+        .filter(|name| !ids.contains_key(*name))
+        .collect();
+    let now = chrono::Utc::now().fixed_offset();
+    for chunk in missing.chunks(INSERT_CHUNK_SIZE) {
+        entities::Benchmark::insert_many(chunk.iter().map(|name| {
+            let (base_name, parameter) = scaling::split_parameter(name).unzip();
+            benchmark::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                project_id: Set(project_id),
+                name: Set(name.to_string()),
+                base_name: Set(base_name.map(str::to_string)),
+                parameter: Set(parameter),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+        }))
+        .on_conflict(
+            OnConflict::columns([benchmark::Column::ProjectId, benchmark::Column::Name])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
+        .await?;
+    }
+    for chunk in missing.chunks(LOOKUP_CHUNK_SIZE) {
+        ids.extend(
+            find(chunk.to_vec())
+                .all(db)
+                .await?
+                .into_iter()
+                .map(|b| (b.name, b.id)),
+        );
+    }
+
+    match missing.into_iter().find(|name| !ids.contains_key(*name)) {
+        Some(name) => Err(DbErr::RecordNotFound(format!("Benchmark {}", name))),
+        None => Ok(ids),
+    }
+}
+
+/// Like [`get_or_create_benchmarks`], for measures.
+pub async fn get_or_create_measures<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    names: &BTreeSet<&str>,
+) -> Result<HashMap<String, Uuid>, DbErr> {
+    let find = |names: Vec<&str>| {
+        entities::Measure::find()
+            .filter(measure::Column::ProjectId.eq(project_id))
+            .filter(measure::Column::Name.is_in(names))
+    };
+    let mut ids: HashMap<String, Uuid> = find(names.iter().copied().collect())
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|m| (m.name, m.id))
+        .collect();
+
+    let missing: Vec<&str> = names
+        .iter()
+        .copied()
+        .filter(|name| !ids.contains_key(*name))
+        .collect();
+    if missing.is_empty() {
+        return Ok(ids);
+    }
+    let now = chrono::Utc::now().fixed_offset();
+    entities::Measure::insert_many(missing.iter().map(|name| measure::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        project_id: Set(project_id),
+        name: Set(name.to_string()),
+        units: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }))
+    .on_conflict(
+        OnConflict::columns([measure::Column::ProjectId, measure::Column::Name])
+            .do_nothing()
+            .to_owned(),
+    )
+    .do_nothing()
+    .exec(db)
+    .await?;
+    ids.extend(
+        find(missing.clone())
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| (m.name, m.id)),
+    );
+
+    match missing.into_iter().find(|name| !ids.contains_key(*name)) {
+        Some(name) => Err(DbErr::RecordNotFound(format!("Measure {}", name))),
+        None => Ok(ids),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dimension_lookups_skip_resolved_names() {
+        let project = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let parse = Uuid::new_v4();
+        let ids = HashMap::from([((project, "parse".to_string()), parse)]);
+        let names = BTreeSet::from(["parse", "render"]);
+
+        assert_eq!(
+            unresolved(&ids, project, &names),
+            BTreeSet::from(["render"])
+        );
+        // Names are per project
+        assert_eq!(unresolved(&ids, other, &names), names);
+        assert_eq!(
+            resolved(&ids, project, &names),
+            HashMap::from([("parse".to_string(), parse)])
+        );
+    }
+}
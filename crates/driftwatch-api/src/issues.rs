@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use sea_orm::prelude::Expr;
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::entities::{self, alert, project, report};
+use crate::evaluation::{alert_streak, CLOSED_STATUSES};
+use crate::github::{self, GitHubClient};
+use crate::jobs::{self, JobRegistry};
+use crate::links;
+use crate::{error_reports, events};
+
+/// Job kind that opens (or links) a GitHub issue for a persistent alert.
+pub const OPEN_ISSUE_JOB: &str = "open_alert_issue";
+
+/// How often linked issues are checked for having been closed on GitHub.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Job kind of the periodic issue state sync.
+pub const SYNC_ISSUES_JOB: &str = "sync_issue_states";
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(OPEN_ISSUE_JOB, |db, payload| async move {
+        let alert_id: Uuid = serde_json::from_value(payload["alert_id"].clone())?;
+        open_issue(&db, alert_id).await
+    });
+
+    registry.schedule(SYNC_ISSUES_JOB, SYNC_INTERVAL, |db| async move {
+        match sync_issue_states(&db).await {
+            Ok(resolved) if resolved > 0 => {
+                tracing::info!("Resolved {} alerts from closed GitHub issues", resolved);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!("GitHub issue sync failed: {:#}", e);
+                error_reports::capture(&e, "issues", &[]);
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Queues an issue job for every new alert that has now fired on the
+/// project's configured number of consecutive reports.
+pub async fn queue_for_persistent_alerts<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    alerts: &[alert::Model],
+) -> Result<(), DbErr> {
+    if alerts.is_empty() {
+        return Ok(());
+    }
+
+    let Some(project) = entities::Project::find_by_id(report.project_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+    let Some(after) = issue_settings(&project).map(|(_, _, after)| after) else {
+        return Ok(());
+    };
+
+    for alert in alerts {
+        if alert.status == alert::AlertStatus::Unconfirmed {
+            continue;
+        }
+        if alert_streak(db, alert, after).await?.is_some() {
+            jobs::enqueue(
+                db,
+                OPEN_ISSUE_JOB,
+                serde_json::json!({ "alert_id": alert.id }),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Repository, token and streak length when issue creation is enabled.
+fn issue_settings(project: &project::Model) -> Option<(&str, String, u64)> {
+    let repo = project.github_repo.as_deref()?;
+    let after = project.github_issue_after_reports.filter(|n| *n > 0)?;
+    let token = github::project_token(project)?;
+    Some((repo, token, after as u64))
+}
+
+/// Links the alert's streak to an issue already opened for it, or opens a
+/// new one.
+pub async fn open_issue(db: &DatabaseConnection, alert_id: Uuid) -> anyhow::Result<()> {
+    let Some((alert, Some(threshold))) = entities::Alert::find_by_id(alert_id)
+        .find_also_related(entities::Threshold)
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+    if alert.github_issue_number.is_some() {
+        return Ok(());
+    }
+
+    let Some(project) = entities::Project::find_by_id(threshold.project_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+    let Some((repo, token, after)) = issue_settings(&project) else {
+        return Ok(());
+    };
+    let Some(streak) = alert_streak(db, &alert, after).await? else {
+        return Ok(());
+    };
+
+    let existing = streak
+        .iter()
+        .find_map(|a| a.github_issue_number.zip(a.github_issue_url.clone()));
+    let (number, url) = match existing {
+        Some(issue) => issue,
+        None => {
+            let (title, body) = describe(db, &project, &alert, after).await?;
+            let issue = GitHubClient::new(&token)
+                .create_issue(repo, &title, &body)
+                .await?;
+            tracing::info!(
+                "Opened GitHub issue {} for alert {}",
+                issue.html_url,
+                alert.id
+            );
+            (issue.number, issue.html_url)
+        }
+    };
+
+    let ids: Vec<Uuid> = streak.iter().map(|a| a.id).collect();
+    entities::Alert::update_many()
+        .col_expr(alert::Column::GithubIssueNumber, Expr::value(number))
+        .col_expr(alert::Column::GithubIssueUrl, Expr::value(url))
+        .filter(alert::Column::Id.is_in(ids))
+        .filter(alert::Column::GithubIssueNumber.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+async fn describe(
+    db: &DatabaseConnection,
+    project: &project::Model,
+    alert: &alert::Model,
+    after: u64,
+) -> anyhow::Result<(String, String)> {
+    let (metric, report) = entities::Metric::find_by_id(alert.metric_id)
+        .find_also_related(entities::Report)
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Metric {} not found", alert.metric_id))?;
+    let report = report.ok_or_else(|| anyhow::anyhow!("Report not found"))?;
+
+    let benchmark = entities::Benchmark::find_by_id(metric.benchmark_id)
+        .one(db)
+        .await?
+        .map(|b| b.name)
+        .unwrap_or_default();
+    let branch = entities::Branch::find_by_id(report.branch_id)
+        .one(db)
+        .await?
+        .map(|b| b.name)
+        .unwrap_or_default();
+    let testbed = entities::Testbed::find_by_id(report.testbed_id)
+        .one(db)
+        .await?
+        .map(|t| t.name)
+        .unwrap_or_default();
+
+    let title = format!(
+        "Performance regression: {} ({:+.1}%)",
+        benchmark, alert.percent_change
+    );
+    let mut body = format!(
+        "`{}` has regressed on `{}` / `{}` for {} consecutive reports.\n\n\
+         | Baseline | Current | Change |\n|---|---|---|\n| {:.2} | {:.2} | {:+.1}% |\n",
+        benchmark,
+        branch,
+        testbed,
+        after,
+        alert.baseline_value,
+        alert.current_value,
+        alert.percent_change
+    );
+    if let Some(hash) = &report.git_hash {
+        body.push_str(&format!("\nLatest commit: {}\n", hash));
+    }
+    if let (Some(alert_url), Some(report_url)) = (
+        links::alert(&project.slug, alert.id),
+        links::report(&project.slug, report.id),
+    ) {
+        body.push_str(&format!(
+            "\n[View the alert]({}) · [Latest report]({})\n",
+            alert_url, report_url
+        ));
+    }
+    body.push_str("\nClosing this issue resolves the Driftwatch alerts linked to it.\n");
+
+    Ok((title, body))
+}
+
+/// Resolves open alerts whose linked GitHub issue has been closed. Returns
+/// how many alerts were resolved.
+pub async fn sync_issue_states(db: &DatabaseConnection) -> anyhow::Result<u64> {
+    let linked = entities::Alert::find()
+        .find_also_related(entities::Threshold)
+        .filter(alert::Column::GithubIssueNumber.is_not_null())
+        .filter(alert::Column::Status.is_not_in(CLOSED_STATUSES))
+        .all(db)
+        .await?;
+
+    let mut by_issue: HashMap<(Uuid, i32), Vec<Uuid>> = HashMap::new();
+    for (alert, threshold) in linked {
+        if let (Some(number), Some(threshold)) = (alert.github_issue_number, threshold) {
+            by_issue
+                .entry((threshold.project_id, number))
+                .or_default()
+                .push(alert.id);
+        }
+    }
+
+    let project_ids: Vec<Uuid> = by_issue.keys().map(|(project_id, _)| *project_id).collect();
+    let projects: HashMap<Uuid, project::Model> = entities::Project::find()
+        .filter(project::Column::Id.is_in(project_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+    let mut resolved = 0;
+
+    for ((project_id, number), alert_ids) in by_issue {
+        let Some(project) = projects.get(&project_id) else {
+            continue;
+        };
+        let (Some(repo), Some(token)) = (&project.github_repo, github::project_token(project))
+        else {
+            continue;
+        };
+
+        let issue = match GitHubClient::new(&token).issue(repo, number).await {
+            Ok(issue) => issue,
+            Err(e) => {
+                tracing::warn!("Failed to fetch {}#{}: {:#}", repo, number, e);
+                continue;
+            }
+        };
+        if issue.state != "closed" {
+            continue;
+        }
+
+        let result = entities::Alert::update_many()
+            .col_expr(
+                alert::Column::Status,
+                Expr::value(alert::AlertStatus::Resolved),
+            )
+            .col_expr(
+                alert::Column::UpdatedAt,
+                Expr::value(chrono::Utc::now().fixed_offset()),
+            )
+            .filter(alert::Column::Id.is_in(alert_ids.clone()))
+            .exec(db)
+            .await?;
+        resolved += result.rows_affected;
+        for id in alert_ids {
+            events::alert_resolved(db, id).await?;
+        }
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(repo: Option<&str>, token: Option<&str>, after: Option<i32>) -> project::Model {
+        let now = chrono::Utc::now().fixed_offset();
+        project::Model {
+            id: Uuid::new_v4(),
+            user_id: Some(Uuid::new_v4()),
+            slug: "p".to_string(),
+            name: "P".to_string(),
+            description: None,
+            public: false,
+            default_branch: "main".to_string(),
+            github_repo: repo.map(String::from),
+            github_token: token.map(String::from),
+            github_pr_comments: false,
+            github_status_checks: false,
+            expected_cadence_hours: None,
+            stale_branches: None,
+            github_issue_after_reports: after,
+            alert_after_reports: None,
+            noise_cv_limit: None,
+            noise_action: project::NoiseAction::Flag,
+            benchmark_required_paths: None,
+            redact_secrets: true,
+            alert_feed_token_hash: None,
+            expires_at: None,
+            share_token_hash: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_issue_settings_requires_repo_token_and_streak() {
+        let enabled = project(Some("o/r"), Some("t"), Some(3));
+        assert_eq!(issue_settings(&enabled), Some(("o/r", "t".to_string(), 3)));
+
+        assert!(issue_settings(&project(None, Some("t"), Some(3))).is_none());
+        assert!(issue_settings(&project(Some("o/r"), None, Some(3))).is_none());
+        assert!(issue_settings(&project(Some("o/r"), Some("t"), None)).is_none());
+        assert!(issue_settings(&project(Some("o/r"), Some("t"), Some(0))).is_none());
+    }
+}
@@ -0,0 +1,498 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset};
+use futures_util::FutureExt;
+use sea_orm::prelude::Expr;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, QueryFilter, Set, Statement,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, job};
+use crate::error_reports;
+
+/// Attempts before a job is moved to the dead-letter state.
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+
+/// How long an idle worker waits before polling the queue again.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Jobs locked for longer than this are assumed to belong to a crashed
+/// worker and are put back in the queue, or dead-lettered if that was their
+/// last attempt.
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// How often a running job renews its lock, so a job that takes longer
+/// than `LOCK_TIMEOUT` isn't taken for one whose worker crashed.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(3 * 60);
+
+/// How often each instance checks whether a scheduled job is due.
+pub const SCHEDULER_INTERVAL: Duration = Duration::from_secs(10);
+
+pub type JobFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+type Handler = Arc<dyn Fn(DatabaseConnection, serde_json::Value) -> JobFuture + Send + Sync>;
+
+/// Maps job kinds to the functions that execute them.
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    handlers: HashMap<&'static str, Handler>,
+    schedules: Vec<(&'static str, Duration)>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<F, Fut>(&mut self, kind: &'static str, handler: F)
+    where
+        F: Fn(DatabaseConnection, serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.handlers.insert(
+            kind,
+            Arc::new(move |db, payload| Box::pin(handler(db, payload))),
+        );
+    }
+
+    /// Registers `handler` as periodic work queued every `every`. However
+    /// many instances are running, each interval queues it once, and not
+    /// while the previous run is still queued or running.
+    pub fn schedule<F, Fut>(&mut self, kind: &'static str, every: Duration, handler: F)
+    where
+        F: Fn(DatabaseConnection) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.register(kind, move |db, _payload| handler(db));
+        self.schedules.push((kind, every));
+    }
+
+    fn get(&self, kind: &str) -> Option<&Handler> {
+        self.handlers.get(kind)
+    }
+}
+
+/// Queues a job to run as soon as a worker is free.
+pub async fn enqueue<C: ConnectionTrait>(
+    db: &C,
+    kind: &str,
+    payload: serde_json::Value,
+) -> Result<job::Model, DbErr> {
+    enqueue_at(db, kind, payload, chrono::Utc::now().fixed_offset()).await
+}
+
+/// Queues a job to run no earlier than `run_at`.
+pub async fn enqueue_at<C: ConnectionTrait>(
+    db: &C,
+    kind: &str,
+    payload: serde_json::Value,
+    run_at: DateTime<FixedOffset>,
+) -> Result<job::Model, DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    job::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        kind: Set(kind.to_string()),
+        payload: Set(payload),
+        status: Set(job::JobStatus::Pending),
+        attempts: Set(0),
+        max_attempts: Set(DEFAULT_MAX_ATTEMPTS),
+        run_at: Set(run_at),
+        locked_at: Set(None),
+        last_error: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+}
+
+/// Queues a run of the scheduled job `kind` if its interval has come
+/// round. Claiming the `job_schedules` row and queueing the job happen in
+/// one statement, so two instances can't both queue the same interval.
+/// Returns whether it queued one.
+pub async fn enqueue_scheduled<C: ConnectionTrait>(
+    db: &C,
+    kind: &str,
+    every: Duration,
+) -> Result<bool, DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    let next_run_at = now + chrono::Duration::from_std(every).unwrap_or_default();
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"WITH due AS (
+                   INSERT INTO job_schedules (kind, next_run_at) VALUES ($1, $2)
+                   ON CONFLICT (kind) DO UPDATE SET next_run_at = EXCLUDED.next_run_at
+                   WHERE job_schedules.next_run_at <= $3
+                   RETURNING kind
+               )
+               INSERT INTO jobs (
+                   id, kind, payload, status, attempts, max_attempts, run_at,
+                   created_at, updated_at
+               )
+               SELECT $4, due.kind, '{}'::jsonb, 'pending', 0, $5, $3, $3, $3 FROM due
+               WHERE NOT EXISTS (
+                   SELECT 1 FROM jobs
+                   WHERE jobs.kind = due.kind AND jobs.status IN ('pending', 'running')
+               )"#,
+            [
+                kind.into(),
+                next_run_at.into(),
+                now.into(),
+                Uuid::new_v4().into(),
+                DEFAULT_MAX_ATTEMPTS.into(),
+            ],
+        ))
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Why a job couldn't be put back in the queue
+#[derive(Debug)]
+pub enum RetryError {
+    NotFound,
+    /// The job is still queued, running or done, so retrying it would run
+    /// it twice
+    NotDead,
+    Db(DbErr),
+}
+
+impl fmt::Display for RetryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Job not found"),
+            Self::NotDead => write!(f, "Only dead jobs can be retried"),
+            Self::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RetryError {}
+
+impl From<DbErr> for RetryError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+/// Puts a dead job back in the queue with a fresh set of attempts.
+pub async fn retry<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<job::Model, RetryError> {
+    let now = chrono::Utc::now().fixed_offset();
+    let retried = entities::Job::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"UPDATE jobs
+               SET status = 'pending', attempts = 0, run_at = $1, locked_at = NULL,
+                   updated_at = $1
+               WHERE id = $2 AND status = 'dead'
+               RETURNING *"#,
+            [now.into(), id.into()],
+        ))
+        .one(db)
+        .await?;
+
+    match retried {
+        Some(job) => Ok(job),
+        None if entities::Job::find_by_id(id).one(db).await?.is_some() => Err(RetryError::NotDead),
+        None => Err(RetryError::NotFound),
+    }
+}
+
+/// Starts `workers` queue consumers, a task that queues scheduled jobs as
+/// they come due, and one that releases jobs held by crashed workers.
+pub fn start_workers(
+    db: DatabaseConnection,
+    registry: JobRegistry,
+    workers: usize,
+) -> Vec<tokio::task::JoinHandle<()>> {
+    let registry = Arc::new(registry);
+    let mut handles: Vec<_> = (0..workers)
+        .map(|_| tokio::spawn(run_worker(db.clone(), registry.clone())))
+        .collect();
+
+    let schedules = registry.schedules.clone();
+    let scheduler_db = db.clone();
+    handles.push(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+        loop {
+            interval.tick().await;
+            for &(kind, every) in &schedules {
+                if let Err(e) = enqueue_scheduled(&scheduler_db, kind, every).await {
+                    tracing::error!("Failed to queue scheduled job {}: {}", kind, e);
+                }
+            }
+        }
+    }));
+
+    handles.push(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(LOCK_TIMEOUT / 3);
+        loop {
+            interval.tick().await;
+            if let Err(e) = release_expired_locks(&db).await {
+                tracing::error!("Failed to release expired job locks: {}", e);
+            }
+        }
+    }));
+
+    handles
+}
+
+async fn run_worker(db: DatabaseConnection, registry: Arc<JobRegistry>) {
+    loop {
+        match claim_next(&db).await {
+            Ok(Some(job)) => {
+                if let Err(e) = execute(&db, &registry, job).await {
+                    tracing::error!("Failed to record job result: {}", e);
+                }
+            }
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(e) => {
+                tracing::error!("Failed to poll job queue: {}", e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// Locks the oldest due job; `SKIP LOCKED` lets several workers poll
+/// concurrently without handing out the same job twice.
+async fn claim_next(db: &DatabaseConnection) -> Result<Option<job::Model>, DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    entities::Job::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"UPDATE jobs
+               SET status = 'running', attempts = attempts + 1, locked_at = $1, updated_at = $1
+               WHERE id = (
+                   SELECT id FROM jobs
+                   WHERE status = 'pending' AND run_at <= $1
+                   ORDER BY run_at
+                   FOR UPDATE SKIP LOCKED
+                   LIMIT 1
+               )
+               RETURNING *"#,
+            [now.into()],
+        ))
+        .one(db)
+        .await
+}
+
+/// Renews the lock of a running job that still holds the one taken at
+/// `locked_at`, returning the new lock time, or `None` when the lock
+/// expired and the job went back to the queue.
+async fn renew_lock(
+    db: &DatabaseConnection,
+    id: Uuid,
+    locked_at: DateTime<FixedOffset>,
+) -> Result<Option<DateTime<FixedOffset>>, DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"UPDATE jobs SET locked_at = $1
+               WHERE id = $2 AND status = 'running' AND locked_at = $3
+               RETURNING locked_at"#,
+            [now.into(), id.into(), locked_at.into()],
+        ))
+        .await?;
+    row.map(|row| row.try_get("", "locked_at")).transpose()
+}
+
+async fn execute(
+    db: &DatabaseConnection,
+    registry: &JobRegistry,
+    job: job::Model,
+) -> Result<(), DbErr> {
+    let Some(mut locked_at) = job.locked_at else {
+        return Ok(());
+    };
+
+    // A panicking handler counts as a failed attempt instead of taking the
+    // worker down with the job still locked
+    let run = async {
+        match registry.get(&job.kind) {
+            Some(handler) => AssertUnwindSafe(handler(db.clone(), job.payload.clone()))
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|panic| {
+                    Err(anyhow::anyhow!("Job panicked: {}", panic_message(&*panic)))
+                }),
+            None => Err(anyhow::anyhow!(
+                "No handler registered for job kind '{}'",
+                job.kind
+            )),
+        }
+    };
+    tokio::pin!(run);
+
+    let mut heartbeat = tokio::time::interval_at(
+        tokio::time::Instant::now() + HEARTBEAT_INTERVAL,
+        HEARTBEAT_INTERVAL,
+    );
+    let result = loop {
+        tokio::select! {
+            result = &mut run => break result,
+            _ = heartbeat.tick() => match renew_lock(db, job.id, locked_at).await {
+                Ok(Some(renewed)) => locked_at = renewed,
+                // Another worker may be running it by now
+                Ok(None) => {
+                    tracing::warn!(
+                        "Job {} {} lost its lock; stopping this attempt",
+                        job.kind,
+                        job.id
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::error!("Failed to renew the lock of job {}: {}", job.id, e)
+                }
+            },
+        }
+    };
+
+    let now = chrono::Utc::now().fixed_offset();
+    let attempts = job.attempts;
+    let kind = job.kind.clone();
+    let job_id = job.id;
+    let update = entities::Job::update_many()
+        .col_expr(
+            job::Column::LockedAt,
+            Expr::value(Option::<DateTime<FixedOffset>>::None),
+        )
+        .col_expr(job::Column::UpdatedAt, Expr::value(now))
+        .filter(job::Column::Id.eq(job_id))
+        .filter(job::Column::Status.eq(job::JobStatus::Running))
+        .filter(job::Column::LockedAt.eq(locked_at));
+
+    let update = match result {
+        Ok(()) => update
+            .col_expr(job::Column::Status, Expr::value(job::JobStatus::Completed))
+            .col_expr(job::Column::LastError, Expr::value(Option::<String>::None)),
+        Err(e) if attempts >= job.max_attempts => {
+            tracing::error!("Job {} failed permanently: {:#}", kind, e);
+            error_reports::capture(
+                &e,
+                "job",
+                &[
+                    ("job_kind", kind.clone()),
+                    ("job_id", job_id.to_string()),
+                    ("attempts", attempts.to_string()),
+                ],
+            );
+            update
+                .col_expr(job::Column::Status, Expr::value(job::JobStatus::Dead))
+                .col_expr(
+                    job::Column::LastError,
+                    Expr::value(Some(format!("{:#}", e))),
+                )
+        }
+        Err(e) => {
+            tracing::warn!("Job {} failed (attempt {}): {:#}", kind, attempts, e);
+            update
+                .col_expr(job::Column::Status, Expr::value(job::JobStatus::Pending))
+                .col_expr(job::Column::RunAt, Expr::value(now + backoff(attempts)))
+                .col_expr(
+                    job::Column::LastError,
+                    Expr::value(Some(format!("{:#}", e))),
+                )
+        }
+    };
+
+    // The lock expired while the job ran and it was requeued, maybe already
+    // claimed again; that attempt owns the job now
+    if update.exec(db).await?.rows_affected == 0 {
+        tracing::warn!(
+            "Job {} {} lost its lock before finishing; its result was discarded",
+            kind,
+            job_id
+        );
+    }
+    Ok(())
+}
+
+async fn release_expired_locks(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    let cutoff = now - chrono::Duration::from_std(LOCK_TIMEOUT).unwrap_or_default();
+    let expired = || {
+        entities::Job::update_many()
+            .col_expr(
+                job::Column::LockedAt,
+                Expr::value(Option::<DateTime<FixedOffset>>::None),
+            )
+            .col_expr(job::Column::UpdatedAt, Expr::value(now))
+            .filter(job::Column::Status.eq(job::JobStatus::Running))
+            .filter(job::Column::LockedAt.lt(cutoff))
+    };
+
+    // Jobs that keep killing their worker would otherwise be retried forever
+    let dead = expired()
+        .col_expr(job::Column::Status, Expr::value(job::JobStatus::Dead))
+        .col_expr(
+            job::Column::LastError,
+            Expr::value(Some("Worker stopped during the last attempt".to_string())),
+        )
+        .filter(Expr::col(job::Column::Attempts).gte(Expr::col(job::Column::MaxAttempts)))
+        .exec(db)
+        .await?;
+    if dead.rows_affected > 0 {
+        tracing::error!(
+            "Dead-lettered {} jobs whose worker stopped during their last attempt",
+            dead.rows_affected
+        );
+    }
+
+    expired()
+        .col_expr(job::Column::Status, Expr::value(job::JobStatus::Pending))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// What a panic was raised with, for the job's last error
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown cause"
+    }
+}
+
+/// Exponential backoff between attempts: 30s, 1m, 2m, ... capped at one hour.
+pub fn backoff(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    let seconds = 30i64.saturating_mul(1 << exponent);
+    chrono::Duration::seconds(seconds.min(3600))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), chrono::Duration::seconds(30));
+        assert_eq!(backoff(2), chrono::Duration::seconds(60));
+        assert_eq!(backoff(3), chrono::Duration::seconds(120));
+        assert_eq!(backoff(20), chrono::Duration::seconds(3600));
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let panic = std::panic::catch_unwind(|| panic!("boom")).unwrap_err();
+        assert_eq!(panic_message(&*panic), "boom");
+        let panic = std::panic::catch_unwind(|| panic!("{} failed", "job")).unwrap_err();
+        assert_eq!(panic_message(&*panic), "job failed");
+        let panic = std::panic::catch_unwind(|| std::panic::panic_any(1)).unwrap_err();
+        assert_eq!(panic_message(&*panic), "unknown cause");
+    }
+}
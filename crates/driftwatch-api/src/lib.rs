@@ -1,48 +1,105 @@
 pub mod auth;
+pub mod backfills;
+pub mod backpressure;
 pub mod cache;
+pub mod catalog;
+pub mod compression;
 pub mod config;
+pub mod context;
+pub mod db;
+pub mod demo;
+pub mod dev;
+pub mod digest;
+pub mod downloads;
 pub mod entities;
+pub mod error_reports;
+pub mod evaluation;
+pub mod events;
+pub mod export;
+pub mod feeds;
+pub mod github;
 pub mod graphql;
 pub mod grpc;
+pub mod http;
+pub mod ingest;
+pub mod issues;
+pub mod jobs;
+pub mod links;
 pub mod loaders;
+pub mod logging;
+pub mod management;
+pub mod manifests;
 pub mod migrations;
+pub mod noise;
+pub mod notifications;
+pub mod openmetrics;
+pub mod overview;
+pub mod owners;
+pub mod pr_checks;
+pub mod redaction;
+pub mod relay;
+pub mod releases;
+pub mod remote_write;
+pub mod report_filters;
+pub mod request_log;
+pub mod scaling;
+pub mod search;
+pub mod secrets;
+pub mod shares;
+pub mod simulation;
+pub mod staleness;
+pub mod storage;
+pub mod summary;
+pub mod svg;
+pub mod templates;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use async_graphql::dataloader::DataLoader;
 use async_graphql::http::GraphiQLSource;
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{
-    extract::State,
-    http::{header::AUTHORIZATION, Method, StatusCode},
+    extract::{ConnectInfo, State},
+    http::StatusCode,
     response::IntoResponse,
     routing::{get, post},
-    Router,
+    Extension, Router,
 };
-use sea_orm::{Database, DatabaseConnection};
+use sea_orm::DatabaseConnection;
 use tonic::transport::Server as TonicServer;
 use tsa::{Auth, AuthConfig, NoopCallbacks};
 use tsa_adapter_seaorm::SeaOrmAdapter;
 
-use auth::{validate_token, TsaAuth};
+use auth::{bearer_token, validate_token, TsaAuth};
+use backpressure::Backpressure;
 use cache::AppCache;
 use grpc::auth::auth_service_server::AuthServiceServer;
 use grpc::AuthServiceImpl;
 use loaders::{
-    BenchmarkLoader, BranchLoader, MeasureLoader, MetricLoader, TestbedLoader, ThresholdLoader,
+    BenchmarkLoader, BranchLoader, MeasureLoader, MetricLoader, ProjectLoader, TestbedLoader,
+    ThresholdLoader,
 };
-use tower_http::cors::{Any, CorsLayer};
 
 use config::Config;
-use graphql::{build_schema, AppSchema};
+use db::ReadDb;
+use graphql::versioning::{ClientVersion, CLIENT_HEADER};
+use graphql::{build_schema_with, sdl_router, AppSchema};
+use jobs::JobRegistry;
+use request_log::{RequestAttribution, RequestId};
+use tower_http::catch_panic::CatchPanicLayer;
 
 #[derive(Clone)]
 struct AppState {
     schema: AppSchema,
     db: DatabaseConnection,
+    read_db: DatabaseConnection,
     auth: Arc<TsaAuth>,
     auth_service: Arc<AuthServiceImpl>,
     cache: AppCache,
+    admin_emails: Arc<Vec<String>>,
+    backpressure: Backpressure,
+    shares: Option<shares::ShareSettings>,
 }
 
 async fn health() -> &'static str {
@@ -55,17 +112,17 @@ async fn graphiql() -> impl IntoResponse {
 
 async fn graphql_handler(
     State(state): State<AppState>,
+    Extension(request_id): Extension<RequestId>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
     headers: axum::http::HeaderMap,
     req: GraphQLRequest,
-) -> Result<GraphQLResponse, (StatusCode, String)> {
-    let auth_header = headers
-        .get(AUTHORIZATION)
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.strip_prefix("Bearer "));
-
-    let user = match auth_header {
+) -> Result<axum::response::Response, (StatusCode, String)> {
+    let user = match bearer_token(&headers) {
         Some(token) => match validate_token(token, &state.auth).await {
-            Ok(user) => Some(user),
+            Ok(mut user) => {
+                user.is_admin = state.admin_emails.contains(&user.user.email.to_lowercase());
+                Some(user)
+            }
             Err(e) => {
                 tracing::warn!("Token validation failed: {}", e.0);
                 None
@@ -76,9 +133,18 @@ async fn graphql_handler(
 
     let mut request = req.into_inner();
     request = request.data(state.db.clone());
+    request = request.data(ReadDb(state.read_db.clone()));
     request = request.data(state.cache.clone());
     request = request.data(state.auth.clone());
     request = request.data(state.auth_service.clone());
+    request = request.data(state.backpressure.clone());
+    if let Some(shares) = &state.shares {
+        request = request.data(shares.clone());
+        let peer = peer.map(|Extension(ConnectInfo(addr))| addr);
+        if let Some(client) = shares.client_ip(&headers, peer) {
+            request = request.data(shares::ShareClient(client));
+        }
+    }
 
     request = request.data(DataLoader::new(
         BranchLoader {
@@ -116,56 +182,187 @@ async fn graphql_handler(
         },
         tokio::spawn,
     ));
+    request = request.data(DataLoader::new(
+        ProjectLoader {
+            db: state.db.clone(),
+        },
+        tokio::spawn,
+    ));
 
-    if let Some(user) = user {
-        request = request.data(user);
+    if let Some(user) = &user {
+        request = request.data(user.clone());
     }
+    if let Some(client) = headers.get(CLIENT_HEADER).and_then(|v| v.to_str().ok()) {
+        request = request.data(ClientVersion(client.to_string()));
+    }
+    let mut attribution = RequestAttribution::new(&request, user.as_ref(), 0);
+    error_reports::tag_request(&attribution);
 
-    Ok(state.schema.execute(request).await.into())
+    let mut response = state.schema.execute(request).await;
+    request_log::tag_errors(&mut response, &request_id);
+    attribution.errors = response.errors.len();
+    let retry_after = backpressure::retry_after(&response);
+    let mut http = (Extension(attribution), GraphQLResponse::from(response)).into_response();
+    backpressure::throttle(&mut http, retry_after);
+    Ok(http)
 }
 
-pub async fn serve(port: Option<u16>, grpc_port: Option<u16>) -> anyhow::Result<()> {
+/// Runs the HTTP and gRPC servers. `dev` starts a local stack first; see
+/// [`dev`].
+pub async fn serve(port: Option<u16>, grpc_port: Option<u16>, dev: bool) -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
+    if dev {
+        dev::prepare_env().await?;
+    }
 
     let mut config = Config::from_env();
     if let Some(p) = port {
         config.port = p;
     }
+    let _error_reports = error_reports::init(&config)?;
 
     tracing::info!("Connecting to database...");
 
-    let db = Database::connect(&config.database_url).await?;
+    let db = db::connect(&config.database_url, &config).await?;
 
     tracing::info!("Database connected");
 
-    migrations::run_migrations(&db).await?;
+    let read_db = match &config.database_read_url {
+        Some(url) => {
+            let read_db = db::connect(url, &config).await?;
+            tracing::info!("Read replica connected");
+            read_db
+        }
+        None => db.clone(),
+    };
+
+    match config.secret_key.as_deref() {
+        Some(key) => {
+            secrets::install(secrets::SecretKey::from_base64(key)?);
+        }
+        None => tracing::warn!("DRIFTWATCH_SECRET_KEY is not set; GitHub tokens can't be stored"),
+    }
+
+    match config.web_url.as_deref() {
+        Some(url) => {
+            links::install(url);
+        }
+        None => tracing::info!("WEB_URL is not set; GitHub issues and statuses won't link back"),
+    }
+
+    if let Some(sink) = config.export_sink.clone() {
+        tracing::info!("Exporting evaluated reports to {}", sink.describe());
+        export::install(sink);
+    }
+    if let Some(bus) = config.event_bus.clone() {
+        tracing::info!("Publishing events to {}", bus.describe());
+        events::install(bus);
+    }
+
+    if config.auto_migrate || dev {
+        migrations::run_migrations(&db).await?;
+    } else {
+        let pending = migrations::pending(&db).await?;
+        if pending > 0 {
+            anyhow::bail!(
+                "{} database migrations are pending and AUTO_MIGRATE is off; run `driftwatch migrate up`",
+                pending
+            );
+        }
+    }
+    migrations::encrypt_github_tokens(&db).await?;
+    if config.metrics_hypertable {
+        migrations::convert_metrics_to_hypertable(&db).await?;
+    }
+
+    let cache = AppCache::new();
+
+    let mut registry = JobRegistry::new();
+    ingest::register_jobs(&mut registry, cache.clone());
+    issues::register_jobs(&mut registry);
+    pr_checks::register_jobs(&mut registry);
+    notifications::register_jobs(&mut registry);
+    export::register_jobs(&mut registry);
+    events::register_jobs(&mut registry);
+    backfills::register_jobs(&mut registry);
+    staleness::register_jobs(&mut registry);
+    noise::register_jobs(&mut registry);
+    storage::register_jobs(&mut registry);
+    digest::register_jobs(&mut registry);
+    remote_write::register_jobs(&mut registry, cache.clone());
+    if config.anonymous_shares.is_some() {
+        shares::register_jobs(&mut registry);
+    }
+    let backpressure = Backpressure::new(db.clone(), config.max_pending_jobs);
+    backpressure.spawn();
+    jobs::start_workers(db.clone(), registry, config.job_workers);
 
     let adapter = SeaOrmAdapter::new(db.clone());
     let auth_config = AuthConfig::new().app_name("Driftwatch");
     let auth = Arc::new(Auth::new(adapter, auth_config, NoopCallbacks));
     let auth_service = Arc::new(AuthServiceImpl { auth: auth.clone() });
+    let dev_token = if dev {
+        Some(dev::seed(&auth).await?)
+    } else {
+        None
+    };
 
-    let schema = build_schema();
+    let schema = build_schema_with(config.graphql_introspection);
+    let sdl = sdl_router(&schema, auth.clone());
+    let uploads = storage::router(db.clone());
+    let feeds = feeds::router(db.clone());
+    let shares = shares::router(db.clone());
+    let remote_write = remote_write::router(
+        db.clone(),
+        auth.clone(),
+        cache.clone(),
+        backpressure.clone(),
+    );
+    let openmetrics = openmetrics::router(db.clone(), auth.clone(), cache.clone());
+    let management = management::router(
+        db.clone(),
+        auth.clone(),
+        cache.clone(),
+        backpressure.clone(),
+    );
+    let downloads = downloads::router(read_db.clone(), auth.clone(), cache.clone());
 
-    let cache = AppCache::new();
     let state = AppState {
         schema,
         db,
+        read_db,
         auth: auth.clone(),
         auth_service: auth_service.clone(),
         cache,
+        admin_emails: Arc::new(config.admin_emails.clone()),
+        backpressure,
+        shares: config.anonymous_shares.clone(),
     };
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers(Any);
-
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/health", get(health))
-        .route("/graphql", post(graphql_handler))
-        .route("/graphiql", get(graphiql))
-        .layer(cors)
+        .route("/graphql", post(graphql_handler));
+    if config.graphql_introspection {
+        app = app.route("/graphiql", get(graphiql));
+    } else {
+        tracing::info!("GraphQL introspection and GraphiQL are disabled");
+    }
+    let app = app
+        .merge(sdl)
+        .merge(uploads)
+        .merge(feeds)
+        .merge(shares)
+        .merge(remote_write)
+        .merge(openmetrics)
+        .merge(management)
+        .merge(downloads)
+        .layer(CatchPanicLayer::new());
+    let mut app = config.http_security.apply(app)?;
+    if config.http_compression {
+        app = app.layer(axum::middleware::from_fn(compression::middleware));
+    }
+    let app = app
+        .layer(axum::middleware::from_fn(request_log::middleware))
         .with_state(state);
 
     let grpc_port = grpc_port.unwrap_or(config.grpc_port);
@@ -183,8 +380,14 @@ pub async fn serve(port: Option<u16>, grpc_port: Option<u16>) -> anyhow::Result<
     let addr = format!("0.0.0.0:{}", config.port);
     tracing::info!("Starting HTTP server on {}", addr);
 
+    // HTTP/1.1 and HTTP/2 are told apart per connection, so clients that
+    // speak h2 over cleartext, or through a proxy that does, get it
     let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
     let http_handle = tokio::spawn(async move { axum::serve(listener, app).await });
+    if let Some(token) = &dev_token {
+        dev::print_ready(config.port, grpc_port, token, config.graphql_introspection);
+    }
 
     tokio::select! {
         result = grpc_handle => {
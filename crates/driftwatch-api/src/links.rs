@@ -0,0 +1,113 @@
+//! Links to projects, reports and alerts in the web UI, for GitHub issues,
+//! commit statuses, digests and CLI output. Report and alert IDs alone
+//! mean nothing to people reading those.
+
+use std::sync::OnceLock;
+
+use uuid::Uuid;
+
+static WEB_URL: OnceLock<String> = OnceLock::new();
+
+/// Sets the process-wide web UI address from `WEB_URL`. Called once by
+/// `serve`; later calls keep the first URL and return false.
+pub fn install(web_url: &str) -> bool {
+    WEB_URL
+        .set(web_url.trim_end_matches('/').to_string())
+        .is_ok()
+}
+
+/// The web UI address, when the server knows it
+pub fn web_url() -> Option<&'static str> {
+    WEB_URL.get().map(String::as_str)
+}
+
+/// Guesses the web UI address from the API's: the same origin, without an
+/// `api.` subdomain or an `/api` or `/graphql` path
+pub fn web_url_from_api(api_url: &str) -> String {
+    let url = api_url.trim_end_matches('/');
+    let url = url
+        .strip_suffix("/graphql")
+        .or_else(|| url.strip_suffix("/api"))
+        .unwrap_or(url);
+    match url.split_once("://") {
+        Some((scheme, host)) => match host.strip_prefix("api.") {
+            Some(host) => format!("{}://{}", scheme, host),
+            None => url.to_string(),
+        },
+        None => url.to_string(),
+    }
+}
+
+pub fn project_url(web_url: &str, project_slug: &str) -> String {
+    format!(
+        "{}/projects/{}",
+        web_url.trim_end_matches('/'),
+        project_slug
+    )
+}
+
+pub fn report_url(web_url: &str, project_slug: &str, report_id: impl std::fmt::Display) -> String {
+    format!(
+        "{}/projects/{}/reports/{}",
+        web_url.trim_end_matches('/'),
+        project_slug,
+        report_id
+    )
+}
+
+pub fn alert_url(web_url: &str, project_slug: &str, alert_id: impl std::fmt::Display) -> String {
+    format!(
+        "{}/projects/{}/alerts/{}",
+        web_url.trim_end_matches('/'),
+        project_slug,
+        alert_id
+    )
+}
+
+/// `report_url` against the installed web URL
+pub fn report(project_slug: &str, report_id: Uuid) -> Option<String> {
+    web_url().map(|web_url| report_url(web_url, project_slug, report_id))
+}
+
+/// `alert_url` against the installed web URL
+pub fn alert(project_slug: &str, alert_id: Uuid) -> Option<String> {
+    web_url().map(|web_url| alert_url(web_url, project_slug, alert_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_url_from_api() {
+        assert_eq!(
+            web_url_from_api("https://api.driftwatch.dev"),
+            "https://driftwatch.dev"
+        );
+        assert_eq!(
+            web_url_from_api("https://bench.example.com/api/"),
+            "https://bench.example.com"
+        );
+        assert_eq!(
+            web_url_from_api("http://localhost:4000/graphql"),
+            "http://localhost:4000"
+        );
+        assert_eq!(
+            web_url_from_api("https://driftwatch.dev"),
+            "https://driftwatch.dev"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let id = Uuid::nil();
+        assert_eq!(
+            report_url("https://driftwatch.dev/", "core", id),
+            format!("https://driftwatch.dev/projects/core/reports/{}", id)
+        );
+        assert_eq!(
+            alert_url("https://driftwatch.dev", "core", "a1"),
+            "https://driftwatch.dev/projects/core/alerts/a1"
+        );
+    }
+}
@@ -3,8 +3,8 @@ use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::entities::{self, benchmark, branch, measure, metric, testbed, threshold};
-use crate::graphql::types::{Benchmark, Branch, Measure, Metric, Testbed, Threshold};
+use crate::entities::{self, benchmark, branch, measure, metric, project, testbed, threshold};
+use crate::graphql::types::{Benchmark, Branch, Measure, Metric, Project, Testbed, Threshold};
 
 macro_rules! define_loader {
     ($name:ident, $entity:ty, $column:expr, $output:ty) => {
@@ -57,3 +57,9 @@ define_loader!(
     threshold::Column::Id,
     Threshold
 );
+define_loader!(
+    ProjectLoader,
+    entities::Project,
+    project::Column::Id,
+    Project
+);
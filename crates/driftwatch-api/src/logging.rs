@@ -0,0 +1,72 @@
+//! Log output for `serve`.
+//!
+//! `LOG_FORMAT=json` writes one JSON object per line with the event's
+//! fields at the top level, e.g. `request_id`, `operation`, `project` and
+//! `duration_ms` on request logs, for Loki, Datadog and the like. The
+//! default is human-readable text. `RUST_LOG` filters both.
+
+use std::str::FromStr;
+
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "text" | "pretty" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "Unknown LOG_FORMAT {:?}; expected text or json",
+                other
+            )),
+        }
+    }
+}
+
+impl LogFormat {
+    pub fn from_env() -> Self {
+        std::env::var("LOG_FORMAT")
+            .unwrap_or_default()
+            .parse()
+            .unwrap_or_else(|e: String| panic!("{}", e))
+    }
+}
+
+/// Installs the global subscriber for the server
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
+    let registry = tracing_subscriber::registry().with(filter);
+    match format {
+        LogFormat::Text => registry.with(tracing_subscriber::fmt::layer()).init(),
+        LogFormat::Json => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(false)
+                    .with_span_list(false),
+            )
+            .init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("".parse(), Ok(LogFormat::Text));
+        assert_eq!("text".parse(), Ok(LogFormat::Text));
+        assert_eq!("JSON".parse(), Ok(LogFormat::Json));
+        assert!("logfmt".parse::<LogFormat>().is_err());
+    }
+}
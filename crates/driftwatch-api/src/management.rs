@@ -0,0 +1,1588 @@
+//! A stable REST API for managing projects, thresholds, notification
+//! channels and project group membership, so infrastructure-as-code tools
+//! such as a Terraform provider can own a project's configuration. It also
+//! takes reports, so pipelines in any language can submit metrics through
+//! clients generated from `openapi.json`, which `GET /api/v1/openapi.json`
+//! serves.
+//!
+//! Routes live under `/api/v1` and take an API key as bearer token.
+//! Resources are addressed by the names used in configuration: project and
+//! group slugs, and a threshold's measure, branch and testbed. `GET` on
+//! those addresses doubles as an import lookup. Writes are idempotent:
+//! `PUT` creates or replaces the whole resource, answering 201 or 200, and
+//! `DELETE` answers 204 whether or not the resource existed. Notification
+//! channels have no natural key, so creating one requires an
+//! `Idempotency-Key` header; retrying with the same key returns the first
+//! response instead of adding a second channel. A report goes through the
+//! same code as the `createReport` mutation, so it's validated, stored and
+//! evaluated alike.
+//!
+//! Field names and error codes are part of the contract: `v1` may gain
+//! fields but won't rename, remove or repurpose any.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use async_graphql::ID;
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Set, SqlErr, Statement,
+    TransactionTrait,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::{bearer_token, validate_token, AuthUser, TsaAuth};
+use crate::backpressure::{self, Backpressure, Overload};
+use crate::cache::AppCache;
+use crate::entities::notification_channel::{self, ChannelKind};
+use crate::entities::{
+    self, branch, measure, project, project_group, project_group_member, testbed, threshold,
+};
+use crate::graphql::authz;
+use crate::graphql::mutation::submit_report;
+use crate::graphql::types::{ContextEntryInput, CreateReportInput, MetricInput, ReportKindInput};
+use crate::{error_reports, ingest, notifications, storage, templates};
+
+/// OpenAPI description of the routes below, for generating clients
+pub const OPENAPI_SPEC: &str = include_str!("../openapi.json");
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// How long a response is kept for replay
+const IDEMPOTENCY_TTL_HOURS: i64 = 24;
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// A failed management request, answered as
+/// `{"error": {"code": ..., "message": ...}}`
+#[derive(Debug)]
+pub enum ApiError {
+    Unauthorized(String),
+    NotFound(&'static str),
+    Invalid(String),
+    Conflict(String),
+    /// The server is saturated; answered with `Retry-After`
+    Overloaded(Overload),
+    Db(DbErr),
+}
+
+impl ApiError {
+    /// Stable, machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unauthorized(_) => "unauthorized",
+            Self::NotFound(_) => "not_found",
+            Self::Invalid(_) => "invalid",
+            Self::Conflict(_) => "conflict",
+            Self::Overloaded(_) => "overloaded",
+            Self::Db(_) => "internal",
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized(message) | Self::Invalid(message) | Self::Conflict(message) => {
+                write!(f, "{}", message)
+            }
+            Self::NotFound(what) => write!(f, "{} not found", what),
+            Self::Overloaded(overload) => write!(f, "{}", overload),
+            Self::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<DbErr> for ApiError {
+    fn from(e: DbErr) -> Self {
+        match e.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => Self::Conflict(
+                "Another request created the resource at the same time; retry".to_string(),
+            ),
+            _ => Self::Db(e),
+        }
+    }
+}
+
+/// Resolver errors are the caller's, except those from the database
+impl From<async_graphql::Error> for ApiError {
+    fn from(e: async_graphql::Error) -> Self {
+        if e.source
+            .as_deref()
+            .is_some_and(|source| source.is::<DbErr>())
+        {
+            Self::Db(DbErr::Custom(e.message))
+        } else {
+            Self::Invalid(e.message)
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Overloaded(overload) => {
+                tracing::warn!("Refusing ingestion: {}", overload.reason);
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Db(e) => {
+                tracing::error!("Management request failed: {}", e);
+                error_reports::capture(&anyhow::anyhow!("{}", e), "management", &[]);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        let message = match &self {
+            Self::Db(_) => "Internal error".to_string(),
+            _ => self.to_string(),
+        };
+        let mut response = (
+            status,
+            Json(serde_json::json!({
+                "error": { "code": self.code(), "message": message }
+            })),
+        )
+            .into_response();
+        if let Self::Overloaded(overload) = &self {
+            backpressure::throttle(&mut response, Some(overload.retry_after_secs));
+        }
+        response
+    }
+}
+
+#[derive(Clone)]
+struct ManagementState {
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+    backpressure: Backpressure,
+}
+
+impl ManagementState {
+    async fn user(&self, headers: &HeaderMap) -> Result<AuthUser, ApiError> {
+        let token = bearer_token(headers)
+            .ok_or_else(|| ApiError::Unauthorized("Missing bearer token".to_string()))?;
+        validate_token(token, &self.auth)
+            .await
+            .map_err(|e| ApiError::Unauthorized(e.0))
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &Bytes) -> Result<T, ApiError> {
+    serde_json::from_slice(body).map_err(|e| ApiError::Invalid(format!("Invalid body: {}", e)))
+}
+
+fn created_or_ok(created: bool) -> StatusCode {
+    if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    }
+}
+
+async fn require_project<C: ConnectionTrait>(
+    db: &C,
+    user: &AuthUser,
+    slug: &str,
+) -> Result<project::Model, ApiError> {
+    authz::find_project(db, user, slug)
+        .await?
+        .ok_or(ApiError::NotFound("Project"))
+}
+
+// Projects
+
+/// The desired state of a project. Omitted settings take their defaults,
+/// so a `PUT` fully describes the project.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub public: bool,
+    /// Branch reports submitted for `HEAD` go to; defaults to `main`
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub expected_cadence_hours: Option<i32>,
+    #[serde(default)]
+    pub alert_after_reports: Option<i32>,
+    #[serde(default)]
+    pub noise_cv_limit: Option<f64>,
+    /// `flag`, `widen_thresholds` or `require_more_samples`
+    #[serde(default)]
+    pub noise_action: Option<String>,
+    #[serde(default = "default_true")]
+    pub redact_secrets: bool,
+    /// Seeds measures and thresholds when the project is created; ignored
+    /// afterwards
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectResource {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub public: bool,
+    pub default_branch: String,
+    pub expected_cadence_hours: Option<i32>,
+    pub alert_after_reports: Option<i32>,
+    pub noise_cv_limit: Option<f64>,
+    pub noise_action: &'static str,
+    pub redact_secrets: bool,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+fn noise_action_name(action: &project::NoiseAction) -> &'static str {
+    match action {
+        project::NoiseAction::Flag => "flag",
+        project::NoiseAction::WidenThresholds => "widen_thresholds",
+        project::NoiseAction::RequireMoreSamples => "require_more_samples",
+    }
+}
+
+impl From<project::Model> for ProjectResource {
+    fn from(model: project::Model) -> Self {
+        Self {
+            id: model.id,
+            noise_action: noise_action_name(&model.noise_action),
+            slug: model.slug,
+            name: model.name,
+            description: model.description,
+            public: model.public,
+            default_branch: model.default_branch,
+            expected_cadence_hours: model.expected_cadence_hours,
+            alert_after_reports: model.alert_after_reports,
+            noise_cv_limit: model.noise_cv_limit,
+            redact_secrets: model.redact_secrets,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+/// The checked settings of a project spec
+struct ProjectSettings {
+    default_branch: String,
+    expected_cadence_hours: Option<i32>,
+    alert_after_reports: Option<i32>,
+    noise_cv_limit: Option<f64>,
+    noise_action: project::NoiseAction,
+}
+
+impl ProjectSpec {
+    fn settings(&self) -> Result<ProjectSettings, ApiError> {
+        if self.name.trim().is_empty() {
+            return Err(ApiError::Invalid("name must not be empty".to_string()));
+        }
+        if self.expected_cadence_hours.is_some_and(|h| h <= 0) {
+            return Err(ApiError::Invalid(
+                "expected_cadence_hours must be positive".to_string(),
+            ));
+        }
+        if self.alert_after_reports.is_some_and(|n| n <= 0) {
+            return Err(ApiError::Invalid(
+                "alert_after_reports must be positive".to_string(),
+            ));
+        }
+        if self.noise_cv_limit.is_some_and(|l| l <= 0.0) {
+            return Err(ApiError::Invalid(
+                "noise_cv_limit must be positive".to_string(),
+            ));
+        }
+        let default_branch = match &self.default_branch {
+            Some(name) => ingest::check_branch_name(name)
+                .map_err(|e| ApiError::Invalid(format!("default_branch: {}", e)))?,
+            None => ingest::DEFAULT_BRANCH,
+        };
+        let noise_action = match self.noise_action.as_deref().unwrap_or("flag") {
+            "flag" => project::NoiseAction::Flag,
+            "widen_thresholds" => project::NoiseAction::WidenThresholds,
+            "require_more_samples" => project::NoiseAction::RequireMoreSamples,
+            other => {
+                return Err(ApiError::Invalid(format!(
+                    "noise_action must be flag, widen_thresholds or require_more_samples, not {}",
+                    other
+                )))
+            }
+        };
+        Ok(ProjectSettings {
+            default_branch: default_branch.to_string(),
+            expected_cadence_hours: self.expected_cadence_hours,
+            // One report is the default, stored as unset
+            alert_after_reports: self.alert_after_reports.filter(|n| *n > 1),
+            noise_cv_limit: self.noise_cv_limit,
+            noise_action,
+        })
+    }
+}
+
+async fn get_project(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ProjectResource>, ApiError> {
+    let user = state.user(&headers).await?;
+    let project = require_project(&state.db, &user, &slug).await?;
+    Ok(Json(project.into()))
+}
+
+async fn put_project(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ProjectResource>), ApiError> {
+    let user = state.user(&headers).await?;
+    let spec: ProjectSpec = parse(&body)?;
+    let settings = spec.settings()?;
+    let now = Utc::now().fixed_offset();
+
+    let txn = state.db.begin().await?;
+    let (project, created) = match authz::find_project(&txn, &user, &slug).await? {
+        Some(existing) => {
+            let mut active: project::ActiveModel = existing.into();
+            active.name = Set(spec.name);
+            active.description = Set(spec.description);
+            active.public = Set(spec.public);
+            active.default_branch = Set(settings.default_branch);
+            active.expected_cadence_hours = Set(settings.expected_cadence_hours);
+            active.alert_after_reports = Set(settings.alert_after_reports);
+            active.noise_cv_limit = Set(settings.noise_cv_limit);
+            active.noise_action = Set(settings.noise_action);
+            active.redact_secrets = Set(spec.redact_secrets);
+            active.updated_at = Set(now);
+            (active.update(&txn).await?, false)
+        }
+        None => {
+            if slug.trim().is_empty() || slug.contains('/') {
+                return Err(ApiError::Invalid(format!("Invalid slug: {}", slug)));
+            }
+            let template_name = spec
+                .template
+                .as_deref()
+                .unwrap_or(templates::DEFAULT_TEMPLATE);
+            let template = templates::find(template_name).ok_or_else(|| {
+                ApiError::Invalid(format!("Unknown project template '{}'", template_name))
+            })?;
+            let project = project::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                user_id: Set(Some(user.user_id())),
+                slug: Set(slug.clone()),
+                name: Set(spec.name),
+                description: Set(spec.description),
+                public: Set(spec.public),
+                default_branch: Set(settings.default_branch),
+                github_repo: Set(None),
+                github_token: Set(None),
+                github_pr_comments: Set(false),
+                github_status_checks: Set(false),
+                expected_cadence_hours: Set(settings.expected_cadence_hours),
+                stale_branches: Set(None),
+                github_issue_after_reports: Set(None),
+                alert_after_reports: Set(settings.alert_after_reports),
+                noise_cv_limit: Set(settings.noise_cv_limit),
+                noise_action: Set(settings.noise_action),
+                benchmark_required_paths: Set(None),
+                redact_secrets: Set(spec.redact_secrets),
+                alert_feed_token_hash: Set(None),
+                expires_at: Set(None),
+                share_token_hash: Set(None),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(&txn)
+            .await?;
+            templates::apply(&txn, project.id, template).await?;
+            (project, true)
+        }
+    };
+    txn.commit().await?;
+
+    state.cache.invalidate_user_projects(user.user_id()).await;
+    state.cache.invalidate_project(user.user_id(), &slug).await;
+    Ok((created_or_ok(created), Json(project.into())))
+}
+
+async fn delete_project(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let user = state.user(&headers).await?;
+    if let Some(project) = authz::find_project(&state.db, &user, &slug).await? {
+        entities::Project::delete_by_id(project.id)
+            .exec(&state.db)
+            .await?;
+        state.cache.invalidate_user_projects(user.user_id()).await;
+        state.cache.invalidate_project(user.user_id(), &slug).await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Thresholds
+
+/// Which branch and testbed a threshold applies to; omitted means all
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdKey {
+    pub branch: Option<String>,
+    pub testbed: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThresholdSpec {
+    #[serde(default)]
+    pub upper_boundary: Option<f64>,
+    #[serde(default)]
+    pub lower_boundary: Option<f64>,
+    #[serde(default = "default_min_sample_size")]
+    pub min_sample_size: i32,
+}
+
+fn default_min_sample_size() -> i32 {
+    2
+}
+
+impl ThresholdSpec {
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.upper_boundary.is_none() && self.lower_boundary.is_none() {
+            return Err(ApiError::Invalid(
+                "Set upper_boundary, lower_boundary or both".to_string(),
+            ));
+        }
+        if [self.upper_boundary, self.lower_boundary]
+            .iter()
+            .flatten()
+            .any(|b| !b.is_finite() || *b < 0.0)
+        {
+            return Err(ApiError::Invalid(
+                "Boundaries are percentages and must not be negative".to_string(),
+            ));
+        }
+        if self.min_sample_size < 1 {
+            return Err(ApiError::Invalid(
+                "min_sample_size must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThresholdResource {
+    pub id: Uuid,
+    pub measure: String,
+    pub branch: Option<String>,
+    pub testbed: Option<String>,
+    pub upper_boundary: Option<f64>,
+    pub lower_boundary: Option<f64>,
+    pub min_sample_size: i32,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+/// Names of a project's measures, branches and testbeds by id
+#[derive(Default)]
+struct Names(HashMap<Uuid, String>);
+
+impl Names {
+    async fn load<C: ConnectionTrait>(db: &C, project_id: Uuid) -> Result<Self, DbErr> {
+        let mut names = HashMap::new();
+        for m in entities::Measure::find()
+            .filter(measure::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?
+        {
+            names.insert(m.id, m.name);
+        }
+        for b in entities::Branch::find()
+            .filter(branch::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?
+        {
+            names.insert(b.id, b.name);
+        }
+        for t in entities::Testbed::find()
+            .filter(testbed::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?
+        {
+            names.insert(t.id, t.name);
+        }
+        Ok(Self(names))
+    }
+
+    fn resource(&self, model: threshold::Model) -> ThresholdResource {
+        let name = |id: &Uuid| self.0.get(id).cloned();
+        ThresholdResource {
+            id: model.id,
+            measure: name(&model.measure_id).unwrap_or_default(),
+            branch: model.branch_id.as_ref().and_then(name),
+            testbed: model.testbed_id.as_ref().and_then(name),
+            upper_boundary: model.upper_boundary,
+            lower_boundary: model.lower_boundary,
+            min_sample_size: model.min_sample_size,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+/// Ids of a threshold's measure, branch and testbed, `None` when one of
+/// them doesn't exist
+async fn threshold_ids<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    measure_name: &str,
+    key: &ThresholdKey,
+) -> Result<Option<(Uuid, Option<Uuid>, Option<Uuid>)>, DbErr> {
+    let Some(measure) = entities::Measure::find()
+        .filter(measure::Column::ProjectId.eq(project_id))
+        .filter(measure::Column::Name.eq(measure_name))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let branch_id = match &key.branch {
+        Some(name) => match ingest::find_branch(db, project_id, name).await? {
+            Some(branch) => Some(branch.id),
+            None => return Ok(None),
+        },
+        None => None,
+    };
+    let testbed_id = match &key.testbed {
+        Some(name) => match ingest::find_testbed(db, project_id, name).await? {
+            Some(testbed) => Some(testbed.id),
+            None => return Ok(None),
+        },
+        None => None,
+    };
+    Ok(Some((measure.id, branch_id, testbed_id)))
+}
+
+/// The oldest threshold with this measure, branch and testbed
+async fn find_threshold<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    (measure_id, branch_id, testbed_id): (Uuid, Option<Uuid>, Option<Uuid>),
+) -> Result<Option<threshold::Model>, DbErr> {
+    let mut query = entities::Threshold::find()
+        .filter(threshold::Column::ProjectId.eq(project_id))
+        .filter(threshold::Column::MeasureId.eq(measure_id));
+    query = match branch_id {
+        Some(id) => query.filter(threshold::Column::BranchId.eq(id)),
+        None => query.filter(threshold::Column::BranchId.is_null()),
+    };
+    query = match testbed_id {
+        Some(id) => query.filter(threshold::Column::TestbedId.eq(id)),
+        None => query.filter(threshold::Column::TestbedId.is_null()),
+    };
+    query
+        .order_by_asc(threshold::Column::CreatedAt)
+        .one(db)
+        .await
+}
+
+async fn list_thresholds(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ThresholdResource>>, ApiError> {
+    let user = state.user(&headers).await?;
+    let project = require_project(&state.db, &user, &slug).await?;
+    let names = Names::load(&state.db, project.id).await?;
+    let thresholds = entities::Threshold::find()
+        .filter(threshold::Column::ProjectId.eq(project.id))
+        .order_by_asc(threshold::Column::CreatedAt)
+        .all(&state.db)
+        .await?;
+    Ok(Json(
+        thresholds.into_iter().map(|t| names.resource(t)).collect(),
+    ))
+}
+
+async fn get_threshold(
+    State(state): State<ManagementState>,
+    Path((slug, measure_name)): Path<(String, String)>,
+    Query(key): Query<ThresholdKey>,
+    headers: HeaderMap,
+) -> Result<Json<ThresholdResource>, ApiError> {
+    let user = state.user(&headers).await?;
+    let project = require_project(&state.db, &user, &slug).await?;
+    let ids = threshold_ids(&state.db, project.id, &measure_name, &key)
+        .await?
+        .ok_or(ApiError::NotFound("Threshold"))?;
+    let threshold = find_threshold(&state.db, project.id, ids)
+        .await?
+        .ok_or(ApiError::NotFound("Threshold"))?;
+    let names = Names::load(&state.db, project.id).await?;
+    Ok(Json(names.resource(threshold)))
+}
+
+async fn put_threshold(
+    State(state): State<ManagementState>,
+    Path((slug, measure_name)): Path<(String, String)>,
+    Query(key): Query<ThresholdKey>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ThresholdResource>), ApiError> {
+    let user = state.user(&headers).await?;
+    let spec: ThresholdSpec = parse(&body)?;
+    spec.validate()?;
+    let project = require_project(&state.db, &user, &slug).await?;
+
+    let txn = state.db.begin().await?;
+    let measure_id = ingest::get_or_create_measure(&txn, project.id, &measure_name)
+        .await?
+        .id;
+    let branch_id = match &key.branch {
+        Some(name) => Some(
+            ingest::get_or_create_branch(&txn, project.id, name)
+                .await?
+                .id,
+        ),
+        None => None,
+    };
+    let testbed_id = match &key.testbed {
+        Some(name) => Some(
+            ingest::get_or_create_testbed(&txn, project.id, name)
+                .await?
+                .id,
+        ),
+        None => None,
+    };
+
+    let now = Utc::now().fixed_offset();
+    let (threshold, created) =
+        match find_threshold(&txn, project.id, (measure_id, branch_id, testbed_id)).await? {
+            Some(existing) => {
+                let mut active: threshold::ActiveModel = existing.into();
+                active.upper_boundary = Set(spec.upper_boundary);
+                active.lower_boundary = Set(spec.lower_boundary);
+                active.min_sample_size = Set(spec.min_sample_size);
+                active.updated_at = Set(now);
+                (active.update(&txn).await?, false)
+            }
+            None => (
+                threshold::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    project_id: Set(project.id),
+                    measure_id: Set(measure_id),
+                    branch_id: Set(branch_id),
+                    testbed_id: Set(testbed_id),
+                    upper_boundary: Set(spec.upper_boundary),
+                    lower_boundary: Set(spec.lower_boundary),
+                    min_sample_size: Set(spec.min_sample_size),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(&txn)
+                .await?,
+                true,
+            ),
+        };
+    txn.commit().await?;
+
+    state
+        .cache
+        .invalidate_project(user.user_id(), &project.slug)
+        .await;
+    let names = Names::load(&state.db, project.id).await?;
+    Ok((created_or_ok(created), Json(names.resource(threshold))))
+}
+
+async fn delete_threshold(
+    State(state): State<ManagementState>,
+    Path((slug, measure_name)): Path<(String, String)>,
+    Query(key): Query<ThresholdKey>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let user = state.user(&headers).await?;
+    let Some(project) = authz::find_project(&state.db, &user, &slug).await? else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
+    if let Some(ids) = threshold_ids(&state.db, project.id, &measure_name, &key).await? {
+        if let Some(threshold) = find_threshold(&state.db, project.id, ids).await? {
+            entities::Threshold::delete_by_id(threshold.id)
+                .exec(&state.db)
+                .await?;
+            state
+                .cache
+                .invalidate_project(user.user_id(), &project.slug)
+                .await;
+        }
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Notification channels
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelSpec {
+    /// `webhook` or `slack`
+    pub kind: String,
+    /// Write-only: stored sealed and shown back only by host
+    pub url: String,
+    /// Defaults to `["digest"]`
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+struct CheckedChannel {
+    kind: ChannelKind,
+    url: String,
+    events: Vec<String>,
+    template: Option<String>,
+}
+
+impl ChannelSpec {
+    fn check(self) -> Result<CheckedChannel, ApiError> {
+        let kind = match self.kind.as_str() {
+            "webhook" => ChannelKind::Webhook,
+            "slack" => ChannelKind::Slack,
+            other => {
+                return Err(ApiError::Invalid(format!(
+                    "kind must be webhook or slack, not {}",
+                    other
+                )))
+            }
+        };
+        let url = self.url.trim().to_string();
+        notifications::validate_url(kind, &url).map_err(ApiError::Invalid)?;
+        let events = notifications::validate_events(
+            &self
+                .events
+                .unwrap_or_else(|| vec![notifications::DIGEST_EVENT.to_string()]),
+        )
+        .map_err(ApiError::Invalid)?;
+        let template = self.template.filter(|t| !t.trim().is_empty());
+        if let Some(template) = &template {
+            notifications::validate_template(template, &events).map_err(ApiError::Invalid)?;
+        }
+        Ok(CheckedChannel {
+            kind,
+            url,
+            events,
+            template,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelResource {
+    pub id: Uuid,
+    pub kind: &'static str,
+    /// Host the channel posts to
+    pub target: String,
+    pub events: Vec<String>,
+    pub template: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl From<notification_channel::Model> for ChannelResource {
+    fn from(model: notification_channel::Model) -> Self {
+        Self {
+            id: model.id,
+            kind: match model.kind {
+                ChannelKind::Webhook => "webhook",
+                ChannelKind::Slack => "slack",
+            },
+            target: notifications::channel_target(&model),
+            events: notifications::channel_events(&model),
+            template: model.template,
+            created_at: model.created_at,
+        }
+    }
+}
+
+async fn find_channel<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    id: &str,
+) -> Result<Option<notification_channel::Model>, DbErr> {
+    let Ok(id) = Uuid::parse_str(id) else {
+        return Ok(None);
+    };
+    entities::NotificationChannel::find_by_id(id)
+        .filter(notification_channel::Column::ProjectId.eq(project.id))
+        .one(db)
+        .await
+}
+
+fn seal(id: Uuid, url: &str) -> Result<String, ApiError> {
+    notifications::seal_url(id, url).map_err(|e| ApiError::Invalid(e.to_string()))
+}
+
+async fn list_channels(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ChannelResource>>, ApiError> {
+    let user = state.user(&headers).await?;
+    let project = require_project(&state.db, &user, &slug).await?;
+    let channels = entities::NotificationChannel::find()
+        .filter(notification_channel::Column::ProjectId.eq(project.id))
+        .order_by_asc(notification_channel::Column::CreatedAt)
+        .all(&state.db)
+        .await?;
+    Ok(Json(channels.into_iter().map(Into::into).collect()))
+}
+
+async fn create_channel(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let user = state.user(&headers).await?;
+    let key = idempotency_key(&headers)?;
+    let request_hash = storage::digest(format!("{} {}\n", method, uri.path()).as_bytes())
+        + &storage::digest(&body);
+
+    idempotent(&state.db, user.user_id(), &key, &request_hash, || async {
+        let channel = parse::<ChannelSpec>(&body)?.check()?;
+        let project = require_project(&state.db, &user, &slug).await?;
+        let id = Uuid::new_v4();
+        let model = notification_channel::ActiveModel {
+            id: Set(id),
+            project_id: Set(project.id),
+            kind: Set(channel.kind),
+            url: Set(seal(id, &channel.url)?),
+            events: Set(serde_json::json!(channel.events)),
+            template: Set(channel.template),
+            created_at: Set(Utc::now().fixed_offset()),
+        }
+        .insert(&state.db)
+        .await?;
+        let resource = serde_json::to_value(ChannelResource::from(model))
+            .map_err(|e| ApiError::Db(DbErr::Custom(format!("channel: {}", e))))?;
+        Ok((StatusCode::CREATED, resource))
+    })
+    .await
+}
+
+async fn get_channel(
+    State(state): State<ManagementState>,
+    Path((slug, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<ChannelResource>, ApiError> {
+    let user = state.user(&headers).await?;
+    let project = require_project(&state.db, &user, &slug).await?;
+    let channel = find_channel(&state.db, &project, &id)
+        .await?
+        .ok_or(ApiError::NotFound("Notification channel"))?;
+    Ok(Json(channel.into()))
+}
+
+async fn put_channel(
+    State(state): State<ManagementState>,
+    Path((slug, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ChannelResource>, ApiError> {
+    let user = state.user(&headers).await?;
+    let channel = parse::<ChannelSpec>(&body)?.check()?;
+    let project = require_project(&state.db, &user, &slug).await?;
+    let existing = find_channel(&state.db, &project, &id)
+        .await?
+        .ok_or(ApiError::NotFound("Notification channel"))?;
+
+    let mut active: notification_channel::ActiveModel = existing.clone().into();
+    active.kind = Set(channel.kind);
+    active.url = Set(seal(existing.id, &channel.url)?);
+    active.events = Set(serde_json::json!(channel.events));
+    active.template = Set(channel.template);
+    Ok(Json(active.update(&state.db).await?.into()))
+}
+
+async fn delete_channel(
+    State(state): State<ManagementState>,
+    Path((slug, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let user = state.user(&headers).await?;
+    if let Some(project) = authz::find_project(&state.db, &user, &slug).await? {
+        if let Some(channel) = find_channel(&state.db, &project, &id).await? {
+            entities::NotificationChannel::delete_by_id(channel.id)
+                .exec(&state.db)
+                .await?;
+        }
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Reports
+
+/// A report submitted over REST, e.g. by a data pipeline: one run's
+/// metrics on a branch and testbed. Fields are those of the `createReport`
+/// mutation's input.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportSpec {
+    pub branch: String,
+    pub testbed: String,
+    #[serde(default)]
+    pub git_hash: Option<String>,
+    #[serde(default)]
+    pub pr_number: Option<i32>,
+    #[serde(default)]
+    pub commit_message: Option<String>,
+    #[serde(default)]
+    pub commit_author: Option<String>,
+    #[serde(default)]
+    pub committed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    #[serde(default)]
+    pub merge_base_hash: Option<String>,
+    #[serde(default)]
+    pub evaluated_commit: Option<String>,
+    /// Marks the report as a release
+    #[serde(default)]
+    pub version: Option<String>,
+    /// What triggered the run: `pr`, `push`, `nightly`, `release` or
+    /// `manual`; inferred from `pr_number` and `version` when omitted
+    #[serde(default)]
+    pub kind: Option<ReportKindInput>,
+    /// Exit code of the benchmark command
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Defaults to whether `exit_code` is non-zero
+    #[serde(default)]
+    pub partial: Option<bool>,
+    /// When the metrics were measured; defaults to now
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub context: HashMap<String, String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Every benchmark the run was meant to produce
+    #[serde(default)]
+    pub expected_benchmarks: Option<Vec<String>>,
+    /// Id for the new report, chosen by the submitter; submitting the same id
+    /// again returns the report it created
+    #[serde(default)]
+    pub id: Option<Uuid>,
+    pub metrics: Vec<MetricSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricSpec {
+    pub benchmark: String,
+    pub measure: String,
+    pub value: f64,
+    #[serde(default)]
+    pub lower_value: Option<f64>,
+    #[serde(default)]
+    pub upper_value: Option<f64>,
+    #[serde(default)]
+    pub reported_change: Option<f64>,
+    #[serde(default)]
+    pub outliers: Option<i32>,
+}
+
+impl ReportSpec {
+    fn into_input(self, project_slug: String) -> CreateReportInput {
+        CreateReportInput {
+            project_slug,
+            branch: self.branch,
+            testbed: self.testbed,
+            git_hash: self.git_hash,
+            pr_number: self.pr_number,
+            commit_message: self.commit_message,
+            commit_author: self.commit_author,
+            committed_at: self.committed_at,
+            base_branch: self.base_branch,
+            merge_base_hash: self.merge_base_hash,
+            evaluated_commit: self.evaluated_commit,
+            version: self.version,
+            kind: self.kind,
+            exit_code: self.exit_code,
+            partial: self.partial,
+            created_at: self.created_at,
+            context: Some(
+                self.context
+                    .into_iter()
+                    .map(|(key, value)| ContextEntryInput { key, value })
+                    .collect(),
+            ),
+            tags: Some(self.tags),
+            expected_benchmarks: self.expected_benchmarks,
+            id: self.id.map(|id| ID(id.to_string())),
+            metrics: self.metrics.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<MetricSpec> for MetricInput {
+    fn from(spec: MetricSpec) -> Self {
+        Self {
+            benchmark: spec.benchmark,
+            measure: spec.measure,
+            value: spec.value,
+            lower_value: spec.lower_value,
+            upper_value: spec.upper_value,
+            reported_change: spec.reported_change,
+            outliers: spec.outliers,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportResource {
+    pub id: Uuid,
+    pub project: String,
+    /// As submitted
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub version: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+/// Stores a report and queues its evaluation, unless the server is
+/// saturated
+async fn create_report(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    let user = state.user(&headers).await?;
+    if let Some(overload) = state.backpressure.check() {
+        return Err(ApiError::Overloaded(overload));
+    }
+    let spec = parse::<ReportSpec>(&body)?;
+    let project = require_project(&state.db, &user, &slug).await?;
+    let (branch, testbed) = (spec.branch.clone(), spec.testbed.clone());
+
+    let input = spec.into_input(project.slug.clone());
+    let report = submit_report(&state.db, &state.cache, &project, input).await?;
+
+    let resource = ReportResource {
+        id: report.id,
+        project: project.slug,
+        branch,
+        testbed,
+        git_hash: report.git_hash,
+        version: report.version,
+        created_at: report.created_at,
+    };
+    Ok((StatusCode::CREATED, Json(resource)).into_response())
+}
+
+// Idempotency keys
+
+fn idempotency_key(headers: &HeaderMap) -> Result<String, ApiError> {
+    let key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .unwrap_or_default();
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(ApiError::Invalid(format!(
+            "Creating a channel requires an Idempotency-Key header of 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        )));
+    }
+    Ok(key.to_string())
+}
+
+#[derive(Debug, FromQueryResult)]
+struct StoredResponse {
+    request_hash: String,
+    status_code: Option<i32>,
+    response: Option<serde_json::Value>,
+}
+
+/// Runs `create` once per idempotency key. A retry of the same request
+/// gets the stored response; reusing the key for a different request, or
+/// while the first is still running, is a conflict. Failed requests free
+/// the key so they can be retried.
+async fn idempotent<F, Fut>(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    key: &str,
+    request_hash: &str,
+    create: F,
+) -> Result<Response, ApiError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<(StatusCode, serde_json::Value), ApiError>>,
+{
+    let now = Utc::now().fixed_offset();
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "DELETE FROM idempotency_keys WHERE created_at < $1",
+        [(now - chrono::Duration::hours(IDEMPOTENCY_TTL_HOURS)).into()],
+    ))
+    .await?;
+    let claimed = db
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"INSERT INTO idempotency_keys (user_id, key, request_hash, created_at)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (user_id, key) DO NOTHING"#,
+            [user_id.into(), key.into(), request_hash.into(), now.into()],
+        ))
+        .await?
+        .rows_affected()
+        == 1;
+
+    if !claimed {
+        let stored = StoredResponse::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"SELECT request_hash, status_code, response FROM idempotency_keys
+               WHERE user_id = $1 AND key = $2"#,
+            [user_id.into(), key.into()],
+        ))
+        .one(db)
+        .await?
+        .ok_or_else(|| {
+            ApiError::Conflict("The Idempotency-Key expired during the request".to_string())
+        })?;
+        if stored.request_hash != request_hash {
+            return Err(ApiError::Conflict(
+                "The Idempotency-Key was already used for a different request".to_string(),
+            ));
+        }
+        let (Some(status), Some(response)) = (stored.status_code, stored.response) else {
+            return Err(ApiError::Conflict(
+                "A request with this Idempotency-Key is still in progress".to_string(),
+            ));
+        };
+        let status = StatusCode::from_u16(status as u16).unwrap_or(StatusCode::OK);
+        let mut response = (status, Json(response)).into_response();
+        response
+            .headers_mut()
+            .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+        return Ok(response);
+    }
+
+    match create().await {
+        Ok((status, body)) => {
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"UPDATE idempotency_keys SET status_code = $3, response = $4
+                   WHERE user_id = $1 AND key = $2"#,
+                [
+                    user_id.into(),
+                    key.into(),
+                    (status.as_u16() as i32).into(),
+                    body.clone().into(),
+                ],
+            ))
+            .await?;
+            Ok((status, Json(body)).into_response())
+        }
+        Err(e) => {
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2",
+                [user_id.into(), key.into()],
+            ))
+            .await?;
+            Err(e)
+        }
+    }
+}
+
+// Project groups
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupResource {
+    pub id: Uuid,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Slugs of the member projects
+    pub projects: Vec<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+async fn group_resource<C: ConnectionTrait>(
+    db: &C,
+    group: project_group::Model,
+) -> Result<GroupResource, DbErr> {
+    let project_ids: Vec<Uuid> = entities::ProjectGroupMember::find()
+        .select_only()
+        .column(project_group_member::Column::ProjectId)
+        .filter(project_group_member::Column::GroupId.eq(group.id))
+        .into_tuple()
+        .all(db)
+        .await?;
+    let projects = entities::Project::find()
+        .filter(project::Column::Id.is_in(project_ids))
+        .order_by_asc(project::Column::Slug)
+        .all(db)
+        .await?;
+    Ok(GroupResource {
+        id: group.id,
+        slug: group.slug,
+        name: group.name,
+        description: group.description,
+        projects: projects.into_iter().map(|p| p.slug).collect(),
+        created_at: group.created_at,
+        updated_at: group.updated_at,
+    })
+}
+
+async fn get_group(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<GroupResource>, ApiError> {
+    let user = state.user(&headers).await?;
+    let group = authz::find_group(&state.db, &user, &slug)
+        .await?
+        .ok_or(ApiError::NotFound("Project group"))?;
+    Ok(Json(group_resource(&state.db, group).await?))
+}
+
+/// Creates or renames a group; its members are managed separately
+async fn put_group(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<GroupResource>), ApiError> {
+    let user = state.user(&headers).await?;
+    let spec: GroupSpec = parse(&body)?;
+    if spec.name.trim().is_empty() {
+        return Err(ApiError::Invalid("name must not be empty".to_string()));
+    }
+    let now = Utc::now().fixed_offset();
+    let (group, created) = match authz::find_group(&state.db, &user, &slug).await? {
+        Some(existing) => {
+            let mut active: project_group::ActiveModel = existing.into();
+            active.name = Set(spec.name);
+            active.description = Set(spec.description);
+            active.updated_at = Set(now);
+            (active.update(&state.db).await?, false)
+        }
+        None => (
+            project_group::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                user_id: Set(user.user_id()),
+                slug: Set(slug),
+                name: Set(spec.name),
+                description: Set(spec.description),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(&state.db)
+            .await?,
+            true,
+        ),
+    };
+    Ok((
+        created_or_ok(created),
+        Json(group_resource(&state.db, group).await?),
+    ))
+}
+
+async fn delete_group(
+    State(state): State<ManagementState>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let user = state.user(&headers).await?;
+    if let Some(group) = authz::find_group(&state.db, &user, &slug).await? {
+        entities::ProjectGroup::delete_by_id(group.id)
+            .exec(&state.db)
+            .await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn put_member(
+    State(state): State<ManagementState>,
+    Path((slug, project_slug)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let user = state.user(&headers).await?;
+    let group = authz::find_group(&state.db, &user, &slug)
+        .await?
+        .ok_or(ApiError::NotFound("Project group"))?;
+    let project = require_project(&state.db, &user, &project_slug).await?;
+    state
+        .db
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"INSERT INTO project_group_members (group_id, project_id, created_at)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (group_id, project_id) DO NOTHING"#,
+            [
+                group.id.into(),
+                project.id.into(),
+                Utc::now().fixed_offset().into(),
+            ],
+        ))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_member(
+    State(state): State<ManagementState>,
+    Path((slug, project_slug)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    let user = state.user(&headers).await?;
+    let group = authz::find_group(&state.db, &user, &slug).await?;
+    let project = authz::find_project(&state.db, &user, &project_slug).await?;
+    if let (Some(group), Some(project)) = (group, project) {
+        entities::ProjectGroupMember::delete_many()
+            .filter(project_group_member::Column::GroupId.eq(group.id))
+            .filter(project_group_member::Column::ProjectId.eq(project.id))
+            .exec(&state.db)
+            .await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn openapi_spec() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "application/json")], OPENAPI_SPEC)
+}
+
+/// Routes for the management API under `/api/v1`
+pub fn router<S: Clone + Send + Sync + 'static>(
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+    backpressure: Backpressure,
+) -> Router<S> {
+    Router::new()
+        .route("/api/v1/openapi.json", get(openapi_spec))
+        .route(
+            "/api/v1/projects/{slug}",
+            get(get_project).put(put_project).delete(delete_project),
+        )
+        .route("/api/v1/projects/{slug}/thresholds", get(list_thresholds))
+        .route(
+            "/api/v1/projects/{slug}/thresholds/{measure}",
+            get(get_threshold)
+                .put(put_threshold)
+                .delete(delete_threshold),
+        )
+        .route(
+            "/api/v1/projects/{slug}/channels",
+            get(list_channels).post(create_channel),
+        )
+        .route(
+            "/api/v1/projects/{slug}/channels/{id}",
+            get(get_channel).put(put_channel).delete(delete_channel),
+        )
+        .route("/api/v1/projects/{slug}/reports", post(create_report))
+        .route(
+            "/api/v1/groups/{slug}",
+            get(get_group).put(put_group).delete(delete_group),
+        )
+        .route(
+            "/api/v1/groups/{slug}/members/{project_slug}",
+            put(put_member).delete(delete_member),
+        )
+        .with_state(ManagementState {
+            db,
+            auth,
+            cache,
+            backpressure,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_spec() {
+        let spec: ProjectSpec = serde_json::from_str(r#"{"name": "Core"}"#).unwrap();
+        assert!(spec.redact_secrets);
+        let settings = spec.settings().unwrap();
+        assert_eq!(settings.noise_action, project::NoiseAction::Flag);
+        assert_eq!(settings.alert_after_reports, None);
+        assert_eq!(settings.default_branch, "main");
+
+        let spec: ProjectSpec = serde_json::from_str(
+            r#"{"name": "Core", "alert_after_reports": 3, "noise_action": "widen_thresholds"}"#,
+        )
+        .unwrap();
+        let settings = spec.settings().unwrap();
+        assert_eq!(settings.alert_after_reports, Some(3));
+        assert_eq!(settings.noise_action, project::NoiseAction::WidenThresholds);
+
+        // Typos in configuration are refused rather than ignored
+        assert!(serde_json::from_str::<ProjectSpec>(r#"{"name": "Core", "pubic": true}"#).is_err());
+        let spec: ProjectSpec =
+            serde_json::from_str(r#"{"name": "Core", "noise_action": "ignore"}"#).unwrap();
+        assert!(spec.settings().is_err());
+        let spec: ProjectSpec =
+            serde_json::from_str(r#"{"name": "Core", "expected_cadence_hours": 0}"#).unwrap();
+        assert!(spec.settings().is_err());
+        let spec: ProjectSpec =
+            serde_json::from_str(r#"{"name": "Core", "default_branch": "HEAD"}"#).unwrap();
+        assert!(spec.settings().is_err());
+    }
+
+    #[test]
+    fn test_threshold_spec() {
+        let spec: ThresholdSpec = serde_json::from_str(r#"{"upper_boundary": 10}"#).unwrap();
+        assert_eq!(spec.min_sample_size, 2);
+        assert!(spec.validate().is_ok());
+        for invalid in [
+            r#"{}"#,
+            r#"{"upper_boundary": -5}"#,
+            r#"{"lower_boundary": 5, "min_sample_size": 0}"#,
+        ] {
+            let spec: ThresholdSpec = serde_json::from_str(invalid).unwrap();
+            assert!(spec.validate().is_err(), "{}", invalid);
+        }
+    }
+
+    /// A value of the schema's type, with every property of objects
+    fn example(spec: &serde_json::Value, schema: &serde_json::Value) -> serde_json::Value {
+        if let Some(path) = schema["$ref"].as_str() {
+            let target = path
+                .trim_start_matches("#/")
+                .split('/')
+                .fold(spec, |node, key| &node[key]);
+            return example(spec, target);
+        }
+        if let Some(inner) = schema["allOf"].get(0) {
+            return example(spec, inner);
+        }
+        if let Some(first) = schema["enum"].get(0) {
+            return first.clone();
+        }
+        match (schema["type"].as_str(), schema["format"].as_str()) {
+            (Some("string"), Some("uuid")) => serde_json::json!(Uuid::nil()),
+            (Some("string"), Some("date-time")) => serde_json::json!("2026-10-16T12:00:00Z"),
+            (Some("string"), Some("uri")) => serde_json::json!("https://hooks.example.com/x"),
+            (Some("string"), _) => serde_json::json!("x"),
+            (Some("integer"), _) => serde_json::json!(1),
+            (Some("number"), _) => serde_json::json!(1.5),
+            (Some("boolean"), _) => serde_json::json!(true),
+            (Some("array"), _) => serde_json::json!([example(spec, &schema["items"])]),
+            _ => serde_json::Value::Object(
+                schema["properties"]
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), example(spec, property)))
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_openapi_request_bodies() {
+        let spec: serde_json::Value = serde_json::from_str(OPENAPI_SPEC).unwrap();
+        let body = |name: &str| {
+            let schema = &spec["components"]["schemas"][name];
+            assert!(schema.is_object(), "{} isn't described", name);
+            example(&spec, schema)
+        };
+        // Specs refuse unknown fields, so every documented one must exist
+        serde_json::from_value::<ProjectSpec>(body("ProjectSpec")).unwrap();
+        serde_json::from_value::<ThresholdSpec>(body("ThresholdSpec")).unwrap();
+        serde_json::from_value::<ChannelSpec>(body("ChannelSpec")).unwrap();
+        serde_json::from_value::<GroupSpec>(body("GroupSpec")).unwrap();
+        let report = serde_json::from_value::<ReportSpec>(body("ReportSpec")).unwrap();
+        assert_eq!(report.metrics.len(), 1);
+        assert_eq!(report.context.len(), 0);
+    }
+
+    #[test]
+    fn test_report_input() {
+        let spec: ReportSpec = serde_json::from_str(
+            r#"{"branch": "main", "testbed": "etl", "context": {"pipeline": "nightly"},
+                "metrics": [{"benchmark": "load", "measure": "latency", "value": 3.5}]}"#,
+        )
+        .unwrap();
+        let input = spec.into_input("pipeline".to_string());
+        assert_eq!(input.project_slug, "pipeline");
+        assert_eq!(input.context.unwrap()[0].value, "nightly");
+        assert_eq!(input.metrics[0].value, 3.5);
+
+        // Typos are refused rather than ignored
+        assert!(serde_json::from_str::<ReportSpec>(
+            r#"{"branch": "main", "testbed": "etl", "metric": []}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_error_body() {
+        let response = ApiError::NotFound("Project").into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(ApiError::Invalid(String::new()).code(), "invalid");
+        assert_eq!(
+            ApiError::NotFound("Notification channel").to_string(),
+            "Notification channel not found"
+        );
+
+        let refused: ApiError = async_graphql::Error::new("Invalid report id").into();
+        assert_eq!(refused.code(), "invalid");
+        let failed: ApiError = async_graphql::Error::from(DbErr::Custom("down".into())).into();
+        assert_eq!(failed.code(), "internal");
+
+        let overloaded = ApiError::Overloaded(Overload {
+            reason: "The job queue is backlogged",
+            retry_after_secs: 30,
+        })
+        .into_response();
+        assert_eq!(overloaded.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(overloaded.headers()[header::RETRY_AFTER], "30");
+
+        let mut headers = HeaderMap::new();
+        assert!(idempotency_key(&headers).is_err());
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static(" abc "));
+        assert_eq!(idempotency_key(&headers).unwrap(), "abc");
+    }
+}
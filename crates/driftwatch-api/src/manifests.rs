@@ -0,0 +1,91 @@
+//! Suite manifests: the benchmarks a run was meant to produce, submitted
+//! with its report. Benchmarks in the manifest that the report has no
+//! metrics for crashed or were filtered out, and are flagged as missing
+//! instead of leaving an unnoticed gap in their series.
+
+use std::collections::BTreeSet;
+
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement};
+use uuid::Uuid;
+
+/// Most benchmarks accepted in one manifest
+pub const MAX_EXPECTED_BENCHMARKS: usize = 50_000;
+
+/// Checks, trims and deduplicates the benchmark names of a manifest
+pub fn validate_expected_benchmarks(names: Vec<String>) -> Result<Vec<String>, String> {
+    let names: BTreeSet<String> = names.iter().map(|n| n.trim().to_string()).collect();
+    if names.len() > MAX_EXPECTED_BENCHMARKS {
+        return Err(format!(
+            "Manifests can list at most {} benchmarks",
+            MAX_EXPECTED_BENCHMARKS
+        ));
+    }
+    if names.contains("") {
+        return Err("Expected benchmark names can't be empty".to_string());
+    }
+    Ok(names.into_iter().collect())
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ExpectedRow {
+    name: String,
+    reported: bool,
+}
+
+/// Benchmarks in the report's manifest that it has no metrics for, sorted
+/// by name. `None` when the report was submitted without a manifest.
+pub async fn missing_benchmarks<C: ConnectionTrait>(
+    db: &C,
+    report_id: Uuid,
+) -> Result<Option<Vec<String>>, DbErr> {
+    let rows = ExpectedRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT e.name,
+                  EXISTS (
+                      SELECT 1 FROM metrics m
+                      JOIN benchmarks b ON b.id = m.benchmark_id
+                      WHERE m.report_id = e.report_id AND b.name = e.name
+                  ) AS reported
+           FROM expected_benchmarks e
+           WHERE e.report_id = $1
+           ORDER BY e.name"#,
+        [report_id.into()],
+    ))
+    .all(db)
+    .await?;
+    if rows.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        rows.into_iter()
+            .filter(|row| !row.reported)
+            .map(|row| row.name)
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_expected_benchmarks() {
+        assert_eq!(
+            validate_expected_benchmarks(vec![
+                "parse/large ".into(),
+                "fib 20".into(),
+                "parse/large".into()
+            ])
+            .unwrap(),
+            vec!["fib 20", "parse/large"]
+        );
+        assert!(validate_expected_benchmarks(vec![" ".into()]).is_err());
+        assert!(validate_expected_benchmarks(
+            (0..=MAX_EXPECTED_BENCHMARKS)
+                .map(|i| i.to_string())
+                .collect()
+        )
+        .is_err());
+        assert_eq!(validate_expected_benchmarks(Vec::new()).unwrap().len(), 0);
+    }
+}
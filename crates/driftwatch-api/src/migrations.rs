@@ -1,8 +1,446 @@
-use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr, Statement};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, Set, Statement, TransactionTrait,
+};
+
+use crate::entities::{self, project};
+use crate::secrets;
+
+/// A change to the schema, as asked for with `driftwatch migrate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+    /// Apply pending migrations, all of them when `None`
+    Up(Option<u32>),
+    /// Roll back this many applied migrations, newest first
+    Down(u32),
+    /// Roll back this many migrations and apply them again
+    Redo(u32),
+}
+
+/// A known migration and when it was applied
+#[derive(Debug, Clone)]
+pub struct MigrationInfo {
+    pub name: String,
+    /// `None` while pending
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// Every known migration, oldest first, followed by any the database has
+/// applied that this build doesn't know about
+pub async fn status(db: &DatabaseConnection) -> Result<Vec<MigrationInfo>, DbErr> {
+    Migrator::install(db).await?;
+    let applied = Migrator::get_migration_models(db).await?;
+    let applied_at = |name: &str| {
+        applied
+            .iter()
+            .find(|m| m.version == name)
+            .and_then(|m| DateTime::from_timestamp(m.applied_at, 0))
+    };
+    let mut migrations: Vec<MigrationInfo> = Migrator::migrations()
+        .iter()
+        .map(|m| MigrationInfo {
+            name: m.name().to_string(),
+            applied_at: applied_at(m.name()),
+        })
+        .collect();
+    for model in &applied {
+        if !migrations.iter().any(|m| m.name == model.version) {
+            migrations.push(MigrationInfo {
+                name: model.version.clone(),
+                applied_at: DateTime::from_timestamp(model.applied_at, 0),
+            });
+        }
+    }
+    Ok(migrations)
+}
+
+/// Number of migrations not yet applied
+pub async fn pending(db: &DatabaseConnection) -> Result<usize, DbErr> {
+    Migrator::install(db).await?;
+    Ok(Migrator::get_pending_migrations(db).await?.len())
+}
+
+/// Runs `plan` in one transaction, so a failing migration leaves the
+/// schema as it was
+pub async fn migrate(db: &DatabaseConnection, plan: Plan) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    match plan {
+        Plan::Up(steps) => Migrator::up(&txn, steps).await?,
+        Plan::Down(steps) => Migrator::down(&txn, Some(steps)).await?,
+        Plan::Redo(steps) => {
+            Migrator::down(&txn, Some(steps)).await?;
+            Migrator::up(&txn, Some(steps)).await?;
+        }
+    }
+    txn.commit().await?;
+    if matches!(plan, Plan::Up(_)) {
+        apply_legacy_fixes(db).await?;
+    }
+    Ok(())
+}
+
+/// Records every statement `db` sends, leaving out migration bookkeeping
+/// and read-only checks
+fn capture_statements(db: &mut DatabaseConnection) -> Arc<Mutex<Vec<String>>> {
+    let statements = Arc::new(Mutex::new(Vec::new()));
+    let captured = statements.clone();
+    db.set_metric_callback(move |info| {
+        let sql = info.statement.to_string();
+        let keyword = sql.split_whitespace().next().unwrap_or_default();
+        if !keyword.eq_ignore_ascii_case("SELECT") && !sql.contains("seaql_migrations") {
+            captured.lock().unwrap().push(sql);
+        }
+    });
+    statements
+}
+
+/// The statements `plan` would run. They really run, inside a transaction
+/// that is rolled back, so the SQL is exactly what `migrate` would send and
+/// a migration that would fail fails here too. Migration bookkeeping and
+/// read-only checks are left out.
+pub async fn dry_run(mut db: DatabaseConnection, plan: Plan) -> Result<Vec<String>, DbErr> {
+    let statements = capture_statements(&mut db);
+
+    let txn = db.begin().await?;
+    let result = match plan {
+        Plan::Up(steps) => Migrator::up(&txn, steps).await,
+        Plan::Down(steps) => Migrator::down(&txn, Some(steps)).await,
+        Plan::Redo(steps) => match Migrator::down(&txn, Some(steps)).await {
+            Ok(()) => Migrator::up(&txn, Some(steps)).await,
+            Err(e) => Err(e),
+        },
+    };
+    txn.rollback().await?;
+    result?;
+
+    let statements = statements.lock().unwrap().clone();
+    Ok(statements)
+}
+
+/// Tables with more rows than this are too large to lock during a deploy
+pub const DEFAULT_MAX_LOCKED_ROWS: i64 = 100_000;
+
+/// Something in a migration that is unsafe while the previous release is
+/// still serving traffic, as it is during a blue/green deploy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hazard {
+    /// `None` when the statement can't be analysed
+    pub table: Option<String>,
+    pub reason: &'static str,
+    /// Breaks the previous release however small the table is. Otherwise
+    /// the statement only holds a lock for as long as the table takes to
+    /// scan or rewrite.
+    pub breaking: bool,
+}
+
+/// A hazard found in a pending migration
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub hazard: Hazard,
+    pub statement: String,
+    /// Estimated size of the table, `None` for tables the pending
+    /// migrations create and tables that don't exist yet
+    pub rows: Option<i64>,
+    /// Whether the hazard rules out running the migration online
+    pub blocking: bool,
+}
+
+/// A pending migration and what makes it unsafe to run online
+#[derive(Debug, Clone)]
+pub struct MigrationCheck {
+    pub name: String,
+    pub findings: Vec<Finding>,
+}
+
+impl MigrationCheck {
+    /// Safe to apply while the previous release keeps running
+    pub fn online(&self) -> bool {
+        !self.findings.iter().any(|f| f.blocking)
+    }
+}
+
+fn is_keyword(word: &str, keyword: &str) -> bool {
+    word.trim_end_matches([',', ';', '('])
+        .eq_ignore_ascii_case(keyword)
+}
+
+/// The unquoted, unqualified table name in `word`
+fn table_name(word: &str) -> String {
+    let word = word.trim_end_matches([',', ';', '(']);
+    let name = word.rsplit('.').next().unwrap_or(word);
+    name.trim_matches('"').to_string()
+}
+
+/// The table named after the words in `keywords`, skipping modifiers such
+/// as `IF EXISTS`
+fn table_after(words: &[&str], keywords: &[&str]) -> Option<String> {
+    let start = words
+        .windows(keywords.len())
+        .position(|w| w.iter().zip(keywords).all(|(a, b)| is_keyword(a, b)))?;
+    words[start + keywords.len()..]
+        .iter()
+        .find(|w| {
+            !["IF", "NOT", "EXISTS", "ONLY", "CONCURRENTLY", "TABLE"]
+                .iter()
+                .any(|k| is_keyword(w, k))
+        })
+        .map(|w| table_name(w))
+}
+
+/// The table `sql` creates, if it is a `CREATE TABLE`
+pub fn created_table(sql: &str) -> Option<String> {
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    match words.as_slice() {
+        [create, table, ..] if is_keyword(create, "CREATE") && is_keyword(table, "TABLE") => {
+            table_after(&words, &["CREATE", "TABLE"])
+        }
+        _ => None,
+    }
+}
+
+/// What in `sql` is unsafe while the previous release is running. Works
+/// from the SQL alone, so it errs on the side of reporting a hazard.
+pub fn classify(sql: &str) -> Vec<Hazard> {
+    let words: Vec<&str> = sql.split_whitespace().collect();
+    let upper = words.join(" ").to_ascii_uppercase();
+    let has = |phrase: &str| upper.contains(phrase);
+    let starts = |keywords: &[&str]| {
+        words.len() >= keywords.len() && words.iter().zip(keywords).all(|(w, k)| is_keyword(w, k))
+    };
+    let hazard = |table: Option<String>, reason, breaking| Hazard {
+        table,
+        reason,
+        breaking,
+    };
+
+    let mut hazards = Vec::new();
+    if starts(&["CREATE", "INDEX"]) || starts(&["CREATE", "UNIQUE", "INDEX"]) {
+        if !has(" CONCURRENTLY ") {
+            hazards.push(hazard(
+                table_after(&words, &["ON"]),
+                "builds an index while blocking writes to the table",
+                false,
+            ));
+        }
+    } else if starts(&["ALTER", "TABLE"]) {
+        let table = table_after(&words, &["ALTER", "TABLE"]);
+        if has(" DROP COLUMN ") {
+            hazards.push(hazard(
+                table.clone(),
+                "drops a column the previous release may still use",
+                true,
+            ));
+        }
+        if has(" RENAME ") {
+            hazards.push(hazard(
+                table.clone(),
+                "renames something the previous release uses",
+                true,
+            ));
+        }
+        if has(" ADD COLUMN ") && has(" NOT NULL") && !has(" DEFAULT ") {
+            hazards.push(hazard(
+                table.clone(),
+                "adds a NOT NULL column without a default, so the previous release can't insert",
+                true,
+            ));
+        }
+        if has(" ALTER COLUMN ") && has(" TYPE ") {
+            hazards.push(hazard(
+                table.clone(),
+                "changes a column type, rewriting the table under an exclusive lock",
+                false,
+            ));
+        }
+        if has(" SET NOT NULL") {
+            hazards.push(hazard(
+                table.clone(),
+                "scans the table under an exclusive lock to set NOT NULL",
+                false,
+            ));
+        }
+        let adds_constraint = [
+            " ADD CONSTRAINT ",
+            " ADD FOREIGN KEY",
+            " ADD PRIMARY KEY",
+            " ADD UNIQUE",
+            " ADD CHECK",
+        ]
+        .iter()
+        .any(|phrase| has(phrase));
+        if adds_constraint && !has(" NOT VALID") {
+            hazards.push(hazard(
+                table,
+                "validates a constraint against every row while holding a lock",
+                false,
+            ));
+        }
+    } else if starts(&["DROP", "TABLE"]) {
+        hazards.push(hazard(
+            table_after(&words, &["DROP", "TABLE"]),
+            "drops a table the previous release may still use",
+            true,
+        ));
+    } else if starts(&["TRUNCATE"]) {
+        hazards.push(hazard(
+            table_after(&words, &["TRUNCATE"]),
+            "deletes every row while the previous release may still read them",
+            true,
+        ));
+    } else if starts(&["UPDATE"]) || starts(&["DELETE", "FROM"]) {
+        if !has(" WHERE ") {
+            let table = if starts(&["UPDATE"]) {
+                table_after(&words, &["UPDATE"])
+            } else {
+                table_after(&words, &["DELETE", "FROM"])
+            };
+            hazards.push(hazard(
+                table,
+                "changes every row in one transaction; schedule a backfill instead",
+                false,
+            ));
+        }
+    } else if starts(&["LOCK"]) {
+        hazards.push(hazard(
+            table_after(&words, &["LOCK"]),
+            "locks the table explicitly",
+            false,
+        ));
+    } else if starts(&["DO"]) {
+        hazards.push(hazard(
+            None,
+            "runs procedural code that can't be checked",
+            false,
+        ));
+    }
+    hazards
+}
+
+/// Whether `hazard` rules out running a migration online, given the tables
+/// created earlier in the same deploy and the size of the rest
+fn is_blocking(
+    hazard: &Hazard,
+    created: &HashSet<String>,
+    rows: Option<i64>,
+    max_rows: i64,
+) -> bool {
+    match &hazard.table {
+        None => true,
+        Some(table) if created.contains(table) => false,
+        Some(_) => hazard.breaking || rows.unwrap_or(0) > max_rows,
+    }
+}
+
+/// Checks the statements of one migration, adding the tables it creates to
+/// `created` for the migrations after it
+fn assess(
+    name: &str,
+    statements: &[String],
+    created: &mut HashSet<String>,
+    sizes: &HashMap<String, i64>,
+    max_rows: i64,
+) -> MigrationCheck {
+    let mut findings = Vec::new();
+    for statement in statements {
+        if let Some(table) = created_table(statement) {
+            created.insert(table);
+            continue;
+        }
+        for hazard in classify(statement) {
+            let rows = hazard
+                .table
+                .as_ref()
+                .filter(|t| !created.contains(*t))
+                .and_then(|t| sizes.get(t).copied());
+            findings.push(Finding {
+                blocking: is_blocking(&hazard, created, rows, max_rows),
+                hazard,
+                statement: statement.clone(),
+                rows,
+            });
+        }
+    }
+    MigrationCheck {
+        name: name.to_string(),
+        findings,
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct TableSize {
+    name: String,
+    row_estimate: i64,
+}
+
+/// Estimated rows in each table, from the planner's statistics
+async fn table_sizes(db: &DatabaseConnection) -> Result<HashMap<String, i64>, DbErr> {
+    let sizes = TableSize::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        r#"SELECT c.relname AS name,
+                  GREATEST(c.reltuples::bigint, COALESCE(s.n_live_tup, 0)) AS row_estimate
+           FROM pg_class c
+           JOIN pg_namespace n ON n.oid = c.relnamespace
+           LEFT JOIN pg_stat_user_tables s ON s.relid = c.oid
+           WHERE c.relkind IN ('r', 'p') AND n.nspname = current_schema()"#,
+    ))
+    .all(db)
+    .await?;
+    Ok(sizes
+        .into_iter()
+        .map(|t| (t.name, t.row_estimate))
+        .collect())
+}
+
+/// Checks whether each pending migration can run while the previous
+/// release keeps serving traffic. Locks count against tables with more
+/// than `max_rows` rows. The migrations run in a transaction that is rolled
+/// back, as in `dry_run`, to see the SQL each would send.
+pub async fn check(
+    mut db: DatabaseConnection,
+    max_rows: i64,
+) -> Result<Vec<MigrationCheck>, DbErr> {
+    Migrator::install(&db).await?;
+    let sizes = table_sizes(&db).await?;
+    let pending: Vec<String> = Migrator::get_pending_migrations(&db)
+        .await?
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+    let statements = capture_statements(&mut db);
+
+    let txn = db.begin().await?;
+    let mut planned = Vec::new();
+    for name in pending {
+        if let Err(e) = Migrator::up(&txn, Some(1)).await {
+            txn.rollback().await?;
+            return Err(e);
+        }
+        planned.push((name, std::mem::take(&mut *statements.lock().unwrap())));
+    }
+    txn.rollback().await?;
+
+    let mut created = HashSet::new();
+    Ok(planned
+        .iter()
+        .map(|(name, statements)| assess(name, statements, &mut created, &sizes, max_rows))
+        .collect())
+}
 
 pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
     tracing::info!("Running database migrations...");
+    migrate(db, Plan::Up(None)).await?;
+    tracing::info!("Database migrations complete");
+    Ok(())
+}
 
+/// Idempotent fixes for databases created before these columns and enum
+/// values had migrations of their own
+async fn apply_legacy_fixes(db: &DatabaseConnection) -> Result<(), DbErr> {
     let migrations = vec![
         "ALTER TABLE branches ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ DEFAULT NOW()",
         "ALTER TABLE testbeds ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ DEFAULT NOW()",
@@ -26,6 +464,9 @@ pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
                 ALTER TABLE alerts ALTER COLUMN status SET DEFAULT 'active'::alert_status;
             END IF;
         END $$"#,
+        "ALTER TYPE alert_status ADD VALUE IF NOT EXISTS 'investigating'",
+        "ALTER TYPE alert_status ADD VALUE IF NOT EXISTS 'wont_fix'",
+        "ALTER TYPE alert_status ADD VALUE IF NOT EXISTS 'unconfirmed'",
     ];
 
     for sql in migrations {
@@ -35,7 +476,220 @@ pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
         ))
         .await?;
     }
+    Ok(())
+}
 
-    tracing::info!("Database migrations complete");
+/// Opt-in conversion of `metrics` into a TimescaleDB hypertable partitioned
+/// by `created_at`, enabled with `METRICS_HYPERTABLE=true`.
+///
+/// Hypertables need the partition column in every unique constraint and
+/// can't be the target of foreign keys, so the primary key becomes
+/// `(id, created_at)` and the `metric_id` cascades of `alerts` and
+/// `threshold_evaluations` are replaced by a trigger. Safe to run
+/// repeatedly.
+pub async fn convert_metrics_to_hypertable(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let backend = db.get_database_backend();
+
+    let already = db
+        .query_one(Statement::from_string(
+            backend,
+            "SELECT 1 FROM pg_extension WHERE extname = 'timescaledb'".to_string(),
+        ))
+        .await?
+        .is_some()
+        && db
+            .query_one(Statement::from_string(
+                backend,
+                "SELECT 1 FROM timescaledb_information.hypertables WHERE hypertable_name = 'metrics'"
+                    .to_string(),
+            ))
+            .await?
+            .is_some();
+
+    if already {
+        return Ok(());
+    }
+
+    tracing::info!("Converting metrics table to a TimescaleDB hypertable...");
+
+    let statements = vec![
+        "CREATE EXTENSION IF NOT EXISTS timescaledb",
+        r#"DO $$
+        DECLARE fk record;
+        BEGIN
+            FOR fk IN
+                SELECT conrelid::regclass AS tbl, conname FROM pg_constraint
+                WHERE contype = 'f' AND confrelid = 'metrics'::regclass
+            LOOP
+                EXECUTE format('ALTER TABLE %s DROP CONSTRAINT %I', fk.tbl, fk.conname);
+            END LOOP;
+        END $$"#,
+        "ALTER TABLE metrics DROP CONSTRAINT IF EXISTS metrics_pkey",
+        "ALTER TABLE metrics ADD PRIMARY KEY (id, created_at)",
+        "SELECT create_hypertable('metrics', 'created_at', migrate_data => true, if_not_exists => true)",
+        r#"CREATE OR REPLACE FUNCTION delete_metric_alerts() RETURNS trigger AS $$
+        BEGIN
+            DELETE FROM alerts WHERE metric_id = OLD.id;
+            DELETE FROM threshold_evaluations WHERE metric_id = OLD.id;
+            RETURN OLD;
+        END $$ LANGUAGE plpgsql"#,
+        "DROP TRIGGER IF EXISTS metrics_delete_alerts ON metrics",
+        r#"CREATE TRIGGER metrics_delete_alerts AFTER DELETE ON metrics
+        FOR EACH ROW EXECUTE FUNCTION delete_metric_alerts()"#,
+    ];
+
+    for sql in statements {
+        db.execute(Statement::from_string(backend, sql.to_string()))
+            .await?;
+    }
+
+    tracing::info!("Metrics hypertable ready");
     Ok(())
 }
+
+/// Encrypts GitHub tokens stored before `DRIFTWATCH_SECRET_KEY` was set.
+/// Without a key they are left as they are and still used. Safe to run
+/// repeatedly.
+pub async fn encrypt_github_tokens(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let plaintext: Vec<project::Model> = entities::Project::find()
+        .filter(project::Column::GithubToken.is_not_null())
+        .filter(project::Column::GithubToken.not_like("enc:%"))
+        .all(db)
+        .await?;
+    if plaintext.is_empty() {
+        return Ok(());
+    }
+
+    let Some(key) = secrets::key() else {
+        tracing::warn!(
+            "{} projects have unencrypted GitHub tokens; set DRIFTWATCH_SECRET_KEY to encrypt them",
+            plaintext.len()
+        );
+        return Ok(());
+    };
+
+    tracing::info!("Encrypting {} stored GitHub tokens...", plaintext.len());
+    for project in plaintext {
+        let Some(token) = project.github_token.clone() else {
+            continue;
+        };
+        let sealed = key.seal(&token, &secrets::github_token_context(project.id));
+        let mut active: project::ActiveModel = project.into();
+        active.github_token = Set(Some(sealed));
+        active.update(db).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables(sql: &str) -> Vec<(Option<String>, bool)> {
+        classify(sql)
+            .into_iter()
+            .map(|h| (h.table, h.breaking))
+            .collect()
+    }
+
+    #[test]
+    fn test_classify() {
+        let reports = || Some("reports".to_string());
+
+        assert!(classify(r#"CREATE TABLE IF NOT EXISTS "things" ("id" uuid)"#).is_empty());
+        assert!(classify(r#"CREATE INDEX CONCURRENTLY "idx" ON "reports" ("x")"#).is_empty());
+        assert!(classify(r#"ALTER TABLE "reports" ADD COLUMN "x" text"#).is_empty());
+        assert!(
+            classify(r#"ALTER TABLE "reports" ADD COLUMN "x" bool NOT NULL DEFAULT FALSE"#)
+                .is_empty()
+        );
+        assert!(classify("UPDATE reports SET x = 1 WHERE id = $1").is_empty());
+        assert!(classify(
+            r#"ALTER TABLE "alerts" ADD CONSTRAINT "fk" FOREIGN KEY ("report_id") REFERENCES "reports" ("id") NOT VALID"#
+        )
+        .is_empty());
+
+        assert_eq!(
+            tables(r#"CREATE UNIQUE INDEX IF NOT EXISTS "idx" ON "public"."reports" ("x")"#),
+            [(reports(), false)]
+        );
+        assert_eq!(
+            tables(r#"ALTER TABLE "reports" ALTER COLUMN "x" TYPE bigint"#),
+            [(reports(), false)]
+        );
+        assert_eq!(
+            tables("ALTER TABLE reports ALTER COLUMN x SET NOT NULL"),
+            [(reports(), false)]
+        );
+        assert_eq!(
+            tables(r#"ALTER TABLE "reports" ADD COLUMN "x" text NOT NULL"#),
+            [(reports(), true)]
+        );
+        assert_eq!(
+            tables(r#"ALTER TABLE "reports" DROP COLUMN "x""#),
+            [(reports(), true)]
+        );
+        assert_eq!(
+            tables("ALTER TABLE reports RENAME COLUMN x TO y"),
+            [(reports(), true)]
+        );
+        assert_eq!(
+            tables(
+                r#"ALTER TABLE "alerts" ADD CONSTRAINT "fk" FOREIGN KEY ("report_id") REFERENCES "reports" ("id")"#
+            ),
+            [(Some("alerts".to_string()), false)]
+        );
+        assert_eq!(tables("DROP TABLE IF EXISTS reports"), [(reports(), true)]);
+        assert_eq!(tables("DELETE FROM reports"), [(reports(), false)]);
+        assert_eq!(tables("TRUNCATE TABLE reports"), [(reports(), true)]);
+        assert_eq!(tables("DO $$ BEGIN PERFORM 1; END $$"), [(None, false)]);
+    }
+
+    #[test]
+    fn test_assess() {
+        let sizes = HashMap::from([("reports".to_string(), 1_000_000), ("tags".to_string(), 10)]);
+        let mut created = HashSet::new();
+        let statements = |sql: &[&str]| sql.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let first = assess(
+            "m1",
+            &statements(&[
+                r#"CREATE TABLE "things" ("id" uuid, "name" text)"#,
+                r#"CREATE INDEX "idx_things_name" ON "things" ("name")"#,
+                r#"CREATE INDEX "idx_tags_name" ON "tags" ("name")"#,
+            ]),
+            &mut created,
+            &sizes,
+            DEFAULT_MAX_LOCKED_ROWS,
+        );
+        assert!(first.online());
+        assert_eq!(first.findings.len(), 2);
+        assert_eq!(first.findings[0].rows, None);
+        assert_eq!(first.findings[1].rows, Some(10));
+
+        // Tables from earlier migrations in the same deploy are new too
+        let second = assess(
+            "m2",
+            &statements(&[
+                r#"ALTER TABLE "things" DROP COLUMN "name""#,
+                r#"CREATE INDEX "idx_reports_x" ON "reports" ("x")"#,
+                r#"ALTER TABLE "tags" RENAME COLUMN "name" TO "label""#,
+            ]),
+            &mut created,
+            &sizes,
+            DEFAULT_MAX_LOCKED_ROWS,
+        );
+        assert!(!second.online());
+        let blocking: Vec<bool> = second.findings.iter().map(|f| f.blocking).collect();
+        assert_eq!(blocking, [false, true, true]);
+
+        let unknown = assess(
+            "m3",
+            &statements(&["DO $$ BEGIN PERFORM 1; END $$"]),
+            &mut created,
+            &sizes,
+            DEFAULT_MAX_LOCKED_ROWS,
+        );
+        assert!(!unknown.online());
+    }
+}
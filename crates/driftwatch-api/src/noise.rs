@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait, QueryFilter, Statement,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, benchmark_noise, project, threshold};
+use crate::error_reports;
+use crate::evaluation::BASELINE_WINDOW;
+use crate::jobs::JobRegistry;
+
+/// How often noise scores are recomputed from raw metrics.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Job kind of the periodic noise score refresh.
+pub const REFRESH_NOISE_JOB: &str = "refresh_noise_scores";
+
+/// Widened boundaries are at least this many standard deviations, so normal
+/// run-to-run variation of a noisy benchmark stays inside them.
+pub const NOISE_WIDEN_FACTOR: f64 = 2.0;
+
+/// Baseline sample size required for noisy benchmarks when the project asks
+/// for more samples.
+pub const NOISY_MIN_SAMPLE_SIZE: i32 = 20;
+
+/// Registers the periodic noise score refresh.
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.schedule(REFRESH_NOISE_JOB, REFRESH_INTERVAL, |db| async move {
+        match refresh_noise_scores(&db).await {
+            Ok(updated) => tracing::debug!("Refreshed {} noise scores", updated),
+            Err(e) => {
+                tracing::error!("Noise score refresh failed: {}", e);
+                error_reports::capture(&e.into(), "noise", &[]);
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Recomputes mean, standard deviation and coefficient of variation over the
+/// last [`BASELINE_WINDOW`] values of every benchmark/measure/testbed, across
+/// branches. Series with fewer than two values are left out.
+pub async fn refresh_noise_scores<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"INSERT INTO benchmark_noise (
+                   id, project_id, benchmark_id, measure_id, testbed_id, sample_size,
+                   mean, std_dev, coefficient_of_variation, updated_at
+               )
+               SELECT gen_random_uuid(), project_id, benchmark_id, measure_id, testbed_id,
+                      COUNT(*), AVG(value), STDDEV_SAMP(value),
+                      STDDEV_SAMP(value) / ABS(AVG(value)) * 100, NOW()
+               FROM (
+                   SELECT r.project_id, m.benchmark_id, m.measure_id, r.testbed_id, m.value,
+                          ROW_NUMBER() OVER (
+                              PARTITION BY m.benchmark_id, m.measure_id, r.testbed_id
+                              ORDER BY m.created_at DESC
+                          ) AS recency
+                   FROM metrics m
+                   JOIN reports r ON r.id = m.report_id
+                   WHERE r.finalized AND NOT r.excluded
+               ) recent
+               WHERE recency <= $1
+               GROUP BY project_id, benchmark_id, measure_id, testbed_id
+               HAVING COUNT(*) >= 2 AND AVG(value) <> 0
+               ON CONFLICT (benchmark_id, measure_id, testbed_id) DO UPDATE SET
+                   sample_size = EXCLUDED.sample_size,
+                   mean = EXCLUDED.mean,
+                   std_dev = EXCLUDED.std_dev,
+                   coefficient_of_variation = EXCLUDED.coefficient_of_variation,
+                   updated_at = EXCLUDED.updated_at"#,
+            [(BASELINE_WINDOW as i64).into()],
+        ))
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Coefficients of variation of the benchmarks on `testbed_id` that exceed
+/// the project's noise limit, keyed by benchmark and measure. Empty when the
+/// project doesn't adjust evaluation for noise.
+pub async fn noisy_series<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+    testbed_id: Uuid,
+) -> Result<HashMap<(Uuid, Uuid), f64>, DbErr> {
+    let Some(limit) = project.noise_cv_limit else {
+        return Ok(HashMap::new());
+    };
+    if project.noise_action == project::NoiseAction::Flag {
+        return Ok(HashMap::new());
+    }
+
+    let scores = entities::BenchmarkNoise::find()
+        .filter(benchmark_noise::Column::ProjectId.eq(project.id))
+        .filter(benchmark_noise::Column::TestbedId.eq(testbed_id))
+        .filter(benchmark_noise::Column::CoefficientOfVariation.gt(limit))
+        .all(db)
+        .await?;
+
+    Ok(scores
+        .into_iter()
+        .map(|s| ((s.benchmark_id, s.measure_id), s.coefficient_of_variation))
+        .collect())
+}
+
+/// The threshold to evaluate a noisy benchmark against: boundaries widened to
+/// [`NOISE_WIDEN_FACTOR`] standard deviations, or a larger minimum sample
+/// size, depending on the project's setting.
+pub fn adjust_for_noise(
+    threshold: &threshold::Model,
+    coefficient_of_variation: f64,
+    action: &project::NoiseAction,
+) -> threshold::Model {
+    let mut adjusted = threshold.clone();
+    match action {
+        project::NoiseAction::Flag => {}
+        project::NoiseAction::WidenThresholds => {
+            let floor = NOISE_WIDEN_FACTOR * coefficient_of_variation;
+            adjusted.upper_boundary = threshold.upper_boundary.map(|b| b.max(floor));
+            adjusted.lower_boundary = threshold.lower_boundary.map(|b| b.max(floor));
+        }
+        project::NoiseAction::RequireMoreSamples => {
+            adjusted.min_sample_size = threshold.min_sample_size.max(NOISY_MIN_SAMPLE_SIZE);
+        }
+    }
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threshold(upper: Option<f64>, lower: Option<f64>, min_sample_size: i32) -> threshold::Model {
+        let now = chrono::Utc::now().fixed_offset();
+        threshold::Model {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            measure_id: Uuid::new_v4(),
+            branch_id: None,
+            testbed_id: None,
+            upper_boundary: upper,
+            lower_boundary: lower,
+            min_sample_size,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_widen_keeps_larger_boundaries_and_unset_sides() {
+        let t = threshold(Some(10.0), None, 2);
+        let adjusted = adjust_for_noise(&t, 8.0, &project::NoiseAction::WidenThresholds);
+        assert_eq!(adjusted.upper_boundary, Some(16.0));
+        assert_eq!(adjusted.lower_boundary, None);
+        assert_eq!(adjusted.min_sample_size, 2);
+
+        let adjusted = adjust_for_noise(&t, 3.0, &project::NoiseAction::WidenThresholds);
+        assert_eq!(adjusted.upper_boundary, Some(10.0));
+    }
+
+    #[test]
+    fn test_require_more_samples() {
+        let adjusted = adjust_for_noise(
+            &threshold(Some(10.0), Some(5.0), 2),
+            30.0,
+            &project::NoiseAction::RequireMoreSamples,
+        );
+        assert_eq!(adjusted.min_sample_size, NOISY_MIN_SAMPLE_SIZE);
+        assert_eq!(adjusted.upper_boundary, Some(10.0));
+        assert_eq!(adjusted.lower_boundary, Some(5.0));
+
+        let adjusted = adjust_for_noise(
+            &threshold(Some(10.0), None, 25),
+            30.0,
+            &project::NoiseAction::RequireMoreSamples,
+        );
+        assert_eq!(adjusted.min_sample_size, 25);
+    }
+}
@@ -0,0 +1,582 @@
+//! Delivery of project notifications, weekly digests and report and alert
+//! events, to the project's channels: plain JSON webhooks and Slack
+//! incoming webhooks.
+//!
+//! Channel URLs usually carry a credential in their path, so they are
+//! sealed like GitHub tokens and only their host is ever shown back.
+//! Deliveries run as jobs, one per channel, so a failing endpoint is
+//! retried without holding up the others.
+//!
+//! A channel may replace its default payload with a template, so no-code
+//! tools such as Zapier or IFTTT get the fields they expect. Templates are
+//! JSON with handlebars-style placeholders: `{{benchmark}}` inserts a value
+//! escaped for use inside a JSON string, `{{{percent_change}}}` inserts it
+//! as raw JSON, and dotted paths reach into nested values.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::notification_channel::{self, ChannelKind};
+use crate::entities::{self, digest, project};
+use crate::events::{self, Event};
+use crate::jobs::{self, JobRegistry};
+use crate::{links, secrets};
+
+/// Job kind that posts a digest to one channel.
+pub const DELIVER_DIGEST_JOB: &str = "deliver_digest";
+
+/// Job kind that posts a report or alert event to one channel.
+pub const DELIVER_EVENT_JOB: &str = "deliver_event";
+
+pub const DIGEST_EVENT: &str = "digest";
+
+/// Events a channel can subscribe to
+pub const CHANNEL_EVENTS: [&str; 4] = [
+    DIGEST_EVENT,
+    events::REPORT_CREATED,
+    events::ALERT_OPENED,
+    events::ALERT_RESOLVED,
+];
+
+/// How long a channel gets to accept a message.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SLACK_WEBHOOK_PREFIX: &str = "https://hooks.slack.com/";
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(DELIVER_DIGEST_JOB, |db, payload| async move {
+        let channel_id: Uuid = serde_json::from_value(payload["channel_id"].clone())?;
+        let digest_id: Uuid = serde_json::from_value(payload["digest_id"].clone())?;
+        deliver_digest(&db, channel_id, digest_id).await
+    });
+    registry.register(DELIVER_EVENT_JOB, |db, payload| async move {
+        let channel_id: Uuid = serde_json::from_value(payload["channel_id"].clone())?;
+        let event: Event = serde_json::from_value(payload["event"].clone())?;
+        deliver_event(&db, channel_id, &event).await
+    });
+}
+
+/// Checks a URL before it is stored as a channel.
+pub fn validate_url(kind: ChannelKind, url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| format!("Invalid URL: {}", url))?;
+    if parsed.scheme() != "https" && parsed.scheme() != "http" {
+        return Err("Channel URLs must be http or https".to_string());
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("Invalid URL: {}", url));
+    }
+    if kind == ChannelKind::Slack && !url.starts_with(SLACK_WEBHOOK_PREFIX) {
+        return Err(format!(
+            "Slack channels take an incoming webhook URL starting with {}",
+            SLACK_WEBHOOK_PREFIX
+        ));
+    }
+    Ok(())
+}
+
+/// Binds a sealed URL to its channel.
+pub fn url_context(channel_id: Uuid) -> Vec<u8> {
+    format!("notification_channels.url:{}", channel_id).into_bytes()
+}
+
+/// Checks the events a channel subscribes to, dropping repeats.
+pub fn validate_events(names: &[String]) -> Result<Vec<String>, String> {
+    let mut events: Vec<String> = Vec::new();
+    for name in names {
+        let name = name.trim();
+        if !CHANNEL_EVENTS.contains(&name) {
+            return Err(format!(
+                "Unknown event {}; expected one of {}",
+                name,
+                CHANNEL_EVENTS.join(", ")
+            ));
+        }
+        if !events.iter().any(|e| e == name) {
+            events.push(name.to_string());
+        }
+    }
+    if events.is_empty() {
+        return Err("A channel must subscribe to at least one event".to_string());
+    }
+    Ok(events)
+}
+
+/// The events the channel is subscribed to.
+pub fn channel_events(channel: &notification_channel::Model) -> Vec<String> {
+    serde_json::from_value(channel.events.clone()).unwrap_or_default()
+}
+
+fn subscribed(channel: &notification_channel::Model, event: &str) -> bool {
+    channel_events(channel).iter().any(|e| e == event)
+}
+
+/// Encrypts a channel URL for storage. Like GitHub tokens, URLs are never
+/// stored in plaintext, so this fails when the server has no key.
+pub fn seal_url(channel_id: Uuid, url: &str) -> Result<String> {
+    let key = secrets::key().ok_or_else(|| {
+        anyhow!("Notification channels can't be stored: the server has no DRIFTWATCH_SECRET_KEY")
+    })?;
+    Ok(key.seal(url, &url_context(channel_id)))
+}
+
+/// The channel's URL in plaintext.
+pub fn channel_url(channel: &notification_channel::Model) -> Result<String> {
+    let key = secrets::key().ok_or_else(|| anyhow!("The server has no DRIFTWATCH_SECRET_KEY"))?;
+    key.open(&channel.url, &url_context(channel.id))
+}
+
+/// Where the channel posts, without the path that holds its credential.
+pub fn channel_target(channel: &notification_channel::Model) -> String {
+    channel_url(channel)
+        .ok()
+        .and_then(|url| reqwest::Url::parse(&url).ok())
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| "(unreadable)".to_string())
+}
+
+/// What a webhook channel receives.
+#[derive(Debug, Serialize)]
+pub struct DigestMessage {
+    pub event: &'static str,
+    pub project: String,
+    pub period_start: chrono::DateTime<chrono::Utc>,
+    pub period_end: chrono::DateTime<chrono::Utc>,
+    pub markdown: String,
+    pub html: String,
+    pub url: Option<String>,
+}
+
+impl DigestMessage {
+    pub fn new(project: &project::Model, digest: &digest::Model) -> Self {
+        Self {
+            event: "digest",
+            project: project.slug.clone(),
+            period_start: digest.period_start.into(),
+            period_end: digest.period_end.into(),
+            markdown: digest.markdown.clone(),
+            html: digest.html.clone(),
+            url: links::web_url().map(|web_url| links::project_url(web_url, &project.slug)),
+        }
+    }
+}
+
+/// The request body for a channel of this kind.
+pub fn payload(kind: ChannelKind, message: &DigestMessage) -> serde_json::Value {
+    default_body(
+        kind,
+        serde_json::to_value(message).unwrap_or_default(),
+        &message.markdown,
+    )
+}
+
+fn default_body(kind: ChannelKind, payload: serde_json::Value, text: &str) -> serde_json::Value {
+    match kind {
+        ChannelKind::Webhook => payload,
+        // Slack renders its own flavor of markdown from `text`
+        ChannelKind::Slack => serde_json::json!({ "text": text }),
+    }
+}
+
+/// What a webhook channel receives for a report or alert event: the
+/// event's data with `event`, `project`, `occurred_at` and `id` alongside,
+/// flat so automation tools can map its fields directly.
+pub fn event_payload(event: &Event) -> serde_json::Value {
+    let mut payload = match &event.data {
+        serde_json::Value::Object(data) => data.clone(),
+        _ => serde_json::Map::new(),
+    };
+    payload.insert("event".to_string(), event.kind.clone().into());
+    payload.insert("project".to_string(), event.project.clone().into());
+    payload.insert(
+        "occurred_at".to_string(),
+        serde_json::to_value(event.occurred_at).unwrap_or_default(),
+    );
+    payload.insert("id".to_string(), event.id.to_string().into());
+    payload.into()
+}
+
+/// The message posted to Slack for a report or alert event.
+fn event_text(payload: &serde_json::Value) -> String {
+    let field = |name: &str| payload[name].as_str().unwrap_or_default().to_string();
+    let series = format!(
+        "{} {} on {}/{}",
+        field("benchmark"),
+        field("measure"),
+        field("branch"),
+        field("testbed")
+    );
+    let mut text = match payload["event"].as_str() {
+        Some(events::ALERT_OPENED) => format!(
+            "Regression in {}: {} changed {:+.1}%",
+            field("project"),
+            series,
+            payload["percent_change"].as_f64().unwrap_or_default()
+        ),
+        Some(events::ALERT_RESOLVED) => {
+            format!("Resolved in {}: {}", field("project"), series)
+        }
+        _ => format!(
+            "New report in {} on {}/{} with {} active alerts",
+            field("project"),
+            field("branch"),
+            field("testbed"),
+            payload["alerts"].as_i64().unwrap_or_default()
+        ),
+    };
+    if let Some(url) = payload["url"].as_str() {
+        text.push_str(&format!(" <{}|View>", url));
+    }
+    text
+}
+
+/// Fills a template's placeholders from `payload`. Missing values render
+/// as empty strings, or as `null` in raw placeholders.
+pub fn render_template(template: &str, payload: &serde_json::Value) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let raw = rest[start..].starts_with("{{{");
+        let (open, close) = if raw { ("{{{", "}}}") } else { ("{{", "}}") };
+        let after = &rest[start + open.len()..];
+        let end = after
+            .find(close)
+            .ok_or_else(|| format!("Unclosed {} in template", open))?;
+        let path = after[..end].trim();
+        if path.is_empty() {
+            return Err(format!("Empty {}{} in template", open, close));
+        }
+        let value = path
+            .split('.')
+            .try_fold(payload, |value, key| match key.parse::<usize>() {
+                Ok(index) => value.get(index),
+                Err(_) => value.get(key),
+            });
+        match (raw, value) {
+            (true, Some(value)) => out.push_str(&value.to_string()),
+            (true, None) => out.push_str("null"),
+            (false, Some(serde_json::Value::String(text))) => {
+                out.push_str(&json_escape(text));
+            }
+            (false, Some(serde_json::Value::Null) | None) => {}
+            (false, Some(value)) => out.push_str(&json_escape(&value.to_string())),
+        }
+        rest = &after[end + close.len()..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `text` escaped to sit between a JSON string's quotes.
+fn json_escape(text: &str) -> String {
+    let quoted = serde_json::Value::String(text.to_string()).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Checks that a template renders to JSON for every event it will be used
+/// for.
+pub fn validate_template(template: &str, events: &[String]) -> Result<(), String> {
+    for event in events {
+        let body = render_template(template, &sample_payload(event, "my-project"))?;
+        serde_json::from_str::<serde_json::Value>(&body).map_err(|e| {
+            format!(
+                "Template doesn't render to JSON for {} events: {}",
+                event, e
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// A made-up but realistic payload for `event`, sent by test deliveries
+/// and used to check templates.
+pub fn sample_payload(event: &str, project_slug: &str) -> serde_json::Value {
+    let now = chrono::Utc::now();
+    if event == DIGEST_EVENT {
+        let markdown = format!(
+            "# {}: week of {}\n\nThis is a test delivery.",
+            project_slug,
+            now.date_naive()
+        );
+        return serde_json::to_value(DigestMessage {
+            event: DIGEST_EVENT,
+            project: project_slug.to_string(),
+            period_start: now - chrono::Duration::days(7),
+            period_end: now,
+            html: format!("<p>{}</p>", markdown),
+            markdown,
+            url: links::web_url().map(|web_url| links::project_url(web_url, project_slug)),
+        })
+        .unwrap_or_default();
+    }
+
+    let (report_id, alert_id) = (Uuid::new_v4(), Uuid::new_v4());
+    let data = match event {
+        events::REPORT_CREATED => serde_json::json!({
+            "report_id": report_id,
+            "branch": "main",
+            "testbed": "ci",
+            "git_hash": "0000000000000000000000000000000000000000",
+            "pr_number": null,
+            "version": null,
+            "alerts": 1,
+            "url": links::report(project_slug, report_id),
+        }),
+        _ => serde_json::json!({
+            "alert_id": alert_id,
+            "report_id": report_id,
+            "status": if event == events::ALERT_RESOLVED { "resolved" } else { "active" },
+            "benchmark": "parse",
+            "measure": "latency",
+            "branch": "main",
+            "testbed": "ci",
+            "baseline_value": 100.0,
+            "current_value": 125.0,
+            "percent_change": 25.0,
+            "url": links::alert(project_slug, alert_id),
+        }),
+    };
+    event_payload(&Event {
+        id: Uuid::new_v4(),
+        kind: event.to_string(),
+        occurred_at: now.fixed_offset(),
+        project: project_slug.to_string(),
+        data,
+    })
+}
+
+/// The request body for the channel: its template filled from `payload`,
+/// or the default body for its kind.
+pub fn request_body(
+    channel: &notification_channel::Model,
+    payload: serde_json::Value,
+    text: &str,
+) -> Result<String> {
+    match &channel.template {
+        Some(template) => render_template(template, &payload).map_err(|e| anyhow!(e)),
+        None => Ok(default_body(channel.kind, payload, text).to_string()),
+    }
+}
+
+async fn post(channel: &notification_channel::Model, body: String, what: &str) -> Result<()> {
+    let url = channel_url(channel)?;
+    let response = reqwest::Client::new()
+        .post(&url)
+        .timeout(DELIVERY_TIMEOUT)
+        .header("User-Agent", "driftwatch")
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {}", channel_target(channel)))?;
+    if !response.status().is_success() {
+        bail!(
+            "{} refused the {}: {}",
+            channel_target(channel),
+            what,
+            response.status()
+        );
+    }
+    Ok(())
+}
+
+/// Posts a sample `event` to the channel right away, so its URL and
+/// template can be checked without waiting for a real one.
+pub async fn send_test(
+    channel: &notification_channel::Model,
+    project: &project::Model,
+    event: &str,
+) -> Result<()> {
+    let payload = sample_payload(event, &project.slug);
+    let text = match event {
+        DIGEST_EVENT => payload["markdown"].as_str().unwrap_or_default().to_string(),
+        _ => event_text(&payload),
+    };
+    post(channel, request_body(channel, payload, &text)?, "test").await
+}
+
+/// Queues delivery of a new digest to each of the project's channels.
+pub async fn queue_digest<C: ConnectionTrait>(db: &C, digest: &digest::Model) -> Result<(), DbErr> {
+    let channels = entities::NotificationChannel::find()
+        .filter(notification_channel::Column::ProjectId.eq(digest.project_id))
+        .all(db)
+        .await?;
+    for channel in channels.into_iter().filter(|c| subscribed(c, DIGEST_EVENT)) {
+        jobs::enqueue(
+            db,
+            DELIVER_DIGEST_JOB,
+            serde_json::json!({ "channel_id": channel.id, "digest_id": digest.id }),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn deliver_digest(db: &DatabaseConnection, channel_id: Uuid, digest_id: Uuid) -> Result<()> {
+    // Channels and digests removed since the job was queued are skipped
+    let Some(channel) = entities::NotificationChannel::find_by_id(channel_id)
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+    let Some((digest, Some(project))) = entities::Digest::find_by_id(digest_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    let message = DigestMessage::new(&project, &digest);
+    let body = request_body(&channel, serde_json::to_value(&message)?, &message.markdown)?;
+    post(&channel, body, "digest").await?;
+    tracing::info!(
+        "Delivered the {} digest of {} to {}",
+        digest.period_start.date_naive(),
+        project.slug,
+        channel_target(&channel)
+    );
+    Ok(())
+}
+
+/// Queues delivery of a report or alert event to each of the project's
+/// channels subscribed to it.
+pub async fn queue_event<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    event: &Event,
+) -> Result<(), DbErr> {
+    let channels = entities::NotificationChannel::find()
+        .filter(notification_channel::Column::ProjectId.eq(project_id))
+        .all(db)
+        .await?;
+    for channel in channels.into_iter().filter(|c| subscribed(c, &event.kind)) {
+        jobs::enqueue(
+            db,
+            DELIVER_EVENT_JOB,
+            serde_json::json!({ "channel_id": channel.id, "event": event }),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn deliver_event(db: &DatabaseConnection, channel_id: Uuid, event: &Event) -> Result<()> {
+    // Channels removed or unsubscribed since the job was queued are skipped
+    let Some(channel) = entities::NotificationChannel::find_by_id(channel_id)
+        .one(db)
+        .await?
+        .filter(|c| subscribed(c, &event.kind))
+    else {
+        return Ok(());
+    };
+
+    let payload = event_payload(event);
+    let text = event_text(&payload);
+    post(
+        &channel,
+        request_body(&channel, payload, &text)?,
+        &event.kind,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url(ChannelKind::Webhook, "https://example.com/hooks/1").is_ok());
+        assert!(validate_url(ChannelKind::Webhook, "http://localhost:8080/digest").is_ok());
+        assert!(validate_url(ChannelKind::Webhook, "ftp://example.com").is_err());
+        assert!(validate_url(ChannelKind::Webhook, "not a url").is_err());
+        assert!(validate_url(
+            ChannelKind::Slack,
+            "https://hooks.slack.com/services/T000/B000/XXXX"
+        )
+        .is_ok());
+        assert!(validate_url(ChannelKind::Slack, "https://example.com/services/T000").is_err());
+    }
+
+    #[test]
+    fn test_payload_shapes() {
+        let at = chrono::DateTime::parse_from_rfc3339("2024-01-08T00:00:00Z")
+            .unwrap()
+            .into();
+        let message = DigestMessage {
+            event: "digest",
+            project: "core".to_string(),
+            period_start: at,
+            period_end: at + chrono::Duration::days(7),
+            markdown: "# core".to_string(),
+            html: "<h1>core</h1>".to_string(),
+            url: None,
+        };
+
+        let webhook = payload(ChannelKind::Webhook, &message);
+        assert_eq!(webhook["event"], "digest");
+        assert_eq!(webhook["project"], "core");
+        assert_eq!(webhook["period_start"], "2024-01-08T00:00:00Z");
+        assert_eq!(webhook["html"], "<h1>core</h1>");
+
+        assert_eq!(
+            payload(ChannelKind::Slack, &message),
+            serde_json::json!({ "text": "# core" })
+        );
+    }
+
+    #[test]
+    fn test_render_template() {
+        let payload = serde_json::json!({
+            "benchmark": "parse \"json\"",
+            "percent_change": 12.5,
+            "tags": ["a", "b"],
+            "git_hash": null,
+        });
+        assert_eq!(
+            render_template(
+                r#"{"v1": "{{ benchmark }}", "v2": {{{percent_change}}}, "v3": "{{tags.1}}"}"#,
+                &payload
+            )
+            .unwrap(),
+            r#"{"v1": "parse \"json\"", "v2": 12.5, "v3": "b"}"#
+        );
+        assert_eq!(
+            render_template(r#"["{{git_hash}}{{missing}}", {{{missing}}}]"#, &payload).unwrap(),
+            r#"["", null]"#
+        );
+        assert!(render_template("{{benchmark", &payload).is_err());
+        assert!(render_template("{{ }}", &payload).is_err());
+
+        let events = vec![DIGEST_EVENT.to_string(), events::ALERT_OPENED.to_string()];
+        assert!(validate_template(r#"{"text": "{{project}}: {{benchmark}}"}"#, &events).is_ok());
+        assert!(validate_template(r#"{"value": {{{percent_change}}}}"#, &events).is_ok());
+        assert!(validate_template(r#"{"value": {{percent_change}}}"#, &events).is_err());
+    }
+
+    #[test]
+    fn test_event_payloads() {
+        assert_eq!(
+            validate_events(&["alert.opened".to_string(), "alert.opened".to_string()]).unwrap(),
+            vec!["alert.opened"]
+        );
+        assert!(validate_events(&[]).is_err());
+        assert!(validate_events(&["alert.snoozed".to_string()]).is_err());
+
+        let payload = sample_payload(events::ALERT_OPENED, "core");
+        assert_eq!(payload["event"], "alert.opened");
+        assert_eq!(payload["project"], "core");
+        assert_eq!(payload["benchmark"], "parse");
+        assert_eq!(
+            event_text(&payload),
+            "Regression in core: parse latency on main/ci changed +25.0%"
+        );
+        assert_eq!(sample_payload(DIGEST_EVENT, "core")["event"], DIGEST_EVENT);
+    }
+}
@@ -0,0 +1,219 @@
+//! The latest value of every benchmark as an OpenMetrics endpoint, so
+//! Prometheus can scrape it and Grafana can overlay benchmark trends on
+//! production dashboards without a custom datasource.
+//!
+//! `GET /prometheus/{project_slug}/metrics` is authenticated with an API key
+//! as bearer token, like remote_write. Each benchmark, measure, branch and
+//! testbed is one series, read from `metric_summaries`, so a scrape costs
+//! one indexed query however long the project's history is.
+
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, FromQueryResult, Statement};
+use uuid::Uuid;
+
+use crate::auth::{bearer_token, validate_token, TsaAuth};
+use crate::cache::AppCache;
+use crate::error_reports;
+
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The latest value of one benchmark series
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct LatestValue {
+    pub benchmark: String,
+    pub measure: String,
+    pub units: Option<String>,
+    pub branch: String,
+    pub testbed: String,
+    pub value: f64,
+    pub delta_7d: Option<f64>,
+    pub delta_30d: Option<f64>,
+    pub latest_at: DateTimeWithTimeZone,
+}
+
+pub async fn latest_values<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+) -> Result<Vec<LatestValue>, DbErr> {
+    LatestValue::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT b.name AS benchmark, ms.name AS measure, ms.units, br.name AS branch,
+                  tb.name AS testbed, s.latest_value AS value, s.delta_7d, s.delta_30d,
+                  s.latest_at
+           FROM metric_summaries s
+           JOIN benchmarks b ON b.id = s.benchmark_id
+           JOIN measures ms ON ms.id = s.measure_id
+           JOIN branches br ON br.id = s.branch_id
+           JOIN testbeds tb ON tb.id = s.testbed_id
+           WHERE s.project_id = $1
+           ORDER BY ms.name, b.name, br.name, tb.name"#,
+        [project_id.into()],
+    ))
+    .all(db)
+    .await
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Renders the values as OpenMetrics text, one gauge family per statistic
+pub fn render(project_slug: &str, values: &[LatestValue]) -> String {
+    type Sample = fn(&LatestValue) -> Option<f64>;
+    let families: [(&str, &str, Sample); 4] = [
+        (
+            "driftwatch_benchmark_value",
+            "Latest value of the benchmark, in the measure's units.",
+            |v| Some(v.value),
+        ),
+        (
+            "driftwatch_benchmark_change_7d_percent",
+            "Percent change against the last value at least 7 days older.",
+            |v| v.delta_7d,
+        ),
+        (
+            "driftwatch_benchmark_change_30d_percent",
+            "Percent change against the last value at least 30 days older.",
+            |v| v.delta_30d,
+        ),
+        (
+            "driftwatch_benchmark_last_report_timestamp_seconds",
+            "When the latest value was reported.",
+            |v| Some(v.latest_at.timestamp_millis() as f64 / 1000.0),
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, help, sample) in families {
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        for value in values {
+            let Some(sample) = sample(value) else {
+                continue;
+            };
+            let _ = writeln!(
+                out,
+                r#"{}{{project="{}",benchmark="{}",measure="{}",units="{}",branch="{}",testbed="{}"}} {}"#,
+                name,
+                escape_label(project_slug),
+                escape_label(&value.benchmark),
+                escape_label(&value.measure),
+                escape_label(value.units.as_deref().unwrap_or_default()),
+                escape_label(&value.branch),
+                escape_label(&value.testbed),
+                number(sample)
+            );
+        }
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+#[derive(Clone)]
+struct ScrapeState {
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+}
+
+async fn scrape(
+    State(state): State<ScrapeState>,
+    Path(project_slug): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(token) = bearer_token(&headers) else {
+        return (StatusCode::UNAUTHORIZED, "Missing bearer token").into_response();
+    };
+    let user = match validate_token(token, &state.auth).await {
+        Ok(user) => user,
+        Err(e) => return (StatusCode::UNAUTHORIZED, e.0).into_response(),
+    };
+
+    let metrics = async {
+        let Some(project) = state
+            .cache
+            .resolve_project(&state.db, user.user_id(), &project_slug)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let values = latest_values(&state.db, project.id).await?;
+        Ok::<_, DbErr>(Some(render(&project.slug, &values)))
+    };
+
+    match metrics.await {
+        Ok(Some(metrics)) => ([(header::CONTENT_TYPE, CONTENT_TYPE)], metrics).into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Project not found").into_response(),
+        Err(e) => {
+            tracing::error!("OpenMetrics scrape failed: {}", e);
+            error_reports::capture(&e.into(), "openmetrics", &[]);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Routes for `GET /prometheus/{project_slug}/metrics`
+pub fn router<S: Clone + Send + Sync + 'static>(
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+) -> Router<S> {
+    Router::new()
+        .route("/prometheus/{project_slug}/metrics", get(scrape))
+        .with_state(ScrapeState { db, auth, cache })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let value = LatestValue {
+            benchmark: "parse \"json\"".to_string(),
+            measure: "latency".to_string(),
+            units: Some("ns".to_string()),
+            branch: "main".to_string(),
+            testbed: "ci".to_string(),
+            value: 1500.5,
+            delta_7d: Some(-2.5),
+            delta_30d: None,
+            latest_at: chrono::DateTime::parse_from_rfc3339("2024-01-08T10:00:00Z").unwrap(),
+        };
+
+        let text = render("core", &[value]);
+        assert!(text.contains(
+            r#"driftwatch_benchmark_value{project="core",benchmark="parse \"json\"",measure="latency",units="ns",branch="main",testbed="ci"} 1500.5"#
+        ), "{}", text);
+        assert!(text.contains(r#"testbed="ci"} -2.5"#));
+        assert!(text.contains("# TYPE driftwatch_benchmark_change_30d_percent gauge\n# HELP"));
+        assert!(!text.contains("driftwatch_benchmark_change_30d_percent{"));
+        assert!(text.contains(r#"testbed="ci"} 1704708000"#));
+        assert!(text.ends_with("# EOF\n"));
+
+        assert_eq!(render("core", &[]).matches("# TYPE").count(), 4);
+        assert_eq!(number(f64::INFINITY), "+Inf");
+        assert_eq!(escape_label("a\\b\nc"), "a\\\\b\\nc");
+    }
+}
@@ -0,0 +1,110 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait, FromQueryResult, PaginatorTrait,
+    QueryFilter, QuerySelect, RelationTrait, Statement,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, alert, threshold};
+use crate::evaluation::CLOSED_STATUSES;
+
+/// How one measure moved across every benchmark, from the metric summaries
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct MeasureTrend {
+    pub measure: String,
+    pub benchmarks: i64,
+    /// Mean percent change against values at least 7 and 30 days older,
+    /// over the benchmarks that have one
+    pub mean_delta_7d: Option<f64>,
+    pub mean_delta_30d: Option<f64>,
+    /// Benchmarks that got worse or better by more than 5% in 30 days;
+    /// lower values are better
+    pub regressed_30d: i64,
+    pub improved_30d: i64,
+}
+
+/// The newest report of one branch and testbed
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct LatestReport {
+    pub branch: String,
+    pub testbed: String,
+    pub report_id: Uuid,
+    pub git_hash: Option<String>,
+    pub reported_at: DateTimeWithTimeZone,
+}
+
+/// Percent change beyond which `MeasureTrend` counts a benchmark as moved
+pub const TREND_CHANGE_PERCENT: f64 = 5.0;
+
+/// Trends per measure, optionally narrowed to a branch and testbed by name
+pub async fn measure_trends<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    branch: Option<&str>,
+    testbed: Option<&str>,
+) -> Result<Vec<MeasureTrend>, DbErr> {
+    MeasureTrend::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT ms.name AS measure,
+                  COUNT(DISTINCT s.benchmark_id) AS benchmarks,
+                  AVG(s.delta_7d) AS mean_delta_7d,
+                  AVG(s.delta_30d) AS mean_delta_30d,
+                  COUNT(DISTINCT s.benchmark_id) FILTER (WHERE s.delta_30d > $4) AS regressed_30d,
+                  COUNT(DISTINCT s.benchmark_id) FILTER (WHERE s.delta_30d < -$4) AS improved_30d
+           FROM metric_summaries s
+           JOIN measures ms ON ms.id = s.measure_id
+           JOIN branches b ON b.id = s.branch_id
+           JOIN testbeds t ON t.id = s.testbed_id
+           WHERE s.project_id = $1
+             AND ($2::text IS NULL OR b.name = $2)
+             AND ($3::text IS NULL OR t.name = $3)
+           GROUP BY ms.name
+           ORDER BY ms.name"#,
+        [
+            project_id.into(),
+            branch.map(str::to_string).into(),
+            testbed.map(str::to_string).into(),
+            TREND_CHANGE_PERCENT.into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
+
+/// The newest report of every branch and testbed, most recent first
+pub async fn latest_reports<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+) -> Result<Vec<LatestReport>, DbErr> {
+    LatestReport::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT * FROM (
+               SELECT DISTINCT ON (r.branch_id, r.testbed_id)
+                      b.name AS branch, t.name AS testbed, r.id AS report_id,
+                      r.git_hash, r.created_at AS reported_at
+               FROM reports r
+               JOIN branches b ON b.id = r.branch_id
+               JOIN testbeds t ON t.id = r.testbed_id
+               WHERE r.project_id = $1 AND NOT r.excluded
+               ORDER BY r.branch_id, r.testbed_id, r.created_at DESC
+           ) latest
+           ORDER BY reported_at DESC"#,
+        [project_id.into()],
+    ))
+    .all(db)
+    .await
+}
+
+/// Alerts raised and not yet resolved or dismissed
+pub async fn open_alerts<C: ConnectionTrait>(db: &C, project_id: Uuid) -> Result<u64, DbErr> {
+    entities::Alert::find()
+        .join(
+            sea_orm::JoinType::InnerJoin,
+            alert::Relation::Threshold.def(),
+        )
+        .filter(threshold::Column::ProjectId.eq(project_id))
+        .filter(alert::Column::Status.ne(alert::AlertStatus::Unconfirmed))
+        .filter(alert::Column::Status.is_not_in(CLOSED_STATUSES))
+        .count(db)
+        .await
+}
@@ -0,0 +1,101 @@
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use uuid::Uuid;
+
+use crate::entities::{self, benchmark_owner};
+
+/// Owners of `benchmark` under `rules`, which must be sorted by position.
+/// Like CODEOWNERS, the last matching rule wins.
+pub fn owners_for(rules: &[benchmark_owner::Model], benchmark: &str) -> Vec<String> {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| glob_match(&rule.pattern, benchmark))
+        .map(|rule| rule.owner_list())
+        .unwrap_or_default()
+}
+
+pub async fn project_rules<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+) -> Result<Vec<benchmark_owner::Model>, DbErr> {
+    entities::BenchmarkOwner::find()
+        .filter(benchmark_owner::Column::ProjectId.eq(project_id))
+        .order_by_asc(benchmark_owner::Column::Position)
+        .all(db)
+        .await
+}
+
+/// Matches `*` against any run of characters (including `/`) and `?` against
+/// exactly one; everything else is literal.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(position: i32, pattern: &str, owners: &str) -> benchmark_owner::Model {
+        benchmark_owner::Model {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            position,
+            pattern: pattern.to_string(),
+            owners: owners.to_string(),
+            created_at: chrono::Utc::now().fixed_offset(),
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "anything/at/all"));
+        assert!(glob_match("parser/*", "parser/json/large"));
+        assert!(glob_match("fib_?0", "fib_20"));
+        assert!(glob_match("*_alloc*", "vec_alloc_small"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("parser/*", "lexer/json"));
+        assert!(!glob_match("fib_?0", "fib_200"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[test]
+    fn test_owners_for_last_match_wins() {
+        let rules = vec![
+            rule(0, "*", "@org/perf"),
+            rule(1, "parser/*", "@org/parsing @alice"),
+        ];
+        assert_eq!(
+            owners_for(&rules, "parser/json"),
+            vec!["@org/parsing", "@alice"]
+        );
+        assert_eq!(owners_for(&rules, "fib"), vec!["@org/perf"]);
+        assert!(owners_for(&rules[1..], "fib").is_empty());
+    }
+}
@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::pull_request_check::CheckState;
+use crate::entities::{self, project, pull_request_check, report};
+use crate::error_reports;
+use crate::github::{self, GitHubClient};
+use crate::jobs::{self, JobRegistry};
+use crate::links;
+use crate::owners::glob_match;
+
+/// Job kind that turns a pull request's check green once its report arrives.
+pub const REFRESH_CHECK_JOB: &str = "refresh_pull_request_check";
+
+/// Job kind that reports a merge queue commit's benchmark result back to
+/// GitHub.
+pub const MERGE_QUEUE_STATUS_JOB: &str = "merge_queue_status";
+
+/// Commit status context; mark it as required in the branch protection rule.
+pub const STATUS_CONTEXT: &str = "driftwatch/benchmarks";
+
+/// How often open pull requests are checked for missing benchmark reports.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Job kind of the periodic pull request check.
+pub const CHECK_PULL_REQUESTS_JOB: &str = "check_pull_requests";
+
+/// How long after its last update a pull request may go without a report
+/// before the check fails instead of staying pending.
+pub const REPORT_GRACE_MINUTES: i64 = 60;
+
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.register(REFRESH_CHECK_JOB, |db, payload| async move {
+        let project_id: Uuid = serde_json::from_value(payload["project_id"].clone())?;
+        let pr_number: i32 = serde_json::from_value(payload["pr_number"].clone())?;
+        mark_reported(&db, project_id, pr_number).await
+    });
+
+    registry.register(MERGE_QUEUE_STATUS_JOB, |db, payload| async move {
+        let report_id: Uuid = serde_json::from_value(payload["report_id"].clone())?;
+        let alerts: usize = serde_json::from_value(payload["alerts"].clone())?;
+        post_evaluated_status(&db, report_id, alerts).await
+    });
+
+    registry.schedule(CHECK_PULL_REQUESTS_JOB, CHECK_INTERVAL, |db| async move {
+        let now = Utc::now().fixed_offset();
+        match check_pull_requests(&db, now).await {
+            Ok(posted) if posted > 0 => {
+                tracing::info!("Updated {} pull request benchmark checks", posted);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!("Pull request check failed: {:#}", e);
+                error_reports::capture(&e, "pr_checks", &[]);
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Repository, token and path globs when required-benchmark checks are on.
+fn check_settings(project: &project::Model) -> Option<(&str, String, Vec<String>)> {
+    if !project.github_status_checks {
+        return None;
+    }
+    let repo = project.github_repo.as_deref()?;
+    let paths = project.required_path_list();
+    if paths.is_empty() {
+        return None;
+    }
+    let token = github::project_token(project)?;
+    Some((repo, token, paths))
+}
+
+/// True when any changed file matches one of the project's path globs.
+pub fn touches_required_paths(patterns: &[String], files: &[String]) -> bool {
+    files
+        .iter()
+        .any(|file| patterns.iter().any(|pattern| glob_match(pattern, file)))
+}
+
+/// Status for a pull request's head commit.
+pub fn decide(
+    touched: bool,
+    reported: bool,
+    pushed_at: DateTime<FixedOffset>,
+    now: DateTime<FixedOffset>,
+) -> (CheckState, &'static str) {
+    if !touched {
+        (CheckState::Success, "No benchmarked paths changed")
+    } else if reported {
+        (CheckState::Success, "Benchmark report submitted")
+    } else if now - pushed_at < chrono::Duration::minutes(REPORT_GRACE_MINUTES) {
+        (CheckState::Pending, "Waiting for a benchmark report")
+    } else {
+        (CheckState::Failure, "No benchmark report for this commit")
+    }
+}
+
+/// Status for a merge queue commit, failing it when the report raised alerts
+/// so the queue drops the entry instead of merging a regression.
+pub fn evaluated_status(alerts: usize) -> (CheckState, String) {
+    match alerts {
+        0 => (CheckState::Success, "No benchmark regressions".to_string()),
+        1 => (CheckState::Failure, "1 benchmark regression".to_string()),
+        n => (CheckState::Failure, format!("{} benchmark regressions", n)),
+    }
+}
+
+fn state_name(state: &CheckState) -> &'static str {
+    match state {
+        CheckState::Pending => "pending",
+        CheckState::Success => "success",
+        CheckState::Failure => "failure",
+    }
+}
+
+/// The latest report on the pull request's head commit, if any
+async fn head_report<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    pr_number: i32,
+    sha: &str,
+) -> Result<Option<Uuid>, DbErr> {
+    let report = entities::Report::find()
+        .filter(report::Column::ProjectId.eq(project_id))
+        .filter(report::Column::PrNumber.eq(pr_number))
+        .filter(report::Column::GitHash.eq(sha))
+        .order_by_desc(report::Column::CreatedAt)
+        .one(db)
+        .await?;
+    Ok(report.map(|r| r.id))
+}
+
+async fn save_check<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    pr_number: i32,
+    sha: &str,
+    state: CheckState,
+    description: &str,
+) -> Result<(), DbErr> {
+    let now = Utc::now().fixed_offset();
+    entities::PullRequestCheck::insert(pull_request_check::ActiveModel {
+        project_id: Set(project_id),
+        pr_number: Set(pr_number),
+        head_sha: Set(sha.to_string()),
+        state: Set(state),
+        description: Set(description.to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            pull_request_check::Column::ProjectId,
+            pull_request_check::Column::PrNumber,
+        ])
+        .update_columns([
+            pull_request_check::Column::HeadSha,
+            pull_request_check::Column::State,
+            pull_request_check::Column::Description,
+            pull_request_check::Column::UpdatedAt,
+        ])
+        .to_owned(),
+    )
+    .exec(db)
+    .await?;
+    Ok(())
+}
+
+/// Posts a commit status for every open pull request of every project with
+/// required benchmark paths, skipping those whose status hasn't changed.
+/// Returns how many statuses were posted.
+pub async fn check_pull_requests(
+    db: &DatabaseConnection,
+    now: DateTime<FixedOffset>,
+) -> anyhow::Result<usize> {
+    let projects = entities::Project::find()
+        .filter(project::Column::GithubStatusChecks.eq(true))
+        .filter(project::Column::BenchmarkRequiredPaths.is_not_null())
+        .all(db)
+        .await?;
+    let mut posted = 0;
+
+    for project in &projects {
+        let Some((repo, token, paths)) = check_settings(project) else {
+            continue;
+        };
+        let client = GitHubClient::new(&token);
+
+        let pulls = match client.open_pull_requests(repo).await {
+            Ok(pulls) => pulls,
+            Err(e) => {
+                tracing::warn!("Failed to list pull requests for {}: {:#}", repo, e);
+                continue;
+            }
+        };
+
+        let existing: HashMap<i32, pull_request_check::Model> = entities::PullRequestCheck::find()
+            .filter(pull_request_check::Column::ProjectId.eq(project.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|c| (c.pr_number, c))
+            .collect();
+
+        for pull in &pulls {
+            let previous = existing
+                .get(&pull.number)
+                .filter(|c| c.head_sha == pull.head.sha);
+            if previous.is_some_and(|c| c.state == CheckState::Success) {
+                continue;
+            }
+
+            let files = match client.pull_request_files(repo, pull.number).await {
+                Ok(files) => files,
+                Err(e) => {
+                    tracing::warn!("Failed to list files of {}#{}: {:#}", repo, pull.number, e);
+                    continue;
+                }
+            };
+            let touched = touches_required_paths(&paths, &files);
+            let report_id = match touched {
+                true => head_report(db, project.id, pull.number, &pull.head.sha).await?,
+                false => None,
+            };
+            let (state, description) = decide(
+                touched,
+                report_id.is_some(),
+                pull.updated_at.fixed_offset(),
+                now,
+            );
+
+            if previous.is_some_and(|c| c.state == state) {
+                continue;
+            }
+
+            if let Err(e) = client
+                .create_status(
+                    repo,
+                    &pull.head.sha,
+                    state_name(&state),
+                    description,
+                    STATUS_CONTEXT,
+                    report_id
+                        .and_then(|id| links::report(&project.slug, id))
+                        .as_deref(),
+                )
+                .await
+            {
+                tracing::warn!("Failed to set status on {}#{}: {:#}", repo, pull.number, e);
+                error_reports::capture(
+                    &e,
+                    "github",
+                    &[
+                        ("project", project.slug.clone()),
+                        ("repo", repo.to_string()),
+                        ("pr", pull.number.to_string()),
+                    ],
+                );
+                continue;
+            }
+            save_check(
+                db,
+                project.id,
+                pull.number,
+                &pull.head.sha,
+                state,
+                description,
+            )
+            .await?;
+            posted += 1;
+        }
+
+        let open: Vec<i32> = pulls.iter().map(|p| p.number).collect();
+        entities::PullRequestCheck::delete_many()
+            .filter(pull_request_check::Column::ProjectId.eq(project.id))
+            .filter(pull_request_check::Column::PrNumber.is_not_in(open))
+            .exec(db)
+            .await?;
+    }
+
+    Ok(posted)
+}
+
+/// Queues a check refresh when a report arrives for a pull request head that
+/// is still waiting on one, so the status doesn't lag until the next sweep.
+pub async fn queue_for_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+) -> Result<(), DbErr> {
+    let (Some(pr_number), Some(sha)) = (report.pr_number, &report.git_hash) else {
+        return Ok(());
+    };
+
+    let waiting = entities::PullRequestCheck::find()
+        .filter(pull_request_check::Column::ProjectId.eq(report.project_id))
+        .filter(pull_request_check::Column::PrNumber.eq(pr_number))
+        .filter(pull_request_check::Column::HeadSha.eq(sha))
+        .filter(pull_request_check::Column::State.ne(CheckState::Success))
+        .count(db)
+        .await?;
+    if waiting == 0 {
+        return Ok(());
+    }
+
+    jobs::enqueue(
+        db,
+        REFRESH_CHECK_JOB,
+        serde_json::json!({ "project_id": report.project_id, "pr_number": pr_number }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Queues a status for the report's evaluated commit, e.g. the merge queue
+/// commit a required check is waiting on. Reports without one are skipped.
+pub async fn queue_for_evaluated_commit<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    alerts: usize,
+) -> Result<(), DbErr> {
+    if report.evaluated_commit.is_none() {
+        return Ok(());
+    }
+    jobs::enqueue(
+        db,
+        MERGE_QUEUE_STATUS_JOB,
+        serde_json::json!({ "report_id": report.id, "alerts": alerts }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Posts the benchmark result on a report's evaluated commit when the
+/// project has status checks on. Unlike pull request checks this doesn't
+/// need required paths: the queue runs the benchmarks for every entry.
+pub async fn post_evaluated_status(
+    db: &DatabaseConnection,
+    report_id: Uuid,
+    alerts: usize,
+) -> anyhow::Result<()> {
+    let Some((report, Some(project))) = entities::Report::find_by_id(report_id)
+        .find_also_related(entities::Project)
+        .one(db)
+        .await?
+    else {
+        return Ok(());
+    };
+    let Some(sha) = report.evaluated_commit.as_deref() else {
+        return Ok(());
+    };
+    let (true, Some(repo), Some(token)) = (
+        project.github_status_checks,
+        project.github_repo.as_deref(),
+        github::project_token(&project),
+    ) else {
+        return Ok(());
+    };
+
+    let (state, description) = evaluated_status(alerts);
+    GitHubClient::new(&token)
+        .create_status(
+            repo,
+            sha,
+            state_name(&state),
+            &description,
+            STATUS_CONTEXT,
+            links::report(&project.slug, report.id).as_deref(),
+        )
+        .await
+}
+
+/// Turns a waiting check green if its head commit now has a report.
+pub async fn mark_reported(
+    db: &DatabaseConnection,
+    project_id: Uuid,
+    pr_number: i32,
+) -> anyhow::Result<()> {
+    let Some((check, Some(project))) =
+        entities::PullRequestCheck::find_by_id((project_id, pr_number))
+            .find_also_related(entities::Project)
+            .one(db)
+            .await?
+    else {
+        return Ok(());
+    };
+    if check.state == CheckState::Success {
+        return Ok(());
+    }
+    let Some((repo, token, _)) = check_settings(&project) else {
+        return Ok(());
+    };
+    let Some(report_id) = head_report(db, project_id, pr_number, &check.head_sha).await? else {
+        return Ok(());
+    };
+
+    let description = "Benchmark report submitted";
+    GitHubClient::new(&token)
+        .create_status(
+            repo,
+            &check.head_sha,
+            state_name(&CheckState::Success),
+            description,
+            STATUS_CONTEXT,
+            links::report(&project.slug, report_id).as_deref(),
+        )
+        .await?;
+    save_check(
+        db,
+        project_id,
+        pr_number,
+        &check.head_sha,
+        CheckState::Success,
+        description,
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes_ago: i64) -> DateTime<FixedOffset> {
+        (Utc::now() - chrono::Duration::minutes(minutes_ago)).fixed_offset()
+    }
+
+    #[test]
+    fn test_touches_required_paths() {
+        let patterns = vec!["crates/core/*".to_string(), "benches/*".to_string()];
+        assert!(touches_required_paths(
+            &patterns,
+            &[
+                "README.md".to_string(),
+                "crates/core/src/lib.rs".to_string()
+            ]
+        ));
+        assert!(!touches_required_paths(
+            &patterns,
+            &[
+                "docs/guide.md".to_string(),
+                "crates/cli/src/main.rs".to_string()
+            ]
+        ));
+        assert!(!touches_required_paths(&patterns, &[]));
+    }
+
+    #[test]
+    fn test_decide() {
+        let now = at(0);
+        assert_eq!(decide(false, false, at(600), now).0, CheckState::Success);
+        assert_eq!(decide(true, true, at(600), now).0, CheckState::Success);
+        assert_eq!(decide(true, false, at(5), now).0, CheckState::Pending);
+        assert_eq!(decide(true, false, at(600), now).0, CheckState::Failure);
+    }
+
+    #[test]
+    fn test_evaluated_status() {
+        assert_eq!(evaluated_status(0).0, CheckState::Success);
+        assert_eq!(
+            evaluated_status(1),
+            (CheckState::Failure, "1 benchmark regression".to_string())
+        );
+        assert_eq!(evaluated_status(3).1, "3 benchmark regressions");
+    }
+}
@@ -0,0 +1,876 @@
+//! Ingestion relays: lightweight servers close to CI runners that accept
+//! report submissions and artifacts, answer straight away and forward them
+//! to the primary server in the background. Runners far from the primary
+//! then pay one short round trip per request instead of several long ones.
+//! `driftwatch relay` runs one, and the CLI submits through it when
+//! `relay_url` is configured.
+//!
+//! Accepted submissions are spooled to disk, one file each, and forwarded
+//! in the order they arrived, so a report reaches the primary before its
+//! artifacts and nothing is lost when the relay restarts. The relay picks
+//! each report's id and the primary creates the report under it, so links
+//! printed at submission work once the report is forwarded, and a
+//! forward retried after a lost response doesn't create it twice.
+//!
+//! Tokens are checked with the primary on first use and trusted for
+//! `TOKEN_CACHE_TTL` after that. Submissions the primary refuses are moved
+//! to `failed/` in the spool directory with the reason next to them; moving
+//! them back to `queue/` retries them.
+//!
+//! While the primary refuses work because it is saturated, or the spool
+//! holds more than its limit, new submissions get a 429 with `Retry-After`
+//! so runners back off instead of piling more onto the queue.
+
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::auth::bearer_token;
+use crate::backpressure::{self, Overload};
+use crate::storage::{MAX_CHUNK_BYTES, MAX_UPLOAD_BYTES, UPLOAD_OFFSET_HEADER};
+
+/// How long a token the primary accepted is trusted without asking again
+const TOKEN_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Largest report body accepted; reports aren't split into batches on the
+/// way in
+const MAX_REPORT_BYTES: usize = 64 * 1024 * 1024;
+
+/// How often the spool is checked when nothing wakes the forwarder
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Waits between attempts while the primary is unreachable, doubling from
+/// the first to the second
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Wait when the primary answers 429 without a usable `Retry-After`
+const DEFAULT_BUSY_DELAY: Duration = Duration::from_secs(10);
+
+/// What runners are told to wait while the spool is full
+const SPOOL_FULL_RETRY_AFTER_SECS: u64 = 30;
+
+const PRIMARY_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct RelayConfig {
+    /// Base URL of the primary server's API
+    pub primary_url: String,
+    pub port: u16,
+    /// Where accepted submissions wait to be forwarded
+    pub spool_dir: PathBuf,
+    /// Most bytes the spool may hold before submissions are refused, or 0
+    /// for no limit
+    pub max_spool_bytes: u64,
+}
+
+/// Something accepted for the primary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Payload {
+    /// `CreateReportInput` as the primary takes it, with the id filled in
+    Report { input: serde_json::Value },
+    Output {
+        report_id: String,
+        /// Compressed output, as `attachReportOutput` takes it
+        content: String,
+    },
+    Flamegraph {
+        project: String,
+        report_id: String,
+        file_name: String,
+        /// Base64 of the file
+        content: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    /// Forwarded with the payload so the primary authorizes it as the
+    /// submitter
+    token: String,
+    received_at: DateTime<Utc>,
+    #[serde(flatten)]
+    payload: Payload,
+}
+
+/// Submissions waiting on disk, oldest first by file name
+struct Spool {
+    queue: PathBuf,
+    failed: PathBuf,
+    seq: AtomicU64,
+    /// Size of the entries in `queue`
+    queued_bytes: AtomicU64,
+    wake: tokio::sync::Notify,
+}
+
+impl Spool {
+    fn open(dir: &FsPath) -> Result<Self> {
+        let queue = dir.join("queue");
+        let failed = dir.join("failed");
+        for dir in [&queue, &failed] {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let mut queued_bytes = 0;
+        for entry in std::fs::read_dir(&queue)
+            .with_context(|| format!("Failed to read {}", queue.display()))?
+        {
+            let entry = entry?;
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                queued_bytes += entry.metadata()?.len();
+            }
+        }
+        Ok(Self {
+            queue,
+            failed,
+            seq: AtomicU64::new(0),
+            queued_bytes: AtomicU64::new(queued_bytes),
+            wake: tokio::sync::Notify::new(),
+        })
+    }
+
+    fn queued_bytes(&self) -> u64 {
+        self.queued_bytes.load(Ordering::Relaxed)
+    }
+
+    async fn dequeued(&self, path: &FsPath) {
+        if let Ok(metadata) = tokio::fs::metadata(path).await {
+            let _ = self
+                .queued_bytes
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bytes| {
+                    Some(bytes.saturating_sub(metadata.len()))
+                });
+        }
+    }
+
+    /// Writes `entry` to the queue. It only appears under its final name
+    /// once complete, so the forwarder never reads half a file.
+    async fn push(&self, entry: &Entry) -> std::io::Result<()> {
+        let name = format!(
+            "{:016}-{:010}.json",
+            entry.received_at.timestamp_millis(),
+            self.seq.fetch_add(1, Ordering::Relaxed)
+        );
+        let partial = self.queue.join(format!(".{}", name));
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        // Entries hold tokens
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&partial).await?;
+        let content = serde_json::to_vec(entry)?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, &content).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&partial, self.queue.join(name)).await?;
+        self.queued_bytes
+            .fetch_add(content.len() as u64, Ordering::Relaxed);
+        self.wake.notify_one();
+        Ok(())
+    }
+
+    async fn pending(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut entries = Vec::new();
+        let mut dir = tokio::fs::read_dir(&self.queue).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.ends_with(".json") && !name.starts_with('.') {
+                entries.push(entry.path());
+            }
+        }
+        entries.sort();
+        Ok(entries)
+    }
+
+    /// Drops an entry the primary has
+    async fn remove(&self, path: &FsPath) -> std::io::Result<()> {
+        self.dequeued(path).await;
+        tokio::fs::remove_file(path).await
+    }
+
+    /// Moves an entry the primary refused out of the queue, with the reason
+    async fn reject(&self, path: &FsPath, reason: &str) -> std::io::Result<()> {
+        let Some(name) = path.file_name() else {
+            return Ok(());
+        };
+        let target = self.failed.join(name);
+        self.dequeued(path).await;
+        tokio::fs::rename(path, &target).await?;
+        tokio::fs::write(target.with_extension("error"), reason).await
+    }
+}
+
+/// Why forwarding an entry failed
+#[derive(Debug, PartialEq)]
+enum ForwardError {
+    /// The primary couldn't be reached or failed; try again later
+    Retry(String),
+    /// The primary is saturated and asked to be left alone this long
+    Busy(String, Duration),
+    /// The primary refused it; it will never succeed
+    Reject(String),
+}
+
+type Forwarded<T> = std::result::Result<T, ForwardError>;
+
+fn classify_status(
+    status: reqwest::StatusCode,
+    retry_after: Option<&HeaderValue>,
+    body: String,
+) -> ForwardError {
+    let message = format!("{} - {}", status, body);
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let wait = retry_after
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_BUSY_DELAY)
+            .min(MAX_RETRY_DELAY);
+        ForwardError::Busy(message, wait)
+    } else if status.is_server_error() {
+        ForwardError::Retry(message)
+    } else {
+        ForwardError::Reject(message)
+    }
+}
+
+#[derive(Clone)]
+struct Primary {
+    url: String,
+    http: reqwest::Client,
+    /// Until when the primary asked not to get more work
+    busy_until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Primary {
+    fn set_busy(&self, wait: Duration) {
+        if let Ok(mut busy_until) = self.busy_until.lock() {
+            *busy_until = Some(Instant::now() + wait);
+        }
+    }
+
+    /// How much longer the primary asked not to get more work
+    fn busy_for(&self) -> Option<Duration> {
+        let busy_until = (*self.busy_until.lock().ok()?)?;
+        let left = busy_until.saturating_duration_since(Instant::now());
+        (!left.is_zero()).then_some(left)
+    }
+
+    async fn graphql(
+        &self,
+        token: &str,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Forwarded<serde_json::Value> {
+        let response = self
+            .http
+            .post(format!("{}/graphql", self.url))
+            .bearer_auth(token)
+            .json(&serde_json::json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .map_err(|e| ForwardError::Retry(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = response.headers().get(header::RETRY_AFTER).cloned();
+            let body = response.text().await.unwrap_or_default();
+            return Err(classify_status(status, retry_after.as_ref(), body));
+        }
+        let mut body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| ForwardError::Retry(e.to_string()))?;
+        if let Some(errors) = body.get("errors").and_then(|e| e.as_array()) {
+            let messages: Vec<&str> = errors
+                .iter()
+                .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                .collect();
+            return Err(ForwardError::Reject(messages.join("; ")));
+        }
+        Ok(body["data"].take())
+    }
+
+    /// Uploads a file to a URL from `createFlamegraphUploadUrl` in chunks
+    async fn upload(&self, signed_url: &str, content: &[u8]) -> Forwarded<()> {
+        let url = if signed_url.starts_with('/') {
+            format!("{}{}", self.url, signed_url)
+        } else {
+            signed_url.to_string()
+        };
+        let total = content.len();
+        let mut offset = 0;
+        while offset < total {
+            let end = (offset + MAX_CHUNK_BYTES).min(total);
+            let response = self
+                .http
+                .put(&url)
+                .header("Content-Type", "image/svg+xml")
+                .header(
+                    "Content-Range",
+                    format!("bytes {}-{}/{}", offset, end - 1, total),
+                )
+                .body(content[offset..end].to_vec())
+                .send()
+                .await
+                .map_err(|e| ForwardError::Retry(e.to_string()))?;
+            let stored = response
+                .headers()
+                .get(UPLOAD_OFFSET_HEADER.as_str())
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<usize>().ok());
+            let status = response.status();
+            offset = match stored {
+                // A retried forward finds chunks it sent before
+                Some(stored) if status.is_success() || status == reqwest::StatusCode::CONFLICT => {
+                    stored
+                }
+                _ if status.is_success() => end,
+                _ => {
+                    let retry_after = response.headers().get(header::RETRY_AFTER).cloned();
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(classify_status(status, retry_after.as_ref(), body));
+                }
+            };
+        }
+        Ok(())
+    }
+
+    async fn forward(&self, entry: &Entry) -> Forwarded<()> {
+        let token = entry.token.as_str();
+        match &entry.payload {
+            Payload::Report { input } => {
+                self.graphql(
+                    token,
+                    "mutation ($input: CreateReportInput!) { createReport(input: $input) { id } }",
+                    serde_json::json!({ "input": input }),
+                )
+                .await?;
+            }
+            Payload::Output { report_id, content } => {
+                self.graphql(
+                    token,
+                    "mutation ($reportId: ID!, $content: String!) { attachReportOutput(reportId: $reportId, content: $content) { redactions } }",
+                    serde_json::json!({ "reportId": report_id, "content": content }),
+                )
+                .await?;
+            }
+            Payload::Flamegraph {
+                project,
+                report_id,
+                file_name,
+                content,
+            } => {
+                let content = STANDARD
+                    .decode(content)
+                    .map_err(|e| ForwardError::Reject(e.to_string()))?;
+                let sha256 = hex::encode(Sha256::digest(&content));
+                let data = self
+                    .graphql(
+                        token,
+                        "mutation ($projectSlug: String!, $fileName: String!, $sha256: String) { createFlamegraphUploadUrl(projectSlug: $projectSlug, fileName: $fileName, sha256: $sha256) { stored signedUrl storagePath } }",
+                        serde_json::json!({
+                            "projectSlug": project,
+                            "fileName": file_name,
+                            "sha256": sha256
+                        }),
+                    )
+                    .await?;
+                let upload = &data["createFlamegraphUploadUrl"];
+                if let (Some(false), Some(signed_url)) =
+                    (upload["stored"].as_bool(), upload["signedUrl"].as_str())
+                {
+                    self.upload(signed_url, &content).await?;
+                }
+                self.graphql(
+                    token,
+                    "mutation ($reportId: ID!, $storagePath: String!, $fileName: String!, $fileSize: Int!, $sha256: String) { confirmFlamegraphUpload(reportId: $reportId, storagePath: $storagePath, fileName: $fileName, fileSize: $fileSize, sha256: $sha256) { id } }",
+                    serde_json::json!({
+                        "reportId": report_id,
+                        "storagePath": upload["storagePath"],
+                        "fileName": file_name,
+                        "fileSize": content.len(),
+                        "sha256": sha256
+                    }),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Forwards spooled entries in order for as long as the relay runs. While
+/// the primary is unreachable or saturated it waits and retries the same
+/// entry, so nothing overtakes it.
+async fn forward_spool(spool: Arc<Spool>, primary: Primary) {
+    let mut delay = MIN_RETRY_DELAY;
+    loop {
+        let pending = match spool.pending().await {
+            Ok(pending) => pending,
+            Err(e) => {
+                tracing::error!("Failed to read the relay spool: {}", e);
+                Vec::new()
+            }
+        };
+        let mut stalled = None;
+        for path in pending {
+            let result = match tokio::fs::read(&path).await {
+                Ok(bytes) => match serde_json::from_slice::<Entry>(&bytes) {
+                    Ok(entry) => primary.forward(&entry).await,
+                    Err(e) => Err(ForwardError::Reject(format!("Unreadable entry: {}", e))),
+                },
+                Err(e) => {
+                    tracing::error!("Failed to read {}: {}", path.display(), e);
+                    stalled = Some(delay);
+                    break;
+                }
+            };
+            let outcome = match result {
+                Ok(()) => spool.remove(&path).await,
+                Err(ForwardError::Reject(reason)) => {
+                    tracing::warn!("Primary refused {}: {}", path.display(), reason);
+                    spool.reject(&path, &reason).await
+                }
+                Err(ForwardError::Retry(reason)) => {
+                    tracing::warn!(
+                        "Primary unavailable ({}); retrying in {}s",
+                        reason,
+                        delay.as_secs()
+                    );
+                    stalled = Some(delay);
+                    break;
+                }
+                Err(ForwardError::Busy(reason, wait)) => {
+                    tracing::warn!(
+                        "Primary is saturated ({}); retrying in {}s",
+                        reason,
+                        wait.as_secs()
+                    );
+                    primary.set_busy(wait);
+                    stalled = Some(wait);
+                    break;
+                }
+            };
+            if let Err(e) = outcome {
+                tracing::error!("Failed to update {}: {}", path.display(), e);
+            }
+            delay = MIN_RETRY_DELAY;
+        }
+        if let Some(wait) = stalled {
+            tokio::time::sleep(wait).await;
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        } else {
+            let _ = tokio::time::timeout(POLL_INTERVAL, spool.wake.notified()).await;
+        }
+    }
+}
+
+#[derive(Debug)]
+enum RelayError {
+    Unauthorized,
+    /// The primary couldn't be asked about a token it hasn't seen
+    Unavailable(String),
+    Invalid(String),
+    TooLarge,
+    /// The primary is saturated or the spool is full
+    Overloaded(Overload),
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for RelayError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl IntoResponse for RelayError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            Self::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "Missing or invalid token".to_string(),
+            ),
+            Self::Unavailable(reason) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Can't check the token with the primary server: {}", reason),
+            ),
+            Self::Invalid(message) => (StatusCode::BAD_REQUEST, message),
+            Self::TooLarge => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Uploads are limited to {} bytes", MAX_UPLOAD_BYTES),
+            ),
+            Self::Overloaded(overload) => {
+                tracing::warn!("Refusing a submission: {}", overload.reason);
+                let mut response =
+                    (StatusCode::TOO_MANY_REQUESTS, overload.to_string()).into_response();
+                backpressure::throttle(&mut response, Some(overload.retry_after_secs));
+                return response;
+            }
+            Self::Io(e) => {
+                tracing::error!("Failed to spool a submission: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        (status, message).into_response()
+    }
+}
+
+#[derive(Clone)]
+struct RelayState {
+    spool: Arc<Spool>,
+    primary: Primary,
+    /// SHA-256 of tokens the primary accepted
+    tokens: moka::future::Cache<String, ()>,
+    max_spool_bytes: u64,
+}
+
+impl RelayState {
+    /// The caller's token, once the primary has accepted it recently
+    async fn authorize(&self, headers: &HeaderMap) -> std::result::Result<String, RelayError> {
+        let token = bearer_token(headers).ok_or(RelayError::Unauthorized)?;
+        let key = hex::encode(Sha256::digest(token.as_bytes()));
+        if self.tokens.get(&key).await.is_none() {
+            match self
+                .primary
+                .graphql(token, "{ me { id } }", serde_json::json!({}))
+                .await
+            {
+                Ok(_) => self.tokens.insert(key, ()).await,
+                Err(ForwardError::Reject(_)) => return Err(RelayError::Unauthorized),
+                Err(ForwardError::Retry(reason)) => return Err(RelayError::Unavailable(reason)),
+            }
+        }
+        Ok(token.to_string())
+    }
+
+    /// Refuses more work while the primary is saturated or the spool is full
+    fn admit(&self) -> std::result::Result<(), RelayError> {
+        if let Some(wait) = self.primary.busy_for() {
+            return Err(RelayError::Overloaded(Overload {
+                reason: "The primary server is saturated",
+                retry_after_secs: wait.as_secs().max(1),
+            }));
+        }
+        if self.max_spool_bytes > 0 && self.spool.queued_bytes() >= self.max_spool_bytes {
+            return Err(RelayError::Overloaded(Overload {
+                reason: "The relay's spool is full",
+                retry_after_secs: SPOOL_FULL_RETRY_AFTER_SECS,
+            }));
+        }
+        Ok(())
+    }
+
+    async fn accept(&self, token: String, payload: Payload) -> std::result::Result<(), RelayError> {
+        self.admit()?;
+        let entry = Entry {
+            token,
+            received_at: Utc::now(),
+            payload,
+        };
+        self.spool.push(&entry).await?;
+        Ok(())
+    }
+}
+
+/// Checks a `CreateReportInput` enough to catch mistakes the primary would
+/// only report after the submitter has gone, and gives it an id unless it
+/// has one. Returns the id.
+fn prepare_report(input: &mut serde_json::Value) -> std::result::Result<Uuid, String> {
+    let fields = input
+        .as_object_mut()
+        .ok_or("Expected a CreateReportInput object")?;
+    if !fields.get("projectSlug").is_some_and(|s| s.is_string()) {
+        return Err("projectSlug is required".to_string());
+    }
+    let metrics = fields.get("metrics").and_then(|m| m.as_array());
+    if metrics.is_none_or(|m| m.is_empty()) {
+        return Err("Report must contain at least one metric".to_string());
+    }
+    let id = match fields.get("id") {
+        None | Some(serde_json::Value::Null) => Uuid::new_v4(),
+        Some(serde_json::Value::String(id)) => {
+            Uuid::parse_str(id).map_err(|_| "Invalid report id")?
+        }
+        Some(_) => return Err("Invalid report id".to_string()),
+    };
+    fields.insert("id".to_string(), id.to_string().into());
+    Ok(id)
+}
+
+/// Answers like `createReport` would before evaluation, so clients can
+/// treat the report as submitted
+async fn submit_report(
+    State(state): State<RelayState>,
+    headers: HeaderMap,
+    Json(mut input): Json<serde_json::Value>,
+) -> std::result::Result<Response, RelayError> {
+    let token = state.authorize(&headers).await?;
+    let id = prepare_report(&mut input).map_err(RelayError::Invalid)?;
+    let git_hash = input.get("gitHash").cloned();
+    state.accept(token, Payload::Report { input }).await?;
+    let body = serde_json::json!({
+        "id": id,
+        "gitHash": git_hash,
+        "status": "queued",
+        "alerts": []
+    });
+    Ok((StatusCode::ACCEPTED, Json(body)).into_response())
+}
+
+async fn attach_output(
+    State(state): State<RelayState>,
+    Path(report_id): Path<Uuid>,
+    headers: HeaderMap,
+    content: String,
+) -> std::result::Result<StatusCode, RelayError> {
+    let token = state.authorize(&headers).await?;
+    let payload = Payload::Output {
+        report_id: report_id.to_string(),
+        content,
+    };
+    state.accept(token, payload).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+struct FlamegraphQuery {
+    project: String,
+}
+
+async fn upload_flamegraph(
+    State(state): State<RelayState>,
+    Path((report_id, file_name)): Path<(Uuid, String)>,
+    Query(query): Query<FlamegraphQuery>,
+    headers: HeaderMap,
+    content: Bytes,
+) -> std::result::Result<StatusCode, RelayError> {
+    let token = state.authorize(&headers).await?;
+    if content.len() as i64 > MAX_UPLOAD_BYTES {
+        return Err(RelayError::TooLarge);
+    }
+    let payload = Payload::Flamegraph {
+        project: query.project,
+        report_id: report_id.to_string(),
+        file_name,
+        content: STANDARD.encode(&content),
+    };
+    state.accept(token, payload).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn health(State(state): State<RelayState>) -> std::result::Result<Response, RelayError> {
+    let queued = state.spool.pending().await?.len();
+    Ok(Json(serde_json::json!({ "status": "ok", "queued": queued })).into_response())
+}
+
+fn router(state: RelayState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/relay/v1/reports", post(submit_report))
+        .route("/relay/v1/reports/{id}/output", put(attach_output))
+        .route(
+            "/relay/v1/reports/{id}/flamegraphs/{file_name}",
+            put(upload_flamegraph),
+        )
+        .layer(DefaultBodyLimit::max(MAX_REPORT_BYTES))
+        .with_state(state)
+}
+
+/// Runs a relay until it fails
+pub async fn serve(config: RelayConfig) -> Result<()> {
+    let spool = Arc::new(Spool::open(&config.spool_dir)?);
+    let primary = Primary {
+        url: config.primary_url.trim_end_matches('/').to_string(),
+        http: reqwest::Client::builder()
+            .timeout(PRIMARY_TIMEOUT)
+            .build()?,
+        busy_until: Arc::default(),
+    };
+    let state = RelayState {
+        spool: spool.clone(),
+        primary: primary.clone(),
+        tokens: moka::future::Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(TOKEN_CACHE_TTL)
+            .build(),
+        max_spool_bytes: config.max_spool_bytes,
+    };
+
+    let queued = spool.pending().await?.len();
+    tracing::info!(
+        "Relaying to {} with {} submissions queued in {}",
+        primary.url,
+        queued,
+        config.spool_dir.display()
+    );
+    tokio::spawn(forward_spool(spool, primary));
+
+    let addr = format!("0.0.0.0:{}", config.port);
+    tracing::info!("Starting relay on {}", addr);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_report() {
+        let mut input = serde_json::json!({
+            "projectSlug": "core",
+            "metrics": [{ "benchmark": "parse", "measure": "latency", "value": 1.5 }]
+        });
+        let id = prepare_report(&mut input).unwrap();
+        assert_eq!(input["id"], id.to_string());
+        // A resubmission keeps its id
+        assert_eq!(prepare_report(&mut input).unwrap(), id);
+
+        input["id"] = "not-a-uuid".into();
+        assert!(prepare_report(&mut input).is_err());
+        input["metrics"] = serde_json::json!([]);
+        assert!(prepare_report(&mut input).is_err());
+        assert!(prepare_report(&mut serde_json::json!([])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_spool_order() {
+        let dir = std::env::temp_dir().join(format!("driftwatch-relay-{}", Uuid::new_v4()));
+        let spool = Spool::open(&dir).unwrap();
+        let entry = |report_id: &str| Entry {
+            token: "dw_test".to_string(),
+            received_at: Utc::now(),
+            payload: Payload::Output {
+                report_id: report_id.to_string(),
+                content: String::new(),
+            },
+        };
+        for id in ["a", "b", "c"] {
+            spool.push(&entry(id)).await.unwrap();
+        }
+
+        let pending = spool.pending().await.unwrap();
+        let ids: Vec<String> = pending
+            .iter()
+            .map(|path| {
+                let entry: Entry = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+                match entry.payload {
+                    Payload::Output { report_id, .. } => report_id,
+                    _ => unreachable!(),
+                }
+            })
+            .collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+
+        spool
+            .reject(&pending[0], "Project not found")
+            .await
+            .unwrap();
+        assert_eq!(spool.pending().await.unwrap().len(), 2);
+        let reason = pending[0].file_name().unwrap();
+        let reason = dir.join("failed").join(reason).with_extension("error");
+        assert_eq!(
+            std::fs::read_to_string(reason).unwrap(),
+            "Project not found"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_classify_status() {
+        assert!(matches!(
+            classify_status(reqwest::StatusCode::BAD_GATEWAY, None, String::new()),
+            ForwardError::Retry(_)
+        ));
+        assert!(matches!(
+            classify_status(
+                reqwest::StatusCode::TOO_MANY_REQUESTS,
+                Some(&HeaderValue::from_static("30")),
+                String::new()
+            ),
+            ForwardError::Busy(_, wait) if wait == Duration::from_secs(30)
+        ));
+        assert!(matches!(
+            classify_status(reqwest::StatusCode::TOO_MANY_REQUESTS, None, String::new()),
+            ForwardError::Busy(_, wait) if wait == DEFAULT_BUSY_DELAY
+        ));
+        assert!(matches!(
+            classify_status(reqwest::StatusCode::UNAUTHORIZED, None, String::new()),
+            ForwardError::Reject(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_admit() {
+        let dir = std::env::temp_dir().join(format!("driftwatch-relay-{}", Uuid::new_v4()));
+        let state = RelayState {
+            spool: Arc::new(Spool::open(&dir).unwrap()),
+            primary: Primary {
+                url: "http://primary.invalid".to_string(),
+                http: reqwest::Client::new(),
+                busy_until: Arc::default(),
+            },
+            tokens: moka::future::Cache::new(1),
+            max_spool_bytes: 100,
+        };
+        let payload = || Payload::Output {
+            report_id: Uuid::new_v4().to_string(),
+            content: "x".repeat(200),
+        };
+
+        state
+            .accept("dw_test".to_string(), payload())
+            .await
+            .unwrap();
+        let full = state.accept("dw_test".to_string(), payload()).await;
+        assert!(matches!(full, Err(RelayError::Overloaded(_))));
+        let response = full.unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+
+        // Forwarding frees the space, and a reopened spool counts what's queued
+        let pending = state.spool.pending().await.unwrap();
+        assert_eq!(
+            Spool::open(&dir).unwrap().queued_bytes(),
+            state.spool.queued_bytes()
+        );
+        state.spool.remove(&pending[0]).await.unwrap();
+        assert_eq!(state.spool.queued_bytes(), 0);
+        state
+            .accept("dw_test".to_string(), payload())
+            .await
+            .unwrap();
+
+        // The primary asking for a break holds new submissions back too
+        let state = RelayState {
+            max_spool_bytes: 0,
+            ..state
+        };
+        state.primary.set_busy(Duration::from_secs(20));
+        let busy = state.accept("dw_test".to_string(), payload()).await;
+        assert!(matches!(
+            busy,
+            Err(RelayError::Overloaded(Overload { retry_after_secs, .. })) if retry_after_secs <= 20
+        ));
+        state.primary.set_busy(Duration::ZERO);
+        assert!(state.accept("dw_test".to_string(), payload()).await.is_ok());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
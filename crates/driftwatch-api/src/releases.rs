@@ -0,0 +1,55 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement};
+use uuid::Uuid;
+
+/// Results of one benchmark across every report tagged with a release.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct ReleasePoint {
+    pub version: String,
+    pub benchmark: String,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub sample_size: i64,
+    /// When the first report for the release was submitted; releases are
+    /// ordered by it
+    pub first_reported_at: DateTimeWithTimeZone,
+}
+
+/// Per-release values of `measure`, one point per release and benchmark,
+/// oldest release first. Excluded and unfinalized reports are left out.
+pub async fn release_series<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    measure: &str,
+    benchmark: Option<&str>,
+    testbed: Option<&str>,
+) -> Result<Vec<ReleasePoint>, DbErr> {
+    ReleasePoint::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT r.version, b.name AS benchmark,
+                  AVG(m.value) AS mean, MIN(m.value) AS min, MAX(m.value) AS max,
+                  COUNT(*) AS sample_size, MIN(r.created_at) AS first_reported_at
+           FROM metrics m
+           JOIN reports r ON r.id = m.report_id
+           JOIN benchmarks b ON b.id = m.benchmark_id
+           JOIN measures ms ON ms.id = m.measure_id
+           LEFT JOIN testbeds t ON t.id = r.testbed_id
+           WHERE r.project_id = $1
+             AND r.version IS NOT NULL
+             AND r.finalized AND NOT r.excluded
+             AND ms.name = $2
+             AND ($3::text IS NULL OR b.name = $3)
+             AND ($4::text IS NULL OR t.name = $4)
+           GROUP BY r.version, b.name
+           ORDER BY b.name, MIN(r.created_at)"#,
+        [
+            project_id.into(),
+            measure.into(),
+            benchmark.map(str::to_string).into(),
+            testbed.map(str::to_string).into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
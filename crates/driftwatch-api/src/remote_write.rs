@@ -0,0 +1,885 @@
+//! Prometheus remote_write ingestion, so production SLIs such as request
+//! latency can be tracked next to microbenchmarks.
+//!
+//! Prometheus POSTs snappy-compressed `WriteRequest`s to
+//! `/prometheus/{project_slug}/write` with a bearer token. Each project has
+//! rules that pick series by metric name and label values and map them onto
+//! a benchmark, measure, branch and testbed; series no rule matches are
+//! ignored, so Prometheus can send everything.
+//!
+//! Prometheus sends a sample per series every scrape, far more often than
+//! anyone wants a report, so samples are summed per series into hourly
+//! buckets. Once an hour has closed, plus a grace period for late writes,
+//! its buckets become one report per branch and testbed whose metrics carry
+//! the hour's mean, with its minimum and maximum as bounds. Samples for an
+//! hour that was already reported, or from the future, are dropped.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use chrono::{DateTime, TimeDelta, Utc};
+use prost::Message;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, QueryFilter, Statement, TransactionTrait, Value,
+};
+use uuid::Uuid;
+
+use crate::auth::{bearer_token, validate_token, TsaAuth};
+use crate::backpressure::{Backpressure, Overload};
+use crate::cache::AppCache;
+use crate::entities::{self, remote_write_rule, report};
+use crate::error_reports;
+use crate::ingest::{self, Dimensions, NewMetric, NewReport};
+use crate::jobs::JobRegistry;
+
+/// Samples are summed over windows this long; each becomes one report
+pub const ROLLUP_WINDOW: TimeDelta = TimeDelta::hours(1);
+
+/// How long after a window closes its samples are still accepted
+pub const FLUSH_DELAY: TimeDelta = TimeDelta::minutes(10);
+
+/// How far ahead of the server's clock a sample may be
+const MAX_CLOCK_SKEW: TimeDelta = TimeDelta::minutes(5);
+
+/// How often closed windows are turned into reports
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Job kind that turns closed windows of samples into reports
+pub const FLUSH_JOB: &str = "flush_remote_write";
+
+/// Largest compressed request body, and the most it may decompress to
+pub const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+const MAX_DECODED_BYTES: usize = 32 * 1024 * 1024;
+
+/// Bucket rows per INSERT, well below Postgres' bind parameter limit
+const UPSERT_CHUNK_SIZE: usize = 1000;
+
+/// The remote_write protobuf messages, minus the fields Driftwatch ignores
+#[derive(Clone, PartialEq, Message)]
+pub struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub timeseries: Vec<TimeSeries>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TimeSeries {
+    #[prost(message, repeated, tag = "1")]
+    pub labels: Vec<Label>,
+    #[prost(message, repeated, tag = "2")]
+    pub samples: Vec<Sample>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Label {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Sample {
+    #[prost(double, tag = "1")]
+    pub value: f64,
+    /// Milliseconds since the Unix epoch
+    #[prost(int64, tag = "2")]
+    pub timestamp: i64,
+}
+
+/// Decompresses a snappy block, the framing-less format remote_write uses,
+/// refusing anything that claims to be larger than `limit`.
+pub fn decompress_snappy(input: &[u8], limit: usize) -> Result<Vec<u8>, String> {
+    let (length, mut pos) = read_varint(input).ok_or("Truncated snappy header")?;
+    let length = usize::try_from(length).map_err(|_| "Snappy length overflows")?;
+    if length > limit {
+        return Err(format!("Request decompresses to more than {} bytes", limit));
+    }
+
+    let mut out = Vec::with_capacity(length);
+    let take = |pos: &mut usize, n: usize| -> Result<&[u8], String> {
+        let bytes = input
+            .get(*pos..*pos + n)
+            .ok_or_else(|| "Truncated snappy data".to_string())?;
+        *pos += n;
+        Ok(bytes)
+    };
+    let little_endian = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .rev()
+            .fold(0usize, |acc, &b| (acc << 8) | b as usize)
+    };
+
+    while pos < input.len() {
+        let tag = input[pos];
+        pos += 1;
+        let (len, offset) = match tag & 0b11 {
+            0 => {
+                let len = match (tag >> 2) as usize {
+                    n @ 0..=59 => n + 1,
+                    n => little_endian(take(&mut pos, n - 59)?) + 1,
+                };
+                if out.len() + len > length {
+                    return Err("Snappy data exceeds its declared length".to_string());
+                }
+                out.extend_from_slice(take(&mut pos, len)?);
+                continue;
+            }
+            1 => {
+                let len = 4 + ((tag >> 2) & 0b111) as usize;
+                let offset = (((tag >> 5) as usize) << 8) | take(&mut pos, 1)?[0] as usize;
+                (len, offset)
+            }
+            2 => (1 + (tag >> 2) as usize, little_endian(take(&mut pos, 2)?)),
+            _ => (1 + (tag >> 2) as usize, little_endian(take(&mut pos, 4)?)),
+        };
+        if offset == 0 || offset > out.len() {
+            return Err("Invalid snappy back-reference".to_string());
+        }
+        if out.len() + len > length {
+            return Err("Snappy data exceeds its declared length".to_string());
+        }
+        // Copies may overlap what they produce, so go a byte at a time
+        let start = out.len() - offset;
+        for i in 0..len {
+            out.push(out[start + i]);
+        }
+    }
+
+    if out.len() != length {
+        return Err("Snappy data is shorter than its declared length".to_string());
+    }
+    Ok(out)
+}
+
+fn read_varint(input: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in input.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Checks a benchmark or testbed template: text with `{label}` placeholders
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Templates can't be empty".to_string());
+    }
+    let mut rest = template;
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return Err(format!("Unmatched '}}' in {:?}", template));
+        }
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed '{{' in {:?}", template))?;
+        let name = &rest[open + 1..open + close];
+        if name.is_empty() || name.contains('{') {
+            return Err(format!("Placeholders need a label name in {:?}", template));
+        }
+        rest = &rest[open + close + 1..];
+    }
+    Ok(())
+}
+
+/// Fills a template's placeholders from the series' labels, or `None` when
+/// the series lacks one of them
+pub fn render_template(template: &str, labels: &HashMap<&str, &str>) -> Option<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let close = open + rest[open..].find('}')?;
+        out.push_str(&rest[..open]);
+        out.push_str(labels.get(&rest[open + 1..close])?);
+        rest = &rest[close + 1..];
+    }
+    out.push_str(rest);
+    let out = out.trim();
+    (!out.is_empty()).then(|| out.to_string())
+}
+
+/// A rule's label matchers, as stored
+pub fn matchers(rule: &remote_write_rule::Model) -> Vec<(String, String)> {
+    rule.matchers
+        .as_object()
+        .map(|object| {
+            object
+                .iter()
+                .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The start of the window a sample falls in
+pub fn window_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    let window = ROLLUP_WINDOW.num_seconds();
+    let start = at.timestamp().div_euclid(window) * window;
+    DateTime::from_timestamp(start, 0).unwrap_or(at)
+}
+
+/// Whether the window starting at `start` no longer takes samples
+pub fn is_closed(start: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    start + ROLLUP_WINDOW + FLUSH_DELAY <= now
+}
+
+/// Where a sample is summed: one per rule, rendered series and window
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BucketKey {
+    pub rule_id: Uuid,
+    pub benchmark: String,
+    pub testbed: String,
+    pub start: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bucket {
+    pub total: f64,
+    pub samples: i64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Bucket {
+    fn new(value: f64) -> Self {
+        Self {
+            total: value,
+            samples: 1,
+            min: value,
+            max: value,
+        }
+    }
+
+    fn merge(&mut self, other: Bucket) {
+        self.total += other.total;
+        self.samples += other.samples;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.total / self.samples as f64
+    }
+}
+
+/// The samples of one write request, summed per bucket
+#[derive(Debug, Default)]
+pub struct Rollup {
+    pub buckets: HashMap<BucketKey, Bucket>,
+    /// Matched samples that were too late, too early, not a number, or
+    /// lacked a label a template needs
+    pub dropped: usize,
+}
+
+/// Sums the samples of the series the rules match into buckets
+pub fn rollup(
+    rules: &[remote_write_rule::Model],
+    request: &WriteRequest,
+    now: DateTime<Utc>,
+) -> Rollup {
+    let rules: Vec<_> = rules.iter().map(|rule| (rule, matchers(rule))).collect();
+    let mut rollup = Rollup::default();
+
+    for series in &request.timeseries {
+        let labels: HashMap<&str, &str> = series
+            .labels
+            .iter()
+            .map(|l| (l.name.as_str(), l.value.as_str()))
+            .collect();
+        let Some(name) = labels.get("__name__") else {
+            continue;
+        };
+
+        for (rule, matchers) in &rules {
+            if rule.metric != *name
+                || !matchers
+                    .iter()
+                    .all(|(label, value)| labels.get(label.as_str()) == Some(&value.as_str()))
+            {
+                continue;
+            }
+            let (Some(benchmark), Some(testbed)) = (
+                render_template(&rule.benchmark, &labels),
+                render_template(&rule.testbed, &labels),
+            ) else {
+                rollup.dropped += series.samples.len();
+                continue;
+            };
+
+            for sample in &series.samples {
+                // Prometheus marks stale series with a NaN
+                let at = DateTime::from_timestamp_millis(sample.timestamp);
+                let Some(at) = at.filter(|at| *at <= now + MAX_CLOCK_SKEW) else {
+                    rollup.dropped += 1;
+                    continue;
+                };
+                let start = window_start(at);
+                if !sample.value.is_finite() || is_closed(start, now) {
+                    rollup.dropped += 1;
+                    continue;
+                }
+                let key = BucketKey {
+                    rule_id: rule.id,
+                    benchmark: benchmark.clone(),
+                    testbed: testbed.clone(),
+                    start,
+                };
+                let bucket = Bucket::new(sample.value);
+                rollup
+                    .buckets
+                    .entry(key)
+                    .and_modify(|b| b.merge(bucket))
+                    .or_insert(bucket);
+            }
+        }
+    }
+    rollup
+}
+
+/// Adds the buckets to what's stored for their windows and returns how
+/// many were stored. Buckets for a window a flush has already reported are
+/// dropped; the rule rows are locked so a flush either waits for the write
+/// or the write sees what it flushed.
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    buckets: &HashMap<BucketKey, Bucket>,
+) -> Result<u64, DbErr> {
+    let buckets: Vec<_> = buckets.iter().collect();
+    let mut stored = 0;
+    for chunk in buckets.chunks(UPSERT_CHUNK_SIZE) {
+        let mut rows = Vec::with_capacity(chunk.len());
+        let mut values: Vec<Value> = Vec::with_capacity(chunk.len() * 8);
+        for (key, bucket) in chunk {
+            let n = values.len();
+            rows.push(format!(
+                "(${}, ${}, ${}, ${}, ${}, ${}, ${}, ${})",
+                n + 1,
+                n + 2,
+                n + 3,
+                n + 4,
+                n + 5,
+                n + 6,
+                n + 7,
+                n + 8
+            ));
+            values.extend([
+                key.rule_id.into(),
+                key.benchmark.clone().into(),
+                key.testbed.clone().into(),
+                key.start.fixed_offset().into(),
+                bucket.total.into(),
+                bucket.samples.into(),
+                bucket.min.into(),
+                bucket.max.into(),
+            ]);
+        }
+        let result = db
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                format!(
+                    r#"INSERT INTO remote_write_buckets
+                     (rule_id, benchmark, testbed, bucket_start, total, samples, min_value, max_value)
+                   SELECT v.* FROM (VALUES {}) AS v
+                     (rule_id, benchmark, testbed, bucket_start, total, samples, min_value, max_value)
+                   JOIN remote_write_rules r ON r.id = v.rule_id
+                   WHERE r.flushed_until IS NULL OR v.bucket_start > r.flushed_until
+                   FOR SHARE OF r
+                   ON CONFLICT (rule_id, benchmark, testbed, bucket_start) DO UPDATE SET
+                     total = remote_write_buckets.total + EXCLUDED.total,
+                     samples = remote_write_buckets.samples + EXCLUDED.samples,
+                     min_value = LEAST(remote_write_buckets.min_value, EXCLUDED.min_value),
+                     max_value = GREATEST(remote_write_buckets.max_value, EXCLUDED.max_value)"#,
+                    rows.join(", ")
+                ),
+                values,
+            ))
+            .await?;
+        stored += result.rows_affected();
+    }
+    Ok(stored)
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ClosedBucket {
+    project_id: Uuid,
+    branch: String,
+    measure: String,
+    benchmark: String,
+    testbed: String,
+    bucket_start: DateTimeWithTimeZone,
+    total: f64,
+    samples: i64,
+    min_value: f64,
+    max_value: f64,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ClosedProject {
+    project_id: Uuid,
+}
+
+/// Turns every closed window into reports, one per project, branch,
+/// testbed and window, and returns them. Each project is flushed in its own
+/// transaction, so one that fails doesn't hold back the others; its windows
+/// are kept for the next flush.
+pub async fn flush(
+    db: &DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<report::Model>, DbErr> {
+    let cutoff = (now - ROLLUP_WINDOW - FLUSH_DELAY).fixed_offset();
+    let projects = ClosedProject::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT DISTINCT r.project_id
+           FROM remote_write_buckets b
+           JOIN remote_write_rules r ON r.id = b.rule_id
+           WHERE b.bucket_start <= $1"#,
+        [cutoff.into()],
+    ))
+    .all(db)
+    .await?;
+
+    let mut created = Vec::new();
+    for ClosedProject { project_id } in projects {
+        match flush_project(db, project_id, cutoff).await {
+            Ok(reports) => created.extend(reports),
+            Err(e) => {
+                tracing::error!(
+                    "Remote write flush failed for project {}: {}",
+                    project_id,
+                    e
+                );
+                error_reports::capture(
+                    &e.into(),
+                    "remote_write",
+                    &[("project_id", project_id.to_string())],
+                );
+            }
+        }
+    }
+    Ok(created)
+}
+
+async fn flush_project(
+    db: &DatabaseConnection,
+    project_id: Uuid,
+    cutoff: DateTimeWithTimeZone,
+) -> Result<Vec<report::Model>, DbErr> {
+    let txn = db.begin().await?;
+    // Waits for writes to the project's rules in progress, and makes later
+    // ones drop samples for the windows flushed here
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"UPDATE remote_write_rules
+           SET flushed_until = GREATEST(flushed_until, $1)
+           WHERE project_id = $2"#,
+        [cutoff.into(), project_id.into()],
+    ))
+    .await?;
+    let closed = ClosedBucket::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"DELETE FROM remote_write_buckets b
+           USING remote_write_rules r
+           WHERE r.id = b.rule_id AND r.project_id = $2 AND b.bucket_start <= $1
+           RETURNING r.project_id, r.branch, r.measure, b.benchmark, b.testbed,
+                     b.bucket_start, b.total, b.samples, b.min_value, b.max_value"#,
+        [cutoff.into(), project_id.into()],
+    ))
+    .all(&txn)
+    .await?;
+
+    // Two rules can map onto the same benchmark and measure
+    let mut reports: BTreeMap<_, BTreeMap<_, Bucket>> = BTreeMap::new();
+    for row in closed {
+        let bucket = Bucket {
+            total: row.total,
+            samples: row.samples,
+            min: row.min_value,
+            max: row.max_value,
+        };
+        reports
+            .entry((row.project_id, row.branch, row.testbed, row.bucket_start))
+            .or_default()
+            .entry((row.benchmark, row.measure))
+            .and_modify(|b| b.merge(bucket))
+            .or_insert(bucket);
+    }
+
+    let mut created = Vec::with_capacity(reports.len());
+    let mut dimensions = Dimensions::default();
+    for ((project_id, branch, testbed, start), metrics) in reports {
+        let input = NewReport {
+            id: None,
+            project_id,
+            branch,
+            testbed,
+            git_hash: None,
+            pr_number: None,
+            commit_message: None,
+            commit_author: None,
+            committed_at: None,
+            base_branch: None,
+            merge_base_hash: None,
+            evaluated_commit: None,
+            version: None,
+            kind: report::ReportKind::Push,
+            exit_code: None,
+            partial: false,
+            created_at: start + ROLLUP_WINDOW,
+            context: vec![("source".to_string(), "prometheus".to_string())],
+            tags: Vec::new(),
+            expected_benchmarks: Vec::new(),
+        };
+        let metrics = metrics
+            .into_iter()
+            .map(|((benchmark, measure), bucket)| NewMetric {
+                benchmark,
+                measure,
+                value: bucket.mean(),
+                lower_value: Some(bucket.min),
+                upper_value: Some(bucket.max),
+                reported_change: None,
+                outliers: None,
+            })
+            .collect();
+        let (report, _) = ingest::insert_report(&txn, &mut dimensions, input, metrics).await?;
+        ingest::enqueue_evaluation(&txn, report.id).await?;
+        created.push(report);
+    }
+    txn.commit().await?;
+    Ok(created)
+}
+
+/// Registers turning closed windows into reports
+pub fn register_jobs(registry: &mut JobRegistry, cache: AppCache) {
+    registry.schedule(FLUSH_JOB, FLUSH_INTERVAL, move |db| {
+        let cache = cache.clone();
+        async move {
+            match flush(&db, Utc::now()).await {
+                Ok(reports) if reports.is_empty() => {}
+                Ok(reports) => {
+                    for report in &reports {
+                        cache.invalidate_latest_reports(report.project_id).await;
+                    }
+                    tracing::info!(
+                        "Created {} reports from remote_write samples",
+                        reports.len()
+                    );
+                }
+                Err(e) => {
+                    tracing::error!("Remote write flush failed: {}", e);
+                    error_reports::capture(&e.into(), "remote_write", &[]);
+                }
+            }
+            Ok(())
+        }
+    });
+}
+
+/// Why a write request was refused
+#[derive(Debug)]
+pub enum WriteError {
+    Unauthorized(String),
+    NotFound,
+    Invalid(String),
+    Overloaded(Overload),
+    Db(DbErr),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized(message) => write!(f, "{}", message),
+            Self::NotFound => write!(f, "Project not found"),
+            Self::Invalid(message) => write!(f, "{}", message),
+            Self::Overloaded(overload) => write!(f, "{}", overload),
+            Self::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+impl From<DbErr> for WriteError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+impl IntoResponse for WriteError {
+    fn into_response(self) -> Response {
+        // Prometheus retries 5xx responses and drops the batch on 4xx
+        let status = match &self {
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::Invalid(_) => StatusCode::BAD_REQUEST,
+            // Not the 429 other uploads get: Prometheus only retries those
+            // when configured to, and would drop the batch
+            Self::Overloaded(overload) => {
+                tracing::warn!("Refusing remote_write: {}", overload.reason);
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(header::RETRY_AFTER, overload.retry_after_secs.to_string())],
+                    self.to_string(),
+                )
+                    .into_response();
+            }
+            Self::Db(e) => {
+                tracing::error!("Remote write failed: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[derive(Clone)]
+struct WriteState {
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+    backpressure: Backpressure,
+}
+
+async fn write(
+    State(state): State<WriteState>,
+    Path(project_slug): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, WriteError> {
+    let token = bearer_token(&headers)
+        .ok_or_else(|| WriteError::Unauthorized("Missing bearer token".to_string()))?;
+    let user = validate_token(token, &state.auth)
+        .await
+        .map_err(|e| WriteError::Unauthorized(e.0))?;
+    let project = state
+        .cache
+        .resolve_project(&state.db, user.user_id(), &project_slug)
+        .await?
+        .ok_or(WriteError::NotFound)?;
+    if let Some(overload) = state.backpressure.check() {
+        return Err(WriteError::Overloaded(overload));
+    }
+
+    let encoding = headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !encoding.eq_ignore_ascii_case("snappy") {
+        return Err(WriteError::Invalid(
+            "Expected Content-Encoding: snappy".to_string(),
+        ));
+    }
+    let decoded = decompress_snappy(&body, MAX_DECODED_BYTES).map_err(WriteError::Invalid)?;
+    let request = WriteRequest::decode(decoded.as_slice())
+        .map_err(|e| WriteError::Invalid(format!("Invalid WriteRequest: {}", e)))?;
+
+    let rules = entities::RemoteWriteRule::find()
+        .filter(remote_write_rule::Column::ProjectId.eq(project.id))
+        .all(&state.db)
+        .await?;
+    let rollup = rollup(&rules, &request, Utc::now());
+    let stored = record(&state.db, &rollup.buckets).await?;
+    if rollup.dropped > 0 {
+        tracing::debug!(
+            "Dropped {} remote_write samples for {}",
+            rollup.dropped,
+            project.slug
+        );
+    }
+    if stored < rollup.buckets.len() as u64 {
+        tracing::debug!(
+            "Dropped {} remote_write buckets for {} whose hour was already reported",
+            rollup.buckets.len() as u64 - stored,
+            project.slug
+        );
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Routes for `POST /prometheus/{project_slug}/write`, authenticated like
+/// the GraphQL API with an API key as bearer token, and refused while the
+/// server is saturated
+pub fn router<S: Clone + Send + Sync + 'static>(
+    db: DatabaseConnection,
+    auth: Arc<TsaAuth>,
+    cache: AppCache,
+    backpressure: Backpressure,
+) -> Router<S> {
+    Router::new()
+        .route("/prometheus/{project_slug}/write", post(write))
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        .with_state(WriteState {
+            db,
+            auth,
+            cache,
+            backpressure,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().into()
+    }
+
+    fn rule(
+        metric: &str,
+        matchers: serde_json::Value,
+        benchmark: &str,
+    ) -> remote_write_rule::Model {
+        remote_write_rule::Model {
+            id: Uuid::new_v4(),
+            project_id: Uuid::nil(),
+            metric: metric.to_string(),
+            matchers,
+            benchmark: benchmark.to_string(),
+            measure: "latency".to_string(),
+            branch: "main".to_string(),
+            testbed: "{region}".to_string(),
+            created_at: at("2024-01-01T00:00:00Z").into(),
+            flushed_until: None,
+        }
+    }
+
+    fn series(labels: &[(&str, &str)], samples: &[(f64, &str)]) -> TimeSeries {
+        TimeSeries {
+            labels: labels
+                .iter()
+                .map(|(name, value)| Label {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+            samples: samples
+                .iter()
+                .map(|(value, time)| Sample {
+                    value: *value,
+                    timestamp: at(time).timestamp_millis(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_decompress_snappy() {
+        // "abc" as a literal, then a 9-byte copy from 3 bytes back
+        let compressed = [12, 0x08, b'a', b'b', b'c', 0x15, 0x03];
+        assert_eq!(
+            decompress_snappy(&compressed, 64).unwrap(),
+            b"abcabcabcabc".to_vec()
+        );
+
+        assert!(decompress_snappy(&compressed, 8).is_err());
+        assert!(decompress_snappy(&compressed[..4], 64).is_err());
+        assert!(decompress_snappy(&[12, 0x08, b'a', b'b', b'c', 0x15, 0x04], 64).is_err());
+        assert!(decompress_snappy(&[13, 0x08, b'a', b'b', b'c', 0x15, 0x03], 64).is_err());
+    }
+
+    #[test]
+    fn test_templates() {
+        let labels = HashMap::from([("handler", "/search"), ("region", "eu")]);
+        assert_eq!(
+            render_template("GET {handler}", &labels).as_deref(),
+            Some("GET /search")
+        );
+        assert_eq!(render_template("{missing}", &labels), None);
+
+        assert!(validate_template("{handler} in {region}").is_ok());
+        assert!(validate_template("api").is_ok());
+        for invalid in ["", "{}", "{handler", "handler}", "{{handler}}"] {
+            assert!(validate_template(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_rollup() {
+        let now = at("2024-01-08T10:05:00Z");
+        let rules = [rule(
+            "http_request_duration_seconds",
+            serde_json::json!({ "quantile": "0.99" }),
+            "{handler}",
+        )];
+        let request = WriteRequest {
+            timeseries: vec![
+                series(
+                    &[
+                        ("__name__", "http_request_duration_seconds"),
+                        ("handler", "/search"),
+                        ("quantile", "0.99"),
+                        ("region", "eu"),
+                    ],
+                    &[
+                        (0.2, "2024-01-08T10:00:00Z"),
+                        (0.4, "2024-01-08T10:02:00Z"),
+                        // The previous hour is still open
+                        (0.3, "2024-01-08T09:59:00Z"),
+                        (f64::NAN, "2024-01-08T10:01:00Z"),
+                        // Reported already, and not yet happened
+                        (0.1, "2024-01-08T08:59:00Z"),
+                        (0.1, "2024-01-08T10:20:00Z"),
+                    ],
+                ),
+                // Wrong quantile, and no region for the testbed
+                series(
+                    &[
+                        ("__name__", "http_request_duration_seconds"),
+                        ("handler", "/search"),
+                        ("quantile", "0.5"),
+                        ("region", "eu"),
+                    ],
+                    &[(0.1, "2024-01-08T10:00:00Z")],
+                ),
+                series(
+                    &[
+                        ("__name__", "http_request_duration_seconds"),
+                        ("handler", "/search"),
+                        ("quantile", "0.99"),
+                    ],
+                    &[(0.1, "2024-01-08T10:00:00Z")],
+                ),
+            ],
+        };
+
+        let rollup = rollup(&rules, &request, now);
+        assert_eq!(rollup.dropped, 4);
+        assert_eq!(rollup.buckets.len(), 2);
+
+        let key = |start| BucketKey {
+            rule_id: rules[0].id,
+            benchmark: "/search".to_string(),
+            testbed: "eu".to_string(),
+            start: at(start),
+        };
+        let current = rollup.buckets[&key("2024-01-08T10:00:00Z")];
+        assert_eq!(current.samples, 2);
+        assert!((current.mean() - 0.3).abs() < 1e-9);
+        assert_eq!((current.min, current.max), (0.2, 0.4));
+        assert_eq!(rollup.buckets[&key("2024-01-08T09:00:00Z")].samples, 1);
+    }
+
+    #[test]
+    fn test_windows() {
+        assert_eq!(
+            window_start(at("2024-01-08T10:59:59Z")),
+            at("2024-01-08T10:00:00Z")
+        );
+        let start = at("2024-01-08T10:00:00Z");
+        assert!(!is_closed(start, at("2024-01-08T11:09:59Z")));
+        assert!(is_closed(start, at("2024-01-08T11:10:00Z")));
+    }
+}
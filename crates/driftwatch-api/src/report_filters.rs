@@ -0,0 +1,153 @@
+//! Report tags and the saved filters that pick reports by tag and context,
+//! e.g. a "nightlies" filter for reports tagged `nightly`.
+
+use std::collections::BTreeSet;
+
+use sea_orm::sea_query::Query;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, Select};
+use uuid::Uuid;
+
+use crate::context;
+use crate::entities::{self, report, report_context, report_filter, report_tag};
+
+/// Most tags accepted on one report or filter.
+pub const MAX_TAGS: usize = 16;
+
+const MAX_TAG_LEN: usize = 64;
+const MAX_FILTER_NAME_LEN: usize = 64;
+
+/// Checks, trims and deduplicates tags. They follow the rules for context
+/// keys, so they never contain whitespace.
+pub fn validate_tags(tags: Vec<String>) -> Result<Vec<String>, String> {
+    let tags: BTreeSet<String> = tags.iter().map(|t| t.trim().to_string()).collect();
+    if tags.len() > MAX_TAGS {
+        return Err(format!("Reports can carry at most {} tags", MAX_TAGS));
+    }
+    for tag in &tags {
+        if tag.is_empty() || tag.len() > MAX_TAG_LEN {
+            return Err(format!("Tags must be 1-{} characters long", MAX_TAG_LEN));
+        }
+        if !tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        {
+            return Err(format!(
+                "Tag '{}' may only contain letters, digits, '_', '.' and '-'",
+                tag
+            ));
+        }
+    }
+    Ok(tags.into_iter().collect())
+}
+
+pub fn validate_filter_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_FILTER_NAME_LEN {
+        return Err(format!(
+            "Filter names must be 1-{} characters long",
+            MAX_FILTER_NAME_LEN
+        ));
+    }
+    Ok(name)
+}
+
+/// What a report must carry to be listed: every tag and every context
+/// key/value.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReportCriteria {
+    pub tags: Vec<String>,
+    pub context: Vec<(String, String)>,
+}
+
+impl ReportCriteria {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.context.is_empty()
+    }
+
+    /// Adds a saved filter's tags and context to these
+    pub fn merge(&mut self, filter: &report_filter::Model) {
+        self.tags.extend(filter.tag_list());
+        self.context.extend(filter.context_entries());
+    }
+
+    pub fn apply(self, mut query: Select<report::Entity>) -> Select<report::Entity> {
+        for tag in self.tags {
+            query = query.filter(
+                report::Column::Id.in_subquery(
+                    Query::select()
+                        .column(report_tag::Column::ReportId)
+                        .from(report_tag::Entity)
+                        .and_where(report_tag::Column::Tag.eq(tag))
+                        .to_owned(),
+                ),
+            );
+        }
+        for (key, value) in self.context {
+            query = query.filter(
+                report::Column::Id.in_subquery(
+                    Query::select()
+                        .column(report_context::Column::ReportId)
+                        .from(report_context::Entity)
+                        .and_where(report_context::Column::Key.eq(key))
+                        .and_where(report_context::Column::Value.eq(value))
+                        .to_owned(),
+                ),
+            );
+        }
+        query
+    }
+}
+
+/// Checks a filter's tags and context before it's saved
+pub fn validate_criteria(criteria: ReportCriteria) -> Result<ReportCriteria, String> {
+    let criteria = ReportCriteria {
+        tags: validate_tags(criteria.tags)?,
+        context: context::validate_context(criteria.context)?,
+    };
+    if criteria.is_empty() {
+        return Err("A filter needs at least one tag or context entry".to_string());
+    }
+    Ok(criteria)
+}
+
+pub async fn find<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<Option<report_filter::Model>, DbErr> {
+    entities::ReportFilter::find()
+        .filter(report_filter::Column::ProjectId.eq(project_id))
+        .filter(report_filter::Column::Name.eq(name.trim()))
+        .one(db)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_tags() {
+        assert_eq!(
+            validate_tags(vec![" nightly".into(), "rc-1".into(), "nightly".into()]).unwrap(),
+            vec!["nightly", "rc-1"]
+        );
+        assert!(validate_tags(vec!["".into()]).is_err());
+        assert!(validate_tags(vec!["two words".into()]).is_err());
+        assert!(validate_tags((0..=MAX_TAGS).map(|i| i.to_string()).collect()).is_err());
+    }
+
+    #[test]
+    fn test_validate_criteria() {
+        assert!(validate_criteria(ReportCriteria::default()).is_err());
+        let criteria = validate_criteria(ReportCriteria {
+            tags: vec![],
+            context: vec![(" allocator ".into(), "jemalloc".into())],
+        })
+        .unwrap();
+        assert_eq!(
+            criteria.context,
+            vec![("allocator".to_string(), "jemalloc".to_string())]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Per-request IDs and access logging.
+//!
+//! Every HTTP request gets an ID, taken from the caller's `x-request-id`
+//! when it looks sane and generated otherwise. The ID is echoed in the
+//! response header, attached to every GraphQL error as `requestId`, and
+//! logged with the method, path, status, GraphQL operation, caller and
+//! duration, so a user quoting it in a bug report leads to the log line.
+//! Tokens are never logged; callers are identified by user id and API key
+//! name, as in the deprecation log, and the token in a feed, share or
+//! upload URL is masked in the logged path.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_graphql::{Response, Value, Variables};
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use sentry::SentryFutureExt;
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::graphql::versioning::token_label;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// GraphQL error extension carrying the request ID
+pub const REQUEST_ID_EXTENSION: &str = "requestId";
+
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// First path segments of URLs whose next segment is a secret token
+const TOKEN_PATH_PREFIXES: [&str; 3] = ["feeds", "shares", "uploads"];
+
+/// The current request's ID, in the HTTP request's extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Keeps a caller-supplied ID only if it can't forge log lines
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        let supplied = value
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+            .filter(|id| {
+                id.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            });
+        match supplied {
+            Some(id) => Self(id.to_string()),
+            None => Self(Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+/// Who made a GraphQL request and what it ran, added to the response by
+/// the GraphQL handler for the access log
+#[derive(Debug, Clone)]
+pub struct RequestAttribution {
+    pub operation: Option<String>,
+    /// Project slug from the request's variables, when it names one
+    pub project: Option<String>,
+    pub user_id: Option<Uuid>,
+    /// API key name and prefix, `session` or `anonymous`; never the token
+    pub token: String,
+    pub errors: usize,
+}
+
+impl RequestAttribution {
+    pub fn new(request: &async_graphql::Request, user: Option<&AuthUser>, errors: usize) -> Self {
+        Self {
+            operation: request.operation_name.clone(),
+            project: project_slug(&request.variables),
+            user_id: user.map(|u| u.user_id()),
+            token: token_label(user),
+            errors,
+        }
+    }
+}
+
+/// `projectSlug`, at the top level or in `input`, or a top-level `slug`,
+/// which project queries and mutations take
+fn project_slug(variables: &Variables) -> Option<String> {
+    let input = match variables.get("input") {
+        Some(Value::Object(input)) => input.get("projectSlug"),
+        _ => None,
+    };
+    match variables
+        .get("projectSlug")
+        .or(input)
+        .or_else(|| variables.get("slug"))
+    {
+        Some(Value::String(slug)) => Some(slug.clone()),
+        _ => None,
+    }
+}
+
+/// `path` with the token of a tokenized URL, which grants access on its
+/// own, replaced by `{token}`
+fn loggable_path(path: &str) -> String {
+    let mut segments: Vec<&str> = path.split('/').collect();
+    if segments.len() > 2 && TOKEN_PATH_PREFIXES.contains(&segments[1]) && !segments[2].is_empty() {
+        segments[2] = "{token}";
+    }
+    segments.join("/")
+}
+
+/// Axum middleware assigning request IDs and logging each request
+pub async fn middleware(mut request: Request, next: Next) -> axum::response::Response {
+    let started = Instant::now();
+    let id = RequestId::from_header(request.headers().get(&REQUEST_ID_HEADER));
+    let method = request.method().clone();
+    let path = loggable_path(request.uri().path());
+    request.extensions_mut().insert(id.clone());
+
+    // Reports of panics while handling the request carry its ID
+    let hub = Arc::new(sentry::Hub::new_from_top(sentry::Hub::current()));
+    hub.configure_scope(|scope| scope.set_tag("request_id", &id.0));
+    let mut response = next.run(request).bind_hub(hub).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id.0) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let status = response.status().as_u16();
+    match response.extensions().get::<RequestAttribution>() {
+        Some(attribution) => tracing::info!(
+            request_id = %id.0,
+            method = %method,
+            path = %path,
+            status,
+            operation = attribution.operation.as_deref().unwrap_or("anonymous"),
+            project = attribution.project.as_deref(),
+            user_id = attribution.user_id.map(tracing::field::display),
+            token = %attribution.token,
+            errors = attribution.errors,
+            duration_ms,
+            "GraphQL request"
+        ),
+        None if path == "/health" => tracing::debug!(
+            request_id = %id.0,
+            status,
+            duration_ms,
+            "Health check"
+        ),
+        None => tracing::info!(
+            request_id = %id.0,
+            method = %method,
+            path = %path,
+            status,
+            duration_ms,
+            "HTTP request"
+        ),
+    }
+    response
+}
+
+/// Adds `requestId` to the extensions of every error in `response`,
+/// including parse and validation errors
+pub fn tag_errors(response: &mut Response, id: &RequestId) {
+    for error in &mut response.errors {
+        error
+            .extensions
+            .get_or_insert_with(Default::default)
+            .set(REQUEST_ID_EXTENSION, id.0.as_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, Value};
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_request_id_from_header() {
+        let keep = HeaderValue::from_static("ci-run-42.retry_1");
+        assert_eq!(RequestId::from_header(Some(&keep)).0, "ci-run-42.retry_1");
+
+        for bad in ["", "id with spaces", "x\u{7f}", &"a".repeat(65)] {
+            let generated = RequestId::from_header(HeaderValue::from_str(bad).ok().as_ref());
+            assert!(Uuid::parse_str(&generated.0).is_ok(), "{:?}", bad);
+        }
+        assert!(Uuid::parse_str(&RequestId::from_header(None).0).is_ok());
+    }
+
+    #[test]
+    fn test_attribution_finds_project_slug() {
+        let request = |variables: serde_json::Value| {
+            async_graphql::Request::new("query { apiVersion { apiVersion } }")
+                .variables(Variables::from_json(variables))
+        };
+        let project = |variables| RequestAttribution::new(&request(variables), None, 0).project;
+
+        assert_eq!(
+            project(serde_json::json!({ "slug": "a" })).as_deref(),
+            Some("a")
+        );
+        assert_eq!(
+            project(serde_json::json!({ "input": { "projectSlug": "b" } })).as_deref(),
+            Some("b")
+        );
+        assert_eq!(
+            project(serde_json::json!({ "projectSlug": "c", "slug": "group" })).as_deref(),
+            Some("c")
+        );
+        assert_eq!(project(serde_json::json!({ "id": "r1" })), None);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_echoes_request_id() {
+        let app = Router::new()
+            .route(
+                "/id",
+                get(|axum::Extension(id): axum::Extension<RequestId>| async move { id.0 }),
+            )
+            .layer(axum::middleware::from_fn(middleware));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/id")
+                    .header(REQUEST_ID_HEADER, "abc-123")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+
+        let response = app
+            .oneshot(Request::get("/id").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let header = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(Uuid::parse_str(header).is_ok());
+    }
+
+    /// Collects what the fmt subscriber writes
+    #[derive(Clone, Default)]
+    struct Captured(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_masks_url_tokens() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/feeds/{token}/alerts.atom", get(|| async { "feed" }))
+            .route("/shares/{token}", get(|| async { "share" }))
+            .route(
+                "/uploads/{token}",
+                axum::routing::put(|| async { "upload" }),
+            )
+            .layer(axum::middleware::from_fn(middleware));
+        for (method, uri) in [
+            ("GET", "/feeds/feed-secret/alerts.atom"),
+            ("GET", "/shares/share-secret"),
+            ("PUT", "/uploads/upload-secret"),
+        ] {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), 200, "{}", uri);
+        }
+
+        let logged = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(
+            logged.contains("path=/feeds/{token}/alerts.atom"),
+            "{}",
+            logged
+        );
+        assert!(logged.contains("path=/shares/{token}"), "{}", logged);
+        assert!(logged.contains("path=/uploads/{token}"), "{}", logged);
+        assert!(!logged.contains("secret"), "{}", logged);
+    }
+
+    #[test]
+    fn test_loggable_path() {
+        assert_eq!(
+            loggable_path("/shares/abc/report.json"),
+            "/shares/{token}/report.json"
+        );
+        assert_eq!(loggable_path("/shares"), "/shares");
+        assert_eq!(loggable_path("/shares/"), "/shares/");
+        assert_eq!(
+            loggable_path("/api/v1/projects/web"),
+            "/api/v1/projects/web"
+        );
+        assert_eq!(loggable_path("/"), "/");
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn fails(&self) -> async_graphql::Result<i32> {
+            Err("boom".into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_errors_carry_request_id() {
+        let schema = Schema::build(Query, EmptyMutation, EmptySubscription).finish();
+        for query in ["{ fails }", "{ fails"] {
+            let mut response = schema.execute(query).await;
+            tag_errors(&mut response, &RequestId("req-1".into()));
+            let extensions = response.errors[0].extensions.as_ref().unwrap();
+            assert_eq!(
+                extensions.get(REQUEST_ID_EXTENSION),
+                Some(&Value::from("req-1")),
+                "{}",
+                query
+            );
+        }
+    }
+}
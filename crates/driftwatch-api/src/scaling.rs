@@ -0,0 +1,183 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement};
+use uuid::Uuid;
+
+/// Splits a trailing numeric path segment off a benchmark name, so
+/// `sort/1000` becomes `("sort", 1000.0)`. Names without one aren't
+/// parameterized.
+pub fn split_parameter(name: &str) -> Option<(&str, f64)> {
+    let (base, parameter) = name.rsplit_once('/')?;
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let numeric = match parameter.split_once('.') {
+        Some((whole, fraction)) => is_digits(whole) && is_digits(fraction),
+        None => is_digits(parameter),
+    };
+    if base.is_empty() || !numeric {
+        return None;
+    }
+    Some((base, parameter.parse().ok()?))
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ScalingRow {
+    report_id: Uuid,
+    git_hash: Option<String>,
+    created_at: DateTimeWithTimeZone,
+    benchmark: String,
+    parameter: f64,
+    value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScalingPoint {
+    pub benchmark: String,
+    pub parameter: f64,
+    pub value: f64,
+}
+
+/// Values of one parameterized benchmark across its parameters in a single
+/// report, smallest parameter first.
+#[derive(Debug, Clone)]
+pub struct ScalingCurve {
+    pub report_id: Uuid,
+    pub git_hash: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub points: Vec<ScalingPoint>,
+}
+
+/// Scaling curves of `base_name` for the latest `limit` reports on a branch
+/// and testbed that measured it, oldest first, so growth can be compared
+/// over time. Excluded and unfinalized reports are left out.
+pub async fn scaling_curves<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    base_name: &str,
+    measure: &str,
+    branch: &str,
+    testbed: Option<&str>,
+    limit: u64,
+) -> Result<Vec<ScalingCurve>, DbErr> {
+    let rows = ScalingRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"WITH family AS (
+               SELECT m.report_id, b.name AS benchmark, b.parameter, m.value
+               FROM metrics m
+               JOIN benchmarks b ON b.id = m.benchmark_id
+               JOIN measures ms ON ms.id = m.measure_id
+               WHERE b.project_id = $1
+                 AND b.base_name = $2
+                 AND b.parameter IS NOT NULL
+                 AND ms.name = $3
+           ),
+           recent AS (
+               SELECT r.id, r.git_hash, r.created_at
+               FROM reports r
+               JOIN branches br ON br.id = r.branch_id
+               LEFT JOIN testbeds t ON t.id = r.testbed_id
+               WHERE r.project_id = $1
+                 AND r.finalized AND NOT r.excluded
+                 AND br.name = $4
+                 AND ($5::text IS NULL OR t.name = $5)
+                 AND EXISTS (SELECT 1 FROM family f WHERE f.report_id = r.id)
+               ORDER BY r.created_at DESC
+               LIMIT $6
+           )
+           SELECT r.id AS report_id, r.git_hash, r.created_at,
+                  f.benchmark, f.parameter, f.value
+           FROM recent r
+           JOIN family f ON f.report_id = r.id
+           ORDER BY r.created_at, r.id, f.parameter"#,
+        [
+            project_id.into(),
+            base_name.into(),
+            measure.into(),
+            branch.into(),
+            testbed.map(str::to_string).into(),
+            (limit as i64).into(),
+        ],
+    ))
+    .all(db)
+    .await?;
+
+    let mut curves: Vec<ScalingCurve> = Vec::new();
+    for row in rows {
+        let point = ScalingPoint {
+            benchmark: row.benchmark,
+            parameter: row.parameter,
+            value: row.value,
+        };
+        match curves.last_mut() {
+            Some(curve) if curve.report_id == row.report_id => curve.points.push(point),
+            _ => curves.push(ScalingCurve {
+                report_id: row.report_id,
+                git_hash: row.git_hash,
+                created_at: row.created_at,
+                points: vec![point],
+            }),
+        }
+    }
+    Ok(curves)
+}
+
+/// Least-squares slope of log(value) against log(parameter): roughly 1 for
+/// linear growth, 2 for quadratic. `None` with fewer than two usable points.
+pub fn growth_exponent(points: &[ScalingPoint]) -> Option<f64> {
+    let logs: Vec<(f64, f64)> = points
+        .iter()
+        .filter(|p| p.parameter > 0.0 && p.value > 0.0)
+        .map(|p| (p.parameter.ln(), p.value.ln()))
+        .collect();
+    if logs.len() < 2 {
+        return None;
+    }
+
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = logs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+    Some(covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_parameter() {
+        assert_eq!(split_parameter("sort/1000"), Some(("sort", 1000.0)));
+        assert_eq!(split_parameter("group/hash/0.5"), Some(("group/hash", 0.5)));
+        assert_eq!(split_parameter("sort"), None);
+        assert_eq!(split_parameter("sort/large"), None);
+        assert_eq!(split_parameter("/1000"), None);
+        assert_eq!(split_parameter("sort/.5"), None);
+        assert_eq!(split_parameter("sort/1.2.3"), None);
+        assert_eq!(split_parameter("sort/1."), None);
+    }
+
+    fn points(values: &[(f64, f64)]) -> Vec<ScalingPoint> {
+        values
+            .iter()
+            .map(|&(parameter, value)| ScalingPoint {
+                benchmark: format!("sort/{}", parameter),
+                parameter,
+                value,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_growth_exponent() {
+        let linear = points(&[(10.0, 100.0), (100.0, 1000.0), (1000.0, 10000.0)]);
+        assert!((growth_exponent(&linear).unwrap() - 1.0).abs() < 1e-9);
+
+        let quadratic = points(&[(10.0, 100.0), (100.0, 10000.0)]);
+        assert!((growth_exponent(&quadratic).unwrap() - 2.0).abs() < 1e-9);
+
+        assert_eq!(growth_exponent(&points(&[(10.0, 100.0)])), None);
+        assert_eq!(growth_exponent(&points(&[(10.0, 1.0), (10.0, 2.0)])), None);
+    }
+}
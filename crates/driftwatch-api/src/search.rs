@@ -0,0 +1,73 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement};
+use uuid::Uuid;
+
+/// A benchmark series whose name matched a search, with its latest value.
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct BenchmarkMatch {
+    pub project_slug: String,
+    pub project_name: String,
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    pub latest_value: f64,
+    pub latest_at: DateTimeWithTimeZone,
+}
+
+/// Escapes LIKE wildcards so `pattern` is matched as a literal substring.
+pub(crate) fn like_pattern(pattern: &str) -> String {
+    let escaped = pattern
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Series across every project `user_id` can read (their own and public
+/// ones) whose benchmark name contains `pattern`, case-insensitively. Closer
+/// matches come first, then the most recently updated series.
+pub async fn search_benchmarks<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    pattern: &str,
+    limit: u64,
+) -> Result<Vec<BenchmarkMatch>, DbErr> {
+    BenchmarkMatch::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT p.slug AS project_slug, p.name AS project_name,
+                  b.name AS benchmark, ms.name AS measure,
+                  br.name AS branch, t.name AS testbed,
+                  s.latest_value, s.latest_at
+           FROM metric_summaries s
+           JOIN benchmarks b ON b.id = s.benchmark_id
+           JOIN projects p ON p.id = s.project_id
+           JOIN measures ms ON ms.id = s.measure_id
+           JOIN branches br ON br.id = s.branch_id
+           JOIN testbeds t ON t.id = s.testbed_id
+           WHERE (p.user_id = $1 OR p.public)
+             AND b.name ILIKE $2
+           ORDER BY similarity(b.name, $3) DESC, s.latest_at DESC
+           LIMIT $4"#,
+        [
+            user_id.into(),
+            like_pattern(pattern).into(),
+            pattern.into(),
+            (limit as i64).into(),
+        ],
+    ))
+    .all(db)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("parse"), "%parse%");
+        assert_eq!(like_pattern("50%_hit"), "%50\\%\\_hit%");
+        assert_eq!(like_pattern("a\\b"), "%a\\\\b%");
+    }
+}
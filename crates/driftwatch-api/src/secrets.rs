@@ -0,0 +1,225 @@
+//! Envelope encryption for secrets stored in the database, such as
+//! projects' GitHub tokens.
+//!
+//! Every value gets its own random data key. The value is sealed with the
+//! data key, and the data key with the server's master key, so the master
+//! key never touches stored data directly. Both use AES-256-GCM, and the
+//! value is bound to the row it belongs to, so a sealed token copied into
+//! another project fails to open.
+//!
+//! Stored values look like `enc:v1:<key id>:<sealed data key>:<sealed value>`,
+//! with the key id identifying the master key that sealed them.
+
+use std::sync::OnceLock;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::entities::project;
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+/// The master key, from `DRIFTWATCH_SECRET_KEY`
+pub struct SecretKey {
+    cipher: Aes256Gcm,
+    id: String,
+}
+
+impl SecretKey {
+    /// Reads a base64-encoded 32-byte key, e.g. from `openssl rand -base64 32`
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .context("Secret key must be base64-encoded")?;
+        if bytes.len() != 32 {
+            bail!("Secret key must be 32 bytes, got {}", bytes.len());
+        }
+        let id = hex::encode(&Sha256::digest(&bytes)[..4]);
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)),
+            id,
+        })
+    }
+
+    /// Short fingerprint of the key, stored with every value it seals
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Seals `plaintext` for the row identified by `context`
+    pub fn seal(&self, plaintext: &str, context: &[u8]) -> String {
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let wrapped = encrypt(&self.cipher, &data_key, self.id.as_bytes());
+        let sealed = encrypt(&Aes256Gcm::new(&data_key), plaintext.as_bytes(), context);
+        format!(
+            "{}{}:{}:{}",
+            PREFIX,
+            self.id,
+            STANDARD.encode(wrapped),
+            STANDARD.encode(sealed)
+        )
+    }
+
+    /// Opens a value sealed by [`SecretKey::seal`] with the same context
+    pub fn open(&self, stored: &str, context: &[u8]) -> Result<String> {
+        let rest = stored
+            .strip_prefix(PREFIX)
+            .ok_or_else(|| anyhow!("Value is not encrypted"))?;
+        let mut parts = rest.splitn(3, ':');
+        let (Some(key_id), Some(wrapped), Some(sealed)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            bail!("Malformed encrypted value");
+        };
+        if key_id != self.id {
+            bail!(
+                "Value was encrypted with key {}, but the server has key {}",
+                key_id,
+                self.id
+            );
+        }
+
+        let wrapped = STANDARD.decode(wrapped).context("Malformed data key")?;
+        let data_key = decrypt(&self.cipher, &wrapped, self.id.as_bytes())
+            .context("Failed to unwrap the data key")?;
+        if data_key.len() != 32 {
+            bail!("Malformed data key");
+        }
+        let sealed = STANDARD.decode(sealed).context("Malformed value")?;
+        let plaintext = decrypt(
+            &Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key)),
+            &sealed,
+            context,
+        )
+        .context("Failed to decrypt the value")?;
+        String::from_utf8(plaintext).context("Decrypted value is not UTF-8")
+    }
+}
+
+/// Nonce followed by ciphertext and tag
+fn encrypt(cipher: &Aes256Gcm, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .expect("AES-GCM encryption of an in-memory buffer can't fail");
+    let mut out = nonce.to_vec();
+    out.extend(ciphertext);
+    out
+}
+
+fn decrypt(cipher: &Aes256Gcm, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("Encrypted value is too short");
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| anyhow!("Authentication failed; wrong key or tampered value"))
+}
+
+/// True for values written by [`SecretKey::seal`]
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+static KEY: OnceLock<SecretKey> = OnceLock::new();
+
+/// Sets the process-wide master key. Called once by `serve`; later calls
+/// keep the first key and return false.
+pub fn install(key: SecretKey) -> bool {
+    KEY.set(key).is_ok()
+}
+
+pub fn key() -> Option<&'static SecretKey> {
+    KEY.get()
+}
+
+/// Binds a sealed token to its project
+pub fn github_token_context(project_id: Uuid) -> Vec<u8> {
+    format!("projects.github_token:{}", project_id).into_bytes()
+}
+
+/// Encrypts a GitHub token for storage on a project. Tokens are never
+/// stored in plaintext, so this fails when the server has no key.
+pub fn seal_github_token(project_id: Uuid, token: &str) -> Result<String> {
+    let key = key().ok_or_else(|| {
+        anyhow!("GitHub tokens can't be stored: the server has no DRIFTWATCH_SECRET_KEY")
+    })?;
+    Ok(key.seal(token, &github_token_context(project_id)))
+}
+
+/// The project's GitHub token in plaintext. Tokens stored before encryption
+/// was enabled are returned as they are until `encrypt_github_tokens` runs.
+pub fn github_token(project: &project::Model) -> Result<Option<String>> {
+    let Some(stored) = project.github_token.as_deref() else {
+        return Ok(None);
+    };
+    if !is_sealed(stored) {
+        return Ok(Some(stored.to_string()));
+    }
+    let key = key().ok_or_else(|| anyhow!("The server has no DRIFTWATCH_SECRET_KEY"))?;
+    key.open(stored, &github_token_context(project.id))
+        .map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> SecretKey {
+        SecretKey::from_base64(&STANDARD.encode([byte; 32])).unwrap()
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = key(7);
+        let sealed = key.seal("ghp_secret", b"project-1");
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("ghp_secret"));
+        assert!(sealed.starts_with(&format!("enc:v1:{}:", key.id())));
+        assert_eq!(key.open(&sealed, b"project-1").unwrap(), "ghp_secret");
+
+        // Every value gets a fresh data key and nonces
+        assert_ne!(sealed, key.seal("ghp_secret", b"project-1"));
+    }
+
+    #[test]
+    fn test_open_rejects_other_rows_keys_and_tampering() {
+        let key_a = key(7);
+        let sealed = key_a.seal("ghp_secret", b"project-1");
+
+        assert!(key_a.open(&sealed, b"project-2").is_err());
+        assert!(key(8).open(&sealed, b"project-1").is_err());
+        assert!(key_a.open("ghp_secret", b"project-1").is_err());
+
+        let (head, value) = sealed.rsplit_once(':').unwrap();
+        let mut value = STANDARD.decode(value).unwrap();
+        *value.last_mut().unwrap() ^= 1;
+        let tampered = format!("{}:{}", head, STANDARD.encode(value));
+        assert!(key_a.open(&tampered, b"project-1").is_err());
+    }
+
+    #[test]
+    fn test_from_base64_checks_length() {
+        assert!(SecretKey::from_base64(&STANDARD.encode([1u8; 16])).is_err());
+        assert!(SecretKey::from_base64("not base64!").is_err());
+    }
+}
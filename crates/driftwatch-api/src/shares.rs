@@ -0,0 +1,646 @@
+//! Anonymous shares: a one-off report submitted without an account, kept
+//! for a limited time and readable by anyone holding its secret URL,
+//! `/shares/{token}`. Handy for pasting results into an issue or a chat.
+//!
+//! A share is an ordinary project without an owner, so it goes through the
+//! same ingestion path as any report. Only the token's SHA-256 is stored,
+//! like feed tokens. Expired shares are deleted hourly along with
+//! everything under them.
+//!
+//! Off unless `ANONYMOUS_SHARES=true`. `ANONYMOUS_SHARE_RETENTION_DAYS`
+//! (default 7) sets how long shares live and `ANONYMOUS_SHARE_MAX_METRICS`
+//! (default 1000) how large they may be. Since anyone can create them,
+//! each client address may create `ANONYMOUS_SHARE_HOURLY_LIMIT` (default
+//! 20) an hour, and at most `ANONYMOUS_SHARE_MAX_LIVE` (default 10000) are
+//! kept at once. Behind a reverse proxy, `ANONYMOUS_SHARE_CLIENT_IP_HEADER`
+//! names the header it puts the client's address in.
+
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fmt::{self, Write as _};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, HeaderName, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, FromQueryResult, PaginatorTrait, QueryFilter, QueryOrder, Set, Statement,
+    TransactionTrait,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{self, project, report};
+use crate::error_reports;
+use crate::feeds::{new_token, token_hash};
+use crate::ingest::{self, Dimensions, NewMetric, NewReport};
+use crate::jobs::JobRegistry;
+
+/// How often expired shares are deleted
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Job kind that deletes expired shares
+pub const PURGE_SHARES_JOB: &str = "purge_expired_shares";
+
+/// Name shares get when the submitter doesn't give one
+pub const DEFAULT_NAME: &str = "Shared benchmark results";
+
+/// Period the per-client limit counts shares over
+const LIMIT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Clients tracked before those that haven't shared within the window are
+/// forgotten
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct ShareSettings {
+    /// How long a share stays readable
+    pub retention: chrono::Duration,
+    /// Most metrics a single share may hold
+    pub max_metrics: usize,
+    /// Most shares one client address may create per hour; 0 for no limit
+    pub hourly_limit: usize,
+    /// Most unexpired shares kept at once; 0 for no limit
+    pub max_live: u64,
+    /// Header a reverse proxy puts the client's address in; the peer's
+    /// address is used without one
+    pub client_ip_header: Option<HeaderName>,
+    /// Recent shares per client, shared by every clone of the settings
+    pub recent: Arc<ShareLimiter>,
+}
+
+impl ShareSettings {
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("ANONYMOUS_SHARES")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let retention_days: i64 = env::var("ANONYMOUS_SHARE_RETENTION_DAYS")
+            .unwrap_or_else(|_| "7".to_string())
+            .parse()
+            .expect("ANONYMOUS_SHARE_RETENTION_DAYS must be a valid number");
+        Some(Self {
+            retention: chrono::Duration::days(retention_days),
+            max_metrics: env::var("ANONYMOUS_SHARE_MAX_METRICS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()
+                .expect("ANONYMOUS_SHARE_MAX_METRICS must be a valid number"),
+            hourly_limit: env::var("ANONYMOUS_SHARE_HOURLY_LIMIT")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .expect("ANONYMOUS_SHARE_HOURLY_LIMIT must be a valid number"),
+            max_live: env::var("ANONYMOUS_SHARE_MAX_LIVE")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()
+                .expect("ANONYMOUS_SHARE_MAX_LIVE must be a valid number"),
+            client_ip_header: env::var("ANONYMOUS_SHARE_CLIENT_IP_HEADER")
+                .ok()
+                .map(|name| {
+                    name.parse()
+                        .expect("ANONYMOUS_SHARE_CLIENT_IP_HEADER must be a valid header name")
+                }),
+            recent: Arc::default(),
+        })
+    }
+
+    /// Address a request came from: with `client_ip_header`, the last
+    /// address in it, which the nearest proxy added; otherwise the peer's.
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+        match &self.client_ip_header {
+            Some(name) => headers
+                .get(name)?
+                .to_str()
+                .ok()?
+                .rsplit(',')
+                .next()?
+                .trim()
+                .parse()
+                .ok(),
+            None => peer.map(|peer| peer.ip()),
+        }
+    }
+
+    /// Counts a share by `client` against the hourly limit. Requests whose
+    /// address is unknown share one allowance.
+    pub fn admit(&self, client: Option<ShareClient>) -> Result<(), ShareError> {
+        if self.hourly_limit == 0 {
+            return Ok(());
+        }
+        let client = client.map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |client| client.0);
+        self.recent
+            .admit(client, self.hourly_limit, Instant::now())
+            .map_err(ShareError::RateLimited)
+    }
+}
+
+/// Address of the client calling `shareReport`, as GraphQL data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShareClient(pub IpAddr);
+
+/// When each client created its shares within the last [`LIMIT_WINDOW`]
+#[derive(Debug, Default)]
+pub struct ShareLimiter {
+    created: Mutex<HashMap<IpAddr, VecDeque<Instant>>>,
+}
+
+impl ShareLimiter {
+    /// Records a share by `client` at `now`, unless it created `limit`
+    /// within the window already; then returns how long until it may
+    /// create another.
+    pub fn admit(&self, client: IpAddr, limit: usize, now: Instant) -> Result<(), Duration> {
+        let recent = |at: &Instant| now.saturating_duration_since(*at) < LIMIT_WINDOW;
+        let mut created = self.created.lock().unwrap();
+        if created.len() >= PRUNE_THRESHOLD {
+            created.retain(|_, times| times.back().is_some_and(recent));
+        }
+        let times = created.entry(client).or_default();
+        while times.front().is_some_and(|at| !recent(at)) {
+            times.pop_front();
+        }
+        if times.len() >= limit {
+            return Err(LIMIT_WINDOW - now.saturating_duration_since(times[0]));
+        }
+        times.push_back(now);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ShareError {
+    /// The client reached its hourly limit; it may share again after this
+    RateLimited(Duration),
+    /// `max_live` shares are kept already
+    Full,
+    Db(DbErr),
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::RateLimited(wait) => write!(
+                f,
+                "Too many shares from this address; try again in {} minutes",
+                wait.as_secs().div_ceil(60)
+            ),
+            ShareError::Full => write!(
+                f,
+                "This server keeps as many shares as it allows; try again later"
+            ),
+            ShareError::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+impl From<DbErr> for ShareError {
+    fn from(e: DbErr) -> Self {
+        ShareError::Db(e)
+    }
+}
+
+/// Where the share for this token is served, relative to the API's URL.
+pub fn share_path(token: &str) -> String {
+    format!("/shares/{}", token)
+}
+
+pub struct NewShare {
+    pub name: Option<String>,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub metrics: Vec<NewMetric>,
+}
+
+/// A stored share and the token to hand out once
+pub struct CreatedShare {
+    pub token: String,
+    pub project: project::Model,
+    pub report: report::Model,
+}
+
+/// Stores `share` as an owner-less project holding a single report, unless
+/// `max_live` shares are kept already.
+pub async fn create(
+    db: &DatabaseConnection,
+    settings: &ShareSettings,
+    share: NewShare,
+) -> Result<CreatedShare, ShareError> {
+    let token = new_token();
+    let id = Uuid::new_v4();
+    let now = Utc::now().fixed_offset();
+    let project = project::ActiveModel {
+        id: Set(id),
+        user_id: Set(None),
+        slug: Set(format!("share-{}", &id.simple().to_string()[..12])),
+        name: Set(share
+            .name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_NAME.to_string())),
+        description: Set(None),
+        public: Set(false),
+        default_branch: Set(ingest::DEFAULT_BRANCH.to_string()),
+        github_repo: Set(None),
+        github_token: Set(None),
+        github_pr_comments: Set(false),
+        github_status_checks: Set(false),
+        expected_cadence_hours: Set(None),
+        stale_branches: Set(None),
+        github_issue_after_reports: Set(None),
+        alert_after_reports: Set(None),
+        noise_cv_limit: Set(None),
+        noise_action: Set(project::NoiseAction::Flag),
+        benchmark_required_paths: Set(None),
+        redact_secrets: Set(true),
+        alert_feed_token_hash: Set(None),
+        expires_at: Set(Some(now + settings.retention)),
+        share_token_hash: Set(Some(token_hash(&token))),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    let txn = db.begin().await?;
+    if settings.max_live > 0 {
+        let live = entities::Project::find()
+            .filter(project::Column::UserId.is_null())
+            .filter(project::Column::ExpiresAt.gt(now))
+            .count(&txn)
+            .await?;
+        if live >= settings.max_live {
+            return Err(ShareError::Full);
+        }
+    }
+    let project = project.insert(&txn).await?;
+    let new_report = NewReport {
+        id: None,
+        project_id: project.id,
+        branch: share.branch,
+        testbed: share.testbed,
+        git_hash: share.git_hash,
+        pr_number: None,
+        commit_message: None,
+        commit_author: None,
+        committed_at: None,
+        base_branch: None,
+        merge_base_hash: None,
+        evaluated_commit: None,
+        version: None,
+        kind: report::ReportKind::Manual,
+        exit_code: None,
+        partial: false,
+        created_at: now,
+        context: Vec::new(),
+        tags: Vec::new(),
+        expected_benchmarks: Vec::new(),
+    };
+    let (report, _) =
+        ingest::insert_report(&txn, &mut Dimensions::default(), new_report, share.metrics).await?;
+    // A share has no history or thresholds to evaluate against
+    let mut active: report::ActiveModel = report.into();
+    active.status = Set(report::ReportStatus::Evaluated);
+    let report = active.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(CreatedShare {
+        token,
+        project,
+        report,
+    })
+}
+
+/// The share behind `token`, unless it has expired.
+pub async fn find<C: ConnectionTrait>(
+    db: &C,
+    token: &str,
+) -> Result<Option<project::Model>, DbErr> {
+    entities::Project::find()
+        .filter(project::Column::ShareTokenHash.eq(token_hash(token)))
+        .filter(project::Column::ExpiresAt.gt(Utc::now().fixed_offset()))
+        .one(db)
+        .await
+}
+
+/// One metric of a shared report.
+#[derive(Debug, Clone, FromQueryResult, Serialize)]
+pub struct ShareRow {
+    pub benchmark: String,
+    pub measure: String,
+    pub units: Option<String>,
+    pub value: f64,
+    pub lower_value: Option<f64>,
+    pub upper_value: Option<f64>,
+}
+
+/// A share as served at `/shares/{token}/report.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedReport {
+    pub name: String,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub metrics: Vec<ShareRow>,
+}
+
+pub async fn load<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+) -> Result<Option<SharedReport>, DbErr> {
+    let Some(report) = entities::Report::find()
+        .filter(report::Column::ProjectId.eq(project.id))
+        .order_by_desc(report::Column::CreatedAt)
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let branch = entities::Branch::find_by_id(report.branch_id)
+        .one(db)
+        .await?;
+    let testbed = entities::Testbed::find_by_id(report.testbed_id)
+        .one(db)
+        .await?;
+    let metrics = ShareRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT b.name AS benchmark, ms.name AS measure, ms.units,
+                  m.value, m.lower_value, m.upper_value
+           FROM metrics m
+           JOIN benchmarks b ON b.id = m.benchmark_id
+           JOIN measures ms ON ms.id = m.measure_id
+           WHERE m.report_id = $1
+           ORDER BY b.name, ms.name"#,
+        [report.id.into()],
+    ))
+    .all(db)
+    .await?;
+
+    Ok(Some(SharedReport {
+        name: project.name.clone(),
+        branch: branch.map(|b| b.name).unwrap_or_default(),
+        testbed: testbed.map(|t| t.name).unwrap_or_default(),
+        git_hash: report.git_hash,
+        created_at: report.created_at,
+        expires_at: project.expires_at,
+        metrics,
+    }))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_value(value: f64, units: Option<&str>) -> String {
+    match units {
+        Some(units) if !units.is_empty() => format!("{} {}", value, units),
+        _ => value.to_string(),
+    }
+}
+
+/// Renders a share as a standalone HTML page.
+pub fn render_html(share: &SharedReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html><head><meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<meta name=\"robots\" content=\"noindex\">");
+    let _ = writeln!(
+        out,
+        "<title>{}</title></head><body>",
+        escape_html(&share.name)
+    );
+    let _ = writeln!(out, "<h1>{}</h1>", escape_html(&share.name));
+    let _ = write!(
+        out,
+        "<p>Branch {}, testbed {}",
+        escape_html(&share.branch),
+        escape_html(&share.testbed)
+    );
+    if let Some(hash) = &share.git_hash {
+        let _ = write!(out, ", commit <code>{}</code>", escape_html(hash));
+    }
+    let _ = writeln!(
+        out,
+        ". Submitted {}.</p>",
+        share.created_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Benchmark</th><th>Measure</th><th>Value</th><th>Range</th></tr>"
+    );
+    for row in &share.metrics {
+        let range = match (row.lower_value, row.upper_value) {
+            (Some(lower), Some(upper)) => format!("{} – {}", lower, upper),
+            _ => String::new(),
+        };
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&row.benchmark),
+            escape_html(&row.measure),
+            escape_html(&format_value(row.value, row.units.as_deref())),
+            range
+        );
+    }
+    let _ = writeln!(out, "</table>");
+    if let Some(expires_at) = share.expires_at {
+        let _ = writeln!(
+            out,
+            "<p>This share is deleted after {}.</p>",
+            expires_at.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+    let _ = writeln!(out, "</body></html>");
+    out
+}
+
+/// Deletes shares that expired before `now`. Reports go first, taking
+/// their metrics with them, since metrics don't cascade from the
+/// benchmarks and measures the project's deletion removes.
+pub async fn purge_expired(
+    db: &DatabaseConnection,
+    now: DateTimeWithTimeZone,
+) -> Result<u64, DbErr> {
+    let txn = db.begin().await?;
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"DELETE FROM reports
+           WHERE project_id IN (
+               SELECT id FROM projects WHERE user_id IS NULL AND expires_at <= $1
+           )"#,
+        [now.into()],
+    ))
+    .await?;
+    let result = entities::Project::delete_many()
+        .filter(project::Column::UserId.is_null())
+        .filter(project::Column::ExpiresAt.lte(now))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    Ok(result.rows_affected)
+}
+
+/// Registers deleting expired shares
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.schedule(PURGE_SHARES_JOB, CLEANUP_INTERVAL, |db| async move {
+        match purge_expired(&db, Utc::now().fixed_offset()).await {
+            Ok(0) => {}
+            Ok(deleted) => tracing::info!("Deleted {} expired anonymous shares", deleted),
+            Err(e) => {
+                tracing::error!("Anonymous share cleanup failed: {}", e);
+                error_reports::capture(&e.into(), "shares", &[]);
+            }
+        }
+        Ok(())
+    });
+}
+
+async fn load_share(db: &DatabaseConnection, token: &str) -> Result<Option<SharedReport>, DbErr> {
+    match find(db, token).await? {
+        Some(project) => load(db, &project).await,
+        None => Ok(None),
+    }
+}
+
+fn failed(e: DbErr) -> Response {
+    tracing::error!("Anonymous share failed: {}", e);
+    error_reports::capture(&e.into(), "shares", &[]);
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
+
+async fn share_page(State(db): State<DatabaseConnection>, Path(token): Path<String>) -> Response {
+    match load_share(&db, &token).await {
+        Ok(Some(share)) => (
+            [
+                (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+                (header::CACHE_CONTROL, "private, max-age=300"),
+            ],
+            render_html(&share),
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => failed(e),
+    }
+}
+
+async fn share_json(State(db): State<DatabaseConnection>, Path(token): Path<String>) -> Response {
+    match load_share(&db, &token).await {
+        Ok(Some(share)) => (
+            [(header::CACHE_CONTROL, "private, max-age=300")],
+            Json(share),
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => failed(e),
+    }
+}
+
+/// Routes for `/shares/{token}`. The token in the path is the only
+/// credential.
+pub fn router<S: Clone + Send + Sync + 'static>(db: DatabaseConnection) -> Router<S> {
+    Router::new()
+        .route("/shares/{token}", get(share_page))
+        .route("/shares/{token}/report.json", get(share_json))
+        .with_state(db)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html_escapes_submitted_text() {
+        let at = Utc::now().fixed_offset();
+        let share = SharedReport {
+            name: "<script>alert(1)</script>".to_string(),
+            branch: "main".to_string(),
+            testbed: "laptop".to_string(),
+            git_hash: Some("abc123".to_string()),
+            created_at: at,
+            expires_at: Some(at + chrono::Duration::days(7)),
+            metrics: vec![ShareRow {
+                benchmark: "parse \"large\" & slow".to_string(),
+                measure: "latency".to_string(),
+                units: Some("ns".to_string()),
+                value: 42.5,
+                lower_value: Some(40.0),
+                upper_value: Some(45.0),
+            }],
+        };
+
+        let html = render_html(&share);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("parse &quot;large&quot; &amp; slow"));
+        assert!(html.contains("<td>42.5 ns</td><td>40 – 45</td>"));
+        assert!(html.contains("commit <code>abc123</code>"));
+        assert!(html.contains("This share is deleted after"));
+    }
+
+    #[test]
+    fn test_share_limiter() {
+        let limiter = ShareLimiter::default();
+        let start = Instant::now();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+
+        assert!(limiter.admit(client, 2, start).is_ok());
+        let later = start + Duration::from_secs(600);
+        assert!(limiter.admit(client, 2, later).is_ok());
+        assert_eq!(
+            limiter.admit(client, 2, later),
+            Err(Duration::from_secs(3000))
+        );
+        assert!(limiter.admit(other, 2, later).is_ok());
+
+        // The first share leaves the window after an hour
+        assert!(limiter.admit(client, 2, start + LIMIT_WINDOW).is_ok());
+        assert!(limiter.admit(client, 2, start + LIMIT_WINDOW).is_err());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let mut settings = ShareSettings {
+            retention: chrono::Duration::days(7),
+            max_metrics: 10,
+            hourly_limit: 1,
+            max_live: 0,
+            client_ip_header: None,
+            recent: Arc::default(),
+        };
+        let peer: SocketAddr = "10.0.0.2:41000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            "198.51.100.1, 203.0.113.7".parse().unwrap(),
+        );
+
+        // Without a configured header the forwarded one can't be trusted
+        assert_eq!(settings.client_ip(&headers, Some(peer)), Some(peer.ip()));
+        settings.client_ip_header = Some(HeaderName::from_static("x-forwarded-for"));
+        assert_eq!(
+            settings.client_ip(&headers, Some(peer)),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(settings.client_ip(&HeaderMap::new(), Some(peer)), None);
+
+        // Clients with no known address share one allowance
+        assert!(settings.admit(None).is_ok());
+        assert!(matches!(
+            settings.admit(None),
+            Err(ShareError::RateLimited(_))
+        ));
+    }
+}
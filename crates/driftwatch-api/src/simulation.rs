@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, metric, report};
+use crate::evaluation::{mean, percent_change, BASELINE_WINDOW};
+
+/// How a simulated threshold scores a value against its baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// Percent change from the mean of the window, like stored thresholds
+    Percent,
+    /// Standard deviations from the mean of the window
+    ZScore,
+}
+
+impl Model {
+    /// Upper boundary used when a simulation gives neither boundary
+    pub fn default_upper_boundary(&self) -> f64 {
+        match self {
+            Model::Percent => 10.0,
+            Model::ZScore => 3.0,
+        }
+    }
+}
+
+/// A proposed threshold configuration to replay history through
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub model: Model,
+    /// Earlier values the baseline is computed from
+    pub window: usize,
+    pub upper_boundary: Option<f64>,
+    pub lower_boundary: Option<f64>,
+    pub min_sample_size: usize,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            model: Model::Percent,
+            window: BASELINE_WINDOW as usize,
+            upper_boundary: None,
+            lower_boundary: None,
+            min_sample_size: 2,
+        }
+    }
+}
+
+/// A value in a series that the configuration would have alerted on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trigger {
+    /// Index into the replayed values
+    pub index: usize,
+    pub baseline: f64,
+    /// Percent change or z-score, depending on the model
+    pub score: f64,
+}
+
+fn std_dev(values: &[f64], mean: f64) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+/// Replays one series, oldest first, checking every value against the
+/// window of values before it. Series without any variation can't be scored
+/// by z-score and never trigger it.
+pub fn replay(values: &[f64], config: &SimulationConfig) -> Vec<Trigger> {
+    let min_sample_size = config.min_sample_size.max(1);
+    let mut triggers = Vec::new();
+
+    for (index, value) in values.iter().enumerate() {
+        let window = &values[index.saturating_sub(config.window)..index];
+        if window.len() < min_sample_size {
+            continue;
+        }
+        let Some(baseline) = mean(window) else {
+            continue;
+        };
+        let score = match config.model {
+            Model::Percent => percent_change(baseline, *value),
+            Model::ZScore => std_dev(window, baseline)
+                .filter(|sd| *sd > 0.0)
+                .map(|sd| (value - baseline) / sd),
+        };
+        let Some(score) = score else {
+            continue;
+        };
+
+        let above = config.upper_boundary.is_some_and(|upper| score > upper);
+        let below = config.lower_boundary.is_some_and(|lower| score < -lower);
+        if above || below {
+            triggers.push(Trigger {
+                index,
+                baseline,
+                score,
+            });
+        }
+    }
+
+    triggers
+}
+
+/// Which part of a project's history to replay; unset fields match
+/// everything
+#[derive(Debug, Clone, Default)]
+pub struct SimulationScope {
+    pub project_id: Uuid,
+    pub branch_id: Option<Uuid>,
+    pub testbed_id: Option<Uuid>,
+    pub measure_id: Option<Uuid>,
+    /// Only values reported at or after this time can trigger; earlier ones
+    /// still fill the window
+    pub since: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatedAlert {
+    pub report_id: Uuid,
+    pub branch_id: Uuid,
+    pub testbed_id: Uuid,
+    pub benchmark_id: Uuid,
+    pub measure_id: Uuid,
+    pub created_at: DateTime<FixedOffset>,
+    pub value: f64,
+    pub baseline: f64,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimulationOutcome {
+    pub series: usize,
+    /// Values that had enough history to be checked
+    pub comparisons: usize,
+    /// Oldest first
+    pub alerts: Vec<SimulatedAlert>,
+}
+
+type SeriesKey = (Uuid, Uuid, Uuid, Uuid);
+
+/// Replays the scope's finalized, non-excluded history through `config`,
+/// one series per branch, testbed, benchmark and measure, without writing
+/// anything.
+pub async fn simulate<C: ConnectionTrait>(
+    db: &C,
+    scope: &SimulationScope,
+    config: &SimulationConfig,
+) -> Result<SimulationOutcome, DbErr> {
+    let mut query = entities::Metric::find()
+        .find_also_related(entities::Report)
+        .filter(report::Column::ProjectId.eq(scope.project_id))
+        .filter(report::Column::Finalized.eq(true))
+        .filter(report::Column::Excluded.eq(false));
+    if let Some(branch_id) = scope.branch_id {
+        query = query.filter(report::Column::BranchId.eq(branch_id));
+    }
+    if let Some(testbed_id) = scope.testbed_id {
+        query = query.filter(report::Column::TestbedId.eq(testbed_id));
+    }
+    if let Some(measure_id) = scope.measure_id {
+        query = query.filter(metric::Column::MeasureId.eq(measure_id));
+    }
+    let rows = query
+        .order_by_asc(metric::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let mut series: HashMap<SeriesKey, Vec<(metric::Model, report::Model)>> = HashMap::new();
+    for (metric, report) in rows {
+        let Some(report) = report else {
+            continue;
+        };
+        let key = (
+            report.branch_id,
+            report.testbed_id,
+            metric.benchmark_id,
+            metric.measure_id,
+        );
+        series.entry(key).or_default().push((metric, report));
+    }
+
+    let min_sample_size = config.min_sample_size.max(1);
+    let mut outcome = SimulationOutcome {
+        series: series.len(),
+        ..Default::default()
+    };
+    let in_scope = |r: &report::Model| scope.since.is_none_or(|since| r.created_at >= since);
+    for points in series.values() {
+        outcome.comparisons += points
+            .iter()
+            .enumerate()
+            .filter(|(i, (_, r))| in_scope(r) && (*i).min(config.window) >= min_sample_size)
+            .count();
+
+        let values: Vec<f64> = points.iter().map(|(m, _)| m.value).collect();
+        for trigger in replay(&values, config) {
+            let (metric, report) = &points[trigger.index];
+            if !in_scope(report) {
+                continue;
+            }
+            outcome.alerts.push(SimulatedAlert {
+                report_id: report.id,
+                branch_id: report.branch_id,
+                testbed_id: report.testbed_id,
+                benchmark_id: metric.benchmark_id,
+                measure_id: metric.measure_id,
+                created_at: report.created_at,
+                value: metric.value,
+                baseline: trigger.baseline,
+                score: trigger.score,
+            });
+        }
+    }
+    outcome.alerts.sort_by_key(|a| a.created_at);
+
+    Ok(outcome)
+}
+
+/// Alerts the project's actual thresholds raised in the same scope, to
+/// compare a simulation against
+pub async fn actual_alert_count<C: ConnectionTrait>(
+    db: &C,
+    scope: &SimulationScope,
+) -> Result<u64, DbErr> {
+    let mut query = entities::Alert::find()
+        .inner_join(entities::Metric)
+        .join(JoinType::InnerJoin, metric::Relation::Report.def())
+        .filter(report::Column::ProjectId.eq(scope.project_id));
+    if let Some(branch_id) = scope.branch_id {
+        query = query.filter(report::Column::BranchId.eq(branch_id));
+    }
+    if let Some(testbed_id) = scope.testbed_id {
+        query = query.filter(report::Column::TestbedId.eq(testbed_id));
+    }
+    if let Some(measure_id) = scope.measure_id {
+        query = query.filter(metric::Column::MeasureId.eq(measure_id));
+    }
+    if let Some(since) = scope.since {
+        query = query.filter(report::Column::CreatedAt.gte(since));
+    }
+    query.count(db).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(model: Model, upper: Option<f64>, lower: Option<f64>) -> SimulationConfig {
+        SimulationConfig {
+            model,
+            window: 5,
+            upper_boundary: upper,
+            lower_boundary: lower,
+            min_sample_size: 3,
+        }
+    }
+
+    #[test]
+    fn test_replay_percent() {
+        let values = [100.0, 101.0, 99.0, 100.0, 115.0, 100.0, 80.0];
+        let triggers = replay(&values, &config(Model::Percent, Some(10.0), None));
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].index, 4);
+        assert_eq!(triggers[0].baseline, 100.0);
+        assert!((triggers[0].score - 15.0).abs() < 1e-9);
+
+        let triggers = replay(&values, &config(Model::Percent, None, Some(10.0)));
+        assert_eq!(
+            triggers.iter().map(|t| t.index).collect::<Vec<_>>(),
+            vec![6]
+        );
+    }
+
+    #[test]
+    fn test_replay_zscore() {
+        let values = [100.0, 102.0, 98.0, 101.0, 99.0, 106.0, 100.0];
+        // A 6% jump is well past 3 standard deviations of this series
+        let triggers = replay(&values, &config(Model::ZScore, Some(3.0), None));
+        assert_eq!(
+            triggers.iter().map(|t| t.index).collect::<Vec<_>>(),
+            vec![5]
+        );
+        assert!(triggers[0].score > 3.0);
+
+        // But not past a 10% boundary
+        assert!(replay(&values, &config(Model::Percent, Some(10.0), None)).is_empty());
+    }
+
+    #[test]
+    fn test_replay_respects_window_and_sample_size() {
+        // Too little history to check against
+        assert!(replay(&[500.0, 900.0], &config(Model::Percent, Some(10.0), None)).is_empty());
+        // A constant series has no spread to score against
+        assert!(replay(&[500.0; 6], &config(Model::ZScore, Some(3.0), None)).is_empty());
+
+        // Once the old level leaves the window it stops being the baseline
+        let mut values = vec![100.0; 3];
+        values.extend([200.0; 6]);
+        let triggers = replay(&values, &config(Model::Percent, Some(10.0), None));
+        assert_eq!(
+            triggers.iter().map(|t| t.index).collect::<Vec<_>>(),
+            vec![3, 4, 5, 6, 7]
+        );
+    }
+}
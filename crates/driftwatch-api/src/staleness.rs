@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset};
+use sea_orm::prelude::Expr;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect,
+    Set,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, branch, project, report, stale_alert};
+use crate::error_reports;
+use crate::jobs::JobRegistry;
+
+/// How often the scheduler looks for branches/testbeds that stopped reporting.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Job kind of the periodic stale-data check.
+pub const CHECK_STALE_DATA_JOB: &str = "check_stale_data";
+
+/// Registers the periodic stale-data check.
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.schedule(CHECK_STALE_DATA_JOB, CHECK_INTERVAL, |db| async move {
+        let now = chrono::Utc::now().fixed_offset();
+        match check_stale_data(&db, now).await {
+            Ok(opened) if !opened.is_empty() => {
+                tracing::info!("Opened {} stale-data alerts", opened.len());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::error!("Stale-data check failed: {}", e);
+                error_reports::capture(&e.into(), "staleness", &[]);
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Opens a stale alert for every branch/testbed whose latest report is older
+/// than its project's expected cadence, on the branches the project checks,
+/// unless one is already active or was dismissed since that report.
+pub async fn check_stale_data<C: ConnectionTrait>(
+    db: &C,
+    now: DateTime<FixedOffset>,
+) -> Result<Vec<stale_alert::Model>, DbErr> {
+    let projects = entities::Project::find()
+        .filter(project::Column::ExpectedCadenceHours.is_not_null())
+        .all(db)
+        .await?;
+
+    let mut opened = Vec::new();
+
+    for project in projects {
+        let Some(cadence_hours) = project.expected_cadence_hours else {
+            continue;
+        };
+
+        let latest: Vec<(Uuid, Uuid, DateTime<FixedOffset>)> = entities::Report::find()
+            .select_only()
+            .column(report::Column::BranchId)
+            .column(report::Column::TestbedId)
+            .column_as(Expr::col(report::Column::CreatedAt).max(), "last_report_at")
+            .inner_join(entities::Branch)
+            .filter(report::Column::ProjectId.eq(project.id))
+            .filter(branch::Column::Name.is_in(project.stale_branch_list()))
+            .group_by(report::Column::BranchId)
+            .group_by(report::Column::TestbedId)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let open = entities::StaleAlert::find()
+            .filter(stale_alert::Column::ProjectId.eq(project.id))
+            .filter(stale_alert::Column::Status.is_in([
+                stale_alert::StaleAlertStatus::Active,
+                stale_alert::StaleAlertStatus::Dismissed,
+            ]))
+            .all(db)
+            .await?;
+
+        for (branch_id, testbed_id, last_report_at) in latest {
+            if !is_stale(last_report_at, cadence_hours, now) {
+                continue;
+            }
+            if open.iter().any(|a| {
+                a.branch_id == branch_id
+                    && a.testbed_id == testbed_id
+                    && (a.status == stale_alert::StaleAlertStatus::Active
+                        || a.last_report_at == last_report_at)
+            }) {
+                continue;
+            }
+
+            tracing::warn!(
+                "Project {} branch {} testbed {} has not reported since {}",
+                project.slug,
+                branch_id,
+                testbed_id,
+                last_report_at
+            );
+
+            // A check running concurrently may have opened it first
+            let id = Uuid::new_v4();
+            let inserted = entities::StaleAlert::insert(stale_alert::ActiveModel {
+                id: Set(id),
+                project_id: Set(project.id),
+                branch_id: Set(branch_id),
+                testbed_id: Set(testbed_id),
+                last_report_at: Set(last_report_at),
+                expected_cadence_hours: Set(cadence_hours),
+                status: Set(stale_alert::StaleAlertStatus::Active),
+                resolved_at: Set(None),
+                created_at: Set(now),
+                updated_at: Set(now),
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    stale_alert::Column::ProjectId,
+                    stale_alert::Column::BranchId,
+                    stale_alert::Column::TestbedId,
+                ])
+                // A literal, not a bind parameter, so Postgres can match the
+                // partial index's predicate
+                .target_and_where(Expr::cust("status = 'active'"))
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+            if inserted > 0 {
+                opened.extend(entities::StaleAlert::find_by_id(id).one(db).await?);
+            }
+        }
+    }
+
+    Ok(opened)
+}
+
+/// Dismisses an active stale alert, so it's not raised again until its
+/// branch/testbed reports. Returns `None` when the alert isn't active.
+pub async fn dismiss<C: ConnectionTrait>(
+    db: &C,
+    alert: stale_alert::Model,
+) -> Result<Option<stale_alert::Model>, DbErr> {
+    if alert.status != stale_alert::StaleAlertStatus::Active {
+        return Ok(None);
+    }
+    let now = chrono::Utc::now().fixed_offset();
+    let mut active: stale_alert::ActiveModel = alert.into();
+    active.status = Set(stale_alert::StaleAlertStatus::Dismissed);
+    active.resolved_at = Set(Some(now));
+    active.updated_at = Set(now);
+    active.update(db).await.map(Some)
+}
+
+/// Resolves active stale alerts for the branch/testbed a new report belongs to.
+pub async fn resolve_for_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+) -> Result<(), DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    entities::StaleAlert::update_many()
+        .col_expr(
+            stale_alert::Column::Status,
+            Expr::value(stale_alert::StaleAlertStatus::Resolved),
+        )
+        .col_expr(stale_alert::Column::ResolvedAt, Expr::value(now))
+        .col_expr(stale_alert::Column::UpdatedAt, Expr::value(now))
+        .filter(stale_alert::Column::BranchId.eq(report.branch_id))
+        .filter(stale_alert::Column::TestbedId.eq(report.testbed_id))
+        .filter(stale_alert::Column::Status.eq(stale_alert::StaleAlertStatus::Active))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub fn is_stale(
+    last_report_at: DateTime<FixedOffset>,
+    cadence_hours: i32,
+    now: DateTime<FixedOffset>,
+) -> bool {
+    cadence_hours > 0 && now - last_report_at > chrono::Duration::hours(cadence_hours as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stale() {
+        let last = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap();
+        let within = DateTime::parse_from_rfc3339("2024-01-01T23:00:00Z").unwrap();
+        let after = DateTime::parse_from_rfc3339("2024-01-02T01:00:00Z").unwrap();
+
+        assert!(!is_stale(last, 24, within));
+        assert!(is_stale(last, 24, after));
+        assert!(!is_stale(last, 0, after));
+    }
+}
@@ -0,0 +1,818 @@
+//! Resumable uploads for artifacts such as flamegraphs.
+//!
+//! A mutation opens an upload and hands out `/uploads/{token}`. The client
+//! PUTs the file there in chunks, each with a `Content-Range`, and every
+//! response carries `Upload-Offset`, the number of bytes stored so far. A
+//! chunk that doesn't start at that offset is refused with `409 Conflict`,
+//! and `HEAD` reports the offset, so a client whose connection dropped
+//! resumes where the server left off instead of starting over. Once every
+//! byte is in, the client confirms the upload with the file's SHA-256,
+//! which is checked against what was stored.
+//!
+//! Confirmed files are content-addressed: each distinct file is stored once
+//! in `blobs`, keyed by its SHA-256, however many artifacts refer to it, and
+//! is deleted once none do. A client that already knows the hash can ask
+//! first and skip uploading a file its project already has.
+//!
+//! Uploads and blobs live in the database next to report outputs. An hourly
+//! garbage collection deletes uploads after a day, including ones that were
+//! never confirmed, and blobs at least as old that no artifact refers to;
+//! `driftwatch storage gc --dry-run` lists what it would delete. Blobs and
+//! report outputs are kept zstd-compressed, recording their encoding and
+//! size before compression; ones stored before that are recompressed by the
+//! cleanup.
+
+use std::fmt;
+use std::io::Read;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Path, State};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::put;
+use axum::Router;
+use chrono::{DateTime, FixedOffset};
+use flate2::read::GzDecoder;
+use sea_orm::prelude::Expr;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, QuerySelect, Set, Statement,
+};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::entities::blob::ContentEncoding;
+use crate::entities::{self, blob, report_output, upload};
+use crate::jobs::JobRegistry;
+use crate::{compression, error_reports, redaction};
+
+/// Largest file that can be uploaded
+pub const MAX_UPLOAD_BYTES: i64 = 10 * 1024 * 1024;
+
+/// Largest single chunk the server accepts
+pub const MAX_CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+/// Content types uploads may declare; the content itself is checked when
+/// the upload is confirmed
+pub const ACCEPTED_CONTENT_TYPES: &[&str] = &["image/svg+xml"];
+
+/// Bytes stored so far, on every upload response
+pub const UPLOAD_OFFSET_HEADER: HeaderName =
+    HeaderName::from_static(driftwatch_protocol::UPLOAD_OFFSET_HEADER);
+
+/// Uploads, and blobs no artifact refers to, are deleted once they are
+/// this old. Younger ones may be an upload in progress or a file about to
+/// be linked again.
+pub const GC_GRACE_PERIOD: chrono::Duration = chrono::Duration::hours(24);
+
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Job kind of the hourly storage cleanup
+pub const CLEAN_UP_STORAGE_JOB: &str = "clean_up_storage";
+
+/// Stored files and outputs are text that zstd shrinks about tenfold; higher
+/// levels gain little for the time they take
+const ZSTD_LEVEL: i32 = 9;
+
+/// Largest report output kept, uncompressed; as much as redaction scans
+pub const MAX_OUTPUT_BYTES: usize = redaction::MAX_SCANNED_BYTES as usize;
+
+/// Blobs and report outputs from before compression recompressed per
+/// cleanup, of each
+const RECOMPRESS_PER_RUN: u64 = 1000;
+
+/// Path of the upload's endpoint, relative to the API's own URL
+pub fn upload_url(upload: &upload::Model) -> String {
+    format!("/uploads/{}", upload.token)
+}
+
+/// How artifacts refer to the upload holding their file
+pub fn storage_path(upload: &upload::Model) -> String {
+    format!("uploads/{}", upload.id)
+}
+
+/// Opens an empty upload for one of the project's files
+pub async fn create<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    file_name: &str,
+) -> Result<upload::Model, DbErr> {
+    upload::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        project_id: Set(project_id),
+        token: Set(Uuid::new_v4().simple().to_string()),
+        file_name: Set(file_name.to_string()),
+        content: Set(Vec::new()),
+        total_size: Set(None),
+        created_at: Set(chrono::Utc::now().into()),
+        completed_at: Set(None),
+    }
+    .insert(db)
+    .await
+}
+
+/// The project's upload behind `storage_path`, or `None`
+pub async fn find<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    storage_path: &str,
+) -> Result<Option<upload::Model>, DbErr> {
+    let Some(id) = storage_path
+        .strip_prefix("uploads/")
+        .and_then(|id| Uuid::parse_str(id).ok())
+    else {
+        return Ok(None);
+    };
+    entities::Upload::find_by_id(id)
+        .filter(upload::Column::ProjectId.eq(project_id))
+        .one(db)
+        .await
+}
+
+/// Ignores parameters such as `; charset=utf-8`
+fn is_accepted_content_type(value: &str) -> bool {
+    let media_type = value.split(';').next().unwrap_or_default().trim();
+    ACCEPTED_CONTENT_TYPES
+        .iter()
+        .any(|accepted| media_type.eq_ignore_ascii_case(accepted))
+}
+
+/// A parsed `Content-Range: bytes <first>-<last>/<total>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    pub start: i64,
+    /// One past the last byte
+    pub end: i64,
+    pub total: i64,
+}
+
+impl ContentRange {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+        let (first, last) = range.split_once('-')?;
+        let (start, last, total): (i64, i64, i64) =
+            (first.parse().ok()?, last.parse().ok()?, total.parse().ok()?);
+        if start < 0 || last < start || last >= total {
+            return None;
+        }
+        Some(Self {
+            start,
+            end: last + 1,
+            total,
+        })
+    }
+}
+
+/// Why a chunk or a confirmation was refused
+#[derive(Debug)]
+pub enum UploadError {
+    NotFound,
+    /// The upload was already confirmed and can't change
+    Completed,
+    /// The chunk doesn't start where the stored bytes end
+    OffsetMismatch(i64),
+    Invalid(String),
+    TooLarge,
+    UnsupportedType,
+    Incomplete {
+        received: i64,
+        expected: i64,
+    },
+    ChecksumMismatch,
+    Db(DbErr),
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Upload not found"),
+            Self::Completed => write!(f, "Upload was already confirmed"),
+            Self::OffsetMismatch(offset) => write!(f, "Upload continues at byte {}", offset),
+            Self::Invalid(message) => write!(f, "{}", message),
+            Self::TooLarge => write!(f, "Uploads are limited to {} bytes", MAX_UPLOAD_BYTES),
+            Self::UnsupportedType => write!(
+                f,
+                "Content-Type must be one of: {}",
+                ACCEPTED_CONTENT_TYPES.join(", ")
+            ),
+            Self::Incomplete { received, expected } => write!(
+                f,
+                "Upload is incomplete: received {} of {} bytes",
+                received, expected
+            ),
+            Self::ChecksumMismatch => write!(
+                f,
+                "Upload doesn't match its SHA-256 and was discarded; upload it again"
+            ),
+            Self::Db(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {}
+
+impl From<DbErr> for UploadError {
+    fn from(e: DbErr) -> Self {
+        Self::Db(e)
+    }
+}
+
+impl IntoResponse for UploadError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::Completed | Self::OffsetMismatch(_) => StatusCode::CONFLICT,
+            Self::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::UnsupportedType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Invalid(_) | Self::Incomplete { .. } | Self::ChecksumMismatch => {
+                StatusCode::BAD_REQUEST
+            }
+            Self::Db(e) => {
+                tracing::error!("Upload failed: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        let mut response = (status, self.to_string()).into_response();
+        if let Self::OffsetMismatch(offset) = self {
+            response
+                .headers_mut()
+                .insert(UPLOAD_OFFSET_HEADER, HeaderValue::from(offset));
+        }
+        response
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct Progress {
+    received: i32,
+    total_size: Option<i64>,
+    completed_at: Option<DateTime<FixedOffset>>,
+}
+
+/// Bytes received for the upload, without loading them
+async fn progress<C: ConnectionTrait>(db: &C, token: &str) -> Result<Option<Progress>, DbErr> {
+    Progress::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT octet_length(content) AS received, total_size, completed_at
+           FROM uploads WHERE token = $1"#,
+        [token.into()],
+    ))
+    .one(db)
+    .await
+}
+
+/// Stores a chunk if it starts exactly where the upload ends, returning the
+/// new offset. The offset check happens in the `UPDATE` itself, so retried
+/// or concurrent chunks can't be stored twice.
+pub async fn append<C: ConnectionTrait>(
+    db: &C,
+    token: &str,
+    range: ContentRange,
+    chunk: &[u8],
+) -> Result<i64, UploadError> {
+    if range.total > MAX_UPLOAD_BYTES {
+        return Err(UploadError::TooLarge);
+    }
+    if range.end - range.start != chunk.len() as i64 {
+        return Err(UploadError::Invalid(format!(
+            "Content-Range covers {} bytes but the body has {}",
+            range.end - range.start,
+            chunk.len()
+        )));
+    }
+
+    #[derive(FromQueryResult)]
+    struct Appended {
+        received: i32,
+    }
+
+    let appended = Appended::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"UPDATE uploads SET content = content || $1, total_size = $2
+           WHERE token = $3
+             AND completed_at IS NULL
+             AND octet_length(content) = $4
+             AND (total_size IS NULL OR total_size = $2)
+           RETURNING octet_length(content) AS received"#,
+        [
+            chunk.to_vec().into(),
+            range.total.into(),
+            token.into(),
+            (range.start as i32).into(),
+        ],
+    ))
+    .one(db)
+    .await?;
+    if let Some(appended) = appended {
+        return Ok(appended.received as i64);
+    }
+
+    // Work out why nothing was stored
+    let progress = progress(db, token).await?.ok_or(UploadError::NotFound)?;
+    if progress.completed_at.is_some() {
+        return Err(UploadError::Completed);
+    }
+    if progress
+        .total_size
+        .is_some_and(|total| total != range.total)
+    {
+        return Err(UploadError::Invalid(
+            "Content-Range total differs from earlier chunks".to_string(),
+        ));
+    }
+    Err(UploadError::OffsetMismatch(progress.received as i64))
+}
+
+/// Hex SHA-256, the key files are stored under
+pub fn digest(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// Compresses a file or output for storage
+pub fn compress(content: &[u8]) -> std::io::Result<Vec<u8>> {
+    zstd::bulk::compress(content, ZSTD_LEVEL)
+}
+
+/// The original bytes of stored content, failing if there are more than
+/// `max_size` of them
+pub fn decompress(
+    content: &[u8],
+    encoding: ContentEncoding,
+    max_size: usize,
+) -> std::io::Result<Vec<u8>> {
+    let decompressed = match encoding {
+        ContentEncoding::Identity => content.to_vec(),
+        ContentEncoding::Zstd => zstd::bulk::decompress(content, max_size)?,
+        ContentEncoding::Gzip => {
+            let mut decompressed = Vec::new();
+            GzDecoder::new(content)
+                .take(max_size as u64 + 1)
+                .read_to_end(&mut decompressed)?;
+            decompressed
+        }
+    };
+    if decompressed.len() > max_size {
+        return Err(std::io::Error::other(format!(
+            "Content is larger than {} bytes uncompressed",
+            max_size
+        )));
+    }
+    Ok(decompressed)
+}
+
+/// A gzip-compressed report output as it is stored: zstd-compressed, with
+/// its size before compression
+pub fn store_output(gzip: &[u8]) -> std::io::Result<(Vec<u8>, i64)> {
+    let output = decompress(gzip, ContentEncoding::Gzip, MAX_OUTPUT_BYTES)?;
+    Ok((compress(&output)?, output.len() as i64))
+}
+
+/// A stored report output gzip-compressed, as the API hands it out
+pub fn output_gzip(output: &report_output::Model) -> std::io::Result<Vec<u8>> {
+    if output.encoding == ContentEncoding::Gzip {
+        return Ok(output.content.clone());
+    }
+    let max_size = output.size.map_or(MAX_OUTPUT_BYTES, |size| size as usize);
+    compression::gzip(&decompress(&output.content, output.encoding, max_size)?)
+}
+
+/// Checks that an upload holds the whole file and, when `sha256` is given,
+/// that it's the file the client sent. Returns the hex SHA-256 of the
+/// stored bytes.
+pub fn verify(
+    upload: &upload::Model,
+    file_size: i64,
+    sha256: Option<&str>,
+) -> Result<String, UploadError> {
+    if upload.completed_at.is_some() {
+        return Err(UploadError::Completed);
+    }
+    let received = upload.content.len() as i64;
+    if received != file_size || upload.total_size != Some(file_size) {
+        return Err(UploadError::Incomplete {
+            received,
+            expected: file_size,
+        });
+    }
+    let digest = digest(&upload.content);
+    if sha256.is_some_and(|expected| !expected.trim().eq_ignore_ascii_case(&digest)) {
+        return Err(UploadError::ChecksumMismatch);
+    }
+    Ok(digest)
+}
+
+/// Stores `content`, the verified upload's bytes as they should be kept,
+/// in the blob for `digest` unless an identical file is already stored, and
+/// seals the upload. Fails if it was confirmed concurrently.
+pub async fn complete<C: ConnectionTrait>(
+    db: &C,
+    upload: &upload::Model,
+    content: &[u8],
+    digest: &str,
+) -> Result<(), UploadError> {
+    let updated = entities::Upload::update_many()
+        .col_expr(
+            upload::Column::CompletedAt,
+            Expr::value(chrono::Utc::now().fixed_offset()),
+        )
+        .col_expr(upload::Column::Content, Expr::value(Vec::<u8>::new()))
+        .filter(upload::Column::Id.eq(upload.id))
+        .filter(upload::Column::CompletedAt.is_null())
+        .exec(db)
+        .await?;
+    if updated.rows_affected == 0 {
+        return Err(UploadError::Completed);
+    }
+
+    let compressed = compress(content).map_err(|e| UploadError::Invalid(e.to_string()))?;
+    entities::Blob::insert(blob::ActiveModel {
+        sha256: Set(digest.to_string()),
+        content: Set(compressed),
+        size: Set(content.len() as i64),
+        encoding: Set(ContentEncoding::Zstd),
+        ref_count: Set(0),
+        created_at: Set(chrono::Utc::now().into()),
+    })
+    .on_conflict(
+        OnConflict::column(blob::Column::Sha256)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(db)
+    .await?;
+    Ok(())
+}
+
+/// Where artifacts with this content are stored
+pub fn blob_path(sha256: &str) -> String {
+    format!("blobs/{}", sha256)
+}
+
+/// A stored file one of the project's artifacts was uploaded as
+#[derive(Debug, FromQueryResult)]
+pub struct StoredFile {
+    /// Hex SHA-256 of the stored, sanitized file
+    pub sha256: String,
+    pub size: i64,
+}
+
+/// The stored file for an upload with this SHA-256, if one of the project's
+/// artifacts was already uploaded with it. Other projects' files don't
+/// count, so nobody can learn what another tenant has stored by guessing
+/// hashes.
+pub async fn find_stored<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    upload_sha256: &str,
+) -> Result<Option<StoredFile>, DbErr> {
+    StoredFile::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT b.sha256, b.size FROM flamegraphs f
+           JOIN reports r ON r.id = f.report_id
+           JOIN blobs b ON b.sha256 = f.sha256
+           WHERE f.upload_sha256 = $1 AND r.project_id = $2
+           LIMIT 1"#,
+        [upload_sha256.to_lowercase().into(), project_id.into()],
+    ))
+    .one(db)
+    .await
+}
+
+fn offset_response(status: StatusCode, offset: i64) -> Response {
+    let mut response = status.into_response();
+    response
+        .headers_mut()
+        .insert(UPLOAD_OFFSET_HEADER, HeaderValue::from(offset));
+    response
+}
+
+async fn put_chunk(
+    State(db): State<DatabaseConnection>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, UploadError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !is_accepted_content_type(content_type) {
+        return Err(UploadError::UnsupportedType);
+    }
+    let range = headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ContentRange::parse)
+        .ok_or_else(|| {
+            UploadError::Invalid("Expected Content-Range: bytes <first>-<last>/<total>".into())
+        })?;
+    let offset = append(&db, &token, range, &body).await?;
+    Ok(offset_response(StatusCode::NO_CONTENT, offset))
+}
+
+async fn head_upload(
+    State(db): State<DatabaseConnection>,
+    Path(token): Path<String>,
+) -> Result<Response, UploadError> {
+    let progress = progress(&db, &token).await?.ok_or(UploadError::NotFound)?;
+    Ok(offset_response(StatusCode::OK, progress.received as i64))
+}
+
+/// Routes for `/uploads/{token}`. The token in the path is the only
+/// credential, like a signed URL.
+pub fn router<S: Clone + Send + Sync + 'static>(db: DatabaseConnection) -> Router<S> {
+    Router::new()
+        .route("/uploads/{token}", put(put_chunk).head(head_upload))
+        .layer(DefaultBodyLimit::max(MAX_CHUNK_BYTES))
+        .with_state(db)
+}
+
+/// A stored object the garbage collection would delete
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct Orphan {
+    /// `upload` or `blob`
+    pub kind: String,
+    /// The upload's id or the blob's SHA-256
+    pub key: String,
+    /// Bytes it takes up in the database
+    pub bytes: i64,
+    pub created_at: DateTime<FixedOffset>,
+}
+
+/// Objects deleted by a garbage collection
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, FromQueryResult)]
+pub struct Garbage {
+    pub uploads: i64,
+    pub blobs: i64,
+    pub bytes: i64,
+}
+
+/// Uploads created before `$1`, confirmed or not; confirmed ones hold no
+/// bytes by then and their URL is of no more use
+const ORPHAN_UPLOADS: &str = "FROM uploads u WHERE u.created_at < $1";
+
+/// Blobs created before `$1` that no artifact refers to
+const ORPHAN_BLOBS: &str = r#"FROM blobs b
+    WHERE b.created_at < $1
+      AND NOT EXISTS (SELECT 1 FROM flamegraphs f WHERE f.sha256 = b.sha256)"#;
+
+/// Uploads and blobs [`collect_garbage`] would delete, oldest first
+pub async fn orphans<C: ConnectionTrait>(
+    db: &C,
+    created_before: DateTime<FixedOffset>,
+) -> Result<Vec<Orphan>, DbErr> {
+    Orphan::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"SELECT 'upload' AS kind, u.id::text AS key,
+                      octet_length(u.content)::bigint AS bytes, u.created_at
+               {ORPHAN_UPLOADS}
+               UNION ALL
+               SELECT 'blob' AS kind, b.sha256 AS key,
+                      octet_length(b.content)::bigint AS bytes, b.created_at
+               {ORPHAN_BLOBS}
+               ORDER BY created_at"#
+        ),
+        [created_before.into()],
+    ))
+    .all(db)
+    .await
+}
+
+/// Deletes uploads created before `created_before` and blobs from before
+/// then that no artifact refers to, checking `flamegraphs` rather than
+/// trusting `ref_count`.
+pub async fn collect_garbage<C: ConnectionTrait>(
+    db: &C,
+    created_before: DateTime<FixedOffset>,
+) -> Result<Garbage, DbErr> {
+    // A count that drifted above zero would keep the blob forever. The
+    // delete still requires a zero count, so a blob that gets linked while
+    // it waits for the row lock is left alone.
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!("UPDATE blobs SET ref_count = 0 WHERE sha256 IN (SELECT b.sha256 {ORPHAN_BLOBS}) AND ref_count <> 0"),
+        [created_before.into()],
+    ))
+    .await?;
+    let garbage = Garbage::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        format!(
+            r#"WITH deleted_uploads AS (
+                   DELETE {ORPHAN_UPLOADS}
+                   RETURNING octet_length(u.content)::bigint AS bytes
+               ), deleted_blobs AS (
+                   DELETE {ORPHAN_BLOBS} AND b.ref_count <= 0
+                   RETURNING octet_length(b.content)::bigint AS bytes
+               )
+               SELECT (SELECT COUNT(*) FROM deleted_uploads) AS uploads,
+                      (SELECT COUNT(*) FROM deleted_blobs) AS blobs,
+                      (SELECT COALESCE(SUM(bytes), 0) FROM deleted_uploads)::bigint
+                        + (SELECT COALESCE(SUM(bytes), 0) FROM deleted_blobs)::bigint AS bytes"#
+        ),
+        [created_before.into()],
+    ))
+    .one(db)
+    .await?;
+    Ok(garbage.unwrap_or_default())
+}
+
+/// Recompresses up to [`RECOMPRESS_PER_RUN`] blobs and as many report
+/// outputs stored before compression with zstd. Returns how many of each
+/// were recompressed. Outputs that aren't valid gzip are left as they are.
+pub async fn recompress<C: ConnectionTrait>(db: &C) -> Result<(u64, u64), DbErr> {
+    let blobs = entities::Blob::find()
+        .filter(blob::Column::Encoding.eq(ContentEncoding::Identity))
+        .limit(RECOMPRESS_PER_RUN)
+        .all(db)
+        .await?;
+    let mut recompressed_blobs = 0;
+    for stored in blobs {
+        let Ok(compressed) = compress(&stored.content) else {
+            continue;
+        };
+        let updated = entities::Blob::update_many()
+            .col_expr(blob::Column::Content, Expr::value(compressed))
+            .col_expr(blob::Column::Encoding, Expr::value(ContentEncoding::Zstd))
+            .filter(blob::Column::Sha256.eq(&stored.sha256))
+            .filter(blob::Column::Encoding.eq(ContentEncoding::Identity))
+            .exec(db)
+            .await?;
+        recompressed_blobs += updated.rows_affected;
+    }
+
+    let outputs = entities::ReportOutput::find()
+        .filter(report_output::Column::Encoding.eq(ContentEncoding::Gzip))
+        .limit(RECOMPRESS_PER_RUN)
+        .all(db)
+        .await?;
+    let mut recompressed_outputs = 0;
+    for output in outputs {
+        let Ok((compressed, size)) = store_output(&output.content) else {
+            continue;
+        };
+        // Unless the output was replaced in the meantime
+        let updated = entities::ReportOutput::update_many()
+            .col_expr(report_output::Column::Content, Expr::value(compressed))
+            .col_expr(
+                report_output::Column::Encoding,
+                Expr::value(ContentEncoding::Zstd),
+            )
+            .col_expr(report_output::Column::Size, Expr::value(size))
+            .filter(report_output::Column::ReportId.eq(output.report_id))
+            .filter(report_output::Column::CreatedAt.eq(output.created_at))
+            .filter(report_output::Column::Encoding.eq(ContentEncoding::Gzip))
+            .exec(db)
+            .await?;
+        recompressed_outputs += updated.rows_affected;
+    }
+
+    Ok((recompressed_blobs, recompressed_outputs))
+}
+
+/// Registers the hourly storage cleanup
+pub fn register_jobs(registry: &mut JobRegistry) {
+    registry.schedule(CLEAN_UP_STORAGE_JOB, CLEANUP_INTERVAL, |db| async move {
+        let created_before = chrono::Utc::now().fixed_offset() - GC_GRACE_PERIOD;
+        match collect_garbage(&db, created_before).await {
+            Ok(garbage) if garbage == Garbage::default() => {}
+            Ok(garbage) => tracing::info!(
+                "Deleted {} expired uploads and {} unreferenced blobs, {} bytes",
+                garbage.uploads,
+                garbage.blobs,
+                garbage.bytes
+            ),
+            Err(e) => {
+                tracing::error!("Upload cleanup failed: {}", e);
+                error_reports::capture(&e.into(), "storage", &[]);
+            }
+        }
+        match recompress(&db).await {
+            Ok((0, 0)) => {}
+            Ok((blobs, outputs)) => tracing::info!(
+                "Recompressed {} blobs and {} report outputs with zstd",
+                blobs,
+                outputs
+            ),
+            Err(e) => {
+                tracing::error!("Storage recompression failed: {}", e);
+                error_reports::capture(&e.into(), "storage", &[]);
+            }
+        }
+        Ok(())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(content: &[u8], total_size: Option<i64>) -> upload::Model {
+        upload::Model {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            token: "t".to_string(),
+            file_name: "flame.svg".to_string(),
+            content: content.to_vec(),
+            total_size,
+            created_at: chrono::Utc::now().into(),
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_accepted_content_types() {
+        assert!(is_accepted_content_type("image/svg+xml"));
+        assert!(is_accepted_content_type("Image/SVG+XML; charset=utf-8"));
+        assert!(!is_accepted_content_type("text/html"));
+        assert!(!is_accepted_content_type(""));
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            ContentRange::parse("bytes 0-1023/4096"),
+            Some(ContentRange {
+                start: 0,
+                end: 1024,
+                total: 4096
+            })
+        );
+        assert_eq!(
+            ContentRange::parse("bytes 4095-4095/4096").map(|r| r.end),
+            Some(4096)
+        );
+        for bad in [
+            "bytes 0-4096/4096",
+            "bytes 10-5/100",
+            "bytes */100",
+            "0-10/100",
+            "bytes 0-10",
+        ] {
+            assert_eq!(ContentRange::parse(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_verify_checks_size_and_checksum() {
+        let svg = b"<svg></svg>";
+        let digest = hex::encode(Sha256::digest(svg));
+
+        let complete = upload(svg, Some(svg.len() as i64));
+        assert_eq!(verify(&complete, svg.len() as i64, None).unwrap(), digest);
+        assert_eq!(
+            verify(&complete, svg.len() as i64, Some(&digest.to_uppercase())).unwrap(),
+            digest
+        );
+        assert!(matches!(
+            verify(&complete, svg.len() as i64, Some(&"0".repeat(64))),
+            Err(UploadError::ChecksumMismatch)
+        ));
+
+        let partial = upload(&svg[..4], Some(svg.len() as i64));
+        assert!(matches!(
+            verify(&partial, svg.len() as i64, Some(&digest)),
+            Err(UploadError::Incomplete {
+                received: 4,
+                expected: 11
+            })
+        ));
+    }
+
+    #[test]
+    fn test_stored_content_round_trips() {
+        let svg = "<svg><rect width=\"10\"/></svg>\n".repeat(200).into_bytes();
+        let stored = compress(&svg).unwrap();
+        assert!(stored.len() < svg.len() / 10);
+        assert_eq!(
+            decompress(&stored, ContentEncoding::Zstd, svg.len()).unwrap(),
+            svg
+        );
+        assert_eq!(
+            decompress(&svg, ContentEncoding::Identity, svg.len()).unwrap(),
+            svg
+        );
+        // A file that grows past its recorded size isn't inflated
+        assert!(decompress(&stored, ContentEncoding::Zstd, svg.len() - 1).is_err());
+        let gzip = compression::gzip(&svg).unwrap();
+        assert!(decompress(&gzip, ContentEncoding::Gzip, svg.len() - 1).is_err());
+
+        // Outputs arrive and leave as gzip, and are kept as zstd
+        let (content, size) = store_output(&gzip).unwrap();
+        assert_eq!(size, svg.len() as i64);
+        let output = report_output::Model {
+            report_id: Uuid::new_v4(),
+            content,
+            encoding: ContentEncoding::Zstd,
+            size: Some(size),
+            redactions: 0,
+            created_at: chrono::Utc::now().into(),
+        };
+        assert_eq!(
+            compression::gunzip(&output_gzip(&output).unwrap()).unwrap(),
+            svg
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, Statement, Value};
+
+use crate::entities::{metric, report};
+
+/// Metrics per upsert, keeping the statement well under Postgres' limit of
+/// 65535 bind parameters
+const UPSERT_CHUNK_SIZE: usize = 1000;
+
+/// Refreshes the summary rows touched by a newly inserted report. Reports
+/// older than the current latest value (e.g. backfills) leave summaries alone.
+///
+/// One statement per chunk of metrics computes both deltas and upserts the
+/// summaries; the `WHERE` on the conflict keeps a concurrent, newer report's
+/// summary from being overwritten.
+pub async fn update_summaries<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<(), DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+    let cutoff_7d = report.created_at - chrono::Duration::days(7);
+    let cutoff_30d = report.created_at - chrono::Duration::days(30);
+
+    for chunk in metrics.chunks(UPSERT_CHUNK_SIZE) {
+        let mut values: Vec<Value> = vec![
+            report.project_id.into(),
+            report.branch_id.into(),
+            report.testbed_id.into(),
+            report.id.into(),
+            report.created_at.into(),
+            cutoff_7d.into(),
+            cutoff_30d.into(),
+            now.into(),
+        ];
+        let mut ids = Vec::with_capacity(chunk.len());
+        for metric in chunk {
+            values.push(metric.id.into());
+            ids.push(format!("${}", values.len()));
+        }
+
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"INSERT INTO metric_summaries (
+                       id, project_id, benchmark_id, measure_id, branch_id, testbed_id,
+                       latest_metric_id, latest_report_id, latest_value, latest_at,
+                       delta_7d, delta_30d, updated_at
+                   )
+                   SELECT DISTINCT ON (m.benchmark_id, m.measure_id)
+                          gen_random_uuid(), $1, m.benchmark_id, m.measure_id, $2, $3,
+                          m.id, $4, m.value, $5,
+                          (m.value - week_ago.value) / NULLIF(week_ago.value, 0) * 100,
+                          (m.value - month_ago.value) / NULLIF(month_ago.value, 0) * 100,
+                          $8
+                   FROM metrics m
+                   LEFT JOIN LATERAL (
+                       SELECT p.value FROM metrics p
+                       JOIN reports r ON r.id = p.report_id
+                       WHERE p.benchmark_id = m.benchmark_id AND p.measure_id = m.measure_id
+                         AND r.branch_id = $2 AND r.testbed_id = $3 AND r.finalized
+                         AND p.created_at <= $6
+                       ORDER BY p.created_at DESC
+                       LIMIT 1
+                   ) week_ago ON true
+                   LEFT JOIN LATERAL (
+                       SELECT p.value FROM metrics p
+                       JOIN reports r ON r.id = p.report_id
+                       WHERE p.benchmark_id = m.benchmark_id AND p.measure_id = m.measure_id
+                         AND r.branch_id = $2 AND r.testbed_id = $3 AND r.finalized
+                         AND p.created_at <= $7
+                       ORDER BY p.created_at DESC
+                       LIMIT 1
+                   ) month_ago ON true
+                   WHERE m.id IN ({})
+                   ORDER BY m.benchmark_id, m.measure_id, m.created_at DESC
+                   ON CONFLICT (benchmark_id, measure_id, branch_id, testbed_id) DO UPDATE SET
+                       latest_metric_id = EXCLUDED.latest_metric_id,
+                       latest_report_id = EXCLUDED.latest_report_id,
+                       latest_value = EXCLUDED.latest_value,
+                       latest_at = EXCLUDED.latest_at,
+                       delta_7d = EXCLUDED.delta_7d,
+                       delta_30d = EXCLUDED.delta_30d,
+                       updated_at = EXCLUDED.updated_at
+                   WHERE EXCLUDED.latest_at >= metric_summaries.latest_at"#,
+                ids.join(", ")
+            ),
+            values,
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,410 @@
+//! Checks that uploaded flamegraphs are SVG images and strips anything that
+//! could run in a browser viewing them.
+//!
+//! Uploads are parsed as XML and rebuilt from an allowlist: SVG elements
+//! that only draw, and attributes that only style or position them. Flamegraph
+//! tools embed JavaScript for zooming and search; it is dropped along with
+//! event handlers, `<foreignObject>` and anything outside the SVG namespace,
+//! whatever prefix it's written with. Links are checked after entities are
+//! resolved. The flamegraph stays readable, just not interactive. Anything
+//! that doesn't parse, or declares entities, is rejected.
+
+use std::borrow::Cow;
+
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{Namespace, PrefixDeclaration, ResolveResult};
+use quick_xml::reader::NsReader;
+use quick_xml::Writer;
+
+const SVG_NS: &[u8] = b"http://www.w3.org/2000/svg";
+const XLINK_NS: &[u8] = b"http://www.w3.org/1999/xlink";
+const XML_NS: &[u8] = b"http://www.w3.org/XML/1998/namespace";
+const XHTML_NS: &[u8] = b"http://www.w3.org/1999/xhtml";
+
+/// Elements that draw, group or describe; no scripts, animation, embedded
+/// documents or `<foreignObject>`
+const ELEMENTS: &[&str] = &[
+    "svg",
+    "g",
+    "defs",
+    "title",
+    "desc",
+    "style",
+    "symbol",
+    "use",
+    "a",
+    "rect",
+    "circle",
+    "ellipse",
+    "line",
+    "polyline",
+    "polygon",
+    "path",
+    "text",
+    "tspan",
+    "linearGradient",
+    "radialGradient",
+    "stop",
+    "clipPath",
+    "mask",
+    "pattern",
+];
+
+/// Geometry, presentation and identity attributes; `href` is checked
+/// separately
+const ATTRIBUTES: &[&str] = &[
+    "id",
+    "class",
+    "style",
+    "type",
+    "version",
+    "baseProfile",
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "dx",
+    "dy",
+    "width",
+    "height",
+    "d",
+    "points",
+    "transform",
+    "viewBox",
+    "preserveAspectRatio",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "stroke",
+    "stroke-width",
+    "stroke-opacity",
+    "stroke-dasharray",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "opacity",
+    "color",
+    "display",
+    "visibility",
+    "overflow",
+    "cursor",
+    "clip-path",
+    "clip-rule",
+    "mask",
+    "font-family",
+    "font-size",
+    "font-weight",
+    "font-style",
+    "text-anchor",
+    "dominant-baseline",
+    "rotate",
+    "textLength",
+    "lengthAdjust",
+    "offset",
+    "stop-color",
+    "stop-opacity",
+    "gradientUnits",
+    "gradientTransform",
+    "patternUnits",
+    "patternContentUnits",
+    "patternTransform",
+    "clipPathUnits",
+    "maskUnits",
+    "maskContentUnits",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Sanitized {
+    pub content: Vec<u8>,
+    /// Scripts, handlers and other elements or attributes removed
+    pub removed: usize,
+}
+
+fn not_svg() -> String {
+    "Flamegraph must be an SVG image".to_string()
+}
+
+/// Rejects anything that isn't an SVG image and strips active content
+/// from the rest
+pub fn sanitize(content: &[u8]) -> Result<Sanitized, String> {
+    let text = std::str::from_utf8(content).map_err(|_| not_svg())?;
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+
+    let mut reader = NsReader::from_str(text);
+    let mut writer = Writer::new(Vec::with_capacity(text.len()));
+    let mut removed = 0;
+    // Open elements that were kept, and how deep into a dropped one we are
+    let mut depth = 0usize;
+    let mut skipping = 0usize;
+    let mut seen_root = false;
+
+    loop {
+        let (ns, event) = reader.read_resolved_event().map_err(|_| not_svg())?;
+        let event = match event {
+            Event::Eof => break,
+            Event::Start(_) | Event::Empty(_) if skipping > 0 => {
+                if matches!(event, Event::Start(_)) {
+                    skipping += 1;
+                }
+                continue;
+            }
+            Event::End(_) if skipping > 0 => {
+                skipping -= 1;
+                continue;
+            }
+            _ if skipping > 0 => continue,
+            // A second root
+            Event::Start(_) | Event::Empty(_) if depth == 0 && seen_root => {
+                return Err(not_svg());
+            }
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                let is_start = matches!(event, Event::Start(_));
+                let local = start.local_name();
+                let name = std::str::from_utf8(local.as_ref()).map_err(|_| not_svg())?;
+                if depth == 0 && (name != "svg" || !svg_namespace(&ns)) {
+                    return Err(not_svg());
+                }
+                seen_root = true;
+                if !svg_namespace(&ns) || !ELEMENTS.contains(&name) {
+                    removed += 1;
+                    if is_start {
+                        skipping = 1;
+                    }
+                    continue;
+                }
+                let kept = keep_attributes(&reader, start, &mut removed)?;
+                if is_start {
+                    depth += 1;
+                    Event::Start(kept)
+                } else {
+                    Event::Empty(kept)
+                }
+            }
+            Event::End(end) => {
+                depth -= 1;
+                Event::End(end)
+            }
+            Event::Text(text) => {
+                // Unknown entities can't be declared, so this only fails on
+                // references that don't resolve
+                text.unescape().map_err(|_| not_svg())?;
+                if depth == 0 && !text.iter().all(u8::is_ascii_whitespace) {
+                    return Err(not_svg());
+                }
+                Event::Text(text)
+            }
+            Event::CData(cdata) if depth > 0 => Event::CData(cdata),
+            Event::Decl(decl) if !seen_root => Event::Decl(decl),
+            Event::DocType(doctype) => {
+                // An internal subset can declare entities, which allow
+                // expansion bombs and external references
+                if seen_root || doctype.contains(&b'[') {
+                    return Err("Flamegraph SVGs can't declare entities".to_string());
+                }
+                Event::DocType(doctype)
+            }
+            Event::Comment(comment) => Event::Comment(comment),
+            Event::PI(_) => {
+                removed += 1;
+                continue;
+            }
+            _ => return Err(not_svg()),
+        };
+        writer.write_event(event).map_err(|_| not_svg())?;
+    }
+
+    if !seen_root || depth > 0 || skipping > 0 {
+        return Err(not_svg());
+    }
+    Ok(Sanitized {
+        content: writer.into_inner(),
+        removed,
+    })
+}
+
+/// Unprefixed elements of a document without namespace declarations are
+/// taken as SVG, like the `<svg>` root says
+fn svg_namespace(ns: &ResolveResult) -> bool {
+    match ns {
+        ResolveResult::Bound(Namespace(ns)) => *ns == SVG_NS,
+        ResolveResult::Unbound => true,
+        ResolveResult::Unknown(_) => false,
+    }
+}
+
+/// The start tag with only allowed attributes, values re-escaped
+fn keep_attributes<'a>(
+    reader: &NsReader<&[u8]>,
+    start: &BytesStart<'a>,
+    removed: &mut usize,
+) -> Result<BytesStart<'a>, String> {
+    let name = std::str::from_utf8(start.name().as_ref())
+        .map_err(|_| not_svg())?
+        .to_string();
+    let mut kept = BytesStart::new(name);
+    for attribute in start.attributes() {
+        let attribute: Attribute = attribute.map_err(|_| not_svg())?;
+        let key = std::str::from_utf8(attribute.key.as_ref()).map_err(|_| not_svg())?;
+        let value: Cow<str> = attribute.unescape_value().map_err(|_| not_svg())?;
+        if keep_attribute(reader, &attribute, &value)? {
+            kept.push_attribute((key, value.as_ref()));
+        } else {
+            *removed += 1;
+        }
+    }
+    Ok(kept)
+}
+
+fn keep_attribute(
+    reader: &NsReader<&[u8]>,
+    attribute: &Attribute,
+    value: &str,
+) -> Result<bool, String> {
+    if let Some(declaration) = attribute.key.as_namespace_binding() {
+        // The parser compares namespaces as written, so one spelled with
+        // character references would be judged differently than a browser
+        // does
+        if attribute.value.contains(&b'&') {
+            return Err(not_svg());
+        }
+        return Ok(match declaration {
+            PrefixDeclaration::Default => value.as_bytes() == SVG_NS,
+            PrefixDeclaration::Named(_) => true,
+        });
+    }
+
+    let (ns, local) = reader.resolve_attribute(attribute.key);
+    let local = std::str::from_utf8(local.as_ref()).map_err(|_| not_svg())?;
+    Ok(match ns {
+        ResolveResult::Unbound => {
+            (ATTRIBUTES.contains(&local) || local.starts_with("data-"))
+                || (local == "href" && safe_url(value))
+        }
+        ResolveResult::Bound(Namespace(ns)) if ns == XLINK_NS => {
+            local == "title" || (local == "href" && safe_url(value))
+        }
+        ResolveResult::Bound(Namespace(ns)) if ns == XML_NS => local == "space" || local == "lang",
+        // Nothing in the SVG or XHTML namespace is meant as an attribute;
+        // other namespaces, such as the one flamegraph tools put their
+        // sample counts in, mean nothing to a browser
+        ResolveResult::Bound(Namespace(ns)) => ns != SVG_NS && ns != XHTML_NS,
+        ResolveResult::Unknown(_) => return Err(not_svg()),
+    })
+}
+
+/// Fragments within the image and web links; browsers ignore whitespace
+/// and control characters anywhere in a scheme, so they are dropped before
+/// looking for one
+fn safe_url(value: &str) -> bool {
+    let url: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if url.starts_with('#') {
+        return true;
+    }
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            scheme == "http" || scheme == "https"
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitized(svg: &str) -> (String, usize) {
+        let result = sanitize(svg.as_bytes()).unwrap();
+        (String::from_utf8(result.content).unwrap(), result.removed)
+    }
+
+    #[test]
+    fn test_accepts_flamegraph_prologs() {
+        let svg = "<?xml version=\"1.0\" standalone=\"no\"?>\n\
+            <!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\" \
+            \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">\n\
+            <!-- generated -->\n\
+            <svg version=\"1.1\" width=\"1200\"><g><title>main (10 samples)</title></g></svg>";
+        assert_eq!(sanitized(svg), (svg.to_string(), 0));
+    }
+
+    #[test]
+    fn test_rejects_non_svg() {
+        for bad in [
+            &b"<html><script>alert(1)</script></html>"[..],
+            b"\x89PNG\r\n\x1a\n",
+            b"not an image",
+            b"<!DOCTYPE svg [<!ENTITY lol \"lol\">]><svg>&lol;</svg>",
+            b"<svg><!ENTITY x SYSTEM \"file:///etc/passwd\"></svg>",
+            b"<svg><script>never closed",
+            b"<svg><g></svg>",
+            b"<svg>&undeclared;</svg>",
+            b"<svg></svg><svg></svg>",
+            b"<x:svg xmlns:x=\"urn:other\"></x:svg>",
+            b"<svg xmlns:x=\"http://www.w3.org/1999/&#120;link\"><a x:href=\"javascript:1\"/></svg>",
+        ] {
+            assert!(sanitize(bad).is_err(), "{:?}", String::from_utf8_lossy(bad));
+        }
+    }
+
+    #[test]
+    fn test_strips_active_content() {
+        let svg = r#"<svg onload="steal()" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+<script type="text/ecmascript"><![CDATA[ function s(e) { } ]]></script>
+<SCRIPT src="https://evil.example/x.js"/>
+<g onmouseover='s(this)' class="func_g"><title>a > b</title><rect x="0"/></g>
+<a xlink:href=" javascript:alert(1)"><text>click</text></a>
+<set attributeName="href" to="javascript:alert(2)"/>
+<foreignObject><body><iframe src="https://evil.example"></iframe></body></foreignObject>
+<g data-label="x > y" onclick=run()><title>on=1</title></g>
+</svg>"#;
+        // Unquoted attributes aren't XML
+        assert!(sanitize(svg.as_bytes()).is_err());
+
+        let svg = svg.replace("onclick=run()", "onclick='run()'");
+        let (clean, removed) = sanitized(&svg);
+        assert_eq!(removed, 8, "{}", clean);
+        for gone in [
+            "steal",
+            "ecmascript",
+            "x.js",
+            "onmouseover",
+            "javascript",
+            "iframe",
+            "run()",
+        ] {
+            assert!(!clean.contains(gone), "{} left in {}", gone, clean);
+        }
+        // Text and ordinary attributes survive
+        assert!(clean.contains(r#"<g class="func_g"><title>a > b</title>"#));
+        assert!(clean.contains(r#"<g data-label="x &gt; y"><title>on=1</title>"#));
+    }
+
+    #[test]
+    fn test_strips_disguised_scripts() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:x="http://www.w3.org/2000/svg" xmlns:l="http://www.w3.org/1999/xlink" xmlns:fg="http://github.com/jonhoo/inferno">
+<x:script>alert(1)</x:script>
+<x:rect fg:w="10" width="10"/>
+<a href="&#106;avascript:alert(2)"><text>one</text></a>
+<a l:href="java&#x09;script:alert(3)"><text>two</text></a>
+<a href="#frame"><text>three</text></a>
+<g xmlns="http://www.w3.org/1999/xhtml"><script>alert(4)</script></g>
+</svg>"##;
+        let (clean, removed) = sanitized(svg);
+        assert_eq!(removed, 4, "{}", clean);
+        assert!(!clean.contains("alert"), "{}", clean);
+        assert!(!clean.contains("avascript"), "{}", clean);
+        assert!(clean.contains(r#"<x:rect fg:w="10" width="10"/>"#));
+        assert!(clean.contains(r##"<a href="#frame">"##));
+    }
+}
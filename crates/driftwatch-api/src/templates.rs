@@ -0,0 +1,141 @@
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DbErr, Set};
+use uuid::Uuid;
+
+use crate::entities::{measure, threshold};
+
+/// Template used when a project is created without naming one.
+pub const DEFAULT_TEMPLATE: &str = "minimal";
+
+/// A measure seeded into new projects, with an optional default threshold.
+/// Boundaries are percentages, as on [`threshold::Model`].
+pub struct MeasureTemplate {
+    pub name: &'static str,
+    pub units: &'static str,
+    pub upper_boundary: Option<f64>,
+    pub lower_boundary: Option<f64>,
+    pub min_sample_size: i32,
+}
+
+pub struct ProjectTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub measures: &'static [MeasureTemplate],
+}
+
+pub const TEMPLATES: &[ProjectTemplate] = &[
+    ProjectTemplate {
+        name: "minimal",
+        description: "Latency only, no thresholds",
+        measures: &[MeasureTemplate {
+            name: "latency",
+            units: "ns",
+            upper_boundary: None,
+            lower_boundary: None,
+            min_sample_size: 2,
+        }],
+    },
+    ProjectTemplate {
+        name: "standard",
+        description: "Latency, throughput, allocs and binary size",
+        measures: &[
+            MeasureTemplate {
+                name: "latency",
+                units: "ns",
+                upper_boundary: Some(10.0),
+                lower_boundary: None,
+                min_sample_size: 5,
+            },
+            MeasureTemplate {
+                name: "throughput",
+                units: "ops/s",
+                upper_boundary: None,
+                lower_boundary: Some(10.0),
+                min_sample_size: 5,
+            },
+            MeasureTemplate {
+                name: "allocs",
+                units: "allocations",
+                upper_boundary: Some(5.0),
+                lower_boundary: None,
+                min_sample_size: 2,
+            },
+            // Binary size is deterministic, so a single earlier build is
+            // enough of a baseline
+            MeasureTemplate {
+                name: "binary_size",
+                units: "bytes",
+                upper_boundary: Some(2.0),
+                lower_boundary: None,
+                min_sample_size: 1,
+            },
+        ],
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static ProjectTemplate> {
+    TEMPLATES.iter().find(|t| t.name == name)
+}
+
+/// Creates the template's measures for a new project, plus a project-wide
+/// threshold for every measure that defines a boundary.
+pub async fn apply<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    template: &ProjectTemplate,
+) -> Result<(), DbErr> {
+    let now = chrono::Utc::now().fixed_offset();
+
+    for m in template.measures {
+        let measure = measure::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project_id),
+            name: Set(m.name.to_string()),
+            units: Set(Some(m.units.to_string())),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+
+        if m.upper_boundary.is_none() && m.lower_boundary.is_none() {
+            continue;
+        }
+
+        threshold::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project_id),
+            measure_id: Set(measure.id),
+            branch_id: Set(None),
+            testbed_id: Set(None),
+            upper_boundary: Set(m.upper_boundary),
+            lower_boundary: Set(m.lower_boundary),
+            min_sample_size: Set(m.min_sample_size),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_are_well_formed() {
+        assert!(find(DEFAULT_TEMPLATE).is_some());
+        assert!(find("unknown").is_none());
+
+        for (i, template) in TEMPLATES.iter().enumerate() {
+            assert!(TEMPLATES[..i].iter().all(|t| t.name != template.name));
+            assert!(!template.measures.is_empty());
+            for (j, m) in template.measures.iter().enumerate() {
+                assert!(template.measures[..j].iter().all(|o| o.name != m.name));
+                assert!(m.min_sample_size >= 1);
+            }
+        }
+    }
+}
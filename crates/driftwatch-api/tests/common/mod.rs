@@ -1,33 +1,51 @@
 use async_graphql::dataloader::DataLoader;
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{
-    extract::State,
-    http::{header::AUTHORIZATION, Method, StatusCode},
+    extract::{ConnectInfo, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    response::IntoResponse,
     routing::{get, post},
-    Router,
+    Extension, Router,
 };
 use driftwatch_api::{
-    auth::{validate_token, TsaAuth},
+    auth::{validate_token, AuthUser, TsaAuth},
+    backpressure::{self, Backpressure},
     cache::AppCache,
-    graphql::build_schema,
+    feeds,
+    graphql::versioning::{ClientVersion, CLIENT_HEADER},
+    graphql::{build_schema, sdl_router},
+    http::HttpSecurity,
+    ingest,
+    jobs::{self, JobRegistry},
     loaders::{
         BenchmarkLoader, BranchLoader, MeasureLoader, MetricLoader, TestbedLoader, ThresholdLoader,
     },
-    migrations,
+    management, migrations, openmetrics, remote_write,
+    request_log::{self, RequestAttribution, RequestId},
+    secrets::{self, SecretKey},
+    shares::{self, ShareSettings},
+    storage,
 };
 use sea_orm::{ConnectionTrait, Database, DatabaseConnection};
 use serde::Deserialize;
 use std::fs::{self, OpenOptions};
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::oneshot;
-use tower_http::cors::{Any, CorsLayer};
 use tsa::{Auth, AuthConfig, NoopCallbacks};
 use tsa_adapter_seaorm::SeaOrmAdapter;
 use uuid::Uuid;
 
-const CONTAINER_NAME: &str = "driftwatch-shared-test-postgres";
+const CONTAINER_NAME: &str = "driftwatch-shared-test-timescaledb";
+
+/// Postgres with TimescaleDB available, so the hypertable conversion is
+/// covered too
+const POSTGRES_IMAGE: &str = "timescale/timescaledb:latest-pg16";
+
+/// Master key for sealing GitHub tokens in tests
+pub const TEST_SECRET_KEY: &str = "ZHJpZnR3YXRjaC10ZXN0LXNlY3JldC1rZXktMzJieXQ=";
 
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
@@ -111,7 +129,7 @@ fn get_or_start_container() -> Option<String> {
                 "POSTGRES_DB=postgres",
                 "-p",
                 "0:5432",
-                "postgres:16-alpine",
+                POSTGRES_IMAGE,
             ])
             .output()
             .ok()?;
@@ -230,13 +248,16 @@ struct TestAppState {
     db: DatabaseConnection,
     auth: Arc<TsaAuth>,
     cache: AppCache,
+    shares: ShareSettings,
 }
 
 async fn graphql_handler(
     State(state): State<TestAppState>,
+    Extension(request_id): Extension<RequestId>,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
     headers: axum::http::HeaderMap,
     req: GraphQLRequest,
-) -> Result<GraphQLResponse, (StatusCode, String)> {
+) -> Result<axum::response::Response, (StatusCode, String)> {
     let auth_header = headers
         .get(AUTHORIZATION)
         .and_then(|v| v.to_str().ok())
@@ -254,6 +275,11 @@ async fn graphql_handler(
     request = request.data(state.db.clone());
     request = request.data(state.cache.clone());
     request = request.data(state.auth.clone());
+    request = request.data(state.shares.clone());
+    let peer = peer.map(|Extension(ConnectInfo(addr))| addr);
+    if let Some(client) = state.shares.client_ip(&headers, peer) {
+        request = request.data(shares::ShareClient(client));
+    }
 
     request = request.data(DataLoader::new(
         BranchLoader {
@@ -292,16 +318,28 @@ async fn graphql_handler(
         tokio::spawn,
     ));
 
-    if let Some(user) = user {
-        request = request.data(user);
+    if let Some(user) = &user {
+        request = request.data(user.clone());
     }
-
-    Ok(state.schema.execute(request).await.into())
+    if let Some(client) = headers.get(CLIENT_HEADER).and_then(|v| v.to_str().ok()) {
+        request = request.data(ClientVersion(client.to_string()));
+    }
+    let mut attribution = RequestAttribution::new(&request, user.as_ref(), 0);
+
+    let mut response = state.schema.execute(request).await;
+    request_log::tag_errors(&mut response, &request_id);
+    attribution.errors = response.errors.len();
+    let retry_after = backpressure::retry_after(&response);
+    let mut http = (Extension(attribution), GraphQLResponse::from(response)).into_response();
+    backpressure::throttle(&mut http, retry_after);
+    Ok(http)
 }
 
 pub struct TestServer {
     pub base_url: String,
     pub client: reqwest::Client,
+    /// Direct access to the test database, for checking what's stored
+    pub db: DatabaseConnection,
     auth: Arc<TsaAuth>,
     shutdown_tx: Option<oneshot::Sender<()>>,
     db_name: String,
@@ -331,6 +369,7 @@ impl TestServer {
             .await
             .expect("Connect to test db");
 
+        secrets::install(SecretKey::from_base64(TEST_SECRET_KEY).expect("Test secret key"));
         migrations::run_migrations(&db)
             .await
             .expect("Run migrations");
@@ -344,22 +383,50 @@ impl TestServer {
         let schema = build_schema();
         let cache = AppCache::new();
 
+        let mut registry = JobRegistry::new();
+        ingest::register_jobs(&mut registry, cache.clone());
+        jobs::start_workers(db.clone(), registry, 2);
+
+        let sdl = sdl_router(&schema, auth.clone());
         let state = TestAppState {
             schema,
-            db,
+            db: db.clone(),
             auth: auth.clone(),
-            cache,
+            cache: cache.clone(),
+            shares: ShareSettings {
+                retention: chrono::Duration::days(7),
+                max_metrics: 10,
+                hourly_limit: 2,
+                max_live: 0,
+                client_ip_header: None,
+                recent: Default::default(),
+            },
         };
 
-        let cors = CorsLayer::new()
-            .allow_origin(Any)
-            .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-            .allow_headers(Any);
-
         let app = Router::new()
             .route("/health", get(|| async { "OK" }))
             .route("/graphql", post(graphql_handler))
-            .layer(cors)
+            .merge(sdl)
+            .merge(storage::router(db.clone()))
+            .merge(feeds::router(db.clone()))
+            .merge(shares::router(db.clone()))
+            .merge(remote_write::router(
+                db.clone(),
+                auth.clone(),
+                cache.clone(),
+                Backpressure::disabled(),
+            ))
+            .merge(openmetrics::router(db.clone(), auth.clone(), cache.clone()))
+            .merge(management::router(
+                db.clone(),
+                auth.clone(),
+                cache.clone(),
+                Backpressure::disabled(),
+            ));
+        let app = HttpSecurity::default()
+            .apply(app)
+            .expect("Apply HTTP security layers")
+            .layer(axum::middleware::from_fn(request_log::middleware))
             .with_state(state);
 
         let addr = format!("127.0.0.1:{}", port);
@@ -370,12 +437,15 @@ impl TestServer {
         let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
 
         tokio::spawn(async move {
-            axum::serve(listener, app)
-                .with_graceful_shutdown(async {
-                    let _ = shutdown_rx.await;
-                })
-                .await
-                .ok();
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+            .ok();
         });
 
         tokio::time::sleep(std::time::Duration::from_millis(50)).await;
@@ -383,6 +453,7 @@ impl TestServer {
         Some(Self {
             base_url: format!("http://127.0.0.1:{}", port),
             client: reqwest::Client::new(),
+            db,
             auth,
             shutdown_tx: Some(shutdown_tx),
             db_name,
@@ -400,6 +471,14 @@ impl TestServer {
         token
     }
 
+    /// The user `token` signs in as, for executing the schema directly
+    pub async fn auth_user(&self, token: &str) -> AuthUser {
+        match validate_token(token, &self.auth).await {
+            Ok(user) => user,
+            Err(e) => panic!("Invalid test token: {}", e.0),
+        }
+    }
+
     pub fn create_test_token(&self, user_id: &str) -> String {
         let rt = tokio::runtime::Handle::current();
         let auth = self.auth.clone();
@@ -429,6 +508,25 @@ impl TestServer {
         })
     }
 
+    /// Polls until the background job has evaluated the report's alerts.
+    pub async fn wait_for_evaluation(&self, report_id: &str, token: &str) {
+        for _ in 0..100 {
+            let result = self
+                .graphql::<serde_json::Value>(
+                    "query Report($id: ID!) { report(id: $id) { status } }",
+                    Some(serde_json::json!({ "id": report_id })),
+                    Some(token),
+                )
+                .await
+                .unwrap();
+            if result["report"]["status"] == "evaluated" {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        panic!("Report {} was not evaluated in time", report_id);
+    }
+
     pub async fn graphql<T: for<'de> Deserialize<'de>>(
         &self,
         query: &str,
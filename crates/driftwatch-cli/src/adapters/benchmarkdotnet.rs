@@ -0,0 +1,127 @@
+use serde::Deserialize;
+
+use super::{json_documents, BenchmarkResult};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Report {
+    benchmarks: Vec<Benchmark>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Benchmark {
+    full_name: String,
+    /// Missing when the benchmark failed to run
+    statistics: Option<Statistics>,
+    memory: Option<Memory>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Statistics {
+    mean: f64,
+    confidence_interval: Option<ConfidenceInterval>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ConfidenceInterval {
+    lower: f64,
+    upper: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Memory {
+    bytes_allocated_per_operation: Option<f64>,
+}
+
+/// Parses BenchmarkDotNet's JSON exporter output into `latency` results
+/// (BenchmarkDotNet already reports nanoseconds), plus `allocated_bytes`
+/// when `[MemoryDiagnoser]` is on. Every report in the output is read, so
+/// several result files can be concatenated.
+pub fn parse_benchmarkdotnet_output(output: &str) -> Vec<BenchmarkResult> {
+    json_documents::<Report>(output)
+        .into_iter()
+        .flat_map(|report| report.benchmarks)
+        .flat_map(|benchmark| {
+            let mut results = Vec::new();
+            if let Some(stats) = benchmark.statistics {
+                let interval = stats.confidence_interval;
+                results.push(BenchmarkResult {
+                    name: benchmark.full_name.clone(),
+                    measure: "latency".to_string(),
+                    value: stats.mean,
+                    lower: interval.as_ref().map(|i| i.lower),
+                    upper: interval.as_ref().map(|i| i.upper),
+                    reported_change: None,
+                    outliers: None,
+                });
+            }
+            if let Some(bytes) = benchmark
+                .memory
+                .and_then(|memory| memory.bytes_allocated_per_operation)
+            {
+                results.push(BenchmarkResult::new(
+                    &benchmark.full_name,
+                    "allocated_bytes",
+                    bytes,
+                ));
+            }
+            results
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_benchmarkdotnet_output() {
+        let output = r#"
+// * Export *
+  BenchmarkDotNet.Artifacts/results/Hashing-report-full.json
+{
+  "Title": "Hashing-20241016-120000",
+  "HostEnvironmentInfo": { "BenchmarkDotNetVersion": "0.13.12" },
+  "Benchmarks": [
+    {
+      "Namespace": "Bench",
+      "Type": "Hashing",
+      "Method": "Sha256",
+      "FullName": "Bench.Hashing.Sha256(N: 1000)",
+      "Statistics": {
+        "N": 15,
+        "Mean": 5123.4,
+        "Median": 5100.0,
+        "ConfidenceInterval": { "N": 15, "Mean": 5123.4, "Lower": 5000.1, "Upper": 5246.7 }
+      },
+      "Memory": { "Gen0Collections": 1, "BytesAllocatedPerOperation": 112 }
+    },
+    {
+      "FullName": "Bench.Hashing.Broken",
+      "Statistics": null
+    }
+  ]
+}
+{ "Benchmarks": [ { "FullName": "Bench.Parsing.Json", "Statistics": { "Mean": 42.0 } } ] }
+        "#;
+
+        let results = parse_benchmarkdotnet_output(output);
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].name, "Bench.Hashing.Sha256(N: 1000)");
+        assert_eq!(results[0].measure, "latency");
+        assert!((results[0].value - 5123.4).abs() < 0.001);
+        assert_eq!(results[0].lower, Some(5000.1));
+        assert_eq!(results[0].upper, Some(5246.7));
+
+        assert_eq!(results[1].measure, "allocated_bytes");
+        assert_eq!(results[1].value, 112.0);
+
+        assert_eq!(results[2].name, "Bench.Parsing.Json");
+        assert_eq!(results[2].lower, None);
+    }
+}
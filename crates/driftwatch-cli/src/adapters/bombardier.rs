@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use super::{find_json, percentile_measure, BenchmarkResult};
+
+#[derive(Deserialize)]
+struct Output {
+    spec: Option<Spec>,
+    result: RunResult,
+}
+
+#[derive(Deserialize)]
+struct Spec {
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RunResult {
+    latency: Stats,
+    rps: Stats,
+}
+
+#[derive(Deserialize)]
+struct Stats {
+    mean: f64,
+    #[serde(default)]
+    percentiles: BTreeMap<String, f64>,
+}
+
+const US_TO_NS: f64 = 1_000.0;
+
+/// Parses bombardier's JSON output into `throughput`, `latency` (the mean)
+/// and, when run with `-l`, `latency_pNN` results.
+pub fn parse_bombardier_output(output: &str, name: Option<&str>) -> Vec<BenchmarkResult> {
+    let Some(run) = find_json::<Output>(output) else {
+        return Vec::new();
+    };
+    let name = name
+        .map(str::to_string)
+        .or_else(|| run.spec.and_then(|spec| spec.url))
+        .unwrap_or_else(|| "bombardier".to_string());
+
+    let mut results = vec![
+        BenchmarkResult::new(&name, "throughput", run.result.rps.mean),
+        BenchmarkResult::new(&name, "latency", run.result.latency.mean * US_TO_NS),
+    ];
+
+    let mut percentiles: Vec<(String, f64)> = run
+        .result
+        .latency
+        .percentiles
+        .iter()
+        .filter_map(|(key, value)| Some((percentile_measure(key)?, value * US_TO_NS)))
+        .collect();
+    percentiles.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (measure, value) in percentiles {
+        results.push(BenchmarkResult::new(&name, &measure, value));
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bombardier_output() {
+        let output = r#"{"spec":{"numberOfConnections":125,"testType":"timed","testDurationSeconds":10,"method":"GET","url":"http://localhost:8080/api"},"result":{"bytesRead":1000,"bytesWritten":500,"timeTakenSeconds":10.0,"req1xx":0,"req2xx":50000,"req3xx":0,"req4xx":0,"req5xx":0,"others":0,"latency":{"mean":2500.5,"stddev":300.0,"max":9000.0,"percentiles":{"50":2000,"75":2600,"90":3100,"95":3500,"99":5000}},"rps":{"mean":4999.8,"stddev":120.0,"max":5400.0,"percentiles":{"50":5000,"75":5100,"90":5200,"95":5300,"99":5400}}}}"#;
+
+        let results = parse_bombardier_output(output, None);
+        let measures: Vec<_> = results.iter().map(|r| r.measure.as_str()).collect();
+        assert_eq!(
+            measures,
+            vec![
+                "throughput",
+                "latency",
+                "latency_p50",
+                "latency_p75",
+                "latency_p90",
+                "latency_p95",
+                "latency_p99"
+            ]
+        );
+        assert!(results
+            .iter()
+            .all(|r| r.name == "http://localhost:8080/api"));
+        assert!((results[0].value - 4999.8).abs() < 0.001);
+        assert!((results[1].value - 2_500_500.0).abs() < 1.0);
+        assert!((results[6].value - 5_000_000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_parse_bombardier_output_without_percentiles() {
+        let output = r#"Bombarding http://localhost:8080 for 10s using 125 connection(s)
+{"result":{"latency":{"mean":100.0,"stddev":1.0,"max":200.0},"rps":{"mean":10.0,"stddev":1.0,"max":12.0}}}"#;
+
+        let results = parse_bombardier_output(output, Some("health"));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "health");
+        assert!((results[1].value - 100_000.0).abs() < 1.0);
+    }
+}
@@ -0,0 +1,142 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::criterion::parse_time;
+use super::BenchmarkResult;
+
+// XML reporter (`-r xml`), values already in nanoseconds:
+// <BenchmarkResults name="fib 20" samples="100" ...>
+//   <mean value="43971.2" lowerBound="43889.1" upperBound="44106.4" ci="0.95"/>
+static XML_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?s)<BenchmarkResults\s[^>]*?name="([^"]*)"[^>]*>.*?<mean\s+value="([^"]+)"\s+lowerBound="([^"]+)"\s+upperBound="([^"]+)""#,
+    )
+    .unwrap()
+});
+
+// Console reporter: the name, samples, iterations and estimated run time,
+// then mean, low mean and high mean on the next line:
+// Fibonacci 20                                   100             1     2.5421 ms
+//                                         43.9712 us    43.8891 us    44.1064 us
+static CONSOLE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^(\S.*?)\s+\d+\s+\d+\s+[0-9.]+ (?:ns|µs|us|ms|s)\s*\n\s+([0-9.]+) (ns|µs|us|ms|s)\s+([0-9.]+) (ns|µs|us|ms|s)\s+([0-9.]+) (ns|µs|us|ms|s)",
+    )
+    .unwrap()
+});
+
+/// Parses Catch2 benchmark output from either the XML or the default
+/// console reporter into `latency` results.
+pub fn parse_catch2_output(output: &str) -> Vec<BenchmarkResult> {
+    if output.contains("<BenchmarkResults") {
+        return parse_xml(output);
+    }
+
+    CONSOLE_REGEX
+        .captures_iter(output)
+        .filter_map(|cap| {
+            Some(BenchmarkResult {
+                name: cap.get(1)?.as_str().to_string(),
+                measure: "latency".to_string(),
+                value: parse_time(cap.get(2)?.as_str(), cap.get(3)?.as_str())?,
+                lower: parse_time(cap.get(4)?.as_str(), cap.get(5)?.as_str()),
+                upper: parse_time(cap.get(6)?.as_str(), cap.get(7)?.as_str()),
+                reported_change: None,
+                outliers: None,
+            })
+        })
+        .collect()
+}
+
+fn parse_xml(output: &str) -> Vec<BenchmarkResult> {
+    XML_REGEX
+        .captures_iter(output)
+        .filter_map(|cap| {
+            Some(BenchmarkResult {
+                name: unescape_xml(cap.get(1)?.as_str()),
+                measure: "latency".to_string(),
+                value: cap.get(2)?.as_str().parse().ok()?,
+                lower: cap.get(3)?.as_str().parse().ok(),
+                upper: cap.get(4)?.as_str().parse().ok(),
+                reported_change: None,
+                outliers: None,
+            })
+        })
+        .collect()
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_catch2_xml() {
+        let output = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Catch2TestRun name="bench" rng-seed="1">
+  <TestCase name="Fibonacci" tags="[!benchmark]" filename="bench.cpp" line="10">
+    <BenchmarkResults name="fib &lt;20&gt;" samples="100" resamples="100000" iterations="1" clockResolution="20.5" estimatedDuration="2542100">
+      <!-- All values in nano seconds -->
+      <mean value="43971.2" lowerBound="43889.1" upperBound="44106.4" ci="0.95"/>
+      <standardDeviation value="513.011" lowerBound="357.546" upperBound="753.636" ci="0.95"/>
+      <outliers variance="0.01" lowMild="0" lowSevere="0" highMild="2" highSevere="0"/>
+    </BenchmarkResults>
+    <BenchmarkResults name="fib 25" samples="100" resamples="100000" iterations="1" clockResolution="20.5" estimatedDuration="4000000">
+      <mean value="487000" lowerBound="486000" upperBound="488500" ci="0.95"/>
+    </BenchmarkResults>
+    <OverallResult success="true"/>
+  </TestCase>
+</Catch2TestRun>"#;
+
+        let results = parse_catch2_output(output);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "fib <20>");
+        assert_eq!(results[0].measure, "latency");
+        assert!((results[0].value - 43971.2).abs() < 0.001);
+        assert_eq!(results[0].lower, Some(43889.1));
+        assert_eq!(results[0].upper, Some(44106.4));
+        assert_eq!(results[1].name, "fib 25");
+    }
+
+    #[test]
+    fn test_parse_catch2_console() {
+        let output = r#"
+-------------------------------------------------------------------------------
+Fibonacci
+-------------------------------------------------------------------------------
+bench.cpp:10
+...............................................................................
+
+benchmark name                       samples       iterations    estimated
+                                     mean          low mean      high mean
+                                     std dev       low std dev   high std dev
+-------------------------------------------------------------------------------
+Fibonacci 20                                   100             1     2.5421 ms
+                                        43.9712 us    43.8891 us    44.1064 us
+                                        513.011 ns    357.546 ns    753.636 ns
+
+Fibonacci 25                                   100             1     48.7 ms
+                                        487.052 us    486.002 us    488.514 us
+                                        6.21 us       4.1 us        9.8 us
+
+===============================================================================
+All tests passed (2 assertions in 1 test case)
+"#;
+
+        let results = parse_catch2_output(output);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "Fibonacci 20");
+        assert!((results[0].value - 43971.2).abs() < 0.01);
+        assert!((results[0].lower.unwrap() - 43889.1).abs() < 0.01);
+        assert_eq!(results[1].name, "Fibonacci 25");
+        assert!((results[1].upper.unwrap() - 488514.0).abs() < 0.01);
+    }
+}
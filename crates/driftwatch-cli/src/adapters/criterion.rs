@@ -1,13 +1,7 @@
 use regex::Regex;
 use std::sync::LazyLock;
 
-#[derive(Debug, Clone)]
-pub struct CriterionResult {
-    pub name: String,
-    pub value: f64,
-    pub lower: Option<f64>,
-    pub upper: Option<f64>,
-}
+use super::BenchmarkResult;
 
 // Matches both single-line and multi-line Criterion output formats:
 // Single line: `benchmark_name            time:   [...]`
@@ -18,26 +12,57 @@ static CRITERION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     ).unwrap()
 });
 
-pub fn parse_criterion_output(output: &str) -> Vec<CriterionResult> {
-    CRITERION_REGEX
-        .captures_iter(output)
-        .filter_map(|cap| {
+// The change estimate printed when a saved baseline exists, either inline or
+// under a `change:` heading when throughput is measured too:
+// `change: [-1.2345% +0.0000% +1.2345%] (p = 0.50 > 0.05)`
+static CHANGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"change:\s*(?:time:\s*)?\[\S+%\s+([+-]?[0-9.]+)%\s+\S+%\]").unwrap()
+});
+
+// `Found 11 outliers among 100 measurements (11.00%)`
+static OUTLIERS_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"Found (\d+) outliers among \d+ measurements").unwrap());
+
+/// Parses Criterion's `time:` lines into `latency` results, along with the
+/// `change:` estimate and outlier count Criterion prints after each one.
+pub fn parse_criterion_output(output: &str) -> Vec<BenchmarkResult> {
+    let captures: Vec<_> = CRITERION_REGEX.captures_iter(output).collect();
+
+    captures
+        .iter()
+        .enumerate()
+        .filter_map(|(i, cap)| {
             let name = cap.get(1)?.as_str().to_string();
             let lower = parse_time(cap.get(2)?.as_str(), cap.get(3)?.as_str())?;
             let mean = parse_time(cap.get(4)?.as_str(), cap.get(5)?.as_str())?;
             let upper = parse_time(cap.get(6)?.as_str(), cap.get(7)?.as_str())?;
 
-            Some(CriterionResult {
+            // Everything up to the next benchmark's name belongs to this one
+            let rest_start = cap.get(0)?.end();
+            let rest_end = captures
+                .get(i + 1)
+                .and_then(|next| next.get(0))
+                .map_or(output.len(), |m| m.start());
+            let rest = &output[rest_start..rest_end];
+
+            Some(BenchmarkResult {
                 name,
+                measure: "latency".to_string(),
                 value: mean,
                 lower: Some(lower),
                 upper: Some(upper),
+                reported_change: CHANGE_REGEX
+                    .captures(rest)
+                    .and_then(|c| c.get(1)?.as_str().parse().ok()),
+                outliers: OUTLIERS_REGEX
+                    .captures(rest)
+                    .and_then(|c| c.get(1)?.as_str().parse().ok()),
             })
         })
         .collect()
 }
 
-fn parse_time(value: &str, unit: &str) -> Option<f64> {
+pub(super) fn parse_time(value: &str, unit: &str) -> Option<f64> {
     let v: f64 = value.parse().ok()?;
     let multiplier = match unit {
         "ns" => 1.0,
@@ -95,6 +120,38 @@ ch_opt_projection_pushdown/100
         assert!((results[1].value - 850120.0).abs() < 1.0); // 850.12 µs = 850120 ns
     }
 
+    #[test]
+    fn test_parse_criterion_change_and_outliers() {
+        let output = r#"
+fibonacci/10            time:   [1.2345 µs 1.2456 µs 1.2567 µs]
+                        change: [+4.1000% +5.2500% +6.3000%] (p = 0.00 < 0.05)
+                        Performance has regressed.
+Found 11 outliers among 100 measurements (11.00%)
+  3 (3.00%) high mild
+  8 (8.00%) high severe
+
+fibonacci/20            time:   [123.45 ns 124.56 ns 125.67 ns]
+                        thrpt:  [7.9573 Melem/s 8.0283 Melem/s 8.1004 Melem/s]
+                 change:
+                        time:   [-2.0000% -1.5000% -1.0000%] (p = 0.01 < 0.05)
+                        thrpt:  [+1.0101% +1.5228% +2.0408%]
+
+fibonacci/30            time:   [10.0 ms 10.1 ms 10.2 ms]
+        "#;
+
+        let results = parse_criterion_output(output);
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].reported_change, Some(5.25));
+        assert_eq!(results[0].outliers, Some(11));
+
+        assert_eq!(results[1].reported_change, Some(-1.5));
+        assert_eq!(results[1].outliers, None);
+
+        assert_eq!(results[2].reported_change, None);
+        assert_eq!(results[2].outliers, None);
+    }
+
     #[test]
     fn test_parse_criterion_output_mixed_formats() {
         // Test a mix of single-line and multi-line formats
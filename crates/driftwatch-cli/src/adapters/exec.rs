@@ -0,0 +1,126 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use super::BenchmarkResult;
+
+/// One metric as printed by an external adapter; the same fields as the
+/// API's `MetricInput`, with `measure` defaulting to `latency`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ExternalMetric {
+    benchmark: String,
+    #[serde(default = "default_measure")]
+    measure: String,
+    value: f64,
+    lower_value: Option<f64>,
+    upper_value: Option<f64>,
+    reported_change: Option<f64>,
+    outliers: Option<i32>,
+}
+
+fn default_measure() -> String {
+    "latency".to_string()
+}
+
+/// Runs `program` through the platform shell with the benchmark output on
+/// stdin. It must exit successfully and print a JSON array of metrics, e.g.
+/// `[{"benchmark": "parse", "measure": "latency", "value": 1520.0}]`.
+pub fn run_exec_adapter(program: &str, output: &str) -> Result<Vec<BenchmarkResult>> {
+    let mut process = if cfg!(target_os = "windows") {
+        let mut process = Command::new("cmd");
+        process.args(["/C", program]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", program]);
+        process
+    };
+
+    let mut child = process
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start adapter {}", program))?;
+
+    // Feed stdin from another thread so a program that writes before it has
+    // read everything can't deadlock against us
+    let mut stdin = child.stdin.take().context("Adapter stdin unavailable")?;
+    let input = output.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let result = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to run adapter {}", program))?;
+    // A program that exits without reading all of its input is fine
+    let _ = writer.join();
+
+    if !result.status.success() {
+        bail!(
+            "Adapter {} failed ({}): {}",
+            program,
+            result.status,
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    parse_metrics_json(&String::from_utf8_lossy(&result.stdout))
+        .with_context(|| format!("Adapter {} printed invalid metric JSON", program))
+}
+
+fn parse_metrics_json(stdout: &str) -> Result<Vec<BenchmarkResult>> {
+    let metrics: Vec<ExternalMetric> = serde_json::from_str(stdout.trim())?;
+    Ok(metrics
+        .into_iter()
+        .map(|m| BenchmarkResult {
+            name: m.benchmark,
+            measure: m.measure,
+            value: m.value,
+            lower: m.lower_value,
+            upper: m.upper_value,
+            reported_change: m.reported_change,
+            outliers: m.outliers,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metrics_json() {
+        let stdout = r#"[
+            {"benchmark": "parse", "value": 1520.0},
+            {"benchmark": "parse", "measure": "throughput", "value": 9.5, "lowerValue": 9.0, "upperValue": 10.0}
+        ]"#;
+
+        let results = parse_metrics_json(stdout).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].measure, "latency");
+        assert_eq!(results[0].lower, None);
+        assert_eq!(results[1].measure, "throughput");
+        assert_eq!(results[1].lower, Some(9.0));
+        assert_eq!(results[1].upper, Some(10.0));
+    }
+
+    #[test]
+    fn test_parse_metrics_json_rejects_unknown_fields() {
+        assert!(parse_metrics_json(r#"[{"benchmark": "a", "vlaue": 1.0}]"#).is_err());
+        assert!(parse_metrics_json("not json").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_exec_adapter_pipes_output() {
+        let program = r#"read line; printf '[{"benchmark": "%s", "value": 1}]' "$line""#;
+        let results = run_exec_adapter(program, "from-stdin\n").unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "from-stdin");
+
+        let err = run_exec_adapter("echo oops >&2; exit 3", "").unwrap_err();
+        assert!(err.to_string().contains("oops"));
+    }
+}
@@ -0,0 +1,144 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::{find_json, percentile_measure, BenchmarkResult};
+
+/// Either summary shape k6 writes: `--summary-export` puts the stats
+/// directly on each metric, `handleSummary` nests them under `values`.
+#[derive(Deserialize)]
+struct Summary {
+    metrics: HashMap<String, Metric>,
+}
+
+#[derive(Deserialize)]
+struct Metric {
+    values: Option<HashMap<String, serde_json::Value>>,
+    #[serde(flatten)]
+    stats: HashMap<String, serde_json::Value>,
+}
+
+impl Metric {
+    fn stat(&self, key: &str) -> Option<f64> {
+        self.values
+            .as_ref()
+            .and_then(|values| values.get(key))
+            .or_else(|| self.stats.get(key))?
+            .as_f64()
+    }
+
+    fn stats(&self) -> &HashMap<String, serde_json::Value> {
+        self.values.as_ref().unwrap_or(&self.stats)
+    }
+}
+
+const MS_TO_NS: f64 = 1_000_000.0;
+
+/// Parses a k6 JSON summary into `throughput` from `http_reqs` and `latency`
+/// (the mean) plus `latency_pNN` from `http_req_duration`.
+pub fn parse_k6_summary(output: &str, name: Option<&str>) -> Vec<BenchmarkResult> {
+    let Some(summary) = find_json::<Summary>(output) else {
+        return Vec::new();
+    };
+    let name = name.unwrap_or("k6");
+
+    let mut results = Vec::new();
+
+    if let Some(rate) = summary
+        .metrics
+        .get("http_reqs")
+        .and_then(|m| m.stat("rate"))
+    {
+        results.push(BenchmarkResult::new(name, "throughput", rate));
+    }
+
+    if let Some(duration) = summary.metrics.get("http_req_duration") {
+        if let Some(avg) = duration.stat("avg") {
+            results.push(BenchmarkResult::new(name, "latency", avg * MS_TO_NS));
+        }
+
+        let mut percentiles: Vec<(String, f64)> = duration
+            .stats()
+            .iter()
+            .filter_map(|(key, value)| {
+                let measure = match key.as_str() {
+                    "med" => "latency_p50".to_string(),
+                    key if key.starts_with("p(") => percentile_measure(key)?,
+                    _ => return None,
+                };
+                Some((measure, value.as_f64()? * MS_TO_NS))
+            })
+            .collect();
+        percentiles.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (measure, value) in percentiles {
+            results.push(BenchmarkResult::new(name, &measure, value));
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_k6_summary_export() {
+        let output = r#"
+          /\      |‾‾| /‾‾/   /‾‾/
+     /\  /  \     |  |/  /   /  /
+{
+  "metrics": {
+    "http_reqs": { "count": 3000, "rate": 99.5 },
+    "http_req_duration": {
+      "avg": 12.5, "min": 2.0, "med": 10.0, "max": 80.0,
+      "p(90)": 20.0, "p(95)": 25.5
+    }
+  }
+}
+        "#;
+
+        let results = parse_k6_summary(output, None);
+        let measures: Vec<_> = results.iter().map(|r| r.measure.as_str()).collect();
+        assert_eq!(
+            measures,
+            vec![
+                "throughput",
+                "latency",
+                "latency_p50",
+                "latency_p90",
+                "latency_p95"
+            ]
+        );
+        assert!(results.iter().all(|r| r.name == "k6"));
+        assert!((results[0].value - 99.5).abs() < 0.001);
+        assert!((results[1].value - 12_500_000.0).abs() < 1.0);
+        assert!((results[4].value - 25_500_000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_parse_k6_handle_summary() {
+        let output = r#"{
+  "root_group": { "name": "" },
+  "metrics": {
+    "http_reqs": { "type": "counter", "values": { "count": 10, "rate": 5.0 } },
+    "http_req_duration": {
+      "type": "trend",
+      "contains": "time",
+      "values": { "avg": 1.5, "med": 1.0, "p(99)": 4.0 }
+    }
+  }
+}"#;
+
+        let results = parse_k6_summary(output, Some("checkout"));
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].name, "checkout");
+        assert_eq!(results[3].measure, "latency_p99");
+        assert!((results[3].value - 4_000_000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_parse_k6_summary_without_json() {
+        assert!(parse_k6_summary("http_reqs......: 10 5/s", None).is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+use serde::Deserialize;
+
+use super::BenchmarkResult;
+
+#[derive(Deserialize)]
+struct Event {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    exec_time: Option<f64>,
+}
+
+const S_TO_NS: f64 = 1_000_000_000.0;
+
+/// Parses libtest's JSON event stream (`--format json --report-time`, also
+/// written by nextest's `--message-format libtest-json`) into a
+/// `test_duration` result per passing test. Failed and ignored tests are
+/// left out so a broken test doesn't look like a speedup.
+pub fn parse_libtest_output(output: &str) -> Vec<BenchmarkResult> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Event>(line.trim()).ok())
+        .filter(|event| event.kind == "test" && event.event == "ok")
+        .filter_map(|event| {
+            Some(BenchmarkResult::new(
+                &event.name?,
+                "test_duration",
+                event.exec_time? * S_TO_NS,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_libtest_output() {
+        let output = r#"
+running 3 tests
+{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::fast" }
+{ "type": "test", "name": "tests::fast", "event": "ok", "exec_time": 0.0015 }
+{ "type": "test", "name": "tests::broken", "event": "failed", "exec_time": 0.2, "stdout": "boom" }
+{ "type": "test", "name": "tests::slow", "event": "ok", "exec_time": 2.5 }
+{ "type": "test", "name": "tests::skipped", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 2, "failed": 1, "ignored": 1, "exec_time": 2.7 }
+        "#;
+
+        let results = parse_libtest_output(output);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "tests::fast");
+        assert_eq!(results[0].measure, "test_duration");
+        assert!((results[0].value - 1_500_000.0).abs() < 1.0);
+        assert_eq!(results[1].name, "tests::slow");
+        assert!((results[1].value - 2_500_000_000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_parse_nextest_libtest_json() {
+        let output = r#"{"type":"test","event":"ok","name":"driftwatch::bin/driftwatch$commands::run::tests::test_parse","exec_time":0.004}
+{"type":"test","event":"ok","name":"driftwatch::bin/driftwatch$adapters::tests::no_time"}"#;
+
+        let results = parse_libtest_output(output);
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].name,
+            "driftwatch::bin/driftwatch$commands::run::tests::test_parse"
+        );
+    }
+}
@@ -1 +1,331 @@
+pub mod benchmarkdotnet;
+pub mod bombardier;
+pub mod catch2;
 pub mod criterion;
+pub mod exec;
+pub mod k6;
+pub mod libtest;
+pub mod wrk;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+
+/// One measured value parsed from benchmark output. Latencies and test
+/// durations are in nanoseconds and throughput in requests per second,
+/// whatever the tool reported them in.
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub name: String,
+    pub measure: String,
+    pub value: f64,
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+    /// Change against its previous run the harness itself reported, in
+    /// percent
+    pub reported_change: Option<f64>,
+    /// Outlying samples the harness flagged
+    pub outliers: Option<i32>,
+}
+
+impl BenchmarkResult {
+    pub(crate) fn new(name: &str, measure: &str, value: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            measure: measure.to_string(),
+            value,
+            lower: None,
+            upper: None,
+            reported_change: None,
+            outliers: None,
+        }
+    }
+}
+
+/// Parsers built into the CLI
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Builtin {
+    /// Criterion's `time: [...]` lines
+    Criterion,
+    /// wrk's summary; pass `--latency` for percentiles
+    Wrk,
+    /// k6's JSON summary from `--summary-export` or `handleSummary`
+    K6,
+    /// bombardier's JSON from `-o json -p result`
+    Bombardier,
+    /// Per-test times from libtest's `--format json --report-time` or
+    /// nextest's `--message-format libtest-json`
+    Libtest,
+    /// BenchmarkDotNet's JSON exporter; print the result files after the run
+    #[value(name = "benchmarkdotnet")]
+    BenchmarkDotNet,
+    /// Catch2 benchmarks from the console or XML (`-r xml`) reporter
+    Catch2,
+}
+
+/// How `driftwatch run` turns command output into results: one of the
+/// built-in parsers by name, or `exec:<program>` to hand the output to an
+/// external program (see [`exec::run_exec_adapter`]).
+#[derive(Debug, Clone)]
+pub enum Adapter {
+    Builtin(Builtin),
+    Exec(String),
+}
+
+impl FromStr for Adapter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(program) = s.strip_prefix("exec:") {
+            if program.trim().is_empty() {
+                return Err("exec: needs a program to run".to_string());
+            }
+            return Ok(Adapter::Exec(program.to_string()));
+        }
+
+        Builtin::from_str(s, true)
+            .map(Adapter::Builtin)
+            .map_err(|_| {
+                let names: Vec<_> = Builtin::value_variants()
+                    .iter()
+                    .filter_map(|b| b.to_possible_value())
+                    .map(|v| v.get_name().to_string())
+                    .collect();
+                format!(
+                    "unknown adapter '{}'; expected one of {} or exec:<program>",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl Adapter {
+    /// Parses `output` into results. Load-test tools report one target per
+    /// run, so `name` names the benchmark; it defaults to the target URL,
+    /// or the tool name when the output doesn't carry one.
+    pub fn parse(&self, output: &str, name: Option<&str>) -> Result<Vec<BenchmarkResult>> {
+        let results = match self {
+            Adapter::Builtin(Builtin::Criterion) => criterion::parse_criterion_output(output),
+            Adapter::Builtin(Builtin::Wrk) => wrk::parse_wrk_output(output, name),
+            Adapter::Builtin(Builtin::K6) => k6::parse_k6_summary(output, name),
+            Adapter::Builtin(Builtin::Bombardier) => {
+                bombardier::parse_bombardier_output(output, name)
+            }
+            Adapter::Builtin(Builtin::Libtest) => libtest::parse_libtest_output(output),
+            Adapter::Builtin(Builtin::BenchmarkDotNet) => {
+                benchmarkdotnet::parse_benchmarkdotnet_output(output)
+            }
+            Adapter::Builtin(Builtin::Catch2) => catch2::parse_catch2_output(output),
+            Adapter::Exec(program) => exec::run_exec_adapter(program, output)?,
+        };
+        Ok(results)
+    }
+
+    /// The benchmark command changed to list the benchmarks it would run
+    /// instead of running them, for harnesses that take libtest's `--list`:
+    /// criterion and libtest. Filters in the command still apply.
+    pub fn list_command(&self, command: &[String]) -> Option<Vec<String>> {
+        if !matches!(
+            self,
+            Adapter::Builtin(Builtin::Criterion | Builtin::Libtest)
+        ) {
+            return None;
+        }
+        let mut list = command.to_vec();
+        // `cargo bench` and `cargo test` pass what follows `--` to the harness
+        if list.first().is_some_and(|program| program == "cargo") && !list.iter().any(|a| a == "--")
+        {
+            list.push("--".to_string());
+        }
+        list.push("--list".to_string());
+        Some(list)
+    }
+
+    /// Benchmark names in the output of [`Adapter::list_command`]: criterion's
+    /// `fib 20: benchmark` lines, or for libtest `tests::parse: test` lines
+    /// or its JSON `discovered` events, which leave out ignored tests. Other
+    /// targets `cargo` lists along the way are skipped.
+    pub fn parse_list(&self, output: &str) -> Vec<String> {
+        #[derive(serde::Deserialize)]
+        struct Discovered {
+            event: String,
+            name: String,
+            #[serde(default)]
+            ignore: bool,
+        }
+
+        let listed_kind = match self {
+            Adapter::Builtin(Builtin::Criterion) => "benchmark",
+            Adapter::Builtin(Builtin::Libtest) => "test",
+            _ => return Vec::new(),
+        };
+        output
+            .lines()
+            .filter_map(|line| {
+                if listed_kind == "test" {
+                    if let Ok(test) = serde_json::from_str::<Discovered>(line.trim()) {
+                        return (test.event == "discovered" && !test.ignore).then_some(test.name);
+                    }
+                }
+                let (name, kind) = line.rsplit_once(": ")?;
+                (kind.trim() == listed_kind).then(|| name.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Every JSON document of type `T` in output, in order, skipping anything
+/// between them.
+fn json_documents<T: DeserializeOwned>(output: &str) -> Vec<T> {
+    let mut documents = Vec::new();
+    let mut rest = output;
+    while let Some(start) = rest.find('{') {
+        let mut stream = serde_json::Deserializer::from_str(&rest[start..]).into_iter::<T>();
+        match stream.next() {
+            Some(Ok(document)) => {
+                documents.push(document);
+                rest = &rest[start + stream.byte_offset()..];
+            }
+            _ => rest = &rest[start + 1..],
+        }
+    }
+    documents
+}
+
+/// Finds the first JSON document of type `T` in output that may have log
+/// lines before or after it.
+fn find_json<T: DeserializeOwned>(output: &str) -> Option<T> {
+    output.match_indices('{').find_map(|(start, _)| {
+        serde_json::Deserializer::from_str(&output[start..])
+            .into_iter::<T>()
+            .next()?
+            .ok()
+    })
+}
+
+/// Converts a percentile key such as "99", "99.0" or "p(99)" into the
+/// `latency_p99` measure name, or `None` for fractional percentiles.
+fn percentile_measure(key: &str) -> Option<String> {
+    let key = key.trim_start_matches("p(").trim_end_matches(')');
+    let percentile: f64 = key.parse().ok()?;
+    if percentile.fract() != 0.0 {
+        return None;
+    }
+    Some(format!("latency_p{}", percentile as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_json_skips_surrounding_output() {
+        let output = "starting {not json}\n{\"a\": 1}\ndone";
+        let value: serde_json::Value = find_json(output).unwrap();
+        assert_eq!(value["a"], 1);
+    }
+
+    #[test]
+    fn test_json_documents_reads_each_document() {
+        let output = "{\"a\": 1}\nlog {\n{\"a\": 2}";
+        let values: Vec<serde_json::Value> = json_documents(output);
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1]["a"], 2);
+    }
+
+    #[test]
+    fn test_adapter_from_str() {
+        assert!(matches!(
+            "wrk".parse::<Adapter>(),
+            Ok(Adapter::Builtin(Builtin::Wrk))
+        ));
+        assert!(matches!(
+            "BenchmarkDotNet".parse::<Adapter>(),
+            Ok(Adapter::Builtin(Builtin::BenchmarkDotNet))
+        ));
+        match "exec:./parse --strict".parse::<Adapter>() {
+            Ok(Adapter::Exec(program)) => assert_eq!(program, "./parse --strict"),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!("exec:".parse::<Adapter>().is_err());
+        let err = "gbench".parse::<Adapter>().unwrap_err();
+        assert!(err.contains("criterion, wrk"));
+    }
+
+    #[test]
+    fn test_list_command() {
+        let list = |adapter: &str, command: &[&str]| {
+            let command: Vec<String> = command.iter().map(|s| s.to_string()).collect();
+            adapter.parse::<Adapter>().unwrap().list_command(&command)
+        };
+        assert_eq!(
+            list("criterion", &["cargo", "bench"]).unwrap(),
+            ["cargo", "bench", "--", "--list"]
+        );
+        assert_eq!(
+            list(
+                "libtest",
+                &[
+                    "cargo",
+                    "test",
+                    "--",
+                    "-Zunstable-options",
+                    "--format",
+                    "json"
+                ]
+            )
+            .unwrap(),
+            [
+                "cargo",
+                "test",
+                "--",
+                "-Zunstable-options",
+                "--format",
+                "json",
+                "--list"
+            ]
+        );
+        assert_eq!(
+            list("criterion", &["./target/release/bench", "parse"]).unwrap(),
+            ["./target/release/bench", "parse", "--list"]
+        );
+        assert_eq!(list("wrk", &["wrk", "http://localhost"]), None);
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let parse =
+            |adapter: &str, output: &str| adapter.parse::<Adapter>().unwrap().parse_list(output);
+        let output = "\
+tests::roundtrip: test
+
+1 test, 0 benchmarks
+fib 20: benchmark
+parse/json/large: benchmark
+
+2 benchmarks
+";
+        assert_eq!(parse("criterion", output), ["fib 20", "parse/json/large"]);
+        assert_eq!(parse("libtest", output), ["tests::roundtrip"]);
+
+        let output = r#"
+{ "type": "suite", "event": "discovery" }
+{ "type": "test", "event": "discovered", "name": "tests::slow", "ignore": true }
+{ "type": "test", "event": "discovered", "name": "tests::fast", "ignore": false }
+{ "type": "suite", "event": "completed", "tests": 2 }
+"#;
+        assert_eq!(parse("libtest", output), ["tests::fast"]);
+        assert!(parse("wrk", output).is_empty());
+    }
+
+    #[test]
+    fn test_percentile_measure() {
+        assert_eq!(percentile_measure("99").as_deref(), Some("latency_p99"));
+        assert_eq!(percentile_measure("p(95)").as_deref(), Some("latency_p95"));
+        assert_eq!(percentile_measure("50.0").as_deref(), Some("latency_p50"));
+        assert_eq!(percentile_measure("99.9"), None);
+        assert_eq!(percentile_measure("med"), None);
+    }
+}
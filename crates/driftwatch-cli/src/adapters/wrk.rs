@@ -0,0 +1,119 @@
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::criterion::parse_time;
+use super::{percentile_measure, BenchmarkResult};
+
+// `Running 30s test @ http://127.0.0.1:8080/index.html`
+static TARGET_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^Running .* @ (\S+)").unwrap());
+
+// Thread stats row: `    Latency   635.91us    0.89ms  12.92ms   93.69%`
+static MEAN_LATENCY_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*Latency\s+([0-9.]+)(ns|us|ms|s)\s").unwrap());
+
+// Rows under `Latency Distribution`, printed with `--latency`: `     99%    5.80ms`
+static PERCENTILE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*([0-9.]+)%\s+([0-9.]+)(ns|us|ms|s)\s*$").unwrap());
+
+static RPS_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^Requests/sec:\s+([0-9.]+)").unwrap());
+
+/// Parses wrk's text summary into `throughput`, `latency` (the mean) and
+/// `latency_pNN` results for the single target of the run.
+pub fn parse_wrk_output(output: &str, name: Option<&str>) -> Vec<BenchmarkResult> {
+    let name = name
+        .map(str::to_string)
+        .or_else(|| Some(TARGET_REGEX.captures(output)?.get(1)?.as_str().to_string()))
+        .unwrap_or_else(|| "wrk".to_string());
+
+    let mut results = Vec::new();
+
+    if let Some(rps) = RPS_REGEX
+        .captures(output)
+        .and_then(|cap| cap.get(1)?.as_str().parse().ok())
+    {
+        results.push(BenchmarkResult::new(&name, "throughput", rps));
+    }
+
+    if let Some(mean) = MEAN_LATENCY_REGEX
+        .captures(output)
+        .and_then(|cap| parse_time(cap.get(1)?.as_str(), cap.get(2)?.as_str()))
+    {
+        results.push(BenchmarkResult::new(&name, "latency", mean));
+    }
+
+    for cap in PERCENTILE_REGEX.captures_iter(output) {
+        let (Some(measure), Some(value)) =
+            (percentile_measure(&cap[1]), parse_time(&cap[2], &cap[3]))
+        else {
+            continue;
+        };
+        results.push(BenchmarkResult::new(&name, &measure, value));
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wrk_output_with_latency_distribution() {
+        let output = r#"
+Running 30s test @ http://127.0.0.1:8080/index.html
+  12 threads and 400 connections
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   635.91us    0.89ms  12.92ms   93.69%
+    Req/Sec    56.20k     8.07k   62.00k    86.54%
+  Latency Distribution
+     50%  250.00us
+     75%  491.00us
+     90%  700.00us
+     99%    5.80ms
+  22464657 requests in 30.00s, 17.76GB read
+Requests/sec: 748868.53
+Transfer/sec:    606.33MB
+        "#;
+
+        let results = parse_wrk_output(output, None);
+        let find = |measure: &str| {
+            results
+                .iter()
+                .find(|r| r.measure == measure)
+                .map(|r| r.value)
+                .unwrap()
+        };
+
+        assert_eq!(results.len(), 6);
+        assert!(results
+            .iter()
+            .all(|r| r.name == "http://127.0.0.1:8080/index.html"));
+        assert!((find("throughput") - 748868.53).abs() < 0.01);
+        assert!((find("latency") - 635_910.0).abs() < 1.0);
+        assert!((find("latency_p50") - 250_000.0).abs() < 1.0);
+        assert!((find("latency_p99") - 5_800_000.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_parse_wrk_output_without_distribution() {
+        let output = r#"
+Running 10s test @ http://localhost:3000
+  2 threads and 10 connections
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency     1.20ms  300.00us   9.00ms   90.00%
+    Req/Sec     4.10k   200.00     4.50k    70.00%
+  81234 requests in 10.00s, 9.80MB read
+Requests/sec:   8123.40
+Transfer/sec:      0.98MB
+        "#;
+
+        let results = parse_wrk_output(output, Some("homepage"));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "homepage");
+        assert_eq!(results[0].measure, "throughput");
+        assert_eq!(results[1].measure, "latency");
+        assert!((results[1].value - 1_200_000.0).abs() < 1.0);
+    }
+}
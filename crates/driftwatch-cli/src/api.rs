@@ -1,7 +1,15 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use driftwatch_api::links;
+use driftwatch_sdk::{HttpTransport, Notify};
+
+pub use driftwatch_sdk::types::*;
+pub use driftwatch_sdk::{Client as ApiClient, MAX_CONCURRENT_REQUESTS};
 
 pub const DEFAULT_API_URL: &str = "https://driftwatch.dev";
 pub const DEFAULT_GRPC_URL: &str = "http://localhost:50051";
@@ -13,6 +21,12 @@ pub struct Config {
     pub api_url: String,
     #[serde(default = "default_grpc_url")]
     pub grpc_url: String,
+    /// Web UI that printed links point to; guessed from the API URL when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_url: Option<String>,
+    /// Ingestion relay that `run` and `backfill` submit reports through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relay_url: Option<String>,
 }
 
 fn default_api_url() -> String {
@@ -30,15 +44,24 @@ impl Config {
         let grpc_url =
             std::env::var("DRIFTWATCH_GRPC_URL").unwrap_or_else(|_| DEFAULT_GRPC_URL.to_string());
 
+        let web_url = std::env::var("DRIFTWATCH_WEB_URL")
+            .ok()
+            .filter(|url| !url.is_empty());
+        let relay_url = std::env::var("DRIFTWATCH_RELAY_URL")
+            .ok()
+            .filter(|url| !url.is_empty());
+
         if let Ok(token) = std::env::var("DRIFTWATCH_TOKEN") {
             return Ok(Config {
                 token,
                 api_url,
                 grpc_url,
+                web_url,
+                relay_url,
             });
         }
 
-        let config_path = get_config_path()?;
+        let config_path = config_path()?;
         let config_str = fs::read_to_string(&config_path)
             .context("Not authenticated. Run 'driftwatch auth login' first.")?;
         let mut config: Config = toml::from_str(&config_str).context("Invalid config file")?;
@@ -49,425 +72,72 @@ impl Config {
         if std::env::var("DRIFTWATCH_GRPC_URL").is_ok() {
             config.grpc_url = grpc_url;
         }
-
-        Ok(config)
-    }
-}
-
-fn get_config_path() -> Result<PathBuf> {
-    let config_dir = dirs::config_dir()
-        .context("Could not determine config directory")?
-        .join("driftwatch");
-    Ok(config_dir.join("config.toml"))
-}
-
-pub struct ApiClient {
-    client: reqwest::Client,
-    base_url: String,
-    token: String,
-}
-
-impl ApiClient {
-    pub fn new(base_url: &str, token: &str) -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            base_url: base_url.trim_end_matches('/').to_string(),
-            token: token.to_string(),
-        }
-    }
-
-    async fn graphql<T: for<'de> Deserialize<'de>>(
-        &self,
-        query: &str,
-        variables: serde_json::Value,
-    ) -> Result<T> {
-        let response = self
-            .client
-            .post(format!("{}/graphql", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "query": query,
-                "variables": variables
-            }))
-            .send()
-            .await
-            .context("Failed to send request")?;
-
-        let status = response.status();
-        let body: GraphQLResponse<T> = response.json().await.context("Failed to parse response")?;
-
-        if let Some(errors) = body.errors {
-            if !errors.is_empty() {
-                return Err(anyhow::anyhow!("GraphQL error: {}", errors[0].message));
-            }
-        }
-
-        body.data
-            .ok_or_else(|| anyhow::anyhow!("No data in response (status: {})", status))
-    }
-
-    pub async fn list_projects(&self) -> Result<Vec<Project>> {
-        let query = r#"
-            query {
-                projects {
-                    id
-                    slug
-                    name
-                    description
-                    public
-                }
-            }
-        "#;
-
-        #[derive(Deserialize)]
-        struct Response {
-            projects: Vec<Project>,
+        if web_url.is_some() {
+            config.web_url = web_url;
         }
-
-        let response: Response = self.graphql(query, serde_json::json!({})).await?;
-        Ok(response.projects)
-    }
-
-    pub async fn get_project(&self, slug: &str) -> Result<Option<ProjectDetails>> {
-        let query = r#"
-            query GetProject($slug: String!) {
-                project(slug: $slug) {
-                    id
-                    slug
-                    name
-                    description
-                    public
-                    branches { id name }
-                    testbeds { id name }
-                    benchmarks { id name }
-                    measures { id name units }
-                }
-            }
-        "#;
-
-        #[derive(Deserialize)]
-        struct Response {
-            project: Option<ProjectDetails>,
+        if relay_url.is_some() {
+            config.relay_url = relay_url;
         }
 
-        let response: Response = self
-            .graphql(query, serde_json::json!({ "slug": slug }))
-            .await?;
-        Ok(response.project)
-    }
-
-    pub async fn create_project(
-        &self,
-        slug: &str,
-        name: &str,
-        description: Option<&str>,
-        public: bool,
-    ) -> Result<Project> {
-        let query = r#"
-            mutation CreateProject($input: CreateProjectInput!) {
-                createProject(input: $input) {
-                    id
-                    slug
-                    name
-                    description
-                    public
-                }
-            }
-        "#;
-
-        #[derive(Deserialize)]
-        struct Response {
-            #[serde(rename = "createProject")]
-            create_project: Project,
-        }
-
-        let response: Response = self
-            .graphql(
-                query,
-                serde_json::json!({
-                    "input": {
-                        "slug": slug,
-                        "name": name,
-                        "description": description,
-                        "public": public
-                    }
-                }),
-            )
-            .await?;
-        Ok(response.create_project)
-    }
-
-    pub async fn create_report(
-        &self,
-        project_slug: &str,
-        branch: &str,
-        testbed: &str,
-        git_hash: Option<&str>,
-        pr_number: Option<i32>,
-        metrics: Vec<MetricInput>,
-    ) -> Result<Report> {
-        let query = r#"
-            mutation CreateReport($input: CreateReportInput!) {
-                createReport(input: $input) {
-                    id
-                    gitHash
-                    alerts {
-                        id
-                        baselineValue
-                        percentChange
-                    }
-                }
-            }
-        "#;
-
-        #[derive(Deserialize)]
-        struct Response {
-            #[serde(rename = "createReport")]
-            create_report: Report,
-        }
-
-        let response: Response = self
-            .graphql(
-                query,
-                serde_json::json!({
-                    "input": {
-                        "projectSlug": project_slug,
-                        "branch": branch,
-                        "testbed": testbed,
-                        "gitHash": git_hash,
-                        "prNumber": pr_number,
-                        "metrics": metrics
-                    }
-                }),
-            )
-            .await?;
-        Ok(response.create_report)
+        Ok(config)
     }
 
-    pub async fn get_flamegraph_upload_url(
-        &self,
-        project_slug: &str,
-        file_name: &str,
-    ) -> Result<FlamegraphUploadUrl> {
-        let query = r#"
-            mutation CreateFlamegraphUploadUrl($projectSlug: String!, $fileName: String!) {
-                createFlamegraphUploadUrl(projectSlug: $projectSlug, fileName: $fileName) {
-                    signedUrl
-                    token
-                    storagePath
-                }
-            }
-        "#;
-
-        #[derive(Deserialize)]
-        struct Response {
-            #[serde(rename = "createFlamegraphUploadUrl")]
-            create_flamegraph_upload_url: FlamegraphUploadUrl,
+    /// Settings for talking to the API without an account, e.g. for
+    /// `run --share` before logging in
+    pub fn anonymous() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        Config {
+            token: String::new(),
+            api_url: var("DRIFTWATCH_API_URL").unwrap_or_else(default_api_url),
+            grpc_url: var("DRIFTWATCH_GRPC_URL").unwrap_or_else(default_grpc_url),
+            web_url: var("DRIFTWATCH_WEB_URL"),
+            relay_url: None,
         }
-
-        let response: Response = self
-            .graphql(
-                query,
-                serde_json::json!({
-                    "projectSlug": project_slug,
-                    "fileName": file_name
-                }),
-            )
-            .await?;
-        Ok(response.create_flamegraph_upload_url)
     }
 
-    pub async fn upload_flamegraph_file(&self, signed_url: &str, file_path: &Path) -> Result<()> {
-        let file_content = fs::read(file_path).context("Failed to read flamegraph file")?;
-
-        let response = self
-            .client
-            .put(signed_url)
-            .header("Content-Type", "image/svg+xml")
-            .body(file_content)
-            .send()
-            .await
-            .context("Failed to upload flamegraph")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!(
-                "Failed to upload flamegraph: {} - {}",
-                status,
-                body
-            ));
-        }
-
-        Ok(())
-    }
-
-    pub async fn confirm_flamegraph_upload(
-        &self,
-        report_id: &str,
-        storage_path: &str,
-        file_name: &str,
-        file_size: i64,
-        benchmark_name: Option<&str>,
-    ) -> Result<Flamegraph> {
-        let query = r#"
-            mutation ConfirmFlamegraphUpload(
-                $reportId: ID!,
-                $storagePath: String!,
-                $fileName: String!,
-                $fileSize: Int!,
-                $benchmarkName: String
-            ) {
-                confirmFlamegraphUpload(
-                    reportId: $reportId,
-                    storagePath: $storagePath,
-                    fileName: $fileName,
-                    fileSize: $fileSize,
-                    benchmarkName: $benchmarkName
-                ) {
-                    id
-                    storagePath
-                    fileName
-                    fileSize
-                }
-            }
-        "#;
-
-        #[derive(Deserialize)]
-        struct Response {
-            #[serde(rename = "confirmFlamegraphUpload")]
-            confirm_flamegraph_upload: Flamegraph,
-        }
-
-        let response: Response = self
-            .graphql(
-                query,
-                serde_json::json!({
-                    "reportId": report_id,
-                    "storagePath": storage_path,
-                    "fileName": file_name,
-                    "fileSize": file_size,
-                    "benchmarkName": benchmark_name
-                }),
-            )
-            .await?;
-        Ok(response.confirm_flamegraph_upload)
+    /// Where links to reports and alerts point, for a client talking to
+    /// `api_url`
+    pub fn web_url(&self, api_url: &str) -> String {
+        self.web_url
+            .clone()
+            .unwrap_or_else(|| links::web_url_from_api(api_url))
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct GraphQLResponse<T> {
-    data: Option<T>,
-    errors: Option<Vec<GraphQLError>>,
-}
-
-#[derive(Debug, Deserialize)]
-struct GraphQLError {
-    message: String,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Project {
-    pub id: String,
-    pub slug: String,
-    pub name: String,
-    pub description: Option<String>,
-    pub public: bool,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct ProjectDetails {
-    pub id: String,
-    pub slug: String,
-    pub name: String,
-    pub description: Option<String>,
-    pub public: bool,
-    pub branches: Vec<Branch>,
-    pub testbeds: Vec<Testbed>,
-    pub benchmarks: Vec<Benchmark>,
-    pub measures: Vec<Measure>,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Branch {
-    pub id: String,
-    pub name: String,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Testbed {
-    pub id: String,
-    pub name: String,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Benchmark {
-    pub id: String,
-    pub name: String,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Measure {
-    pub id: String,
-    pub name: String,
-    pub units: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct MetricInput {
-    pub benchmark: String,
-    pub measure: String,
-    pub value: f64,
-    #[serde(rename = "lowerValue")]
-    pub lower_value: Option<f64>,
-    #[serde(rename = "upperValue")]
-    pub upper_value: Option<f64>,
+/// Where `driftwatch auth login` and `config set` save the config
+pub fn config_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .context("Could not determine config directory")?
+        .join("driftwatch");
+    Ok(config_dir.join("config.toml"))
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Report {
-    pub id: String,
-    #[serde(rename = "gitHash")]
-    pub git_hash: Option<String>,
-    pub alerts: Vec<Alert>,
-}
+/// Set by `--strict`: check the server's API version before the first
+/// request and refuse to talk to an incompatible one, instead of warning
+static STRICT: AtomicBool = AtomicBool::new(false);
 
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Alert {
-    pub id: String,
-    #[serde(rename = "baselineValue")]
-    pub baseline_value: f64,
-    #[serde(rename = "percentChange")]
-    pub percent_change: f64,
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct FlamegraphUploadUrl {
-    #[serde(rename = "signedUrl")]
-    pub signed_url: String,
-    pub token: String,
-    #[serde(rename = "storagePath")]
-    pub storage_path: String,
+/// A client for the API at `api_url`, printing retries and version
+/// warnings to stderr and honoring `--strict`
+pub fn connect(api_url: &str, token: &str) -> ApiClient {
+    let notify: Notify = Arc::new(|message| eprintln!("{}", message));
+    let transport = HttpTransport::new(api_url, token)
+        .with_client_name(concat!("driftwatch-cli/", env!("CARGO_PKG_VERSION")))
+        .with_notify(notify.clone());
+    ApiClient::with_transport(Arc::new(transport))
+        .with_notify(notify)
+        .strict(STRICT.load(Ordering::Relaxed))
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Deserialize)]
-pub struct Flamegraph {
-    pub id: String,
-    #[serde(rename = "storagePath")]
-    pub storage_path: String,
-    #[serde(rename = "fileName")]
-    pub file_name: String,
-    #[serde(rename = "fileSize")]
-    pub file_size: i64,
+/// Like [`connect`], but submitting reports through the configured relay
+/// when there is one
+pub fn connect_submitter(api_url: &str, config: &Config) -> ApiClient {
+    let client = connect(api_url, &config.token);
+    match &config.relay_url {
+        Some(relay) => client.with_relay(relay, &config.token),
+        None => client,
+    }
 }
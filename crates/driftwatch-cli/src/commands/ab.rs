@@ -0,0 +1,294 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use std::collections::BTreeMap;
+
+use crate::adapters::Adapter;
+use crate::api::{connect, Config, ExperimentResultInput, RecordExperimentInput};
+use crate::commands::run::execute_shell;
+use crate::git;
+use crate::stats::{mean, welch_t_test};
+use crate::testbed;
+
+#[derive(Args)]
+pub struct AbArgs {
+    /// Command that benchmarks the current behaviour
+    #[arg(long)]
+    pub baseline: String,
+
+    /// Command that benchmarks the change being evaluated
+    #[arg(long)]
+    pub candidate: String,
+
+    /// Times to run each command; they alternate so drift on the machine
+    /// affects both alike
+    #[arg(long, default_value = "5", value_parser = clap::value_parser!(u32).range(2..))]
+    pub rounds: u32,
+
+    /// Significance level of the t-test
+    #[arg(long, default_value = "0.05")]
+    pub alpha: f64,
+
+    /// Smallest change, in percent, worth calling an improvement or
+    /// regression even when significant
+    #[arg(long, default_value = "1.0")]
+    pub min_change: f64,
+
+    /// Tool whose output to parse, or exec:<program> to run an external
+    /// parser that prints metric JSON
+    #[arg(long, default_value = "criterion")]
+    pub adapter: Adapter,
+
+    /// Benchmark name for load-test results (defaults to the target URL)
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Record the experiment in this project
+    #[arg(long, short)]
+    pub project: Option<String>,
+
+    /// Label for the recorded experiment
+    #[arg(long)]
+    pub label: Option<String>,
+
+    #[arg(long, short)]
+    pub testbed: Option<String>,
+
+    /// Exit with an error if the candidate regressed any benchmark
+    #[arg(long)]
+    pub err: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Improved,
+    Regressed,
+    Unchanged,
+    Inconclusive,
+}
+
+impl Verdict {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Improved => "improved",
+            Verdict::Regressed => "regressed",
+            Verdict::Unchanged => "unchanged",
+            Verdict::Inconclusive => "inconclusive",
+        }
+    }
+}
+
+/// Decides a benchmark's verdict. Higher values count as regressions, as
+/// they do for alerts.
+pub fn verdict(
+    baseline: &[f64],
+    candidate: &[f64],
+    alpha: f64,
+    min_change: f64,
+) -> (Option<f64>, Verdict) {
+    let Some(p) = welch_t_test(baseline, candidate) else {
+        return (None, Verdict::Inconclusive);
+    };
+
+    let before = mean(baseline);
+    let change = if before == 0.0 {
+        0.0
+    } else {
+        (mean(candidate) - before) / before.abs() * 100.0
+    };
+
+    let verdict = if p >= alpha || change.abs() < min_change {
+        Verdict::Unchanged
+    } else if change > 0.0 {
+        Verdict::Regressed
+    } else {
+        Verdict::Improved
+    };
+    (Some(p), verdict)
+}
+
+/// Runs a command once and adds its results to `samples`
+fn collect(
+    args: &AbArgs,
+    command: &str,
+    label: &str,
+    samples: &mut BTreeMap<(String, String), Vec<f64>>,
+) -> Result<()> {
+    let output = execute_shell(command)?;
+    let combined_output = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let results = args.adapter.parse(&combined_output, args.name.as_deref())?;
+    if results.is_empty() {
+        bail!(
+            "The {} command produced no benchmark results. Make sure --adapter matches the tool it runs.",
+            label
+        );
+    }
+
+    for result in results {
+        samples
+            .entry((result.name, result.measure))
+            .or_default()
+            .push(result.value);
+    }
+    Ok(())
+}
+
+pub async fn handle(args: AbArgs, api_url: &str) -> Result<()> {
+    if !(args.alpha > 0.0 && args.alpha < 1.0) {
+        bail!("--alpha must be between 0 and 1");
+    }
+
+    let mut baseline = BTreeMap::new();
+    let mut candidate = BTreeMap::new();
+
+    for round in 1..=args.rounds {
+        println!("Round {}/{}...", round, args.rounds);
+        // Swap the order every round so neither command always runs on a
+        // warmer machine
+        if round % 2 == 1 {
+            collect(&args, &args.baseline, "baseline", &mut baseline)?;
+            collect(&args, &args.candidate, "candidate", &mut candidate)?;
+        } else {
+            collect(&args, &args.candidate, "candidate", &mut candidate)?;
+            collect(&args, &args.baseline, "baseline", &mut baseline)?;
+        }
+    }
+    println!();
+
+    let only_baseline = baseline
+        .keys()
+        .filter(|k| !candidate.contains_key(*k))
+        .count();
+    let only_candidate = candidate
+        .keys()
+        .filter(|k| !baseline.contains_key(*k))
+        .count();
+
+    let mut results = Vec::new();
+    println!(
+        "{:<40} {:>14} {:>14} {:>9} {:>8}  VERDICT",
+        "BENCHMARK", "BASELINE", "CANDIDATE", "CHANGE", "P"
+    );
+    println!("{}", "-".repeat(100));
+
+    for ((benchmark, measure), before) in &baseline {
+        let Some(after) = candidate.get(&(benchmark.clone(), measure.clone())) else {
+            continue;
+        };
+        let (p_value, verdict) = verdict(before, after, args.alpha, args.min_change);
+        let (before_mean, after_mean) = (mean(before), mean(after));
+        let change = if before_mean == 0.0 {
+            "-".to_string()
+        } else {
+            format!(
+                "{:+.1}%",
+                (after_mean - before_mean) / before_mean.abs() * 100.0
+            )
+        };
+
+        let name = if measure == "latency" {
+            benchmark.clone()
+        } else {
+            format!("{} ({})", benchmark, measure)
+        };
+        println!(
+            "{:<40} {:>14.2} {:>14.2} {:>9} {:>8}  {}",
+            name,
+            before_mean,
+            after_mean,
+            change,
+            p_value
+                .map(|p| format!("{:.3}", p))
+                .unwrap_or_else(|| "-".to_string()),
+            verdict.as_str()
+        );
+
+        results.push(ExperimentResultInput {
+            benchmark: benchmark.clone(),
+            measure: measure.clone(),
+            baseline_mean: before_mean,
+            candidate_mean: after_mean,
+            p_value,
+            verdict: verdict.as_str().to_uppercase(),
+        });
+    }
+
+    if results.is_empty() {
+        bail!("The baseline and candidate share no benchmarks to compare");
+    }
+
+    let count = |v: Verdict| {
+        results
+            .iter()
+            .filter(|r| r.verdict == v.as_str().to_uppercase())
+            .count()
+    };
+    let regressed = count(Verdict::Regressed);
+    println!();
+    println!(
+        "Candidate: {} regressed, {} improved, {} unchanged, {} inconclusive (alpha {}, min change {}%)",
+        regressed,
+        count(Verdict::Improved),
+        count(Verdict::Unchanged),
+        count(Verdict::Inconclusive),
+        args.alpha,
+        args.min_change
+    );
+    if only_baseline + only_candidate > 0 {
+        println!(
+            "Skipped {} benchmark(s) only in the baseline and {} only in the candidate.",
+            only_baseline, only_candidate
+        );
+    }
+
+    if let Some(project) = &args.project {
+        let config = Config::load()?;
+        let client = connect(api_url, &config.token);
+
+        let experiment = client
+            .record_experiment(RecordExperimentInput {
+                project_slug: project.clone(),
+                name: args.label.clone(),
+                baseline_command: args.baseline.clone(),
+                candidate_command: args.candidate.clone(),
+                git_hash: git::git(&["rev-parse", "HEAD"], None).ok(),
+                testbed: Some(args.testbed.clone().unwrap_or_else(testbed::default_name)),
+                rounds: args.rounds as i32,
+                alpha: args.alpha,
+                results,
+            })
+            .await?;
+        println!("\nExperiment recorded: {}", experiment.id);
+    }
+
+    if args.err && regressed > 0 {
+        bail!("Candidate regressed {} benchmark(s)", regressed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verdict() {
+        let stable = [100.0, 101.0, 99.0, 100.5, 99.5];
+        let slower = [120.0, 121.0, 119.0, 120.5, 119.5];
+        let faster = [80.0, 81.0, 79.0, 80.5, 79.5];
+
+        assert_eq!(verdict(&stable, &slower, 0.05, 1.0).1, Verdict::Regressed);
+        assert_eq!(verdict(&stable, &faster, 0.05, 1.0).1, Verdict::Improved);
+        assert_eq!(verdict(&stable, &stable, 0.05, 1.0).1, Verdict::Unchanged);
+        // Significant but smaller than the minimum change
+        assert_eq!(verdict(&stable, &slower, 0.05, 50.0).1, Verdict::Unchanged);
+        assert_eq!(
+            verdict(&[100.0], &[120.0], 0.05, 1.0),
+            (None, Verdict::Inconclusive)
+        );
+    }
+}
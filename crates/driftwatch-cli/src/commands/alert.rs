@@ -0,0 +1,159 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+
+use crate::api::{connect, AlertDetails, AlertStatus, ApiClient, Config, UpdateAlertInput};
+
+#[derive(Subcommand)]
+pub enum AlertCommands {
+    /// List a project's alerts, newest first
+    List {
+        #[arg(long, short)]
+        project: String,
+        #[arg(long, value_enum)]
+        status: Option<AlertStatus>,
+    },
+    /// Change an alert's triage state, assignee or resolution note
+    Update {
+        id: String,
+        #[arg(long, value_enum)]
+        status: Option<AlertStatus>,
+        /// GitHub handle of whoever is looking into it; "" unassigns
+        #[arg(long)]
+        assignee: Option<String>,
+        /// Why the alert was resolved or dismissed; "" clears it
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Show how the alert's threshold was checked: baseline, change,
+    /// boundaries and verdict, including any re-evaluations
+    Explain { id: String },
+    /// Create an Atom feed of a project's alerts for feed readers and other
+    /// tools that can't receive webhooks. Running it again replaces the URL.
+    Feed {
+        #[arg(long, short)]
+        project: String,
+        /// Turn the feed off instead
+        #[arg(long)]
+        delete: bool,
+    },
+}
+
+pub async fn handle(command: AlertCommands, api_url: &str) -> Result<()> {
+    let config = Config::load()?;
+    let client = connect(api_url, &config.token);
+
+    match command {
+        AlertCommands::List { project, status } => list(&client, &project, status).await,
+        AlertCommands::Update {
+            id,
+            status,
+            assignee,
+            note,
+        } => {
+            let input = UpdateAlertInput {
+                status,
+                assignee,
+                resolution_note: note,
+            };
+            update(&client, &id, &input).await
+        }
+        AlertCommands::Explain { id } => explain(&client, &id).await,
+        AlertCommands::Feed { project, delete } if delete => {
+            if client.delete_alert_feed(&project).await? {
+                println!("Deleted the alert feed of {}", project);
+            } else {
+                println!("{} has no alert feed", project);
+            }
+            Ok(())
+        }
+        AlertCommands::Feed { project, .. } => {
+            let path = client.create_alert_feed_url(&project).await?;
+            println!("Alert feed for {}:", project);
+            println!("  {}{}", api_url.trim_end_matches('/'), path);
+            println!("Anyone with this URL can read the project's alerts; keep it private.");
+            Ok(())
+        }
+    }
+}
+
+async fn explain(client: &ApiClient, id: &str) -> Result<()> {
+    let Some(alert) = client.alert_explanation(id).await? else {
+        bail!("Alert not found: {}", id);
+    };
+
+    println!("Alert {} ({})", alert.id, alert.status);
+    println!("  Benchmark: {}", alert.metric.benchmark.name);
+    println!("  Change: {:+.2}%", alert.percent_change);
+    println!();
+
+    if alert.evaluations.is_empty() {
+        println!("No evaluation was recorded for this alert; it predates evaluation history.");
+        return Ok(());
+    }
+    for evaluation in &alert.evaluations {
+        println!(
+            "{}  {:<20} report {}",
+            evaluation.created_at, evaluation.verdict, evaluation.report_id
+        );
+        println!("  {}", evaluation.explanation);
+    }
+    Ok(())
+}
+
+async fn list(client: &ApiClient, project: &str, status: Option<AlertStatus>) -> Result<()> {
+    let Some(alerts) = client.list_alerts(project, status).await? else {
+        println!("Project not found: {}", project);
+        return Ok(());
+    };
+
+    if alerts.is_empty() {
+        println!("No alerts found.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<36} {:<14} {:>9} {:<16} BENCHMARK",
+        "ID", "STATUS", "CHANGE", "ASSIGNEE"
+    );
+    println!("{}", "-".repeat(100));
+
+    for alert in alerts {
+        println!(
+            "{:<36} {:<14} {:>+8.1}% {:<16} {}",
+            alert.id,
+            alert.status,
+            alert.percent_change,
+            alert.assignee.as_deref().unwrap_or("-"),
+            alert.metric.benchmark.name
+        );
+    }
+
+    Ok(())
+}
+
+async fn update(client: &ApiClient, id: &str, input: &UpdateAlertInput) -> Result<()> {
+    if input.status.is_none() && input.assignee.is_none() && input.resolution_note.is_none() {
+        bail!("Nothing to update; pass --status, --assignee or --note");
+    }
+
+    let alert = client.update_alert(id, input).await?;
+    print_alert(&alert);
+    Ok(())
+}
+
+fn print_alert(alert: &AlertDetails) {
+    println!("Alert: {}", alert.id);
+    println!("  Benchmark: {}", alert.metric.benchmark.name);
+    println!("  Status: {}", alert.status);
+    println!(
+        "  Change: {:+.1}% ({:.2} -> {:.2})",
+        alert.percent_change, alert.baseline_value, alert.current_value
+    );
+    println!("  Raised: {}", alert.created_at);
+    if let Some(ref assignee) = alert.assignee {
+        println!("  Assignee: {}", assignee);
+    }
+    if let Some(ref note) = alert.resolution_note {
+        println!("  Note: {}", note);
+    }
+}
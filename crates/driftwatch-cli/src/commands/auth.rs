@@ -88,6 +88,8 @@ async fn login(token: Option<String>, api_url: &str, grpc_url: &str) -> Result<(
         token,
         api_url: api_url.to_string(),
         grpc_url: grpc_url.to_string(),
+        web_url: None,
+        relay_url: None,
     };
     let config_path = get_config_path()?;
 
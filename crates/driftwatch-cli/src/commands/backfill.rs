@@ -0,0 +1,342 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::adapters::Adapter;
+use crate::api::{connect_submitter, Config, CreateReportInput};
+use crate::commands::run::{execute_command, shell_wrapped, to_metric_inputs};
+use crate::git::{commit_info, git};
+use crate::testbed;
+
+#[derive(Args)]
+pub struct BackfillArgs {
+    #[arg(long, short)]
+    pub project: String,
+
+    #[arg(long, short, default_value = "main")]
+    pub branch: String,
+
+    /// Machine the benchmarks run on (defaults to the OS and architecture,
+    /// e.g. linux-x86_64)
+    #[arg(long, short)]
+    pub testbed: Option<String>,
+
+    /// Oldest commit to benchmark (tag, branch or hash)
+    #[arg(long)]
+    pub from: String,
+
+    /// Newest commit to benchmark
+    #[arg(long, default_value = "HEAD")]
+    pub to: String,
+
+    /// Benchmark every Nth first-parent commit in the range
+    #[arg(long, default_value = "1")]
+    pub every: usize,
+
+    /// Discard recorded progress and benchmark the whole range again
+    #[arg(long)]
+    pub restart: bool,
+
+    /// Tool whose output to parse, or exec:<program> to run an external
+    /// parser that prints metric JSON
+    #[arg(long, default_value = "criterion")]
+    pub adapter: Adapter,
+
+    /// Benchmark name for load-test results (defaults to the target URL)
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Run the command through the platform shell, its words joined with
+    /// spaces, e.g. for pipes or &&
+    #[arg(long)]
+    pub shell: bool,
+
+    #[arg(long)]
+    pub dry_run: bool,
+
+    #[arg(trailing_var_arg = true, required = true)]
+    pub command: Vec<String>,
+}
+
+/// Progress of a backfill, persisted after every commit so an interrupted
+/// run can pick up where it left off.
+#[derive(Debug, Serialize, Deserialize)]
+struct BackfillState {
+    /// Namespace of the run's report ids, so a resumed run resubmits a
+    /// commit under the same id and `--restart` gets new ones
+    #[serde(default = "Uuid::new_v4")]
+    run: Uuid,
+    completed: Vec<String>,
+}
+
+impl Default for BackfillState {
+    fn default() -> Self {
+        Self {
+            run: Uuid::new_v4(),
+            completed: Vec::new(),
+        }
+    }
+}
+
+impl BackfillState {
+    fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).context("Invalid backfill state file"),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Id of the report for `hash`. The server takes a report it already
+    /// has as a no-op, so a commit submitted just before the process died,
+    /// but not yet recorded as completed, isn't stored twice on resume.
+    fn report_id(&self, project: &str, branch: &str, testbed: &str, hash: &str) -> Uuid {
+        let name = format!("{}\n{}\n{}\n{}", project, branch, testbed, hash);
+        Uuid::new_v5(&self.run, name.as_bytes())
+    }
+}
+
+/// Pick every `every`th commit, always keeping the newest one so the range end is covered
+pub fn select_commits(commits: &[String], every: usize) -> Vec<String> {
+    let every = every.max(1);
+    let mut selected: Vec<String> = commits.iter().step_by(every).cloned().collect();
+    if let Some(last) = commits.last() {
+        if selected.last() != Some(last) {
+            selected.push(last.clone());
+        }
+    }
+    selected
+}
+
+fn list_commits(from: &str, to: &str) -> Result<Vec<String>> {
+    let from_hash = git(&["rev-parse", &format!("{}^{{commit}}", from)], None)?;
+    let range = format!("{}..{}", from_hash, to);
+    let rest = git(&["rev-list", "--reverse", "--first-parent", &range], None)?;
+
+    let mut commits = vec![from_hash];
+    commits.extend(rest.lines().map(|l| l.to_string()));
+    Ok(commits)
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+pub async fn handle(args: BackfillArgs, api_url: &str) -> Result<()> {
+    let config = Config::load()?;
+    let client = connect_submitter(api_url, &config);
+
+    let testbed = args.testbed.clone().unwrap_or_else(testbed::default_name);
+    if args.testbed.is_none() {
+        if let Ok(Some(project)) = client.get_project(&args.project).await {
+            let legacy = testbed::legacy_name();
+            if let Some(warning) = testbed::transition_warning(&project, &testbed, legacy) {
+                eprintln!("Warning: {}", warning);
+            }
+        }
+    }
+
+    let git_dir = PathBuf::from(git(&["rev-parse", "--git-common-dir"], None)?);
+    let git_dir = fs::canonicalize(&git_dir).unwrap_or(git_dir);
+    let work_dir = git_dir.join("driftwatch");
+    let key = format!("{}-{}", sanitize(&args.project), sanitize(&args.branch));
+    let state_path = work_dir.join(format!("backfill-{}.json", key));
+    let worktree = work_dir.join(format!("worktree-{}", key));
+
+    let mut state = if args.restart {
+        BackfillState::default()
+    } else {
+        BackfillState::load(&state_path)?
+    };
+
+    let commits = select_commits(&list_commits(&args.from, &args.to)?, args.every);
+    let pending: Vec<&String> = commits
+        .iter()
+        .filter(|c| !state.completed.contains(c))
+        .collect();
+
+    println!("Backfilling benchmarks...");
+    println!("  Project: {}", args.project);
+    println!("  Branch: {}", args.branch);
+    println!("  Testbed: {}", testbed);
+    println!("  Range: {}..{} (every {})", args.from, args.to, args.every);
+    println!(
+        "  Commits: {} selected, {} already done",
+        commits.len(),
+        commits.len() - pending.len()
+    );
+    println!();
+
+    if pending.is_empty() {
+        println!("Nothing to do.");
+        return Ok(());
+    }
+
+    // Benchmarks run in a dedicated worktree so the user's checkout is never touched
+    let _ = git(&["worktree", "prune"], None);
+    if !worktree.exists() {
+        git(
+            &[
+                "worktree",
+                "add",
+                "--detach",
+                worktree.to_str().ok_or_else(|| anyhow!("Invalid path"))?,
+                pending[0],
+            ],
+            None,
+        )?;
+    }
+
+    // The run's id namespace has to be on disk before the first submission
+    if !args.dry_run {
+        state.save(&state_path)?;
+    }
+
+    let command = if args.shell {
+        shell_wrapped(&args.command, &[])
+    } else {
+        args.command.clone()
+    };
+    for (i, hash) in pending.iter().enumerate() {
+        let short = &hash[..hash.len().min(10)];
+        println!("[{}/{}] {}", i + 1, pending.len(), short);
+
+        git(&["checkout", "--detach", "--force", hash], Some(&worktree))?;
+        let commit = commit_info(hash, Some(&worktree))
+            .ok_or_else(|| anyhow!("Failed to read commit {}", short))?;
+
+        let output = execute_command(&command, Some(&worktree))?;
+        let combined_output = format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let results = args.adapter.parse(&combined_output, args.name.as_deref())?;
+        if results.is_empty() {
+            eprintln!("  Warning: no benchmark results found, skipping");
+            continue;
+        }
+        println!("  Found {} benchmark results", results.len());
+
+        if args.dry_run {
+            continue;
+        }
+
+        let id = state.report_id(&args.project, &args.branch, &testbed, hash);
+        let report = client
+            .submit_report(CreateReportInput {
+                project_slug: args.project.clone(),
+                branch: args.branch.clone(),
+                testbed: testbed.clone(),
+                git_hash: Some(hash.to_string()),
+                pr_number: None,
+                commit_message: Some(commit.message),
+                commit_author: Some(commit.author),
+                created_at: Some(commit.committed_at.clone()),
+                committed_at: Some(commit.committed_at),
+                base_branch: None,
+                merge_base_hash: None,
+                evaluated_commit: None,
+                version: None,
+                kind: None,
+                exit_code: output.status.code(),
+                partial: Some(!output.status.success()),
+                context: Vec::new(),
+                tags: Vec::new(),
+                expected_benchmarks: Vec::new(),
+                id: Some(id.to_string()),
+                metrics: to_metric_inputs(results),
+            })
+            .await
+            .with_context(|| format!("Failed to submit report for {}", short))?;
+        println!("  Report submitted: {}", report.id);
+
+        state.completed.push(hash.to_string());
+        state.save(&state_path)?;
+    }
+
+    let _ = git(
+        &[
+            "worktree",
+            "remove",
+            "--force",
+            worktree.to_str().unwrap_or_default(),
+        ],
+        None,
+    );
+
+    println!();
+    if args.dry_run {
+        println!("Dry run - not submitting results.");
+    } else {
+        println!("Backfill complete.");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commits(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("c{}", i)).collect()
+    }
+
+    #[test]
+    fn test_select_commits_every_nth_keeps_last() {
+        let selected = select_commits(&commits(10), 4);
+        assert_eq!(selected, vec!["c0", "c4", "c8", "c9"]);
+    }
+
+    #[test]
+    fn test_select_commits_exact_multiple() {
+        let selected = select_commits(&commits(9), 4);
+        assert_eq!(selected, vec!["c0", "c4", "c8"]);
+    }
+
+    #[test]
+    fn test_select_commits_every_one_and_zero() {
+        assert_eq!(select_commits(&commits(3), 1).len(), 3);
+        assert_eq!(select_commits(&commits(3), 0).len(), 3);
+        assert!(select_commits(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_report_id_survives_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backfill.json");
+        let state = BackfillState::default();
+        state.save(&path).unwrap();
+        let id = state.report_id("proj", "main", "ci", "abc123");
+
+        // A resumed run submits the same commit under the same id
+        let resumed = BackfillState::load(&path).unwrap();
+        assert_eq!(resumed.report_id("proj", "main", "ci", "abc123"), id);
+        assert_ne!(resumed.report_id("proj", "main", "ci", "def456"), id);
+        assert_ne!(resumed.report_id("proj", "main", "other", "abc123"), id);
+
+        // A restarted one doesn't
+        let restarted = BackfillState::default();
+        assert_ne!(restarted.report_id("proj", "main", "ci", "abc123"), id);
+
+        // State files from before run ids get one
+        fs::write(&path, r#"{"completed":["abc123"]}"#).unwrap();
+        let legacy = BackfillState::load(&path).unwrap();
+        assert_eq!(legacy.completed, vec!["abc123"]);
+        assert_ne!(legacy.run, Uuid::nil());
+    }
+}
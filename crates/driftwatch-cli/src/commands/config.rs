@@ -16,6 +16,16 @@ pub enum ConfigCommands {
         /// gRPC server URL
         #[arg(long)]
         grpc_url: Option<String>,
+
+        /// Web UI URL for printed report and alert links (defaults to one
+        /// guessed from the API URL)
+        #[arg(long)]
+        web_url: Option<String>,
+
+        /// Ingestion relay to submit reports through, or "none" to submit
+        /// to the API directly
+        #[arg(long)]
+        relay_url: Option<String>,
     },
     /// Show current configuration
     Show,
@@ -23,15 +33,25 @@ pub enum ConfigCommands {
 
 pub async fn handle(command: ConfigCommands) -> Result<()> {
     match command {
-        ConfigCommands::Set { api_url, grpc_url } => set(api_url, grpc_url).await,
+        ConfigCommands::Set {
+            api_url,
+            grpc_url,
+            web_url,
+            relay_url,
+        } => set(api_url, grpc_url, web_url, relay_url).await,
         ConfigCommands::Show => show().await,
     }
 }
 
-async fn set(api_url: Option<String>, grpc_url: Option<String>) -> Result<()> {
-    if api_url.is_none() && grpc_url.is_none() {
+async fn set(
+    api_url: Option<String>,
+    grpc_url: Option<String>,
+    web_url: Option<String>,
+    relay_url: Option<String>,
+) -> Result<()> {
+    if api_url.is_none() && grpc_url.is_none() && web_url.is_none() && relay_url.is_none() {
         println!("No configuration options provided.");
-        println!("Usage: driftwatch config set --api-url <url> --grpc-url <url>");
+        println!("Usage: driftwatch config set --api-url <url> --grpc-url <url> --web-url <url> --relay-url <url>");
         return Ok(());
     }
 
@@ -45,6 +65,8 @@ async fn set(api_url: Option<String>, grpc_url: Option<String>) -> Result<()> {
             token: String::new(),
             api_url: String::new(),
             grpc_url: String::new(),
+            web_url: None,
+            relay_url: None,
         }
     };
 
@@ -58,6 +80,23 @@ async fn set(api_url: Option<String>, grpc_url: Option<String>) -> Result<()> {
         println!("gRPC URL set to: {}", config.grpc_url);
     }
 
+    if let Some(url) = web_url {
+        config.web_url = Some(url.trim_end_matches('/').to_string());
+        println!("Web URL set to: {}", url.trim_end_matches('/'));
+    }
+
+    match relay_url.as_deref() {
+        Some("none") => {
+            config.relay_url = None;
+            println!("Relay removed; reports go to the API directly");
+        }
+        Some(url) => {
+            config.relay_url = Some(url.trim_end_matches('/').to_string());
+            println!("Relay URL set to: {}", url.trim_end_matches('/'));
+        }
+        None => {}
+    }
+
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent)?;
     }
@@ -91,6 +130,10 @@ async fn show() -> Result<()> {
     println!();
     println!("API URL: {}", config.api_url);
     println!("gRPC URL: {}", config.grpc_url);
+    println!("Web URL: {}", config.web_url(&config.api_url));
+    if let Some(relay) = &config.relay_url {
+        println!("Relay URL: {}", relay);
+    }
     if !config.token.is_empty() {
         println!("Token: {}...", &config.token[..8.min(config.token.len())]);
     } else {
@@ -0,0 +1,496 @@
+use anyhow::{bail, Result};
+use clap::Args;
+use driftwatch_api::graphql::versioning::API_VERSION;
+use driftwatch_api::grpc::auth::auth_service_client::AuthServiceClient;
+use driftwatch_api::grpc::auth::GetMeRequest;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tonic::transport::Channel;
+
+use crate::api::{config_path, connect, Config};
+use crate::git::git;
+
+/// How long each network check waits before calling the service unreachable
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How deep adapter detection looks below the repository root
+const DETECT_DEPTH: usize = 3;
+
+/// Directories adapter detection never descends into
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules", "bin", "obj", "vendor"];
+
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Directory to check for a git repository and benchmark tools
+    /// (defaults to the current one)
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one check, with what to do about it when it isn't ok
+#[derive(Debug)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn print(&self) {
+        let label = match self.status {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+        };
+        println!("[{:<4}] {:<12} {}", label, self.name, self.detail);
+        if let Some(fix) = &self.fix {
+            println!("       {:<12} Fix: {}", "", fix);
+        }
+    }
+}
+
+pub async fn handle(args: DoctorArgs, api_url: &str) -> Result<()> {
+    let dir = match args.dir {
+        Some(dir) => dir,
+        None => std::env::current_dir()?,
+    };
+
+    let mut checks = vec![check_config_file()];
+    let config = Config::load();
+    checks.push(check_api(api_url, config.as_ref().ok()).await);
+    match &config {
+        Ok(config) => checks.extend(check_grpc(config).await),
+        Err(_) => checks.push(Check::fail(
+            "Token",
+            "not set in the config file or DRIFTWATCH_TOKEN",
+            "Run `driftwatch auth login`, or set DRIFTWATCH_TOKEN to an API token",
+        )),
+    }
+    let (git_check, root) = check_git(&dir);
+    checks.push(git_check);
+    checks.push(check_adapters(root.as_deref().unwrap_or(&dir)));
+
+    for check in &checks {
+        check.print();
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!();
+    if failed > 0 {
+        bail!("{} of {} checks failed", failed, checks.len());
+    }
+    if warned > 0 {
+        println!("All checks passed with {} warning(s).", warned);
+    } else {
+        println!("All checks passed.");
+    }
+    Ok(())
+}
+
+fn check_config_file() -> Check {
+    const NAME: &str = "Config file";
+    let from_env = std::env::var("DRIFTWATCH_TOKEN").is_ok();
+    let path = match config_path() {
+        Ok(path) => path,
+        Err(e) => return Check::fail(NAME, e.to_string(), "Set HOME or DRIFTWATCH_TOKEN"),
+    };
+    if !path.exists() {
+        if from_env {
+            return Check::ok(NAME, "none; using DRIFTWATCH_TOKEN");
+        }
+        return Check::fail(
+            NAME,
+            format!("{} doesn't exist", path.display()),
+            "Run `driftwatch auth login` to create it",
+        );
+    }
+    let parsed = fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(toml::from_str::<Config>(&content)?));
+    match parsed {
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} is invalid: {}", path.display(), e),
+            format!(
+                "Fix the file, or delete it and run `driftwatch auth login` ({})",
+                path.display()
+            ),
+        ),
+        Ok(config) if config.token.is_empty() && !from_env => Check::fail(
+            NAME,
+            format!("{} has no token", path.display()),
+            "Run `driftwatch auth login`",
+        ),
+        Ok(_) if from_env => Check::ok(
+            NAME,
+            format!(
+                "{} (its token is overridden by DRIFTWATCH_TOKEN)",
+                path.display()
+            ),
+        ),
+        Ok(_) => Check::ok(NAME, path.display().to_string()),
+    }
+}
+
+fn elapsed(started: Instant) -> String {
+    format!("{} ms", started.elapsed().as_millis())
+}
+
+/// Checks the GraphQL API answers and speaks this CLI's API version
+async fn check_api(api_url: &str, config: Option<&Config>) -> Check {
+    const NAME: &str = "API";
+    let token = config.map(|c| c.token.as_str()).unwrap_or_default();
+    let client = connect(api_url, token);
+    let started = Instant::now();
+    match tokio::time::timeout(TIMEOUT, client.api_version()).await {
+        Err(_) => Check::fail(
+            NAME,
+            format!("{} didn't answer within {}s", api_url, TIMEOUT.as_secs()),
+            "Check the URL with --api-url or DRIFTWATCH_API_URL, and any proxy or firewall in between",
+        ),
+        Ok(Err(e)) => Check::fail(
+            NAME,
+            format!("{} is unreachable: {:#}", api_url, anyhow::Error::from(e)),
+            "Check the URL with --api-url or DRIFTWATCH_API_URL, and that the server is running",
+        ),
+        Ok(Ok(server)) => {
+            let latency = elapsed(started);
+            match driftwatch_sdk::compatibility_warning(API_VERSION, server.api_version) {
+                Some(warning) => Check::warn(
+                    NAME,
+                    format!("{} in {}, but {}", api_url, latency, warning),
+                    format!(
+                        "Run `driftwatch self-update` to get the CLI for server {}",
+                        server.server_version
+                    ),
+                ),
+                None => Check::ok(
+                    NAME,
+                    format!(
+                        "{} in {} (server {})",
+                        api_url, latency, server.server_version
+                    ),
+                ),
+            }
+        }
+    }
+}
+
+/// Checks the gRPC endpoint answers, then validates the token with GetMe
+async fn check_grpc(config: &Config) -> Vec<Check> {
+    let grpc_fix =
+        "Check the URL with `driftwatch config set --grpc-url` or DRIFTWATCH_GRPC_URL, and that the port is open";
+    let started = Instant::now();
+    let channel = match Channel::from_shared(config.grpc_url.clone()) {
+        Ok(endpoint) => tokio::time::timeout(TIMEOUT, endpoint.connect()).await,
+        Err(e) => {
+            return vec![Check::fail(
+                "gRPC",
+                format!("{} is not a valid URL: {}", config.grpc_url, e),
+                grpc_fix,
+            )]
+        }
+    };
+    let channel = match channel {
+        Ok(Ok(channel)) => channel,
+        Ok(Err(e)) => {
+            return vec![Check::fail(
+                "gRPC",
+                format!("{} is unreachable: {}", config.grpc_url, e),
+                grpc_fix,
+            )]
+        }
+        Err(_) => {
+            return vec![Check::fail(
+                "gRPC",
+                format!(
+                    "{} didn't answer within {}s",
+                    config.grpc_url,
+                    TIMEOUT.as_secs()
+                ),
+                grpc_fix,
+            )]
+        }
+    };
+
+    let request = GetMeRequest {
+        token: config.token.clone(),
+    };
+    let mut client = AuthServiceClient::new(channel);
+    match tokio::time::timeout(TIMEOUT, client.get_me(request)).await {
+        Err(_) => vec![Check::fail(
+            "gRPC",
+            format!(
+                "{} accepted a connection but didn't answer within {}s",
+                config.grpc_url,
+                TIMEOUT.as_secs()
+            ),
+            grpc_fix,
+        )],
+        Ok(Err(status)) if status.code() == tonic::Code::Unauthenticated => vec![
+            Check::ok(
+                "gRPC",
+                format!("{} in {}", config.grpc_url, elapsed(started)),
+            ),
+            Check::fail(
+                "Token",
+                format!("rejected: {}", status.message()),
+                "The token expired or was revoked; run `driftwatch auth login` or create a new API token",
+            ),
+        ],
+        Ok(Err(status)) => vec![Check::fail(
+            "gRPC",
+            format!("{} failed: {}", config.grpc_url, status.message()),
+            grpc_fix,
+        )],
+        Ok(Ok(response)) => {
+            let latency = elapsed(started);
+            let token = match response.into_inner().user {
+                Some(user) => Check::ok("Token", format!("authenticated as {}", user.email)),
+                None => Check::fail(
+                    "Token",
+                    "the server returned no user for it",
+                    "Run `driftwatch auth login`",
+                ),
+            };
+            vec![
+                Check::ok("gRPC", format!("{} in {}", config.grpc_url, latency)),
+                token,
+            ]
+        }
+    }
+}
+
+/// Checks git is installed and `dir` is inside a repository, returning the
+/// repository root
+fn check_git(dir: &Path) -> (Check, Option<PathBuf>) {
+    const NAME: &str = "git";
+    let version = match git(&["--version"], Some(dir)) {
+        Ok(version) => version,
+        Err(e) => {
+            return (
+                Check::fail(
+                    NAME,
+                    format!("not usable: {:#}", e),
+                    "Install git and put it on PATH; `driftwatch run` reads commits and branches from it",
+                ),
+                None,
+            )
+        }
+    };
+    match git(&["rev-parse", "--show-toplevel"], Some(dir)) {
+        Ok(root) => {
+            let root = PathBuf::from(root.trim());
+            (
+                Check::ok(
+                    NAME,
+                    format!("{}, repository at {}", version.trim(), root.display()),
+                ),
+                Some(root),
+            )
+        }
+        Err(_) => (
+            Check::warn(
+                NAME,
+                format!("{}, but {} is not in a repository", version.trim(), dir.display()),
+                "Run driftwatch from your checkout, or pass --hash and --branch to `driftwatch run`",
+            ),
+            None,
+        ),
+    }
+}
+
+/// A benchmark tool found in a repository, with the file that gave it away
+#[derive(Debug, PartialEq, Eq)]
+struct Detected {
+    adapter: &'static str,
+    evidence: PathBuf,
+}
+
+fn file_mentions(path: &Path, needles: &[&str]) -> bool {
+    fs::read_to_string(path)
+        .map(|content| needles.iter().any(|needle| content.contains(needle)))
+        .unwrap_or(false)
+}
+
+/// What a single file says about the benchmark tools a repository uses
+fn detect_file(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    match (name, extension) {
+        ("Cargo.toml", _) if file_mentions(path, &["criterion"]) => Some("criterion"),
+        ("Cargo.toml", _) => Some("libtest"),
+        (_, "csproj" | "fsproj" | "vbproj") if file_mentions(path, &["BenchmarkDotNet"]) => {
+            Some("benchmarkdotnet")
+        }
+        ("CMakeLists.txt" | "conanfile.txt" | "conanfile.py" | "vcpkg.json" | "meson.build", _)
+            if file_mentions(path, &["Catch2", "catch2"]) =>
+        {
+            Some("catch2")
+        }
+        (_, "js" | "ts") if file_mentions(path, &["from 'k6", "from \"k6", "require('k6"]) => {
+            Some("k6")
+        }
+        _ => None,
+    }
+}
+
+/// Adapters for the benchmark tools a repository appears to use, one per
+/// adapter, nearest file first
+fn detect_adapters(root: &Path) -> Vec<Detected> {
+    let mut detected: Vec<Detected> = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    for _ in 0..=DETECT_DEPTH {
+        let mut next = Vec::new();
+        for dir in dirs {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+            paths.sort();
+            for path in paths {
+                if path.is_dir() {
+                    let name = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or_default();
+                    if !SKIPPED_DIRS.contains(&name) && !name.starts_with('.') {
+                        next.push(path);
+                    }
+                } else if let Some(adapter) = detect_file(&path) {
+                    if !detected.iter().any(|d| d.adapter == adapter) {
+                        detected.push(Detected {
+                            adapter,
+                            evidence: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                        });
+                    }
+                }
+            }
+        }
+        dirs = next;
+    }
+    // Criterion output is what `cargo bench` prints; libtest only matters
+    // when there's no benchmark harness
+    if detected.iter().any(|d| d.adapter == "criterion") {
+        detected.retain(|d| d.adapter != "libtest");
+    }
+    detected
+}
+
+fn check_adapters(root: &Path) -> Check {
+    const NAME: &str = "Adapters";
+    let detected = detect_adapters(root);
+    if detected.is_empty() {
+        return Check::warn(
+            NAME,
+            format!("no known benchmark tool found in {}", root.display()),
+            "Pass --adapter to `driftwatch run`, or exec:<program> to parse the output yourself",
+        );
+    }
+    let found: Vec<String> = detected
+        .iter()
+        .map(|d| format!("{} ({})", d.adapter, d.evidence.display()))
+        .collect();
+    Check::ok(
+        NAME,
+        format!(
+            "{}; pass one to `driftwatch run --adapter`",
+            found.join(", ")
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_adapters() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect_adapters(dir.path()).is_empty());
+
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        let detected = detect_adapters(dir.path());
+        assert_eq!(detected[0].adapter, "libtest");
+
+        let bench = dir.path().join("crates/bench");
+        fs::create_dir_all(&bench).unwrap();
+        fs::write(
+            bench.join("Cargo.toml"),
+            "[dev-dependencies]\ncriterion = \"0.5\"\n",
+        )
+        .unwrap();
+        let load = dir.path().join("load");
+        fs::create_dir_all(&load).unwrap();
+        fs::write(load.join("smoke.js"), "import http from 'k6/http';\n").unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/k6")).unwrap();
+        fs::write(
+            dir.path().join("node_modules/k6/index.js"),
+            "import http from 'k6/http';\n",
+        )
+        .unwrap();
+
+        let detected = detect_adapters(dir.path());
+        let adapters: Vec<_> = detected.iter().map(|d| d.adapter).collect();
+        assert_eq!(adapters, ["k6", "criterion"]);
+        assert_eq!(detected[0].evidence, Path::new("load/smoke.js"));
+        assert_eq!(detected[1].evidence, Path::new("crates/bench/Cargo.toml"));
+    }
+
+    #[test]
+    fn test_detect_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let csproj = dir.path().join("Bench.csproj");
+        fs::write(
+            &csproj,
+            r#"<PackageReference Include="BenchmarkDotNet" Version="0.13.12" />"#,
+        )
+        .unwrap();
+        assert_eq!(detect_file(&csproj), Some("benchmarkdotnet"));
+
+        let cmake = dir.path().join("CMakeLists.txt");
+        fs::write(&cmake, "find_package(Catch2 3 REQUIRED)\n").unwrap();
+        assert_eq!(detect_file(&cmake), Some("catch2"));
+        fs::write(&cmake, "project(app)\n").unwrap();
+        assert_eq!(detect_file(&cmake), None);
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{bail, Result};
+use clap::{Args, ValueEnum};
+
+use crate::api::{connect, Config, PublicProject};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExploreSort {
+    /// Most recently reported first
+    Recent,
+    /// Most benchmarks first
+    Benchmarks,
+    Name,
+}
+
+impl ExploreSort {
+    fn as_graphql(self) -> &'static str {
+        match self {
+            ExploreSort::Recent => "RECENT",
+            ExploreSort::Benchmarks => "BENCHMARKS",
+            ExploreSort::Name => "NAME",
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct ExploreArgs {
+    /// Only projects whose name, slug or description contains this
+    pub search: Option<String>,
+
+    /// Show one project and its latest results, by the ID the list prints
+    #[arg(long, conflicts_with_all = ["search", "sort", "page"])]
+    pub id: Option<String>,
+
+    #[arg(long, value_enum)]
+    pub sort: Option<ExploreSort>,
+
+    /// Projects per page, or results shown with --id
+    #[arg(long, short, default_value = "20")]
+    pub limit: i32,
+
+    #[arg(long, default_value = "1")]
+    pub page: i32,
+}
+
+pub async fn handle(args: ExploreArgs, api_url: &str) -> Result<()> {
+    // The catalog is public; signed-in users still send their token
+    let config = Config::load().unwrap_or_else(|_| Config::anonymous());
+    let client = connect(api_url, &config.token);
+
+    if let Some(id) = &args.id {
+        let Some(project) = client.public_project(id, Some(args.limit)).await? else {
+            bail!("No public project with ID {}", id);
+        };
+        print_project(&project);
+        return Ok(());
+    }
+
+    if args.page < 1 {
+        bail!("--page starts at 1");
+    }
+    if !(1..=100).contains(&args.limit) {
+        bail!("--limit must be between 1 and 100");
+    }
+    let page = client
+        .public_projects(
+            args.search.as_deref(),
+            args.sort.map(ExploreSort::as_graphql),
+            Some(args.limit),
+            Some((args.page - 1) * args.limit),
+        )
+        .await?;
+    if page.projects.is_empty() {
+        match &args.search {
+            Some(search) => println!("No public projects matching \"{}\".", search),
+            None if args.page > 1 => println!("No public projects on page {}.", args.page),
+            None => println!("No public projects yet."),
+        }
+        return Ok(());
+    }
+
+    println!(
+        "{:<36} {:<30} {:>10} {:>8} {:<12}",
+        "ID", "PROJECT", "BENCHMARKS", "REPORTS", "LAST REPORT"
+    );
+    println!("{}", "-".repeat(100));
+    for project in &page.projects {
+        println!(
+            "{:<36} {:<30} {:>10} {:>8} {:<12}",
+            project.id,
+            project.name,
+            project.benchmarks,
+            project.reports,
+            date(project.last_report_at.as_deref())
+        );
+    }
+    let pages = (page.total_count + args.limit - 1) / args.limit;
+    println!();
+    println!(
+        "Page {} of {}, {} public project(s). Show one with `driftwatch explore --id <ID>`.",
+        args.page, pages, page.total_count
+    );
+    Ok(())
+}
+
+/// The date part of an RFC 3339 timestamp
+fn date(at: Option<&str>) -> &str {
+    at.map_or("-", |at| at.get(..10).unwrap_or(at))
+}
+
+fn print_project(project: &PublicProject) {
+    println!("{} ({})", project.name, project.slug);
+    if let Some(description) = &project.description {
+        println!("{}", description);
+    }
+    if let Some(repo) = &project.github_repo {
+        println!("Repository: https://github.com/{}", repo);
+    }
+    println!(
+        "{} benchmarks in {} series, {} reports, last on {}",
+        project.benchmarks,
+        project.series,
+        project.reports,
+        date(project.last_report_at.as_deref())
+    );
+    if project.latest_results.is_empty() {
+        return;
+    }
+
+    println!();
+    println!(
+        "{:<40} {:<16} {:<16} {:<16} {:>14}",
+        "BENCHMARK", "MEASURE", "BRANCH", "TESTBED", "LATEST"
+    );
+    println!("{}", "-".repeat(106));
+    for result in &project.latest_results {
+        println!(
+            "{:<40} {:<16} {:<16} {:<16} {:>14.2}",
+            result.benchmark, result.measure, result.branch, result.testbed, result.latest_value
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::adapters::Adapter;
+use crate::release_signing;
+
+/// The composite action, as published in the repository's `action/`
+/// directory. `{repository}` is filled with the `owner/name` releases are
+/// downloaded from.
+const ACTION_TEMPLATE: &str = r#"name: Driftwatch
+description: Run benchmarks with the driftwatch CLI and submit the results
+branding:
+  icon: activity
+  color: blue
+
+inputs:
+  command:
+    description: Benchmark command to run, e.g. `cargo bench`
+    required: true
+  project:
+    description: Project slug
+    required: true
+  token:
+    description: Driftwatch API token, e.g. `secrets.DRIFTWATCH_TOKEN`
+    required: true
+  api-url:
+    description: Driftwatch API URL (defaults to the CLI's)
+    required: false
+    default: ""
+  branch:
+    description: Branch the results belong to (defaults to the pull request's head or the pushed branch)
+    required: false
+    default: ""
+  testbed:
+    description: Testbed name
+    required: false
+    default: github-actions
+  adapter:
+    description: Tool that produced the output, or exec:<program>
+    required: false
+    default: criterion
+  err:
+    description: Fail the step when an alert is raised
+    required: false
+    default: "false"
+  args:
+    description: Extra arguments for `driftwatch run`
+    required: false
+    default: ""
+  version:
+    description: CLI version to install, or `latest`
+    required: false
+    default: latest
+  public-key:
+    description: Base64 Ed25519 key CLI releases are signed with; without one the CLI is built from source
+    required: false
+    default: ""
+  allow-unsigned:
+    description: Install the release binary on its checksum alone when there is no public-key
+    required: false
+    default: "false"
+
+runs:
+  using: composite
+  steps:
+    - name: Install driftwatch
+      shell: bash
+      env:
+        DRIFTWATCH_VERSION: ${{ inputs.version }}
+        PUBLIC_KEY: ${{ inputs.public-key }}
+        ALLOW_UNSIGNED: ${{ inputs.allow-unsigned }}
+      run: |
+        case "$RUNNER_OS" in
+          Linux) os=linux ;;
+          macOS) os=macos ;;
+          Windows) os=windows ;;
+        esac
+        case "$RUNNER_ARCH" in
+          X64) arch=x86_64 ;;
+          ARM64) arch=aarch64 ;;
+        esac
+        ext=""
+        [ "$os" = windows ] && ext=.exe
+        asset="driftwatch-$os-$arch$ext"
+        if [ "$DRIFTWATCH_VERSION" = latest ]; then
+          url="https://github.com/{repository}/releases/latest/download/$asset"
+        else
+          url="https://github.com/{repository}/releases/download/v${DRIFTWATCH_VERSION#v}/$asset"
+        fi
+
+        # crates.io checks what cargo downloads, so building from source
+        # needs no signature
+        build_from_source() {
+          if [ "$DRIFTWATCH_VERSION" = latest ]; then
+            cargo install driftwatch --locked
+          else
+            cargo install driftwatch --locked --version "${DRIFTWATCH_VERSION#v}"
+          fi
+        }
+        fail() {
+          echo "::error::$*"
+          exit 1
+        }
+        if [ -z "$PUBLIC_KEY" ] && [ "$ALLOW_UNSIGNED" != true ]; then
+          echo "No public-key to check the release signature with; building from source"
+          build_from_source
+          exit 0
+        fi
+
+        download="$RUNNER_TEMP/driftwatch-download"
+        mkdir -p "$download"
+        if ! curl -fsSL "$url" -o "$download/$asset"; then
+          build_from_source
+          exit 0
+        fi
+        curl -fsSL "$url.sha256" -o "$download/$asset.sha256" \
+          || fail "No checksum published for $asset"
+        expected=$(awk '{ print $1 }' "$download/$asset.sha256")
+        if command -v sha256sum > /dev/null; then
+          actual=$(sha256sum "$download/$asset" | awk '{ print $1 }')
+        else
+          actual=$(shasum -a 256 "$download/$asset" | awk '{ print $1 }')
+        fi
+        [ "$expected" = "$actual" ] || fail "Checksum mismatch for $asset"
+
+        if [ -n "$PUBLIC_KEY" ]; then
+          curl -fsSL "$url.sha256.sig" -o "$download/$asset.sha256.sig" \
+            || fail "$asset isn't signed"
+          # An Ed25519 SubjectPublicKeyInfo is a fixed 12-byte prefix and the key
+          printf -- '-----BEGIN PUBLIC KEY-----\nMCowBQYDK2VwAyEA%s\n-----END PUBLIC KEY-----\n' \
+            "$PUBLIC_KEY" > "$download/release.pem"
+          openssl base64 -d -A -in "$download/$asset.sha256.sig" -out "$download/signature"
+          openssl pkeyutl -verify -pubin -inkey "$download/release.pem" -rawin \
+            -in "$download/$asset.sha256" -sigfile "$download/signature" > /dev/null \
+            || fail "The signature of $asset doesn't match public-key"
+        fi
+        # The checksum file names its release, so an older signed release
+        # can't stand in for the pinned one
+        released=$(awk '{ print $3 }' "$download/$asset.sha256")
+        if [ "$DRIFTWATCH_VERSION" != latest ]; then
+          if [ -n "$released" ]; then
+            [ "$released" = "${DRIFTWATCH_VERSION#v}" ] \
+              || fail "The checksum of $asset is for driftwatch $released"
+          elif [ -n "$PUBLIC_KEY" ]; then
+            fail "The signed checksum of $asset doesn't name its release"
+          fi
+        fi
+
+        bin="$RUNNER_TEMP/driftwatch-bin"
+        mkdir -p "$bin"
+        mv "$download/$asset" "$bin/driftwatch$ext"
+        chmod +x "$bin/driftwatch$ext"
+        echo "$bin" >> "$GITHUB_PATH"
+
+    - name: Run benchmarks
+      shell: bash
+      env:
+        DRIFTWATCH_TOKEN: ${{ inputs.token }}
+        DRIFTWATCH_API_URL: ${{ inputs.api-url }}
+        INPUT_COMMAND: ${{ inputs.command }}
+        INPUT_PROJECT: ${{ inputs.project }}
+        INPUT_BRANCH: ${{ inputs.branch || github.head_ref || github.ref_name }}
+        INPUT_TESTBED: ${{ inputs.testbed }}
+        INPUT_ADAPTER: ${{ inputs.adapter }}
+        INPUT_ERR: ${{ inputs.err }}
+        INPUT_ARGS: ${{ inputs.args }}
+      run: |
+        echo "::add-mask::$DRIFTWATCH_TOKEN"
+        [ -z "$DRIFTWATCH_API_URL" ] && unset DRIFTWATCH_API_URL
+        flags=()
+        [ "$INPUT_ERR" = true ] && flags+=(--err)
+        # Extra arguments are split on whitespace, like on a command line
+        read -ra extra <<< "$INPUT_ARGS"
+        driftwatch run \
+          --project "$INPUT_PROJECT" \
+          --branch "$INPUT_BRANCH" \
+          --testbed "$INPUT_TESTBED" \
+          --adapter "$INPUT_ADAPTER" \
+          --shell \
+          "${flags[@]}" "${extra[@]}" \
+          -- "$INPUT_COMMAND"
+"#;
+
+/// Where `--vendor` writes the action, relative to the repository root
+const VENDORED_ACTION: &str = ".github/actions/driftwatch/action.yml";
+
+const WORKFLOW: &str = ".github/workflows/benchmarks.yml";
+
+#[derive(Args)]
+pub struct GhaInstallArgs {
+    #[arg(long, short)]
+    pub project: String,
+
+    /// Benchmark command the workflow runs
+    #[arg(long, default_value = "cargo bench")]
+    pub command: String,
+
+    #[arg(long, short, default_value = "github-actions")]
+    pub testbed: String,
+
+    /// Tool that produces the benchmark output, or exec:<program>
+    #[arg(long, default_value = "criterion", value_parser = parse_adapter)]
+    pub adapter: String,
+
+    /// Fail pull requests when an alert is raised
+    #[arg(long)]
+    pub err: bool,
+
+    /// Copy the action into the repository instead of using the published
+    /// one
+    #[arg(long)]
+    pub vendor: bool,
+
+    /// Repository root to write into
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+
+    /// Print the files instead of writing them
+    #[arg(long)]
+    pub print: bool,
+
+    /// Overwrite existing files
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Keeps the adapter as written, after checking the CLI would accept it
+fn parse_adapter(s: &str) -> Result<String, String> {
+    s.parse::<Adapter>().map(|_| s.to_string())
+}
+
+/// `owner/name` of the repository the CLI is published from
+fn repository() -> &'static str {
+    env!("CARGO_PKG_REPOSITORY")
+        .trim_end_matches('/')
+        .trim_start_matches("https://github.com/")
+}
+
+pub fn action_yaml() -> String {
+    ACTION_TEMPLATE.replace("{repository}", repository())
+}
+
+/// Quotes a value for a YAML scalar when it could be misread
+fn yaml_value(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " -_./:".contains(c))
+        && !value.starts_with(['-', ' ', ':'])
+        && !value.ends_with([' ', ':'])
+        && !value.contains(": ");
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+pub fn workflow_yaml(args: &GhaInstallArgs) -> String {
+    let uses = if args.vendor {
+        format!(
+            "./{}",
+            Path::new(VENDORED_ACTION).parent().unwrap().display()
+        )
+    } else {
+        format!("{}/action@v{}", repository(), env!("CARGO_PKG_VERSION"))
+    };
+    let mut with = vec![
+        ("command", yaml_value(&args.command)),
+        ("project", yaml_value(&args.project)),
+        ("token", "${{ secrets.DRIFTWATCH_TOKEN }}".to_string()),
+        ("testbed", yaml_value(&args.testbed)),
+        ("adapter", yaml_value(&args.adapter)),
+    ];
+    if args.err {
+        with.push(("err", "true".to_string()));
+    }
+    // Lets the action check the release it installs, as self-update does
+    if let Some(key) = release_signing::EMBEDDED_PUBLIC_KEY.filter(|key| !key.trim().is_empty()) {
+        with.push(("public-key", yaml_value(key.trim())));
+    }
+    let with: String = with
+        .iter()
+        .map(|(key, value)| format!("          {}: {}\n", key, value))
+        .collect();
+
+    format!(
+        r#"name: Benchmarks
+
+on:
+  push:
+    branches: [main]
+  pull_request:
+
+jobs:
+  bench:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          # History for merge-base baselines on pull requests
+          fetch-depth: 0
+
+      - uses: {}
+        with:
+{}"#,
+        uses, with
+    )
+}
+
+fn write_file(path: &Path, content: &str, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!(
+            "{} already exists; pass --force to overwrite it",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+pub async fn handle(args: GhaInstallArgs) -> Result<()> {
+    let mut files = vec![(args.dir.join(WORKFLOW), workflow_yaml(&args))];
+    if args.vendor {
+        files.push((args.dir.join(VENDORED_ACTION), action_yaml()));
+    }
+
+    if args.print {
+        for (path, content) in &files {
+            println!("# {}\n{}", path.display(), content);
+        }
+        return Ok(());
+    }
+
+    for (path, content) in &files {
+        write_file(path, content, args.force)?;
+    }
+    println!(
+        "\nAdd an API token as the DRIFTWATCH_TOKEN repository secret, then commit the files."
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(vendor: bool) -> GhaInstallArgs {
+        GhaInstallArgs {
+            project: "my-project".to_string(),
+            command: "cargo bench -- --noplot".to_string(),
+            testbed: "github-actions".to_string(),
+            adapter: "criterion".to_string(),
+            err: true,
+            vendor,
+            dir: PathBuf::from("."),
+            print: false,
+            force: false,
+        }
+    }
+
+    #[test]
+    fn test_published_action_is_current() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../action/action.yml");
+        let published = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            published,
+            action_yaml(),
+            "action/action.yml is out of date with the CLI's copy"
+        );
+    }
+
+    #[test]
+    fn test_workflow_yaml() {
+        let workflow = workflow_yaml(&args(false));
+        assert!(workflow.contains(&format!(
+            "- uses: {}/action@v{}\n",
+            repository(),
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(workflow.contains("          command: cargo bench -- --noplot\n"));
+        assert!(workflow.contains("          project: my-project\n"));
+        assert!(workflow.contains("          token: ${{ secrets.DRIFTWATCH_TOKEN }}\n"));
+        assert!(workflow.ends_with("          err: true\n"));
+
+        let vendored = workflow_yaml(&args(true));
+        assert!(vendored.contains("- uses: ./.github/actions/driftwatch\n"));
+    }
+
+    #[test]
+    fn test_yaml_value() {
+        assert_eq!(yaml_value("cargo bench"), "cargo bench");
+        assert_eq!(yaml_value("exec:./parse.sh"), "exec:./parse.sh");
+        assert_eq!(yaml_value("it's"), "'it''s'");
+        assert_eq!(yaml_value("make bench #fast"), "'make bench #fast'");
+        assert_eq!(yaml_value(""), "''");
+    }
+
+    #[test]
+    fn test_write_file_refuses_to_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".github/workflows/benchmarks.yml");
+        write_file(&path, "a", false).unwrap();
+        assert!(write_file(&path, "b", false).is_err());
+        write_file(&path, "b", true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "b");
+    }
+}
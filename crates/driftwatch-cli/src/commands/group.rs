@@ -0,0 +1,191 @@
+use anyhow::Result;
+use clap::Subcommand;
+
+use crate::api::{connect, AlertStatus, ApiClient, Config};
+
+#[derive(Subcommand)]
+pub enum GroupCommands {
+    List,
+    /// Group related projects to follow their alerts and history together
+    Create {
+        slug: String,
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        description: Option<String>,
+        /// Project to add; repeat for several
+        #[arg(long = "project", short)]
+        projects: Vec<String>,
+    },
+    Delete {
+        slug: String,
+    },
+    /// Add a project to a group
+    Add {
+        group: String,
+        project: String,
+    },
+    /// Remove a project from a group
+    Remove {
+        group: String,
+        project: String,
+    },
+    /// Combined alert feed of every project in the group, newest first
+    Alerts {
+        slug: String,
+        #[arg(long, value_enum)]
+        status: Option<AlertStatus>,
+        #[arg(long, default_value = "50")]
+        limit: i32,
+    },
+    /// Combined report history of every project in the group, newest first
+    History {
+        slug: String,
+        #[arg(long, default_value = "20")]
+        limit: i32,
+        /// Only reports carrying this tag; repeatable
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+    },
+}
+
+pub async fn handle(command: GroupCommands, api_url: &str) -> Result<()> {
+    let config = Config::load()?;
+    let client = connect(api_url, &config.token);
+
+    match command {
+        GroupCommands::List => list(&client).await,
+        GroupCommands::Create {
+            slug,
+            name,
+            description,
+            projects,
+        } => {
+            let group = client
+                .create_project_group(&slug, &name, description.as_deref(), &projects)
+                .await?;
+            println!("Created project group: {} ({})", group.name, group.slug);
+            Ok(())
+        }
+        GroupCommands::Delete { slug } => {
+            client.delete_project_group(&slug).await?;
+            println!("Deleted project group: {}", slug);
+            Ok(())
+        }
+        GroupCommands::Add { group, project } => {
+            client.set_group_membership(&group, &project, true).await?;
+            println!("Added {} to {}", project, group);
+            Ok(())
+        }
+        GroupCommands::Remove { group, project } => {
+            client.set_group_membership(&group, &project, false).await?;
+            println!("Removed {} from {}", project, group);
+            Ok(())
+        }
+        GroupCommands::Alerts {
+            slug,
+            status,
+            limit,
+        } => alerts(&client, &slug, status, limit).await,
+        GroupCommands::History { slug, limit, tags } => history(&client, &slug, limit, &tags).await,
+    }
+}
+
+async fn list(client: &ApiClient) -> Result<()> {
+    let groups = client.list_project_groups().await?;
+
+    if groups.is_empty() {
+        println!("No project groups found.");
+        println!(
+            "Create one with: driftwatch group create my-group --name \"My Group\" -p my-project"
+        );
+        return Ok(());
+    }
+
+    println!("{:<20} {:<30} PROJECTS", "SLUG", "NAME");
+    println!("{}", "-".repeat(80));
+
+    for group in groups {
+        let projects: Vec<&str> = group.projects.iter().map(|p| p.slug.as_str()).collect();
+        println!(
+            "{:<20} {:<30} {}",
+            group.slug,
+            group.name,
+            projects.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+async fn alerts(
+    client: &ApiClient,
+    slug: &str,
+    status: Option<AlertStatus>,
+    limit: i32,
+) -> Result<()> {
+    let Some(alerts) = client.group_alerts(slug, status, limit).await? else {
+        println!("Project group not found: {}", slug);
+        return Ok(());
+    };
+
+    if alerts.is_empty() {
+        println!("No alerts found.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<36} {:<20} {:<14} {:>9} BENCHMARK",
+        "ID", "PROJECT", "STATUS", "CHANGE"
+    );
+    println!("{}", "-".repeat(100));
+
+    for alert in alerts {
+        println!(
+            "{:<36} {:<20} {:<14} {:>+8.1}% {}",
+            alert.id,
+            alert.project.slug,
+            alert.status,
+            alert.percent_change,
+            alert.metric.benchmark.name
+        );
+    }
+
+    Ok(())
+}
+
+async fn history(client: &ApiClient, slug: &str, limit: i32, tags: &[String]) -> Result<()> {
+    let Some(reports) = client.group_reports(slug, limit, tags).await? else {
+        println!("Project group not found: {}", slug);
+        return Ok(());
+    };
+
+    if reports.is_empty() {
+        println!("No reports found.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<26} {:<20} {:<20} {:<10} ALERTS",
+        "CREATED", "PROJECT", "BRANCH", "COMMIT"
+    );
+    println!("{}", "-".repeat(90));
+
+    for report in reports {
+        let commit = report
+            .git_hash
+            .as_deref()
+            .map(|h| &h[..h.len().min(8)])
+            .unwrap_or("-");
+        println!(
+            "{:<26} {:<20} {:<20} {:<10} {}",
+            report.created_at,
+            report.project.slug,
+            report.branch.name,
+            commit,
+            report.alerts.len()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,238 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use driftwatch_api::backfills::{self, Backfill};
+use driftwatch_api::config::Config;
+use driftwatch_api::db;
+use driftwatch_api::migrations::{self, MigrationCheck, MigrationInfo, Plan};
+
+#[derive(Subcommand)]
+pub enum MigrateCommands {
+    /// List migrations and whether each is applied
+    Status,
+    /// Fail if a pending migration can't run while the current release is
+    /// serving traffic
+    Check {
+        /// Locks on tables with more rows than this need downtime
+        #[arg(long, default_value_t = migrations::DEFAULT_MAX_LOCKED_ROWS)]
+        max_rows: i64,
+
+        /// Accept downtime for this migration (repeatable)
+        #[arg(long = "allow", value_name = "MIGRATION")]
+        allowed: Vec<String>,
+    },
+    /// Apply pending migrations
+    Up {
+        /// Apply only this many, oldest first
+        #[arg(long)]
+        steps: Option<u32>,
+
+        /// Print the SQL instead of applying it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Roll back applied migrations, newest first
+    Down {
+        #[arg(long, default_value = "1")]
+        steps: u32,
+
+        /// Print the SQL instead of applying it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Roll back migrations and apply them again
+    Redo {
+        #[arg(long, default_value = "1")]
+        steps: u32,
+
+        /// Print the SQL instead of applying it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn handle(command: MigrateCommands) -> Result<()> {
+    dotenvy::dotenv().ok();
+    if std::env::var("DATABASE_URL").map_or(true, |url| url.is_empty()) {
+        bail!("Set DATABASE_URL to the database to migrate");
+    }
+    let config = Config::from_env();
+    let db = db::connect(&config.database_url, &config)
+        .await
+        .context("Failed to connect to the database")?;
+
+    let (plan, dry_run) = match command {
+        MigrateCommands::Status => {
+            print_status(&migrations::status(&db).await?);
+            print_backfills(&backfills::unfinished(&db).await?);
+            return Ok(());
+        }
+        MigrateCommands::Check { max_rows, allowed } => {
+            let unfinished = backfills::unfinished(&db).await?;
+            let checks = migrations::check(db, max_rows).await?;
+            let downtime = print_checks(&checks, &allowed);
+            print_backfills(&unfinished);
+            if downtime > 0 {
+                bail!(
+                    "{} pending migrations need downtime; apply them in a maintenance window or pass --allow <migration>",
+                    downtime
+                );
+            }
+            return Ok(());
+        }
+        MigrateCommands::Up { steps, dry_run } => (Plan::Up(steps), dry_run),
+        MigrateCommands::Down { steps, dry_run } => (Plan::Down(steps), dry_run),
+        MigrateCommands::Redo { steps, dry_run } => (Plan::Redo(steps), dry_run),
+    };
+    if matches!(plan, Plan::Up(Some(0)) | Plan::Down(0) | Plan::Redo(0)) {
+        bail!("--steps must be at least 1");
+    }
+
+    if dry_run {
+        let statements = migrations::dry_run(db, plan).await?;
+        if statements.is_empty() {
+            println!("-- Nothing to run");
+        }
+        for statement in statements {
+            println!("{};\n", statement.trim_end().trim_end_matches(';'));
+        }
+        return Ok(());
+    }
+
+    let affected = affected(&migrations::status(&db).await?, plan);
+    migrations::migrate(&db, plan).await?;
+    if affected.is_empty() {
+        println!("Nothing to do.");
+    }
+    let verb = match plan {
+        Plan::Up(_) => "Applied",
+        Plan::Down(_) => "Rolled back",
+        Plan::Redo(_) => "Reapplied",
+    };
+    for name in affected {
+        println!("{} {}", verb, name);
+    }
+    Ok(())
+}
+
+/// Names of the migrations `plan` touches, in the order it runs them
+fn affected(migrations: &[MigrationInfo], plan: Plan) -> Vec<String> {
+    let applied = migrations.iter().filter(|m| m.applied_at.is_some()).rev();
+    let names: Vec<&str> = match plan {
+        Plan::Up(steps) => migrations
+            .iter()
+            .filter(|m| m.applied_at.is_none())
+            .take(steps.map_or(usize::MAX, |s| s as usize))
+            .map(|m| m.name.as_str())
+            .collect(),
+        Plan::Down(steps) | Plan::Redo(steps) => applied
+            .take(steps as usize)
+            .map(|m| m.name.as_str())
+            .collect(),
+    };
+    names.into_iter().map(str::to_string).collect()
+}
+
+fn print_status(migrations: &[MigrationInfo]) {
+    println!("{:<60} APPLIED", "MIGRATION");
+    for migration in migrations {
+        let applied = match migration.applied_at {
+            Some(at) => at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            None => "pending".to_string(),
+        };
+        println!("{:<60} {}", migration.name, applied);
+    }
+    let pending = migrations.iter().filter(|m| m.applied_at.is_none()).count();
+    println!();
+    println!(
+        "{} applied, {} pending",
+        migrations.len() - pending,
+        pending
+    );
+}
+
+/// Truncates SQL to one line for the terminal
+fn summarize(sql: &str) -> String {
+    const WIDTH: usize = 100;
+    let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    match sql.char_indices().nth(WIDTH) {
+        Some((end, _)) => format!("{}...", &sql[..end]),
+        None => sql,
+    }
+}
+
+/// Prints whether each pending migration can run online and returns how
+/// many need downtime that wasn't allowed
+fn print_checks(checks: &[MigrationCheck], allowed: &[String]) -> usize {
+    if checks.is_empty() {
+        println!("No pending migrations.");
+        return 0;
+    }
+    let mut downtime = 0;
+    println!("{:<60} SAFETY", "MIGRATION");
+    for check in checks {
+        let safety = if check.online() {
+            "online"
+        } else if allowed.contains(&check.name) {
+            "downtime (allowed)"
+        } else {
+            downtime += 1;
+            "DOWNTIME"
+        };
+        println!("{:<60} {}", check.name, safety);
+        for finding in &check.findings {
+            let table = match (&finding.hazard.table, finding.rows) {
+                (Some(table), Some(rows)) => format!("{} (~{} rows)", table, rows),
+                (Some(table), None) => table.clone(),
+                (None, _) => "unknown table".to_string(),
+            };
+            let marker = if finding.blocking { "!" } else { "-" };
+            println!("  {} {}: {}", marker, table, finding.hazard.reason);
+            println!("      {}", summarize(&finding.statement));
+        }
+    }
+    downtime
+}
+
+fn print_backfills(unfinished: &[Backfill]) {
+    if unfinished.is_empty() {
+        return;
+    }
+    println!();
+    println!("Backfills still running:");
+    for backfill in unfinished {
+        println!(
+            "  {}: {} rows in {} batches since {}",
+            backfill.name,
+            backfill.rows_done,
+            backfill.batches,
+            backfill.created_at.format("%Y-%m-%d %H:%M:%S %Z")
+        );
+        if let Some(error) = &backfill.last_error {
+            println!("    last error: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affected() {
+        let migration = |name: &str, applied: bool| MigrationInfo {
+            name: name.to_string(),
+            applied_at: applied.then(chrono::Utc::now),
+        };
+        let migrations = [
+            migration("m1", true),
+            migration("m2", true),
+            migration("m3", false),
+            migration("m4", false),
+        ];
+
+        assert_eq!(affected(&migrations, Plan::Up(None)), ["m3", "m4"]);
+        assert_eq!(affected(&migrations, Plan::Up(Some(1))), ["m3"]);
+        assert_eq!(affected(&migrations, Plan::Down(1)), ["m2"]);
+        assert_eq!(affected(&migrations, Plan::Redo(5)), ["m2", "m1"]);
+    }
+}
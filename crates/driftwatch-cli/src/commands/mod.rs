@@ -1,4 +1,19 @@
+pub mod ab;
+pub mod alert;
 pub mod auth;
+pub mod backfill;
 pub mod config;
+pub mod doctor;
+pub mod explore;
+pub mod gha_install;
+pub mod group;
+pub mod migrate;
 pub mod project;
+pub mod report;
 pub mod run;
+pub mod search;
+pub mod self_update;
+pub mod signing;
+pub mod storage;
+pub mod threshold;
+pub mod verify;
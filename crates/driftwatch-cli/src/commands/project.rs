@@ -1,7 +1,16 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use clap::Subcommand;
+use std::fs;
+use std::path::PathBuf;
 
-use crate::api::{ApiClient, Config};
+use driftwatch_api::links;
+
+use crate::api::{
+    connect, AnnotationKind, ApiClient, ChannelKind, Config, ContextEntry, CreateAnnotationInput,
+    LabelMatcher, RemoteWriteRuleInput, ReportKind,
+};
+use crate::commands::run::parse_context_entry;
+use crate::owners::{parse_owners, DEFAULT_OWNERS_FILE};
 
 #[derive(Subcommand)]
 pub enum ProjectCommands {
@@ -15,15 +24,249 @@ pub enum ProjectCommands {
         description: Option<String>,
         #[arg(long, default_value = "false")]
         public: bool,
+        /// Seed measures and default thresholds from a server-side template
+        /// (see `project templates`)
+        #[arg(long)]
+        template: Option<String>,
     },
+    /// List the templates available to `project create --template`
+    Templates,
     Show {
         slug: String,
     },
+    /// Set the branch reports submitted with `--branch HEAD` go to
+    SetDefaultBranch {
+        slug: String,
+        branch: String,
+    },
+    /// Let reports submitted for ALIAS count as reports of BRANCH, e.g.
+    /// `main` for `master` after the repository renamed its primary branch
+    AddBranchAlias {
+        slug: String,
+        alias: String,
+        branch: String,
+    },
+    RemoveBranchAlias {
+        slug: String,
+        alias: String,
+    },
+    /// Let reports submitted for ALIAS count as reports of TESTBED, e.g.
+    /// `linux-x86_64` for the `linux` testbed `run` used to default to
+    AddTestbedAlias {
+        slug: String,
+        alias: String,
+        testbed: String,
+    },
+    RemoveTestbedAlias {
+        slug: String,
+        alias: String,
+    },
+    /// List the latest reports, e.g. only nightlies with --tag nightly
+    Reports {
+        slug: String,
+        /// Only reports of this kind, e.g. nightly
+        #[arg(long, value_enum)]
+        kind: Option<ReportKind>,
+        /// Only reports carrying this tag; repeatable
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Only reports matching this saved filter (see `project filters`)
+        #[arg(long)]
+        filter: Option<String>,
+        #[arg(long, default_value = "20")]
+        limit: i32,
+    },
+    /// List the project's saved report filters
+    Filters {
+        slug: String,
+    },
+    /// Save a named report filter for `project reports --filter`, replacing
+    /// the one of the same name
+    SaveFilter {
+        slug: String,
+        name: String,
+        /// Reports need this tag; repeatable
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Reports need this context entry; repeatable
+        #[arg(long, value_name = "KEY=VALUE", value_parser = parse_context_entry)]
+        context: Vec<ContextEntry>,
+    },
+    RemoveFilter {
+        slug: String,
+        name: String,
+    },
+    /// Summarize how each measure moved over 7 and 30 days, open alerts
+    /// and when each branch/testbed last reported
+    Status {
+        slug: String,
+        /// Only count trends on this branch
+        #[arg(long, short)]
+        branch: Option<String>,
+        /// Only count trends on this testbed
+        #[arg(long, short)]
+        testbed: Option<String>,
+    },
+    /// Upload benchmark owner rules from the repository's owners file
+    SyncOwners {
+        slug: String,
+        #[arg(long, default_value = DEFAULT_OWNERS_FILE)]
+        file: PathBuf,
+    },
+    /// Show how noisy each benchmark is on each testbed
+    Noise {
+        slug: String,
+        #[arg(long, short)]
+        testbed: Option<String>,
+        /// Only list benchmarks above the project's noise limit
+        #[arg(long)]
+        noisy: bool,
+    },
+    /// Note what changed around a shift in benchmark history
+    Annotate {
+        slug: String,
+        note: String,
+        #[arg(long, value_enum, default_value = "note")]
+        kind: AnnotationKind,
+        /// Attach the note to this report
+        #[arg(long, conflicts_with = "at")]
+        report: Option<String>,
+        /// When it happened, as an RFC 3339 timestamp; defaults to now
+        #[arg(long)]
+        at: Option<String>,
+    },
+    /// List a project's annotations, newest first
+    Annotations {
+        slug: String,
+    },
+    /// Print the project's latest weekly digest
+    Digest {
+        slug: String,
+        /// Compile the current week so far instead
+        #[arg(long)]
+        preview: bool,
+        /// Print HTML instead of markdown
+        #[arg(long)]
+        html: bool,
+    },
+    /// List where weekly digests and alerts are sent
+    Channels {
+        slug: String,
+    },
+    /// Send weekly digests or alerts to a webhook or Slack incoming webhook
+    AddChannel {
+        slug: String,
+        url: String,
+        #[arg(long, value_enum, default_value = "webhook")]
+        kind: ChannelKind,
+        /// Event to send: digest, report.created, alert.opened or
+        /// alert.resolved; repeatable. Defaults to digest
+        #[arg(long = "event")]
+        events: Vec<String>,
+        /// File with a JSON request body template, e.g. for Zapier or IFTTT
+        #[arg(long)]
+        template: Option<PathBuf>,
+    },
+    /// Change the events a channel receives or its template
+    UpdateChannel {
+        id: String,
+        /// Event to send; repeatable, replaces the current events
+        #[arg(long = "event")]
+        events: Vec<String>,
+        /// File with a JSON request body template
+        #[arg(long, conflicts_with = "no_template")]
+        template: Option<PathBuf>,
+        /// Go back to the default payload
+        #[arg(long)]
+        no_template: bool,
+    },
+    /// Post a sample event to a channel to check its URL and template
+    TestChannel {
+        id: String,
+        /// Event to send a sample of; defaults to the channel's first
+        #[arg(long)]
+        event: Option<String>,
+    },
+    /// Stop sending digests and alerts to a channel
+    RemoveChannel {
+        id: String,
+    },
+    /// List the rules mapping Prometheus remote_write series onto benchmarks
+    RemoteWriteRules {
+        slug: String,
+    },
+    /// Track a Prometheus metric sent with remote_write as a benchmark,
+    /// averaged per hour
+    AddRemoteWriteRule {
+        slug: String,
+        /// Metric name, e.g. http_request_duration_seconds
+        metric: String,
+        /// Only series with this label value, e.g. --match quantile=0.99
+        #[arg(long = "match", value_name = "LABEL=VALUE", value_parser = parse_label_matcher)]
+        matchers: Vec<LabelMatcher>,
+        /// Benchmark name; {label} is replaced with the series' label value
+        #[arg(long, default_value = "{__name__}")]
+        benchmark: String,
+        #[arg(long, short, default_value = "latency")]
+        measure: String,
+        #[arg(long, short, default_value = "main")]
+        branch: String,
+        /// Testbed name; {label} is replaced like in --benchmark
+        #[arg(long, short, default_value = "production")]
+        testbed: String,
+    },
+    /// Stop mapping a rule's series
+    RemoveRemoteWriteRule {
+        id: String,
+    },
+    /// Show benchmark results per tagged release, oldest release first
+    Releases {
+        slug: String,
+        #[arg(long, short, default_value = "latency")]
+        measure: String,
+        #[arg(long)]
+        benchmark: Option<String>,
+        #[arg(long, short)]
+        testbed: Option<String>,
+    },
+    /// Plot how a parameterized benchmark (e.g. sort/1000, sort/100000)
+    /// grows with its parameter, across recent reports
+    Scaling {
+        slug: String,
+        /// Benchmark name without the parameter, e.g. sort
+        benchmark: String,
+        #[arg(long, short, default_value = "latency")]
+        measure: String,
+        #[arg(long, short, default_value = "main")]
+        branch: String,
+        #[arg(long, short)]
+        testbed: Option<String>,
+        /// Number of recent reports to show
+        #[arg(long, default_value = "10")]
+        limit: i32,
+    },
+    /// Compare a benchmark's recent results split by a value passed to
+    /// `run --context`, e.g. allocator
+    Context {
+        slug: String,
+        benchmark: String,
+        /// Context key to split by
+        key: String,
+        #[arg(long, short, default_value = "latency")]
+        measure: String,
+        #[arg(long, short)]
+        branch: Option<String>,
+        #[arg(long, short)]
+        testbed: Option<String>,
+        /// Number of recent reports to include
+        #[arg(long, default_value = "100")]
+        limit: i32,
+    },
 }
 
 pub async fn handle(command: ProjectCommands, api_url: &str) -> Result<()> {
     let config = Config::load()?;
-    let client = ApiClient::new(api_url, &config.token);
+    let client = connect(api_url, &config.token);
 
     match command {
         ProjectCommands::List => list(&client).await,
@@ -32,8 +275,309 @@ pub async fn handle(command: ProjectCommands, api_url: &str) -> Result<()> {
             name,
             description,
             public,
-        } => create(&client, &slug, &name, description.as_deref(), public).await,
+            template,
+        } => {
+            create(
+                &client,
+                &slug,
+                &name,
+                description.as_deref(),
+                public,
+                template.as_deref(),
+            )
+            .await
+        }
+        ProjectCommands::Templates => templates(&client).await,
         ProjectCommands::Show { slug } => show(&client, &slug).await,
+        ProjectCommands::Reports {
+            slug,
+            kind,
+            tags,
+            filter,
+            limit,
+        } => reports(&client, &slug, kind, &tags, filter.as_deref(), limit).await,
+        ProjectCommands::Filters { slug } => filters(&client, &slug).await,
+        ProjectCommands::SaveFilter {
+            slug,
+            name,
+            tags,
+            context,
+        } => {
+            if tags.is_empty() && context.is_empty() {
+                bail!("Give the filter at least one --tag or --context");
+            }
+            let filter = client
+                .save_report_filter(&slug, &name, &tags, &context)
+                .await?;
+            println!("Saved report filter {}", filter.name);
+            println!(
+                "  driftwatch project reports {} --filter {}",
+                slug, filter.name
+            );
+            Ok(())
+        }
+        ProjectCommands::RemoveFilter { slug, name } => {
+            if client.delete_report_filter(&slug, &name).await? {
+                println!("Removed report filter {}", name);
+            } else {
+                println!("No report filter {}", name);
+            }
+            Ok(())
+        }
+        ProjectCommands::SetDefaultBranch { slug, branch } => {
+            let branch = client.set_default_branch(&slug, &branch).await?;
+            println!("Reports for HEAD now go to {}", branch);
+            Ok(())
+        }
+        ProjectCommands::AddBranchAlias {
+            slug,
+            alias,
+            branch,
+        } => {
+            let alias = client.set_branch_alias(&slug, &alias, &branch).await?;
+            println!(
+                "Reports for {} now count as reports of {}",
+                alias.alias, alias.branch
+            );
+            Ok(())
+        }
+        ProjectCommands::RemoveBranchAlias { slug, alias } => {
+            if client.remove_branch_alias(&slug, &alias).await? {
+                println!("Removed branch alias {}", alias);
+            } else {
+                println!("No branch alias {}", alias);
+            }
+            Ok(())
+        }
+        ProjectCommands::AddTestbedAlias {
+            slug,
+            alias,
+            testbed,
+        } => {
+            let alias = client.set_testbed_alias(&slug, &alias, &testbed).await?;
+            println!(
+                "Reports for {} now count as reports of {}",
+                alias.alias, alias.testbed
+            );
+            Ok(())
+        }
+        ProjectCommands::RemoveTestbedAlias { slug, alias } => {
+            if client.remove_testbed_alias(&slug, &alias).await? {
+                println!("Removed testbed alias {}", alias);
+            } else {
+                println!("No testbed alias {}", alias);
+            }
+            Ok(())
+        }
+        ProjectCommands::Status {
+            slug,
+            branch,
+            testbed,
+        } => {
+            let web_url = config.web_url(api_url);
+            status(
+                &client,
+                &web_url,
+                &slug,
+                branch.as_deref(),
+                testbed.as_deref(),
+            )
+            .await
+        }
+        ProjectCommands::SyncOwners { slug, file } => sync_owners(&client, &slug, &file).await,
+        ProjectCommands::Noise {
+            slug,
+            testbed,
+            noisy,
+        } => noise(&client, &slug, testbed.as_deref(), noisy).await,
+        ProjectCommands::Annotate {
+            slug,
+            note,
+            kind,
+            report,
+            at,
+        } => {
+            let input = CreateAnnotationInput {
+                project_slug: slug,
+                report_id: report,
+                kind,
+                note,
+                occurred_at: at,
+            };
+            let annotation = client.create_annotation(&input).await?;
+            println!("Added annotation {}", annotation.id);
+            Ok(())
+        }
+        ProjectCommands::Annotations { slug } => annotations(&client, &slug).await,
+        ProjectCommands::Digest {
+            slug,
+            preview,
+            html,
+        } => digest(&client, &slug, preview, html).await,
+        ProjectCommands::Channels { slug } => channels(&client, &slug).await,
+        ProjectCommands::AddChannel {
+            slug,
+            url,
+            kind,
+            events,
+            template,
+        } => {
+            let template = template.map(read_template).transpose()?;
+            let channel = client
+                .add_notification_channel(
+                    &slug,
+                    kind,
+                    &url,
+                    (!events.is_empty()).then_some(&events[..]),
+                    template.as_deref(),
+                )
+                .await?;
+            println!(
+                "Added {} channel {} ({}) for {}",
+                channel.kind,
+                channel.id,
+                channel.target,
+                channel.events.join(", ")
+            );
+            Ok(())
+        }
+        ProjectCommands::UpdateChannel {
+            id,
+            events,
+            template,
+            no_template,
+        } => {
+            let template = match template {
+                Some(path) => Some(read_template(path)?),
+                None if no_template => Some(String::new()),
+                None => None,
+            };
+            let channel = client
+                .update_notification_channel(
+                    &id,
+                    (!events.is_empty()).then_some(&events[..]),
+                    template.as_deref(),
+                )
+                .await?;
+            println!(
+                "Channel {} sends {}{}",
+                channel.id,
+                channel.events.join(", "),
+                if channel.template.is_some() {
+                    " with a template"
+                } else {
+                    ""
+                }
+            );
+            Ok(())
+        }
+        ProjectCommands::TestChannel { id, event } => {
+            let test = client
+                .test_notification_channel(&id, event.as_deref())
+                .await?;
+            match test.error {
+                None if test.delivered => println!("Delivered a test message to {}", id),
+                error => bail!(
+                    "Test delivery failed: {}",
+                    error.unwrap_or_else(|| "unknown error".to_string())
+                ),
+            }
+            Ok(())
+        }
+        ProjectCommands::RemoveChannel { id } => {
+            client.remove_notification_channel(&id).await?;
+            println!("Removed channel {}", id);
+            Ok(())
+        }
+        ProjectCommands::RemoteWriteRules { slug } => remote_write_rules(&client, &slug).await,
+        ProjectCommands::AddRemoteWriteRule {
+            slug,
+            metric,
+            matchers,
+            benchmark,
+            measure,
+            branch,
+            testbed,
+        } => {
+            let input = RemoteWriteRuleInput {
+                metric,
+                matchers,
+                benchmark,
+                measure,
+                branch,
+                testbed,
+            };
+            let rule = client.add_remote_write_rule(&slug, &input).await?;
+            println!("Added remote_write rule {}", rule.id);
+            println!("Point Prometheus' remote_write at:");
+            println!(
+                "  {}/prometheus/{}/write",
+                api_url.trim_end_matches('/'),
+                slug
+            );
+            println!("with an API key as bearer token.");
+            Ok(())
+        }
+        ProjectCommands::RemoveRemoteWriteRule { id } => {
+            client.remove_remote_write_rule(&id).await?;
+            println!("Removed remote_write rule {}", id);
+            Ok(())
+        }
+        ProjectCommands::Releases {
+            slug,
+            measure,
+            benchmark,
+            testbed,
+        } => {
+            releases(
+                &client,
+                &slug,
+                &measure,
+                benchmark.as_deref(),
+                testbed.as_deref(),
+            )
+            .await
+        }
+        ProjectCommands::Scaling {
+            slug,
+            benchmark,
+            measure,
+            branch,
+            testbed,
+            limit,
+        } => {
+            scaling(
+                &client,
+                &slug,
+                &benchmark,
+                &measure,
+                &branch,
+                testbed.as_deref(),
+                limit,
+            )
+            .await
+        }
+        ProjectCommands::Context {
+            slug,
+            benchmark,
+            key,
+            measure,
+            branch,
+            testbed,
+            limit,
+        } => {
+            context(
+                &client,
+                &slug,
+                &benchmark,
+                &measure,
+                &key,
+                branch.as_deref(),
+                testbed.as_deref(),
+                limit,
+            )
+            .await
+        }
     }
 }
 
@@ -65,14 +609,34 @@ async fn create(
     name: &str,
     description: Option<&str>,
     public: bool,
+    template: Option<&str>,
 ) -> Result<()> {
     let project = client
-        .create_project(slug, name, description, public)
+        .create_project(slug, name, description, public, template)
         .await?;
     println!("Created project: {} ({})", project.name, project.slug);
     Ok(())
 }
 
+async fn templates(client: &ApiClient) -> Result<()> {
+    let templates = client.project_templates().await?;
+
+    println!("{:<12} {:<40} MEASURES", "NAME", "DESCRIPTION");
+    println!("{}", "-".repeat(80));
+
+    for template in templates {
+        let measures: Vec<&str> = template.measures.iter().map(|m| m.name.as_str()).collect();
+        println!(
+            "{:<12} {:<40} {}",
+            template.name,
+            template.description,
+            measures.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
 async fn show(client: &ApiClient, slug: &str) -> Result<()> {
     let project = client.get_project(slug).await?;
 
@@ -85,14 +649,36 @@ async fn show(client: &ApiClient, slug: &str) -> Result<()> {
                 println!("Description: {}", desc);
             }
 
+            println!("Default branch: {}", p.default_branch);
+
             println!("\nBranches: {}", p.branches.len());
             for branch in &p.branches {
-                println!("  - {}", branch.name);
+                let aliases: Vec<_> = p
+                    .branch_aliases
+                    .iter()
+                    .filter(|a| a.branch == branch.name)
+                    .map(|a| a.alias.as_str())
+                    .collect();
+                if aliases.is_empty() {
+                    println!("  - {}", branch.name);
+                } else {
+                    println!("  - {} (also {})", branch.name, aliases.join(", "));
+                }
             }
 
             println!("\nTestbeds: {}", p.testbeds.len());
             for testbed in &p.testbeds {
-                println!("  - {}", testbed.name);
+                let aliases: Vec<_> = p
+                    .testbed_aliases
+                    .iter()
+                    .filter(|a| a.testbed == testbed.name)
+                    .map(|a| a.alias.as_str())
+                    .collect();
+                if aliases.is_empty() {
+                    println!("  - {}", testbed.name);
+                } else {
+                    println!("  - {} (also {})", testbed.name, aliases.join(", "));
+                }
             }
 
             println!("\nBenchmarks: {}", p.benchmarks.len());
@@ -107,3 +693,536 @@ async fn show(client: &ApiClient, slug: &str) -> Result<()> {
 
     Ok(())
 }
+
+async fn reports(
+    client: &ApiClient,
+    slug: &str,
+    kind: Option<ReportKind>,
+    tags: &[String],
+    filter: Option<&str>,
+    limit: i32,
+) -> Result<()> {
+    let Some(reports) = client
+        .project_reports(slug, kind, tags, filter, limit)
+        .await?
+    else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if reports.is_empty() {
+        println!("No reports found.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<36} {:<26} {:<16} {:<16} {:<10} {:<8} {:>6} TAGS",
+        "ID", "CREATED", "BRANCH", "TESTBED", "COMMIT", "KIND", "ALERTS"
+    );
+    println!("{}", "-".repeat(133));
+
+    for report in reports {
+        let commit = report
+            .git_hash
+            .as_deref()
+            .map(|h| &h[..h.len().min(8)])
+            .unwrap_or("-");
+        println!(
+            "{:<36} {:<26} {:<16} {:<16} {:<10} {:<8} {:>6} {}",
+            report.id,
+            report.created_at,
+            report.branch.name,
+            report.testbed.name,
+            commit,
+            report.kind,
+            report.alerts.len(),
+            report.tags.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+async fn filters(client: &ApiClient, slug: &str) -> Result<()> {
+    let Some(filters) = client.report_filters(slug).await? else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if filters.is_empty() {
+        println!("No saved report filters. Add one with `driftwatch project save-filter`.");
+        return Ok(());
+    }
+
+    println!("{:<24} {:<30} CONTEXT", "NAME", "TAGS");
+    println!("{}", "-".repeat(80));
+    for filter in filters {
+        let context: Vec<_> = filter
+            .context
+            .iter()
+            .map(|e| format!("{}={}", e.key, e.value))
+            .collect();
+        println!(
+            "{:<24} {:<30} {}",
+            filter.name,
+            filter.tags.join(", "),
+            context.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+async fn sync_owners(client: &ApiClient, slug: &str, file: &PathBuf) -> Result<()> {
+    let content = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read owners file {}", file.display()))?;
+    let rules = parse_owners(&content)?;
+
+    let stored = client.set_benchmark_owners(slug, &rules).await?;
+    println!("Synced {} owner rule(s) to {}", stored, slug);
+    Ok(())
+}
+
+async fn noise(client: &ApiClient, slug: &str, testbed: Option<&str>, noisy: bool) -> Result<()> {
+    let Some(scores) = client.noise_scores(slug, testbed, noisy).await? else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if scores.is_empty() {
+        println!("No noise scores yet.");
+        return Ok(());
+    }
+
+    println!(
+        "{:>8} {:>7} {:<6} {:<20} BENCHMARK",
+        "CV", "SAMPLES", "NOISY", "TESTBED"
+    );
+    println!("{}", "-".repeat(80));
+
+    for score in scores {
+        println!(
+            "{:>7.1}% {:>7} {:<6} {:<20} {}",
+            score.coefficient_of_variation,
+            score.sample_size,
+            if score.noisy { "yes" } else { "" },
+            score.testbed.name,
+            score.benchmark.name
+        );
+    }
+
+    Ok(())
+}
+
+/// A percent change for display, or `-` when there's nothing to compare
+fn format_delta(delta: Option<f64>) -> String {
+    delta
+        .map(|d| format!("{:+.1}%", d))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+async fn status(
+    client: &ApiClient,
+    web_url: &str,
+    slug: &str,
+    branch: Option<&str>,
+    testbed: Option<&str>,
+) -> Result<()> {
+    let Some(overview) = client.project_overview(slug, branch, testbed).await? else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    match overview.open_alerts {
+        0 => println!("No open alerts."),
+        1 => println!(
+            "1 open alert - see `driftwatch alert list --project {}`",
+            slug
+        ),
+        n => println!(
+            "{} open alerts - see `driftwatch alert list --project {}`",
+            n, slug
+        ),
+    }
+    println!();
+
+    if overview.measures.is_empty() {
+        println!("No results yet.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<20} {:>10} {:>8} {:>8} {:>9} {:>8}",
+        "MEASURE", "BENCHMARKS", "7D", "30D", "REGRESSED", "IMPROVED"
+    );
+    println!("{}", "-".repeat(68));
+    for trend in &overview.measures {
+        println!(
+            "{:<20} {:>10} {:>8} {:>8} {:>9} {:>8}",
+            trend.measure,
+            trend.benchmarks,
+            format_delta(trend.mean_delta_7d),
+            format_delta(trend.mean_delta_30d),
+            trend.regressed_30d,
+            trend.improved_30d
+        );
+    }
+    println!();
+
+    println!(
+        "{:<26} {:<20} {:<20} COMMIT",
+        "LAST REPORT", "BRANCH", "TESTBED"
+    );
+    println!("{}", "-".repeat(80));
+    for report in &overview.latest_reports {
+        let commit = report
+            .git_hash
+            .as_deref()
+            .map(|hash| &hash[..hash.len().min(10)])
+            .unwrap_or("-");
+        println!(
+            "{:<26} {:<20} {:<20} {:<10} {}",
+            report.reported_at,
+            report.branch,
+            report.testbed,
+            commit,
+            links::report_url(web_url, slug, &report.report_id)
+        );
+    }
+
+    Ok(())
+}
+
+async fn annotations(client: &ApiClient, slug: &str) -> Result<()> {
+    let Some(annotations) = client.annotations(slug).await? else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if annotations.is_empty() {
+        println!("No annotations found.");
+        return Ok(());
+    }
+
+    println!("{:<26} {:<16} NOTE", "WHEN", "KIND");
+    println!("{}", "-".repeat(80));
+
+    for annotation in annotations {
+        let note = match &annotation.report_id {
+            Some(report_id) => format!("{} (report {})", annotation.note, report_id),
+            None => annotation.note,
+        };
+        println!(
+            "{:<26} {:<16} {}",
+            annotation.occurred_at, annotation.kind, note
+        );
+    }
+
+    Ok(())
+}
+
+async fn digest(client: &ApiClient, slug: &str, preview: bool, html: bool) -> Result<()> {
+    let Some(digests) = client.digests(slug, preview, 1).await? else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    let Some(digest) = digests.into_iter().next() else {
+        println!("No digests yet; one is generated as each week with reports ends.");
+        println!("Use --preview to see this week's so far.");
+        return Ok(());
+    };
+
+    print!("{}", if html { &digest.html } else { &digest.markdown });
+    Ok(())
+}
+
+async fn channels(client: &ApiClient, slug: &str) -> Result<()> {
+    let Some(channels) = client.notification_channels(slug).await? else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if channels.is_empty() {
+        println!("No notification channels found.");
+        return Ok(());
+    }
+
+    println!("{:<38} {:<10} {:<30} EVENTS", "ID", "KIND", "TARGET");
+    println!("{}", "-".repeat(100));
+
+    for channel in channels {
+        println!(
+            "{:<38} {:<10} {:<30} {}{}",
+            channel.id,
+            channel.kind,
+            channel.target,
+            channel.events.join(", "),
+            if channel.template.is_some() {
+                " (template)"
+            } else {
+                ""
+            }
+        );
+    }
+
+    Ok(())
+}
+
+fn read_template(path: PathBuf) -> Result<String> {
+    fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Parse a `--match` argument of the form LABEL=VALUE
+fn parse_label_matcher(arg: &str) -> Result<LabelMatcher, String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() && !value.is_empty() => Ok(LabelMatcher {
+            name: name.trim().to_string(),
+            value: value.to_string(),
+        }),
+        _ => Err(format!("expected LABEL=VALUE, got '{}'", arg)),
+    }
+}
+
+async fn remote_write_rules(client: &ApiClient, slug: &str) -> Result<()> {
+    let Some(rules) = client.remote_write_rules(slug).await? else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if rules.is_empty() {
+        println!("No remote_write rules found.");
+        return Ok(());
+    }
+
+    for rule in rules {
+        let matchers: Vec<String> = rule
+            .matchers
+            .iter()
+            .map(|m| format!("{}=\"{}\"", m.name, m.value))
+            .collect();
+        println!("{}", rule.id);
+        println!("  series:    {}{{{}}}", rule.metric, matchers.join(", "));
+        println!("  benchmark: {} ({})", rule.benchmark, rule.measure);
+        println!("  branch:    {}", rule.branch);
+        println!("  testbed:   {}", rule.testbed);
+    }
+
+    Ok(())
+}
+
+async fn releases(
+    client: &ApiClient,
+    slug: &str,
+    measure: &str,
+    benchmark: Option<&str>,
+    testbed: Option<&str>,
+) -> Result<()> {
+    let Some(points) = client
+        .release_series(slug, measure, benchmark, testbed)
+        .await?
+    else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if points.is_empty() {
+        println!("No reports tagged with a release yet.");
+        println!("Tag one with: driftwatch run --version 1.0.0 ...");
+        return Ok(());
+    }
+
+    println!(
+        "{:<40} {:<16} {:>14} {:>14} {:>14} {:>8}",
+        "BENCHMARK", "VERSION", "MEAN", "MIN", "MAX", "SAMPLES"
+    );
+    println!("{}", "-".repeat(111));
+
+    for point in points {
+        println!(
+            "{:<40} {:<16} {:>14.2} {:>14.2} {:>14.2} {:>8}",
+            point.benchmark,
+            point.version,
+            point.value,
+            point.min_value,
+            point.max_value,
+            point.sample_size
+        );
+    }
+
+    Ok(())
+}
+
+async fn scaling(
+    client: &ApiClient,
+    slug: &str,
+    benchmark: &str,
+    measure: &str,
+    branch: &str,
+    testbed: Option<&str>,
+    limit: i32,
+) -> Result<()> {
+    let Some(curves) = client
+        .scaling_curves(slug, benchmark, measure, branch, testbed, limit)
+        .await?
+    else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if curves.is_empty() {
+        println!("No parameterized results for {} on {}.", benchmark, branch);
+        println!(
+            "Name benchmarks with a numeric last segment, e.g. {}/1000.",
+            benchmark
+        );
+        return Ok(());
+    }
+
+    let parameters: Vec<String> = curves
+        .last()
+        .map(|c| c.points.iter().map(|p| p.parameter.to_string()).collect())
+        .unwrap_or_default();
+    println!(
+        "{} ({}) by parameter: {}",
+        benchmark,
+        measure,
+        parameters.join(", ")
+    );
+    println!();
+    println!("{:<12} {:<20} {:>8}  CURVE", "COMMIT", "DATE", "GROWTH");
+    println!("{}", "-".repeat(60));
+
+    for curve in &curves {
+        let commit = curve
+            .git_hash
+            .as_deref()
+            .map(|h| &h[..h.len().min(10)])
+            .unwrap_or("-");
+        let date = curve.created_at.get(..19).unwrap_or(&curve.created_at);
+        let growth = curve
+            .growth_exponent
+            .map(|e| format!("n^{:.2}", e))
+            .unwrap_or_else(|| "-".to_string());
+        let values: Vec<f64> = curve.points.iter().map(|p| p.value).collect();
+        println!(
+            "{:<12} {:<20} {:>8}  {}",
+            commit,
+            date,
+            growth,
+            sparkline(&values)
+        );
+    }
+
+    let exponents: Vec<f64> = curves.iter().filter_map(|c| c.growth_exponent).collect();
+    if let (Some(first), Some(last)) = (exponents.first(), exponents.last()) {
+        println!();
+        println!(
+            "Growth exponent {:.2} -> {:.2} over {} reports {}",
+            first,
+            last,
+            curves.len(),
+            sparkline(&exponents)
+        );
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn context(
+    client: &ApiClient,
+    slug: &str,
+    benchmark: &str,
+    measure: &str,
+    key: &str,
+    branch: Option<&str>,
+    testbed: Option<&str>,
+    limit: i32,
+) -> Result<()> {
+    let Some(points) = client
+        .context_series(slug, benchmark, measure, key, branch, testbed, limit)
+        .await?
+    else {
+        println!("Project not found: {}", slug);
+        return Ok(());
+    };
+
+    if points.is_empty() {
+        println!("No results for {} ({}).", benchmark, measure);
+        return Ok(());
+    }
+
+    // Points arrive grouped by context value, oldest first within a group
+    let mut groups: Vec<(Option<String>, Vec<f64>)> = Vec::new();
+    for point in points {
+        match groups.last_mut() {
+            Some((value, values)) if *value == point.context_value => values.push(point.value),
+            _ => groups.push((point.context_value, vec![point.value])),
+        }
+    }
+
+    println!("{} ({}) by {}:", benchmark, measure, key);
+    println!();
+    println!(
+        "{:<20} {:>8} {:>14} {:>14}  TREND",
+        "VALUE", "REPORTS", "MEAN", "LATEST"
+    );
+    println!("{}", "-".repeat(76));
+
+    for (value, values) in &groups {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        println!(
+            "{:<20} {:>8} {:>14.2} {:>14.2}  {}",
+            value.as_deref().unwrap_or("(not set)"),
+            values.len(),
+            mean,
+            values.last().copied().unwrap_or_default(),
+            sparkline(values)
+        );
+    }
+
+    Ok(())
+}
+
+/// Renders values as block characters scaled between their min and max
+fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max > min {
+                BARS[(((v - min) / (max - min)) * 7.0).round() as usize]
+            } else {
+                BARS[0]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[1.0, 2.0, 3.0, 8.0]), "▁▂▃█");
+        assert_eq!(sparkline(&[5.0, 5.0]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+
+    #[test]
+    fn test_parse_label_matcher() {
+        let matcher = parse_label_matcher("quantile=0.99").unwrap();
+        assert_eq!(
+            (matcher.name.as_str(), matcher.value.as_str()),
+            ("quantile", "0.99")
+        );
+        assert!(parse_label_matcher("quantile").is_err());
+        assert!(parse_label_matcher("=0.99").is_err());
+        assert!(parse_label_matcher("quantile=").is_err());
+    }
+}
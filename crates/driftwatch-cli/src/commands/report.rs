@@ -0,0 +1,105 @@
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use clap::Subcommand;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+use crate::api::{connect, ApiClient, Config};
+
+/// Largest compressed output the server accepts with a report
+pub const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Leave a report out of the baselines later reports are compared against
+    Exclude { id: String },
+    /// Put an excluded report back into baselines
+    Include { id: String },
+    /// Print the benchmark command output stored with a report
+    Output { id: String },
+    /// Check a report against the project's current thresholds again, e.g.
+    /// after changing them
+    Reevaluate { id: String },
+}
+
+pub async fn handle(command: ReportCommands, api_url: &str) -> Result<()> {
+    let config = Config::load()?;
+    let client = connect(api_url, &config.token);
+
+    let (id, excluded) = match command {
+        ReportCommands::Exclude { id } => (id, true),
+        ReportCommands::Include { id } => (id, false),
+        ReportCommands::Output { id } => return output(&client, &id).await,
+        ReportCommands::Reevaluate { id } => return reevaluate(&client, &id).await,
+    };
+
+    client.set_report_excluded(&id, excluded).await?;
+    if excluded {
+        println!("Excluded report {} from baselines.", id);
+    } else {
+        println!("Restored report {} to baselines.", id);
+    }
+    println!("Open alerts on later reports will be re-evaluated shortly.");
+    Ok(())
+}
+
+async fn reevaluate(client: &ApiClient, id: &str) -> Result<()> {
+    let outcome = client.reevaluate_report(id).await?;
+    println!("Re-evaluated report {} against current thresholds:", id);
+    println!("  {} new alert(s)", outcome.raised);
+    println!("  {} open alert(s) still regressed", outcome.updated);
+    println!("  {} alert(s) resolved", outcome.resolved);
+    Ok(())
+}
+
+async fn output(client: &ApiClient, id: &str) -> Result<()> {
+    let Some(output) = client.report_output(id).await? else {
+        println!("No output stored for report {}.", id);
+        println!("Attach it on submission with: driftwatch run --attach-output ...");
+        return Ok(());
+    };
+
+    eprintln!("Output captured {}", output.created_at);
+    print!("{}", decompress_output(&output.content)?);
+    Ok(())
+}
+
+/// Gzips and base64-encodes command output for `attachReportOutput`
+pub fn compress_output(output: &str) -> Result<String> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(output.as_bytes())?;
+    let compressed = encoder.finish()?;
+    if compressed.len() > MAX_OUTPUT_BYTES {
+        bail!(
+            "Compressed output is {} bytes, over the {} byte limit",
+            compressed.len(),
+            MAX_OUTPUT_BYTES
+        );
+    }
+    Ok(base64::engine::general_purpose::STANDARD.encode(compressed))
+}
+
+fn decompress_output(content: &str) -> Result<String> {
+    let compressed = base64::engine::general_purpose::STANDARD
+        .decode(content)
+        .context("Stored output is not valid base64")?;
+    let mut output = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut output)
+        .context("Stored output is not valid gzip")?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_round_trip() {
+        let output = "Benchmarking fib\nWarning: Found 3 outliers among 100 measurements\n";
+        let content = compress_output(output).unwrap();
+        assert_eq!(decompress_output(&content).unwrap(), output);
+    }
+}
@@ -1,22 +1,55 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::Args;
-use std::path::PathBuf;
-use std::process::Command;
+use driftwatch_api::links;
+use driftwatch_api::storage::MAX_UPLOAD_BYTES;
+use futures_util::{stream, StreamExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::Instant;
 
-use crate::adapters::criterion::parse_criterion_output;
-use crate::api::{ApiClient, Config, MetricInput};
+use crate::adapters::{Adapter, BenchmarkResult};
+use crate::api::{
+    connect, connect_submitter, ApiClient, Config, ContextEntry, CreateReportInput, MetricInput,
+    Report, ReportKind, ShareReportInput, MAX_CONCURRENT_REQUESTS,
+};
+use crate::commands::report;
+use crate::container::{self, Container, ContainerArgs};
+use crate::git;
+use crate::hooks::Hooks;
+use crate::limits::{self, Limits};
+use crate::nix::{self, NixEnv};
+use crate::profiling;
+use crate::routing::Routes;
+use crate::testbed;
+use crate::timing::{self, CommandTimes};
+use crate::tuning::{self, TuningArgs};
 
 #[derive(Args)]
 pub struct RunArgs {
+    /// Project to submit to; with a routing file, where benchmarks that
+    /// match no route go
     #[arg(long, short)]
-    pub project: String,
+    pub project: Option<String>,
 
+    /// File mapping benchmark name prefixes to projects, so one run can
+    /// report to several (defaults to driftwatch.toml when present)
+    #[arg(long, value_name = "FILE")]
+    pub routes: Option<PathBuf>,
+
+    /// Branch to report to; HEAD means the project's default branch
     #[arg(long, short, default_value = "main")]
     pub branch: String,
 
+    /// Machine the benchmarks run on (defaults to the OS and architecture,
+    /// e.g. linux-x86_64)
     #[arg(long, short)]
     pub testbed: Option<String>,
 
+    /// Append the cloud instance type, e.g. c7g.xlarge, to the testbed name,
+    /// read from the EC2, GCE or Azure instance metadata service
+    #[arg(long)]
+    pub testbed_instance_type: bool,
+
     #[arg(long)]
     pub hash: Option<String>,
 
@@ -24,10 +57,143 @@ pub struct RunArgs {
     #[arg(long)]
     pub pr: Option<i32>,
 
+    /// Branch the PR targets; alerts compare against it at the merge-base
+    /// (auto-detected from GITHUB_BASE_REF)
+    #[arg(long)]
+    pub base_branch: Option<String>,
+
+    /// Base commit to compare against instead of the merge-base git finds,
+    /// e.g. the commit a merge queue entry was built on (auto-detected in
+    /// GitHub merge queues)
+    #[arg(long, value_name = "SHA")]
+    pub merge_base: Option<String>,
+
+    /// Commit the benchmarks ran on when it isn't the PR head, e.g. a merge
+    /// queue's temporary merge commit; the benchmark status is posted to it
+    /// (auto-detected in GitHub merge queues)
+    #[arg(long, value_name = "SHA")]
+    pub evaluated_commit: Option<String>,
+
+    /// Release the results belong to, e.g. 1.4.0 (auto-detected from a
+    /// GitHub tag ref)
+    #[arg(long)]
+    pub version: Option<String>,
+
+    /// What triggered the run; PR runs only count toward the same PR's
+    /// baselines (auto-detected from GITHUB_EVENT_NAME or
+    /// CI_PIPELINE_SOURCE)
+    #[arg(long, value_enum)]
+    pub kind: Option<ReportKind>,
+
+    /// Describe the run, e.g. --context rustc=1.80 --context allocator=jemalloc,
+    /// to filter reports and split series by later
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_context_entry)]
+    pub context: Vec<ContextEntry>,
+
+    /// Label the report, e.g. --tag nightly, to list reports by later;
+    /// repeatable
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
     /// Path to flamegraph SVG file(s) to upload with the report
     #[arg(long, value_name = "FILE")]
     pub flamegraph: Vec<PathBuf>,
 
+    /// Run the command once more under `flamegraph` (perf on Linux) and
+    /// upload the flamegraph with the report; skipped with a warning when
+    /// the profiler isn't installed
+    #[arg(long, conflicts_with = "container")]
+    pub profile: bool,
+
+    /// Store the command's stdout and stderr with the report, for
+    /// `driftwatch report output`
+    #[arg(long)]
+    pub attach_output: bool,
+
+    /// Wait for alert evaluation and exit with an error if any alerts were raised
+    #[arg(long)]
+    pub err: bool,
+
+    /// With --err, rerun the benchmarks up to this many times while the server
+    /// holds regressions back for confirmation
+    #[arg(long, default_value = "0", requires = "err")]
+    pub confirm_reruns: u32,
+
+    /// Submit the results of a benchmark command that exits non-zero or
+    /// times out, marking the report partial, instead of failing the run
+    #[arg(long)]
+    pub allow_failure: bool,
+
+    /// Kill the benchmark command, and everything it started, after this
+    /// long, e.g. 90s, 30m or 2h
+    #[arg(long, value_name = "DURATION", value_parser = limits::parse_duration)]
+    pub timeout: Option<std::time::Duration>,
+
+    /// Run the command through the platform shell (sh -c, or cmd /C on
+    /// Windows), its words joined with spaces, e.g. for pipes or &&.
+    /// Otherwise it runs as given, each argument passed on unchanged.
+    #[arg(long)]
+    pub shell: bool,
+
+    /// Don't show the benchmark command's output as it runs; it's still
+    /// printed when no results are found in it or the command fails
+    #[arg(long, short)]
+    pub quiet: bool,
+
+    /// Tool whose output to parse, or exec:<program> to run an external
+    /// parser that prints metric JSON
+    #[arg(long, default_value = "criterion")]
+    pub adapter: Adapter,
+
+    /// Measure the command itself - wall, user and system time and peak
+    /// memory - instead of parsing its output, e.g. to track a release build.
+    /// A failed command's times aren't recorded, so there's nothing for
+    /// --allow-failure to submit
+    #[arg(long, conflicts_with_all = ["container", "allow_failure"])]
+    pub time: bool,
+
+    /// Benchmark name for load-test results (defaults to the target URL),
+    /// or for --time (defaults to the command)
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Benchmarks the run should produce: a file with one name per line, or
+    /// `adapter` to have the command list them first (criterion and
+    /// libtest). Those without results are flagged on the report and fail
+    /// the run with --err.
+    #[arg(long, value_name = "FILE|adapter")]
+    pub manifest: Option<String>,
+
+    /// Command to run before the benchmarks, e.g. to warm caches or start a
+    /// database; may be repeated. Runs after any from driftwatch.toml.
+    #[arg(long, value_name = "COMMAND")]
+    pub pre: Vec<String>,
+
+    /// Command to run after the benchmarks, e.g. to collect system stats;
+    /// may be repeated
+    #[arg(long, value_name = "COMMAND")]
+    pub post: Vec<String>,
+
+    /// Append the hash of the Nix environment the run happens in to the
+    /// testbed name, so a toolchain change starts a new series
+    #[arg(long)]
+    pub nix_testbed: bool,
+
+    #[command(flatten)]
+    pub tuning: TuningArgs,
+
+    #[command(flatten)]
+    pub container: ContainerArgs,
+
+    /// Share the results at a secret link instead of submitting them to a
+    /// project; needs no account on servers that allow anonymous shares.
+    /// The link expires after a while.
+    #[arg(
+        long,
+        conflicts_with_all = ["project", "err", "flamegraph", "profile", "attach_output"]
+    )]
+    pub share: bool,
+
     #[arg(long)]
     pub dry_run: bool,
 
@@ -63,13 +229,502 @@ pub fn detect_pr_number() -> Option<i32> {
         })
 }
 
+/// Where a GitHub merge queue entry is headed, from its branch name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeQueueRef {
+    pub base_branch: String,
+    pub pr_number: i32,
+    /// Base branch commit the entry was built on
+    pub base_sha: String,
+}
+
+/// Parse a merge queue branch from GITHUB_REF environment variable format
+/// e.g., "refs/heads/gh-readonly-queue/main/pr-42-<sha>" -> main, 42, <sha>
+pub fn parse_merge_queue_ref(github_ref: &str) -> Option<MergeQueueRef> {
+    let rest = github_ref
+        .strip_prefix("refs/heads/")
+        .unwrap_or(github_ref)
+        .strip_prefix("gh-readonly-queue/")?;
+    let (base_branch, entry) = rest.rsplit_once('/')?;
+    let (pr_number, base_sha) = entry.strip_prefix("pr-")?.split_once('-')?;
+    let pr_number = pr_number.parse::<i32>().ok().filter(|&n| n > 0)?;
+    if base_branch.is_empty() || base_sha.is_empty() {
+        return None;
+    }
+    Some(MergeQueueRef {
+        base_branch: base_branch.to_string(),
+        pr_number,
+        base_sha: base_sha.to_string(),
+    })
+}
+
+/// Parse a release tag from GITHUB_REF environment variable format
+/// e.g., "refs/tags/v1.4.0" -> Some("v1.4.0")
+pub fn parse_version_from_github_ref(github_ref: &str) -> Option<String> {
+    github_ref
+        .strip_prefix("refs/tags/")
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+}
+
+/// Map a CI trigger, GitHub Actions' GITHUB_EVENT_NAME or GitLab's
+/// CI_PIPELINE_SOURCE, to a report kind
+/// e.g., "pull_request" -> Some(Pr), "schedule" -> Some(Nightly)
+pub fn parse_report_kind(event: &str) -> Option<ReportKind> {
+    match event {
+        "pull_request"
+        | "pull_request_target"
+        | "merge_group"
+        | "merge_request_event"
+        | "external_pull_request_event" => Some(ReportKind::Pr),
+        "push" => Some(ReportKind::Push),
+        "schedule" => Some(ReportKind::Nightly),
+        "release" => Some(ReportKind::Release),
+        "workflow_dispatch" | "web" | "api" | "trigger" => Some(ReportKind::Manual),
+        _ => None,
+    }
+}
+
+/// Detect the report kind from CI environment variables
+pub fn detect_report_kind() -> Option<ReportKind> {
+    ["GITHUB_EVENT_NAME", "CI_PIPELINE_SOURCE"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .and_then(|event| parse_report_kind(&event))
+}
+
+/// Parse a `--context` argument of the form KEY=VALUE
+pub fn parse_context_entry(arg: &str) -> Result<ContextEntry, String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => {
+            Ok(ContextEntry {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+            })
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", arg)),
+    }
+}
+
+/// The command for the platform shell, as `--shell` runs it: its words
+/// joined with spaces, so pipes, `&&` and globs work. Under `prefix`, e.g.
+/// in a container, that's always `sh`.
+pub fn shell_wrapped(command: &[String], prefix: &[String]) -> Vec<String> {
+    let line = command.join(" ");
+    if cfg!(target_os = "windows") && prefix.is_empty() {
+        vec!["cmd".to_string(), "/C".to_string(), line]
+    } else {
+        vec!["sh".to_string(), "-c".to_string(), line]
+    }
+}
+
+/// Run a command line such as a hook through the platform shell
+pub fn execute_shell(line: &str) -> Result<Output> {
+    execute_command(&shell_wrapped(&[line.to_string()], &[]), None)
+}
+
+/// Run a command with its arguments as given, optionally from another directory
+pub fn execute_command(command: &[String], current_dir: Option<&Path>) -> Result<Output> {
+    execute_wrapped(command, current_dir, &[])
+}
+
+/// Like `execute_command`, with the command started under `prefix`, e.g.
+/// `taskset --cpu-list 2-3`, so every process it spawns inherits it
+pub fn execute_wrapped(
+    command: &[String],
+    current_dir: Option<&Path>,
+    prefix: &[String],
+) -> Result<Output> {
+    wrapped_command(command, current_dir, prefix)
+        .output()
+        .context("Failed to execute benchmark command")
+}
+
+/// The process `execute_wrapped` runs, for callers that need to spawn it
+/// themselves. No shell re-parses the arguments, so each reaches the
+/// program as given, spaces and quotes included.
+pub fn wrapped_command(
+    command: &[String],
+    current_dir: Option<&Path>,
+    prefix: &[String],
+) -> Command {
+    let mut words = prefix.iter().chain(command);
+    let mut process = Command::new(words.next().map_or("", String::as_str));
+    process.args(words);
+
+    if let Some(dir) = current_dir {
+        process.current_dir(dir);
+    }
+    process
+}
+
+/// One run of the benchmark command
+struct BenchmarkRun {
+    output: Output,
+    /// The command's own resource usage, with `--time`
+    times: Option<CommandTimes>,
+    /// Killed at `--timeout`; the output is what it printed until then
+    timed_out: bool,
+    /// The output was already shown as the command ran
+    streamed: bool,
+}
+
+impl BenchmarkRun {
+    fn failed(&self) -> bool {
+        self.timed_out || !self.output.status.success()
+    }
+}
+
+/// Runs the benchmark command between its hooks and within its limits,
+/// with `stream` showing its output as it runs. Only the command itself is
+/// timed; the post hooks run even when it couldn't be started.
+fn run_benchmarks(
+    command: &[String],
+    prefix: &[String],
+    hooks: &Hooks,
+    timed: bool,
+    limits: &Limits,
+    stream: bool,
+) -> Result<BenchmarkRun> {
+    hooks.run_pre()?;
+    let started = Instant::now();
+    let run = if timed {
+        timing::execute_timed(command, prefix, limits, stream).map(|(output, times, timed_out)| {
+            BenchmarkRun {
+                output,
+                times: Some(times),
+                timed_out,
+                streamed: stream,
+            }
+        })
+    } else {
+        limits::execute(command, prefix, limits, stream).map(|(output, timed_out)| BenchmarkRun {
+            output,
+            times: None,
+            timed_out,
+            streamed: stream,
+        })
+    };
+    match &run {
+        Ok(run) if run.timed_out => println!(
+            "Benchmarks timed out after {}",
+            limits::format_duration(limits.timeout.unwrap_or_default())
+        ),
+        Ok(_) => println!(
+            "Benchmarks finished in {:.1}s",
+            started.elapsed().as_secs_f64()
+        ),
+        Err(_) => {}
+    }
+    hooks.run_post();
+    run
+}
+
+/// Lists the benchmarks that completed before the command was killed at
+/// the timeout, as far as the adapter can tell from its output
+fn print_timed_out(run: &BenchmarkRun, adapter: &Adapter, name: Option<&str>) {
+    let combined_output = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&run.output.stdout),
+        String::from_utf8_lossy(&run.output.stderr)
+    );
+    let mut completed: Vec<String> = Vec::new();
+    if run.times.is_none() {
+        for result in adapter.parse(&combined_output, name).unwrap_or_default() {
+            if !completed.contains(&result.name) {
+                completed.push(result.name);
+            }
+        }
+    }
+    if completed.is_empty() {
+        eprintln!("No benchmarks completed before the timeout.");
+        return;
+    }
+    eprintln!(
+        "{} benchmark(s) completed before the timeout:",
+        completed.len()
+    );
+    for name in &completed {
+        eprintln!("  {}", name);
+    }
+}
+
+/// Fails the run when the benchmark command exited non-zero or timed out,
+/// unless `allow_failure` lets its results through as a partial report
+fn check_exit_status(run: &BenchmarkRun, allow_failure: bool) -> Result<()> {
+    if !run.failed() {
+        return Ok(());
+    }
+    let failure = if run.timed_out {
+        "timed out".to_string()
+    } else {
+        format!("failed ({})", run.output.status)
+    };
+    if !allow_failure {
+        let mut message = format!(
+            "Benchmark command {}; pass --allow-failure to submit the results it produced",
+            failure
+        );
+        // Streamed output is on the terminal already
+        if !run.streamed {
+            message.push_str(":\n");
+            message.push_str(String::from_utf8_lossy(&run.output.stderr).trim_end());
+        }
+        bail!(message);
+    }
+    eprintln!(
+        "Warning: benchmark command {}; submitting its results as partial\n",
+        failure
+    );
+    Ok(())
+}
+
+/// Results of one run: the command's own resource usage with `--time`,
+/// otherwise whatever the adapter parses from its output
+fn collect_results(
+    adapter: &Adapter,
+    name: Option<&str>,
+    command: &[String],
+    output: &Output,
+    times: Option<&CommandTimes>,
+) -> Result<Vec<BenchmarkResult>> {
+    let Some(times) = times else {
+        let combined_output = format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return adapter.parse(&combined_output, name);
+    };
+
+    // `check_exit_status` has already failed the run if the command failed,
+    // since --time and --allow-failure don't go together
+    let name = name
+        .map(str::to_string)
+        .unwrap_or_else(|| command.join(" "));
+    Ok(times.to_results(&name))
+}
+
+/// Shares a one-off report and prints its link
+async fn share_results(client: &ApiClient, api_url: &str, input: &ShareReportInput) -> Result<()> {
+    println!("Sharing results...");
+    let share = client.share_report(input).await?;
+    println!(
+        "Shared {} result(s). Anyone with this link can see them:",
+        input.metrics.len()
+    );
+    println!("  {}{}", api_url.trim_end_matches('/'), share.path);
+    println!("The link stops working after {}.", share.expires_at);
+    Ok(())
+}
+
+pub fn to_metric_inputs(results: Vec<BenchmarkResult>) -> Vec<MetricInput> {
+    results
+        .into_iter()
+        .map(|r| MetricInput {
+            benchmark: r.name,
+            measure: r.measure,
+            value: r.value,
+            lower_value: r.lower,
+            upper_value: r.upper,
+            reported_change: r.reported_change,
+            outliers: r.outliers,
+        })
+        .collect()
+}
+
+/// Uploads the command output for a report. Output too large to store is
+/// skipped with a warning rather than failing the run.
+async fn attach_output(client: &ApiClient, report_id: &str, output: &str) -> Result<()> {
+    let content = match report::compress_output(output) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Warning: not attaching output: {:#}", e);
+            return Ok(());
+        }
+    };
+    if client.relayed() {
+        client
+            .relay_report_output(report_id, &content)
+            .await
+            .context("Failed to attach command output")?;
+        println!("  Command output queued at the relay");
+        return Ok(());
+    }
+    let redactions = client
+        .attach_report_output(report_id, &content)
+        .await
+        .context("Failed to attach command output")?;
+    match redactions {
+        0 => println!("  Attached command output"),
+        1 => println!("  Attached command output (redacted 1 secret)"),
+        n => println!("  Attached command output (redacted {} secrets)", n),
+    }
+    Ok(())
+}
+
+/// The suite manifest from `--manifest`: names read from a file, or listed
+/// by the benchmark command itself when the source is `adapter`
+fn load_manifest(
+    source: &str,
+    adapter: &Adapter,
+    command: &[String],
+    prefix: &[String],
+    shell: bool,
+) -> Result<Vec<String>> {
+    if source != "adapter" {
+        let content = std::fs::read_to_string(source)
+            .with_context(|| format!("Failed to read manifest {}", source))?;
+        return Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect());
+    }
+
+    let Some(list) = adapter.list_command(command) else {
+        bail!("--manifest adapter needs --adapter criterion or libtest; pass a file instead");
+    };
+    let output = if shell {
+        execute_wrapped(&shell_wrapped(&list, prefix), None, prefix)?
+    } else {
+        execute_wrapped(&list, None, prefix)?
+    };
+    if !output.status.success() {
+        bail!(
+            "Listing benchmarks with `{}` failed ({}):\n{}",
+            list.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(adapter.parse_list(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Benchmarks in `manifest` without a result, in manifest order
+fn missing_benchmarks<'a>(manifest: &'a [String], results: &[BenchmarkResult]) -> Vec<&'a str> {
+    let reported: std::collections::HashSet<&str> =
+        results.iter().map(|r| r.name.as_str()).collect();
+    manifest
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !reported.contains(name))
+        .collect()
+}
+
+/// The part of the manifest routed to `project`
+fn project_manifest(
+    manifest: &[String],
+    routes: &Routes,
+    fallback: Option<&str>,
+    project: &str,
+) -> Vec<String> {
+    manifest
+        .iter()
+        .filter(|name| routes.project_for(name.as_str()).or(fallback) == Some(project))
+        .cloned()
+        .collect()
+}
+
+/// Prints a report's comparison and alerts, with links to them, after
+/// evaluation
+fn print_outcome(report: &Report, web_url: &str, project: &str) {
+    if let Some(c) = &report.comparison {
+        print!(
+            "{} benchmarks: {} regressed, {} improved, {} unchanged, {} new",
+            c.total_benchmarks, c.regressed, c.improved, c.unchanged, c.without_baseline
+        );
+        match c.max_regression {
+            Some(max) => println!(" (worst +{:.1}%)", max),
+            None => println!(),
+        }
+    }
+    if let Some(missing) = report.missing_benchmarks.as_ref().filter(|m| !m.is_empty()) {
+        println!(
+            "{} expected benchmark(s) missing: {}",
+            missing.len(),
+            missing.join(", ")
+        );
+    }
+
+    if report.alerts.is_empty() {
+        if report.unconfirmed_alerts.is_empty() {
+            println!("No alerts.");
+        } else {
+            println!(
+                "No alerts; {} possible regression(s) awaiting confirmation.",
+                report.unconfirmed_alerts.len()
+            );
+        }
+        return;
+    }
+
+    println!("{} alerts generated:", report.alerts.len());
+    for alert in &report.alerts {
+        let direction = if alert.percent_change > 0.0 { "+" } else { "" };
+        let owners = if alert.owners.is_empty() {
+            String::new()
+        } else {
+            format!(" - owners: {}", alert.owners.join(" "))
+        };
+        println!(
+            "  - {}{:.1}% change (baseline: {:.2}){}",
+            direction, alert.percent_change, alert.baseline_value, owners
+        );
+        println!("    {}", links::alert_url(web_url, project, &alert.id));
+    }
+}
+
 pub async fn handle(args: RunArgs, api_url: &str) -> Result<()> {
-    let config = Config::load()?;
-    let client = ApiClient::new(api_url, &config.token);
+    let routes = Routes::load(args.routes.as_deref())?;
+    if routes.is_empty() && args.project.is_none() && !args.share {
+        bail!("--project is required unless a driftwatch.toml routes benchmarks to projects or --share is given");
+    }
 
-    let testbed = args
+    let mut hooks = routes.hooks.clone();
+    hooks.extend(&args.pre, &args.post);
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(_) if args.share => Config::anonymous(),
+        Err(e) => return Err(e),
+    };
+    let client = connect_submitter(api_url, &config);
+    let web_url = config.web_url(api_url);
+
+    let container = Container::prepare(&args.container)?;
+    let mut testbed = args.testbed.clone().unwrap_or_else(testbed::default_name);
+    if args.testbed_instance_type {
+        match testbed::instance_type().await {
+            Some(instance_type) => testbed = testbed::with_instance_type(&testbed, &instance_type),
+            None => eprintln!(
+                "Warning: --testbed-instance-type found no cloud instance metadata; using the testbed as is"
+            ),
+        }
+    }
+    // The series this run would have continued before the default testbed
+    // included the architecture
+    let legacy_testbed = args
         .testbed
-        .unwrap_or_else(|| std::env::consts::OS.to_string());
+        .is_none()
+        .then(|| testbed::legacy_name().to_string());
+    let nix_env = NixEnv::detect();
+    let suffixed = |testbed: String| {
+        let testbed = match &container {
+            Some(container) => container::testbed_name(&testbed, &container.digest),
+            None => testbed,
+        };
+        match &nix_env {
+            Some(env) if args.nix_testbed => nix::testbed_name(&testbed, env),
+            _ => testbed,
+        }
+    };
+    let legacy_testbed = legacy_testbed.map(&suffixed);
+    let testbed = suffixed(testbed);
+    if args.nix_testbed && nix_env.is_none() {
+        eprintln!("Warning: --nix-testbed given outside a Nix shell; using the testbed as is");
+    }
 
     let git_hash = args.hash.or_else(|| {
         Command::new("git")
@@ -80,56 +735,218 @@ pub async fn handle(args: RunArgs, api_url: &str) -> Result<()> {
             .map(|s| s.trim().to_string())
     });
 
+    let commit = git_hash
+        .as_deref()
+        .and_then(|hash| git::commit_info(hash, None));
+
+    // A merge queue runs on a temporary merge commit; its branch name says
+    // which PR it is and the base commit it was built on
+    let merge_queue = std::env::var("GITHUB_REF")
+        .ok()
+        .and_then(|r| parse_merge_queue_ref(&r));
+    let evaluated_commit = args.evaluated_commit.or_else(|| {
+        merge_queue.as_ref().and_then(|_| {
+            std::env::var("GITHUB_SHA")
+                .ok()
+                .filter(|sha| !sha.is_empty())
+                .or_else(|| git_hash.clone())
+        })
+    });
+
     // Auto-detect PR number from GitHub Actions environment
-    let pr_number = args.pr.or_else(detect_pr_number);
+    let pr_number = args
+        .pr
+        .or(merge_queue.as_ref().map(|mq| mq.pr_number))
+        .or_else(detect_pr_number);
+
+    let base_branch = args
+        .base_branch
+        .or_else(|| merge_queue.as_ref().map(|mq| mq.base_branch.clone()))
+        .or_else(|| {
+            std::env::var("GITHUB_BASE_REF")
+                .ok()
+                .filter(|b| !b.is_empty())
+        });
+    let merge_base_hash = args
+        .merge_base
+        .or_else(|| merge_queue.map(|mq| mq.base_sha))
+        .or_else(|| {
+            let head = evaluated_commit.as_deref().or(git_hash.as_deref())?;
+            git::merge_base(base_branch.as_deref()?, head, None)
+        });
+
+    let version = args.version.clone().or_else(|| {
+        std::env::var("GITHUB_REF")
+            .ok()
+            .and_then(|r| parse_version_from_github_ref(&r))
+    });
+    // Pushing a tag is a release
+    let kind = args.kind.or_else(|| match detect_report_kind() {
+        Some(ReportKind::Push) if version.is_some() => Some(ReportKind::Release),
+        None if pr_number.is_some() => Some(ReportKind::Pr),
+        kind => kind,
+    });
 
     println!("Running benchmarks...");
-    println!("  Project: {}", args.project);
+    match &args.project {
+        _ if args.share => println!("  Project: none, sharing at a secret link"),
+        Some(project) if routes.is_empty() => println!("  Project: {}", project),
+        Some(project) => println!("  Projects: routed by prefix, otherwise {}", project),
+        None => println!("  Projects: routed by prefix"),
+    }
     println!("  Branch: {}", args.branch);
     println!("  Testbed: {}", testbed);
     if let Some(ref hash) = git_hash {
         println!("  Git hash: {}", hash);
     }
+    if let Some(ref commit) = commit {
+        println!(
+            "  Commit: {} ({})",
+            commit.message.lines().next().unwrap_or_default(),
+            commit.author
+        );
+    }
+    if let Some(ref base) = base_branch {
+        match merge_base_hash {
+            Some(ref mb) => println!("  Base: {} (merge-base {})", base, &mb[..mb.len().min(10)]),
+            None => println!("  Base: {}", base),
+        }
+    }
     if let Some(pr) = pr_number {
         println!("  PR: #{}", pr);
     }
+    if let Some(kind) = kind {
+        println!("  Kind: {}", format!("{:?}", kind).to_lowercase());
+    }
+    if let Some(ref sha) = evaluated_commit {
+        println!("  Evaluated commit: {}", sha);
+    }
+    // In a container the cores are pinned with --cpuset-cpus rather than taskset
+    let mut tuning_args = args.tuning.clone();
+    let container_cpus = match container {
+        Some(_) => tuning_args.cpus.take(),
+        None => None,
+    };
+    // Held until the run ends, when machine-wide settings are restored
+    let tuned = tuning_args.apply()?;
+    let mut applied = tuned.context.clone();
+    let prefix = match &container {
+        Some(container) => {
+            let workspace = std::env::current_dir()?;
+            let raise_priority = args.tuning.nice.is_some_and(|n| n < 0);
+            let mut prefix = container.prefix(&workspace, container_cpus.as_ref(), raise_priority);
+            prefix.extend(tuned.prefix.iter().cloned());
+            if let Some(cpus) = &container_cpus {
+                applied.push(ContextEntry {
+                    key: "cpus".to_string(),
+                    value: tuning::format_cpu_list(&cpus.0),
+                });
+            }
+            applied.extend(container.context());
+            prefix
+        }
+        None => tuned.prefix.clone(),
+    };
+    if let Some(env) = &nix_env {
+        applied.extend(env.context());
+    }
+    let command = if args.shell {
+        shell_wrapped(&args.command, &prefix)
+    } else {
+        args.command.clone()
+    };
+    // In a container the runtime enforces --memory for the whole command
+    let limits = Limits {
+        timeout: args.timeout,
+        memory: match (&container, &args.container.memory) {
+            (None, Some(memory)) => {
+                if cfg!(not(unix)) {
+                    eprintln!("Warning: --memory only applies in a container on this platform");
+                }
+                applied.push(ContextEntry {
+                    key: "memory_limit".to_string(),
+                    value: memory.clone(),
+                });
+                Some(limits::memory_bytes(memory).map_err(anyhow::Error::msg)?)
+            }
+            _ => None,
+        },
+    };
+    let context = tuning::merge_context(&args.context, &applied);
+    for entry in &context {
+        println!("  Context: {}={}", entry.key, entry.value);
+    }
+    if !args.tags.is_empty() {
+        println!("  Tags: {}", args.tags.join(", "));
+    }
     if !args.flamegraph.is_empty() {
         println!("  Flamegraphs: {} file(s)", args.flamegraph.len());
     }
+    let profile = args.profile
+        && match profiling::unavailable() {
+            Some(reason) => {
+                eprintln!("Warning: not profiling: {}", reason);
+                false
+            }
+            None => true,
+        };
+    if profile {
+        println!("  Profile: flamegraph, in an extra run");
+    }
+    if let Some(timeout) = limits.timeout {
+        println!("  Timeout: {}", limits::format_duration(timeout));
+    }
+    let manifest = match &args.manifest {
+        Some(source) => {
+            let manifest =
+                load_manifest(source, &args.adapter, &args.command, &prefix, args.shell)?;
+            println!("  Manifest: {} benchmarks", manifest.len());
+            manifest
+        }
+        None => Vec::new(),
+    };
+    if !hooks.is_empty() {
+        println!(
+            "  Hooks: {} pre, {} post",
+            hooks.pre.len(),
+            hooks.post.len()
+        );
+    }
     println!();
 
-    let cmd = args.command.join(" ");
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", &cmd])
-            .output()
-            .context("Failed to execute benchmark command")?
-    } else {
-        Command::new("sh")
-            .args(["-c", &cmd])
-            .output()
-            .context("Failed to execute benchmark command")?
-    };
+    let run = run_benchmarks(&command, &prefix, &hooks, args.time, &limits, !args.quiet)?;
+    let output = &run.output;
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let stderr = String::from_utf8_lossy(&output.stderr);
 
     let combined_output = format!("{}\n{}", stdout, stderr);
 
-    let results = parse_criterion_output(&combined_output);
+    if run.timed_out {
+        print_timed_out(&run, &args.adapter, args.name.as_deref());
+    }
+    check_exit_status(&run, args.allow_failure)?;
+    let results = collect_results(
+        &args.adapter,
+        args.name.as_deref(),
+        &args.command,
+        output,
+        run.times.as_ref(),
+    )?;
 
     if results.is_empty() {
         println!("No benchmark results found in output.");
-        println!("Make sure you're running Criterion benchmarks.");
-        if !stdout.is_empty() {
+        println!("Make sure --adapter matches the tool the command runs.");
+        if !run.streamed && !stdout.is_empty() {
             println!("\nStdout:\n{}", stdout);
         }
-        if !stderr.is_empty() {
+        if !run.streamed && !stderr.is_empty() {
             println!("\nStderr:\n{}", stderr);
         }
         return Ok(());
     }
 
+    let missing = missing_benchmarks(&manifest, &results);
     println!("Found {} benchmark results:", results.len());
     for result in &results {
         let lower = result
@@ -141,58 +958,149 @@ pub async fn handle(args: RunArgs, api_url: &str) -> Result<()> {
             .map(|v| format!("{:.2}", v))
             .unwrap_or_default();
         println!(
-            "  {} : {:.2} ns [{} - {}]",
-            result.name, result.value, lower, upper
+            "  {} ({}) : {:.2} [{} - {}]",
+            result.name, result.measure, result.value, lower, upper
         );
     }
     println!();
+    if !missing.is_empty() {
+        eprintln!(
+            "Warning: {} expected benchmark(s) produced no results:",
+            missing.len()
+        );
+        for name in &missing {
+            eprintln!("  {}", name);
+        }
+        eprintln!();
+    }
+
+    if args.share {
+        if args.dry_run {
+            println!("Dry run - not sharing results.");
+            return Ok(());
+        }
+        let input = ShareReportInput {
+            name: None,
+            branch: args.branch.clone(),
+            testbed,
+            git_hash,
+            metrics: to_metric_inputs(results),
+        };
+        return share_results(&connect(api_url, &config.token), api_url, &input).await;
+    }
+
+    let (groups, unrouted) = routes.split(results, args.project.as_deref());
+    if !routes.is_empty() {
+        println!("Routed to {} project(s):", groups.len());
+        for (project, results) in &groups {
+            println!("  {}: {} result(s)", project, results.len());
+        }
+        println!();
+    }
+    if !unrouted.is_empty() {
+        eprintln!(
+            "Warning: skipping {} result(s) that match no route:",
+            unrouted.len()
+        );
+        for result in &unrouted {
+            eprintln!("  {}", result.name);
+        }
+    }
+    if groups.len() > 1 && (!args.flamegraph.is_empty() || profile) {
+        bail!(
+            "--flamegraph and --profile need every result to go to one project, but they span {}",
+            groups.len()
+        );
+    }
 
     if args.dry_run {
         println!("Dry run - not submitting results.");
         return Ok(());
     }
+    if groups.is_empty() {
+        println!("No results to submit.");
+        return Ok(());
+    }
+    // A relay can't look projects up, and only real submissions start series
+    if let Some(legacy) = legacy_testbed.as_deref().filter(|_| !client.relayed()) {
+        for project in groups.keys() {
+            if let Ok(Some(details)) = client.get_project(project).await {
+                if let Some(warning) = testbed::transition_warning(&details, &testbed, legacy) {
+                    eprintln!("Warning: {}", warning);
+                }
+            }
+        }
+    }
 
-    let metrics: Vec<MetricInput> = results
-        .into_iter()
-        .map(|r| MetricInput {
-            benchmark: r.name,
-            measure: "latency".to_string(),
-            value: r.value,
-            lower_value: r.lower,
-            upper_value: r.upper,
-        })
-        .collect();
+    let input = CreateReportInput {
+        project_slug: String::new(),
+        branch: args.branch.clone(),
+        testbed,
+        git_hash,
+        pr_number,
+        commit_message: commit.as_ref().map(|c| c.message.clone()),
+        commit_author: commit.as_ref().map(|c| c.author.clone()),
+        committed_at: commit.map(|c| c.committed_at),
+        base_branch,
+        merge_base_hash,
+        evaluated_commit,
+        version,
+        kind,
+        exit_code: output.status.code(),
+        partial: Some(run.failed()),
+        created_at: None,
+        context,
+        tags: args.tags.clone(),
+        expected_benchmarks: Vec::new(),
+        id: None,
+        metrics: Vec::new(),
+    };
 
-    println!("Submitting results...");
-    let report = client
-        .create_report(
-            &args.project,
-            &args.branch,
-            &testbed,
-            git_hash.as_deref(),
-            pr_number,
-            metrics,
-        )
-        .await?;
-
-    println!("Report submitted: {}", report.id);
-
-    if !report.alerts.is_empty() {
-        println!("\n{} alerts generated:", report.alerts.len());
-        for alert in &report.alerts {
-            let direction = if alert.percent_change > 0.0 { "+" } else { "" };
-            println!(
-                "  - {}{:.1}% change (baseline: {:.2})",
-                direction, alert.percent_change, alert.baseline_value
-            );
+    let mut flamegraphs = args.flamegraph.clone();
+    if profile {
+        println!("Profiling benchmarks...");
+        match profiling::capture(&command, &prefix, &hooks) {
+            Ok(path) => flamegraphs.push(path),
+            Err(e) => eprintln!("Warning: no flamegraph from this run: {:#}", e),
         }
+        println!();
     }
 
-    // Upload flamegraphs if provided
-    if !args.flamegraph.is_empty() {
-        println!("\nUploading {} flamegraph(s)...", args.flamegraph.len());
+    println!("Submitting results...");
+    let mut reports = Vec::new();
+    for (project, results) in groups {
+        let report = client
+            .submit_report(CreateReportInput {
+                project_slug: project.clone(),
+                expected_benchmarks: project_manifest(
+                    &manifest,
+                    &routes,
+                    args.project.as_deref(),
+                    &project,
+                ),
+                metrics: to_metric_inputs(results),
+                ..input.clone()
+            })
+            .await?;
 
-        for flamegraph_path in &args.flamegraph {
+        if client.relayed() {
+            println!("Report queued at the relay for {}: {}", project, report.id);
+        } else {
+            println!("Report submitted to {}: {}", project, report.id);
+        }
+        println!("  {}", links::report_url(&web_url, &project, &report.id));
+        if args.attach_output {
+            attach_output(&client, &report.id, &combined_output).await?;
+        }
+        reports.push((project, report.id));
+    }
+
+    // Upload flamegraphs if provided; they only go with a single report
+    if !flamegraphs.is_empty() {
+        let (project, report_id) = &reports[0];
+        println!("\nUploading {} flamegraph(s)...", flamegraphs.len());
+        let mut uploads = Vec::new();
+        for flamegraph_path in &flamegraphs {
             // Validate file exists and is SVG
             if !flamegraph_path.exists() {
                 eprintln!(
@@ -211,9 +1119,16 @@ pub async fn handle(args: RunArgs, api_url: &str) -> Result<()> {
                 .context("Failed to read flamegraph file metadata")?;
             let file_size = metadata.len() as i64;
 
+            if file_size == 0 {
+                eprintln!(
+                    "Warning: Flamegraph file is empty: {}",
+                    flamegraph_path.display()
+                );
+                continue;
+            }
+
             // Check file size (10 MiB limit)
-            const MAX_FILE_SIZE: i64 = 10 * 1024 * 1024;
-            if file_size > MAX_FILE_SIZE {
+            if file_size > MAX_UPLOAD_BYTES {
                 eprintln!(
                     "Warning: Flamegraph file too large ({}MB > 10MB limit): {}",
                     file_size / 1024 / 1024,
@@ -222,34 +1137,150 @@ pub async fn handle(args: RunArgs, api_url: &str) -> Result<()> {
                 continue;
             }
 
-            // Get signed upload URL
-            let upload_url = client
-                .get_flamegraph_upload_url(&args.project, file_name)
-                .await
-                .context("Failed to get flamegraph upload URL")?;
-
-            // Upload file to storage
-            client
-                .upload_flamegraph_file(&upload_url.signed_url, flamegraph_path)
-                .await
-                .context("Failed to upload flamegraph file")?;
-
-            // Confirm upload and link to report
-            let flamegraph = client
-                .confirm_flamegraph_upload(
-                    &report.id,
-                    &upload_url.storage_path,
-                    file_name,
-                    file_size,
-                    None, // No specific benchmark association
-                )
-                .await
-                .context("Failed to confirm flamegraph upload")?;
-
-            println!("  Uploaded: {} ({})", file_name, flamegraph.id);
+            uploads.push((flamegraph_path.as_path(), file_name, file_size));
+        }
+
+        // Upload a few at a time, reporting them in the order given
+        let client = &client;
+        let mut uploaded = stream::iter(uploads)
+            .map(|(path, file_name, file_size)| async move {
+                if client.relayed() {
+                    client
+                        .relay_flamegraph(project, report_id, path, file_name)
+                        .await?;
+                    return anyhow::Ok((file_name, None));
+                }
+                let uploaded = client
+                    .upload_flamegraph(project, report_id, path, file_name, file_size)
+                    .await?;
+                anyhow::Ok((file_name, Some(uploaded)))
+            })
+            .buffered(MAX_CONCURRENT_REQUESTS);
+        while let Some(result) = uploaded.next().await {
+            match result? {
+                (file_name, None) => println!("  Queued at the relay: {}", file_name),
+                (file_name, Some((flamegraph, true))) => {
+                    println!("  Already stored: {} ({})", file_name, flamegraph.id)
+                }
+                (file_name, Some((flamegraph, false))) => {
+                    println!("  Uploaded: {} ({})", file_name, flamegraph.id)
+                }
+            }
         }
     }
+    if profile {
+        let _ = std::fs::remove_dir_all(profiling::output_dir());
+    }
+
+    if !args.err {
+        println!("\nAlerts are evaluated in the background; pass --err to wait for them.");
+        return Ok(());
+    }
+
+    println!("\nWaiting for alert evaluation...");
+    let (projects, report_ids): (Vec<_>, Vec<_>) = reports.into_iter().unzip();
+    let mut evaluated: Vec<_> = projects
+        .into_iter()
+        .zip(client.wait_for_evaluations(&report_ids).await?)
+        .collect();
+
+    // The project only raises an alert once a regression reproduces, so give
+    // it the chance to before deciding the outcome
+    let mut reruns = 0;
+    while evaluated.iter().all(|(_, r)| r.alerts.is_empty())
+        && evaluated
+            .iter()
+            .any(|(_, r)| !r.unconfirmed_alerts.is_empty())
+        && reruns < args.confirm_reruns
+    {
+        reruns += 1;
+        let unconfirmed: usize = evaluated
+            .iter()
+            .map(|(_, r)| r.unconfirmed_alerts.len())
+            .sum();
+        println!(
+            "{} possible regression(s) need confirmation, rerunning benchmarks ({}/{})...",
+            unconfirmed, reruns, args.confirm_reruns
+        );
+
+        let run = run_benchmarks(&command, &prefix, &hooks, args.time, &limits, !args.quiet)?;
+        let combined_output = format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&run.output.stdout),
+            String::from_utf8_lossy(&run.output.stderr)
+        );
+        if run.timed_out {
+            print_timed_out(&run, &args.adapter, args.name.as_deref());
+        }
+        check_exit_status(&run, args.allow_failure)?;
+        let results = collect_results(
+            &args.adapter,
+            args.name.as_deref(),
+            &args.command,
+            &run.output,
+            run.times.as_ref(),
+        )?;
+        let (mut groups, _) = routes.split(results, args.project.as_deref());
+
+        // Only projects still waiting on confirmation need another report
+        let mut reruns_submitted = Vec::new();
+        for (i, (project, report)) in evaluated.iter().enumerate() {
+            if report.unconfirmed_alerts.is_empty() {
+                continue;
+            }
+            let Some(results) = groups.remove(project.as_str()) else {
+                bail!("Rerun produced no benchmark results for {}", project);
+            };
+
+            let rerun = client
+                .submit_report(CreateReportInput {
+                    project_slug: project.clone(),
+                    expected_benchmarks: project_manifest(
+                        &manifest,
+                        &routes,
+                        args.project.as_deref(),
+                        project,
+                    ),
+                    exit_code: run.output.status.code(),
+                    partial: Some(run.failed()),
+                    metrics: to_metric_inputs(results),
+                    ..input.clone()
+                })
+                .await?;
+            println!("Report submitted to {}: {}", project, rerun.id);
+            println!("  {}", links::report_url(&web_url, project, &rerun.id));
+            if args.attach_output {
+                attach_output(&client, &rerun.id, &combined_output).await?;
+            }
+            reruns_submitted.push((i, rerun.id));
+        }
+
+        let (indices, rerun_ids): (Vec<_>, Vec<_>) = reruns_submitted.into_iter().unzip();
+        for (i, report) in indices
+            .into_iter()
+            .zip(client.wait_for_evaluations(&rerun_ids).await?)
+        {
+            evaluated[i].1 = report;
+        }
+    }
+
+    let mut alerts = 0;
+    let mut missing = 0;
+    for (project, report) in &evaluated {
+        if evaluated.len() > 1 {
+            println!("\n{}:", project);
+        }
+        print_outcome(report, &web_url, project);
+        alerts += report.alerts.len();
+        missing += report.missing_benchmarks.as_ref().map_or(0, Vec::len);
+    }
 
+    if alerts > 0 {
+        bail!("{} alert(s) raised", alerts);
+    }
+    if missing > 0 {
+        bail!("{} expected benchmark(s) produced no results", missing);
+    }
     Ok(())
 }
 
@@ -257,6 +1288,59 @@ pub async fn handle(args: RunArgs, api_url: &str) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_time_rejects_allow_failure() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            run: RunArgs,
+        }
+
+        let parse = |flags: &[&str]| {
+            let args = ["driftwatch", "--project", "p"]
+                .iter()
+                .chain(flags)
+                .chain(&["--", "make"]);
+            <Cli as clap::Parser>::try_parse_from(args)
+        };
+        assert!(parse(&["--time"]).unwrap().run.time);
+        assert!(parse(&["--allow-failure"]).unwrap().run.allow_failure);
+        assert!(parse(&["--time", "--allow-failure"]).is_err());
+    }
+
+    #[test]
+    fn test_shell_wrapped() {
+        let command: Vec<String> = ["cargo", "bench", "|", "tee", "out.txt"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let container = vec!["docker".to_string(), "run".to_string()];
+        assert_eq!(
+            shell_wrapped(&command, &container),
+            ["sh", "-c", "cargo bench | tee out.txt"]
+        );
+        let shell = if cfg!(target_os = "windows") {
+            ["cmd", "/C"]
+        } else {
+            ["sh", "-c"]
+        };
+        assert_eq!(shell_wrapped(&command, &[])[..2], shell);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_command_passes_arguments_as_given() {
+        let command: Vec<String> = ["printf", "%s|", "two words", "$HOME", "a\"b"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let output = execute_command(&command, None).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "two words|$HOME|a\"b|"
+        );
+    }
+
     #[test]
     fn test_parse_pr_from_github_ref_valid() {
         assert_eq!(parse_pr_from_github_ref("refs/pull/123/merge"), Some(123));
@@ -277,6 +1361,89 @@ mod tests {
         assert_eq!(parse_pr_from_github_ref("refs/pull/abc/merge"), None);
     }
 
+    #[test]
+    fn test_parse_merge_queue_ref() {
+        let sha = "4f1c2a9e0b7d3c6a5f8e2d1b0a9c8e7f6d5c4b3a";
+        assert_eq!(
+            parse_merge_queue_ref(&format!("refs/heads/gh-readonly-queue/main/pr-42-{}", sha)),
+            Some(MergeQueueRef {
+                base_branch: "main".to_string(),
+                pr_number: 42,
+                base_sha: sha.to_string(),
+            })
+        );
+        // Base branches may contain slashes
+        assert_eq!(
+            parse_merge_queue_ref(&format!("gh-readonly-queue/release/1.x/pr-7-{}", sha))
+                .map(|mq| mq.base_branch),
+            Some("release/1.x".to_string())
+        );
+        assert_eq!(parse_merge_queue_ref("refs/heads/main"), None);
+        assert_eq!(parse_merge_queue_ref("refs/pull/42/merge"), None);
+        assert_eq!(
+            parse_merge_queue_ref("refs/heads/gh-readonly-queue/main/pr-x-abc"),
+            None
+        );
+        assert_eq!(
+            parse_merge_queue_ref("refs/heads/gh-readonly-queue/main/pr-42-"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_context_entry() {
+        assert_eq!(
+            parse_context_entry("allocator=jemalloc"),
+            Ok(ContextEntry {
+                key: "allocator".to_string(),
+                value: "jemalloc".to_string()
+            })
+        );
+        assert_eq!(
+            parse_context_entry("flags=-C target-cpu=native")
+                .unwrap()
+                .value,
+            "-C target-cpu=native"
+        );
+        assert!(parse_context_entry("allocator").is_err());
+        assert!(parse_context_entry("=jemalloc").is_err());
+        assert!(parse_context_entry("allocator=").is_err());
+    }
+
+    #[test]
+    fn test_parse_version_from_github_ref() {
+        assert_eq!(
+            parse_version_from_github_ref("refs/tags/v1.4.0"),
+            Some("v1.4.0".to_string())
+        );
+        assert_eq!(
+            parse_version_from_github_ref("refs/tags/release/2024.1"),
+            Some("release/2024.1".to_string())
+        );
+        assert_eq!(parse_version_from_github_ref("refs/tags/"), None);
+        assert_eq!(parse_version_from_github_ref("refs/heads/main"), None);
+        assert_eq!(parse_version_from_github_ref("refs/pull/123/merge"), None);
+    }
+
+    #[test]
+    fn test_parse_report_kind() {
+        assert_eq!(parse_report_kind("pull_request"), Some(ReportKind::Pr));
+        assert_eq!(parse_report_kind("merge_group"), Some(ReportKind::Pr));
+        assert_eq!(
+            parse_report_kind("merge_request_event"),
+            Some(ReportKind::Pr)
+        );
+        assert_eq!(parse_report_kind("push"), Some(ReportKind::Push));
+        assert_eq!(parse_report_kind("schedule"), Some(ReportKind::Nightly));
+        assert_eq!(parse_report_kind("release"), Some(ReportKind::Release));
+        assert_eq!(
+            parse_report_kind("workflow_dispatch"),
+            Some(ReportKind::Manual)
+        );
+        assert_eq!(parse_report_kind("issue_comment"), None);
+        assert_eq!(parse_report_kind(""), None);
+    }
+
     #[test]
     fn test_parse_pr_from_github_ref_edge_cases() {
         // Not starting with refs/pull/
@@ -295,4 +1462,38 @@ mod tests {
         // Zero (should be rejected - PR numbers start at 1)
         assert_eq!(parse_pr_from_github_ref("refs/pull/0/merge"), None);
     }
+
+    #[test]
+    fn test_missing_benchmarks() {
+        let manifest: Vec<String> = ["fib 10", "fib 20", "parse"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let results = [
+            BenchmarkResult::new("fib 10", "latency", 1.0),
+            BenchmarkResult::new("parse", "latency", 2.0),
+            BenchmarkResult::new("parse", "throughput", 3.0),
+            BenchmarkResult::new("unlisted", "latency", 4.0),
+        ];
+        assert_eq!(missing_benchmarks(&manifest, &results), ["fib 20"]);
+        assert!(missing_benchmarks(&[], &results).is_empty());
+    }
+
+    #[test]
+    fn test_project_manifest() {
+        let routes = Routes::parse("[projects]\n\"parser/\" = \"parser\"\n").unwrap();
+        let manifest: Vec<String> = ["parser/json", "fib", "parser/xml"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            project_manifest(&manifest, &routes, Some("core"), "parser"),
+            ["parser/json", "parser/xml"]
+        );
+        assert_eq!(
+            project_manifest(&manifest, &routes, Some("core"), "core"),
+            ["fib"]
+        );
+        assert!(project_manifest(&manifest, &routes, None, "core").is_empty());
+    }
 }
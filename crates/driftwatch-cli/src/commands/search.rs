@@ -0,0 +1,40 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::api::{connect, Config};
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Case-insensitive substring of the benchmark name
+    pub pattern: String,
+
+    /// Maximum number of series to show
+    #[arg(long, short)]
+    pub limit: Option<i32>,
+}
+
+pub async fn handle(args: SearchArgs, api_url: &str) -> Result<()> {
+    let config = Config::load()?;
+    let client = connect(api_url, &config.token);
+
+    let matches = client.search_benchmarks(&args.pattern, args.limit).await?;
+    if matches.is_empty() {
+        println!("No benchmarks matching \"{}\".", args.pattern);
+        return Ok(());
+    }
+
+    println!(
+        "{:<20} {:<40} {:<16} {:<16} {:<16} {:>14}",
+        "PROJECT", "BENCHMARK", "MEASURE", "BRANCH", "TESTBED", "LATEST"
+    );
+    println!("{}", "-".repeat(127));
+
+    for hit in matches {
+        println!(
+            "{:<20} {:<40} {:<16} {:<16} {:<16} {:>14.2}",
+            hit.project_slug, hit.benchmark, hit.measure, hit.branch, hit.testbed, hit.latest_value
+        );
+    }
+
+    Ok(())
+}
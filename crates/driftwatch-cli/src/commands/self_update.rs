@@ -0,0 +1,284 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use std::path::Path;
+
+use crate::api::{connect, Config};
+use crate::release_signing::{self, Verification, CHECKSUM_SUFFIX, SIGNATURE_SUFFIX};
+
+/// Where release binaries are published; `{version}` and `{asset}` are
+/// filled in
+const DEFAULT_RELEASE_URL: &str = concat!(
+    env!("CARGO_PKG_REPOSITORY"),
+    "/releases/download/v{version}/{asset}"
+);
+
+/// Where releases come from and the key they're signed with
+#[derive(Args)]
+pub struct ReleaseSource {
+    /// URL template for release binaries, with {version} and {asset}
+    /// placeholders
+    #[arg(long, env = "DRIFTWATCH_RELEASE_URL", default_value = DEFAULT_RELEASE_URL)]
+    pub release_url: String,
+
+    /// Base64 Ed25519 key releases are signed with (defaults to the key
+    /// this binary was built with)
+    #[arg(long, env = "DRIFTWATCH_RELEASE_PUBLIC_KEY")]
+    pub public_key: Option<String>,
+}
+
+impl ReleaseSource {
+    pub fn public_key(&self) -> Option<&str> {
+        self.public_key
+            .as_deref()
+            .or(release_signing::EMBEDDED_PUBLIC_KEY)
+            .filter(|key| !key.trim().is_empty())
+    }
+}
+
+#[derive(Args)]
+pub struct SelfUpdateArgs {
+    /// Version to install (defaults to the server's version)
+    #[arg(long, value_name = "VERSION")]
+    pub to: Option<String>,
+
+    #[command(flatten)]
+    pub release: ReleaseSource,
+
+    /// Install a release whose signature can't be checked, because none is
+    /// published or there is no release public key
+    #[arg(long)]
+    pub allow_unsigned: bool,
+
+    /// Only report whether an update is available
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// Release asset name for this platform, e.g. `driftwatch-linux-x86_64`
+pub fn asset_name() -> String {
+    let suffix = if cfg!(windows) { ".exe" } else { "" };
+    format!(
+        "driftwatch-{}-{}{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        suffix
+    )
+}
+
+pub fn release_url(template: &str, version: &str) -> String {
+    template
+        .replace("{version}", version.trim_start_matches('v'))
+        .replace("{asset}", &asset_name())
+}
+
+pub async fn download(client: &reqwest::Client, url: &str) -> Result<Option<Vec<u8>>> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to download {}", url))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("Failed to download {}", url))?;
+    Ok(Some(response.bytes().await?.to_vec()))
+}
+
+/// Refuses a download that doesn't match its checksum or signature, or
+/// whose checksum is for another version than `version`, and one missing
+/// either, or with no key to check it, unless `allow_unsigned`
+fn check_download(
+    binary: &[u8],
+    version: &str,
+    checksum: Option<&[u8]>,
+    signature: Option<&[u8]>,
+    public_key: Option<&str>,
+    allow_unsigned: bool,
+) -> Result<()> {
+    let Some(checksum) = checksum else {
+        if !allow_unsigned {
+            bail!("No checksum is published for this release; pass --allow-unsigned to install it anyway");
+        }
+        eprintln!("Warning: no checksum published for this release; not verified");
+        return Ok(());
+    };
+    let signature = signature.map(String::from_utf8_lossy);
+    match release_signing::verify_release(
+        binary,
+        checksum,
+        signature.as_deref(),
+        public_key,
+        Some(version),
+    )? {
+        Verification::Signed => println!("Verified the release signature."),
+        Verification::Unsigned if allow_unsigned => {
+            eprintln!("Warning: this release isn't signed; checked its checksum only")
+        }
+        Verification::Unsigned => bail!(
+            "This release isn't signed; pass --allow-unsigned to install it on its checksum alone"
+        ),
+        Verification::NoKey if allow_unsigned => eprintln!(
+            "Warning: no release public key to check the signature with; checked its checksum only"
+        ),
+        Verification::NoKey => bail!(
+            "No release public key to check the signature with; set DRIFTWATCH_RELEASE_PUBLIC_KEY, or pass --allow-unsigned to install it on its checksum alone"
+        ),
+    }
+    Ok(())
+}
+
+/// Swaps the running binary for `binary`. The new file is written next to
+/// the old one first so the rename can't leave a half-written binary.
+fn replace_executable(current: &Path, binary: &[u8]) -> Result<()> {
+    let staged = current.with_extension("new");
+    std::fs::write(&staged, binary)
+        .with_context(|| format!("Failed to write {}", staged.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // Windows can't overwrite a running executable, but can rename it
+    if cfg!(windows) {
+        let old = current.with_extension("old");
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(current, &old)?;
+    }
+    std::fs::rename(&staged, current)
+        .with_context(|| format!("Failed to replace {}", current.display()))?;
+    Ok(())
+}
+
+pub async fn handle(args: SelfUpdateArgs, api_url: &str) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+
+    let target = match args.to {
+        Some(version) => version.trim_start_matches('v').to_string(),
+        None => {
+            // The version query needs no credentials
+            let token = Config::load().map(|c| c.token).unwrap_or_default();
+            let server = connect(api_url, &token)
+                .api_version()
+                .await
+                .context("Couldn't get the server's version; pass --to to pick one")?;
+            server.server_version
+        }
+    };
+
+    if target == current {
+        println!("driftwatch {} is up to date.", current);
+        return Ok(());
+    }
+    if args.check {
+        println!(
+            "driftwatch {} is available (installed: {}).",
+            target, current
+        );
+        println!("Run `driftwatch self-update` to install it.");
+        return Ok(());
+    }
+
+    let url = release_url(&args.release.release_url, &target);
+    println!("Downloading driftwatch {} from {}...", target, url);
+    let client = reqwest::Client::new();
+    let Some(binary) = download(&client, &url).await? else {
+        bail!(
+            "No driftwatch {} release for {}; build it from source instead",
+            target,
+            asset_name()
+        );
+    };
+
+    let checksum = download(&client, &format!("{}{}", url, CHECKSUM_SUFFIX)).await?;
+    let signature = download(&client, &format!("{}{}", url, SIGNATURE_SUFFIX)).await?;
+    check_download(
+        &binary,
+        &target,
+        checksum.as_deref(),
+        signature.as_deref(),
+        args.release.public_key(),
+        args.allow_unsigned,
+    )?;
+
+    let executable = std::env::current_exe().context("Couldn't locate the running binary")?;
+    replace_executable(&executable, &binary)?;
+    println!("Updated driftwatch {} -> {}", current, target);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_url() {
+        let url = release_url("https://example.com/v{version}/{asset}", "v1.2.0");
+        assert_eq!(url, format!("https://example.com/v1.2.0/{}", asset_name()));
+    }
+
+    #[test]
+    fn test_check_download() {
+        let (private_key, public_key) = release_signing::generate_key().unwrap();
+        let binary = b"new driftwatch";
+        let checksum = release_signing::checksum_line(binary, &asset_name(), "1.2.0");
+        let signature = release_signing::sign(&private_key, checksum.as_bytes()).unwrap();
+        let check = |checksum: Option<&str>, signature: Option<&str>, allow_unsigned| {
+            check_download(
+                binary,
+                "1.2.0",
+                checksum.map(str::as_bytes),
+                signature.map(str::as_bytes),
+                Some(&public_key),
+                allow_unsigned,
+            )
+        };
+
+        assert!(check(Some(&checksum), Some(&signature), false).is_ok());
+        assert!(check(Some(&checksum), None, false).is_err());
+        assert!(check(Some(&checksum), None, true).is_ok());
+        assert!(check(None, None, false).is_err());
+        assert!(check(None, None, true).is_ok());
+        // A bad signature fails even when unsigned releases are allowed
+        assert!(check(Some(&checksum), Some(&"A".repeat(88)), true).is_err());
+
+        // Without a key the signature can't be checked
+        let no_key = |allow_unsigned| {
+            check_download(
+                binary,
+                "1.2.0",
+                Some(checksum.as_bytes()),
+                Some(signature.as_bytes()),
+                None,
+                allow_unsigned,
+            )
+        };
+        assert!(no_key(false).is_err());
+        assert!(no_key(true).is_ok());
+
+        // A validly signed checksum of another release is refused
+        let other_release = check_download(
+            binary,
+            "1.3.0",
+            Some(checksum.as_bytes()),
+            Some(signature.as_bytes()),
+            Some(&public_key),
+            true,
+        );
+        assert!(other_release.is_err());
+    }
+
+    #[test]
+    fn test_replace_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("driftwatch");
+        std::fs::write(&path, b"old").unwrap();
+
+        replace_executable(&path, b"new").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert!(!path.with_extension("new").exists());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::release_signing::{self, CHECKSUM_SUFFIX, SIGNATURE_SUFFIX};
+
+#[derive(Subcommand)]
+pub enum SigningCommands {
+    /// Create a release signing key and print its public key
+    Keygen {
+        /// Where to write the private key
+        #[arg(long, default_value = "driftwatch-release.key")]
+        out: PathBuf,
+    },
+    /// Write a .sha256 checksum and a .sha256.sig signature next to each
+    /// release artifact
+    Sign {
+        /// File holding the private key from `signing keygen`
+        #[arg(long, conflicts_with = "key")]
+        key_file: Option<PathBuf>,
+
+        /// The private key itself, e.g. from a CI secret
+        #[arg(long, env = "DRIFTWATCH_SIGNING_KEY", hide_env_values = true)]
+        key: Option<String>,
+
+        /// Release the artifacts belong to, named in each checksum file
+        /// (defaults to this binary's version)
+        #[arg(long, default_value = env!("CARGO_PKG_VERSION"))]
+        version: String,
+
+        #[arg(required = true)]
+        artifacts: Vec<PathBuf>,
+    },
+}
+
+pub async fn handle(command: SigningCommands) -> Result<()> {
+    match command {
+        SigningCommands::Keygen { out } => keygen(&out),
+        SigningCommands::Sign {
+            key_file,
+            key,
+            version,
+            artifacts,
+        } => {
+            let private_key = match (key_file, key) {
+                (Some(path), _) => std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?,
+                (None, Some(key)) => key,
+                (None, None) => bail!("Pass --key-file or set DRIFTWATCH_SIGNING_KEY"),
+            };
+            println!(
+                "Signing with release key {}",
+                release_signing::public_key(&private_key)?
+            );
+            for artifact in &artifacts {
+                sign(&private_key, &version, artifact)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn keygen(out: &Path) -> Result<()> {
+    let (private_key, public_key) = release_signing::generate_key()?;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(out)
+        .with_context(|| format!("Failed to create {}", out.display()))?;
+    file.write_all(private_key.as_bytes())?;
+
+    println!("Wrote the private key to {}", out.display());
+    println!("Keep it secret, e.g. as the DRIFTWATCH_SIGNING_KEY secret of the release workflow.");
+    println!();
+    println!("Public key:");
+    println!("  {}", public_key);
+    println!();
+    println!(
+        "Build releases with DRIFTWATCH_RELEASE_PUBLIC_KEY set to it so they can check updates."
+    );
+    Ok(())
+}
+
+fn sign(private_key: &str, version: &str, artifact: &Path) -> Result<()> {
+    let content = std::fs::read(artifact)
+        .with_context(|| format!("Failed to read {}", artifact.display()))?;
+    let name = artifact
+        .file_name()
+        .context("Artifact has no file name")?
+        .to_string_lossy();
+    let checksum = release_signing::checksum_line(&content, &name, version);
+    let signature = release_signing::sign(private_key, checksum.as_bytes())?;
+
+    for (suffix, content) in [
+        (CHECKSUM_SUFFIX, checksum),
+        (SIGNATURE_SUFFIX, signature + "\n"),
+    ] {
+        let path = release_signing::sidecar(artifact, suffix);
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    println!("Signed {}", artifact.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::release_signing::Verification;
+
+    #[test]
+    fn test_sign_writes_verifiable_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let artifact = dir.path().join("driftwatch-linux-x86_64");
+        std::fs::write(&artifact, b"binary").unwrap();
+        let (private_key, public_key) = release_signing::generate_key().unwrap();
+
+        sign(&private_key, "0.9.0", &artifact).unwrap();
+        let checksum = std::fs::read(dir.path().join("driftwatch-linux-x86_64.sha256")).unwrap();
+        assert!(String::from_utf8_lossy(&checksum).ends_with("  driftwatch-linux-x86_64  0.9.0\n"));
+        let signature =
+            std::fs::read_to_string(dir.path().join("driftwatch-linux-x86_64.sha256.sig")).unwrap();
+        assert_eq!(
+            release_signing::verify_release(
+                b"binary",
+                &checksum,
+                Some(&signature),
+                Some(&public_key),
+                Some("0.9.0")
+            )
+            .unwrap(),
+            Verification::Signed
+        );
+    }
+}
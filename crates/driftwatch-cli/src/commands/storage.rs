@@ -0,0 +1,110 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use driftwatch_api::config::Config;
+use driftwatch_api::db;
+use driftwatch_api::storage::{self, Garbage, Orphan};
+
+#[derive(Subcommand)]
+pub enum StorageCommands {
+    /// Delete uploads that were never confirmed and files no artifact
+    /// refers to. The server also does this every hour.
+    Gc {
+        /// Only delete objects created at least this many hours ago
+        #[arg(long, default_value_t = storage::GC_GRACE_PERIOD.num_hours())]
+        grace_hours: i64,
+
+        /// List what would be deleted instead of deleting it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn handle(command: StorageCommands) -> Result<()> {
+    dotenvy::dotenv().ok();
+    if std::env::var("DATABASE_URL").map_or(true, |url| url.is_empty()) {
+        bail!("Set DATABASE_URL to the server's database");
+    }
+    let StorageCommands::Gc {
+        grace_hours,
+        dry_run,
+    } = command;
+    // Anything younger may be an upload still in progress
+    if grace_hours < 1 {
+        bail!("--grace-hours must be at least 1");
+    }
+    let config = Config::from_env();
+    let db = db::connect(&config.database_url, &config)
+        .await
+        .context("Failed to connect to the database")?;
+
+    let created_before = chrono::Utc::now().fixed_offset() - chrono::Duration::hours(grace_hours);
+    if dry_run {
+        let orphans = storage::orphans(&db, created_before).await?;
+        if !orphans.is_empty() {
+            println!("{:<8} {:<66} {:>12} CREATED", "KIND", "KEY", "BYTES");
+        }
+        for orphan in &orphans {
+            println!(
+                "{:<8} {:<66} {:>12} {}",
+                orphan.kind,
+                orphan.key,
+                orphan.bytes,
+                orphan.created_at.format("%Y-%m-%d %H:%M:%S %Z")
+            );
+        }
+        print_garbage("Would delete", totals(&orphans));
+        return Ok(());
+    }
+
+    print_garbage(
+        "Deleted",
+        storage::collect_garbage(&db, created_before).await?,
+    );
+    Ok(())
+}
+
+/// What deleting `orphans` would free
+fn totals(orphans: &[Orphan]) -> Garbage {
+    orphans
+        .iter()
+        .fold(Garbage::default(), |mut garbage, orphan| {
+            match orphan.kind.as_str() {
+                "upload" => garbage.uploads += 1,
+                _ => garbage.blobs += 1,
+            }
+            garbage.bytes += orphan.bytes;
+            garbage
+        })
+}
+
+fn print_garbage(verb: &str, garbage: Garbage) {
+    println!(
+        "{} {} uploads and {} unreferenced files, {} bytes",
+        verb, garbage.uploads, garbage.blobs, garbage.bytes
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals() {
+        let orphan = |kind: &str, bytes: i64| Orphan {
+            kind: kind.to_string(),
+            key: "k".to_string(),
+            bytes,
+            created_at: chrono::Utc::now().fixed_offset(),
+        };
+        let orphans = [orphan("upload", 10), orphan("blob", 5), orphan("blob", 0)];
+        assert_eq!(
+            totals(&orphans),
+            Garbage {
+                uploads: 1,
+                blobs: 2,
+                bytes: 15
+            }
+        );
+        assert_eq!(totals(&[]), Garbage::default());
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::adapters::Adapter;
+use crate::api::{
+    connect, ApiClient, Config, SimulateThresholdsInput, ThresholdModel, ThresholdSimulation,
+    ThresholdTestInput,
+};
+use crate::commands::run::to_metric_inputs;
+use crate::testbed;
+
+#[derive(Subcommand)]
+pub enum ThresholdCommands {
+    /// Show which thresholds would fire, without creating alerts
+    Test {
+        #[arg(long, short)]
+        project: String,
+        /// Existing report to check against the current thresholds
+        #[arg(long, conflicts_with = "input")]
+        report: Option<String>,
+        /// Benchmark output to check as hypothetical results; "-" reads stdin
+        #[arg(long, required_unless_present = "report")]
+        input: Option<PathBuf>,
+        /// Tool that produced --input, or exec:<program>
+        #[arg(long, default_value = "criterion")]
+        adapter: Adapter,
+        /// Benchmark name for load-test results (defaults to the target URL)
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long, short, default_value = "main")]
+        branch: String,
+        #[arg(long, short)]
+        testbed: Option<String>,
+    },
+    /// Replay history through a proposed threshold and show the alerts it
+    /// would have raised
+    Simulate {
+        #[arg(long, short)]
+        project: String,
+        #[arg(long, value_enum, default_value = "percent")]
+        model: ThresholdModel,
+        /// Earlier reports the baseline is computed from
+        #[arg(long, default_value = "30")]
+        window: i32,
+        /// Percent for the percent model, standard deviations for zscore
+        /// (defaults to 10% or 3 when neither boundary is given)
+        #[arg(long)]
+        upper: Option<f64>,
+        #[arg(long)]
+        lower: Option<f64>,
+        #[arg(long, default_value = "2")]
+        min_sample_size: i32,
+        #[arg(long, short)]
+        measure: Option<String>,
+        #[arg(long, short)]
+        branch: Option<String>,
+        #[arg(long, short)]
+        testbed: Option<String>,
+        /// Only count alerts from the last N days
+        #[arg(long)]
+        days: Option<i32>,
+    },
+}
+
+pub async fn handle(command: ThresholdCommands, api_url: &str) -> Result<()> {
+    let config = Config::load()?;
+    let client = connect(api_url, &config.token);
+
+    match command {
+        ThresholdCommands::Test {
+            project,
+            report,
+            input,
+            adapter,
+            name,
+            branch,
+            testbed,
+        } => {
+            let input = match (report, input) {
+                (Some(report_id), _) => ThresholdTestInput {
+                    project_slug: project,
+                    report_id: Some(report_id),
+                    ..Default::default()
+                },
+                (None, Some(path)) => {
+                    let results = adapter.parse(&read_input(&path)?, name.as_deref())?;
+                    if results.is_empty() {
+                        println!("No benchmark results found in input.");
+                        return Ok(());
+                    }
+                    ThresholdTestInput {
+                        project_slug: project,
+                        metrics: Some(to_metric_inputs(results)),
+                        branch: Some(branch),
+                        testbed: Some(testbed.unwrap_or_else(testbed::default_name)),
+                        ..Default::default()
+                    }
+                }
+                (None, None) => unreachable!("clap requires --report or --input"),
+            };
+            test(&client, &input).await
+        }
+        ThresholdCommands::Simulate {
+            project,
+            model,
+            window,
+            upper,
+            lower,
+            min_sample_size,
+            measure,
+            branch,
+            testbed,
+            days,
+        } => {
+            let input = SimulateThresholdsInput {
+                project_slug: project,
+                model,
+                window,
+                upper_boundary: upper,
+                lower_boundary: lower,
+                min_sample_size,
+                measure,
+                branch,
+                testbed,
+                days,
+            };
+            let simulation = client.simulate_thresholds(&input).await?;
+            print_simulation(&simulation, model);
+            Ok(())
+        }
+    }
+}
+
+fn read_input(path: &PathBuf) -> Result<String> {
+    if path.as_os_str() == "-" {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read stdin")?;
+        return Ok(content);
+    }
+    std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+async fn test(client: &ApiClient, input: &ThresholdTestInput) -> Result<()> {
+    let results = client.threshold_test(input).await?;
+
+    if results.is_empty() {
+        println!("No thresholds apply, or there is not enough history to compare against.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<6} {:>9} {:>9} {:>9} {:<12} BENCHMARK",
+        "", "CHANGE", "UPPER", "LOWER", "MEASURE"
+    );
+    println!("{}", "-".repeat(80));
+
+    let boundary = |b: Option<f64>| b.map(|b| format!("{:.1}%", b)).unwrap_or("-".into());
+    for result in &results {
+        println!(
+            "{:<6} {:>+8.1}% {:>9} {:>9} {:<12} {}",
+            if result.would_alert { "ALERT" } else { "ok" },
+            result.percent_change,
+            boundary(result.threshold.upper_boundary),
+            boundary(result.threshold.lower_boundary),
+            result.measure,
+            result.benchmark
+        );
+    }
+
+    let firing = results.iter().filter(|r| r.would_alert).count();
+    println!(
+        "\n{} of {} comparison(s) would raise an alert.",
+        firing,
+        results.len()
+    );
+
+    Ok(())
+}
+
+fn format_score(model: ThresholdModel, score: f64) -> String {
+    match model {
+        ThresholdModel::Percent => format!("{:+.1}%", score),
+        ThresholdModel::Zscore => format!("{:+.1}σ", score),
+    }
+}
+
+/// Simulated alerts per day, oldest first
+fn alerts_per_day(simulation: &ThresholdSimulation) -> Vec<(&str, usize)> {
+    let mut days: Vec<(&str, usize)> = Vec::new();
+    for alert in &simulation.alerts {
+        let day = alert.created_at.get(..10).unwrap_or(&alert.created_at);
+        match days.last_mut() {
+            Some((last, count)) if *last == day => *count += 1,
+            _ => days.push((day, 1)),
+        }
+    }
+    days
+}
+
+fn print_simulation(simulation: &ThresholdSimulation, model: ThresholdModel) {
+    if simulation.comparisons == 0 {
+        println!("Not enough history to simulate; lower --min-sample-size or report more results.");
+        return;
+    }
+
+    if !simulation.alerts.is_empty() {
+        println!(
+            "{:<20} {:>9} {:<20} {:<12} BENCHMARK",
+            "REPORTED", "SCORE", "BRANCH/TESTBED", "MEASURE"
+        );
+        println!("{}", "-".repeat(80));
+        for alert in &simulation.alerts {
+            let series = format!("{}/{}", alert.branch, alert.testbed);
+            println!(
+                "{:<20} {:>9} {:<20} {:<12} {}",
+                alert.created_at.get(..19).unwrap_or(&alert.created_at),
+                format_score(model, alert.score),
+                series,
+                alert.measure,
+                alert.benchmark
+            );
+        }
+
+        let days = alerts_per_day(simulation);
+        let busiest = days.iter().max_by_key(|(_, count)| *count);
+        if let Some((day, count)) = busiest {
+            println!(
+                "\nAlerts fell on {} day(s); the busiest was {} with {}.",
+                days.len(),
+                day,
+                count
+            );
+        }
+    }
+
+    println!(
+        "\n{} alert(s) from {} comparison(s) across {} series; the current thresholds raised {}.",
+        simulation.alerts.len(),
+        simulation.comparisons,
+        simulation.series,
+        simulation.actual_alerts
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::SimulatedAlert;
+
+    fn alert(created_at: &str) -> SimulatedAlert {
+        SimulatedAlert {
+            benchmark: "sort".to_string(),
+            measure: "latency".to_string(),
+            branch: "main".to_string(),
+            testbed: "linux".to_string(),
+            created_at: created_at.to_string(),
+            score: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_alerts_per_day() {
+        let simulation = ThresholdSimulation {
+            series: 1,
+            comparisons: 10,
+            actual_alerts: 0,
+            alerts: vec![
+                alert("2026-03-01T10:00:00+00:00"),
+                alert("2026-03-01T18:00:00+00:00"),
+                alert("2026-03-04T09:00:00+00:00"),
+            ],
+        };
+        assert_eq!(
+            alerts_per_day(&simulation),
+            vec![("2026-03-01", 2), ("2026-03-04", 1)]
+        );
+    }
+
+    #[test]
+    fn test_format_score() {
+        assert_eq!(format_score(ThresholdModel::Percent, 12.34), "+12.3%");
+        assert_eq!(format_score(ThresholdModel::Zscore, -3.04), "-3.0σ");
+    }
+}
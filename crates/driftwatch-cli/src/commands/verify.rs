@@ -0,0 +1,114 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+use crate::commands::self_update::{asset_name, download, release_url, ReleaseSource};
+use crate::release_signing::{self, Verification, CHECKSUM_SUFFIX, SIGNATURE_SUFFIX};
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Binary to check against the `.sha256` and `.sha256.sig` files next
+    /// to it. Without one, the running driftwatch is checked against its
+    /// published release.
+    pub file: Option<PathBuf>,
+
+    /// Checksum file to use instead of FILE.sha256
+    #[arg(long, requires = "file")]
+    pub checksum: Option<PathBuf>,
+
+    /// Signature file to use instead of FILE.sha256.sig
+    #[arg(long, requires = "file")]
+    pub signature: Option<PathBuf>,
+
+    /// Release FILE is expected to be; its checksum file must name it
+    #[arg(long = "release", value_name = "VERSION", requires = "file")]
+    pub version: Option<String>,
+
+    #[command(flatten)]
+    pub release: ReleaseSource,
+}
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+pub async fn handle(args: VerifyArgs) -> Result<()> {
+    let (binary, checksum, signature, version) = match &args.file {
+        Some(file) => {
+            let binary = std::fs::read(file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let checksum_path = args
+                .checksum
+                .clone()
+                .unwrap_or_else(|| release_signing::sidecar(file, CHECKSUM_SUFFIX));
+            let signature_path = args
+                .signature
+                .clone()
+                .unwrap_or_else(|| release_signing::sidecar(file, SIGNATURE_SUFFIX));
+            (
+                binary,
+                read_optional(&checksum_path)?,
+                read_optional(&signature_path)?,
+                args.version.clone(),
+            )
+        }
+        None => {
+            let executable =
+                std::env::current_exe().context("Couldn't locate the running binary")?;
+            let binary = std::fs::read(&executable)
+                .with_context(|| format!("Failed to read {}", executable.display()))?;
+            let url = release_url(&args.release.release_url, env!("CARGO_PKG_VERSION"));
+            println!("Checking {} against {}", executable.display(), url);
+            let client = reqwest::Client::new();
+            (
+                binary,
+                download(&client, &format!("{}{}", url, CHECKSUM_SUFFIX)).await?,
+                download(&client, &format!("{}{}", url, SIGNATURE_SUFFIX)).await?,
+                Some(env!("CARGO_PKG_VERSION").to_string()),
+            )
+        }
+    };
+
+    let Some(checksum) = checksum else {
+        bail!(
+            "No checksum found for {}",
+            args.file
+                .as_deref()
+                .map(|f| f.display().to_string())
+                .unwrap_or_else(asset_name)
+        );
+    };
+    let signature = signature.as_deref().map(String::from_utf8_lossy);
+    let public_key = args.release.public_key();
+    match release_signing::verify_release(
+        &binary,
+        &checksum,
+        signature.as_deref(),
+        public_key,
+        version.as_deref(),
+    )? {
+        Verification::Signed => {
+            println!("Checksum matches.");
+            if let Some(named) =
+                release_signing::parse_checksum_version(&String::from_utf8_lossy(&checksum))
+            {
+                println!("Checksum is for driftwatch {}.", named);
+            }
+            println!(
+                "Signature is valid for release key {}.",
+                public_key.unwrap_or_default()
+            );
+            Ok(())
+        }
+        Verification::Unsigned => {
+            bail!("Checksum matches, but there is no signature to check")
+        }
+        Verification::NoKey => bail!(
+            "Checksum matches, but no release public key is known to check the signature; pass --public-key"
+        ),
+    }
+}
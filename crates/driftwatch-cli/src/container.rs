@@ -0,0 +1,212 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use std::path::Path;
+use std::process::Command;
+
+use crate::api::ContextEntry;
+use crate::tuning::{format_cpu_list, CpuList};
+
+/// Where the working directory is mounted inside the container
+const WORKSPACE: &str = "/workspace";
+
+#[derive(Args, Debug, Default, Clone)]
+pub struct ContainerArgs {
+    /// Run the benchmark command inside this image, with the working
+    /// directory mounted at /workspace
+    #[arg(long, value_name = "IMAGE")]
+    pub container: Option<String>,
+
+    /// Container engine to use (defaults to docker, or podman when docker
+    /// isn't installed)
+    #[arg(long, value_name = "PROGRAM", requires = "container")]
+    pub container_runtime: Option<String>,
+
+    /// Memory limit for the benchmark command, e.g. 4g: the container's, or
+    /// outside one the virtual address space of each of its processes
+    /// (unix), which the JVM, Go and sanitizers outgrow without using it
+    #[arg(long, value_name = "SIZE", value_parser = crate::limits::parse_memory)]
+    pub memory: Option<String>,
+}
+
+/// An image resolved to the exact digest the benchmarks run in
+#[derive(Debug)]
+pub struct Container {
+    runtime: String,
+    image: String,
+    /// `repo@sha256:...` when the image came from a registry, otherwise
+    /// the local image id
+    pub digest: String,
+    memory: Option<String>,
+}
+
+fn runtime_output(runtime: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(runtime)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run {}", runtime))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed: {}",
+            runtime,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn detect_runtime() -> Result<String> {
+    for runtime in ["docker", "podman"] {
+        if Command::new(runtime).arg("--version").output().is_ok() {
+            return Ok(runtime.to_string());
+        }
+    }
+    bail!("--container needs docker or podman installed");
+}
+
+/// Digest from `image inspect --format '{{json .RepoDigests}} {{.Id}}'`,
+/// preferring the registry digest, which identifies the image anywhere
+pub fn parse_inspect(output: &str) -> Option<String> {
+    let (repo_digests, id) = output.trim().rsplit_once(' ')?;
+    let repo_digests: Vec<String> = serde_json::from_str(repo_digests).unwrap_or_default();
+    repo_digests
+        .into_iter()
+        .next()
+        .or_else(|| (!id.is_empty()).then(|| id.to_string()))
+}
+
+/// Testbed name with the image digest appended, so results from different
+/// images land in separate series
+pub fn testbed_name(testbed: &str, digest: &str) -> String {
+    let hash = digest.rsplit(':').next().unwrap_or(digest);
+    format!("{}@{}", testbed, &hash[..hash.len().min(12)])
+}
+
+impl Container {
+    /// Resolves the image's digest, pulling it first when it isn't present
+    pub fn prepare(args: &ContainerArgs) -> Result<Option<Self>> {
+        let Some(image) = &args.container else {
+            return Ok(None);
+        };
+        let runtime = match &args.container_runtime {
+            Some(runtime) => runtime.clone(),
+            None => detect_runtime()?,
+        };
+
+        let inspect = |runtime: &str| {
+            runtime_output(
+                runtime,
+                &[
+                    "image",
+                    "inspect",
+                    "--format",
+                    "{{json .RepoDigests}} {{.Id}}",
+                    image,
+                ],
+            )
+        };
+        let output = match inspect(&runtime) {
+            Ok(output) => output,
+            Err(_) => {
+                println!("Pulling {}...", image);
+                runtime_output(&runtime, &["pull", image])?;
+                inspect(&runtime)?
+            }
+        };
+        let digest = parse_inspect(&output)
+            .with_context(|| format!("Couldn't determine the digest of {}", image))?;
+
+        Ok(Some(Self {
+            runtime,
+            image: image.clone(),
+            digest,
+            memory: args.memory.clone(),
+        }))
+    }
+
+    /// `<runtime> run ...` up to the image; the command to run inside follows
+    pub fn prefix(&self, workspace: &Path, cpus: Option<&CpuList>, nice: bool) -> Vec<String> {
+        let mut prefix = vec![
+            self.runtime.clone(),
+            "run".to_string(),
+            "--rm".to_string(),
+            "--volume".to_string(),
+            format!("{}:{}", workspace.display(), WORKSPACE),
+            "--workdir".to_string(),
+            WORKSPACE.to_string(),
+        ];
+        if let Some(cpus) = cpus {
+            prefix.push("--cpuset-cpus".to_string());
+            prefix.push(format_cpu_list(&cpus.0));
+        }
+        if let Some(memory) = &self.memory {
+            prefix.push("--memory".to_string());
+            prefix.push(memory.clone());
+        }
+        // Raising priority needs the capability even as root in the container
+        if nice {
+            prefix.push("--cap-add".to_string());
+            prefix.push("SYS_NICE".to_string());
+        }
+        prefix.push(self.image.clone());
+        prefix
+    }
+
+    pub fn context(&self) -> Vec<ContextEntry> {
+        let mut context = vec![ContextEntry {
+            key: "container_image".to_string(),
+            value: self.digest.clone(),
+        }];
+        if let Some(memory) = &self.memory {
+            context.push(ContextEntry {
+                key: "memory_limit".to_string(),
+                value: memory.clone(),
+            });
+        }
+        context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inspect() {
+        assert_eq!(
+            parse_inspect("[\"rust@sha256:abc123\"] sha256:def456\n").as_deref(),
+            Some("rust@sha256:abc123")
+        );
+        // Locally built images have no registry digest
+        assert_eq!(
+            parse_inspect("[] sha256:def456").as_deref(),
+            Some("sha256:def456")
+        );
+        assert_eq!(parse_inspect(""), None);
+    }
+
+    #[test]
+    fn test_testbed_name() {
+        assert_eq!(
+            testbed_name("linux", "rust@sha256:0123456789abcdef0123"),
+            "linux@0123456789ab"
+        );
+        assert_eq!(testbed_name("ci", "sha256:abc"), "ci@abc");
+    }
+
+    #[test]
+    fn test_prefix() {
+        let container = Container {
+            runtime: "podman".to_string(),
+            image: "rust:1.80".to_string(),
+            digest: "sha256:abc".to_string(),
+            memory: Some("4g".to_string()),
+        };
+        let prefix = container.prefix(Path::new("/src"), Some(&CpuList(vec![2, 3])), false);
+        assert_eq!(
+            prefix.join(" "),
+            "podman run --rm --volume /src:/workspace --workdir /workspace \
+             --cpuset-cpus 2-3 --memory 4g rust:1.80"
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Run a git command and return its trimmed stdout
+pub fn git(args: &[&str], dir: Option<&Path>) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args);
+    if let Some(dir) = dir {
+        cmd.current_dir(dir);
+    }
+    let output = cmd.output().context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitInfo {
+    pub message: String,
+    pub author: String,
+    /// Committer date in RFC 3339 format
+    pub committed_at: String,
+}
+
+/// Look up author, commit date and message of `rev`
+pub fn commit_info(rev: &str, dir: Option<&Path>) -> Option<CommitInfo> {
+    let output = git(&["show", "-s", "--format=%an <%ae>%n%cI%n%B", rev], dir).ok()?;
+    parse_commit_info(&output)
+}
+
+/// Merge-base of `rev` and `base`, preferring the remote-tracking branch
+/// since CI checkouts rarely have a local copy of the base branch
+pub fn merge_base(base: &str, rev: &str, dir: Option<&Path>) -> Option<String> {
+    [format!("origin/{}", base), base.to_string()]
+        .iter()
+        .find_map(|candidate| git(&["merge-base", candidate, rev], dir).ok())
+}
+
+fn parse_commit_info(output: &str) -> Option<CommitInfo> {
+    let mut lines = output.splitn(3, '\n');
+    let author = lines.next()?.trim().to_string();
+    let committed_at = lines.next()?.trim().to_string();
+    let message = lines.next().unwrap_or_default().trim().to_string();
+
+    if author.is_empty() || committed_at.is_empty() {
+        return None;
+    }
+
+    Some(CommitInfo {
+        message,
+        author,
+        committed_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commit_info() {
+        let output =
+            "Jane Doe <jane@example.com>\n2024-03-01T12:00:00+01:00\nFix parser\n\nLonger body";
+        let info = parse_commit_info(output).unwrap();
+        assert_eq!(info.author, "Jane Doe <jane@example.com>");
+        assert_eq!(info.committed_at, "2024-03-01T12:00:00+01:00");
+        assert_eq!(info.message, "Fix parser\n\nLonger body");
+    }
+
+    #[test]
+    fn test_parse_commit_info_invalid() {
+        assert_eq!(parse_commit_info(""), None);
+        assert_eq!(parse_commit_info("Jane Doe"), None);
+    }
+}
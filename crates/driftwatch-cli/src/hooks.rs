@@ -0,0 +1,125 @@
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+
+use crate::commands::run::execute_shell;
+
+/// Lines of hook output shown when a hook fails
+const FAILURE_TAIL_LINES: usize = 20;
+
+/// Commands run around the benchmark command, e.g. to warm caches or start a
+/// database. They aren't part of the measured command or its timing.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    #[serde(default)]
+    pub pre: Vec<String>,
+    #[serde(default)]
+    pub post: Vec<String>,
+}
+
+fn tail(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(FAILURE_TAIL_LINES)..].join("\n")
+}
+
+/// Runs one hook through the shell, failing with its exit status and the end
+/// of its output when it doesn't succeed
+pub fn run_hook(stage: &str, command: &str) -> Result<Duration> {
+    let started = Instant::now();
+    let output = execute_shell(command)?;
+    let elapsed = started.elapsed();
+
+    if !output.status.success() {
+        let combined = format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let status = output
+            .status
+            .code()
+            .map(|code| format!("exit code {}", code))
+            .unwrap_or_else(|| "a signal".to_string());
+        bail!(
+            "{} hook `{}` failed with {}:\n{}",
+            stage,
+            command,
+            status,
+            tail(&combined)
+        );
+    }
+    Ok(elapsed)
+}
+
+impl Hooks {
+    /// Adds hooks given on the command line, which run after the file's
+    pub fn extend(&mut self, pre: &[String], post: &[String]) {
+        self.pre.extend_from_slice(pre);
+        self.post.extend_from_slice(post);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+
+    /// Runs the pre hooks in order, stopping at the first failure since the
+    /// benchmarks wouldn't run in the intended state
+    pub fn run_pre(&self) -> Result<()> {
+        for command in &self.pre {
+            let elapsed = run_hook("Pre", command)?;
+            println!(
+                "  Pre hook `{}` took {:.1}s",
+                command,
+                elapsed.as_secs_f64()
+            );
+        }
+        Ok(())
+    }
+
+    /// Runs every post hook. Failures are reported but don't fail the run,
+    /// since the results were already measured.
+    pub fn run_post(&self) {
+        for command in &self.post {
+            match run_hook("Post", command) {
+                Ok(elapsed) => {
+                    println!(
+                        "  Post hook `{}` took {:.1}s",
+                        command,
+                        elapsed.as_secs_f64()
+                    )
+                }
+                Err(e) => eprintln!("Warning: {:#}", e),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_hook() {
+        assert!(run_hook("Pre", "true").is_ok());
+
+        let err = run_hook("Pre", "echo warming up; echo no database >&2; exit 3")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Pre hook `echo warming up;"));
+        assert!(err.contains("exit code 3"));
+        assert!(err.contains("warming up"));
+        assert!(err.contains("no database"));
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut hooks = Hooks {
+            pre: vec!["make warm".to_string()],
+            post: Vec::new(),
+        };
+        hooks.extend(&["pg_ctl start".to_string()], &["pg_ctl stop".to_string()]);
+        assert_eq!(hooks.pre, vec!["make warm", "pg_ctl start"]);
+        assert_eq!(hooks.post, vec!["pg_ctl stop"]);
+    }
+}
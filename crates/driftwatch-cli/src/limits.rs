@@ -0,0 +1,465 @@
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Output, Stdio};
+#[cfg(unix)]
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::commands::run::wrapped_command;
+
+/// How often a command with a timeout is checked on
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Time a timed-out command gets to exit after SIGTERM before it's killed
+const KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// Bounds on the benchmark command from `run --timeout` and `--memory`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub timeout: Option<Duration>,
+    /// Address space of each process, in bytes (unix). This is virtual
+    /// memory, not what's resident: runtimes that reserve large ranges up
+    /// front, like the JVM, Go and AddressSanitizer, fail to start under a
+    /// limit their working set would fit in.
+    pub memory: Option<u64>,
+}
+
+/// Process group of the command running with a timeout, for
+/// `forward_signal`; 0 when there is none
+#[cfg(unix)]
+static GROUP: AtomicU32 = AtomicU32::new(0);
+
+/// Parse a duration such as `90s`, `30m`, `2h` or `1h30m`; a bare number is
+/// seconds
+pub fn parse_duration(arg: &str) -> Result<Duration, String> {
+    let invalid = || format!("expected a duration like 90s, 30m or 2h, got '{}'", arg);
+    let arg = arg.trim();
+    if let Ok(secs) = arg.parse::<u64>() {
+        return Some(secs)
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+            .ok_or_else(invalid);
+    }
+    let mut total = 0;
+    let mut digits = String::new();
+    for c in arg.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = digits.parse().map_err(|_| invalid())?;
+        total += value * unit;
+        digits.clear();
+    }
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// A duration the way `parse_duration` takes it, in its largest whole unit
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{:.1}s", duration.as_secs_f64()),
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Bytes in a memory size such as `512m` or `4g`, with the suffixes docker's
+/// `--memory` takes; a bare number is bytes
+pub fn memory_bytes(arg: &str) -> Result<u64, String> {
+    let arg = arg.trim().to_ascii_lowercase();
+    let (number, unit) = match arg.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => arg.split_at(i),
+        None => (arg.as_str(), ""),
+    };
+    let scale: u64 = match unit {
+        "" | "b" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        _ => return Err(format!("expected a size like 512m or 4g, got '{}'", arg)),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+        .filter(|&bytes| bytes > 0)
+        .ok_or_else(|| format!("expected a size like 512m or 4g, got '{}'", arg))
+}
+
+/// Validates `--memory` while keeping it as given, for the container runtime
+pub fn parse_memory(arg: &str) -> Result<String, String> {
+    memory_bytes(arg).map(|_| arg.trim().to_string())
+}
+
+pub type Drained = JoinHandle<std::io::Result<Vec<u8>>>;
+
+/// Reads a pipe to the end on its own thread, so a chatty command can't
+/// block on a full pipe while it's waited on. With `echo`, everything read
+/// is also copied there as it arrives.
+pub fn drain<R, W>(pipe: Option<R>, mut echo: Option<W>) -> Drained
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return Ok(buf);
+        };
+        let mut chunk = [0; 8192];
+        loop {
+            let read = match pipe.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            buf.extend_from_slice(&chunk[..read]);
+            if let Some(echo) = &mut echo {
+                // A closed terminal shouldn't lose the output for parsing
+                let _ = echo.write_all(&chunk[..read]).and_then(|_| echo.flush());
+            }
+        }
+        Ok(buf)
+    })
+}
+
+pub fn collect(drained: Drained) -> Result<Vec<u8>> {
+    drained
+        .join()
+        .map_err(|_| anyhow::anyhow!("Output reader panicked"))?
+        .context("Failed to read benchmark command output")
+}
+
+impl Limits {
+    /// Starts the command with its output piped. With a timeout it gets a
+    /// process group of its own, so everything it starts can be killed
+    /// together; since Ctrl-C then no longer reaches it, SIGINT and SIGTERM
+    /// kill that group before the CLI exits.
+    pub fn spawn(&self, mut command: Command) -> Result<Child> {
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            if self.timeout.is_some() {
+                command.process_group(0);
+            }
+            if let Some(bytes) = self.memory {
+                let limit = libc::rlimit {
+                    rlim_cur: bytes as libc::rlim_t,
+                    rlim_max: bytes as libc::rlim_t,
+                };
+                // SAFETY: setrlimit is async-signal-safe, so it may run
+                // between fork and exec
+                unsafe {
+                    command.pre_exec(move || {
+                        if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        Ok(())
+                    });
+                }
+            }
+        }
+        let child = command
+            .spawn()
+            .context("Failed to execute benchmark command")?;
+        #[cfg(unix)]
+        if self.timeout.is_some() {
+            forward_signals(child.id());
+        }
+        Ok(child)
+    }
+
+    /// Waits for a child started by `spawn` through `poll`, which reaps it
+    /// when it has exited, blocking when asked to. At the timeout its
+    /// process group gets SIGTERM, then SIGKILL after a grace period. Also
+    /// returns whether it timed out.
+    pub fn wait<T>(
+        &self,
+        child: &mut Child,
+        mut poll: impl FnMut(&mut Child, bool) -> Result<Option<T>>,
+    ) -> Result<(T, bool)> {
+        let Some(timeout) = self.timeout else {
+            let reaped = poll(child, true)?.context("Benchmark command wasn't reaped")?;
+            return Ok((reaped, false));
+        };
+
+        #[cfg(unix)]
+        let _forwarding = Forwarding;
+        let mut deadline = Some(Instant::now() + timeout);
+        let mut timed_out = false;
+        loop {
+            if let Some(reaped) = poll(child, false)? {
+                // Whatever it started may outlive it and hold the pipes open
+                if timed_out {
+                    kill_group(child.id(), true);
+                }
+                return Ok((reaped, timed_out));
+            }
+            match deadline {
+                Some(at) if Instant::now() >= at && !timed_out => {
+                    timed_out = true;
+                    kill_group(child.id(), false);
+                    deadline = Some(at + KILL_GRACE);
+                }
+                Some(at) if Instant::now() >= at => {
+                    kill_group(child.id(), true);
+                    deadline = None;
+                }
+                _ => {}
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Stops `forward_signal` from killing a group once its command is waited
+/// on, however the wait ends
+#[cfg(unix)]
+struct Forwarding;
+
+#[cfg(unix)]
+impl Drop for Forwarding {
+    fn drop(&mut self) {
+        GROUP.store(0, Ordering::SeqCst);
+    }
+}
+
+#[cfg(unix)]
+fn forward_signals(group: u32) {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    GROUP.store(group, Ordering::SeqCst);
+    INSTALL.call_once(|| {
+        let handler: extern "C" fn(libc::c_int) = forward_signal;
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            // SAFETY: forward_signal only makes async-signal-safe calls
+            unsafe {
+                libc::signal(signal, handler as libc::sighandler_t);
+            }
+        }
+    });
+}
+
+/// Kills the command's process group, then lets the signal end the CLI the
+/// way it would have without the handler
+#[cfg(unix)]
+extern "C" fn forward_signal(signal: libc::c_int) {
+    let group = GROUP.load(Ordering::SeqCst);
+    if group != 0 {
+        kill_group(group, true);
+    }
+    // SAFETY: signal and raise are async-signal-safe
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+#[cfg(unix)]
+fn kill_group(group: u32, force: bool) {
+    let signal = if force { libc::SIGKILL } else { libc::SIGTERM };
+    // SAFETY: plain syscall on the process group `spawn` created; it fails
+    // harmlessly once the group is gone
+    unsafe {
+        libc::kill(-(group as libc::pid_t), signal);
+    }
+}
+
+/// Windows has no process groups to signal; `taskkill /T` ends the tree
+#[cfg(not(unix))]
+fn kill_group(pid: u32, force: bool) {
+    let pid = pid.to_string();
+    let mut taskkill = Command::new("taskkill");
+    taskkill.args(["/T", "/PID", &pid]);
+    if force {
+        taskkill.arg("/F");
+    }
+    let _ = taskkill.output();
+}
+
+/// Runs the command like `execute_wrapped`, within `limits`, with `stream`
+/// also showing its output as it runs. Also returns whether it was killed
+/// at the timeout, in which case the output is what it printed until then.
+pub fn execute(
+    command: &[String],
+    prefix: &[String],
+    limits: &Limits,
+    stream: bool,
+) -> Result<(Output, bool)> {
+    let mut child = limits.spawn(wrapped_command(command, None, prefix))?;
+    let stdout = drain(child.stdout.take(), stream.then(std::io::stdout));
+    let stderr = drain(child.stderr.take(), stream.then(std::io::stderr));
+    let (status, timed_out) = limits.wait(&mut child, |child, block| {
+        Ok(if block {
+            Some(child.wait()?)
+        } else {
+            child.try_wait()?
+        })
+    })?;
+    let output = Output {
+        status,
+        stdout: collect(stdout)?,
+        stderr: collect(stderr)?,
+    };
+    Ok((output, timed_out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::run::shell_wrapped;
+    use std::sync::{Arc, Mutex};
+
+    /// A terminal stand-in that keeps what's echoed to it
+    #[derive(Clone, Default)]
+    struct Echo(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Echo {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Echo {
+        fn contents(&self) -> Vec<u8> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    fn sh(line: &str) -> Vec<String> {
+        shell_wrapped(&[line.to_string()], &[])
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("30x").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2h");
+        assert_eq!(format_duration(Duration::from_secs(1800)), "30m");
+        assert_eq!(format_duration(Duration::from_secs(90)), "90s");
+        assert_eq!(format_duration(Duration::from_millis(200)), "0.2s");
+    }
+
+    #[test]
+    fn test_memory_bytes() {
+        assert_eq!(memory_bytes("4g").unwrap(), 4 << 30);
+        assert_eq!(memory_bytes("512M").unwrap(), 512 << 20);
+        assert_eq!(memory_bytes("1024").unwrap(), 1024);
+        assert!(memory_bytes("0").is_err());
+        assert!(memory_bytes("4gb").is_err());
+        assert!(memory_bytes("g").is_err());
+        assert_eq!(parse_memory(" 4g ").unwrap(), "4g");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_kills_at_timeout() {
+        let limits = Limits {
+            timeout: Some(Duration::from_millis(200)),
+            memory: None,
+        };
+        let started = Instant::now();
+        // The background sleep would keep the pipes open if it survived
+        let (output, timed_out) =
+            execute(&sh("echo done; sleep 30 & sleep 30"), &[], &limits, false).unwrap();
+        assert!(timed_out);
+        assert!(!output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "done\n");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let (output, timed_out) = execute(&sh("exit 3"), &[], &limits, false).unwrap();
+        assert!(!timed_out);
+        assert_eq!(output.status.code(), Some(3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_drain_keeps_interleaved_streams_apart() {
+        let script = "for i in $(seq 1 500); do echo out$i; echo err$i >&2; done";
+        let mut child = Limits::default()
+            .spawn(wrapped_command(&sh(script), None, &[]))
+            .unwrap();
+        let (out_echo, err_echo) = (Echo::default(), Echo::default());
+        let stdout = drain(child.stdout.take(), Some(out_echo.clone()));
+        let stderr = drain(child.stderr.take(), Some(err_echo.clone()));
+        assert!(child.wait().unwrap().success());
+        let stdout = collect(stdout).unwrap();
+        let stderr = collect(stderr).unwrap();
+
+        let lines =
+            |prefix: &str| -> String { (1..=500).map(|i| format!("{}{}\n", prefix, i)).collect() };
+        assert_eq!(String::from_utf8_lossy(&stdout), lines("out"));
+        assert_eq!(String::from_utf8_lossy(&stderr), lines("err"));
+        // What was shown live is exactly what was captured
+        assert_eq!(out_echo.contents(), stdout);
+        assert_eq!(err_echo.contents(), stderr);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_captures_output_past_the_pipe_buffer() {
+        // A megabyte on each stream is far past what a pipe holds, so the
+        // command would block if either weren't read while it's waited on
+        let script = "head -c 1048576 /dev/zero; head -c 1048576 /dev/zero >&2";
+        for timeout in [None, Some(Duration::from_secs(60))] {
+            let limits = Limits {
+                timeout,
+                memory: None,
+            };
+            let (output, timed_out) = execute(&sh(script), &[], &limits, false).unwrap();
+            assert!(!timed_out);
+            assert!(output.status.success());
+            assert_eq!(output.stdout.len(), 1 << 20);
+            assert_eq!(output.stderr.len(), 1 << 20);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_drain_reads_output_left_after_exit() {
+        let script = "head -c 30000 /dev/zero | tr '\\0' x; printf done >&2";
+        let mut child = Limits::default()
+            .spawn(wrapped_command(&sh(script), None, &[]))
+            .unwrap();
+        // Nothing reads the pipes until the command is gone, so all of its
+        // output is still sitting in them
+        assert!(child.wait().unwrap().success());
+        let echo = Echo::default();
+        let stdout = drain(child.stdout.take(), Some(echo.clone()));
+        let stderr = drain(child.stderr.take(), None::<Echo>);
+        let stdout = collect(stdout).unwrap();
+        assert_eq!(stdout, vec![b'x'; 30000]);
+        assert_eq!(echo.contents(), stdout);
+        assert_eq!(collect(stderr).unwrap(), b"done");
+    }
+}
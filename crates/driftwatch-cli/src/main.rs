@@ -5,8 +5,24 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 mod adapters;
 mod api;
 mod commands;
+mod container;
+mod git;
+mod hooks;
+mod limits;
+mod nix;
+mod owners;
+mod profiling;
+mod release_signing;
+mod routing;
+mod stats;
+mod testbed;
+mod timing;
+mod tuning;
 
-use commands::{auth, config, project, run};
+use commands::{
+    ab, alert, auth, backfill, config, doctor, explore, gha_install, group, migrate, project,
+    report, run, search, self_update, signing, storage, threshold, verify,
+};
 
 #[derive(Parser)]
 #[command(name = "driftwatch")]
@@ -23,11 +39,29 @@ struct Cli {
         global = true
     )]
     api_url: String,
+
+    /// Check the server's API version before talking to it and refuse an
+    /// incompatible one, instead of warning
+    #[arg(long, env = "DRIFTWATCH_STRICT", global = true)]
+    strict: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     Serve(ServeArgs),
+    /// Accept report submissions near CI runners and forward them to the
+    /// primary server in the background
+    Relay(RelayArgs),
+    /// Apply or roll back database schema migrations
+    Migrate {
+        #[command(subcommand)]
+        command: migrate::MigrateCommands,
+    },
+    /// Clean up the server's stored uploads and files
+    Storage {
+        #[command(subcommand)]
+        command: storage::StorageCommands,
+    },
     Auth {
         #[command(subcommand)]
         command: auth::AuthCommands,
@@ -36,11 +70,53 @@ enum Commands {
         #[command(subcommand)]
         command: config::ConfigCommands,
     },
+    /// Check the config, token, server connections, git and benchmark tools
+    Doctor(doctor::DoctorArgs),
     Project {
         #[command(subcommand)]
         command: project::ProjectCommands,
     },
-    Run(run::RunArgs),
+    /// Follow several related projects together
+    Group {
+        #[command(subcommand)]
+        command: group::GroupCommands,
+    },
+    /// Triage regression alerts
+    Alert {
+        #[command(subcommand)]
+        command: alert::AlertCommands,
+    },
+    /// Manage submitted reports
+    Report {
+        #[command(subcommand)]
+        command: report::ReportCommands,
+    },
+    /// Tune alert thresholds
+    Threshold {
+        #[command(subcommand)]
+        command: threshold::ThresholdCommands,
+    },
+    Run(Box<run::RunArgs>),
+    /// Find benchmarks by name across every project you can read
+    Search(search::SearchArgs),
+    /// Browse the public projects on the server, no account needed
+    Explore(explore::ExploreArgs),
+    /// Run benchmarks across a range of historical commits
+    Backfill(backfill::BackfillArgs),
+    /// Compare two benchmark commands in an A/B experiment
+    Ab(ab::AbArgs),
+    /// Install the CLI release matching the server
+    SelfUpdate(self_update::SelfUpdateArgs),
+    /// Check a driftwatch binary against its published checksum and
+    /// release signature
+    Verify(verify::VerifyArgs),
+    /// Sign release artifacts
+    Signing {
+        #[command(subcommand)]
+        command: signing::SigningCommands,
+    },
+    /// Add a GitHub Actions workflow that runs and submits benchmarks
+    GhaInstall(gha_install::GhaInstallArgs),
 }
 
 #[derive(Args)]
@@ -50,23 +126,65 @@ struct ServeArgs {
 
     #[arg(long, env = "GRPC_PORT", default_value = "50051")]
     grpc_port: u16,
+
+    /// Run a local development stack: Postgres in Docker unless
+    /// DATABASE_URL is set, a seeded user and a printed CLI login command
+    #[arg(long)]
+    dev: bool,
+}
+
+#[derive(Args)]
+struct RelayArgs {
+    /// API URL of the primary server
+    #[arg(long, env = "DRIFTWATCH_PRIMARY_URL")]
+    primary: String,
+
+    #[arg(short, long, env = "PORT", default_value = "4100")]
+    port: u16,
+
+    /// Where submissions wait until the primary has them
+    #[arg(
+        long,
+        env = "DRIFTWATCH_RELAY_SPOOL",
+        default_value = "driftwatch-relay"
+    )]
+    spool_dir: std::path::PathBuf,
+
+    /// Refuse submissions while the spool holds more than this many MiB, or
+    /// 0 for no limit
+    #[arg(long, env = "DRIFTWATCH_RELAY_MAX_SPOOL_MB", default_value = "1024")]
+    max_spool_mb: u64,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    api::set_strict(cli.strict);
 
-    match cli.command {
+    let result = match cli.command {
         Commands::Serve(args) => {
-            tracing_subscriber::registry()
-                .with(
-                    tracing_subscriber::EnvFilter::try_from_default_env()
-                        .unwrap_or_else(|_| "info".into()),
-                )
-                .with(tracing_subscriber::fmt::layer())
-                .init();
+            driftwatch_api::logging::init(driftwatch_api::logging::LogFormat::from_env());
+
+            driftwatch_api::serve(Some(args.port), Some(args.grpc_port), args.dev).await
+        }
+        Commands::Relay(args) => {
+            driftwatch_api::logging::init(driftwatch_api::logging::LogFormat::from_env());
 
-            driftwatch_api::serve(Some(args.port), Some(args.grpc_port)).await
+            driftwatch_api::relay::serve(driftwatch_api::relay::RelayConfig {
+                primary_url: args.primary,
+                port: args.port,
+                spool_dir: args.spool_dir,
+                max_spool_bytes: args.max_spool_mb * 1024 * 1024,
+            })
+            .await
+        }
+        Commands::Migrate { command } => {
+            init_cli_tracing();
+            migrate::handle(command).await
+        }
+        Commands::Storage { command } => {
+            init_cli_tracing();
+            storage::handle(command).await
         }
         Commands::Auth { command } => {
             init_cli_tracing();
@@ -76,14 +194,84 @@ async fn main() -> Result<()> {
             init_cli_tracing();
             config::handle(command).await
         }
+        Commands::Doctor(args) => {
+            init_cli_tracing();
+            doctor::handle(args, &cli.api_url).await
+        }
         Commands::Project { command } => {
             init_cli_tracing();
             project::handle(command, &cli.api_url).await
         }
+        Commands::Group { command } => {
+            init_cli_tracing();
+            group::handle(command, &cli.api_url).await
+        }
+        Commands::Alert { command } => {
+            init_cli_tracing();
+            alert::handle(command, &cli.api_url).await
+        }
+        Commands::Report { command } => {
+            init_cli_tracing();
+            report::handle(command, &cli.api_url).await
+        }
+        Commands::Threshold { command } => {
+            init_cli_tracing();
+            threshold::handle(command, &cli.api_url).await
+        }
         Commands::Run(args) => {
             init_cli_tracing();
-            run::handle(args, &cli.api_url).await
+            run::handle(*args, &cli.api_url).await
+        }
+        Commands::Search(args) => {
+            init_cli_tracing();
+            search::handle(args, &cli.api_url).await
+        }
+        Commands::Explore(args) => {
+            init_cli_tracing();
+            explore::handle(args, &cli.api_url).await
+        }
+        Commands::Backfill(args) => {
+            init_cli_tracing();
+            backfill::handle(args, &cli.api_url).await
+        }
+        Commands::Ab(args) => {
+            init_cli_tracing();
+            ab::handle(args, &cli.api_url).await
+        }
+        Commands::SelfUpdate(args) => {
+            init_cli_tracing();
+            self_update::handle(args, &cli.api_url).await
+        }
+        Commands::Verify(args) => {
+            init_cli_tracing();
+            verify::handle(args).await
+        }
+        Commands::Signing { command } => {
+            init_cli_tracing();
+            signing::handle(command).await
+        }
+        Commands::GhaInstall(args) => {
+            init_cli_tracing();
+            gha_install::handle(args).await
+        }
+    };
+    result.map_err(suggest_update)
+}
+
+/// Points `--strict` failures at the release matching the server
+fn suggest_update(error: anyhow::Error) -> anyhow::Error {
+    let server_version = error.chain().find_map(|cause| match cause.downcast_ref() {
+        Some(driftwatch_sdk::Error::Incompatible { server_version, .. }) => {
+            Some(server_version.clone())
         }
+        _ => None,
+    });
+    match server_version {
+        Some(server_version) => error.context(format!(
+            "Refusing to continue with --strict. Run `driftwatch self-update` to get the CLI matching server {}.",
+            server_version
+        )),
+        None => error,
     }
 }
 
@@ -0,0 +1,143 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+use crate::api::ContextEntry;
+
+const STORE: &str = "/nix/store/";
+
+/// Variables nix-shell, `nix develop` and devenv fill with store paths;
+/// together they pin the toolchain the benchmarks were built with
+const STORE_PATH_VARS: [&str; 5] = [
+    "PATH",
+    "buildInputs",
+    "nativeBuildInputs",
+    "propagatedBuildInputs",
+    "propagatedNativeBuildInputs",
+];
+
+/// The Nix environment a run happens in
+#[derive(Debug, Clone, PartialEq)]
+pub struct NixEnv {
+    /// `pure`, `impure` or `devenv`
+    pub shell: String,
+    /// Short hash of every store path the environment provides
+    pub hash: String,
+}
+
+impl NixEnv {
+    /// The environment of this process, when it runs in a Nix shell
+    pub fn detect() -> Option<Self> {
+        let vars: Vec<(String, String)> = STORE_PATH_VARS
+            .iter()
+            .filter_map(|name| std::env::var(name).ok().map(|v| (name.to_string(), v)))
+            .collect();
+        let shell = std::env::var("IN_NIX_SHELL")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .or_else(|| {
+                std::env::var("DEVENV_ROOT")
+                    .ok()
+                    .map(|_| "devenv".to_string())
+            })?;
+        Some(Self {
+            shell,
+            hash: environment_hash(&vars)?,
+        })
+    }
+
+    pub fn context(&self) -> Vec<ContextEntry> {
+        vec![
+            ContextEntry {
+                key: "nix_shell".to_string(),
+                value: self.shell.clone(),
+            },
+            ContextEntry {
+                key: "nix_env".to_string(),
+                value: self.hash.clone(),
+            },
+        ]
+    }
+}
+
+/// Store path roots (`/nix/store/<hash>-<name>`) mentioned in a variable,
+/// whether `:`-separated like PATH or space-separated like buildInputs
+fn store_paths(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .filter_map(|token| {
+            let rest = token.strip_prefix(STORE)?;
+            let root_len = STORE.len() + rest.find('/').unwrap_or(rest.len());
+            (root_len > STORE.len()).then(|| &token[..root_len])
+        })
+}
+
+/// Hash of the distinct store paths across the variables. Store paths embed
+/// the hash of the derivation that built them, so any toolchain change
+/// changes this; the order they appear in doesn't.
+pub fn environment_hash(vars: &[(String, String)]) -> Option<String> {
+    let paths: BTreeSet<&str> = vars.iter().flat_map(|(_, v)| store_paths(v)).collect();
+    if paths.is_empty() {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    for path in paths {
+        hasher.update(path.as_bytes());
+        hasher.update(b"\n");
+    }
+    Some(hex::encode(&hasher.finalize()[..8]))
+}
+
+/// Testbed name with the environment hash appended, so a toolchain change on
+/// the same machine starts a new series
+pub fn testbed_name(testbed: &str, env: &NixEnv) -> String {
+    format!("{}+nix-{}", testbed, env.hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str, value: &str) -> (String, String) {
+        (name.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_store_paths() {
+        let paths: Vec<&str> = store_paths(
+            "/nix/store/abc-rustc-1.80/bin:/usr/bin:/nix/store/def-cargo-1.80/bin /nix/store/",
+        )
+        .collect();
+        assert_eq!(
+            paths,
+            vec!["/nix/store/abc-rustc-1.80", "/nix/store/def-cargo-1.80"]
+        );
+    }
+
+    #[test]
+    fn test_environment_hash() {
+        let a = environment_hash(&[
+            var("PATH", "/nix/store/abc-rustc-1.80/bin:/usr/bin"),
+            var("buildInputs", "/nix/store/def-openssl-3.0"),
+        ])
+        .unwrap();
+        assert_eq!(a.len(), 16);
+
+        // Same paths in a different order and with different subdirectories
+        let b = environment_hash(&[
+            var("nativeBuildInputs", "/nix/store/def-openssl-3.0/lib"),
+            var("PATH", "/usr/bin:/nix/store/abc-rustc-1.80/bin"),
+        ])
+        .unwrap();
+        assert_eq!(a, b);
+
+        let upgraded = environment_hash(&[
+            var("PATH", "/nix/store/xyz-rustc-1.81/bin:/usr/bin"),
+            var("buildInputs", "/nix/store/def-openssl-3.0"),
+        ])
+        .unwrap();
+        assert_ne!(a, upgraded);
+
+        assert_eq!(environment_hash(&[var("PATH", "/usr/bin:/bin")]), None);
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{bail, Result};
+
+pub use driftwatch_sdk::OwnerRule;
+
+/// Default location of the owners file, relative to the repository root
+pub const DEFAULT_OWNERS_FILE: &str = ".driftwatch/OWNERS";
+
+/// Parse a CODEOWNERS-style file: one `pattern @owner [@owner...]` rule per
+/// line, `#` comments and blank lines ignored. Later rules take precedence.
+pub fn parse_owners(content: &str) -> Result<Vec<OwnerRule>> {
+    let mut rules = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let pattern = parts.next().unwrap_or_default().to_string();
+        let owners: Vec<String> = parts.map(String::from).collect();
+        if owners.is_empty() {
+            bail!("Line {}: '{}' has no owners", i + 1, pattern);
+        }
+
+        rules.push(OwnerRule { pattern, owners });
+    }
+
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_owners() {
+        let content = "\
+# Default owners
+*              @org/perf
+
+parser/*       @org/parsing @alice  # JSON and YAML parsers
+";
+        let rules = parse_owners(content).unwrap();
+        assert_eq!(
+            rules,
+            vec![
+                OwnerRule {
+                    pattern: "*".to_string(),
+                    owners: vec!["@org/perf".to_string()],
+                },
+                OwnerRule {
+                    pattern: "parser/*".to_string(),
+                    owners: vec!["@org/parsing".to_string(), "@alice".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_owners_requires_owner() {
+        let err = parse_owners("*  @org/perf\nlonely_pattern\n").unwrap_err();
+        assert!(err.to_string().contains("Line 2"));
+    }
+}
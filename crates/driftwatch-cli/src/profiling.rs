@@ -0,0 +1,113 @@
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+use crate::commands::run::execute_wrapped;
+use crate::hooks::Hooks;
+
+/// Installed by `cargo install flamegraph`, alongside `cargo flamegraph`;
+/// unlike the cargo subcommand it profiles any command
+const PROFILER: &str = "flamegraph";
+
+fn runs(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .output()
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Why `run --profile` can't profile on this machine, or `None` when it can
+pub fn unavailable() -> Option<String> {
+    if !runs(PROFILER) {
+        return Some(format!(
+            "{} not found; install it with `cargo install flamegraph`",
+            PROFILER
+        ));
+    }
+    // Other platforms use dtrace or ETW, which ship with the OS
+    if cfg!(target_os = "linux") && !runs("perf") {
+        return Some("perf not found; install linux-tools for this kernel".to_string());
+    }
+    None
+}
+
+/// Directory the profiler writes this run's flamegraph to; remove it once
+/// the flamegraph is uploaded
+pub fn output_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("driftwatch-profile-{}", std::process::id()))
+}
+
+/// `prefix` with the profiler in front, so it samples the command and every
+/// process it starts
+pub fn profiler_prefix(output: &Path, prefix: &[String]) -> Vec<String> {
+    let mut profiler = vec![
+        PROFILER.to_string(),
+        "--output".to_string(),
+        output.display().to_string(),
+        "--".to_string(),
+    ];
+    profiler.extend(prefix.iter().cloned());
+    profiler
+}
+
+/// Runs the benchmark command once more under the profiler, between its
+/// hooks, and returns the flamegraph it drew. This is a separate pass so
+/// sampling overhead never shows up in the submitted results.
+pub fn capture(command: &[String], prefix: &[String], hooks: &Hooks) -> Result<PathBuf> {
+    let output_dir = output_dir();
+    std::fs::create_dir_all(&output_dir).context("Failed to create profile directory")?;
+    let output_path = output_dir.join("flamegraph.svg");
+    hooks.run_pre()?;
+    let started = Instant::now();
+    let output = execute_wrapped(command, None, &profiler_prefix(&output_path, prefix));
+    hooks.run_post();
+
+    let output = output?;
+    if !output.status.success() {
+        bail!(
+            "{} failed ({}):\n{}",
+            PROFILER,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    if !output_path.exists() {
+        bail!("{} finished without writing a flamegraph", PROFILER);
+    }
+    println!(
+        "Profiled benchmarks in {:.1}s",
+        started.elapsed().as_secs_f64()
+    );
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiler_prefix_wraps_tuning() {
+        let prefix = vec![
+            "taskset".to_string(),
+            "--cpu-list".to_string(),
+            "2-3".to_string(),
+        ];
+        assert_eq!(
+            profiler_prefix(Path::new("/tmp/flame.svg"), &prefix),
+            [
+                "flamegraph",
+                "--output",
+                "/tmp/flame.svg",
+                "--",
+                "taskset",
+                "--cpu-list",
+                "2-3"
+            ]
+        );
+        assert_eq!(
+            profiler_prefix(Path::new("/tmp/flame.svg"), &[]),
+            ["flamegraph", "--output", "/tmp/flame.svg", "--"]
+        );
+    }
+}
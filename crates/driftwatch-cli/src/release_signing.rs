@@ -0,0 +1,237 @@
+//! Ed25519 signatures for release binaries.
+//!
+//! Every release asset is published with a `.sha256` file, `sha256sum`
+//! output followed by the release version, and a `.sha256.sig` holding the
+//! base64 signature of that checksum file. Signing the small checksum file
+//! rather than the binary lets install scripts check both with `sha256sum`
+//! and `openssl pkeyutl`; naming the version in it keeps an older signed
+//! release from being served in place of the requested one.
+//!
+//! Release builds embed the public key from `DRIFTWATCH_RELEASE_PUBLIC_KEY`
+//! at compile time; setting the variable at runtime overrides it.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use sha2::{Digest, Sha256};
+
+/// Public key the release workflow built this binary with, if any
+pub const EMBEDDED_PUBLIC_KEY: Option<&str> = option_env!("DRIFTWATCH_RELEASE_PUBLIC_KEY");
+
+/// Published next to each asset
+pub const CHECKSUM_SUFFIX: &str = ".sha256";
+pub const SIGNATURE_SUFFIX: &str = ".sha256.sig";
+
+/// `artifact` with `suffix` appended, e.g. `driftwatch-linux-x86_64.sha256`
+pub fn sidecar(artifact: &Path, suffix: &str) -> PathBuf {
+    let mut path = artifact.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// What checking a downloaded binary established
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verification {
+    /// The checksum matches and carries a valid signature
+    Signed,
+    /// The checksum matches but the release has no signature
+    Unsigned,
+    /// The checksum matches; no public key to check a signature with
+    NoKey,
+}
+
+/// A new signing key as base64 PKCS#8, and its base64 public key
+pub fn generate_key() -> Result<(String, String)> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| anyhow::anyhow!("Failed to generate a signing key"))?;
+    let private_key = STANDARD.encode(pkcs8.as_ref());
+    let public = public_key(&private_key)?;
+    Ok((private_key, public))
+}
+
+fn key_pair(private_key: &str) -> Result<Ed25519KeyPair> {
+    let pkcs8 = STANDARD
+        .decode(private_key.trim())
+        .context("Signing key isn't base64")?;
+    Ed25519KeyPair::from_pkcs8(&pkcs8)
+        .map_err(|e| anyhow::anyhow!("Signing key isn't an Ed25519 PKCS#8 key: {}", e))
+}
+
+/// The base64 public key of a base64 PKCS#8 signing key
+pub fn public_key(private_key: &str) -> Result<String> {
+    Ok(STANDARD.encode(key_pair(private_key)?.public_key().as_ref()))
+}
+
+/// Base64 signature of `message`
+pub fn sign(private_key: &str, message: &[u8]) -> Result<String> {
+    Ok(STANDARD.encode(key_pair(private_key)?.sign(message).as_ref()))
+}
+
+pub fn verify_signature(public_key: &str, message: &[u8], signature: &str) -> Result<()> {
+    let public_key = STANDARD
+        .decode(public_key.trim())
+        .context("Release public key isn't base64")?;
+    if public_key.len() != 32 {
+        bail!("Release public key must be 32 bytes of Ed25519 key");
+    }
+    let signature = STANDARD
+        .decode(signature.trim())
+        .context("Signature file isn't base64")?;
+    UnparsedPublicKey::new(&ED25519, &public_key)
+        .verify(message, &signature)
+        .map_err(|_| anyhow::anyhow!("Signature doesn't match the release public key"))
+}
+
+/// `sha256sum` output for `content` published as `name`, followed by the
+/// release `version`
+pub fn checksum_line(content: &[u8], name: &str, version: &str) -> String {
+    format!(
+        "{}  {}  {}\n",
+        hex::encode(Sha256::digest(content)),
+        name,
+        version.trim_start_matches('v')
+    )
+}
+
+/// First hex digest in a `.sha256` file, which may be just the digest or
+/// `sha256sum` output
+pub fn parse_checksum(content: &str) -> Option<String> {
+    content
+        .split_whitespace()
+        .next()
+        .filter(|digest| digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_lowercase)
+}
+
+/// Release version a `.sha256` file names after the asset, if any
+pub fn parse_checksum_version(content: &str) -> Option<&str> {
+    content.split_whitespace().nth(2)
+}
+
+/// Checks `binary` against its checksum file and, when there is a key and
+/// a signature, the signature of that file. A mismatch of either fails, as
+/// does a checksum file for a release other than `version`; a signed one
+/// must name its version.
+pub fn verify_release(
+    binary: &[u8],
+    checksum: &[u8],
+    signature: Option<&str>,
+    public_key: Option<&str>,
+    version: Option<&str>,
+) -> Result<Verification> {
+    let content = String::from_utf8_lossy(checksum);
+    let expected = parse_checksum(&content).context("Malformed checksum file")?;
+    let actual = hex::encode(Sha256::digest(binary));
+    if actual != expected {
+        bail!(
+            "Checksum mismatch for the binary (expected {}, got {})",
+            expected,
+            actual
+        );
+    }
+    let named = parse_checksum_version(&content);
+    if let (Some(version), Some(named)) = (version, named) {
+        if named.trim_start_matches('v') != version.trim_start_matches('v') {
+            bail!(
+                "The checksum file is for driftwatch {}, not {}",
+                named,
+                version
+            );
+        }
+    }
+    match (public_key, signature) {
+        (None, _) => Ok(Verification::NoKey),
+        (Some(_), None) => Ok(Verification::Unsigned),
+        (Some(key), Some(signature)) => {
+            verify_signature(key, checksum, signature)?;
+            if version.is_some() && named.is_none() {
+                bail!("The signed checksum file doesn't name the release it's for");
+            }
+            Ok(Verification::Signed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checksum() {
+        let digest = "a".repeat(64);
+        assert_eq!(parse_checksum(&digest), Some(digest.clone()));
+        assert_eq!(
+            parse_checksum(&format!(
+                "{}  driftwatch-linux-x86_64\n",
+                digest.to_uppercase()
+            )),
+            Some(digest)
+        );
+        assert_eq!(parse_checksum("not a checksum"), None);
+    }
+
+    #[test]
+    fn test_verify_release() {
+        let (private_key, public) = generate_key().unwrap();
+        assert_eq!(public_key(&private_key).unwrap(), public);
+        let binary = b"driftwatch binary";
+        let checksum = checksum_line(binary, "driftwatch-linux-x86_64", "v1.2.0");
+        let signature = sign(&private_key, checksum.as_bytes()).unwrap();
+        let verify = |binary: &[u8], signature: Option<&str>, key: Option<&str>| {
+            verify_release(binary, checksum.as_bytes(), signature, key, Some("1.2.0"))
+        };
+
+        assert_eq!(
+            verify(binary, Some(&signature), Some(&public)).unwrap(),
+            Verification::Signed
+        );
+        assert_eq!(
+            verify(binary, None, Some(&public)).unwrap(),
+            Verification::Unsigned
+        );
+        assert_eq!(
+            verify(binary, Some(&signature), None).unwrap(),
+            Verification::NoKey
+        );
+        assert!(verify(b"tampered", Some(&signature), Some(&public)).is_err());
+
+        let (_, other) = generate_key().unwrap();
+        assert!(verify(binary, Some(&signature), Some(&other)).is_err());
+        let forged = sign(&generate_key().unwrap().0, checksum.as_bytes()).unwrap();
+        assert!(verify(binary, Some(&forged), Some(&public)).is_err());
+    }
+
+    #[test]
+    fn test_verify_release_version() {
+        let (private_key, public) = generate_key().unwrap();
+        let binary = b"driftwatch binary";
+        let checksum = checksum_line(binary, "driftwatch-linux-x86_64", "1.1.0");
+        assert_eq!(parse_checksum_version(&checksum), Some("1.1.0"));
+        let signature = sign(&private_key, checksum.as_bytes()).unwrap();
+        let verify = |checksum: &str, signature: &str, version| {
+            verify_release(
+                binary,
+                checksum.as_bytes(),
+                Some(signature),
+                Some(&public),
+                version,
+            )
+        };
+
+        assert!(verify(&checksum, &signature, Some("v1.1.0")).is_ok());
+        // An older release's valid signature doesn't pass for a newer one
+        let err = verify(&checksum, &signature, Some("1.2.0")).unwrap_err();
+        assert!(err.to_string().contains("for driftwatch 1.1.0, not 1.2.0"));
+        // Nor does a signed checksum that names no release at all
+        let bare = format!(
+            "{}  driftwatch-linux-x86_64\n",
+            parse_checksum(&checksum).unwrap()
+        );
+        let bare_signature = sign(&private_key, bare.as_bytes()).unwrap();
+        assert!(verify(&bare, &bare_signature, Some("1.1.0")).is_err());
+        assert!(verify(&bare, &bare_signature, None).is_ok());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::adapters::BenchmarkResult;
+use crate::hooks::Hooks;
+
+/// Default location of the project routing file, relative to the working directory
+pub const DEFAULT_ROUTES_FILE: &str = "driftwatch.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RoutesFile {
+    /// Benchmark name prefix -> project slug
+    #[serde(default)]
+    projects: BTreeMap<String, String>,
+    #[serde(default)]
+    hooks: Hooks,
+}
+
+/// Sends benchmarks to projects by name prefix, so a monorepo can report
+/// each crate or package to its own project from one run.
+#[derive(Debug, Default)]
+pub struct Routes {
+    /// Longest prefix first, so the most specific route wins
+    prefixes: Vec<(String, String)>,
+    /// Commands from the `[hooks]` table to run around the benchmarks
+    pub hooks: Hooks,
+}
+
+impl Routes {
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: RoutesFile = toml::from_str(content)?;
+        let mut prefixes: Vec<(String, String)> = file.projects.into_iter().collect();
+        prefixes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Ok(Self {
+            prefixes,
+            hooks: file.hooks,
+        })
+    }
+
+    /// Reads `path`, or `driftwatch.toml` when it exists and no path was given
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_ROUTES_FILE).exists() => Path::new(DEFAULT_ROUTES_FILE),
+            None => return Ok(Self::default()),
+        };
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid routing file {}", path.display()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+
+    pub fn project_for(&self, benchmark: &str) -> Option<&str> {
+        self.prefixes
+            .iter()
+            .find(|(prefix, _)| benchmark.starts_with(prefix.as_str()))
+            .map(|(_, project)| project.as_str())
+    }
+
+    /// Groups results by project, sending those no route matches to
+    /// `fallback`. Returns the groups and the results that went nowhere.
+    pub fn split(
+        &self,
+        results: Vec<BenchmarkResult>,
+        fallback: Option<&str>,
+    ) -> (BTreeMap<String, Vec<BenchmarkResult>>, Vec<BenchmarkResult>) {
+        let mut groups: BTreeMap<String, Vec<BenchmarkResult>> = BTreeMap::new();
+        let mut unrouted = Vec::new();
+
+        for result in results {
+            match self.project_for(&result.name).or(fallback) {
+                Some(project) => groups.entry(project.to_string()).or_default().push(result),
+                None => unrouted.push(result),
+            }
+        }
+
+        (groups, unrouted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUTES: &str = r#"
+[projects]
+"parser/" = "parser"
+"parser/json/" = "json"
+"storage::" = "storage"
+"#;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let routes = Routes::parse(ROUTES).unwrap();
+        assert_eq!(routes.project_for("parser/tokenize"), Some("parser"));
+        assert_eq!(routes.project_for("parser/json/decode"), Some("json"));
+        assert_eq!(routes.project_for("storage::write"), Some("storage"));
+        assert_eq!(routes.project_for("bench_misc"), None);
+    }
+
+    #[test]
+    fn test_split_uses_fallback() {
+        let routes = Routes::parse(ROUTES).unwrap();
+        let results = vec![
+            BenchmarkResult::new("parser/tokenize", "latency", 1.0),
+            BenchmarkResult::new("storage::write", "latency", 2.0),
+            BenchmarkResult::new("bench_misc", "latency", 3.0),
+        ];
+
+        let (groups, unrouted) = routes.split(results.clone(), None);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["parser", "storage"]);
+        assert_eq!(unrouted.len(), 1);
+        assert_eq!(unrouted[0].name, "bench_misc");
+
+        let (groups, unrouted) = routes.split(results, Some("monorepo"));
+        assert_eq!(groups["monorepo"][0].name, "bench_misc");
+        assert!(unrouted.is_empty());
+    }
+
+    #[test]
+    fn test_parses_hooks() {
+        let routes =
+            Routes::parse("[hooks]\npre = [\"make warm\"]\npost = [\"make stats\"]\n").unwrap();
+        assert!(routes.is_empty());
+        assert_eq!(routes.hooks.pre, vec!["make warm"]);
+        assert_eq!(routes.hooks.post, vec!["make stats"]);
+        assert!(Routes::parse("[hooks]\nbefore = []\n").is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        assert!(Routes::parse("[project]\n\"a/\" = \"a\"\n").is_err());
+        assert!(Routes::parse("").unwrap().is_empty());
+    }
+}
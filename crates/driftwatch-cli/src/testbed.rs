@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use crate::api::ProjectDetails;
+
+/// Link-local address of the EC2, GCE and Azure instance metadata services
+const METADATA_HOST: &str = "http://169.254.169.254";
+
+/// How long each metadata service gets to answer; off the cloud nothing
+/// listens there, so this is what `--testbed-instance-type` costs
+const METADATA_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Testbed `run` and `backfill` report to without `--testbed`: the OS and
+/// architecture, e.g. `linux-x86_64`, so x86 and ARM runners of the same OS
+/// don't share series
+pub fn default_name() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// The OS alone, which earlier versions defaulted to
+pub fn legacy_name() -> &'static str {
+    std::env::consts::OS
+}
+
+/// Testbed name with the cloud instance type appended, e.g.
+/// `linux-aarch64-c7g.xlarge`
+pub fn with_instance_type(testbed: &str, instance_type: &str) -> String {
+    format!("{}-{}", testbed, instance_type)
+}
+
+/// Warning for a run about to start a series on the new default testbed of
+/// a project whose history is on the legacy one, or `None` when the default
+/// already exists there, as a testbed or an alias.
+pub fn transition_warning(project: &ProjectDetails, testbed: &str, legacy: &str) -> Option<String> {
+    let exists = |name: &str| project.testbeds.iter().any(|t| t.name == name);
+    let aliased = project.testbed_aliases.iter().any(|a| a.alias == testbed);
+    if !exists(legacy) || exists(testbed) || aliased {
+        return None;
+    }
+    Some(format!(
+        "the default testbed is now {testbed}, which starts a new series; earlier results are on \
+         {legacy}. To continue them, run `driftwatch project add-testbed-alias {slug} {testbed} \
+         {legacy}` or pass --testbed {os}",
+        testbed = testbed,
+        legacy = legacy,
+        slug = project.slug,
+        os = legacy_name(),
+    ))
+}
+
+/// Machine type from the instance metadata service of EC2, GCE or Azure,
+/// whichever answers
+pub async fn instance_type() -> Option<String> {
+    let client = reqwest::Client::builder()
+        .timeout(METADATA_TIMEOUT)
+        .build()
+        .ok()?;
+    let (ec2, gce, azure) = tokio::join!(ec2(&client), gce(&client), azure(&client));
+    ec2.or(gce).or(azure)
+}
+
+async fn fetch(request: reqwest::RequestBuilder) -> Option<String> {
+    let response = request.send().await.ok()?.error_for_status().ok()?;
+    let text = response.text().await.ok()?;
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+/// IMDSv2 needs a session token first
+async fn ec2(client: &reqwest::Client) -> Option<String> {
+    let token = fetch(
+        client
+            .put(format!("{}/latest/api/token", METADATA_HOST))
+            .header("X-aws-ec2-metadata-token-ttl-seconds", "60"),
+    )
+    .await?;
+    fetch(
+        client
+            .get(format!("{}/latest/meta-data/instance-type", METADATA_HOST))
+            .header("X-aws-ec2-metadata-token", token),
+    )
+    .await
+}
+
+async fn gce(client: &reqwest::Client) -> Option<String> {
+    let machine_type = fetch(
+        client
+            .get(format!(
+                "{}/computeMetadata/v1/instance/machine-type",
+                METADATA_HOST
+            ))
+            .header("Metadata-Flavor", "Google"),
+    )
+    .await?;
+    Some(gce_machine_type(&machine_type).to_string())
+}
+
+/// GCE answers with the machine type's full path,
+/// `projects/<number>/zones/<zone>/machineTypes/<type>`
+fn gce_machine_type(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+async fn azure(client: &reqwest::Client) -> Option<String> {
+    fetch(
+        client
+            .get(format!(
+                "{}/metadata/instance/compute/vmSize?api-version=2021-02-01&format=text",
+                METADATA_HOST
+            ))
+            .header("Metadata", "true"),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Testbed, TestbedAlias};
+
+    fn project(testbeds: &[&str], aliases: &[(&str, &str)]) -> ProjectDetails {
+        ProjectDetails {
+            id: "1".to_string(),
+            slug: "my-project".to_string(),
+            name: "My Project".to_string(),
+            description: None,
+            public: false,
+            default_branch: "main".to_string(),
+            branches: Vec::new(),
+            branch_aliases: Vec::new(),
+            testbeds: testbeds
+                .iter()
+                .map(|name| Testbed {
+                    id: name.to_string(),
+                    name: name.to_string(),
+                })
+                .collect(),
+            testbed_aliases: aliases
+                .iter()
+                .map(|(alias, testbed)| TestbedAlias {
+                    alias: alias.to_string(),
+                    testbed: testbed.to_string(),
+                })
+                .collect(),
+            benchmarks: Vec::new(),
+            measures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_default_name() {
+        let name = default_name();
+        assert!(name.starts_with(legacy_name()));
+        assert!(name.ends_with(std::env::consts::ARCH));
+        assert_eq!(
+            with_instance_type("linux-aarch64", "c7g.xlarge"),
+            "linux-aarch64-c7g.xlarge"
+        );
+    }
+
+    #[test]
+    fn test_transition_warning() {
+        let warning =
+            transition_warning(&project(&["linux"], &[]), "linux-x86_64", "linux").unwrap();
+        assert!(warning.contains("add-testbed-alias my-project linux-x86_64 linux"));
+
+        // Nothing to continue, already moved, or already aliased
+        for details in [
+            project(&[], &[]),
+            project(&["linux", "linux-x86_64"], &[]),
+            project(&["linux"], &[("linux-x86_64", "linux")]),
+        ] {
+            assert_eq!(transition_warning(&details, "linux-x86_64", "linux"), None);
+        }
+    }
+
+    #[test]
+    fn test_gce_machine_type() {
+        assert_eq!(
+            gce_machine_type("projects/123/zones/us-central1-a/machineTypes/n2-standard-4"),
+            "n2-standard-4"
+        );
+        assert_eq!(gce_machine_type("e2-micro"), "e2-micro");
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::Result;
+use std::process::Output;
+use std::time::{Duration, Instant};
+
+use crate::adapters::BenchmarkResult;
+use crate::commands::run::wrapped_command;
+use crate::limits::{self, Limits};
+
+/// Resources the benchmark command used, measured by `run --time`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandTimes {
+    pub wall: Duration,
+    /// CPU time of the command and everything it waited on; not available
+    /// on Windows
+    pub user: Option<Duration>,
+    pub system: Option<Duration>,
+    /// Peak resident set size of the largest process, in bytes
+    pub max_rss: Option<u64>,
+}
+
+impl CommandTimes {
+    /// One result per measure, with times in nanoseconds like other
+    /// durations and memory in bytes
+    pub fn to_results(&self, name: &str) -> Vec<BenchmarkResult> {
+        let nanos = |d: Duration| d.as_nanos() as f64;
+        let mut results = vec![BenchmarkResult::new(name, "wall_time", nanos(self.wall))];
+        if let Some(user) = self.user {
+            results.push(BenchmarkResult::new(name, "user_time", nanos(user)));
+        }
+        if let Some(system) = self.system {
+            results.push(BenchmarkResult::new(name, "system_time", nanos(system)));
+        }
+        if let Some(max_rss) = self.max_rss {
+            results.push(BenchmarkResult::new(name, "max_rss", max_rss as f64));
+        }
+        results
+    }
+}
+
+#[cfg(unix)]
+fn timeval(tv: libc::timeval) -> Duration {
+    Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+}
+
+/// Runs the command like `limits::execute`, reaping it with `wait4` to get
+/// the resource usage of it and its descendants. Also returns whether it
+/// was killed at the timeout.
+#[cfg(unix)]
+pub fn execute_timed(
+    command: &[String],
+    prefix: &[String],
+    limits: &Limits,
+    stream: bool,
+) -> Result<(Output, CommandTimes, bool)> {
+    use anyhow::Context;
+    use std::os::unix::process::ExitStatusExt;
+
+    let started = Instant::now();
+    let mut child = limits.spawn(wrapped_command(command, None, prefix))?;
+    let stdout = limits::drain(child.stdout.take(), stream.then(std::io::stdout));
+    let stderr = limits::drain(child.stderr.take(), stream.then(std::io::stderr));
+
+    let pid = child.id() as libc::pid_t;
+    let ((status, usage), timed_out) = limits.wait(&mut child, |_, block| {
+        let mut status = 0;
+        // SAFETY: rusage is plain data that wait4 fills in
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        let flags = if block { 0 } else { libc::WNOHANG };
+        loop {
+            // SAFETY: pid is our unreaped child and both pointers are valid
+            // for the call
+            let reaped = unsafe { libc::wait4(pid, &mut status, flags, &mut usage) };
+            if reaped == pid {
+                return Ok(Some((status, usage)));
+            }
+            if reaped == 0 {
+                return Ok(None);
+            }
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::Interrupted {
+                return Err(err).context("Failed to wait for benchmark command");
+            }
+        }
+    })?;
+    let wall = started.elapsed();
+
+    // Linux reports kilobytes, macOS bytes
+    let rss_unit = if cfg!(target_os = "macos") { 1 } else { 1024 };
+    let times = CommandTimes {
+        wall,
+        user: Some(timeval(usage.ru_utime)),
+        system: Some(timeval(usage.ru_stime)),
+        max_rss: Some(usage.ru_maxrss as u64 * rss_unit),
+    };
+    let output = Output {
+        status: std::process::ExitStatus::from_raw(status),
+        stdout: limits::collect(stdout)?,
+        stderr: limits::collect(stderr)?,
+    };
+    Ok((output, times, timed_out))
+}
+
+/// Without `wait4` only the wall time can be measured
+#[cfg(not(unix))]
+pub fn execute_timed(
+    command: &[String],
+    prefix: &[String],
+    limits: &Limits,
+    stream: bool,
+) -> Result<(Output, CommandTimes, bool)> {
+    let started = Instant::now();
+    let (output, timed_out) = limits::execute(command, prefix, limits, stream)?;
+    let times = CommandTimes {
+        wall: started.elapsed(),
+        user: None,
+        system: None,
+        max_rss: None,
+    };
+    Ok((output, times, timed_out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crate::commands::run::shell_wrapped;
+
+    #[test]
+    fn test_to_results() {
+        let times = CommandTimes {
+            wall: Duration::from_millis(1500),
+            user: Some(Duration::from_secs(1)),
+            system: None,
+            max_rss: Some(4096),
+        };
+        let results: Vec<(String, f64)> = times
+            .to_results("release build")
+            .into_iter()
+            .map(|r| (r.measure, r.value))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                ("wall_time".to_string(), 1.5e9),
+                ("user_time".to_string(), 1e9),
+                ("max_rss".to_string(), 4096.0),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_timed() {
+        let sh = |line: &str| shell_wrapped(&[line.to_string()], &[]);
+        let limits = Limits::default();
+        let (output, times, _) = execute_timed(
+            &sh("echo out; echo err >&2; sleep 0.1"),
+            &[],
+            &limits,
+            false,
+        )
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "out\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "err\n");
+        assert!(times.wall >= Duration::from_millis(100));
+        assert!(times.max_rss.unwrap() > 0);
+
+        let (output, _, timed_out) = execute_timed(&sh("exit 4"), &[], &limits, false).unwrap();
+        assert_eq!(output.status.code(), Some(4));
+        assert!(!timed_out);
+    }
+}
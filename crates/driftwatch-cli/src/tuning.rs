@@ -0,0 +1,322 @@
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::api::ContextEntry;
+
+const CPU_SYSFS: &str = "/sys/devices/system/cpu";
+const INTEL_NO_TURBO: &str = "/sys/devices/system/cpu/intel_pstate/no_turbo";
+const CPUFREQ_BOOST: &str = "/sys/devices/system/cpu/cpufreq/boost";
+
+/// Ways to quiet a dedicated runner while the benchmarks run
+#[derive(Args, Debug, Default, Clone)]
+pub struct TuningArgs {
+    /// Pin the benchmark command to these cores, e.g. 2,3 or 4-7 (Linux)
+    #[arg(long, value_name = "LIST", value_parser = parse_cpu_list)]
+    pub cpus: Option<CpuList>,
+
+    /// Run the benchmark command at this niceness; negative values need root
+    #[arg(long, allow_negative_numbers = true, value_parser = clap::value_parser!(i32).range(-20..=19))]
+    pub nice: Option<i32>,
+
+    /// Switch the CPU frequency governor to `performance` for the run (Linux, needs root)
+    #[arg(long)]
+    pub performance_governor: bool,
+
+    /// Disable turbo boost for the run (Linux, needs root)
+    #[arg(long)]
+    pub no_turbo: bool,
+}
+
+/// Sorted, deduplicated core numbers
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuList(pub Vec<usize>);
+
+/// Parse a core list such as `0,2,4-7`
+pub fn parse_cpu_list(arg: &str) -> Result<CpuList, String> {
+    let mut cpus = Vec::new();
+    for part in arg.split(',').map(str::trim) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => (part, part),
+        };
+        let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) else {
+            return Err(format!("expected cores like 2,3 or 4-7, got '{}'", arg));
+        };
+        if start > end {
+            return Err(format!("core range '{}' runs backwards", part));
+        }
+        cpus.extend(start..=end);
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(CpuList(cpus))
+}
+
+/// Format cores back into the `taskset --cpu-list` form, collapsing runs
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < cpus.len() {
+        let start = cpus[i];
+        while i + 1 < cpus.len() && cpus[i + 1] == cpus[i] + 1 {
+            i += 1;
+        }
+        if cpus[i] == start {
+            parts.push(start.to_string());
+        } else {
+            parts.push(format!("{}-{}", start, cpus[i]));
+        }
+        i += 1;
+    }
+    parts.join(",")
+}
+
+/// What was actually applied for a run. Machine-wide settings go back to
+/// their previous values when this is dropped.
+#[derive(Debug, Default)]
+pub struct Applied {
+    /// Program and arguments the benchmark command runs under
+    pub prefix: Vec<String>,
+    /// Recorded with the report so tuned and untuned runs can be told apart
+    pub context: Vec<ContextEntry>,
+    restore: Vec<(PathBuf, String)>,
+}
+
+impl Drop for Applied {
+    fn drop(&mut self) {
+        for (path, value) in self.restore.drain(..).rev() {
+            if let Err(e) = std::fs::write(&path, &value) {
+                eprintln!(
+                    "Warning: failed to restore {} to {}: {}",
+                    path.display(),
+                    value.trim(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+impl Applied {
+    fn record(&mut self, key: &str, value: String) {
+        self.context.push(ContextEntry {
+            key: key.to_string(),
+            value,
+        });
+    }
+
+    /// Writes `value` to a sysfs file, remembering the old contents
+    fn write(&mut self, path: &Path, value: &str) -> std::io::Result<()> {
+        let previous = std::fs::read_to_string(path)?;
+        if previous.trim() == value {
+            return Ok(());
+        }
+        std::fs::write(path, value)?;
+        self.restore.push((path.to_path_buf(), previous));
+        Ok(())
+    }
+}
+
+/// True when a wrapper program accepts the settings, so a niceness the user
+/// isn't permitted isn't recorded as applied
+fn probe(program: &str, args: &[String]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .arg("true")
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() || !stderr.trim().is_empty() {
+        bail!("{}", stderr.trim());
+    }
+    Ok(())
+}
+
+/// Cores whose governor to set: the pinned ones, or every core
+fn governor_paths(cpus: Option<&[usize]>) -> Result<Vec<PathBuf>> {
+    let paths = match cpus {
+        Some(cpus) => cpus
+            .iter()
+            .map(|cpu| PathBuf::from(format!("{}/cpu{}/cpufreq/scaling_governor", CPU_SYSFS, cpu)))
+            .collect(),
+        None => {
+            let mut paths = Vec::new();
+            for entry in std::fs::read_dir(CPU_SYSFS)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let is_core = name
+                    .strip_prefix("cpu")
+                    .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+                if is_core {
+                    paths.push(entry.path().join("cpufreq/scaling_governor"));
+                }
+            }
+            paths.sort();
+            paths
+        }
+    };
+    Ok(paths)
+}
+
+impl TuningArgs {
+    pub fn is_empty(&self) -> bool {
+        self.cpus.is_none() && self.nice.is_none() && !self.performance_governor && !self.no_turbo
+    }
+
+    /// Applies what the platform and permissions allow. Settings that can't
+    /// be applied are skipped with a warning rather than failing the run.
+    pub fn apply(&self) -> Result<Applied> {
+        let mut applied = Applied::default();
+        if self.is_empty() {
+            return Ok(applied);
+        }
+        let linux = cfg!(target_os = "linux");
+
+        if let Some(cpus) = &self.cpus {
+            if !linux {
+                bail!("--cpus is only supported on Linux");
+            }
+            let list = format_cpu_list(&cpus.0);
+            let args = vec!["--cpu-list".to_string(), list.clone()];
+            probe("taskset", &args).context("Can't pin to the requested cores")?;
+            applied.prefix.push("taskset".to_string());
+            applied.prefix.extend(args);
+            applied.record("cpus", list);
+        }
+
+        if let Some(nice) = self.nice {
+            if cfg!(windows) {
+                bail!("--nice is not supported on Windows");
+            }
+            let args = vec!["-n".to_string(), nice.to_string()];
+            match probe("nice", &args) {
+                Ok(()) => {
+                    applied.prefix.push("nice".to_string());
+                    applied.prefix.extend(args);
+                    applied.record("nice", nice.to_string());
+                }
+                Err(e) => eprintln!("Warning: not setting niceness {}: {:#}", nice, e),
+            }
+        }
+
+        if self.performance_governor {
+            let result = if linux {
+                governor_paths(self.cpus.as_ref().map(|c| c.0.as_slice())).and_then(|paths| {
+                    for path in paths {
+                        applied
+                            .write(&path, "performance")
+                            .with_context(|| format!("Failed to write {}", path.display()))?;
+                    }
+                    Ok(())
+                })
+            } else {
+                Err(anyhow::anyhow!("only supported on Linux"))
+            };
+            match result {
+                Ok(()) => applied.record("cpu_governor", "performance".to_string()),
+                Err(e) => eprintln!("Warning: not setting the performance governor: {:#}", e),
+            }
+        }
+
+        if self.no_turbo {
+            let result = if !linux {
+                Err(anyhow::anyhow!("only supported on Linux"))
+            } else if Path::new(INTEL_NO_TURBO).exists() {
+                applied
+                    .write(Path::new(INTEL_NO_TURBO), "1")
+                    .map_err(Into::into)
+            } else if Path::new(CPUFREQ_BOOST).exists() {
+                applied
+                    .write(Path::new(CPUFREQ_BOOST), "0")
+                    .map_err(Into::into)
+            } else {
+                Err(anyhow::anyhow!("no turbo control found"))
+            };
+            match result {
+                Ok(()) => applied.record("turbo", "off".to_string()),
+                Err(e) => eprintln!("Warning: not disabling turbo: {:#}", e),
+            }
+        }
+
+        Ok(applied)
+    }
+}
+
+/// Adds applied settings to the user's context, replacing any entries the
+/// user gave for the same keys
+pub fn merge_context(user: &[ContextEntry], applied: &[ContextEntry]) -> Vec<ContextEntry> {
+    user.iter()
+        .filter(|entry| !applied.iter().any(|a| a.key == entry.key))
+        .chain(applied)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("2,3").unwrap(), CpuList(vec![2, 3]));
+        assert_eq!(
+            parse_cpu_list("0, 4-6,5").unwrap(),
+            CpuList(vec![0, 4, 5, 6])
+        );
+        assert!(parse_cpu_list("").is_err());
+        assert!(parse_cpu_list("a-b").is_err());
+        assert!(parse_cpu_list("6-4").is_err());
+    }
+
+    #[test]
+    fn test_format_cpu_list() {
+        assert_eq!(format_cpu_list(&[2, 3]), "2-3");
+        assert_eq!(format_cpu_list(&[0, 4, 5, 6, 9]), "0,4-6,9");
+        assert_eq!(format_cpu_list(&[7]), "7");
+    }
+
+    #[test]
+    fn test_merge_context() {
+        let entry = |key: &str, value: &str| ContextEntry {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let merged = merge_context(
+            &[entry("rustc", "1.80"), entry("cpus", "0")],
+            &[entry("cpus", "2-3")],
+        );
+        assert_eq!(merged, vec![entry("rustc", "1.80"), entry("cpus", "2-3")]);
+    }
+
+    #[test]
+    fn test_parses_flags() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            tuning: TuningArgs,
+        }
+
+        let cli = <Cli as clap::Parser>::try_parse_from([
+            "driftwatch",
+            "--cpus",
+            "2-3",
+            "--nice",
+            "-10",
+            "--no-turbo",
+        ])
+        .unwrap();
+        assert_eq!(cli.tuning.cpus, Some(CpuList(vec![2, 3])));
+        assert_eq!(cli.tuning.nice, Some(-10));
+        assert!(cli.tuning.no_turbo && !cli.tuning.performance_governor);
+        assert!(<Cli as clap::Parser>::try_parse_from(["driftwatch", "--nice", "30"]).is_err());
+    }
+
+    #[test]
+    fn test_nothing_requested() {
+        let applied = TuningArgs::default().apply().unwrap();
+        assert!(applied.prefix.is_empty());
+        assert!(applied.context.is_empty());
+    }
+}
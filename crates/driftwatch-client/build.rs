@@ -0,0 +1,505 @@
+//! Generates Rust types for `schema.graphql` and the operations in
+//! `operations/`. Every selected field and argument is checked against the
+//! schema, so an operation that drifts from the server fails the build
+//! rather than at runtime.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use async_graphql_parser::types::{
+    BaseType, DocumentOperations, ExecutableDocument, InputObjectType, OperationType, Selection,
+    SelectionSet, ServiceDocument, Type, TypeDefinition, TypeKind, TypeSystemDefinition,
+};
+use async_graphql_parser::Positioned;
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> Result<()> {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR")?);
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+    let schema_path = manifest_dir.join("schema.graphql");
+    let operations_dir = manifest_dir.join("operations");
+    println!("cargo:rerun-if-changed={}", schema_path.display());
+    println!("cargo:rerun-if-changed={}", operations_dir.display());
+
+    let document = async_graphql_parser::parse_schema(std::fs::read_to_string(&schema_path)?)
+        .map_err(|e| format!("schema.graphql: {}", e))?;
+    let schema = Schema::new(&document);
+    std::fs::write(out_dir.join("types.rs"), schema.types())?;
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&operations_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "graphql"));
+    paths.sort();
+
+    let mut operations = String::new();
+    for path in &paths {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let source = std::fs::read_to_string(path)?;
+        schema
+            .operation(&mut operations, &source)
+            .map_err(|e| format!("{}: {}", display_name(path), e))?;
+    }
+    std::fs::write(out_dir.join("operations.rs"), operations)?;
+    Ok(())
+}
+
+fn display_name(path: &Path) -> String {
+    format!(
+        "operations/{}",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    )
+}
+
+struct Schema<'a> {
+    types: Vec<&'a TypeDefinition>,
+    by_name: HashMap<&'a str, &'a TypeDefinition>,
+    query: String,
+    mutation: String,
+}
+
+impl<'a> Schema<'a> {
+    fn new(document: &'a ServiceDocument) -> Self {
+        let mut schema = Schema {
+            types: Vec::new(),
+            by_name: HashMap::new(),
+            query: "Query".to_string(),
+            mutation: "Mutation".to_string(),
+        };
+        for definition in &document.definitions {
+            match definition {
+                TypeSystemDefinition::Schema(roots) => {
+                    if let Some(query) = &roots.node.query {
+                        schema.query = query.node.to_string();
+                    }
+                    if let Some(mutation) = &roots.node.mutation {
+                        schema.mutation = mutation.node.to_string();
+                    }
+                }
+                TypeSystemDefinition::Type(ty) => {
+                    schema.types.push(&ty.node);
+                    schema.by_name.insert(ty.node.name.node.as_str(), &ty.node);
+                }
+                TypeSystemDefinition::Directive(_) => {}
+            }
+        }
+        schema
+    }
+
+    /// The Rust type for a scalar, enum or input object name.
+    fn leaf_type(&self, name: &str) -> String {
+        match name {
+            "ID" | "String" => "String".to_string(),
+            "Int" => "i32".to_string(),
+            "Float" => "f64".to_string(),
+            "Boolean" => "bool".to_string(),
+            "DateTime" => "chrono::DateTime<chrono::Utc>".to_string(),
+            _ => match self.by_name.get(name).map(|ty| &ty.kind) {
+                Some(TypeKind::Enum(_) | TypeKind::InputObject(_)) => {
+                    format!("crate::types::{}", name)
+                }
+                _ => "serde_json::Value".to_string(),
+            },
+        }
+    }
+
+    /// Whether an input object can derive `Default`: enums can't, so
+    /// neither can inputs that require one.
+    fn input_has_default(&self, input: &InputObjectType, depth: usize) -> bool {
+        depth < 8
+            && input.fields.iter().all(|field| {
+                let ty = &field.node.ty.node;
+                match &ty.base {
+                    _ if ty.nullable => true,
+                    BaseType::List(_) => true,
+                    BaseType::Named(name) => match self.by_name.get(name.as_str()).map(|t| &t.kind)
+                    {
+                        Some(TypeKind::Enum(_)) => false,
+                        Some(TypeKind::InputObject(inner)) => {
+                            self.input_has_default(inner, depth + 1)
+                        }
+                        _ => true,
+                    },
+                }
+            })
+    }
+
+    /// Enums and input objects, shared by every operation.
+    fn types(&self) -> String {
+        let mut out = String::new();
+        for ty in &self.types {
+            let name = ty.name.node.as_str();
+            match &ty.kind {
+                TypeKind::Enum(enum_type) => {
+                    write_doc(&mut out, "", ty.description.as_ref().map(|d| &d.node));
+                    out.push_str(
+                        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]\n",
+                    );
+                    let _ = writeln!(out, "pub enum {} {{", name);
+                    for value in &enum_type.values {
+                        let value_name = value.node.value.node.as_str();
+                        write_doc(
+                            &mut out,
+                            "    ",
+                            value.node.description.as_ref().map(|d| &d.node),
+                        );
+                        let _ = writeln!(out, "    #[serde(rename = \"{}\")]", value_name);
+                        let _ = writeln!(out, "    {},", pascal_case(value_name));
+                    }
+                    out.push_str("}\n\n");
+                }
+                TypeKind::InputObject(input) => {
+                    write_doc(&mut out, "", ty.description.as_ref().map(|d| &d.node));
+                    if self.input_has_default(input, 0) {
+                        out.push_str("#[derive(Debug, Clone, Default, Serialize)]\n");
+                    } else {
+                        out.push_str("#[derive(Debug, Clone, Serialize)]\n");
+                    }
+                    let _ = writeln!(out, "pub struct {} {{", name);
+                    for field in &input.fields {
+                        let field_type = rust_type(&field.node.ty.node, &|base| {
+                            let leaf = self.leaf_type(base);
+                            // A nullable field of its own type needs a box
+                            if base == name {
+                                format!("Box<{}>", leaf)
+                            } else {
+                                leaf
+                            }
+                        });
+                        write_doc(
+                            &mut out,
+                            "    ",
+                            field.node.description.as_ref().map(|d| &d.node),
+                        );
+                        write_input_field(&mut out, field.node.name.node.as_str(), &field_type);
+                    }
+                    out.push_str("}\n\n");
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    /// A module with the operation's variables and response types, and a
+    /// marker type implementing `GraphQLOperation`.
+    fn operation(&self, out: &mut String, source: &str) -> Result<()> {
+        let document = async_graphql_parser::parse_query(source)?;
+        let (name, operation) = match &document.operations {
+            DocumentOperations::Multiple(operations) if operations.len() == 1 => {
+                let (name, operation) = operations.iter().next().ok_or("no operation")?;
+                (name.as_str(), &operation.node)
+            }
+            _ => return Err("each file needs exactly one named operation".into()),
+        };
+        let root = match operation.ty {
+            OperationType::Query => &self.query,
+            OperationType::Mutation => &self.mutation,
+            OperationType::Subscription => return Err("subscriptions aren't supported".into()),
+        };
+        let module = snake_case(name);
+
+        let _ = writeln!(out, "pub struct {};\n", name);
+        let _ = writeln!(out, "impl crate::GraphQLOperation for {} {{", name);
+        let _ = writeln!(out, "    type Variables = {}::Variables;", module);
+        let _ = writeln!(out, "    type ResponseData = {}::ResponseData;", module);
+        let _ = writeln!(
+            out,
+            "    const OPERATION_NAME: &'static str = \"{}\";",
+            name
+        );
+        let _ = writeln!(out, "    const QUERY: &'static str = {}::QUERY;", module);
+        out.push_str("}\n\n");
+
+        let _ = writeln!(out, "pub mod {} {{", module);
+        out.push_str("    use serde::{Deserialize, Serialize};\n\n");
+        let _ = writeln!(out, "    pub const QUERY: &str = {:?};\n", source.trim());
+
+        let has_default = operation.variable_definitions.iter().all(|variable| {
+            let ty = &variable.node.var_type.node;
+            ty.nullable
+                || !self.leaf_type(base_name(ty)).starts_with("crate::types::")
+                || matches!(
+                    self.by_name.get(base_name(ty)).map(|t| &t.kind),
+                    Some(TypeKind::InputObject(input)) if self.input_has_default(input, 0)
+                )
+        });
+        if has_default {
+            out.push_str("    #[derive(Debug, Clone, Default, Serialize)]\n");
+        } else {
+            out.push_str("    #[derive(Debug, Clone, Serialize)]\n");
+        }
+        out.push_str("    pub struct Variables {\n");
+        for variable in &operation.variable_definitions {
+            let variable = &variable.node;
+            let ty = rust_type(&variable.var_type.node, &|base| self.leaf_type(base));
+            let mut field = String::new();
+            write_input_field(&mut field, variable.name.node.as_str(), &ty);
+            for line in field.lines() {
+                let _ = writeln!(out, "    {}", line);
+            }
+        }
+        out.push_str("    }\n\n");
+
+        let mut structs = Vec::new();
+        self.selection(
+            &document,
+            root,
+            &operation.selection_set.node,
+            "ResponseData",
+            "",
+            &mut structs,
+        )?;
+        for (i, body) in structs.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            for line in body.lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    let _ = writeln!(out, "    {}", line);
+                }
+            }
+        }
+        out.push_str("}\n\n");
+        Ok(())
+    }
+
+    /// Emits a response struct for a selection on `type_name`, and one for
+    /// each nested object, named after the path of fields leading to it.
+    fn selection(
+        &self,
+        document: &ExecutableDocument,
+        type_name: &str,
+        selection_set: &SelectionSet,
+        struct_name: &str,
+        path: &str,
+        structs: &mut Vec<String>,
+    ) -> Result<()> {
+        let object_fields = match self.by_name.get(type_name).map(|ty| &ty.kind) {
+            Some(TypeKind::Object(object)) => &object.fields,
+            Some(TypeKind::Interface(interface)) => &interface.fields,
+            _ => return Err(format!("{} has no fields to select", type_name).into()),
+        };
+
+        let mut fields = Vec::new();
+        flatten(document, selection_set, &mut fields)?;
+
+        let mut body = String::new();
+        body.push_str("#[derive(Debug, Clone, Deserialize)]\n");
+        let _ = writeln!(body, "pub struct {} {{", struct_name);
+        let mut nested = Vec::new();
+        for field in fields {
+            let field = &field.node;
+            let field_name = field.name.node.as_str();
+            let response_name = field
+                .alias
+                .as_ref()
+                .map_or(field_name, |alias| alias.node.as_str());
+            if field_name == "__typename" {
+                write_output_field(&mut body, response_name, "String");
+                continue;
+            }
+            let definition = object_fields
+                .iter()
+                .find(|f| f.node.name.node == field_name)
+                .ok_or_else(|| format!("{} has no field {}", type_name, field_name))?;
+            for (argument, _) in &field.arguments {
+                if !definition
+                    .node
+                    .arguments
+                    .iter()
+                    .any(|a| a.node.name.node == argument.node)
+                {
+                    return Err(format!(
+                        "{}.{} takes no argument {}",
+                        type_name, field_name, argument.node
+                    )
+                    .into());
+                }
+            }
+
+            let field_type = &definition.node.ty.node;
+            let base = base_name(field_type);
+            let selects_object = matches!(
+                self.by_name.get(base).map(|ty| &ty.kind),
+                Some(TypeKind::Object(_) | TypeKind::Interface(_))
+            );
+            let rust = if selects_object {
+                if field.selection_set.node.items.is_empty() {
+                    return Err(format!("{}.{} needs a selection", type_name, field_name).into());
+                }
+                let nested_name = format!("{}{}", path, upper_first(response_name));
+                nested.push((base, &field.selection_set.node, nested_name.clone()));
+                rust_type(field_type, &|_| nested_name.clone())
+            } else {
+                if !field.selection_set.node.items.is_empty() {
+                    return Err(
+                        format!("{}.{} can't have a selection", type_name, field_name).into(),
+                    );
+                }
+                rust_type(field_type, &|base| self.leaf_type(base))
+            };
+            write_output_field(&mut body, response_name, &rust);
+        }
+        body.push_str("}\n");
+        structs.push(body);
+
+        for (base, selection_set, nested_name) in nested {
+            self.selection(
+                document,
+                base,
+                selection_set,
+                &nested_name,
+                &nested_name,
+                structs,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The fields of a selection set, with fragment spreads and inline
+/// fragments merged in.
+fn flatten<'d>(
+    document: &'d ExecutableDocument,
+    selection_set: &'d SelectionSet,
+    fields: &mut Vec<&'d Positioned<async_graphql_parser::types::Field>>,
+) -> Result<()> {
+    for item in &selection_set.items {
+        match &item.node {
+            Selection::Field(field) => {
+                let response_name = |f: &async_graphql_parser::types::Field| {
+                    f.alias
+                        .as_ref()
+                        .map_or(f.name.node.to_string(), |a| a.node.to_string())
+                };
+                if !fields
+                    .iter()
+                    .any(|f| response_name(&f.node) == response_name(&field.node))
+                {
+                    fields.push(field);
+                }
+            }
+            Selection::FragmentSpread(spread) => {
+                let name = &spread.node.fragment_name.node;
+                let fragment = document
+                    .fragments
+                    .get(name)
+                    .ok_or_else(|| format!("unknown fragment {}", name))?;
+                flatten(document, &fragment.node.selection_set.node, fields)?;
+            }
+            Selection::InlineFragment(fragment) => {
+                flatten(document, &fragment.node.selection_set.node, fields)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn base_name(ty: &Type) -> &str {
+    match &ty.base {
+        BaseType::Named(name) => name.as_str(),
+        BaseType::List(item) => base_name(item),
+    }
+}
+
+fn rust_type(ty: &Type, leaf: &dyn Fn(&str) -> String) -> String {
+    let inner = match &ty.base {
+        BaseType::Named(name) => leaf(name.as_str()),
+        BaseType::List(item) => format!("Vec<{}>", rust_type(item, leaf)),
+    };
+    if ty.nullable {
+        format!("Option<{}>", inner)
+    } else {
+        inner
+    }
+}
+
+fn write_doc(out: &mut String, indent: &str, description: Option<&String>) {
+    if let Some(description) = description {
+        for line in description.lines() {
+            let _ = writeln!(out, "{}#[doc = {:?}]", indent, format!(" {}", line));
+        }
+    }
+}
+
+/// A field's Rust name, and a rename when it differs from the GraphQL one.
+fn field_name(name: &str) -> (String, Option<String>) {
+    let mut rust = snake_case(name);
+    if RUST_KEYWORDS.contains(&rust.as_str()) {
+        rust.push('_');
+    }
+    let rename = (rust != name).then(|| format!("#[serde(rename = \"{}\")]", name));
+    (rust, rename)
+}
+
+fn write_input_field(out: &mut String, name: &str, ty: &str) {
+    let (rust, rename) = field_name(name);
+    if let Some(rename) = rename {
+        let _ = writeln!(out, "    {}", rename);
+    }
+    if ty.starts_with("Option<") {
+        out.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+    }
+    let _ = writeln!(out, "    pub {}: {},", rust, ty);
+}
+
+fn write_output_field(out: &mut String, name: &str, ty: &str) {
+    let (rust, rename) = field_name(name);
+    if let Some(rename) = rename {
+        let _ = writeln!(out, "    {}", rename);
+    }
+    let _ = writeln!(out, "    pub {}: {},", rust, ty);
+}
+
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let after_lower =
+                i > 0 && (chars[i - 1].is_ascii_lowercase() || chars[i - 1].is_ascii_digit());
+            let acronym_end = i > 0
+                && chars[i - 1].is_ascii_uppercase()
+                && chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if after_lower || acronym_end {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// `WONT_FIX` becomes `WontFix`.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+            })
+        })
+        .collect()
+}
+
+fn upper_first(name: &str) -> String {
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_ascii_uppercase().to_string() + chars.as_str()
+    })
+}
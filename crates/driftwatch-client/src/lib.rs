@@ -0,0 +1,448 @@
+//! Typed client for the Driftwatch GraphQL API.
+//!
+//! Each query in `operations/` becomes a marker type in [`operations`]
+//! with a module of the same name, in snake case, holding its `Variables`
+//! and `ResponseData`. Enums and input objects from the schema live in
+//! [`types`]. Both are generated at build time from `schema.graphql`, so a
+//! query that no longer matches the server's schema doesn't compile.
+//!
+//! ```no_run
+//! use driftwatch_client::{operations, Client};
+//!
+//! # async fn run() -> Result<(), driftwatch_client::Error> {
+//! let client = Client::new("https://api.driftwatch.dev").with_token("dw_...");
+//! let data = client
+//!     .execute::<operations::Reports>(operations::reports::Variables {
+//!         project_slug: "core".to_string(),
+//!         limit: Some(10),
+//!     })
+//!     .await?;
+//! for report in data.project.map(|p| p.reports).unwrap_or_default() {
+//!     println!("{} {}", report.id, report.branch.name);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Client::send`] runs any document and hands back the whole response,
+//! for callers such as `driftwatch-sdk` that build their own queries.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use driftwatch_protocol::{compression, CLIENT_HEADER};
+
+/// How often a request refused with 429 is retried, and the longest wait
+/// honored from `Retry-After`
+pub const BUSY_RETRIES: u32 = 5;
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+/// Receives messages meant for a person, such as retries while the server
+/// is busy.
+pub type Notify = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Enums and input objects of the schema.
+#[allow(clippy::all)]
+pub mod types {
+    use serde::{Deserialize, Serialize};
+
+    include!(concat!(env!("OUT_DIR"), "/types.rs"));
+}
+
+/// One marker type per operation in `operations/`.
+#[allow(clippy::all)]
+pub mod operations {
+    include!(concat!(env!("OUT_DIR"), "/operations.rs"));
+}
+
+/// A GraphQL query or mutation with its typed variables and response.
+pub trait GraphQLOperation {
+    type Variables: Serialize;
+    type ResponseData: DeserializeOwned;
+    const OPERATION_NAME: &'static str;
+    const QUERY: &'static str;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Failed to parse response: {0}")]
+    Decode(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("GraphQL error: {}", .0.first().map(ToString::to_string).unwrap_or_default())]
+    GraphQL(Vec<GraphQLError>),
+    #[error("No data in response (status: {0})")]
+    NoData(reqwest::StatusCode),
+}
+
+/// An error the server returned for an operation.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(default)]
+    pub extensions: ErrorExtensions,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ErrorExtensions {
+    /// The ID to quote when reporting the error
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+}
+
+impl std::fmt::Display for GraphQLError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.extensions.request_id {
+            Some(id) => write!(f, "{} (request ID {})", self.message, id),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Request<'a, V> {
+    query: &'a str,
+    #[serde(rename = "operationName", skip_serializing_if = "Option::is_none")]
+    operation_name: Option<&'a str>,
+    variables: &'a V,
+}
+
+/// A response as the server sent it, errors and extensions included.
+#[derive(Debug, Deserialize)]
+pub struct Response<T> {
+    #[serde(skip)]
+    pub status: reqwest::StatusCode,
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQLError>,
+    #[serde(default)]
+    pub extensions: ResponseExtensions,
+}
+
+impl<T> Response<T> {
+    /// The data, unless the server reported errors, which win even over
+    /// partial data
+    pub fn into_data(self) -> Result<T, Error> {
+        if !self.errors.is_empty() {
+            return Err(Error::GraphQL(self.errors));
+        }
+        self.data.ok_or(Error::NoData(self.status))
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResponseExtensions {
+    /// The server's API version; missing from servers that predate API
+    /// versioning
+    #[serde(rename = "apiVersion")]
+    pub api_version: Option<i32>,
+}
+
+/// Sends operations to a Driftwatch server.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    user_agent: String,
+    notify: Option<Notify>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("base_url", &self.base_url)
+            .field("user_agent", &self.user_agent)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// A client for the API at `base_url`, e.g. `https://api.driftwatch.dev`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: None,
+            user_agent: concat!("driftwatch-client/", env!("CARGO_PKG_VERSION")).to_string(),
+            notify: None,
+        }
+    }
+
+    /// Authenticates requests with an API key or session token.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Names the integration to the server, e.g. `my-tool/1.2.0`, so its
+    /// use of deprecated fields can be traced back to it.
+    pub fn with_client_name(mut self, name: &str) -> Self {
+        self.user_agent = name.to_string();
+        self
+    }
+
+    /// Uses an existing `reqwest` client, e.g. one with a proxy or timeouts.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Tells `notify` when a request is retried because the server is busy.
+    pub fn with_notify(mut self, notify: Notify) -> Self {
+        self.notify = Some(notify);
+        self
+    }
+
+    /// The API's address, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Runs an operation. Errors the server reports fail the call even
+    /// when it returned partial data.
+    pub async fn execute<Op: GraphQLOperation>(
+        &self,
+        variables: Op::Variables,
+    ) -> Result<Op::ResponseData, Error> {
+        self.send(Op::QUERY, Some(Op::OPERATION_NAME), &variables)
+            .await?
+            .into_data()
+    }
+
+    /// Runs any GraphQL document and returns the whole response. A request
+    /// the server refuses with 429 is sent again after the wait it asks
+    /// for, up to [`BUSY_RETRIES`] times; it refuses before doing any of
+    /// the work, so that's safe for mutations too.
+    pub async fn send<V: Serialize, T: DeserializeOwned>(
+        &self,
+        query: &str,
+        operation_name: Option<&str>,
+        variables: &V,
+    ) -> Result<Response<T>, Error> {
+        let mut retries = 0;
+        let response = loop {
+            let mut request = self
+                .http
+                .post(format!("{}/graphql", self.base_url))
+                .header(CLIENT_HEADER, &self.user_agent)
+                .header(reqwest::header::ACCEPT_ENCODING, "gzip")
+                .json(&Request {
+                    query,
+                    operation_name,
+                    variables,
+                });
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+            let response = request.send().await?;
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS
+                || retries == BUSY_RETRIES
+            {
+                break response;
+            }
+            retries += 1;
+            let wait = retry_after(response.headers().get(reqwest::header::RETRY_AFTER));
+            if let Some(notify) = &self.notify {
+                notify(&format!(
+                    "Server is busy; retrying in {}s ({}/{})",
+                    wait.as_secs(),
+                    retries,
+                    BUSY_RETRIES
+                ));
+            }
+            tokio::time::sleep(wait).await;
+        };
+
+        let status = response.status();
+        let gzipped = response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"gzip"));
+        let bytes = response.bytes().await?;
+        let mut body: Response<T> = decode_response(&bytes, gzipped)?;
+        body.status = status;
+        Ok(body)
+    }
+}
+
+/// Parses a response body, which the server gzips when it's large.
+fn decode_response<T: DeserializeOwned>(bytes: &[u8], gzipped: bool) -> Result<Response<T>, Error> {
+    let decoded;
+    let json = if gzipped {
+        decoded = compression::gunzip(bytes).map_err(|e| Error::Decode(e.into()))?;
+        &decoded[..]
+    } else {
+        bytes
+    };
+    serde_json::from_slice(json).map_err(|e| Error::Decode(e.into()))
+}
+
+/// How long to wait before retrying, from a `Retry-After` header in seconds.
+/// HTTP dates and missing headers fall back to a default.
+pub fn retry_after(header: Option<&reqwest::header::HeaderValue>) -> Duration {
+    header
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+        .min(MAX_RETRY_AFTER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `schema.graphql` must match the server's schema; regenerate it with
+    /// `UPDATE_SCHEMA=1 cargo test -p driftwatch-client`
+    #[test]
+    fn test_schema_is_current() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schema.graphql");
+        let sdl = driftwatch_api::graphql::build_schema().sdl();
+        if std::env::var_os("UPDATE_SCHEMA").is_some() {
+            std::fs::write(&path, &sdl).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == sdl,
+            "schema.graphql is out of date; run UPDATE_SCHEMA=1 cargo test -p driftwatch-client"
+        );
+    }
+
+    #[test]
+    fn test_retry_after() {
+        use reqwest::header::HeaderValue;
+        assert_eq!(
+            retry_after(Some(&HeaderValue::from_static("30"))),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            retry_after(Some(&HeaderValue::from_static("86400"))),
+            MAX_RETRY_AFTER
+        );
+        assert_eq!(
+            retry_after(Some(&HeaderValue::from_static(
+                "Wed, 21 Oct 2026 07:28:00 GMT"
+            ))),
+            DEFAULT_RETRY_AFTER
+        );
+        assert_eq!(retry_after(None), DEFAULT_RETRY_AFTER);
+    }
+
+    #[test]
+    fn test_reads_api_version_extension() {
+        let body: Response<serde_json::Value> =
+            serde_json::from_str(r#"{"data": {}, "extensions": {"apiVersion": 3}}"#).unwrap();
+        assert_eq!(body.extensions.api_version, Some(3));
+
+        let body: Response<serde_json::Value> = serde_json::from_str(r#"{"data": {}}"#).unwrap();
+        assert_eq!(body.extensions.api_version, None);
+    }
+
+    #[test]
+    fn test_decode_gzipped_response() {
+        let json = br#"{"data": {"me": {"id": "1"}}}"#;
+        let body: Response<serde_json::Value> =
+            decode_response(&compression::gzip(json).unwrap(), true).unwrap();
+        assert_eq!(body.into_data().unwrap()["me"]["id"], "1");
+        let body: Response<serde_json::Value> = decode_response(json, false).unwrap();
+        assert_eq!(body.into_data().unwrap()["me"]["id"], "1");
+        assert!(matches!(
+            decode_response::<serde_json::Value>(json, true),
+            Err(Error::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_errors_win_over_data() {
+        let body: Response<serde_json::Value> = serde_json::from_str(
+            r#"{"data": {"project": null}, "errors": [{"message": "Forbidden"}]}"#,
+        )
+        .unwrap();
+        assert!(matches!(body.into_data(), Err(Error::GraphQL(_))));
+
+        let mut body: Response<serde_json::Value> =
+            serde_json::from_str(r#"{"data": null}"#).unwrap();
+        body.status = reqwest::StatusCode::INTERNAL_SERVER_ERROR;
+        assert!(matches!(
+            body.into_data(),
+            Err(Error::NoData(reqwest::StatusCode::INTERNAL_SERVER_ERROR))
+        ));
+    }
+
+    #[test]
+    fn test_variables_shape() {
+        let variables = operations::alerts::Variables {
+            project_slug: "core".to_string(),
+            status: Some(types::AlertStatusInput::WontFix),
+        };
+        assert_eq!(
+            serde_json::to_value(&variables).unwrap(),
+            serde_json::json!({ "projectSlug": "core", "status": "WONT_FIX" })
+        );
+
+        let input = types::CreateReportInput {
+            project_slug: "core".to_string(),
+            branch: "main".to_string(),
+            testbed: "ci".to_string(),
+            metrics: vec![types::MetricInput {
+                benchmark: "parse".to_string(),
+                measure: "latency".to_string(),
+                value: 1.5,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&input).unwrap(),
+            serde_json::json!({
+                "projectSlug": "core",
+                "branch": "main",
+                "testbed": "ci",
+                "metrics": [{ "benchmark": "parse", "measure": "latency", "value": 1.5 }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_response_shape() {
+        let data: <operations::Report as GraphQLOperation>::ResponseData =
+            serde_json::from_value(serde_json::json!({
+                "report": {
+                    "id": "r1",
+                    "gitHash": "abc123",
+                    "prNumber": null,
+                    "commitMessage": null,
+                    "status": "complete",
+                    "excluded": false,
+                    "createdAt": "2024-01-08T10:00:00Z",
+                    "project": { "slug": "core" },
+                    "branch": { "name": "main" },
+                    "testbed": { "name": "ci" },
+                    "metrics": [{
+                        "value": 1.5,
+                        "lower": null,
+                        "upper": 2.0,
+                        "benchmark": { "name": "parse" },
+                        "measure": { "name": "latency", "units": "ns" }
+                    }],
+                    "alerts": []
+                }
+            }))
+            .unwrap();
+
+        let report = data.report.unwrap();
+        assert_eq!(report.git_hash.as_deref(), Some("abc123"));
+        assert_eq!(report.branch.name, "main");
+        assert_eq!(report.metrics[0].measure.units.as_deref(), Some("ns"));
+        assert_eq!(report.created_at.to_rfc3339(), "2024-01-08T10:00:00+00:00");
+        assert_eq!(
+            <operations::Report as GraphQLOperation>::OPERATION_NAME,
+            "Report"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//! What the Driftwatch server and its clients have to agree on, kept apart
+//! from the server so clients can depend on it without pulling in the
+//! database, web and auth stacks.
+
+/// Version of the GraphQL API. Bump it whenever a field or argument is
+/// removed or changes meaning; additions don't need a bump. Deprecated
+/// fields stay for at least one version after they're deprecated.
+pub const API_VERSION: i32 = 1;
+
+/// Response extension carrying `API_VERSION`, so clients can tell whether
+/// they're behind the server without an extra request
+pub const API_VERSION_EXTENSION: &str = "apiVersion";
+
+/// Header clients send with their own version, e.g. `driftwatch-cli/0.1.0`
+pub const CLIENT_HEADER: &str = "x-driftwatch-client";
+
+/// Bytes stored so far, on every upload response
+pub const UPLOAD_OFFSET_HEADER: &str = "upload-offset";
+
+/// Gzip as the server uses it for large responses and stored output
+pub mod compression {
+    use std::io::{Read, Write};
+
+    use flate2::read::GzDecoder;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    pub fn gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut encoder =
+            GzEncoder::new(Vec::with_capacity(data.len() / 4), Compression::default());
+        encoder.write_all(data)?;
+        encoder.finish()
+    }
+
+    pub fn gunzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut decoded = Vec::with_capacity(data.len() * 4);
+        GzDecoder::new(data).read_to_end(&mut decoded)?;
+        Ok(decoded)
+    }
+}
@@ -0,0 +1,2578 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use driftwatch_client::{retry_after, BUSY_RETRIES};
+use driftwatch_protocol::{API_VERSION, UPLOAD_OFFSET_HEADER};
+
+use crate::error::{Error, Result};
+use crate::transport::{compatibility_warning, HttpTransport, Notify, Transport};
+use crate::types::*;
+
+/// Reports with more metrics than this are uploaded in batches
+pub const CHUNKED_UPLOAD_THRESHOLD: usize = 5_000;
+pub const METRIC_BATCH_SIZE: usize = 5_000;
+
+/// How often and for how long `wait_for_evaluations` polls pending reports
+pub const EVALUATION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub const EVALUATION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Flamegraphs are uploaded in chunks of this size, and a failed chunk is
+/// retried from the server's offset with exponential backoff
+const UPLOAD_CHUNK_BYTES: usize = 1024 * 1024;
+const UPLOAD_RETRIES: u32 = 5;
+const UPLOAD_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Requests in flight at once when uploading or fetching several things
+pub const MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Fields fetched for a report once it may have been evaluated
+const REPORT_FIELDS: &str = r#"
+    fragment ReportFields on Report {
+        id
+        gitHash
+        status
+        comparison {
+            totalBenchmarks
+            improved
+            regressed
+            unchanged
+            withoutBaseline
+            maxRegression
+        }
+        alerts {
+            id
+            baselineValue
+            percentChange
+            owners
+        }
+        unconfirmedAlerts {
+            id
+            baselineValue
+            percentChange
+        }
+        missingBenchmarks
+    }
+"#;
+
+/// One query fetching `count` reports, aliased `r0`, `r1`, ... and taking
+/// their ids as `$id0`, `$id1`, ...
+fn reports_query(count: usize) -> String {
+    let params: Vec<String> = (0..count).map(|i| format!("$id{}: ID!", i)).collect();
+    let fields: String = (0..count)
+        .map(|i| format!("r{i}: report(id: $id{i}) {{ ...ReportFields }}\n"))
+        .collect();
+    format!(
+        "query GetReports({}) {{\n{}}}\n{}",
+        params.join(", "),
+        fields,
+        REPORT_FIELDS
+    )
+}
+
+/// `Content-Range` for bytes `start..end` of a `total`-byte file
+fn content_range(start: usize, end: usize, total: usize) -> String {
+    format!("bytes {}-{}/{}", start, end - 1, total)
+}
+
+/// Bytes the server has stored, from an upload response
+fn upload_offset(headers: &reqwest::header::HeaderMap) -> Option<usize> {
+    headers
+        .get(UPLOAD_OFFSET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+/// An ingestion relay that takes report submissions and artifacts in place
+/// of the API, see `driftwatch_api::relay`
+struct Relay {
+    url: String,
+    token: String,
+}
+
+/// Typed operations against the API, over any [`Transport`]
+pub struct Client {
+    transport: Arc<dyn Transport>,
+    /// Uploads go straight to storage, whatever the transport
+    http: reqwest::Client,
+    notify: Option<Notify>,
+    strict: bool,
+    /// Server API version, once checked in strict mode
+    handshake: tokio::sync::OnceCell<i32>,
+    relay: Option<Relay>,
+}
+
+impl Client {
+    /// A client talking GraphQL over HTTP to the API at `base_url`
+    pub fn new(base_url: &str, token: &str) -> Self {
+        Self::with_transport(Arc::new(HttpTransport::new(base_url, token)))
+    }
+
+    pub fn with_transport(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            http: reqwest::Client::new(),
+            notify: None,
+            strict: false,
+            handshake: tokio::sync::OnceCell::new(),
+            relay: None,
+        }
+    }
+
+    /// Submits reports through the relay at `url`, which answers at once
+    /// and forwards them to the API later. Everything else still goes to
+    /// the API.
+    pub fn with_relay(mut self, url: &str, token: &str) -> Self {
+        self.relay = Some(Relay {
+            url: url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        });
+        self
+    }
+
+    /// Whether submissions go through a relay
+    pub fn relayed(&self) -> bool {
+        self.relay.is_some()
+    }
+
+    /// Receives progress messages, e.g. when an upload resumes
+    pub fn with_notify(mut self, notify: Notify) -> Self {
+        self.notify = Some(notify);
+        self
+    }
+
+    /// Checks the server's API version before the first request and fails
+    /// with [`Error::Incompatible`] instead of talking to a mismatched one
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn notify(&self, message: &str) {
+        if let Some(notify) = &self.notify {
+            notify(message);
+        }
+    }
+
+    async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T> {
+        if self.strict {
+            self.handshake
+                .get_or_try_init(|| self.check_compatible())
+                .await?;
+        }
+        self.request(query, variables).await
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T> {
+        let data = self.transport.execute(query, variables).await?;
+        serde_json::from_value(data).map_err(|e| Error::Decode(e.into()))
+    }
+
+    /// Fails unless the server speaks the API version this client was built for
+    async fn check_compatible(&self) -> Result<i32> {
+        let server = self.api_version().await.map_err(|e| match e {
+            Error::GraphQL(_) => Error::Incompatible {
+                server_version: "(unknown)".to_string(),
+                warning: "the server doesn't report an API version; it predates this client"
+                    .to_string(),
+            },
+            e => e,
+        })?;
+        if let Some(warning) = compatibility_warning(API_VERSION, server.api_version) {
+            return Err(Error::Incompatible {
+                server_version: server.server_version,
+                warning,
+            });
+        }
+        Ok(server.api_version)
+    }
+
+    /// Versions of the server's API and the server itself
+    pub async fn api_version(&self) -> Result<ServerVersion> {
+        let query = r#"
+            query {
+                apiVersion {
+                    apiVersion
+                    serverVersion
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "apiVersion")]
+            api_version: ServerVersion,
+        }
+
+        let response: Response = self.request(query, serde_json::json!({})).await?;
+        Ok(response.api_version)
+    }
+
+    pub async fn list_projects(&self) -> Result<Vec<Project>> {
+        let query = r#"
+            query {
+                projects {
+                    id
+                    slug
+                    name
+                    description
+                    public
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            projects: Vec<Project>,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({})).await?;
+        Ok(response.projects)
+    }
+
+    pub async fn get_project(&self, slug: &str) -> Result<Option<ProjectDetails>> {
+        let query = r#"
+            query GetProject($slug: String!) {
+                project(slug: $slug) {
+                    id
+                    slug
+                    name
+                    description
+                    public
+                    defaultBranch
+                    branches { id name }
+                    branchAliases { alias branch }
+                    testbeds { id name }
+                    testbedAliases { alias testbed }
+                    benchmarks { id name }
+                    measures { id name units }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectDetails>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": slug }))
+            .await?;
+        Ok(response.project)
+    }
+
+    pub async fn create_project(
+        &self,
+        slug: &str,
+        name: &str,
+        description: Option<&str>,
+        public: bool,
+        template: Option<&str>,
+    ) -> Result<Project> {
+        let query = r#"
+            mutation CreateProject($input: CreateProjectInput!) {
+                createProject(input: $input) {
+                    id
+                    slug
+                    name
+                    description
+                    public
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createProject")]
+            create_project: Project,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "input": {
+                        "slug": slug,
+                        "name": name,
+                        "description": description,
+                        "public": public,
+                        "template": template
+                    }
+                }),
+            )
+            .await?;
+        Ok(response.create_project)
+    }
+
+    pub async fn create_report(&self, input: &CreateReportInput) -> Result<Report> {
+        let query = r#"
+            mutation CreateReport($input: CreateReportInput!) {
+                createReport(input: $input) {
+                    id
+                    gitHash
+                    status
+                    alerts {
+                        id
+                        baselineValue
+                        percentChange
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createReport")]
+            create_report: Report,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.create_report)
+    }
+
+    /// Share a one-off report without an account, on servers that allow it.
+    /// Works with an empty token.
+    pub async fn share_report(&self, input: &ShareReportInput) -> Result<SharedReport> {
+        let query = r#"
+            mutation ShareReport($input: ShareReportInput!) {
+                shareReport(input: $input) {
+                    path
+                    expiresAt
+                    reportId
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "shareReport")]
+            share_report: SharedReport,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.share_report)
+    }
+
+    /// Submit a report, switching to a chunked upload for very large suites.
+    /// Through a relay, the report comes back `queued` and reaches the API
+    /// shortly after.
+    pub async fn submit_report(&self, mut input: CreateReportInput) -> Result<Report> {
+        if let Some(relay) = &self.relay {
+            let response = self
+                .send_to_relay(
+                    relay,
+                    self.http
+                        .post(format!("{}/relay/v1/reports", relay.url))
+                        .json(&input),
+                )
+                .await?;
+            return response.json().await.map_err(|e| Error::Decode(e.into()));
+        }
+        if input.metrics.len() <= CHUNKED_UPLOAD_THRESHOLD {
+            return self.create_report(&input).await;
+        }
+
+        let metrics = std::mem::take(&mut input.metrics);
+        let report = self.open_report(&input).await?;
+        for batch in metrics.chunks(METRIC_BATCH_SIZE) {
+            self.append_report_metrics(&report.id, batch).await?;
+        }
+        self.finalize_report(&report.id).await
+    }
+
+    async fn open_report(&self, input: &CreateReportInput) -> Result<Report> {
+        let query = r#"
+            mutation OpenReport($input: OpenReportInput!) {
+                openReport(input: $input) {
+                    id
+                    gitHash
+                    status
+                    alerts {
+                        id
+                        baselineValue
+                        percentChange
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "openReport")]
+            open_report: Report,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.open_report)
+    }
+
+    async fn append_report_metrics(&self, report_id: &str, metrics: &[MetricInput]) -> Result<()> {
+        let query = r#"
+            mutation AppendReportMetrics($reportId: ID!, $metrics: [MetricInput!]!) {
+                appendReportMetrics(reportId: $reportId, metrics: $metrics)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "appendReportMetrics")]
+            _append_report_metrics: i32,
+        }
+
+        let _: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "reportId": report_id, "metrics": metrics }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn finalize_report(&self, report_id: &str) -> Result<Report> {
+        let query = r#"
+            mutation FinalizeReport($reportId: ID!) {
+                finalizeReport(reportId: $reportId) {
+                    id
+                    gitHash
+                    status
+                    alerts {
+                        id
+                        baselineValue
+                        percentChange
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "finalizeReport")]
+            finalize_report: Report,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "reportId": report_id }))
+            .await?;
+        Ok(response.finalize_report)
+    }
+
+    /// Sends `request` to the relay, waiting and sending it again while the
+    /// relay answers 429
+    async fn send_to_relay(
+        &self,
+        relay: &Relay,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let mut retries = 0;
+        let response = loop {
+            let attempt = request
+                .try_clone()
+                .ok_or_else(|| Error::Relay("request body can't be resent".to_string()))?;
+            let response = attempt
+                .bearer_auth(&relay.token)
+                .send()
+                .await
+                .map_err(|e| Error::Request(e.into()))?;
+            if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS
+                || retries == BUSY_RETRIES
+            {
+                break response;
+            }
+            retries += 1;
+            let wait = retry_after(response.headers().get(reqwest::header::RETRY_AFTER));
+            self.notify(&format!(
+                "Relay is busy; retrying in {}s ({}/{})",
+                wait.as_secs(),
+                retries,
+                BUSY_RETRIES
+            ));
+            tokio::time::sleep(wait).await;
+        };
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Relay(format!("{} - {}", status, body)));
+        }
+        Ok(response)
+    }
+
+    /// Hands compressed command output for a report to the relay
+    pub async fn relay_report_output(&self, report_id: &str, content: &str) -> Result<()> {
+        let Some(relay) = &self.relay else {
+            return Err(Error::Relay("no relay configured".to_string()));
+        };
+        let url = format!("{}/relay/v1/reports/{}/output", relay.url, report_id);
+        self.send_to_relay(relay, self.http.put(url).body(content.to_string()))
+            .await?;
+        Ok(())
+    }
+
+    /// Hands a flamegraph for a report to the relay
+    pub async fn relay_flamegraph(
+        &self,
+        project_slug: &str,
+        report_id: &str,
+        file_path: &Path,
+        file_name: &str,
+    ) -> Result<()> {
+        let Some(relay) = &self.relay else {
+            return Err(Error::Relay("no relay configured".to_string()));
+        };
+        let file_content = tokio::fs::read(file_path)
+            .await
+            .map_err(|source| Error::Io {
+                path: file_path.display().to_string(),
+                source,
+            })?;
+        let url = format!(
+            "{}/relay/v1/reports/{}/flamegraphs/{}?project={}",
+            relay.url,
+            report_id,
+            urlencoding::encode(file_name),
+            urlencoding::encode(project_slug)
+        );
+        self.send_to_relay(relay, self.http.put(url).body(file_content))
+            .await?;
+        Ok(())
+    }
+
+    /// Fetches several reports in one round trip, in the order of `ids`
+    pub async fn get_reports(&self, ids: &[&str]) -> Result<Vec<Option<Report>>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let variables: serde_json::Map<String, serde_json::Value> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (format!("id{}", i), serde_json::json!(id)))
+            .collect();
+
+        let mut response: HashMap<String, Option<Report>> = self
+            .graphql(
+                &reports_query(ids.len()),
+                serde_json::Value::Object(variables),
+            )
+            .await?;
+        Ok((0..ids.len())
+            .map(|i| response.remove(&format!("r{}", i)).flatten())
+            .collect())
+    }
+
+    /// Poll several reports until all are evaluated, fetching the ones still
+    /// pending together on each poll. Returns them in the order of `ids`.
+    pub async fn wait_for_evaluations(&self, ids: &[String]) -> Result<Vec<Report>> {
+        let started = Instant::now();
+        let mut evaluated: Vec<Option<Report>> = ids.iter().map(|_| None).collect();
+        loop {
+            let pending: Vec<usize> = (0..ids.len()).filter(|&i| evaluated[i].is_none()).collect();
+            let pending_ids: Vec<&str> = pending.iter().map(|&i| ids[i].as_str()).collect();
+            let reports = self.get_reports(&pending_ids).await?;
+            for (&i, report) in pending.iter().zip(reports) {
+                let report = match report {
+                    Some(report) => report,
+                    // Still on its way from the relay
+                    None if self.relayed() => continue,
+                    None => return Err(Error::NotFound(format!("Report {}", ids[i]))),
+                };
+                if report.status == "evaluated" {
+                    evaluated[i] = Some(report);
+                }
+            }
+            if evaluated.iter().all(Option::is_some) {
+                return Ok(evaluated.into_iter().flatten().collect());
+            }
+            if started.elapsed() >= EVALUATION_TIMEOUT {
+                let waiting = (0..ids.len())
+                    .filter(|&i| evaluated[i].is_none())
+                    .map(|i| ids[i].clone())
+                    .collect();
+                return Err(Error::EvaluationTimeout(waiting));
+            }
+            tokio::time::sleep(EVALUATION_POLL_INTERVAL).await;
+        }
+    }
+
+    pub async fn list_alerts(
+        &self,
+        project_slug: &str,
+        status: Option<AlertStatus>,
+    ) -> Result<Option<Vec<AlertDetails>>> {
+        let query = r#"
+            query ListAlerts($slug: String!, $status: AlertStatusInput) {
+                project(slug: $slug) {
+                    alerts(status: $status) {
+                        id
+                        status
+                        percentChange
+                        baselineValue
+                        currentValue
+                        assignee
+                        resolutionNote
+                        createdAt
+                        metric { benchmark { name } }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectAlerts {
+            alerts: Vec<AlertDetails>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectAlerts>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "slug": project_slug, "status": status }),
+            )
+            .await?;
+        Ok(response.project.map(|p| p.alerts))
+    }
+
+    pub async fn alert_explanation(&self, id: &str) -> Result<Option<AlertExplanation>> {
+        let query = r#"
+            query AlertExplanation($id: ID!) {
+                alert(id: $id) {
+                    id
+                    status
+                    percentChange
+                    metric { benchmark { name } }
+                    evaluations {
+                        reportId
+                        verdict
+                        explanation
+                        createdAt
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            alert: Option<AlertExplanation>,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({ "id": id })).await?;
+        Ok(response.alert)
+    }
+
+    pub async fn update_alert(&self, id: &str, input: &UpdateAlertInput) -> Result<AlertDetails> {
+        let query = r#"
+            mutation UpdateAlert($id: ID!, $input: UpdateAlertInput!) {
+                updateAlert(id: $id, input: $input) {
+                    id
+                    status
+                    percentChange
+                    baselineValue
+                    currentValue
+                    assignee
+                    resolutionNote
+                    createdAt
+                    metric { benchmark { name } }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "updateAlert")]
+            update_alert: AlertDetails,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "id": id, "input": input }))
+            .await?;
+        Ok(response.update_alert)
+    }
+
+    /// Excludes a report from baselines, or restores it.
+    pub async fn set_report_excluded(&self, id: &str, excluded: bool) -> Result<()> {
+        let query = r#"
+            mutation SetReportExcluded($id: ID!, $excluded: Boolean!) {
+                setReportExcluded(id: $id, excluded: $excluded) {
+                    id
+                }
+            }
+        "#;
+
+        let _: serde_json::Value = self
+            .graphql(query, serde_json::json!({ "id": id, "excluded": excluded }))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn reevaluate_report(&self, id: &str) -> Result<ReportReevaluation> {
+        let query = r#"
+            mutation ReevaluateReport($id: ID!) {
+                reevaluateReport(id: $id) {
+                    raised
+                    updated
+                    resolved
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "reevaluateReport")]
+            reevaluate_report: ReportReevaluation,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({ "id": id })).await?;
+        Ok(response.reevaluate_report)
+    }
+
+    pub async fn search_benchmarks(
+        &self,
+        pattern: &str,
+        limit: Option<i32>,
+    ) -> Result<Vec<BenchmarkMatch>> {
+        let query = r#"
+            query SearchBenchmarks($pattern: String!, $limit: Int) {
+                searchBenchmarks(pattern: $pattern, limit: $limit) {
+                    projectSlug
+                    benchmark
+                    measure
+                    branch
+                    testbed
+                    latestValue
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "searchBenchmarks")]
+            search_benchmarks: Vec<BenchmarkMatch>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "pattern": pattern, "limit": limit }),
+            )
+            .await?;
+        Ok(response.search_benchmarks)
+    }
+
+    /// Browse the server's public projects; works with an empty token.
+    /// `sort` is `RECENT` (the default), `BENCHMARKS` or `NAME`.
+    pub async fn public_projects(
+        &self,
+        search: Option<&str>,
+        sort: Option<&str>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<PublicProjectPage> {
+        let query = r#"
+            query PublicProjects($search: String, $sort: PublicProjectSort, $limit: Int, $offset: Int) {
+                publicProjects(search: $search, sort: $sort, limit: $limit, offset: $offset) {
+                    totalCount
+                    projects {
+                        id
+                        slug
+                        name
+                        description
+                        githubRepo
+                        benchmarks
+                        series
+                        reports
+                        lastReportAt
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "publicProjects")]
+            public_projects: PublicProjectPage,
+        }
+
+        let mut variables = serde_json::json!({
+            "search": search,
+            "limit": limit,
+            "offset": offset,
+        });
+        if let Some(sort) = sort {
+            variables["sort"] = serde_json::json!(sort);
+        }
+        let response: Response = self.graphql(query, variables).await?;
+        Ok(response.public_projects)
+    }
+
+    /// A public project with its most recently updated results
+    pub async fn public_project(
+        &self,
+        id: &str,
+        results: Option<i32>,
+    ) -> Result<Option<PublicProject>> {
+        let query = r#"
+            query PublicProject($id: ID!, $results: Int) {
+                publicProject(id: $id) {
+                    id
+                    slug
+                    name
+                    description
+                    githubRepo
+                    benchmarks
+                    series
+                    reports
+                    lastReportAt
+                    latestResults(limit: $results) {
+                        projectSlug
+                        benchmark
+                        measure
+                        branch
+                        testbed
+                        latestValue
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "publicProject")]
+            public_project: Option<PublicProject>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "id": id, "results": results }))
+            .await?;
+        Ok(response.public_project)
+    }
+
+    /// Returns how many secrets the server redacted from the output
+    pub async fn attach_report_output(&self, report_id: &str, content: &str) -> Result<i32> {
+        let query = r#"
+            mutation AttachReportOutput($reportId: ID!, $content: String!) {
+                attachReportOutput(reportId: $reportId, content: $content) {
+                    redactions
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Attached {
+            redactions: i32,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "attachReportOutput")]
+            attach_report_output: Attached,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "reportId": report_id, "content": content }),
+            )
+            .await?;
+        Ok(response.attach_report_output.redactions)
+    }
+
+    /// The report's attached command output, still compressed, or `None`
+    /// when the report doesn't exist or has no output
+    pub async fn report_output(&self, report_id: &str) -> Result<Option<ReportOutput>> {
+        let query = r#"
+            query ReportOutput($id: ID!) {
+                report(id: $id) {
+                    output {
+                        content
+                        createdAt
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ReportWithOutput {
+            output: Option<ReportOutput>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            report: Option<ReportWithOutput>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "id": report_id }))
+            .await?;
+        Ok(response.report.and_then(|r| r.output))
+    }
+
+    pub async fn scaling_curves(
+        &self,
+        project_slug: &str,
+        benchmark: &str,
+        measure: &str,
+        branch: &str,
+        testbed: Option<&str>,
+        limit: i32,
+    ) -> Result<Option<Vec<ScalingCurve>>> {
+        let query = r#"
+            query ScalingCurves(
+                $slug: String!
+                $benchmark: String!
+                $measure: String!
+                $branch: String!
+                $testbed: String
+                $limit: Int!
+            ) {
+                project(slug: $slug) {
+                    scalingCurves(
+                        benchmark: $benchmark
+                        measure: $measure
+                        branch: $branch
+                        testbed: $testbed
+                        limit: $limit
+                    ) {
+                        gitHash
+                        createdAt
+                        growthExponent
+                        points { parameter value }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectScaling {
+            #[serde(rename = "scalingCurves")]
+            scaling_curves: Vec<ScalingCurve>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectScaling>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "benchmark": benchmark,
+                    "measure": measure,
+                    "branch": branch,
+                    "testbed": testbed,
+                    "limit": limit,
+                }),
+            )
+            .await?;
+        Ok(response.project.map(|p| p.scaling_curves))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn context_series(
+        &self,
+        project_slug: &str,
+        benchmark: &str,
+        measure: &str,
+        key: &str,
+        branch: Option<&str>,
+        testbed: Option<&str>,
+        limit: i32,
+    ) -> Result<Option<Vec<ContextPoint>>> {
+        let query = r#"
+            query ContextSeries(
+                $slug: String!
+                $benchmark: String!
+                $measure: String!
+                $key: String!
+                $branch: String
+                $testbed: String
+                $limit: Int!
+            ) {
+                project(slug: $slug) {
+                    contextSeries(
+                        benchmark: $benchmark
+                        measure: $measure
+                        key: $key
+                        branch: $branch
+                        testbed: $testbed
+                        limit: $limit
+                    ) {
+                        contextValue
+                        value
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectContextSeries {
+            #[serde(rename = "contextSeries")]
+            context_series: Vec<ContextPoint>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectContextSeries>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "benchmark": benchmark,
+                    "measure": measure,
+                    "key": key,
+                    "branch": branch,
+                    "testbed": testbed,
+                    "limit": limit,
+                }),
+            )
+            .await?;
+        Ok(response.project.map(|p| p.context_series))
+    }
+
+    pub async fn record_experiment(&self, input: RecordExperimentInput) -> Result<Experiment> {
+        let query = r#"
+            mutation RecordExperiment($input: RecordExperimentInput!) {
+                recordExperiment(input: $input) {
+                    id
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "recordExperiment")]
+            record_experiment: Experiment,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.record_experiment)
+    }
+
+    pub async fn simulate_thresholds(
+        &self,
+        input: &SimulateThresholdsInput,
+    ) -> Result<ThresholdSimulation> {
+        let query = r#"
+            query SimulateThresholds($input: SimulateThresholdsInput!) {
+                simulateThresholds(input: $input) {
+                    series
+                    comparisons
+                    actualAlerts
+                    alerts {
+                        benchmark
+                        measure
+                        branch
+                        testbed
+                        createdAt
+                        score
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "simulateThresholds")]
+            simulate_thresholds: ThresholdSimulation,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.simulate_thresholds)
+    }
+
+    pub async fn project_templates(&self) -> Result<Vec<ProjectTemplate>> {
+        let query = r#"
+            query ProjectTemplates {
+                projectTemplates {
+                    name
+                    description
+                    measures { name }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "projectTemplates")]
+            project_templates: Vec<ProjectTemplate>,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({})).await?;
+        Ok(response.project_templates)
+    }
+
+    /// Check a report or hypothetical metrics against the project's thresholds
+    /// without creating alerts
+    pub async fn threshold_test(
+        &self,
+        input: &ThresholdTestInput,
+    ) -> Result<Vec<ThresholdTestResult>> {
+        let query = r#"
+            query ThresholdTest($input: ThresholdTestInput!) {
+                thresholdTest(input: $input) {
+                    threshold { upperBoundary lowerBoundary }
+                    benchmark
+                    measure
+                    value
+                    baselineValue
+                    percentChange
+                    wouldAlert
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "thresholdTest")]
+            threshold_test: Vec<ThresholdTestResult>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.threshold_test)
+    }
+
+    /// Noise scores for the project's benchmarks, noisiest first
+    pub async fn noise_scores(
+        &self,
+        project_slug: &str,
+        testbed: Option<&str>,
+        noisy_only: bool,
+    ) -> Result<Option<Vec<NoiseScore>>> {
+        let query = r#"
+            query NoiseScores($slug: String!, $testbed: String, $noisyOnly: Boolean!) {
+                project(slug: $slug) {
+                    noiseScores(testbed: $testbed, noisyOnly: $noisyOnly) {
+                        sampleSize
+                        coefficientOfVariation
+                        noisy
+                        benchmark { name }
+                        testbed { name }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectNoise {
+            #[serde(rename = "noiseScores")]
+            noise_scores: Vec<NoiseScore>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectNoise>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "testbed": testbed,
+                    "noisyOnly": noisy_only,
+                }),
+            )
+            .await?;
+        Ok(response.project.map(|p| p.noise_scores))
+    }
+
+    pub async fn project_overview(
+        &self,
+        project_slug: &str,
+        branch: Option<&str>,
+        testbed: Option<&str>,
+    ) -> Result<Option<ProjectOverview>> {
+        let query = r#"
+            query ProjectOverview($slug: String!, $branch: String, $testbed: String) {
+                project(slug: $slug) {
+                    overview(branch: $branch, testbed: $testbed) {
+                        openAlerts
+                        measures {
+                            measure
+                            benchmarks
+                            meanDelta7d
+                            meanDelta30d
+                            regressed30d
+                            improved30d
+                        }
+                        latestReports {
+                            branch
+                            testbed
+                            reportId
+                            gitHash
+                            reportedAt
+                        }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectWithOverview {
+            overview: ProjectOverview,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectWithOverview>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "branch": branch,
+                    "testbed": testbed,
+                }),
+            )
+            .await?;
+        Ok(response.project.map(|p| p.overview))
+    }
+
+    pub async fn create_annotation(&self, input: &CreateAnnotationInput) -> Result<Annotation> {
+        let query = r#"
+            mutation CreateAnnotation($input: CreateAnnotationInput!) {
+                createAnnotation(input: $input) {
+                    id
+                    kind
+                    note
+                    reportId
+                    occurredAt
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createAnnotation")]
+            create_annotation: Annotation,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.create_annotation)
+    }
+
+    pub async fn annotations(&self, project_slug: &str) -> Result<Option<Vec<Annotation>>> {
+        let query = r#"
+            query Annotations($slug: String!) {
+                project(slug: $slug) {
+                    annotations {
+                        id
+                        kind
+                        note
+                        reportId
+                        occurredAt
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectAnnotations {
+            annotations: Vec<Annotation>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectAnnotations>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": project_slug }))
+            .await?;
+        Ok(response.project.map(|p| p.annotations))
+    }
+
+    /// The project's stored weekly digests, newest first, or the week so
+    /// far when `preview` is set
+    pub async fn digests(
+        &self,
+        project_slug: &str,
+        preview: bool,
+        limit: u64,
+    ) -> Result<Option<Vec<ProjectDigest>>> {
+        let query = r#"
+            query Digests($slug: String!, $preview: Boolean!, $limit: Int!) {
+                project(slug: $slug) {
+                    digests(limit: $limit) @skip(if: $preview) {
+                        markdown
+                        html
+                    }
+                    digestPreview @include(if: $preview) {
+                        markdown
+                        html
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectDigests {
+            digests: Option<Vec<ProjectDigest>>,
+            #[serde(rename = "digestPreview")]
+            digest_preview: Option<ProjectDigest>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectDigests>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "slug": project_slug, "preview": preview, "limit": limit }),
+            )
+            .await?;
+        Ok(response.project.map(|p| match p.digest_preview {
+            Some(preview) => vec![preview],
+            None => p.digests.unwrap_or_default(),
+        }))
+    }
+
+    pub async fn notification_channels(
+        &self,
+        project_slug: &str,
+    ) -> Result<Option<Vec<NotificationChannel>>> {
+        let query = r#"
+            query NotificationChannels($slug: String!) {
+                project(slug: $slug) {
+                    notificationChannels {
+                        id
+                        kind
+                        target
+                        events
+                        template
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectChannels {
+            #[serde(rename = "notificationChannels")]
+            notification_channels: Vec<NotificationChannel>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectChannels>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": project_slug }))
+            .await?;
+        Ok(response.project.map(|p| p.notification_channels))
+    }
+
+    /// Adds a channel for the `events` named, or only digests when `None`
+    pub async fn add_notification_channel(
+        &self,
+        project_slug: &str,
+        kind: ChannelKind,
+        url: &str,
+        events: Option<&[String]>,
+        template: Option<&str>,
+    ) -> Result<NotificationChannel> {
+        let query = r#"
+            mutation AddNotificationChannel(
+                $slug: String!
+                $kind: NotificationChannelKindInput!
+                $url: String!
+                $events: [String!]
+                $template: String
+            ) {
+                addNotificationChannel(
+                    projectSlug: $slug
+                    kind: $kind
+                    url: $url
+                    events: $events
+                    template: $template
+                ) {
+                    id
+                    kind
+                    target
+                    events
+                    template
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "addNotificationChannel")]
+            add_notification_channel: NotificationChannel,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "kind": kind,
+                    "url": url,
+                    "events": events,
+                    "template": template,
+                }),
+            )
+            .await?;
+        Ok(response.add_notification_channel)
+    }
+
+    /// Changes a channel's events and template; `None` keeps them and an
+    /// empty template restores the default payload
+    pub async fn update_notification_channel(
+        &self,
+        id: &str,
+        events: Option<&[String]>,
+        template: Option<&str>,
+    ) -> Result<NotificationChannel> {
+        let query = r#"
+            mutation UpdateNotificationChannel($id: ID!, $events: [String!], $template: String) {
+                updateNotificationChannel(id: $id, events: $events, template: $template) {
+                    id
+                    kind
+                    target
+                    events
+                    template
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "updateNotificationChannel")]
+            update_notification_channel: NotificationChannel,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "id": id, "events": events, "template": template }),
+            )
+            .await?;
+        Ok(response.update_notification_channel)
+    }
+
+    /// Posts a sample `event` to the channel, by default the first it
+    /// receives
+    pub async fn test_notification_channel(
+        &self,
+        id: &str,
+        event: Option<&str>,
+    ) -> Result<NotificationChannelTest> {
+        let query = r#"
+            mutation TestNotificationChannel($id: ID!, $event: String) {
+                testNotificationChannel(id: $id, event: $event) {
+                    delivered
+                    error
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "testNotificationChannel")]
+            test_notification_channel: NotificationChannelTest,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "id": id, "event": event }))
+            .await?;
+        Ok(response.test_notification_channel)
+    }
+
+    /// Creates or replaces the project's alert feed and returns its path
+    pub async fn create_alert_feed_url(&self, project_slug: &str) -> Result<String> {
+        let query = r#"
+            mutation CreateAlertFeedUrl($slug: String!) {
+                createAlertFeedUrl(projectSlug: $slug)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createAlertFeedUrl")]
+            create_alert_feed_url: String,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": project_slug }))
+            .await?;
+        Ok(response.create_alert_feed_url)
+    }
+
+    pub async fn delete_alert_feed(&self, project_slug: &str) -> Result<bool> {
+        let query = r#"
+            mutation DeleteAlertFeed($slug: String!) {
+                deleteAlertFeed(projectSlug: $slug)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "deleteAlertFeed")]
+            delete_alert_feed: bool,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": project_slug }))
+            .await?;
+        Ok(response.delete_alert_feed)
+    }
+
+    pub async fn remove_notification_channel(&self, id: &str) -> Result<bool> {
+        let query = r#"
+            mutation RemoveNotificationChannel($id: ID!) {
+                removeNotificationChannel(id: $id)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "removeNotificationChannel")]
+            remove_notification_channel: bool,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({ "id": id })).await?;
+        Ok(response.remove_notification_channel)
+    }
+
+    pub async fn remote_write_rules(
+        &self,
+        project_slug: &str,
+    ) -> Result<Option<Vec<RemoteWriteRule>>> {
+        let query = r#"
+            query RemoteWriteRules($slug: String!) {
+                project(slug: $slug) {
+                    remoteWriteRules {
+                        id
+                        metric
+                        matchers { name value }
+                        benchmark
+                        measure
+                        branch
+                        testbed
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectRules {
+            #[serde(rename = "remoteWriteRules")]
+            remote_write_rules: Vec<RemoteWriteRule>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectRules>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": project_slug }))
+            .await?;
+        Ok(response.project.map(|p| p.remote_write_rules))
+    }
+
+    pub async fn add_remote_write_rule(
+        &self,
+        project_slug: &str,
+        input: &RemoteWriteRuleInput,
+    ) -> Result<RemoteWriteRule> {
+        let query = r#"
+            mutation AddRemoteWriteRule($slug: String!, $input: RemoteWriteRuleInput!) {
+                addRemoteWriteRule(projectSlug: $slug, input: $input) {
+                    id
+                    metric
+                    matchers { name value }
+                    benchmark
+                    measure
+                    branch
+                    testbed
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "addRemoteWriteRule")]
+            add_remote_write_rule: RemoteWriteRule,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "slug": project_slug, "input": input }),
+            )
+            .await?;
+        Ok(response.add_remote_write_rule)
+    }
+
+    pub async fn remove_remote_write_rule(&self, id: &str) -> Result<bool> {
+        let query = r#"
+            mutation RemoveRemoteWriteRule($id: ID!) {
+                removeRemoteWriteRule(id: $id)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "removeRemoteWriteRule")]
+            remove_remote_write_rule: bool,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({ "id": id })).await?;
+        Ok(response.remove_remote_write_rule)
+    }
+
+    pub async fn release_series(
+        &self,
+        project_slug: &str,
+        measure: &str,
+        benchmark: Option<&str>,
+        testbed: Option<&str>,
+    ) -> Result<Option<Vec<ReleasePoint>>> {
+        let query = r#"
+            query ReleaseSeries(
+                $slug: String!
+                $measure: String!
+                $benchmark: String
+                $testbed: String
+            ) {
+                project(slug: $slug) {
+                    releaseSeries(measure: $measure, benchmark: $benchmark, testbed: $testbed) {
+                        version
+                        benchmark
+                        value
+                        minValue
+                        maxValue
+                        sampleSize
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectReleases {
+            #[serde(rename = "releaseSeries")]
+            release_series: Vec<ReleasePoint>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectReleases>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "measure": measure,
+                    "benchmark": benchmark,
+                    "testbed": testbed,
+                }),
+            )
+            .await?;
+        Ok(response.project.map(|p| p.release_series))
+    }
+
+    pub async fn list_project_groups(&self) -> Result<Vec<ProjectGroup>> {
+        let query = r#"
+            query ProjectGroups {
+                projectGroups {
+                    slug
+                    name
+                    projects { slug }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "projectGroups")]
+            project_groups: Vec<ProjectGroup>,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({})).await?;
+        Ok(response.project_groups)
+    }
+
+    pub async fn create_project_group(
+        &self,
+        slug: &str,
+        name: &str,
+        description: Option<&str>,
+        project_slugs: &[String],
+    ) -> Result<ProjectGroup> {
+        let query = r#"
+            mutation CreateProjectGroup($input: CreateProjectGroupInput!) {
+                createProjectGroup(input: $input) {
+                    slug
+                    name
+                    projects { slug }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createProjectGroup")]
+            create_project_group: ProjectGroup,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "input": {
+                        "slug": slug,
+                        "name": name,
+                        "description": description,
+                        "projectSlugs": project_slugs,
+                    }
+                }),
+            )
+            .await?;
+        Ok(response.create_project_group)
+    }
+
+    pub async fn delete_project_group(&self, slug: &str) -> Result<()> {
+        let query = r#"
+            mutation DeleteProjectGroup($slug: String!) {
+                deleteProjectGroup(slug: $slug)
+            }
+        "#;
+
+        let _: serde_json::Value = self
+            .graphql(query, serde_json::json!({ "slug": slug }))
+            .await?;
+        Ok(())
+    }
+
+    /// Adds a project to a group, or removes it when `member` is false
+    pub async fn set_group_membership(
+        &self,
+        group_slug: &str,
+        project_slug: &str,
+        member: bool,
+    ) -> Result<()> {
+        let query = if member {
+            r#"
+            mutation AddProjectToGroup($groupSlug: String!, $projectSlug: String!) {
+                addProjectToGroup(groupSlug: $groupSlug, projectSlug: $projectSlug) { slug }
+            }
+        "#
+        } else {
+            r#"
+            mutation RemoveProjectFromGroup($groupSlug: String!, $projectSlug: String!) {
+                removeProjectFromGroup(groupSlug: $groupSlug, projectSlug: $projectSlug) { slug }
+            }
+        "#
+        };
+
+        let _: serde_json::Value = self
+            .graphql(
+                query,
+                serde_json::json!({ "groupSlug": group_slug, "projectSlug": project_slug }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn group_alerts(
+        &self,
+        group_slug: &str,
+        status: Option<AlertStatus>,
+        limit: i32,
+    ) -> Result<Option<Vec<GroupAlert>>> {
+        let query = r#"
+            query GroupAlerts($slug: String!, $status: AlertStatusInput, $limit: Int) {
+                projectGroup(slug: $slug) {
+                    alerts(status: $status, limit: $limit) {
+                        id
+                        status
+                        percentChange
+                        project { slug }
+                        metric { benchmark { name } }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct GroupAlerts {
+            alerts: Vec<GroupAlert>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "projectGroup")]
+            project_group: Option<GroupAlerts>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "slug": group_slug, "status": status, "limit": limit }),
+            )
+            .await?;
+        Ok(response.project_group.map(|g| g.alerts))
+    }
+
+    pub async fn group_reports(
+        &self,
+        group_slug: &str,
+        limit: i32,
+        tags: &[String],
+    ) -> Result<Option<Vec<GroupReport>>> {
+        let query = r#"
+            query GroupReports($slug: String!, $limit: Int, $tags: [String!]) {
+                projectGroup(slug: $slug) {
+                    reports(limit: $limit, tags: $tags) {
+                        gitHash
+                        createdAt
+                        project { slug }
+                        branch { name }
+                        alerts { id }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct GroupReports {
+            reports: Vec<GroupReport>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "projectGroup")]
+            project_group: Option<GroupReports>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "slug": group_slug, "limit": limit, "tags": tags }),
+            )
+            .await?;
+        Ok(response.project_group.map(|g| g.reports))
+    }
+
+    /// A project's latest reports of `kind` carrying every tag in `tags` and
+    /// matching the saved filter `filter`; `None` when the project doesn't
+    /// exist
+    pub async fn project_reports(
+        &self,
+        project_slug: &str,
+        kind: Option<ReportKind>,
+        tags: &[String],
+        filter: Option<&str>,
+        limit: i32,
+    ) -> Result<Option<Vec<ProjectReport>>> {
+        let query = r#"
+            query ProjectReports(
+                $slug: String!
+                $kind: ReportKindInput
+                $tags: [String!]
+                $filter: String
+                $limit: Int
+            ) {
+                project(slug: $slug) {
+                    reports(kind: $kind, tags: $tags, filter: $filter, limit: $limit) {
+                        id
+                        gitHash
+                        createdAt
+                        branch { name }
+                        testbed { name }
+                        kind
+                        tags
+                        alerts { id }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct ProjectReports {
+            reports: Vec<ProjectReport>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectReports>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "kind": kind,
+                    "tags": tags,
+                    "filter": filter,
+                    "limit": limit
+                }),
+            )
+            .await?;
+        Ok(response.project.map(|p| p.reports))
+    }
+
+    pub async fn report_filters(&self, project_slug: &str) -> Result<Option<Vec<ReportFilter>>> {
+        let query = r#"
+            query ReportFilters($slug: String!) {
+                project(slug: $slug) {
+                    reportFilters {
+                        name
+                        tags
+                        context { key value }
+                    }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Filters {
+            #[serde(rename = "reportFilters")]
+            report_filters: Vec<ReportFilter>,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<Filters>,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": project_slug }))
+            .await?;
+        Ok(response.project.map(|p| p.report_filters))
+    }
+
+    /// Save a named filter, replacing the one of the same name
+    pub async fn save_report_filter(
+        &self,
+        project_slug: &str,
+        name: &str,
+        tags: &[String],
+        context: &[ContextEntry],
+    ) -> Result<ReportFilter> {
+        let query = r#"
+            mutation SaveReportFilter($projectSlug: String!, $input: ReportFilterInput!) {
+                saveReportFilter(projectSlug: $projectSlug, input: $input) {
+                    name
+                    tags
+                    context { key value }
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "saveReportFilter")]
+            save_report_filter: ReportFilter,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "projectSlug": project_slug,
+                    "input": { "name": name, "tags": tags, "context": context }
+                }),
+            )
+            .await?;
+        Ok(response.save_report_filter)
+    }
+
+    /// Returns whether the filter existed
+    pub async fn delete_report_filter(&self, project_slug: &str, name: &str) -> Result<bool> {
+        let query = r#"
+            mutation DeleteReportFilter($projectSlug: String!, $name: String!) {
+                deleteReportFilter(projectSlug: $projectSlug, name: $name)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "deleteReportFilter")]
+            delete_report_filter: bool,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "projectSlug": project_slug, "name": name }),
+            )
+            .await?;
+        Ok(response.delete_report_filter)
+    }
+
+    /// Replace the project's benchmark owner rules; returns how many were stored
+    pub async fn set_benchmark_owners(
+        &self,
+        project_slug: &str,
+        rules: &[OwnerRule],
+    ) -> Result<usize> {
+        let query = r#"
+            mutation SetBenchmarkOwners($projectSlug: String!, $rules: [BenchmarkOwnerInput!]!) {
+                setBenchmarkOwners(projectSlug: $projectSlug, rules: $rules) {
+                    id
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Stored {
+            #[serde(rename = "id")]
+            _id: String,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "setBenchmarkOwners")]
+            set_benchmark_owners: Vec<Stored>,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "projectSlug": project_slug, "rules": rules }),
+            )
+            .await?;
+        Ok(response.set_benchmark_owners.len())
+    }
+
+    /// Set the branch reports submitted for `HEAD` go to
+    pub async fn set_default_branch(&self, project_slug: &str, branch: &str) -> Result<String> {
+        let query = r#"
+            mutation SetDefaultBranch($slug: String!, $input: UpdateProjectInput!) {
+                updateProject(slug: $slug, input: $input) {
+                    defaultBranch
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Updated {
+            #[serde(rename = "defaultBranch")]
+            default_branch: String,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "updateProject")]
+            update_project: Updated,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "slug": project_slug,
+                    "input": { "defaultBranch": branch }
+                }),
+            )
+            .await?;
+        Ok(response.update_project.default_branch)
+    }
+
+    /// Make reports submitted for `alias` count as reports of `branch`
+    pub async fn set_branch_alias(
+        &self,
+        project_slug: &str,
+        alias: &str,
+        branch: &str,
+    ) -> Result<BranchAlias> {
+        let query = r#"
+            mutation SetBranchAlias($projectSlug: String!, $alias: String!, $branch: String!) {
+                setBranchAlias(projectSlug: $projectSlug, alias: $alias, branch: $branch) {
+                    alias
+                    branch
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "setBranchAlias")]
+            set_branch_alias: BranchAlias,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "projectSlug": project_slug,
+                    "alias": alias,
+                    "branch": branch
+                }),
+            )
+            .await?;
+        Ok(response.set_branch_alias)
+    }
+
+    /// Returns whether the alias existed
+    pub async fn remove_branch_alias(&self, project_slug: &str, alias: &str) -> Result<bool> {
+        let query = r#"
+            mutation RemoveBranchAlias($projectSlug: String!, $alias: String!) {
+                removeBranchAlias(projectSlug: $projectSlug, alias: $alias)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "removeBranchAlias")]
+            remove_branch_alias: bool,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "projectSlug": project_slug, "alias": alias }),
+            )
+            .await?;
+        Ok(response.remove_branch_alias)
+    }
+
+    /// Make reports submitted for `alias` count as reports of `testbed`
+    pub async fn set_testbed_alias(
+        &self,
+        project_slug: &str,
+        alias: &str,
+        testbed: &str,
+    ) -> Result<TestbedAlias> {
+        let query = r#"
+            mutation SetTestbedAlias($projectSlug: String!, $alias: String!, $testbed: String!) {
+                setTestbedAlias(projectSlug: $projectSlug, alias: $alias, testbed: $testbed) {
+                    alias
+                    testbed
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "setTestbedAlias")]
+            set_testbed_alias: TestbedAlias,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "projectSlug": project_slug,
+                    "alias": alias,
+                    "testbed": testbed
+                }),
+            )
+            .await?;
+        Ok(response.set_testbed_alias)
+    }
+
+    /// Returns whether the alias existed
+    pub async fn remove_testbed_alias(&self, project_slug: &str, alias: &str) -> Result<bool> {
+        let query = r#"
+            mutation RemoveTestbedAlias($projectSlug: String!, $alias: String!) {
+                removeTestbedAlias(projectSlug: $projectSlug, alias: $alias)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "removeTestbedAlias")]
+            remove_testbed_alias: bool,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({ "projectSlug": project_slug, "alias": alias }),
+            )
+            .await?;
+        Ok(response.remove_testbed_alias)
+    }
+
+    /// Uploads a flamegraph and links it to a report: sends the file's hash
+    /// for a signed URL, uploads the file to it unless the server already
+    /// has the same file, then confirms the upload. Returns the flamegraph
+    /// and whether the upload was skipped.
+    pub async fn upload_flamegraph(
+        &self,
+        project_slug: &str,
+        report_id: &str,
+        file_path: &Path,
+        file_name: &str,
+        file_size: i64,
+    ) -> Result<(Flamegraph, bool)> {
+        let file_content = tokio::fs::read(file_path)
+            .await
+            .map_err(|source| Error::Io {
+                path: file_path.display().to_string(),
+                source,
+            })?;
+        let sha256 = hex::encode(Sha256::digest(&file_content));
+
+        let upload_url = self
+            .get_flamegraph_upload_url(project_slug, file_name, Some(&sha256))
+            .await?;
+        if let (false, Some(signed_url)) = (upload_url.stored, &upload_url.signed_url) {
+            self.upload_flamegraph_file(signed_url, &file_content)
+                .await?;
+        }
+        let flamegraph = self
+            .confirm_flamegraph_upload(
+                report_id,
+                &upload_url.storage_path,
+                file_name,
+                file_size,
+                None, // No specific benchmark association
+                &sha256,
+            )
+            .await?;
+        Ok((flamegraph, upload_url.stored))
+    }
+
+    pub async fn get_flamegraph_upload_url(
+        &self,
+        project_slug: &str,
+        file_name: &str,
+        sha256: Option<&str>,
+    ) -> Result<FlamegraphUploadUrl> {
+        let query = r#"
+            mutation CreateFlamegraphUploadUrl(
+                $projectSlug: String!,
+                $fileName: String!,
+                $sha256: String
+            ) {
+                createFlamegraphUploadUrl(
+                    projectSlug: $projectSlug,
+                    fileName: $fileName,
+                    sha256: $sha256
+                ) {
+                    stored
+                    signedUrl
+                    token
+                    storagePath
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createFlamegraphUploadUrl")]
+            create_flamegraph_upload_url: FlamegraphUploadUrl,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "projectSlug": project_slug,
+                    "fileName": file_name,
+                    "sha256": sha256
+                }),
+            )
+            .await?;
+        Ok(response.create_flamegraph_upload_url)
+    }
+
+    /// Uploads a file in chunks to a URL from `createFlamegraphUploadUrl`.
+    /// When a chunk fails, asks the server how much it has and carries on
+    /// from there.
+    pub async fn upload_flamegraph_file(
+        &self,
+        signed_url: &str,
+        file_content: &[u8],
+    ) -> Result<()> {
+        // The built-in storage hands out paths under the API's own URL
+        let url = if signed_url.starts_with('/') {
+            format!("{}{}", self.transport.base_url(), signed_url)
+        } else {
+            signed_url.to_string()
+        };
+
+        let total = file_content.len();
+        let mut offset = 0;
+        let mut failures = 0;
+        while offset < total {
+            let end = (offset + UPLOAD_CHUNK_BYTES).min(total);
+            let sent = self
+                .http
+                .put(&url)
+                .header("Content-Type", "image/svg+xml")
+                .header("Content-Range", content_range(offset, end, total))
+                .body(file_content[offset..end].to_vec())
+                .send()
+                .await;
+
+            let error = match sent {
+                Ok(response) if response.status().is_success() => {
+                    offset = upload_offset(response.headers()).unwrap_or(end);
+                    failures = 0;
+                    continue;
+                }
+                // The server holds a different number of bytes than we
+                // thought, e.g. a retried chunk had already arrived
+                Ok(response) if response.status() == reqwest::StatusCode::CONFLICT => {
+                    if let Some(stored) = upload_offset(response.headers()) {
+                        offset = stored;
+                        continue;
+                    }
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(Error::Upload(body));
+                }
+                Ok(response) if !response.status().is_server_error() => {
+                    let status = response.status();
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(Error::Upload(format!("{} - {}", status, body)));
+                }
+                Ok(response) => response.status().to_string(),
+                Err(e) => e.to_string(),
+            };
+
+            failures += 1;
+            if failures > UPLOAD_RETRIES {
+                return Err(Error::Upload(format!(
+                    "{} after {} attempts",
+                    error, failures
+                )));
+            }
+            let wait = UPLOAD_RETRY_DELAY * 2u32.pow(failures - 1);
+            self.notify(&format!(
+                "  Upload interrupted at {} of {} bytes ({}); resuming in {}s",
+                offset,
+                total,
+                error,
+                wait.as_secs()
+            ));
+            tokio::time::sleep(wait).await;
+            if let Ok(response) = self.http.head(&url).send().await {
+                if let Some(stored) = upload_offset(response.headers()) {
+                    offset = stored;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn confirm_flamegraph_upload(
+        &self,
+        report_id: &str,
+        storage_path: &str,
+        file_name: &str,
+        file_size: i64,
+        benchmark_name: Option<&str>,
+        sha256: &str,
+    ) -> Result<Flamegraph> {
+        let query = r#"
+            mutation ConfirmFlamegraphUpload(
+                $reportId: ID!,
+                $storagePath: String!,
+                $fileName: String!,
+                $fileSize: Int!,
+                $benchmarkName: String,
+                $sha256: String
+            ) {
+                confirmFlamegraphUpload(
+                    reportId: $reportId,
+                    storagePath: $storagePath,
+                    fileName: $fileName,
+                    fileSize: $fileSize,
+                    benchmarkName: $benchmarkName,
+                    sha256: $sha256
+                ) {
+                    id
+                    storagePath
+                    fileName
+                    fileSize
+                }
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "confirmFlamegraphUpload")]
+            confirm_flamegraph_upload: Flamegraph,
+        }
+
+        let response: Response = self
+            .graphql(
+                query,
+                serde_json::json!({
+                    "reportId": report_id,
+                    "storagePath": storage_path,
+                    "fileName": file_name,
+                    "fileSize": file_size,
+                    "benchmarkName": benchmark_name,
+                    "sha256": sha256
+                }),
+            )
+            .await?;
+        Ok(response.confirm_flamegraph_upload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+
+    fn report(id: &str, status: &str) -> serde_json::Value {
+        serde_json::json!({ "id": id, "gitHash": null, "status": status, "alerts": [] })
+    }
+
+    #[test]
+    fn test_reports_query_aliases_each_id() {
+        let query = reports_query(2);
+        assert!(query.starts_with("query GetReports($id0: ID!, $id1: ID!) {"));
+        assert!(query.contains("r0: report(id: $id0) { ...ReportFields }"));
+        assert!(query.contains("r1: report(id: $id1) { ...ReportFields }"));
+        assert!(!query.contains("$id2"));
+        assert_eq!(query.matches("fragment ReportFields on Report").count(), 1);
+    }
+
+    #[test]
+    fn test_upload_chunk_headers() {
+        assert_eq!(content_range(0, 1024, 4096), "bytes 0-1023/4096");
+        assert_eq!(content_range(4095, 4096, 4096), "bytes 4095-4095/4096");
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(upload_offset(&headers), None);
+        headers.insert("upload-offset", "2048".parse().unwrap());
+        assert_eq!(upload_offset(&headers), Some(2048));
+    }
+
+    #[tokio::test]
+    async fn test_get_reports_keeps_order() {
+        let transport = Arc::new(MockTransport::new());
+        transport.respond(serde_json::json!({
+            "r1": report("b", "pending"),
+            "r0": null,
+        }));
+        let client = Client::with_transport(transport.clone());
+
+        let reports = client.get_reports(&["a", "b"]).await.unwrap();
+        assert!(reports[0].is_none());
+        assert_eq!(reports[1].as_ref().unwrap().id, "b");
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].variables,
+            serde_json::json!({ "id0": "a", "id1": "b" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_large_reports_are_chunked() {
+        let transport = Arc::new(MockTransport::new());
+        transport
+            .respond(serde_json::json!({ "openReport": report("r", "pending") }))
+            .respond(serde_json::json!({ "appendReportMetrics": METRIC_BATCH_SIZE }))
+            .respond(serde_json::json!({ "appendReportMetrics": 1 }))
+            .respond(serde_json::json!({ "finalizeReport": report("r", "pending") }));
+        let client = Client::with_transport(transport.clone());
+
+        let metric = MetricInput {
+            benchmark: "parse".to_string(),
+            measure: "latency".to_string(),
+            value: 1.0,
+            lower_value: None,
+            upper_value: None,
+            reported_change: None,
+            outliers: None,
+        };
+        let input = CreateReportInput {
+            project_slug: "core".to_string(),
+            branch: "main".to_string(),
+            testbed: "ci".to_string(),
+            git_hash: None,
+            pr_number: None,
+            commit_message: None,
+            commit_author: None,
+            committed_at: None,
+            base_branch: None,
+            merge_base_hash: None,
+            evaluated_commit: None,
+            version: None,
+            kind: None,
+            exit_code: None,
+            partial: None,
+            created_at: None,
+            context: Vec::new(),
+            tags: Vec::new(),
+            expected_benchmarks: Vec::new(),
+            id: None,
+            metrics: vec![metric; CHUNKED_UPLOAD_THRESHOLD + 1],
+        };
+        let report = client.submit_report(input).await.unwrap();
+        assert_eq!(report.id, "r");
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[0].query.contains("openReport"));
+        assert!(requests[0].variables["input"].get("metrics").is_none());
+        assert_eq!(
+            requests[1].variables["metrics"].as_array().unwrap().len(),
+            METRIC_BATCH_SIZE
+        );
+        assert_eq!(
+            requests[2].variables["metrics"].as_array().unwrap().len(),
+            1
+        );
+        assert!(requests[3].query.contains("finalizeReport"));
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_refuses_other_versions() {
+        let transport = Arc::new(MockTransport::new());
+        transport.respond(serde_json::json!({
+            "apiVersion": { "apiVersion": API_VERSION + 1, "serverVersion": "9.9.9" }
+        }));
+        let client = Client::with_transport(transport.clone()).strict(true);
+
+        let error = client.list_projects().await.unwrap_err();
+        assert!(
+            matches!(&error, Error::Incompatible { server_version, .. } if server_version == "9.9.9")
+        );
+        // Only the version was asked for
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
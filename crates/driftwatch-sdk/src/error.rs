@@ -0,0 +1,82 @@
+pub use driftwatch_client::{ErrorExtensions, GraphQLError};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Why a request to the API failed
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The server couldn't be reached, or the connection broke
+    #[error("Failed to send request")]
+    Request(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The server answered with something that isn't the expected response
+    #[error("Failed to parse response")]
+    Decode(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The server refused the operation; the first error is shown
+    #[error("GraphQL error: {}", .0.first().map(ToString::to_string).unwrap_or_default())]
+    GraphQL(Vec<GraphQLError>),
+    #[error("No data in response (status: {status})")]
+    NoData { status: u16 },
+    /// Raised in strict mode when the server's API version differs
+    #[error("Incompatible server {server_version}: {warning}")]
+    Incompatible {
+        server_version: String,
+        warning: String,
+    },
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("Timed out waiting for report(s) {} to be evaluated", .0.join(", "))]
+    EvaluationTimeout(Vec<String>),
+    #[error("Failed to upload flamegraph: {0}")]
+    Upload(String),
+    /// An ingestion relay refused a submission
+    #[error("Relay refused the submission: {0}")]
+    Relay(String),
+    #[error("Failed to read {path}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl Error {
+    /// Whether the server answered and refused, as opposed to not answering
+    pub fn is_graphql(&self) -> bool {
+        matches!(self, Error::GraphQL(_))
+    }
+}
+
+impl From<driftwatch_client::Error> for Error {
+    fn from(e: driftwatch_client::Error) -> Self {
+        match e {
+            driftwatch_client::Error::Http(e) => Error::Request(e.into()),
+            driftwatch_client::Error::Decode(e) => Error::Decode(e),
+            driftwatch_client::Error::GraphQL(errors) => Error::GraphQL(errors),
+            driftwatch_client::Error::NoData(status) => Error::NoData {
+                status: status.as_u16(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphql_error_includes_request_id() {
+        let error: GraphQLError = serde_json::from_str(
+            r#"{"message": "Report not found", "extensions": {"requestId": "abc-123"}}"#,
+        )
+        .unwrap();
+        assert_eq!(error.to_string(), "Report not found (request ID abc-123)");
+        assert_eq!(
+            Error::GraphQL(vec![error]).to_string(),
+            "GraphQL error: Report not found (request ID abc-123)"
+        );
+
+        let error: GraphQLError =
+            serde_json::from_str(r#"{"message": "Report not found"}"#).unwrap();
+        assert_eq!(error.to_string(), "Report not found");
+    }
+}
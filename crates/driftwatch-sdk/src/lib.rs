@@ -0,0 +1,22 @@
+//! The client the CLI uses to talk to the Driftwatch API, for scripts,
+//! the GitHub Action and other tools that want the same operations.
+//!
+//! [`Client`] sends its queries through a [`Transport`]: GraphQL over HTTP
+//! with [`Client::new`], or anything else given to
+//! [`Client::with_transport`], such as a [`MockTransport`] in tests.
+//! Failures come back as a typed [`Error`].
+
+mod client;
+pub mod error;
+pub mod transport;
+pub mod types;
+
+pub use client::{
+    Client, CHUNKED_UPLOAD_THRESHOLD, EVALUATION_POLL_INTERVAL, EVALUATION_TIMEOUT,
+    MAX_CONCURRENT_REQUESTS, METRIC_BATCH_SIZE,
+};
+pub use error::{Error, GraphQLError, Result};
+pub use transport::{
+    compatibility_warning, HttpTransport, MockRequest, MockTransport, Notify, Transport,
+};
+pub use types::*;
@@ -0,0 +1,199 @@
+//! How GraphQL documents reach the server. [`HttpTransport`] posts them to
+//! `/graphql`; [`MockTransport`] answers from canned responses so code
+//! using a [`Client`](crate::Client) can be tested without a server.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, Once};
+
+use async_trait::async_trait;
+
+use driftwatch_protocol::API_VERSION;
+
+use crate::error::{Error, Result};
+
+pub use driftwatch_client::Notify;
+
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Runs a GraphQL document and returns the response's `data`
+    async fn execute(&self, query: &str, variables: serde_json::Value)
+        -> Result<serde_json::Value>;
+
+    /// The API's address, which relative upload URLs are resolved against
+    fn base_url(&self) -> &str;
+}
+
+/// GraphQL over HTTP through [`driftwatch_client::Client`], authenticated
+/// with a bearer token. Requests the server refuses with 429 are retried
+/// after the wait it asks for.
+pub struct HttpTransport {
+    client: driftwatch_client::Client,
+    notify: Option<Notify>,
+}
+
+impl HttpTransport {
+    pub fn new(base_url: &str, token: &str) -> Self {
+        let mut client = driftwatch_client::Client::new(base_url)
+            .with_client_name(concat!("driftwatch-sdk/", env!("CARGO_PKG_VERSION")));
+        // Without a token the request is anonymous, e.g. `shareReport`
+        if !token.is_empty() {
+            client = client.with_token(token);
+        }
+        Self {
+            client,
+            notify: None,
+        }
+    }
+
+    /// Sent as `x-driftwatch-client`, e.g. `my-tool/1.2.0`, so the server
+    /// can tell who still uses deprecated fields
+    pub fn with_client_name(mut self, name: &str) -> Self {
+        self.client = self.client.with_client_name(name);
+        self
+    }
+
+    pub fn with_notify(mut self, notify: Notify) -> Self {
+        self.client = self.client.with_notify(notify.clone());
+        self.notify = Some(notify);
+        self
+    }
+
+    fn notify(&self, message: &str) {
+        if let Some(notify) = &self.notify {
+            notify(message);
+        }
+    }
+
+    /// Warns about an API version mismatch once per process
+    fn warn_if_incompatible(&self, server: i32) {
+        static WARNED: Once = Once::new();
+        if let Some(warning) = compatibility_warning(API_VERSION, server) {
+            WARNED.call_once(|| self.notify(&format!("Warning: {}", warning)));
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn execute(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self.client.send(query, None, &variables).await?;
+        if let Some(server) = response.extensions.api_version {
+            self.warn_if_incompatible(server);
+        }
+        Ok(response.into_data()?)
+    }
+
+    fn base_url(&self) -> &str {
+        self.client.base_url()
+    }
+}
+
+/// Explains a mismatch between the API version this client was built
+/// against and the server's, or `None` when they match
+pub fn compatibility_warning(client: i32, server: i32) -> Option<String> {
+    match server.cmp(&client) {
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater => Some(format!(
+            "the server's API (version {}) is newer than this CLI's (version {}); \
+             upgrade driftwatch if commands fail",
+            server, client
+        )),
+        std::cmp::Ordering::Less => Some(format!(
+            "the server's API (version {}) is older than this CLI's (version {}); \
+             some commands may fail until the server is upgraded",
+            server, client
+        )),
+    }
+}
+
+/// How long to wait before retrying, from a `Retry-After` header in seconds.
+/// HTTP dates and missing headers fall back to a default.
+pub fn retry_after(header: Option<&reqwest::header::HeaderValue>) -> Duration {
+    header
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+        .min(MAX_RETRY_AFTER)
+}
+
+/// A request a [`MockTransport`] received
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub query: String,
+    pub variables: serde_json::Value,
+}
+
+/// Answers each request with the next queued response, in order, and
+/// records what was asked
+#[derive(Default)]
+pub struct MockTransport {
+    responses: Mutex<VecDeque<Result<serde_json::Value>>>,
+    requests: Mutex<Vec<MockRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `data` as the next response
+    pub fn respond(&self, data: serde_json::Value) -> &Self {
+        self.responses.lock().unwrap().push_back(Ok(data));
+        self
+    }
+
+    /// Queues an error as the next response
+    pub fn fail(&self, error: Error) -> &Self {
+        self.responses.lock().unwrap().push_back(Err(error));
+        self
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Transport for MockTransport {
+    async fn execute(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.requests.lock().unwrap().push(MockRequest {
+            query: query.to_string(),
+            variables,
+        });
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Err(Error::Request("no response queued".into())))
+    }
+
+    fn base_url(&self) -> &str {
+        "http://localhost"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatibility_warning() {
+        assert_eq!(compatibility_warning(2, 2), None);
+        assert!(compatibility_warning(1, 2)
+            .unwrap()
+            .contains("upgrade driftwatch"));
+        assert!(compatibility_warning(2, 1)
+            .unwrap()
+            .contains("until the server is upgraded"));
+    }
+}
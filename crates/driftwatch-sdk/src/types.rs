@@ -0,0 +1,763 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub public: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectDetails {
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub public: bool,
+    #[serde(rename = "defaultBranch")]
+    pub default_branch: String,
+    pub branches: Vec<Branch>,
+    #[serde(rename = "branchAliases")]
+    pub branch_aliases: Vec<BranchAlias>,
+    pub testbeds: Vec<Testbed>,
+    #[serde(rename = "testbedAliases")]
+    pub testbed_aliases: Vec<TestbedAlias>,
+    pub benchmarks: Vec<Benchmark>,
+    pub measures: Vec<Measure>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Branch {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BranchAlias {
+    pub alias: String,
+    pub branch: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Testbed {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestbedAlias {
+    pub alias: String,
+    pub testbed: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Benchmark {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Measure {
+    pub id: String,
+    pub name: String,
+    pub units: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricInput {
+    pub benchmark: String,
+    pub measure: String,
+    pub value: f64,
+    #[serde(rename = "lowerValue")]
+    pub lower_value: Option<f64>,
+    #[serde(rename = "upperValue")]
+    pub upper_value: Option<f64>,
+    #[serde(rename = "reportedChange")]
+    pub reported_change: Option<f64>,
+    pub outliers: Option<i32>,
+}
+
+/// Key/value recorded with a report, e.g. `allocator=jemalloc`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextEntry {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateReportInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    pub branch: String,
+    pub testbed: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    #[serde(rename = "prNumber")]
+    pub pr_number: Option<i32>,
+    #[serde(rename = "commitMessage")]
+    pub commit_message: Option<String>,
+    #[serde(rename = "commitAuthor")]
+    pub commit_author: Option<String>,
+    #[serde(rename = "committedAt")]
+    pub committed_at: Option<String>,
+    #[serde(rename = "baseBranch")]
+    pub base_branch: Option<String>,
+    #[serde(rename = "mergeBaseHash")]
+    pub merge_base_hash: Option<String>,
+    #[serde(rename = "evaluatedCommit", skip_serializing_if = "Option::is_none")]
+    pub evaluated_commit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The server infers it from `pr_number` and `version` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ReportKind>,
+    /// Exit code of the benchmark command
+    #[serde(rename = "exitCode", skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Results of a benchmark command that failed, e.g. one killed by a
+    /// signal without an exit code; the server infers it from `exit_code`
+    /// when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partial: Option<bool>,
+    #[serde(rename = "createdAt", skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<ContextEntry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Suite manifest: every benchmark the run was meant to produce, so the
+    /// server can flag the ones without metrics
+    #[serde(rename = "expectedBenchmarks", skip_serializing_if = "Vec::is_empty")]
+    pub expected_benchmarks: Vec<String>,
+    /// Id for the new report; the server, or a relay, picks one when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Left out when empty so the same input can open a chunked upload
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<MetricInput>,
+}
+
+/// What triggered a run, serialized as the server's `ReportKindInput` values.
+/// PR reports only count toward the baselines of the same PR's later runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReportKind {
+    Pr,
+    Push,
+    Nightly,
+    Release,
+    Manual,
+}
+
+/// A one-off report for `shareReport`, submitted without an account
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareReportInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub branch: String,
+    pub testbed: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    pub metrics: Vec<MetricInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SharedReport {
+    /// Secret path under the API's URL the report can be read at
+    pub path: String,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: String,
+    #[serde(rename = "reportId")]
+    pub report_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Report {
+    pub id: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    /// `pending` until the server has evaluated alerts
+    pub status: String,
+    /// Only requested when fetching a report after evaluation
+    #[serde(default)]
+    pub comparison: Option<ReportComparison>,
+    pub alerts: Vec<Alert>,
+    /// Regressions the server holds back until they reproduce
+    #[serde(default, rename = "unconfirmedAlerts")]
+    pub unconfirmed_alerts: Vec<Alert>,
+    /// Benchmarks of the suite manifest without metrics; only requested
+    /// after evaluation, and `None` without a manifest
+    #[serde(default, rename = "missingBenchmarks")]
+    pub missing_benchmarks: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportComparison {
+    #[serde(rename = "totalBenchmarks")]
+    pub total_benchmarks: i32,
+    pub improved: i32,
+    pub regressed: i32,
+    pub unchanged: i32,
+    #[serde(rename = "withoutBaseline")]
+    pub without_baseline: i32,
+    #[serde(rename = "maxRegression")]
+    pub max_regression: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Alert {
+    pub id: String,
+    #[serde(rename = "baselineValue")]
+    pub baseline_value: f64,
+    #[serde(rename = "percentChange")]
+    pub percent_change: f64,
+    #[serde(default)]
+    pub owners: Vec<String>,
+}
+
+/// Triage states, serialized as the server's `AlertStatusInput` values
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AlertStatus {
+    Unconfirmed,
+    Active,
+    Acknowledged,
+    Investigating,
+    Resolved,
+    WontFix,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateAlertInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<AlertStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(rename = "resolutionNote", skip_serializing_if = "Option::is_none")]
+    pub resolution_note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertDetails {
+    pub id: String,
+    pub status: String,
+    #[serde(rename = "percentChange")]
+    pub percent_change: f64,
+    #[serde(rename = "baselineValue")]
+    pub baseline_value: f64,
+    #[serde(rename = "currentValue")]
+    pub current_value: f64,
+    pub assignee: Option<String>,
+    #[serde(rename = "resolutionNote")]
+    pub resolution_note: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub metric: AlertMetric,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertExplanation {
+    pub id: String,
+    pub status: String,
+    #[serde(rename = "percentChange")]
+    pub percent_change: f64,
+    pub metric: AlertMetric,
+    pub evaluations: Vec<ThresholdEvaluation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThresholdEvaluation {
+    #[serde(rename = "reportId")]
+    pub report_id: String,
+    pub verdict: String,
+    pub explanation: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertMetric {
+    pub benchmark: AlertBenchmark,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertBenchmark {
+    pub name: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ThresholdTestInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    #[serde(rename = "reportId", skip_serializing_if = "Option::is_none")]
+    pub report_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<Vec<MetricInput>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub testbed: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThresholdTestResult {
+    pub threshold: ThresholdBoundaries,
+    pub benchmark: String,
+    pub measure: String,
+    pub value: f64,
+    #[serde(rename = "baselineValue")]
+    pub baseline_value: f64,
+    #[serde(rename = "percentChange")]
+    pub percent_change: f64,
+    #[serde(rename = "wouldAlert")]
+    pub would_alert: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThresholdBoundaries {
+    #[serde(rename = "upperBoundary")]
+    pub upper_boundary: Option<f64>,
+    #[serde(rename = "lowerBoundary")]
+    pub lower_boundary: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectTemplate {
+    pub name: String,
+    pub description: String,
+    pub measures: Vec<NamedRef>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NoiseScore {
+    #[serde(rename = "sampleSize")]
+    pub sample_size: i32,
+    /// Standard deviation as a percentage of the mean
+    #[serde(rename = "coefficientOfVariation")]
+    pub coefficient_of_variation: f64,
+    pub noisy: bool,
+    pub benchmark: NamedRef,
+    pub testbed: NamedRef,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectOverview {
+    #[serde(rename = "openAlerts")]
+    pub open_alerts: i32,
+    pub measures: Vec<MeasureTrend>,
+    #[serde(rename = "latestReports")]
+    pub latest_reports: Vec<LatestReport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MeasureTrend {
+    pub measure: String,
+    pub benchmarks: i32,
+    /// Mean percent change across benchmarks
+    #[serde(rename = "meanDelta7d")]
+    pub mean_delta_7d: Option<f64>,
+    #[serde(rename = "meanDelta30d")]
+    pub mean_delta_30d: Option<f64>,
+    #[serde(rename = "regressed30d")]
+    pub regressed_30d: i32,
+    #[serde(rename = "improved30d")]
+    pub improved_30d: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LatestReport {
+    pub branch: String,
+    pub testbed: String,
+    #[serde(rename = "reportId")]
+    pub report_id: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    #[serde(rename = "reportedAt")]
+    pub reported_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NamedRef {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectGroup {
+    pub slug: String,
+    pub name: String,
+    pub projects: Vec<ProjectRef>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectRef {
+    pub slug: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GroupAlert {
+    pub id: String,
+    pub status: String,
+    #[serde(rename = "percentChange")]
+    pub percent_change: f64,
+    pub project: ProjectRef,
+    pub metric: AlertMetric,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GroupReport {
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub project: ProjectRef,
+    pub branch: NamedRef,
+    /// Only counted
+    pub alerts: Vec<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectReport {
+    pub id: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub branch: NamedRef,
+    pub testbed: NamedRef,
+    /// `pr`, `push`, `nightly`, `release` or `manual`
+    pub kind: String,
+    pub tags: Vec<String>,
+    /// Only counted
+    pub alerts: Vec<serde::de::IgnoredAny>,
+}
+
+/// A saved set of tags and context to list reports by
+#[derive(Debug, Deserialize)]
+pub struct ReportFilter {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub context: Vec<ContextEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportOutput {
+    /// Gzip-compressed, base64-encoded stdout and stderr
+    pub content: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BenchmarkMatch {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    #[serde(rename = "latestValue")]
+    pub latest_value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicProject {
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "githubRepo")]
+    pub github_repo: Option<String>,
+    pub benchmarks: i32,
+    pub series: i32,
+    pub reports: i32,
+    #[serde(rename = "lastReportAt")]
+    pub last_report_at: Option<String>,
+    /// Only fetched for a single project
+    #[serde(rename = "latestResults", default)]
+    pub latest_results: Vec<BenchmarkMatch>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicProjectPage {
+    #[serde(rename = "totalCount")]
+    pub total_count: i32,
+    pub projects: Vec<PublicProject>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScalingCurve {
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "growthExponent")]
+    pub growth_exponent: Option<f64>,
+    pub points: Vec<ScalingPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScalingPoint {
+    pub parameter: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContextPoint {
+    /// None for reports that didn't record the key
+    #[serde(rename = "contextValue")]
+    pub context_value: Option<String>,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExperimentResultInput {
+    pub benchmark: String,
+    pub measure: String,
+    #[serde(rename = "baselineMean")]
+    pub baseline_mean: f64,
+    #[serde(rename = "candidateMean")]
+    pub candidate_mean: f64,
+    #[serde(rename = "pValue")]
+    pub p_value: Option<f64>,
+    pub verdict: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordExperimentInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    pub name: Option<String>,
+    #[serde(rename = "baselineCommand")]
+    pub baseline_command: String,
+    #[serde(rename = "candidateCommand")]
+    pub candidate_command: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    pub testbed: Option<String>,
+    pub rounds: i32,
+    pub alpha: f64,
+    pub results: Vec<ExperimentResultInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Experiment {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServerVersion {
+    #[serde(rename = "apiVersion")]
+    pub api_version: i32,
+    #[serde(rename = "serverVersion")]
+    pub server_version: String,
+}
+
+/// How a simulated threshold scores values, serialized as the server's
+/// `ThresholdModelInput` values
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ThresholdModel {
+    /// Percent change from the window's mean
+    Percent,
+    /// Standard deviations from the window's mean
+    Zscore,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulateThresholdsInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    pub model: ThresholdModel,
+    pub window: i32,
+    #[serde(rename = "upperBoundary", skip_serializing_if = "Option::is_none")]
+    pub upper_boundary: Option<f64>,
+    #[serde(rename = "lowerBoundary", skip_serializing_if = "Option::is_none")]
+    pub lower_boundary: Option<f64>,
+    #[serde(rename = "minSampleSize")]
+    pub min_sample_size: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measure: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub testbed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimulatedAlert {
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThresholdSimulation {
+    pub series: i32,
+    pub comparisons: i32,
+    #[serde(rename = "actualAlerts")]
+    pub actual_alerts: i32,
+    pub alerts: Vec<SimulatedAlert>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportReevaluation {
+    pub raised: i32,
+    pub updated: i32,
+    pub resolved: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleasePoint {
+    pub version: String,
+    pub benchmark: String,
+    pub value: f64,
+    #[serde(rename = "minValue")]
+    pub min_value: f64,
+    #[serde(rename = "maxValue")]
+    pub max_value: f64,
+    #[serde(rename = "sampleSize")]
+    pub sample_size: i32,
+}
+
+/// Annotation markers, serialized as the server's `AnnotationKindInput` values
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AnnotationKind {
+    Note,
+    RunnerUpgrade,
+    DependencyBump,
+    ConfigChange,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateAnnotationInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    #[serde(rename = "reportId", skip_serializing_if = "Option::is_none")]
+    pub report_id: Option<String>,
+    pub kind: AnnotationKind,
+    pub note: String,
+    #[serde(rename = "occurredAt", skip_serializing_if = "Option::is_none")]
+    pub occurred_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Annotation {
+    pub id: String,
+    pub kind: String,
+    pub note: String,
+    #[serde(rename = "reportId")]
+    pub report_id: Option<String>,
+    #[serde(rename = "occurredAt")]
+    pub occurred_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectDigest {
+    pub markdown: String,
+    pub html: String,
+}
+
+/// Notification channel kinds, serialized as the server's
+/// `NotificationChannelKindInput` values
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChannelKind {
+    /// JSON POSTed to any URL
+    Webhook,
+    /// A Slack incoming webhook
+    Slack,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationChannel {
+    pub id: String,
+    pub kind: String,
+    pub target: String,
+    /// Events posted to the channel, e.g. `digest` or `alert.opened`
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Request body template replacing the default payload
+    pub template: Option<String>,
+}
+
+/// The outcome of a test delivery to a notification channel
+#[derive(Debug, Deserialize)]
+pub struct NotificationChannelTest {
+    pub delivered: bool,
+    pub error: Option<String>,
+}
+
+/// A label a Prometheus series must have, with this exact value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelMatcher {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoteWriteRule {
+    pub id: String,
+    pub metric: String,
+    pub matchers: Vec<LabelMatcher>,
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    pub testbed: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RemoteWriteRuleInput {
+    pub metric: String,
+    pub matchers: Vec<LabelMatcher>,
+    /// Benchmark name template; `{label}` is replaced with the label's value
+    pub benchmark: String,
+    pub measure: String,
+    pub branch: String,
+    /// Testbed name template, like `benchmark`
+    pub testbed: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FlamegraphUploadUrl {
+    /// The server already has this file; there's nothing to upload
+    #[serde(default)]
+    pub stored: bool,
+    #[serde(rename = "signedUrl")]
+    pub signed_url: Option<String>,
+    pub token: Option<String>,
+    #[serde(rename = "storagePath")]
+    pub storage_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Flamegraph {
+    pub id: String,
+    #[serde(rename = "storagePath")]
+    pub storage_path: String,
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+    #[serde(rename = "fileSize")]
+    pub file_size: i64,
+}
+
+/// Benchmarks matching `pattern` belong to `owners`, serialized as the
+/// server's `BenchmarkOwnerInput`
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OwnerRule {
+    pub pattern: String,
+    pub owners: Vec<String>,
+}
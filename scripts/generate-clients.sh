@@ -0,0 +1,49 @@
+#!/bin/sh
+# Generates Python and TypeScript clients for the REST API under /api/v1
+# from its OpenAPI description, so pipelines outside Rust can submit
+# metrics. Needs Docker; nothing else is installed.
+#
+#   scripts/generate-clients.sh [output-dir]
+#
+# output-dir             where to write python/ and typescript/ (default:
+#                        clients/, which is ignored by git)
+# OPENAPI_GENERATOR_IMAGE
+#                        generator image to run (default: a pinned
+#                        openapitools/openapi-generator-cli release)
+# CLIENT_VERSION         package version of the clients (default: the
+#                        workspace version)
+
+set -eu
+
+ROOT=$(cd "$(dirname "$0")/.." && pwd)
+SPEC="crates/driftwatch-api/openapi.json"
+IMAGE="${OPENAPI_GENERATOR_IMAGE:-openapitools/openapi-generator-cli:v7.10.0}"
+OUT="${1:-clients}"
+VERSION="${CLIENT_VERSION:-$(sed -n 's/^version = "\(.*\)"/\1/p' "$ROOT/Cargo.toml" | head -n 1)}"
+
+fail() {
+    echo "generate-clients.sh: $*" >&2
+    exit 1
+}
+
+command -v docker >/dev/null 2>&1 || fail "docker is required"
+[ -f "$ROOT/$SPEC" ] || fail "$SPEC not found"
+case "$OUT" in
+    /*) fail "output-dir must be inside the repository" ;;
+esac
+
+# The repository is mounted at /local, so paths below are relative to it
+generator() {
+    docker run --rm --user "$(id -u):$(id -g)" -v "$ROOT:/local" -w /local "$IMAGE" "$@"
+}
+
+generator validate -i "$SPEC"
+
+rm -rf "$ROOT/$OUT/python" "$ROOT/$OUT/typescript"
+generator generate -i "$SPEC" -g python -o "$OUT/python" \
+    --additional-properties "packageName=driftwatch_client,projectName=driftwatch-client,packageVersion=$VERSION"
+generator generate -i "$SPEC" -g typescript-fetch -o "$OUT/typescript" \
+    --additional-properties "npmName=@driftwatch/client,npmVersion=$VERSION,supportsES6=true"
+
+echo "Python client:     $OUT/python"
+echo "TypeScript client: $OUT/typescript"
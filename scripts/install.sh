@@ -0,0 +1,96 @@
+#!/bin/sh
+# Installs the driftwatch CLI from the project's GitHub releases after
+# checking the binary's checksum and, when DRIFTWATCH_RELEASE_PUBLIC_KEY is
+# set, the release signature.
+#
+#   curl -fsSL .../scripts/install.sh | DRIFTWATCH_RELEASE_PUBLIC_KEY=<key> sh
+#
+# DRIFTWATCH_VERSION     release to install, e.g. 0.4.0 (default: latest)
+# DRIFTWATCH_INSTALL_DIR where to put the binary (default: ~/.local/bin)
+# DRIFTWATCH_RELEASE_URL mirror URL template with {version} and {asset}
+# DRIFTWATCH_RELEASE_PUBLIC_KEY
+#                        base64 Ed25519 key releases are signed with;
+#                        signature checks need OpenSSL 3
+# DRIFTWATCH_ALLOW_UNSIGNED=1
+#                        install without a signature check when no key is set
+
+set -eu
+
+REPOSITORY="https://github.com/yourusername/driftwatch"
+VERSION="${DRIFTWATCH_VERSION:-latest}"
+INSTALL_DIR="${DRIFTWATCH_INSTALL_DIR:-$HOME/.local/bin}"
+PUBLIC_KEY="${DRIFTWATCH_RELEASE_PUBLIC_KEY:-}"
+
+fail() {
+    echo "install.sh: $*" >&2
+    exit 1
+}
+
+os=$(uname -s | tr '[:upper:]' '[:lower:]')
+if [ "$os" = darwin ]; then os=macos; fi
+arch=$(uname -m)
+case "$arch" in
+    amd64) arch=x86_64 ;;
+    arm64) arch=aarch64 ;;
+esac
+asset="driftwatch-$os-$arch"
+
+if [ -n "${DRIFTWATCH_RELEASE_URL:-}" ]; then
+    url=$(echo "$DRIFTWATCH_RELEASE_URL" | sed "s|{version}|${VERSION#v}|g; s|{asset}|$asset|g")
+elif [ "$VERSION" = latest ]; then
+    url="$REPOSITORY/releases/latest/download/$asset"
+else
+    url="$REPOSITORY/releases/download/v${VERSION#v}/$asset"
+fi
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+echo "Downloading $url"
+curl -fsSL -o "$tmp/$asset" "$url" || fail "no release for $asset at $url"
+curl -fsSL -o "$tmp/$asset.sha256" "$url.sha256" || fail "no checksum published for $asset"
+
+expected=$(cut -d ' ' -f 1 < "$tmp/$asset.sha256")
+if command -v sha256sum > /dev/null; then
+    actual=$(sha256sum "$tmp/$asset" | cut -d ' ' -f 1)
+else
+    actual=$(shasum -a 256 "$tmp/$asset" | cut -d ' ' -f 1)
+fi
+[ "$expected" = "$actual" ] || fail "checksum mismatch (expected $expected, got $actual)"
+echo "Checksum matches."
+
+if [ -n "$PUBLIC_KEY" ]; then
+    curl -fsSL -o "$tmp/$asset.sha256.sig" "$url.sha256.sig" || fail "release isn't signed"
+    command -v openssl > /dev/null || fail "openssl is needed to check the signature"
+    # An Ed25519 SubjectPublicKeyInfo is a fixed 12-byte prefix and the key
+    {
+        echo "-----BEGIN PUBLIC KEY-----"
+        echo "MCowBQYDK2VwAyEA$PUBLIC_KEY"
+        echo "-----END PUBLIC KEY-----"
+    } > "$tmp/release.pem"
+    openssl base64 -d -A -in "$tmp/$asset.sha256.sig" -out "$tmp/signature"
+    openssl pkeyutl -verify -pubin -inkey "$tmp/release.pem" -rawin \
+        -in "$tmp/$asset.sha256" -sigfile "$tmp/signature" > /dev/null 2>&1 \
+        || fail "signature doesn't match DRIFTWATCH_RELEASE_PUBLIC_KEY"
+    echo "Signature is valid."
+elif [ "${DRIFTWATCH_ALLOW_UNSIGNED:-}" = 1 ]; then
+    echo "Warning: DRIFTWATCH_RELEASE_PUBLIC_KEY isn't set; signature not checked" >&2
+else
+    fail "set DRIFTWATCH_RELEASE_PUBLIC_KEY to check the release signature, or DRIFTWATCH_ALLOW_UNSIGNED=1 to skip it"
+fi
+
+# The checksum file names its release, so an older signed release can't
+# stand in for the one asked for
+released=$(awk '{ print $3 }' < "$tmp/$asset.sha256")
+if [ "$VERSION" != latest ]; then
+    if [ -n "$released" ]; then
+        [ "$released" = "${VERSION#v}" ] || fail "checksum is for driftwatch $released, not ${VERSION#v}"
+    elif [ -n "$PUBLIC_KEY" ]; then
+        fail "the signed checksum doesn't name the release it's for"
+    fi
+fi
+
+mkdir -p "$INSTALL_DIR"
+chmod 755 "$tmp/$asset"
+mv "$tmp/$asset" "$INSTALL_DIR/driftwatch"
+echo "Installed $("$INSTALL_DIR/driftwatch" --version) to $INSTALL_DIR"